@@ -0,0 +1,56 @@
+//! A `no_std` crate using the library without its default `std` feature, checking that the rules
+//! engine builds and works with only `alloc`. Build or test this package on its own, with
+//! `cargo test -p no-std-check`, since building it together with the rest of the workspace
+//! enables the `std` feature of the library.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use leben_chess::board::Board;
+use leben_chess::board::board_pos::BoardPosition;
+use leben_chess::chess::{ChessError, ChessGame, GameStatus};
+use leben_chess::moves::ChessMove;
+
+/// Plays a sequence of moves given as pairs of square names from the standard starting position.
+///
+/// returns: The game after the moves, or the first error.
+pub fn play(moves: &[(&str, &str)]) -> Result<ChessGame, ChessError> {
+    let mut game = ChessGame::new(Board::default_board());
+    for (from, to) in moves {
+        // square names in this crate are always valid
+        let from = BoardPosition::try_from(*from).unwrap();
+        let to = BoardPosition::try_from(*to).unwrap();
+        game.do_move(ChessMove::new(from, to))?;
+    }
+    Ok(game)
+}
+
+/// returns: The status of the game after fool's mate, the shortest possible checkmate.
+pub fn fools_mate() -> GameStatus {
+    let moves = [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")];
+    // these moves are legal
+    *play(&moves).unwrap().game_status()
+}
+
+/// returns: The legal moves in the standard starting position.
+pub fn opening_moves() -> Vec<ChessMove> {
+    ChessGame::new(Board::default_board()).legal_moves()
+}
+
+#[cfg(test)]
+mod tests {
+    use leben_chess::board::piece::PlayerColor;
+    use leben_chess::chess::WinReason;
+    use super::*;
+
+    #[test]
+    fn checkmate_detection() {
+        assert_eq!(opening_moves().len(), 20);
+        assert_eq!(fools_mate(), GameStatus::Win(PlayerColor::Black, WinReason::Checkmate));
+        assert!(play(&[("e2", "e5")]).is_err());
+        let game = play(&[("e2", "e4"), ("e7", "e5")]).unwrap();
+        assert_eq!(game.fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+    }
+}
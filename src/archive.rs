@@ -0,0 +1,727 @@
+//! A compact binary format for storing large numbers of games, see [GameArchive].
+//!
+//! An encoded game consists of a header followed by the moves of the game. All integers are
+//! little-endian. The header consists of:
+//! - a flags byte, where bit 0 is set if a starting position follows and bit 1 is set if a
+//!   handicap follows,
+//! - the [variant](Variant) of the game as one byte: 0 for standard chess, 1 for King of the Hill,
+//!   2 for Horde, 3 for Crazyhouse and 4 for Shatranj,
+//! - the way the game ended, if not by its moves, as one byte: 0 if the result follows from the
+//!   moves, 1 for a draw by agreement, 2 for a resignation, 3 for a draw claimed by the fifty-move
+//!   rule, 4 for a win on time, 5 for a draw on time against insufficient material, 6 for a win
+//!   by adjudication, 7 for a draw by adjudication, 8 for a win by forfeit and 9 for a win by
+//!   illegal moves of the opponent, with bit 7 set if black won,
+//! - for a game ended by an arbiter (6 to 8), the [reason](ChessGame::termination) given by the
+//!   arbiter prefixed with a 16-bit length,
+//! - the tags of the game: a 16-bit count followed by each tag as a name of at most 255 bytes
+//!   prefixed with an 8-bit length and a value of at most 65535 bytes prefixed with a 16-bit
+//!   length, all in UTF-8,
+//! - if the game starts from another position than the default one, the
+//!   [FEN string](ChessGame::fen) of the starting position prefixed with an 8-bit length,
+//! - if the game was set up with a [Handicap], its kind as one byte (0 for pawn and move, 1 for
+//!   pawn and two moves, 2 for knight odds, 3 for rook odds and 4 for queen odds) and the
+//!   [giving player](ChessGame::handicap) as one byte (0 for white and 1 for black), instead of
+//!   the starting position,
+//! - the number of moves as a 32-bit integer.
+//!
+//! Each move is then encoded in 16 bits: 6 bits for the
+//! [index](crate::board::board_pos::BoardPosition::index) of the origin square, 6 bits for the
+//! index of the destination square, 2 bits for the promotion type (knight, bishop, rook or queen)
+//! and 2 bits of flags, from the most to the least significant bit. The flags are 1 for a
+//! promotion and 2 for a [drop](crate::chess::crazyhouse::DropMove) in Crazyhouse, in which case
+//! the origin is the index of the dropped piece in [PieceType::ALL]. Castling moves are encoded as
+//! the move of the king.
+//!
+//! Decoding replays the moves, so an archived game is always a legal game.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use thiserror::Error;
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::{
+    AdjudicatedResult, ChessGame, DrawReason, FenError, GameStatus, MoveDetails, WinReason,
+};
+use crate::chess::crazyhouse::DropMove;
+use crate::chess::handicap::{ChessGameBuilder, Handicap};
+use crate::chess::variant::Variant;
+use crate::moves::{ChessMove, PromotionType};
+
+const HAS_STARTING_POSITION: u8 = 0b01;
+const HAS_HANDICAP: u8 = 0b10;
+
+const BLACK_WINS: u8 = 0b1000_0000;
+const ADJUDICATED_ENDINGS: [u8; 3] = [6, 7, 8];
+
+const PROMOTION_FLAG: u16 = 1;
+const DROP_FLAG: u16 = 2;
+
+const VARIANTS: [Variant; 5] = [
+    Variant::Standard,
+    Variant::KingOfTheHill,
+    Variant::Horde,
+    Variant::Crazyhouse,
+    Variant::Shatranj,
+];
+
+const HANDICAPS: [Handicap; 5] = [
+    Handicap::PawnAndMove,
+    Handicap::PawnAndTwoMoves,
+    Handicap::KnightOdds,
+    Handicap::RookOdds,
+    Handicap::QueenOdds,
+];
+
+/// An error caused by attempting to decode invalid archive data.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum ArchiveError {
+    /// The data ended in the middle of a game or of the archive.
+    #[error("unexpected end of data at byte {0}")]
+    UnexpectedEnd(usize),
+    /// The archive does not start with [GameArchive::MAGIC].
+    #[error("not a game archive")]
+    InvalidMagic,
+    /// A header field has a value which is not defined by the format.
+    #[error("invalid {0} in game header")]
+    InvalidHeader(&'static str),
+    /// A tag is not valid UTF-8.
+    #[error("tag is not valid UTF-8")]
+    InvalidTag,
+    /// The starting position is not a valid FEN string.
+    #[error("invalid starting position: {0}")]
+    InvalidPosition(#[from] FenError),
+    /// A move is not legal in the position it is played in, with the index of the move.
+    #[error("move {0} is not legal")]
+    IllegalMove(usize),
+    /// The encoded game is followed by more data.
+    #[error("unexpected data after the end of the game")]
+    TrailingData,
+    /// The archive does not contain a game with the index.
+    #[error("no game with index {0}")]
+    NoSuchGame(usize),
+}
+
+/// Reads the fields of an encoded game, keeping track of the position for errors.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ArchiveError> {
+        let end = self.position.checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(ArchiveError::UnexpectedEnd(self.bytes.len()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ArchiveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ArchiveError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ArchiveError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self, len: usize) -> Result<String, ArchiveError> {
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ArchiveError::InvalidTag)
+    }
+}
+
+/// returns: The longest prefix of the string which is at most `max_len` bytes long and ends on a
+///          character boundary.
+fn truncate(string: &str, max_len: usize) -> &str {
+    let len = (0..=max_len.min(string.len())).rev()
+        .find(|&len| string.is_char_boundary(len))
+        .unwrap_or_default();
+    &string[..len]
+}
+
+/// Appends a string of at most 65535 bytes prefixed with its 16-bit length, truncating longer
+/// strings.
+fn push_string(bytes: &mut Vec<u8>, string: &str) {
+    let string = truncate(string, u16::MAX as usize);
+    bytes.extend_from_slice(&(string.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+/// returns: The status of a game ended in the way given by its ending byte, or `None` if the game
+///          ended by its moves. For a win, bit 7 of the ending is set if black won.
+fn decode_ending(ending: u8) -> Result<Option<GameStatus>, ArchiveError> {
+    let winner = match ending & BLACK_WINS {
+        0 => PlayerColor::White,
+        _ => PlayerColor::Black,
+    };
+    let status = match ending & !BLACK_WINS {
+        0 => None,
+        1 => Some(GameStatus::Draw(DrawReason::DrawByAgreement)),
+        2 => Some(GameStatus::Win(winner, WinReason::Resignation)),
+        3 => Some(GameStatus::Draw(DrawReason::FiftyMoveRule)),
+        4 => Some(GameStatus::Win(winner, WinReason::Timeout)),
+        5 => Some(GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)),
+        6 => Some(GameStatus::Win(winner, WinReason::Adjudication)),
+        7 => Some(GameStatus::Draw(DrawReason::Adjudication)),
+        8 => Some(GameStatus::Win(winner, WinReason::Forfeit)),
+        9 => Some(GameStatus::Win(winner, WinReason::IllegalMoveForfeit)),
+        _ => return Err(ArchiveError::InvalidHeader("game ending")),
+    };
+    match status {
+        Some(GameStatus::Win(..)) => Ok(status),
+        _ if winner == PlayerColor::Black => Err(ArchiveError::InvalidHeader("game ending")),
+        _ => Ok(status),
+    }
+}
+
+/// returns: The ending byte of a game with the status, see [decode_ending].
+fn encode_ending(status: &GameStatus) -> u8 {
+    let (ending, winner) = match *status {
+        GameStatus::Draw(DrawReason::DrawByAgreement) => (1, None),
+        GameStatus::Win(winner, WinReason::Resignation) => (2, Some(winner)),
+        GameStatus::Draw(DrawReason::FiftyMoveRule) => (3, None),
+        GameStatus::Win(winner, WinReason::Timeout) => (4, Some(winner)),
+        GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial) => (5, None),
+        GameStatus::Win(winner, WinReason::Adjudication) => (6, Some(winner)),
+        GameStatus::Draw(DrawReason::Adjudication) => (7, None),
+        GameStatus::Win(winner, WinReason::Forfeit) => (8, Some(winner)),
+        GameStatus::Win(winner, WinReason::IllegalMoveForfeit) => (9, Some(winner)),
+        _ => (0, None),
+    };
+    match winner {
+        Some(PlayerColor::Black) => ending | BLACK_WINS,
+        _ => ending,
+    }
+}
+
+/// Ends a decoded game after its moves have been replayed, in the way the game originally ended.
+///
+/// # Arguments
+///
+/// * `game`: The game, with all of its moves played.
+/// * `status`: The status the game ended with, see [decode_ending].
+/// * `reason`: The reason given by the arbiter, for an adjudicated game.
+///
+/// returns: `Some(())` if the game could end with the status, otherwise `None`.
+fn end_decoded_game(game: &mut ChessGame, status: GameStatus, reason: &str) -> Option<()> {
+    match status {
+        GameStatus::Draw(DrawReason::DrawByAgreement) => game.draw_by_agreement().ok()?,
+        GameStatus::Win(_, WinReason::Resignation) => game.resign().ok()?,
+        GameStatus::Draw(DrawReason::FiftyMoveRule) => game.claim_fifty_move_draw().ok()?,
+        GameStatus::Win(winner, WinReason::Timeout) => {
+            game.flag(winner.other_player()).ok()?;
+        }
+        GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial) => {
+            // the flagged player is the one whose opponent cannot checkmate them
+            let player = game.active_player();
+            let flagged = if game.can_checkmate(player.other_player()) {
+                player.other_player()
+            } else {
+                player
+            };
+            game.flag(flagged).ok()?;
+        }
+        GameStatus::Win(winner, WinReason::Adjudication | WinReason::Forfeit) => {
+            game.adjudicate(AdjudicatedResult::WinFor(winner), reason).ok()?
+        }
+        GameStatus::Draw(DrawReason::Adjudication) => {
+            game.adjudicate(AdjudicatedResult::Draw, reason).ok()?
+        }
+        GameStatus::Win(_, WinReason::IllegalMoveForfeit) if !game.game_status().is_over() => {
+            game.set_game_status(status)
+        }
+        _ => return None,
+    }
+    // the moves decide some of the details, such as the player who resigned
+    (*game.game_status() == status).then_some(())
+}
+
+/// returns: The board games of a variant start from unless another position is given.
+fn default_board(variant: Variant) -> Board {
+    match variant {
+        Variant::Horde => Board::horde_board(),
+        _ => Board::default_board(),
+    }
+}
+
+/// returns: A move of the game's history in the 16-bit encoding of the archive format.
+fn encode_move(details: &MoveDetails) -> u16 {
+    let chess_move = details.chess_move;
+    let to = chess_move.piece_movement.to.index() as u16;
+    if details.is_drop {
+        let piece = PieceType::ALL.iter()
+            .position(|&piece_type| piece_type == details.piece.piece_type)
+            .unwrap_or_default() as u16;
+        return piece << 10 | to << 4 | DROP_FLAG;
+    }
+    let from = chess_move.piece_movement.from.index() as u16;
+    match chess_move.promotion {
+        Some(promotion) => {
+            let promotion = PromotionType::ALL.iter()
+                .position(|&promotion_type| promotion_type == promotion)
+                .unwrap_or_default() as u16;
+            from << 10 | to << 4 | promotion << 2 | PROMOTION_FLAG
+        }
+        None => from << 10 | to << 4,
+    }
+}
+
+/// Plays a move in the 16-bit encoding of the archive format.
+///
+/// returns: `Some(())` if the move was legal, otherwise `None`.
+fn play_encoded_move(game: &mut ChessGame, encoded: u16) -> Option<()> {
+    let from = (encoded >> 10) as u8;
+    let to = BoardPosition::from_index((encoded >> 4 & 0b11_1111) as u8)?;
+    let promotion = PromotionType::ALL[(encoded >> 2 & 0b11) as usize];
+    match encoded & 0b11 {
+        0 => game.do_move(ChessMove::new(BoardPosition::from_index(from)?, to)).ok()?,
+        PROMOTION_FLAG => {
+            let from = BoardPosition::from_index(from)?;
+            game.do_move(ChessMove::promoting(from, to, promotion)).ok()?
+        }
+        DROP_FLAG => {
+            let piece = *PieceType::ALL.get(from as usize)?;
+            game.do_drop(DropMove::new(piece, to)).ok()?
+        }
+        _ => return None,
+    };
+    Some(())
+}
+
+/// A collection of games in the compact binary format described in the
+/// [module documentation](self), which is much smaller and faster to read than PGN.
+///
+/// Single games are encoded with [GameArchive::encode] and decoded with [GameArchive::decode].
+/// An archive of many games consists of [GameArchive::MAGIC] followed by the encoded games, each
+/// prefixed with its length in bytes as a little-endian 32-bit integer. New games are appended
+/// at the end without changing the existing data, and the index of the games is rebuilt by
+/// skipping from one length prefix to the next when an archive is loaded, without decoding the
+/// games.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameArchive {
+    bytes: Vec<u8>,
+    index: Vec<Range<usize>>,
+}
+
+impl Default for GameArchive {
+    fn default() -> Self {
+        GameArchive::new()
+    }
+}
+
+impl GameArchive {
+    /// The bytes an archive of several games starts with.
+    pub const MAGIC: [u8; 4] = *b"LCA1";
+
+    /// returns: A new archive without any games.
+    pub fn new() -> GameArchive {
+        GameArchive { bytes: GameArchive::MAGIC.to_vec(), index: Vec::new() }
+    }
+
+    /// Loads an archive from its binary representation, as returned by
+    /// [as_bytes](GameArchive::as_bytes), building the index of its games. The games themselves
+    /// are only decoded when they are accessed.
+    ///
+    /// returns: The archive, or [InvalidMagic](ArchiveError::InvalidMagic) or
+    ///          [UnexpectedEnd](ArchiveError::UnexpectedEnd) if the data is not an archive.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<GameArchive, ArchiveError> {
+        if !bytes.starts_with(&GameArchive::MAGIC) {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let mut reader = Reader { bytes: &bytes, position: GameArchive::MAGIC.len() };
+        let mut index = Vec::new();
+        while reader.position < bytes.len() {
+            let len = reader.u32()? as usize;
+            let start = reader.position;
+            reader.take(len)?;
+            index.push(start..reader.position);
+        }
+        Ok(GameArchive { bytes, index })
+    }
+
+    /// returns: The binary representation of the archive.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// returns: The binary representation of the archive.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// returns: The number of games in the archive.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// returns: Whether the archive contains no games.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Appends a game with the given tags to the end of the archive, see
+    /// [encode_with_tags](GameArchive::encode_with_tags).
+    pub fn push(&mut self, game: &ChessGame, tags: &[(&str, &str)]) {
+        let encoded = GameArchive::encode_with_tags(game, tags);
+        self.bytes.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        let start = self.bytes.len();
+        self.bytes.extend_from_slice(&encoded);
+        self.index.push(start..self.bytes.len());
+    }
+
+    /// returns: The encoded data of the game with the given index, borrowed from the archive, or
+    ///          `None` if there is no such game.
+    pub fn game_bytes(&self, index: usize) -> Option<&[u8]> {
+        self.index.get(index).map(|range| &self.bytes[range.clone()])
+    }
+
+    /// returns: The game with the given index decoded like in
+    ///          [decode_with_tags](GameArchive::decode_with_tags), or
+    ///          [NoSuchGame](ArchiveError::NoSuchGame) if there is no such game.
+    pub fn game(&self, index: usize) -> Result<(ChessGame, Vec<(String, String)>), ArchiveError> {
+        let bytes = self.game_bytes(index).ok_or(ArchiveError::NoSuchGame(index))?;
+        GameArchive::decode_with_tags(bytes)
+    }
+
+    /// returns: A single game encoded without tags, see
+    ///          [encode_with_tags](GameArchive::encode_with_tags).
+    pub fn encode(game: &ChessGame) -> Vec<u8> {
+        GameArchive::encode_with_tags(game, &[])
+    }
+
+    /// Encodes a single game with tags, such as the PGN tags `Event` or `White`. Tag names longer
+    /// than 255 bytes and tag values longer than 65535 bytes are truncated to the last whole
+    /// character that fits, as is the reason of an adjudicated game, and the list of tags is
+    /// truncated after 65535 tags. Extra moves given with
+    /// [ChessGameBuilder::first_moves] are only recorded as part of a [Handicap].
+    ///
+    /// returns: The game in the binary format described in the [module documentation](self).
+    pub fn encode_with_tags(game: &ChessGame, tags: &[(&str, &str)]) -> Vec<u8> {
+        let starting_position = game.starting_position();
+        let default_position = ChessGame::with_variant(default_board(game.variant()),
+                                                       game.variant());
+        let mut flags = 0;
+        if game.handicap().is_some() {
+            flags |= HAS_HANDICAP;
+        } else if starting_position.fen() != default_position.fen() {
+            flags |= HAS_STARTING_POSITION;
+        }
+        let variant = VARIANTS.iter().position(|&variant| variant == game.variant())
+            .unwrap_or_default() as u8;
+        let ending = encode_ending(game.game_status());
+        let mut bytes = vec![flags, variant, ending];
+        if ADJUDICATED_ENDINGS.contains(&(ending & !BLACK_WINS)) {
+            push_string(&mut bytes, game.termination().unwrap_or_default());
+        }
+
+        let tags = &tags[..tags.len().min(u16::MAX as usize)];
+        bytes.extend_from_slice(&(tags.len() as u16).to_le_bytes());
+        for (name, value) in tags {
+            let name = truncate(name, u8::MAX as usize);
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name.as_bytes());
+            push_string(&mut bytes, value);
+        }
+
+        if flags & HAS_STARTING_POSITION != 0 {
+            let fen = starting_position.fen();
+            bytes.push(fen.len() as u8);
+            bytes.extend_from_slice(fen.as_bytes());
+        }
+        if let Some((handicap, giver)) = game.handicap() {
+            let handicap = HANDICAPS.iter().position(|&kind| kind == handicap)
+                .unwrap_or_default() as u8;
+            bytes.push(handicap);
+            bytes.push(match giver {
+                PlayerColor::White => 0,
+                PlayerColor::Black => 1,
+            });
+        }
+
+        bytes.extend_from_slice(&(game.history().len() as u32).to_le_bytes());
+        for details in game.history() {
+            bytes.extend_from_slice(&encode_move(details).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// returns: A single game decoded like in [decode_with_tags](GameArchive::decode_with_tags),
+    ///          discarding its tags.
+    pub fn decode(bytes: &[u8]) -> Result<ChessGame, ArchiveError> {
+        GameArchive::decode_with_tags(bytes).map(|(game, _)| game)
+    }
+
+    /// Decodes a single game encoded with [encode_with_tags](GameArchive::encode_with_tags),
+    /// replaying its moves to validate them.
+    ///
+    /// returns: The game and its tags, or the [ArchiveError] describing the first problem found
+    ///          in the data.
+    pub fn decode_with_tags(bytes: &[u8])
+                            -> Result<(ChessGame, Vec<(String, String)>), ArchiveError>
+    {
+        let mut reader = Reader { bytes, position: 0 };
+        let flags = reader.u8()?;
+        if flags & !(HAS_STARTING_POSITION | HAS_HANDICAP) != 0
+            || flags == HAS_STARTING_POSITION | HAS_HANDICAP
+        {
+            return Err(ArchiveError::InvalidHeader("flags"));
+        }
+        let variant = *VARIANTS.get(reader.u8()? as usize)
+            .ok_or(ArchiveError::InvalidHeader("variant"))?;
+        let ending = reader.u8()?;
+        let status = decode_ending(ending)?;
+        let reason = if ADJUDICATED_ENDINGS.contains(&(ending & !BLACK_WINS)) {
+            let len = reader.u16()? as usize;
+            reader.string(len)?
+        } else {
+            String::new()
+        };
+
+        let tag_count = reader.u16()?;
+        let mut tags = Vec::with_capacity(tag_count as usize);
+        for _ in 0..tag_count {
+            let name_len = reader.u8()? as usize;
+            let name = reader.string(name_len)?;
+            let value_len = reader.u16()? as usize;
+            let value = reader.string(value_len)?;
+            tags.push((name, value));
+        }
+
+        let mut game = if flags & HAS_STARTING_POSITION != 0 {
+            let fen_len = reader.u8()? as usize;
+            let fen = core::str::from_utf8(reader.take(fen_len)?)
+                .map_err(|_| FenError::InvalidSyntax)?;
+            let start = ChessGame::from_fen(fen)?;
+            let mut game = ChessGame::with_variant(start.board().clone(), variant);
+            game.set_position(start.board().clone(), start.active_player(),
+                              (start.castling_rights(PlayerColor::White),
+                               start.castling_rights(PlayerColor::Black)),
+                              start.en_passant_target())
+                .map_err(FenError::from)?;
+            game.set_starting_counters(start.halfmove_clock(), start.fullmove_number());
+            game
+        } else if flags & HAS_HANDICAP != 0 {
+            let handicap = *HANDICAPS.get(reader.u8()? as usize)
+                .ok_or(ArchiveError::InvalidHeader("handicap"))?;
+            let giver = match reader.u8()? {
+                0 => PlayerColor::White,
+                1 => PlayerColor::Black,
+                _ => return Err(ArchiveError::InvalidHeader("handicap giver")),
+            };
+            ChessGameBuilder::new().variant(variant).with_handicap(handicap, giver).build()
+        } else {
+            ChessGame::with_variant(default_board(variant), variant)
+        };
+
+        let move_count = reader.u32()?;
+        for index in 0..move_count as usize {
+            let encoded = reader.u16()?;
+            play_encoded_move(&mut game, encoded).ok_or(ArchiveError::IllegalMove(index))?;
+        }
+        if reader.position != bytes.len() {
+            return Err(ArchiveError::TrailingData);
+        }
+        if let Some(status) = status {
+            end_decoded_game(&mut game, status, &reason)
+                .ok_or(ArchiveError::InvalidHeader("game ending"))?;
+        }
+        Ok((game, tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for coordinates in moves {
+            let chess_move = game.parse_coordinate_move(coordinates).unwrap();
+            game.do_move(chess_move).unwrap();
+        }
+    }
+
+    fn assert_round_trip(game: &ChessGame) {
+        let decoded = GameArchive::decode(&GameArchive::encode(game)).unwrap();
+        assert_eq!(decoded.fen(), game.fen());
+        assert_eq!(decoded.history(), game.history());
+        assert_eq!(decoded.game_status(), game.game_status());
+        assert_eq!(decoded.starting_position().fen(), game.starting_position().fen());
+        assert_eq!(decoded.termination(), game.termination());
+    }
+
+    #[test]
+    fn special_moves_round_trip() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8h6", "f6g7", "b8c6",
+                          "g7h8q", "c8f5", "g1f3", "d8d7", "f1c4", "e8c8", "e1g1"]);
+        let encoded = GameArchive::encode(&game);
+        // 3 header bytes, 2 bytes of tag count, 4 bytes of move count and 2 bytes per move
+        assert_eq!(encoded.len(), 3 + 2 + 4 + 2 * 15);
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        play(&mut game, &["b7b8n", "e8e7", "b8a6"]);
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K1N1 b - - 12 40").unwrap();
+        play(&mut game, &["e8e7", "g1f3"]);
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert!(game.game_status().is_over());
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4"]);
+        game.resign().unwrap();
+        assert_round_trip(&game);
+    }
+
+    #[test]
+    fn endings_round_trip() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4", "e7e5", "g1f3"]);
+        game.resign().unwrap();
+        assert_eq!(game.game_status(),
+                   &GameStatus::Win(PlayerColor::White, WinReason::Resignation));
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4", "e7e5"]);
+        game.flag(PlayerColor::White).unwrap();
+        assert_eq!(game.game_status(), &GameStatus::Win(PlayerColor::Black, WinReason::Timeout));
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        play(&mut game, &["e1d2"]);
+        game.flag(PlayerColor::White).unwrap();
+        assert_eq!(game.game_status(),
+                   &GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial));
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["d2d4"]);
+        game.adjudicate(AdjudicatedResult::WinFor(PlayerColor::Black), "White left the hall")
+            .unwrap();
+        assert_eq!(game.game_status(),
+                   &GameStatus::Win(PlayerColor::Black, WinReason::Adjudication));
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["d2d4", "d7d5"]);
+        game.adjudicate(AdjudicatedResult::Draw, "dead position").unwrap();
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        game.adjudicate(AdjudicatedResult::WinFor(PlayerColor::White), "Black did not show up")
+            .unwrap();
+        assert_eq!(game.game_status(), &GameStatus::Win(PlayerColor::White, WinReason::Forfeit));
+        assert_round_trip(&game);
+
+        let mut game = ChessGame::new(Board::default_board());
+        game.set_illegal_move_limit(Some(1));
+        play(&mut game, &["e2e4"]);
+        let illegal = ChessMove::new("e7".parse().unwrap(), "e4".parse().unwrap());
+        assert!(game.do_move(illegal).is_err());
+        assert_eq!(game.game_status(),
+                   &GameStatus::Win(PlayerColor::White, WinReason::IllegalMoveForfeit));
+        assert_round_trip(&game);
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        play(&mut game, &["e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5a5"]);
+        game.do_drop(DropMove::new(PieceType::Pawn, "e6".parse().unwrap())).unwrap();
+        assert_round_trip(&game);
+
+        let mut game = ChessGameBuilder::new()
+            .with_handicap(Handicap::PawnAndTwoMoves, PlayerColor::Black)
+            .build();
+        play(&mut game, &["e2e4", "d2d4", "e7e6"]);
+        let decoded = GameArchive::decode(&GameArchive::encode(&game)).unwrap();
+        assert_eq!(decoded.handicap(), game.handicap());
+        assert_round_trip(&game);
+    }
+
+    #[test]
+    fn tags_and_multiple_games() {
+        let mut archive = GameArchive::new();
+        let mut first = ChessGame::new(Board::default_board());
+        play(&mut first, &["d2d4", "d7d5"]);
+        first.draw_by_agreement().unwrap();
+        let second = ChessGame::from_fen("8/8/8/4k3/8/8/3P4/4K3 w - - 0 1").unwrap();
+        archive.push(&first, &[("White", "Alice"), ("Black", "Bob")]);
+        archive.push(&second, &[]);
+
+        let loaded = GameArchive::from_bytes(archive.as_bytes().to_vec()).unwrap();
+        assert_eq!(loaded, archive);
+        assert_eq!(loaded.len(), 2);
+        let (game, tags) = loaded.game(0).unwrap();
+        assert_eq!(game.game_status(), &GameStatus::Draw(DrawReason::DrawByAgreement));
+        assert_eq!(tags, [("White".to_string(), "Alice".to_string()),
+                          ("Black".to_string(), "Bob".to_string())]);
+        assert_eq!(loaded.game(1).unwrap().0.fen(), second.fen());
+        assert_eq!(loaded.game(2).unwrap_err(), ArchiveError::NoSuchGame(2));
+    }
+
+    #[test]
+    fn long_tags_are_truncated_on_character_boundaries() {
+        let game = ChessGame::new(Board::default_board());
+        // two bytes per character, so a byte limit of an odd number falls inside a character
+        let name = "é".repeat(200);
+        let value = "é".repeat(40000);
+        let encoded = GameArchive::encode_with_tags(&game, &[(&name, &value)]);
+        let (_, tags) = GameArchive::decode_with_tags(&encoded).unwrap();
+        assert_eq!(tags, [("é".repeat(127), "é".repeat(32767))]);
+    }
+
+    #[test]
+    fn corrupted_data() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4", "e7e5", "g1f3"]);
+        let encoded = GameArchive::encode(&game);
+
+        assert_eq!(GameArchive::decode(&encoded[..encoded.len() - 1]).unwrap_err(),
+                   ArchiveError::UnexpectedEnd(encoded.len() - 1));
+        let mut illegal = encoded.clone();
+        // turn the last move into g1g3
+        let g1g3: u16 = 48 << 10 | 50 << 4;
+        let last = illegal.len() - 2;
+        illegal[last..].copy_from_slice(&g1g3.to_le_bytes());
+        assert_eq!(GameArchive::decode(&illegal).unwrap_err(), ArchiveError::IllegalMove(2));
+        let mut variant = encoded.clone();
+        variant[1] = 9;
+        assert_eq!(GameArchive::decode(&variant).unwrap_err(),
+                   ArchiveError::InvalidHeader("variant"));
+        // an undefined ending, a draw with a winner and a resignation of the player not to move
+        for invalid_ending in [10, 1 | BLACK_WINS, 2 | BLACK_WINS] {
+            let mut ending = encoded.clone();
+            ending[2] = invalid_ending;
+            assert_eq!(GameArchive::decode(&ending).unwrap_err(),
+                       ArchiveError::InvalidHeader("game ending"));
+        }
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(GameArchive::decode(&trailing).unwrap_err(), ArchiveError::TrailingData);
+
+        assert_eq!(GameArchive::from_bytes(b"PGN1".to_vec()).unwrap_err(),
+                   ArchiveError::InvalidMagic);
+        let mut archive = GameArchive::new();
+        archive.push(&game, &[]);
+        let mut bytes = archive.into_bytes();
+        bytes.pop();
+        assert!(matches!(GameArchive::from_bytes(bytes), Err(ArchiveError::UnexpectedEnd(_))));
+    }
+}
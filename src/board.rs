@@ -4,34 +4,63 @@
 
 pub mod piece;
 pub mod board_pos;
+mod diagram;
+mod diff;
+mod render;
+mod transform;
+#[cfg(feature = "rand")]
+pub mod random;
 
-use std::fmt::{Display, Formatter};
-use crate::board::board_pos::BoardPosition;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::{Index, IndexMut};
+use core::str::FromStr;
+use thiserror::Error;
+use crate::board::board_pos::{BoardPosition, PositionParseError};
 use crate::board::piece::{Piece, PieceType::*, PieceType, PlayerColor::*, PlayerColor};
+use crate::chess::FenError;
+use crate::moves::PieceMovement;
+use crate::moves::util::BoardBitmap;
+use crate::util::U3;
+
+pub use diagram::DiagramError;
+pub use diff::{board_diff_report, SquareDiff};
+pub use render::{BoardDisplayOptions, BorderStyle, EmptySquareStyle};
 
 /// The `Board` type. Represents a grid of squares that are either empty or contain a piece.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Board { squares: [[Option<Piece>; 8]; 8] }
 
+/// Formats the board as a diagram from white's perspective, with a rank label before each rank
+/// and a row of file labels at the bottom. Pieces are written as FEN letters and empty squares as
+/// spaces. The alternate flag (`{:#}`) writes the pieces as Unicode chess symbols, and the empty
+/// squares as `░` for light squares and `▓` for dark squares. See [Board::display_from] for
+/// formatting the board from black's perspective, and [Board::render] for more options.
 impl Display for Board {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for rank in (0u8..8).rev() {
-            write!(f, "\n{}", rank + 1)?;
-            for file in 0u8..8 {
-                let pos = BoardPosition {
-                    file: file.try_into().unwrap(),
-                    rank: rank.try_into().unwrap()
-                };
-                let piece = self.get_piece(pos);
-                if let Some(piece) = piece {
-                    write!(f, " {}", piece.get_char())?;
-                } else {
-                    write!(f, "  ")?;
-                }
-            }
-        }
-        write!(f, "\n  a b c d e f g h")?;
-        Ok(())
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.display_from(White).fmt(f)
+    }
+}
+
+/// A [Board] formatted from one player's perspective, with that player's pieces at the bottom.
+/// Formatted like the [Display] implementation of [Board], including the alternate flag. See
+/// [Board::display_from].
+#[derive(Copy, Clone, Debug)]
+pub struct BoardDisplay<'a> {
+    board: &'a Board,
+    perspective: PlayerColor,
+}
+
+impl Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let options = if f.alternate() {
+            BoardDisplayOptions::unicode()
+        } else {
+            BoardDisplayOptions::default()
+        };
+        let options = BoardDisplayOptions { perspective: self.perspective, ..options };
+        write!(f, "\n{}", self.board.render(&options))
     }
 }
 
@@ -45,6 +74,27 @@ pub(crate) enum OccupantState {
     Enemy,
 }
 
+/// An error caused by a board or game position which could not occur in a standard chess game.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PositionError {
+    /// A player does not have exactly one king.
+    #[error("{0} does not have exactly one king")]
+    InvalidKingCount(PlayerColor),
+    /// There is a pawn on the first or the last rank.
+    #[error("pawn on the first or last rank at {0}")]
+    PawnOnBackRank(BoardPosition),
+    /// The player who is not to move is in check.
+    #[error("the player who is not to move is in check")]
+    InactivePlayerInCheck,
+    /// A player has castling rights, but the king or the rook is not on its starting square.
+    #[error("{0} has castling rights without the king and rook on their starting squares")]
+    InvalidCastlingRights(PlayerColor),
+    /// The en passant target square does not lie behind a pawn which has just made a double
+    /// move.
+    #[error("invalid en passant target {0}")]
+    InvalidEnPassantTarget(BoardPosition),
+}
+
 impl Board {
     const EMPTY_BOARD: Board = Board {
         squares: [[None; 8]; 8]
@@ -91,6 +141,99 @@ impl Board {
         *self.square_at_mut(pos) = piece;
     }
 
+    /// returns: An iterator over the occupied squares of the board and their pieces, in the same
+    ///          order as iterating over the whole board, rank by rank (a1, b1, ..., h8).
+    pub fn pieces(&self) -> impl Iterator<Item=(BoardPosition, Piece)> + '_ {
+        self.into_iter().filter_map(|(pos, piece)| Some((pos, piece?)))
+    }
+
+    /// returns: An iterator over the squares of the pieces of the given player and their pieces,
+    ///          like [pieces](Board::pieces).
+    pub fn pieces_of(&self, player: PlayerColor)
+                     -> impl Iterator<Item=(BoardPosition, Piece)> + '_
+    {
+        self.pieces().filter(move |(_, piece)| piece.player == player)
+    }
+
+    /// returns: The bitmap of the squares holding a piece of the given type and player, which
+    ///          may be any number of squares, for example after promotions to a second queen.
+    pub fn find(&self, piece_type: PieceType, player: PlayerColor) -> BoardBitmap {
+        let mut bitmap = BoardBitmap::all_zeros();
+        for (pos, _) in self.pieces_of(player).filter(|(_, piece)| piece.piece_type == piece_type) {
+            bitmap.set(pos, true);
+        }
+        bitmap
+    }
+
+    /// Finds a piece which there usually is at most one of, such as a king. If there are several
+    /// pieces of the type, the first one in the order of [pieces](Board::pieces) is found.
+    ///
+    /// returns: `Some(BoardPosition)` of a piece of the given type and player, or `None` if the
+    ///          player has no such piece.
+    pub fn find_one(&self, piece_type: PieceType, player: PlayerColor) -> Option<BoardPosition> {
+        self.pieces_of(player)
+            .find(|(_, piece)| piece.piece_type == piece_type)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Gets the piece on a square given by its name, like [get_piece](Board::get_piece). The board
+    /// can also be indexed by [BoardPosition] directly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::Board;
+    /// use leben_chess::board::board_pos::BoardPosition;
+    /// use leben_chess::board::piece::{Piece, PieceType, PlayerColor};
+    ///
+    /// let mut board = Board::default_board();
+    /// let e2: BoardPosition = "e2".parse().unwrap();
+    /// let e4: BoardPosition = "e4".parse().unwrap();
+    /// board[e4] = board[e2].take();
+    /// assert_eq!(board.get("e4"), Ok(Some(Piece::new(PieceType::Pawn, PlayerColor::White))));
+    /// assert_eq!(board.get("e2"), Ok(None));
+    /// assert!(board.get("e9").is_err());
+    /// ```
+    ///
+    /// returns: `Ok(Option<Piece>)` with the piece on the square, if any.
+    ///          [PositionParseError] if the square name is invalid.
+    pub fn get(&self, square: &str) -> Result<Option<Piece>, PositionParseError> {
+        Ok(self.get_piece(square.parse()?))
+    }
+
+    /// Iterates over the squares in the order of FEN strings and diagrams: the ranks from 8 down
+    /// to 1, with the files of each rank from a to h (a8, b8, ..., h8, a7, ..., h1). Iterating
+    /// over the board itself instead goes from a1 to h8, rank by rank.
+    ///
+    /// returns: An iterator over all 64 squares and their contents.
+    pub fn iter_fen_order(&self) -> impl Iterator<Item=(BoardPosition, Option<Piece>)> + '_ {
+        self.iter_from_perspective(White)
+    }
+
+    /// Iterates over the squares in reading order of a diagram from one player's perspective,
+    /// like [display_from](Board::display_from): from the top left corner, row by row. From
+    /// white's perspective, this is the [FEN order](Board::iter_fen_order), and from black's
+    /// perspective, the order starts at h1 and ends at a8 (h1, g1, ..., a1, h2, ..., a8).
+    ///
+    /// returns: An iterator over all 64 squares and their contents.
+    pub fn iter_from_perspective(&self, perspective: PlayerColor)
+                                 -> impl Iterator<Item=(BoardPosition, Option<Piece>)> + '_
+    {
+        squares_from_perspective(perspective).map(|pos| (pos, self.get_piece(pos)))
+    }
+
+    /// returns: An iterator over the 8 squares of a rank and their contents, from the a-file to
+    ///          the h-file.
+    pub fn iter_rank(&self, rank: U3) -> impl Iterator<Item=(BoardPosition, Option<Piece>)> + '_ {
+        rank_squares(rank).map(|pos| (pos, self.get_piece(pos)))
+    }
+
+    /// returns: An iterator over the 8 squares of a file and their contents, from rank 1 to
+    ///          rank 8.
+    pub fn iter_file(&self, file: U3) -> impl Iterator<Item=(BoardPosition, Option<Piece>)> + '_ {
+        file_squares(file).map(|pos| (pos, self.get_piece(pos)))
+    }
+
     pub(crate) fn get_occupant_state(&self, pos: BoardPosition,
                                      active_player: PlayerColor) -> OccupantState
     {
@@ -120,6 +263,69 @@ impl Board {
         &self.squares
     }
 
+    /// Gives read-only access to the squares of the board as a 2D array, without copying it.
+    /// The array is file-major: it is indexed first by file and then by rank, both counted from
+    /// 0, so `squares[0][0]` is a1, `squares[0][1]` is a2 and `squares[2][4]` is c5. This is the
+    /// transpose of the rank-by-rank order of FEN strings and diagrams, which list the ranks from
+    /// 8 down to 1 with the files of each rank from a to h. The layout is the same as for
+    /// [from_array](Board::from_array).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::Board;
+    ///
+    /// let board = Board::default_board();
+    /// // the e-file, from e1 to e8
+    /// let e_file = &board.as_array()[4];
+    /// assert_eq!(e_file[0], board.get("e1").unwrap());
+    /// assert_eq!(e_file[7], board.get("e8").unwrap());
+    /// ```
+    ///
+    /// returns: The squares indexed by file and then by rank.
+    pub const fn as_array(&self) -> &[[Option<Piece>; 8]; 8] {
+        &self.squares
+    }
+
+    /// Sets the contents of several squares, like calling [set_piece](Board::set_piece) for each
+    /// of them in order, so a square listed more than once gets its last contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `pieces`: The squares with their new contents, where `None` empties the square.
+    pub fn set_pieces(&mut self, pieces: &[(BoardPosition, Option<Piece>)]) {
+        for &(pos, piece) in pieces {
+            self.set_piece(pos, piece);
+        }
+    }
+
+    /// Relocates pieces from square to square, one movement at a time, for example to replay
+    /// trusted data or to drag pieces around in a board editor. Each movement empties its origin
+    /// square and overwrites its destination square with the piece from the origin, or empties it
+    /// if the origin was empty.
+    ///
+    /// **This is a board editing primitive, not a chess move.** The movements are not checked
+    /// for legality, and no chess rules are applied: castling moves only the king, without the
+    /// rook, an en passant capture leaves the captured pawn on the board, and a pawn reaching the
+    /// last rank stays a pawn. Use [ChessGame::do_move](crate::chess::ChessGame::do_move) to
+    /// perform moves by the rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves`: The movements to perform, in order.
+    pub fn apply_moves(&mut self, moves: &[PieceMovement]) {
+        for movement in moves {
+            let piece = self.get_piece(movement.from);
+            self.set_piece(movement.from, None);
+            self.set_piece(movement.to, piece);
+        }
+    }
+
+    /// Removes all pieces from the board.
+    pub fn clear(&mut self) {
+        self.squares = Board::EMPTY_BOARD.squares;
+    }
+
     /// Instantiate an empty board
     pub fn empty_board() -> Board {
         Board::EMPTY_BOARD
@@ -130,16 +336,152 @@ impl Board {
         Board::DEFAULT_BOARD
     }
 
+    /// Instantiate a board with the starting position of
+    /// [Horde](crate::chess::variant::Variant::Horde): black's pieces are in their default
+    /// configuration, and white has 36 pawns filling the first four ranks and the b, c, f and g
+    /// files of the fifth rank.
+    pub fn horde_board() -> Board {
+        let mut board = Board::default_board();
+        for pos in BoardPosition::all() {
+            let (file, rank) = (pos.file.get(), pos.rank.get());
+            if rank < 4 || rank == 4 && matches!(file, 1 | 2 | 5 | 6) {
+                board.set_piece(pos, Some(Piece { piece_type: Pawn, player: White }));
+            }
+        }
+        board
+    }
+
+    /// The number of starting positions in Chess960.
+    pub const CHESS960_POSITIONS: u16 = 960;
+
+    /// Instantiate a board with a Chess960 starting position, by its index in the standard
+    /// Scharnagl numbering. The bishops are placed on squares of opposite colors and the king
+    /// between the rooks, and black's pieces mirror white's. Position 518 is the
+    /// [default board](Board::default_board).
+    ///
+    /// see: [Fischer random chess numbering scheme - Wikipedia](https://en.wikipedia.org/wiki/Fischer_random_chess_numbering_scheme)
+    ///
+    /// returns: `Some(Board)`, or `None` if `index` is not below [Board::CHESS960_POSITIONS].
+    pub fn chess960_start(index: u16) -> Option<Board> {
+        // the squares of the knights among the five squares left after placing the bishops and
+        // the queen
+        const KNIGHTS: [(usize, usize); 10] = [
+            (0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4),
+        ];
+
+        if index >= Board::CHESS960_POSITIONS {
+            return None;
+        }
+        let mut n = index as usize;
+        let mut back_rank = [None; 8];
+        // light-squared bishop on b, d, f or h, dark-squared bishop on a, c, e or g
+        back_rank[n % 4 * 2 + 1] = Some(Bishop);
+        n /= 4;
+        back_rank[n % 4 * 2] = Some(Bishop);
+        n /= 4;
+        let empty_files = |back_rank: &[Option<PieceType>; 8]| -> Vec<usize> {
+            (0..8).filter(|&file| back_rank[file].is_none()).collect()
+        };
+        back_rank[empty_files(&back_rank)[n % 6]] = Some(Queen);
+        n /= 6;
+        let (first, second) = KNIGHTS[n];
+        let files = empty_files(&back_rank);
+        back_rank[files[first]] = Some(Knight);
+        back_rank[files[second]] = Some(Knight);
+        for (file, piece_type) in empty_files(&back_rank).into_iter().zip([Rook, King, Rook]) {
+            back_rank[file] = Some(piece_type);
+        }
+
+        let mut board = Board::empty_board();
+        for (file, piece_type) in back_rank.into_iter().enumerate() {
+            let piece_type = piece_type?;
+            board.squares[file] = Board::default_board_file(piece_type);
+        }
+        Some(board)
+    }
+
+    /// Checks that the board could occur in a standard chess game, disregarding whose turn it is:
+    /// each player has exactly one king, and there are no pawns on the first or last rank.
+    ///
+    /// returns: `Ok(())` if the board is valid, otherwise the first [PositionError] found.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        for player in [White, Black] {
+            let kings = self.into_iter()
+                .filter(|(_, piece)| *piece == Some(Piece { piece_type: King, player }))
+                .count();
+            if kings != 1 {
+                return Err(PositionError::InvalidKingCount(player));
+            }
+        }
+        for (pos, piece) in self {
+            if piece.is_some_and(|piece| piece.piece_type == Pawn)
+                && (pos.rank.get() == 0 || pos.rank.get() == 7)
+            {
+                return Err(PositionError::PawnOnBackRank(pos));
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats the board from one player's perspective, for example for showing the board to
+    /// the player with black. From black's perspective, rank 1 is at the top and the h-file is
+    /// on the left.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::Board;
+    /// use leben_chess::board::piece::PlayerColor;
+    ///
+    /// let board = Board::default_board();
+    /// println!("{:#}", board.display_from(PlayerColor::Black));
+    /// ```
+    ///
+    /// returns: A [BoardDisplay], which implements [Display] like [Board] does.
+    pub fn display_from(&self, perspective: PlayerColor) -> BoardDisplay<'_> {
+        BoardDisplay { board: self, perspective }
+    }
+
+    /// returns: The piece placement section of a FEN string representing the board. See
+    ///          [from_fen_string](Board::from_fen_string).
+    pub fn to_fen_string(&self) -> String {
+        let mut string = String::new();
+        let mut empty_squares = 0;
+        for (pos, piece) in self.iter_fen_order() {
+            match piece {
+                Some(piece) => {
+                    if empty_squares > 0 {
+                        string += &empty_squares.to_string();
+                        empty_squares = 0;
+                    }
+                    string.push(piece.to_char());
+                }
+                None => empty_squares += 1,
+            }
+            // the end of a rank
+            if pos.file.get() == 7 {
+                if empty_squares > 0 {
+                    string += &empty_squares.to_string();
+                    empty_squares = 0;
+                }
+                if pos.rank.get() > 0 {
+                    string.push('/');
+                }
+            }
+        }
+        string
+    }
+
     /// Instantiate a board from the piece placement section of a FEN string
     ///
     /// # Arguments
     ///
     /// * `string`: A string containing the eight ranks from 1 to 8 separated by `/`, with each
-    /// piece within a rank represented by the standard English chess piece names in algebraic
-    /// notation (pawn = "P", knight = "N", bishop = "B", rook = "R", queen = "Q", king = "K"), with
-    /// white pieces represented with uppercase letters and black pieces with lowercase letters.
-    /// Consecutive empty squares within a rank are represented collectively with a single digit 1-8
-    /// corresponding to the amount of squares.
+    ///   piece within a rank represented by the standard English chess piece names in algebraic
+    ///   notation (pawn = "P", knight = "N", bishop = "B", rook = "R", queen = "Q", king = "K"),
+    ///   with white pieces represented with uppercase letters and black pieces with lowercase
+    ///   letters. Consecutive empty squares within a rank are represented collectively with a
+    ///   single digit 1-8 corresponding to the amount of squares.
     ///
     /// see: [Forsyth–Edwards Notation - Wikipedia](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation#Definition)
     ///
@@ -153,7 +495,7 @@ impl Board {
                 if file >= 8 || rank >= 8 {
                     return None;
                 }
-                let pos = BoardPosition::try_from((file, 7 - rank)).unwrap();
+                let pos = BoardPosition::try_from((file, 7 - rank)).ok()?;
                 board.set_piece(pos, Some(piece));
                 file += 1;
             } else if let Some(digit) = ch.to_digit(10) {
@@ -176,6 +518,197 @@ impl Board {
         }
         Some(board)
     }
+
+    /// Instantiate a board from the piece placement section of a FEN string, like
+    /// [from_fen_string](Board::from_fen_string), but usable in `const` and `static` items. A
+    /// full FEN string is also accepted, in which case everything after the piece placement is
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the piece placement is invalid, which is a compile-time error when evaluated in
+    /// a constant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use leben_chess::board::Board;
+    ///
+    /// const OPEN_FILES: Board = Board::const_from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    /// assert_eq!(Some(OPEN_FILES), Board::from_fen_string("4k3/8/8/8/8/8/8/R3K2R"));
+    /// ```
+    ///
+    /// ```rust,compile_fail
+    /// use leben_chess::board::Board;
+    ///
+    /// // only seven ranks
+    /// const INVALID: Board = Board::const_from_fen("4k3/8/8/8/8/8/R3K2R");
+    /// ```
+    pub const fn const_from_fen(fen: &str) -> Board {
+        let bytes = fen.as_bytes();
+        let mut board = Board::EMPTY_BOARD;
+        let mut file = 0;
+        let mut rank = 0;
+        let mut i = 0;
+        while i < bytes.len() && bytes[i] != b' ' {
+            let ch = bytes[i];
+            if let Some(piece) = Board::const_piece_from_byte(ch) {
+                if file >= 8 {
+                    panic!("too many squares in a rank of the FEN string");
+                }
+                board.squares[file][7 - rank] = Some(piece);
+                file += 1;
+            } else if ch >= b'1' && ch <= b'8' {
+                file += (ch - b'0') as usize;
+                if file > 8 {
+                    panic!("too many squares in a rank of the FEN string");
+                }
+            } else if ch == b'/' {
+                if file != 8 || rank >= 7 {
+                    panic!("invalid rank separator in the FEN string");
+                }
+                file = 0;
+                rank += 1;
+            } else {
+                panic!("invalid character in the FEN string");
+            }
+            i += 1;
+        }
+        if file != 8 || rank != 7 {
+            panic!("the FEN string does not contain exactly eight ranks");
+        }
+        board
+    }
+
+    /// Instantiates a board from the eight rows of a diagram, usable in `const` and `static`
+    /// items. This is the function behind the [board!](crate::board!) macro.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows`: The ranks from 8 to 1, each giving the squares from the a-file to the h-file as
+    ///   FEN piece letters, or `.` for an empty square, optionally separated by spaces, such as
+    ///   `"r . . . k . . r"` or `"r...k..r"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a row does not have exactly eight squares or contains an invalid character,
+    /// which is a compile-time error when evaluated in a constant.
+    pub const fn const_from_rows(rows: [&str; 8]) -> Board {
+        let mut board = Board::EMPTY_BOARD;
+        let mut rank = 0;
+        while rank < 8 {
+            let bytes = rows[rank].as_bytes();
+            let mut file = 0;
+            let mut i = 0;
+            while i < bytes.len() {
+                let ch = bytes[i];
+                i += 1;
+                if ch == b' ' {
+                    continue;
+                }
+                if file >= 8 {
+                    panic!("too many squares in a row of the board");
+                }
+                if ch != b'.' {
+                    match Board::const_piece_from_byte(ch) {
+                        Some(piece) => board.squares[file][7 - rank] = Some(piece),
+                        None => panic!("invalid character in a row of the board"),
+                    }
+                }
+                file += 1;
+            }
+            if file != 8 {
+                panic!("too few squares in a row of the board");
+            }
+            rank += 1;
+        }
+        board
+    }
+
+    const fn const_piece_from_byte(ch: u8) -> Option<Piece> {
+        let piece_type = match ch.to_ascii_lowercase() {
+            b'p' => Pawn,
+            b'n' => Knight,
+            b'b' => Bishop,
+            b'r' => Rook,
+            b'q' => Queen,
+            b'k' => King,
+            _ => return None,
+        };
+        let player = if ch.is_ascii_uppercase() { White } else { Black };
+        Some(Piece { piece_type, player })
+    }
+
+    /// The board of "Kiwipete", the second standard perft test position, which contains many
+    /// castling, en passant and promotion edge cases. White is to move, and both players have
+    /// all castling rights.
+    ///
+    /// see: [Perft Results - Chessprogramming wiki](https://www.chessprogramming.org/Perft_Results)
+    pub const KIWIPETE: Board = Board::const_from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+
+    /// The board of the third standard perft test position, a rook and pawn endgame with many
+    /// checks and en passant captures. White is to move, and neither player can castle.
+    ///
+    /// see: [Perft Results - Chessprogramming wiki](https://www.chessprogramming.org/Perft_Results)
+    pub const PERFT_POSITION_3: Board = Board::const_from_fen(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+
+    /// The board of the fourth standard perft test position, with many promotions and pinned
+    /// pieces. White is to move, and only black can castle, on either side.
+    ///
+    /// see: [Perft Results - Chessprogramming wiki](https://www.chessprogramming.org/Perft_Results)
+    pub const PERFT_POSITION_4: Board = Board::const_from_fen(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+}
+
+/// Parses the piece placement section of a FEN string, like [Board::from_fen_string], so that
+/// `"8/8/8/4k3/8/8/8/4K3".parse::<Board>()` works.
+impl FromStr for Board {
+    type Err = FenError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::from_fen_string(s).ok_or(FenError::InvalidSyntax)
+    }
+}
+
+/// Indexes the board by square, like [get_piece](Board::get_piece). See also
+/// [get](Board::get) for squares given by name.
+impl Index<BoardPosition> for Board {
+    type Output = Option<Piece>;
+
+    fn index(&self, pos: BoardPosition) -> &Self::Output {
+        self.square_at(pos)
+    }
+}
+
+/// Indexes the board mutably by square, like [set_piece](Board::set_piece).
+impl IndexMut<BoardPosition> for Board {
+    fn index_mut(&mut self, pos: BoardPosition) -> &mut Self::Output {
+        self.square_at_mut(pos)
+    }
+}
+
+/// returns: The squares of a rank, from the a-file to the h-file.
+fn rank_squares(rank: U3) -> impl Iterator<Item=BoardPosition> {
+    U3::all().map(move |file| BoardPosition { file, rank })
+}
+
+/// returns: The squares of a file, from rank 1 to rank 8.
+fn file_squares(file: U3) -> impl Iterator<Item=BoardPosition> {
+    U3::all().map(move |rank| BoardPosition { file, rank })
+}
+
+/// returns: The squares in reading order of a diagram from one player's perspective, from the
+///          top left corner, row by row. See [Board::iter_from_perspective].
+fn squares_from_perspective(perspective: PlayerColor) -> impl Iterator<Item=BoardPosition> {
+    (0..64).map(move |index: u8| {
+        let (row, column) = (index / 8, index % 8);
+        let (file, rank) = match perspective {
+            White => (column, 7 - row),
+            Black => (7 - column, row),
+        };
+        BoardPosition { file: U3::from_low_bits(file), rank: U3::from_low_bits(rank) }
+    })
 }
 
 /// An iterator that iterates over the squares of a [Board] object.
@@ -190,10 +723,7 @@ impl<'a> Iterator for BoardIterator<'a> {
     type Item = (BoardPosition, Option<Piece>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.rank > 7 {
-            return None;
-        }
-        let pos = BoardPosition::try_from((self.file, self.rank)).unwrap();
+        let pos = BoardPosition::try_from((self.file, self.rank)).ok()?;
         let piece = self.board.get_piece(pos);
         self.file += 1;
         if self.file > 7 {
@@ -235,6 +765,193 @@ mod tests {
         assert_eq!(format!("{}", board), expected);
     }
 
+    #[test]
+    fn board_pieces() {
+        let board = Board::default_board();
+        assert_eq!(board.pieces().count(), 32);
+        assert_eq!(board.pieces_of(White).count(), 16);
+        assert_eq!(board.pieces_of(Black).count(), 16);
+        assert!(board.pieces_of(White).all(|(pos, piece)| {
+            piece.player == White && pos.rank.get() < 2 && board.get_piece(pos) == Some(piece)
+        }));
+        assert_eq!(board.pieces().next(), Some(("a1".parse().unwrap(), Piece::new(Rook, White))));
+        assert_eq!(Board::empty_board().pieces().next(), None);
+    }
+
+    #[test]
+    fn find_pieces() {
+        let board = Board::default_board();
+        let table = [
+            (Pawn, "a2,b2,c2,d2,e2,f2,g2,h2", "a7,b7,c7,d7,e7,f7,g7,h7"),
+            (Knight, "b1,g1", "b8,g8"),
+            (Bishop, "c1,f1", "c8,f8"),
+            (Rook, "a1,h1", "a8,h8"),
+            (Queen, "d1", "d8"),
+            (King, "e1", "e8"),
+        ];
+        for (piece_type, white, black) in table {
+            assert_eq!(board.find(piece_type, White).to_square_list(), white);
+            assert_eq!(board.find(piece_type, Black).to_square_list(), black);
+            assert_eq!(board.find_one(piece_type, White).unwrap().to_string(), &white[..2]);
+        }
+
+        // promoted queens
+        let board = Board::from_fen_string("Q3k2Q/8/8/8/8/8/8/3QK2q").unwrap();
+        assert_eq!(board.find(Queen, White).to_square_list(), "a8,d1,h8");
+        assert_eq!(board.find(Queen, Black).to_square_list(), "h1");
+        assert_eq!(board.find_one(Queen, White), Some("d1".parse().unwrap()));
+        assert_eq!(board.find_one(King, Black), Some("e8".parse().unwrap()));
+        assert!(board.find(Pawn, White).is_all_zeros());
+        assert_eq!(board.find_one(Rook, Black), None);
+    }
+
+    #[test]
+    fn board_indexing() {
+        let mut board = Board::default_board();
+        for pos in BoardPosition::all() {
+            assert_eq!(board[pos], board.get_piece(pos));
+            assert_eq!(board.get(&pos.to_string()), Ok(board.get_piece(pos)));
+        }
+        let (e1, e4) = ("e1".parse().unwrap(), "e4".parse().unwrap());
+        board[e4] = Some(Piece::new(Queen, Black));
+        board[e1] = None;
+        assert_eq!(board.get_piece(e4), Some(Piece::new(Queen, Black)));
+        assert_eq!(board.get_piece(e1), None);
+        assert_eq!(board.get("i1"), Err(PositionParseError::InvalidFile('i')));
+    }
+
+    #[test]
+    fn bulk_access() {
+        let board = Board::from_fen_string("r3k2r/pp3ppp/8/8/4P3/8/PP3PPP/R3K2R").unwrap();
+        for pos in BoardPosition::all() {
+            assert_eq!(board.as_array()[pos.file.get() as usize][pos.rank.get() as usize],
+                       board.get_piece(pos));
+        }
+
+        let mut bulk = Board::default_board();
+        bulk.clear();
+        assert_eq!(bulk, Board::empty_board());
+        let square = |name: &str| BoardPosition::try_from(name).unwrap();
+        let pieces = [
+            (square("e1"), Some(Piece::new(King, White))),
+            (square("e8"), Some(Piece::new(King, Black))),
+            (square("a7"), Some(Piece::new(Pawn, White))),
+            (square("h2"), Some(Piece::new(Rook, Black))),
+            (square("a7"), None),
+            (square("b7"), Some(Piece::new(Pawn, White))),
+        ];
+        bulk.set_pieces(&pieces);
+        let mut single = Board::empty_board();
+        for (pos, piece) in pieces {
+            single.set_piece(pos, piece);
+        }
+        assert_eq!(bulk, single);
+        assert_eq!(bulk.to_fen_string(), "4k3/1P6/8/8/8/8/7r/4K3");
+        assert_eq!(single.to_fen_string(), bulk.to_fen_string());
+    }
+
+    #[test]
+    fn raw_movements() {
+        let mut board: Board = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R".parse().unwrap();
+        let moves: Vec<PieceMovement> = [("e1", "g1"), ("e7", "e5"), ("d2", "d7"), ("c3", "a8"),
+                                         ("e5", "e5")]
+            .into_iter()
+            .map(|(from, to)| PieceMovement::new(from, to).unwrap())
+            .collect();
+        board.apply_moves(&moves);
+        // the rook is not moved by the castling king, a move from an empty square empties the
+        // destination, and a move to the same square changes nothing
+        assert_eq!(board.to_fen_string(), "4k2r/pppP1ppp/8/4p3/8/8/PPP1PPPP/R5KR");
+
+        assert_eq!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR".parse(),
+                   Ok(Board::default_board()));
+        assert_eq!("8/8/8/8/8/8/8/8".parse(), Ok(Board::empty_board()));
+        assert_eq!("8/8/8/8/8/8/8".parse::<Board>(), Err(FenError::InvalidSyntax));
+        assert_eq!("rnbqkbnr/ppppXppp/8/8/8/8/PPPPPPPP/RNBQKBNR".parse::<Board>(),
+                   Err(FenError::InvalidSyntax));
+        assert_eq!("".parse::<Board>(), Err(FenError::InvalidSyntax));
+    }
+
+    #[test]
+    fn traversal_orders() {
+        // a single marked piece in the corner of each player's perspective
+        let board = Board::from_fen_string("n7/8/8/8/8/8/8/7N").unwrap();
+        let names = |squares: &mut dyn Iterator<Item=(BoardPosition, Option<Piece>)>| {
+            squares
+                .map(|(pos, piece)| {
+                    assert_eq!(piece, board.get_piece(pos));
+                    pos.to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let fen_order = names(&mut board.iter_fen_order());
+        assert!(fen_order.starts_with("a8,b8,c8,d8,e8,f8,g8,h8,a7,b7,"), "{}", fen_order);
+        assert!(fen_order.ends_with(",g2,h2,a1,b1,c1,d1,e1,f1,g1,h1"), "{}", fen_order);
+        assert_eq!(names(&mut board.iter_from_perspective(White)), fen_order);
+        let black_order = names(&mut board.iter_from_perspective(Black));
+        assert!(black_order.starts_with("h1,g1,f1,e1,d1,c1,b1,a1,h2,g2,"), "{}", black_order);
+        assert!(black_order.ends_with(",b7,a7,h8,g8,f8,e8,d8,c8,b8,a8"), "{}", black_order);
+        for order in [&fen_order, &black_order] {
+            assert_eq!(order.split(',').count(), 64);
+        }
+        assert_eq!(board.iter_fen_order().next(), Some(("a8".parse().unwrap(),
+                                                        Some(Piece::new(Knight, Black)))));
+        assert_eq!(board.iter_from_perspective(Black).next(),
+                   Some(("h1".parse().unwrap(), Some(Piece::new(Knight, White)))));
+
+        assert_eq!(names(&mut board.iter_rank(U3::from_low_bits(2))), "a3,b3,c3,d3,e3,f3,g3,h3");
+        assert_eq!(names(&mut board.iter_file(U3::from_low_bits(7))), "h1,h2,h3,h4,h5,h6,h7,h8");
+        assert_eq!(board.iter_file(U3::from_low_bits(0)).filter_map(|(_, piece)| piece).count(), 1);
+    }
+
+    #[test]
+    fn board_display_modes() {
+        let board = Board::from_fen_string("r3k2r/pp3ppp/8/8/4P3/8/PP3PPP/R3K2R").unwrap();
+        let unicode = concat!(
+            "\n",
+            "8 ♜ ▓ ░ ▓ ♚ ▓ ░ ♜\n",
+            "7 ♟ ♟ ▓ ░ ▓ ♟ ♟ ♟\n",
+            "6 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "5 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "4 ░ ▓ ░ ▓ ♙ ▓ ░ ▓\n",
+            "3 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "2 ♙ ♙ ░ ▓ ░ ♙ ♙ ♙\n",
+            "1 ♖ ░ ▓ ░ ♔ ░ ▓ ♖\n",
+            "  a b c d e f g h"
+        );
+        assert_eq!(format!("{:#}", board), unicode);
+        assert_eq!(format!("{:#}", board.display_from(White)), unicode);
+
+        let flipped = concat!(
+            "\n",
+            "1 R     K       R\n",
+            "2 P P P       P P\n",
+            "3                \n",
+            "4       P        \n",
+            "5                \n",
+            "6                \n",
+            "7 p p p       p p\n",
+            "8 r     k       r\n",
+            "  h g f e d c b a"
+        );
+        assert_eq!(format!("{}", board.display_from(Black)), flipped);
+
+        let flipped_unicode = concat!(
+            "\n",
+            "1 ♖ ▓ ░ ♔ ░ ▓ ░ ♖\n",
+            "2 ♙ ♙ ♙ ░ ▓ ░ ♙ ♙\n",
+            "3 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "4 ▓ ░ ▓ ♙ ▓ ░ ▓ ░\n",
+            "5 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "6 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "7 ♟ ♟ ♟ ▓ ░ ▓ ♟ ♟\n",
+            "8 ♜ ░ ▓ ♚ ▓ ░ ▓ ♜\n",
+            "  h g f e d c b a"
+        );
+        assert_eq!(format!("{:#}", board.display_from(Black)), flipped_unicode);
+    }
+
     #[test]
     fn board_iter() {
         let board = Board::default_board();
@@ -284,4 +1001,67 @@ mod tests {
             Some(Board::default_board())
         );
     }
+
+    #[test]
+    fn board_to_fen() {
+        assert_eq!(Board::empty_board().to_fen_string(), "8/8/8/8/8/8/8/8");
+        assert_eq!(Board::default_board().to_fen_string(),
+                   "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+        let fen = "r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR";
+        assert_eq!(Board::from_fen_string(fen).unwrap().to_fen_string(), fen);
+    }
+
+    #[test]
+    fn const_from_fen() {
+        const DEFAULT: Board = Board::const_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+        static EMPTY: Board = Board::const_from_fen("8/8/8/8/8/8/8/8");
+        assert_eq!(DEFAULT, Board::default_board());
+        assert_eq!(EMPTY, Board::empty_board());
+        for (board, fen) in [
+            (Board::KIWIPETE, "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R"),
+            (Board::PERFT_POSITION_3, "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8"),
+            (Board::PERFT_POSITION_4, "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1"),
+        ] {
+            assert_eq!(Some(board.clone()), Board::from_fen_string(fen));
+            assert_eq!(board.to_fen_string(), fen);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid character")]
+    fn const_from_fen_invalid() {
+        Board::const_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX");
+    }
+
+    #[test]
+    fn chess960_start() {
+        assert_eq!(Board::chess960_start(518), Some(Board::default_board()));
+        assert_eq!(Board::chess960_start(960), None);
+        let back_rank = |index| {
+            Board::chess960_start(index).unwrap().to_fen_string().split('/').next_back()
+                .unwrap().to_string()
+        };
+        assert_eq!(back_rank(0), "BBQNNRKR");
+        assert_eq!(back_rank(1), "BQNBNRKR");
+        assert_eq!(back_rank(959), "RKRNNQBB");
+
+        let mut back_ranks = std::collections::HashSet::new();
+        for index in 0..Board::CHESS960_POSITIONS {
+            let board = Board::chess960_start(index).unwrap();
+            let fen = board.to_fen_string();
+            let (black, white) = (fen.split('/').next().unwrap(), back_rank(index));
+            assert_eq!(black, white.to_lowercase());
+            assert!(fen.contains("/pppppppp/8/8/8/8/PPPPPPPP/"));
+            let bishops: Vec<usize> = white.match_indices('B').map(|(file, _)| file).collect();
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2);
+            let rooks: Vec<usize> = white.match_indices('R').map(|(file, _)| file).collect();
+            let king = white.find('K').unwrap();
+            assert!(rooks[0] < king && king < rooks[1]);
+            assert_eq!(white.matches('N').count(), 2);
+            assert_eq!(white.matches('Q').count(), 1);
+            back_ranks.insert(white);
+        }
+        assert_eq!(back_ranks.len(), 960);
+    }
 }
@@ -1,16 +1,21 @@
-use std::fmt::{Display, Formatter};
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use thiserror::Error;
+use crate::moves::util::BoardBitmap;
 use crate::util::U3;
 
-/// Representation of the position of a chess board square.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// Representation of the position of a chess board square. Positions are ordered by their
+/// [index](BoardPosition::index), that is, first by file and then by rank.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct BoardPosition {
     pub file: U3,
     pub rank: U3
 }
 
-impl Into<(u8, u8)> for BoardPosition {
-    fn into(self) -> (u8, u8) {
-        (self.file.into(), self.rank.into())
+impl From<BoardPosition> for (u8, u8) {
+    fn from(value: BoardPosition) -> Self {
+        (value.file.into(), value.rank.into())
     }
 }
 
@@ -21,31 +26,71 @@ impl TryFrom<(u8, u8)> for BoardPosition {
     }
 }
 
+/// An error caused by attempting to parse an invalid square name, such as `"e9"`, as a
+/// [BoardPosition].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PositionParseError {
+    /// The string did not consist of exactly two characters.
+    #[error("expected a file letter followed by a rank digit")]
+    WrongLength,
+    /// The first character was not a file letter `a`-`h` (or `A`-`H`).
+    #[error("invalid file `{0}`: expected a letter a-h")]
+    InvalidFile(char),
+    /// The second character was not a rank digit `1`-`8`.
+    #[error("invalid rank `{0}`: expected a digit 1-8")]
+    InvalidRank(char),
+}
+
+impl TryFrom<(char, char)> for BoardPosition {
+    type Error = PositionParseError;
+    fn try_from(value: (char, char)) -> Result<Self, Self::Error> {
+        let file = match value.0 {
+            'a' | 'A' => 0,
+            'b' | 'B' => 1,
+            'c' | 'C' => 2,
+            'd' | 'D' => 3,
+            'e' | 'E' => 4,
+            'f' | 'F' => 5,
+            'g' | 'G' => 6,
+            'h' | 'H' => 7,
+            ch => return Err(PositionParseError::InvalidFile(ch)),
+        };
+        let rank = match value.1 {
+            '1'..='8' => value.1 as u8 - b'1',
+            ch => return Err(PositionParseError::InvalidRank(ch)),
+        };
+        Ok(BoardPosition { file: U3::from_low_bits(file), rank: U3::from_low_bits(rank) })
+    }
+}
+
+/// Parses a square name such as `"e4"`. The file letter may be either lowercase or uppercase.
+impl FromStr for BoardPosition {
+    type Err = PositionParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => BoardPosition::try_from((file, rank)),
+            _ => Err(PositionParseError::WrongLength),
+        }
+    }
+}
+
 impl TryFrom<&str> for BoardPosition {
-    type Error = ();
+    type Error = PositionParseError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let value = value.as_bytes();
-        if value.len() != 2 { return Err(()); }
-        let file = match value[0] {
-            b'a' | b'A' => 0,
-            b'b' | b'B' => 1,
-            b'c' | b'C' => 2,
-            b'd' | b'D' => 3,
-            b'e' | b'E' => 4,
-            b'f' | b'F' => 5,
-            b'g' | b'G' => 6,
-            b'h' | b'H' => 7,
-            _ => return Err(()),
-        };
-        let rank = if let Some(rank) = (value[1] as char).to_digit(10)
-            { rank } else { return Err(()); };
-        let rank = if rank > 0 { rank - 1 } else { return Err(()); };
-        BoardPosition::try_from((file, rank as u8))
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for BoardPosition {
+    type Error = PositionParseError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
 impl Display for BoardPosition {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let files = b"abcdefgh";
         let ranks = b"12345678";
         let file = files[self.file.get() as usize] as char;
@@ -54,16 +99,304 @@ impl Display for BoardPosition {
     }
 }
 
+/// The color of a square on the chess board: Light or dark
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
+/// One of the eight directions a king may step in. North is towards rank 8, and east is towards
+/// file h.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All eight directions, clockwise starting from north.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The four directions along ranks and files, in which a rook moves.
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The four diagonal directions, in which a bishop moves.
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+
+    /// returns: The `(file, rank)` offset of a single step in this direction.
+    pub const fn offset(self) -> (i8, i8) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, -1),
+            Direction::South => (0, -1),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, 1),
+        }
+    }
+
+    /// returns: The direction pointing the opposite way.
+    pub const fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::East => Direction::West,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::South => Direction::North,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::West => Direction::East,
+            Direction::NorthWest => Direction::SouthEast,
+        }
+    }
+
+    /// returns: Whether the direction is along a rank or a file.
+    pub const fn is_orthogonal(self) -> bool {
+        matches!(self, Direction::North | Direction::East | Direction::South | Direction::West)
+    }
+
+    /// returns: Whether the direction is along a diagonal.
+    pub const fn is_diagonal(self) -> bool {
+        !self.is_orthogonal()
+    }
+}
+
+/// One of the eight jumps a knight may make, named after the long leg of the jump followed by
+/// the short one. For example, `NorthNorthEast` is two squares north and one square east.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KnightDirection {
+    NorthNorthEast,
+    EastNorthEast,
+    EastSouthEast,
+    SouthSouthEast,
+    SouthSouthWest,
+    WestSouthWest,
+    WestNorthWest,
+    NorthNorthWest,
+}
+
+impl KnightDirection {
+    /// All eight knight jumps, clockwise starting from north-north-east.
+    pub const ALL: [KnightDirection; 8] = [
+        KnightDirection::NorthNorthEast,
+        KnightDirection::EastNorthEast,
+        KnightDirection::EastSouthEast,
+        KnightDirection::SouthSouthEast,
+        KnightDirection::SouthSouthWest,
+        KnightDirection::WestSouthWest,
+        KnightDirection::WestNorthWest,
+        KnightDirection::NorthNorthWest,
+    ];
+
+    /// returns: The `(file, rank)` offset of the jump.
+    pub const fn offset(self) -> (i8, i8) {
+        match self {
+            KnightDirection::NorthNorthEast => (1, 2),
+            KnightDirection::EastNorthEast => (2, 1),
+            KnightDirection::EastSouthEast => (2, -1),
+            KnightDirection::SouthSouthEast => (1, -2),
+            KnightDirection::SouthSouthWest => (-1, -2),
+            KnightDirection::WestSouthWest => (-2, -1),
+            KnightDirection::WestNorthWest => (-2, 1),
+            KnightDirection::NorthNorthWest => (-1, 2),
+        }
+    }
+
+    /// returns: The jump pointing the opposite way.
+    pub const fn opposite(self) -> KnightDirection {
+        match self {
+            KnightDirection::NorthNorthEast => KnightDirection::SouthSouthWest,
+            KnightDirection::EastNorthEast => KnightDirection::WestSouthWest,
+            KnightDirection::EastSouthEast => KnightDirection::WestNorthWest,
+            KnightDirection::SouthSouthEast => KnightDirection::NorthNorthWest,
+            KnightDirection::SouthSouthWest => KnightDirection::NorthNorthEast,
+            KnightDirection::WestSouthWest => KnightDirection::EastNorthEast,
+            KnightDirection::WestNorthWest => KnightDirection::EastSouthEast,
+            KnightDirection::NorthNorthWest => KnightDirection::SouthSouthEast,
+        }
+    }
+}
+
 impl BoardPosition {
-    pub(crate) fn add(&self, offset: (i8, i8)) -> Option<BoardPosition> {
-        let file = self.file.get() as i8 + offset.0;
-        let rank = self.rank.get() as i8 + offset.1;
-        if file < 0 || rank < 0 {
-            None
+    /// All 64 squares of the board in index order (see [index](BoardPosition::index)).
+    pub const ALL_SQUARES: [BoardPosition; 64] = {
+        let a1 = BoardPosition { file: U3::new(0).unwrap(), rank: U3::new(0).unwrap() };
+        let mut squares = [a1; 64];
+        let mut index = 0;
+        while index < 64 {
+            squares[index as usize] = BoardPosition::from_index(index).unwrap();
+            index += 1;
+        }
+        squares
+    };
+
+    /// returns: `Some(BoardPosition)` corresponding to the given square index if it is in the range
+    /// `0..64`, otherwise `None`. See [index](BoardPosition::index).
+    pub const fn from_index(index: u8) -> Option<BoardPosition> {
+        if index >= 64 {
+            return None;
+        }
+        match (U3::new(index >> 3), U3::new(index & 0b0000_0111)) {
+            (Some(file), Some(rank)) => Some(BoardPosition { file, rank }),
+            _ => None,
+        }
+    }
+
+    /// returns: The index of the square in the range `0..64`, which is `file * 8 + rank`. This is
+    /// the same layout as used by [U6](crate::util::U6) and
+    /// [BoardBitmap](crate::moves::util::BoardBitmap), so a1 = 0, a2 = 1, ..., a8 = 7, b1 = 8, ...,
+    /// h8 = 63.
+    pub const fn index(&self) -> u8 {
+        (self.file.get() << 3) | self.rank.get()
+    }
+
+    /// returns: An iterator over all 64 squares of the board in index order (a1, a2, ..., h8). See
+    /// [index](BoardPosition::index).
+    pub fn all() -> impl Iterator<Item=BoardPosition> {
+        BoardPosition::ALL_SQUARES.into_iter()
+    }
+
+    /// returns: The color of the square. a1 is a dark square, and the colors alternate along each
+    /// rank and file.
+    pub const fn square_color(&self) -> SquareColor {
+        if (self.file.get() + self.rank.get()).is_multiple_of(2) {
+            SquareColor::Dark
         } else {
-            BoardPosition::try_from((file as u8, rank as u8)).ok()
+            SquareColor::Light
         }
     }
+
+    /// returns: `Some(BoardPosition)` of the adjacent square in the given direction, or `None` if
+    /// the step would leave the board.
+    pub fn step(&self, direction: Direction) -> Option<BoardPosition> {
+        self.add(direction.offset())
+    }
+
+    /// returns: `Some(BoardPosition)` of the square a knight jump away in the given direction, or
+    /// `None` if the jump would leave the board.
+    pub fn knight_step(&self, direction: KnightDirection) -> Option<BoardPosition> {
+        self.add(direction.offset())
+    }
+
+    /// returns: An iterator over the squares in the given direction, starting with the adjacent
+    /// square (the square itself is not included) and ending at the edge of the board.
+    pub fn ray(&self, direction: Direction) -> impl Iterator<Item=BoardPosition> + use<> {
+        core::iter::successors(self.step(direction), move |pos| pos.step(direction))
+    }
+
+    /// returns: `Some(Direction)` if the other square lies on the same rank, file or diagonal as
+    /// this square, pointing from this square towards the other square. Returns `None` if the
+    /// squares are not aligned, or if they are the same square.
+    pub fn direction_to(&self, other: BoardPosition) -> Option<Direction> {
+        let file_diff = other.file.get() as i8 - self.file.get() as i8;
+        let rank_diff = other.rank.get() as i8 - self.rank.get() as i8;
+        if file_diff != 0 && rank_diff != 0 && file_diff.abs() != rank_diff.abs() {
+            return None;
+        }
+        Direction::ALL.into_iter()
+            .find(|direction| direction.offset() == (file_diff.signum(), rank_diff.signum()))
+    }
+
+    /// returns: The number of moves a king needs to go from this square to the other square, that
+    /// is, the largest of the file and rank distances.
+    pub fn chebyshev_distance(&self, other: BoardPosition) -> u8 {
+        let file_distance = self.file.get().abs_diff(other.file.get());
+        let rank_distance = self.rank.get().abs_diff(other.rank.get());
+        file_distance.max(rank_distance)
+    }
+
+    /// returns: The sum of the file and rank distances between this square and the other square.
+    pub fn manhattan_distance(&self, other: BoardPosition) -> u8 {
+        let file_distance = self.file.get().abs_diff(other.file.get());
+        let rank_distance = self.rank.get().abs_diff(other.rank.get());
+        file_distance + rank_distance
+    }
+
+    /// returns: The minimum number of moves a knight needs to go from this square to the other
+    /// square on an otherwise empty board. Note that this takes the edges of the board into
+    /// account, so for example the distance from a1 to b2 is 4, not 2.
+    pub fn knight_distance(&self, other: BoardPosition) -> u8 {
+        // breadth-first search over the knight jumps
+        let mut distances = [u8::MAX; 64];
+        let mut queue = alloc::collections::VecDeque::from([*self]);
+        distances[self.index() as usize] = 0;
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[pos.index() as usize];
+            if pos == other {
+                break;
+            }
+            for direction in KnightDirection::ALL {
+                if let Some(next) = pos.knight_step(direction)
+                    && distances[next.index() as usize] == u8::MAX
+                {
+                    distances[next.index() as usize] = distance + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        // every square is reachable by a knight
+        distances[other.index() as usize]
+    }
+
+    pub(crate) fn add(&self, offset: (i8, i8)) -> Option<BoardPosition> {
+        Some(BoardPosition {
+            file: self.file.checked_add_signed(offset.0)?,
+            rank: self.rank.checked_add_signed(offset.1)?,
+        })
+    }
+}
+
+/// returns: A [BoardBitmap] of the squares strictly between `a` and `b` if they lie on the same
+/// rank, file or diagonal, otherwise an empty bitmap. Neither `a` nor `b` is included.
+pub fn between(a: BoardPosition, b: BoardPosition) -> BoardBitmap {
+    let mut bitmap = BoardBitmap::all_zeros();
+    if let Some(direction) = a.direction_to(b) {
+        a.ray(direction)
+            .take_while(|pos| *pos != b)
+            .for_each(|pos| bitmap.set(pos, true));
+    }
+    bitmap
+}
+
+/// returns: Whether the three squares all lie on a single rank, file or diagonal. For example, a
+/// piece on `b` is pinned by a slider on `a` against a king on `c` only if they are aligned (and
+/// `b` lies between the others).
+pub fn aligned(a: BoardPosition, b: BoardPosition, c: BoardPosition) -> bool {
+    let direction = match a.direction_to(b).or_else(|| a.direction_to(c)) {
+        Some(direction) => direction,
+        None => return a == b && a == c,
+    };
+    [b, c].into_iter().all(|pos| {
+        pos == a || a.direction_to(pos)
+            .is_some_and(|d| d == direction || d == direction.opposite())
+    })
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -104,10 +437,13 @@ impl<'a> Iterator for BoardLineIterator<'a> {
                 self.current_line_length = 0;
                 continue;
             }
-            let pos = match self.origin.add((
-                current_line.offset.0 * (self.current_line_length + 1) as i8,
-                current_line.offset.1 * (self.current_line_length + 1) as i8,
-            )) {
+            // lines leave the board long before the offset could overflow, but a line which
+            // somehow did not is treated as having left the board
+            let offset = i8::try_from(self.current_line_length + 1).ok().and_then(|step| {
+                Some((current_line.offset.0.checked_mul(step)?,
+                      current_line.offset.1.checked_mul(step)?))
+            });
+            let pos = match offset.and_then(|offset| self.origin.add(offset)) {
                 Some(pos) => pos,
                 None => {
                     // if already outside board, no other squares on this line can be inside the
@@ -145,7 +481,7 @@ impl<'a> BoardLineIterator<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::moves::util::BoardBitmap;
+    use crate::util::U6;
     use super::*;
 
     #[test]
@@ -184,4 +520,193 @@ mod tests {
         expected_bitset.set(BoardPosition::try_from((7, 4)).unwrap(), true);
         assert_eq!(bitset, expected_bitset, "Left:  {}\nRight: {}", bitset, expected_bitset);
     }
+
+    #[test]
+    fn board_pos_parse() {
+        assert_eq!("e4".parse(), Ok(BoardPosition::try_from((4, 3)).unwrap()));
+        assert_eq!("E4".parse(), Ok(BoardPosition::try_from((4, 3)).unwrap()));
+        assert_eq!("a1".parse(), Ok(BoardPosition::try_from((0, 0)).unwrap()));
+        assert_eq!("h8".parse(), Ok(BoardPosition::try_from((7, 7)).unwrap()));
+        assert_eq!(BoardPosition::try_from("c6"), Ok(BoardPosition::try_from((2, 5)).unwrap()));
+        assert_eq!(BoardPosition::try_from(String::from("c6")),
+                   Ok(BoardPosition::try_from((2, 5)).unwrap()));
+        assert_eq!(BoardPosition::try_from(('c', '6')),
+                   Ok(BoardPosition::try_from((2, 5)).unwrap()));
+
+        assert_eq!("e9".parse::<BoardPosition>(), Err(PositionParseError::InvalidRank('9')));
+        assert_eq!("e0".parse::<BoardPosition>(), Err(PositionParseError::InvalidRank('0')));
+        assert_eq!("i4".parse::<BoardPosition>(), Err(PositionParseError::InvalidFile('i')));
+        assert_eq!("e44".parse::<BoardPosition>(), Err(PositionParseError::WrongLength));
+        assert_eq!("".parse::<BoardPosition>(), Err(PositionParseError::WrongLength));
+        assert_eq!("e".parse::<BoardPosition>(), Err(PositionParseError::WrongLength));
+        assert_eq!("é4".parse::<BoardPosition>(), Err(PositionParseError::InvalidFile('é')));
+        assert_eq!("e٤".parse::<BoardPosition>(), Err(PositionParseError::InvalidRank('٤')));
+        assert_eq!("♔".parse::<BoardPosition>(), Err(PositionParseError::WrongLength));
+        assert_eq!("♔♔♔".parse::<BoardPosition>(), Err(PositionParseError::WrongLength));
+
+        for pos in ["a1", "b7", "h8", "d4"] {
+            assert_eq!(pos.parse::<BoardPosition>().unwrap().to_string(), pos);
+        }
+    }
+
+    #[test]
+    fn board_pos_index() {
+        for index in 0..64 {
+            let pos = BoardPosition::from_index(index).unwrap();
+            assert_eq!(pos.index(), index);
+            assert_eq!(U6::from(pos).get(), index);
+            assert_eq!(BoardPosition::from(U6::new(index).unwrap()), pos);
+        }
+        assert_eq!(BoardPosition::from_index(64), None);
+        assert_eq!(BoardPosition::from_index(255), None);
+        assert_eq!(BoardPosition::from_index(0), Some("a1".parse().unwrap()));
+        assert_eq!(BoardPosition::from_index(7), Some("a8".parse().unwrap()));
+        assert_eq!(BoardPosition::from_index(8), Some("b1".parse().unwrap()));
+        assert_eq!(BoardPosition::from_index(63), Some("h8".parse().unwrap()));
+
+        let all: Vec<BoardPosition> = BoardPosition::all().collect();
+        assert_eq!(all.len(), 64);
+        for (index, pos) in all.iter().enumerate() {
+            assert_eq!(pos.index() as usize, index);
+        }
+    }
+
+    #[test]
+    fn direction_step_and_ray() {
+        let corners: Vec<BoardPosition> = ["a1", "a8", "h1", "h8"].iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        for pos in corners {
+            for direction in Direction::ALL {
+                assert_eq!(pos.step(direction), pos.add(direction.offset()));
+                assert_eq!(direction.opposite().opposite(), direction);
+                let (file, rank) = direction.offset();
+                assert_eq!(direction.opposite().offset(), (-file, -rank));
+            }
+            for direction in KnightDirection::ALL {
+                assert_eq!(pos.knight_step(direction), pos.add(direction.offset()));
+                assert_eq!(direction.opposite().opposite(), direction);
+            }
+        }
+
+        let d4: BoardPosition = "d4".parse().unwrap();
+        let north: Vec<String> = d4.ray(Direction::North).map(|p| p.to_string()).collect();
+        assert_eq!(north, ["d5", "d6", "d7", "d8"]);
+        let south_west: Vec<String> = d4.ray(Direction::SouthWest).map(|p| p.to_string()).collect();
+        assert_eq!(south_west, ["c3", "b2", "a1"]);
+        let east: Vec<String> = d4.ray(Direction::East).map(|p| p.to_string()).collect();
+        assert_eq!(east, ["e4", "f4", "g4", "h4"]);
+        let a1: BoardPosition = "a1".parse().unwrap();
+        assert_eq!(a1.ray(Direction::South).count(), 0);
+        assert_eq!(a1.ray(Direction::West).count(), 0);
+        assert_eq!(a1.ray(Direction::NorthEast).count(), 7);
+
+        let mut bitset = BoardBitmap::all_zeros();
+        for direction in Direction::ALL {
+            d4.ray(direction).for_each(|p| bitset.set(p, true));
+        }
+        let mut queen = BoardBitmap::all_zeros();
+        BoardLineIterator::new(d4, crate::moves::move_patterns::QUEEN_BOARD_LINES)
+            .for_each(|p| queen.set(p.position, true));
+        assert_eq!(bitset, queen);
+    }
+
+    #[test]
+    fn between_and_aligned() {
+        let pos = |s: &str| s.parse::<BoardPosition>().unwrap();
+        let squares = |bitmap: BoardBitmap| format!("{:#}", bitmap);
+
+        assert_eq!(squares(between(pos("a1"), pos("a8"))), "a2,a3,a4,a5,a6,a7");
+        assert_eq!(between(pos("a8"), pos("a1")), between(pos("a1"), pos("a8")));
+        assert_eq!(squares(between(pos("a1"), pos("h1"))), "b1,c1,d1,e1,f1,g1");
+        assert_eq!(squares(between(pos("a1"), pos("h8"))), "b2,c3,d4,e5,f6,g7");
+        assert_eq!(squares(between(pos("h2"), pos("e5"))), "f4,g3");
+        assert!(between(pos("a1"), pos("b3")).is_all_zeros());
+        assert!(between(pos("a1"), pos("b2")).is_all_zeros());
+        assert!(between(pos("a1"), pos("a2")).is_all_zeros());
+        assert!(between(pos("d4"), pos("d4")).is_all_zeros());
+
+        assert_eq!(pos("e1").direction_to(pos("e8")), Some(Direction::North));
+        assert_eq!(pos("e1").direction_to(pos("a5")), Some(Direction::NorthWest));
+        assert_eq!(pos("e1").direction_to(pos("f3")), None);
+        assert_eq!(pos("e1").direction_to(pos("e1")), None);
+
+        // bishop on b5 pins knight on c6 against king on e8
+        assert!(aligned(pos("b5"), pos("c6"), pos("e8")));
+        assert!(aligned(pos("e8"), pos("b5"), pos("c6")));
+        assert!(!aligned(pos("b5"), pos("c6"), pos("e7")));
+        assert!(aligned(pos("a1"), pos("a4"), pos("a8")));
+        assert!(aligned(pos("a4"), pos("a1"), pos("a8")));
+        assert!(!aligned(pos("a1"), pos("b2"), pos("b3")));
+        assert!(!aligned(pos("a1"), pos("c3"), pos("c1")));
+        assert!(aligned(pos("d4"), pos("d4"), pos("g7")));
+        assert!(aligned(pos("d4"), pos("d4"), pos("d4")));
+        assert!(!aligned(pos("d4"), pos("d4"), pos("e6")));
+    }
+
+    #[test]
+    fn distances() {
+        let pos = |s: &str| s.parse::<BoardPosition>().unwrap();
+        // (a, b, chebyshev, manhattan, knight)
+        let table = [
+            ("a1", "a1", 0, 0, 0),
+            ("a1", "h8", 7, 14, 6),
+            ("a1", "b2", 1, 2, 4),
+            ("h8", "g7", 1, 2, 4),
+            ("a8", "b7", 1, 2, 4),
+            ("h1", "g2", 1, 2, 4),
+            ("b2", "c3", 1, 2, 2),
+            ("a1", "b3", 2, 3, 1),
+            ("a1", "a2", 1, 1, 3),
+            ("a1", "c3", 2, 4, 4),
+            ("d4", "d5", 1, 1, 3),
+            ("d4", "e5", 1, 2, 2),
+            ("d4", "d6", 2, 2, 2),
+            ("e1", "e8", 7, 7, 5),
+        ];
+        for (a, b, chebyshev, manhattan, knight) in table {
+            for (a, b) in [(pos(a), pos(b)), (pos(b), pos(a))] {
+                assert_eq!(a.chebyshev_distance(b), chebyshev, "chebyshev {} {}", a, b);
+                assert_eq!(a.manhattan_distance(b), manhattan, "manhattan {} {}", a, b);
+                assert_eq!(a.knight_distance(b), knight, "knight {} {}", a, b);
+            }
+        }
+
+        for a in BoardPosition::all() {
+            for b in BoardPosition::all() {
+                assert_eq!(a.knight_distance(b), b.knight_distance(a));
+                assert_eq!(a.knight_distance(b) == 1,
+                           KnightDirection::ALL.iter().any(|d| a.knight_step(*d) == Some(b)));
+            }
+        }
+    }
+
+    #[test]
+    fn board_pos_ordering() {
+        let squares: std::collections::BTreeSet<BoardPosition> = ["h8", "a2", "b1", "a1", "e4"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let squares: Vec<String> = squares.iter().map(|p| p.to_string()).collect();
+        assert_eq!(squares, ["a1", "a2", "b1", "e4", "h8"]);
+
+        for a in BoardPosition::all() {
+            for b in BoardPosition::all() {
+                assert_eq!(a.cmp(&b), a.index().cmp(&b.index()));
+            }
+        }
+    }
+
+    #[test]
+    fn square_color() {
+        let pos = |s: &str| s.parse::<BoardPosition>().unwrap();
+        assert_eq!(pos("a1").square_color(), SquareColor::Dark);
+        assert_eq!(pos("h1").square_color(), SquareColor::Light);
+        assert_eq!(pos("a8").square_color(), SquareColor::Light);
+        assert_eq!(pos("h8").square_color(), SquareColor::Dark);
+        assert_eq!(pos("d1").square_color(), SquareColor::Light);
+        assert_eq!(pos("e1").square_color(), SquareColor::Dark);
+        assert_ne!(pos("c1").square_color(), pos("f1").square_color());
+        assert_ne!(pos("c8").square_color(), pos("f8").square_color());
+    }
 }
@@ -0,0 +1,215 @@
+use alloc::vec::Vec;
+use thiserror::Error;
+use crate::board::Board;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::util::U3;
+
+/// An error caused by a text diagram which does not describe a board. See [Board::from_diagram].
+/// Line and column numbers are counted from 1.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiagramError {
+    /// A square is not a piece letter, a Unicode chess symbol or an empty square.
+    #[error("invalid square on line {line}, column {column}")]
+    InvalidSquare { line: usize, column: usize },
+    /// A rank does not consist of eight squares.
+    #[error("rank on line {0} does not have eight squares")]
+    InvalidRank(usize),
+    /// The rank label of a rank does not match its position in the diagram.
+    #[error("unexpected rank label on line {0}")]
+    WrongRankLabel(usize),
+    /// The diagram does not have eight ranks, with the line the ninth rank is on if there are
+    /// too many, or the line after the end of the diagram if there are too few.
+    #[error("expected eight ranks, found {ranks} (line {line})")]
+    WrongRankCount { line: usize, ranks: usize },
+}
+
+/// returns: The contents of the square given by a character, where `Some(None)` is an empty
+///          square, or `None` if the character is not a square.
+fn parse_square(ch: char) -> Option<Option<Piece>> {
+    if matches!(ch, '.' | '░' | '▓') || ch.is_whitespace() {
+        return Some(None);
+    }
+    if let Some(piece) = Piece::from_char(ch) {
+        return Some(Some(piece));
+    }
+    for player in [PlayerColor::White, PlayerColor::Black] {
+        for piece_type in PieceType::ALL {
+            let piece = Piece::new(piece_type, player);
+            if piece.get_unicode_char().starts_with(ch) {
+                return Some(Some(piece));
+            }
+        }
+    }
+    None
+}
+
+/// returns: Whether the line is a row of file labels, such as `"  a b c d e f g h"`.
+fn is_file_labels(line: &str) -> bool {
+    line.chars().filter(|ch| !ch.is_whitespace()).eq("abcdefgh".chars())
+}
+
+/// returns: The squares of a rank from its line in a diagram, from the a-file to the h-file.
+fn parse_rank(line: &str, line_number: usize, rank: usize)
+              -> Result<[Option<Piece>; 8], DiagramError>
+{
+    let mut content = line.trim_start();
+    let mut column = line.chars().count() - content.chars().count() + 1;
+    // a rank label is followed by a space
+    if let Some(label) = content.chars().next().and_then(|ch| ch.to_digit(10)) {
+        if label as usize != 8 - rank {
+            return Err(DiagramError::WrongRankLabel(line_number));
+        }
+        column += 1;
+        content = &content[1..];
+        if let Some(rest) = content.strip_prefix(' ') {
+            column += 1;
+            content = rest;
+        }
+    }
+    let chars: Vec<char> = content.trim_end().chars().collect();
+    let mut squares = [None; 8];
+    if chars.len() == 8 && !chars.iter().any(|ch| ch.is_whitespace()) {
+        // a square per character, such as "r...k..r"
+        for (i, ch) in chars.into_iter().enumerate() {
+            squares[i] = parse_square(ch).ok_or(DiagramError::InvalidSquare {
+                line: line_number,
+                column: column + i,
+            })?;
+        }
+        return Ok(squares);
+    }
+    // squares separated by spaces, such as "r . . . k . . r"
+    if chars.len() > 15 {
+        return Err(DiagramError::InvalidRank(line_number));
+    }
+    for (i, ch) in chars.into_iter().enumerate() {
+        if i % 2 == 1 {
+            if !ch.is_whitespace() {
+                return Err(DiagramError::InvalidRank(line_number));
+            }
+            continue;
+        }
+        squares[i / 2] = parse_square(ch).ok_or(DiagramError::InvalidSquare {
+            line: line_number,
+            column: column + i,
+        })?;
+    }
+    Ok(squares)
+}
+
+impl Board {
+    /// Parses a board from a text diagram with the ranks from 8 to 1, one rank per line. The
+    /// following formats are accepted, and detected per line:
+    /// - The formats of the [Display] implementation of [Board] from white's perspective, with a
+    ///   rank label before each rank and a row of file labels at the bottom.
+    /// - A grid of FEN piece letters separated by spaces, with `.` or a space for empty squares,
+    ///   such as `"r . . . k . . r"`.
+    /// - Eight FEN piece letters or `.` without spaces, such as `"r...k..r"`.
+    ///
+    /// Unicode chess symbols such as `♜` may be used in place of the piece letters, and the
+    /// shading characters `░` and `▓` in place of `.` for empty squares. Rank labels are
+    /// optional, and blank lines and rows of file labels are ignored. Lines may be indented, so
+    /// in a grid without rank labels, an empty square at the start of a rank or a rank without
+    /// pieces has to be written as `.`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::Board;
+    ///
+    /// let board = Board::from_diagram("
+    ///     . . . . k . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . P . . .
+    ///     . . . . K . . .
+    /// ").unwrap();
+    /// assert_eq!(Some(board), Board::from_fen_string("4k3/8/8/8/8/8/4P3/4K3"));
+    /// ```
+    ///
+    /// returns: The board, or the [DiagramError] describing the first problem found.
+    pub fn from_diagram(diagram: &str) -> Result<Board, DiagramError> {
+        let mut board = Board::empty_board();
+        let mut ranks = 0;
+        let mut last_line = 0;
+        for (index, line) in diagram.lines().enumerate() {
+            let line_number = index + 1;
+            last_line = line_number;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || is_file_labels(line) {
+                continue;
+            }
+            if ranks == 8 {
+                return Err(DiagramError::WrongRankCount { line: line_number, ranks: 9 });
+            }
+            let squares = parse_rank(line, line_number, ranks)?;
+            // the ranks are listed from 8 down to 1, and fewer than 8 have been parsed
+            let rank = U3::from_low_bits(7 - ranks as u8);
+            for (pos, piece) in super::rank_squares(rank).zip(squares) {
+                board.set_piece(pos, piece);
+            }
+            ranks += 1;
+        }
+        if ranks < 8 {
+            return Err(DiagramError::WrongRankCount { line: last_line + 1, ranks });
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use super::*;
+
+    #[test]
+    fn display_round_trip() {
+        let fens = ["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+                    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                    "8/8/8/8/8/8/8/8",
+                    "7k/8/8/8/8/8/8/K7"];
+        for fen in fens {
+            let board = Board::from_fen_string(fen).unwrap();
+            assert_eq!(Board::from_diagram(&board.to_string()), Ok(board.clone()), "{}", fen);
+            assert_eq!(Board::from_diagram(&format!("{:#}", board)), Ok(board), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn grids() {
+        let expected = Board::from_fen_string("r3k2r/8/8/8/8/8/4P3/R3K2R").unwrap();
+        let dotted = "r . . . k . . r\n. . . . . . . .\n. . . . . . . .\n. . . . . . . .\n\
+                      . . . . . . . .\n. . . . . . . .\n. . . . P . . .\nR . . . K . . R\n";
+        assert_eq!(Board::from_diagram(dotted), Ok(expected.clone()));
+        let compact = "r...k..r\n........\n........\n........\n........\n........\n....P...\n\
+                       R...K..R";
+        assert_eq!(Board::from_diagram(compact), Ok(expected.clone()));
+        let unicode = "♜ . . . ♚ . . ♜\n. . . . . . . .\n. . . . . . . .\n. . . . . . . .\n\
+                       . . . . . . . .\n. . . . . . . .\n. . . . ♙ . . .\n♖ . . . ♔ . . ♖\n\
+                       a b c d e f g h";
+        assert_eq!(Board::from_diagram(unicode), Ok(expected.clone()));
+        let spaced = "8 r       k     r\n7\n6\n5\n4\n3\n2         P\n1 R       K     R\n";
+        assert_eq!(Board::from_diagram(spaced), Ok(expected));
+    }
+
+    #[test]
+    fn invalid_diagrams() {
+        let seven_ranks = "r . . . k . . r\n. . . . . . . .\n. . . . . . . .\n. . . . . . . .\n\
+                           . . . . . . . .\n. . . . P . . .\nR . . . K . . R\n\na b c d e f g h";
+        assert_eq!(Board::from_diagram(seven_ranks),
+                   Err(DiagramError::WrongRankCount { line: 10, ranks: 7 }));
+        let nine_ranks = "........\n".repeat(9);
+        assert_eq!(Board::from_diagram(&nine_ranks),
+                   Err(DiagramError::WrongRankCount { line: 9, ranks: 9 }));
+        let invalid_piece = "........\n".repeat(3) + "...x....\n" + &"........\n".repeat(4);
+        assert_eq!(Board::from_diagram(&invalid_piece),
+                   Err(DiagramError::InvalidSquare { line: 4, column: 4 }));
+        let long_rank = "........\n".repeat(7) + ". . . . . . . . .";
+        assert_eq!(Board::from_diagram(&long_rank), Err(DiagramError::InvalidRank(8)));
+        let wrong_label = Board::default_board().to_string().replacen("7 ", "6 ", 1);
+        assert_eq!(Board::from_diagram(&wrong_label), Err(DiagramError::WrongRankLabel(3)));
+    }
+}
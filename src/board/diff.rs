@@ -0,0 +1,168 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::Piece;
+
+/// A square whose contents differ between two boards. See [Board::diff].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SquareDiff {
+    /// The square.
+    pub pos: BoardPosition,
+    /// The contents of the square on the first board.
+    pub before: Option<Piece>,
+    /// The contents of the square on the second board.
+    pub after: Option<Piece>,
+}
+
+impl Board {
+    /// Compares the board with another board square by square.
+    ///
+    /// # Arguments
+    ///
+    /// * `other`: The board to compare with.
+    ///
+    /// returns: A [SquareDiff] for every square whose contents differ between the boards, in
+    ///          square index order (see [BoardPosition::index]), where `before` is the piece on
+    ///          this board and `after` the piece on `other`. Empty if the boards are equal.
+    pub fn diff(&self, other: &Board) -> Vec<SquareDiff> {
+        BoardPosition::all()
+            .map(|pos| SquareDiff { pos, before: self.get_piece(pos), after: other.get_piece(pos) })
+            .filter(|diff| diff.before != diff.after)
+            .collect()
+    }
+
+    /// Replays a list of square changes, such as one made by [diff](Board::diff), by setting each
+    /// square to its `after` contents. The `before` contents are not checked, so applying the
+    /// diff of two boards to the first board always gives the second board.
+    ///
+    /// # Arguments
+    ///
+    /// * `diffs`: The changes to apply, in order.
+    pub fn apply_diff(&mut self, diffs: &[SquareDiff]) {
+        for diff in diffs {
+            self.set_piece(diff.pos, diff.after);
+        }
+    }
+}
+
+/// Describes how two boards differ, as printed by [assert_board_eq!](crate::assert_board_eq):
+/// the diagrams of both boards side by side, followed by a line for every differing square in
+/// square index order, such as `"e4: left P, right empty"`.
+///
+/// # Arguments
+///
+/// * `left`: The first board, shown on the left.
+/// * `right`: The second board, shown on the right.
+///
+/// returns: `Some(String)` of the report, or `None` if the boards are equal.
+pub fn board_diff_report(left: &Board, right: &Board) -> Option<String> {
+    let diffs = left.diff(right);
+    if diffs.is_empty() {
+        return None;
+    }
+    let describe = |piece: Option<Piece>| {
+        piece.map_or("empty".to_string(), |piece| piece.get_char().to_string())
+    };
+    let mut report = format!("boards differ on {} squares\n{:<21}right:", diffs.len(), "left:");
+    let left_diagram = left.to_string();
+    let right_diagram = right.to_string();
+    // the diagrams start with a line break
+    for (left_line, right_line) in left_diagram.lines().zip(right_diagram.lines()).skip(1) {
+        let _ = write!(report, "\n{:<21}{}", left_line, right_line.trim_end());
+    }
+    for diff in diffs {
+        let _ = write!(report, "\n{}: left {}, right {}", diff.pos, describe(diff.before),
+                       describe(diff.after));
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::piece::{PieceType, PlayerColor};
+    use crate::chess::ChessGame;
+    use super::*;
+
+    fn pos(pos: &str) -> BoardPosition {
+        BoardPosition::try_from(pos).unwrap()
+    }
+
+    #[test]
+    fn diff_move() {
+        let board = Board::default_board();
+        assert!(board.diff(&board).is_empty());
+        let mut moved = board.clone();
+        moved.set_piece(pos("e2"), None);
+        moved.set_piece(pos("e4"), Some(Piece::new(PieceType::Pawn, PlayerColor::White)));
+        let pawn = Some(Piece::new(PieceType::Pawn, PlayerColor::White));
+        assert_eq!(board.diff(&moved), [
+            SquareDiff { pos: pos("e2"), before: pawn, after: None },
+            SquareDiff { pos: pos("e4"), before: None, after: pawn },
+        ]);
+        assert_eq!(board_diff_report(&board, &board), None);
+    }
+
+    /// returns: The diff of the board before and after the last of the moves.
+    fn last_move_diff(moves: &[&str]) -> Vec<SquareDiff> {
+        let mut game = ChessGame::new(Board::default_board());
+        let (last, earlier) = moves.split_last().unwrap();
+        for chess_move in earlier {
+            game.try_move_str(chess_move).unwrap();
+        }
+        let before = game.board().clone();
+        game.try_move_str(last).unwrap();
+        let diff = before.diff(game.board());
+        let mut replayed = before.clone();
+        replayed.apply_diff(&diff);
+        assert_eq!(&replayed, game.board());
+        diff
+    }
+
+    #[test]
+    fn move_diffs() {
+        assert_eq!(last_move_diff(&["Nf3"]).len(), 2);
+        assert_eq!(last_move_diff(&["e4", "d5", "exd5"]).len(), 2);
+        let castling = last_move_diff(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"]);
+        let squares: Vec<String> = castling.iter().map(|diff| diff.pos.to_string()).collect();
+        assert_eq!(squares, ["e1", "f1", "g1", "h1"]);
+        let en_passant = last_move_diff(&["e4", "a6", "e5", "d5", "exd6"]);
+        let pawn = Some(Piece::new(PieceType::Pawn, PlayerColor::White));
+        let black_pawn = Some(Piece::new(PieceType::Pawn, PlayerColor::Black));
+        assert_eq!(en_passant, [
+            SquareDiff { pos: pos("d5"), before: black_pawn, after: None },
+            SquareDiff { pos: pos("d6"), before: None, after: pawn },
+            SquareDiff { pos: pos("e5"), before: pawn, after: None },
+        ]);
+
+        // the diff back to the original board undoes the changes
+        let board = Board::default_board();
+        let mut moved = board.clone();
+        moved.apply_diff(&[SquareDiff { pos: pos("g1"), before: None, after: None }]);
+        assert_eq!(moved.get_piece(pos("g1")), None);
+        moved.apply_diff(&moved.diff(&board));
+        assert_eq!(moved, board);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn assertion_message() {
+        let board = Board::default_board();
+        let mut moved = board.clone();
+        moved.set_piece(pos("g1"), None);
+        moved.set_piece(pos("f3"), Some(Piece::new(PieceType::Knight, PlayerColor::White)));
+        crate::assert_board_eq!(board, board.clone());
+        let panic = std::panic::catch_unwind(|| crate::assert_board_eq!(board, moved))
+            .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("assertion `left == right` failed\n\
+                                     boards differ on 2 squares\n\
+                                     left:                right:\n\
+                                     8 r n b q k b n r    8 r n b q k b n r\n"), "{}", message);
+        assert!(message.contains("\n3                    3           N\n"), "{}", message);
+        assert!(message.ends_with("\nf3: left empty, right N\ng1: left N, right empty"),
+                "{}", message);
+    }
+}
@@ -1,15 +1,36 @@
 //! Types for representing chess pieces.
 
+use core::fmt::{Display, Formatter};
+use core::ops::Not;
+use core::str::FromStr;
+use thiserror::Error;
 use PieceType::*;
 use PlayerColor::*;
+use crate::util::U3;
 
 /// One of the standard chess piece types: Pawn, knight, bishop, rook, queen, king
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum PieceType {
     Pawn, Knight, Bishop, Rook, Queen, King
 }
 
 impl PieceType {
+    /// All six piece types, in the order pawn, knight, bishop, rook, queen, king.
+    pub const ALL: [PieceType; 6] = [Pawn, Knight, Bishop, Rook, Queen, King];
+
+    /// returns: The piece type's uppercase letter in algebraic notation (pawn = 'P',
+    /// knight = 'N', bishop = 'B', rook = 'R', queen = 'Q', king = 'K').
+    pub const fn to_char(&self) -> char {
+        match self {
+            Pawn => 'P',
+            Knight => 'N',
+            Bishop => 'B',
+            Rook => 'R',
+            Queen => 'Q',
+            King => 'K',
+        }
+    }
+
     /// see: [Chess piece relative value - Wikipedia](https://en.wikipedia.org/wiki/Chess_piece_relative_value#Standard_valuations)
     ///
     /// returns: The standard valuation of the given piece type.
@@ -25,8 +46,61 @@ impl PieceType {
     }
 }
 
+/// Displays the piece type's letter as used in SAN: the uppercase letter for every piece type
+/// except the pawn, which is displayed as an empty string.
+impl Display for PieceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Pawn => Ok(()),
+            piece_type => write!(f, "{}", piece_type.to_char()),
+        }
+    }
+}
+
+/// An error caused by attempting to parse an invalid string or character as a [Piece] or a
+/// [PieceType].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PieceParseError {
+    /// The string did not consist of a single character.
+    #[error("expected a single piece letter")]
+    WrongLength,
+    /// The character is not one of the piece letters.
+    #[error("invalid piece letter `{0}`: expected one of P, N, B, R, Q, K")]
+    InvalidLetter(char),
+}
+
+/// Parses a piece type from its letter (see [to_char](PieceType::to_char)), ignoring case.
+impl TryFrom<char> for PieceType {
+    type Error = PieceParseError;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            'P' => Ok(Pawn),
+            'N' => Ok(Knight),
+            'B' => Ok(Bishop),
+            'R' => Ok(Rook),
+            'Q' => Ok(Queen),
+            'K' => Ok(King),
+            _ => Err(PieceParseError::InvalidLetter(value)),
+        }
+    }
+}
+
+/// Parses a piece type from its letter, ignoring case. The empty string is parsed as a pawn, so
+/// that the [Display] output of every piece type can be parsed back.
+impl FromStr for PieceType {
+    type Err = PieceParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => Ok(Pawn),
+            (Some(ch), None) => PieceType::try_from(ch),
+            _ => Err(PieceParseError::WrongLength),
+        }
+    }
+}
+
 /// One of the piece colors: White or black
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum PlayerColor {
     White, Black
 }
@@ -39,16 +113,90 @@ impl PlayerColor {
             Black => White,
         }
     }
+
+    /// returns: The rank the player's pieces start on: rank 1 (`0`) for white and rank 8 (`7`) for
+    /// black.
+    pub const fn back_rank(&self) -> U3 {
+        match self {
+            White => U3::from_low_bits(0),
+            Black => U3::from_low_bits(7),
+        }
+    }
+
+    /// returns: The rank the player's pawns start on: rank 2 (`1`) for white and rank 7 (`6`) for
+    /// black.
+    pub const fn pawn_start_rank(&self) -> U3 {
+        match self {
+            White => U3::from_low_bits(1),
+            Black => U3::from_low_bits(6),
+        }
+    }
+
+    /// returns: The rank the player's pawns promote on, which is the other player's back rank.
+    pub const fn promotion_rank(&self) -> U3 {
+        match self {
+            White => Black.back_rank(),
+            Black => White.back_rank(),
+        }
+    }
+
+    /// returns: The rank offset of a single pawn step: `1` for white and `-1` for black.
+    pub const fn pawn_direction(&self) -> i8 {
+        match self {
+            White => 1,
+            Black => -1,
+        }
+    }
+}
+
+impl Not for PlayerColor {
+    type Output = PlayerColor;
+    fn not(self) -> Self::Output {
+        self.other_player()
+    }
+}
+
+impl Display for PlayerColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let string = match self {
+            White => "White",
+            Black => "Black",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// An error caused by attempting to parse an invalid string as a [PlayerColor].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+#[error("expected `w`, `b`, `white` or `black`")]
+pub struct PlayerColorParseError;
+
+/// Parses a player color from either its FEN letter (`w` or `b`) or its name (`white` or `black`),
+/// ignoring case.
+impl FromStr for PlayerColor {
+    type Err = PlayerColorParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "w" | "white" => Ok(White),
+            "b" | "black" => Ok(Black),
+            _ => Err(PlayerColorParseError),
+        }
+    }
 }
 
 /// Represents a piece on the chess board, with a given type and color.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Piece {
     pub piece_type: PieceType,
     pub player: PlayerColor,
 }
 
 impl Piece {
+    /// returns: A piece of the given type and color.
+    pub const fn new(piece_type: PieceType, player: PlayerColor) -> Piece {
+        Piece { piece_type, player }
+    }
+
     /// Gets a piece's FEN notation letter (pawn = "P", knight = "N", bishop = "B", rook = "R",
     /// queen = "Q", king = "K"), with white pieces represented with uppercase letters and black
     /// pieces with lowercase letters.
@@ -69,6 +217,14 @@ impl Piece {
         }
     }
 
+    /// returns: The piece's FEN notation letter as a `char`. See [get_char](Piece::get_char).
+    pub const fn to_char(&self) -> char {
+        match self.player {
+            White => self.piece_type.to_char(),
+            Black => self.piece_type.to_char().to_ascii_lowercase(),
+        }
+    }
+
 
     /// see: [Chess symbols in Unicode - Wikipedia](https://en.wikipedia.org/wiki/Chess_symbols_in_Unicode#Miscellaneous_symbols)
     ///
@@ -94,20 +250,104 @@ impl Piece {
     ///
     /// returns: `Some(Piece)` if the character was parsed successfully, otherwise `None`.
     pub fn from_char(ch: char) -> Option<Piece> {
-        match ch {
-            'P' => Some(Piece { piece_type: Pawn, player: White }),
-            'N' => Some(Piece { piece_type: Knight, player: White }),
-            'B' => Some(Piece { piece_type: Bishop, player: White }),
-            'R' => Some(Piece { piece_type: Rook, player: White }),
-            'Q' => Some(Piece { piece_type: Queen, player: White }),
-            'K' => Some(Piece { piece_type: King, player: White }),
-            'p' => Some(Piece { piece_type: Pawn, player: Black }),
-            'n' => Some(Piece { piece_type: Knight, player: Black }),
-            'b' => Some(Piece { piece_type: Bishop, player: Black }),
-            'r' => Some(Piece { piece_type: Rook, player: Black }),
-            'q' => Some(Piece { piece_type: Queen, player: Black }),
-            'k' => Some(Piece { piece_type: King, player: Black }),
-            _ => None,
+        Piece::try_from(ch).ok()
+    }
+}
+
+/// Displays the piece's FEN notation letter. See [get_char](Piece::get_char).
+impl Display for Piece {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.get_char())
+    }
+}
+
+/// Parses a piece from its FEN notation letter, where uppercase letters are white pieces and
+/// lowercase letters are black pieces.
+impl TryFrom<char> for Piece {
+    type Error = PieceParseError;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        let piece_type = PieceType::try_from(value)?;
+        let player = if value.is_ascii_uppercase() { White } else { Black };
+        Ok(Piece { piece_type, player })
+    }
+}
+
+/// Parses a piece from its FEN notation letter. See [TryFrom<char>](Piece::try_from).
+impl FromStr for Piece {
+    type Err = PieceParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Piece::try_from(ch),
+            _ => Err(PieceParseError::WrongLength),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn piece_conversions() {
+        for piece_type in PieceType::ALL {
+            for player in [White, Black] {
+                let piece = Piece::new(piece_type, player);
+                assert_eq!(Piece::try_from(piece.to_char()), Ok(piece));
+                assert_eq!(Piece::from_char(piece.to_char()), Some(piece));
+                assert_eq!(piece.to_string().parse(), Ok(piece));
+                assert_eq!(piece.to_string(), piece.get_char());
+                assert_eq!(piece.to_string(), piece.to_char().to_string());
+            }
+            assert_eq!(PieceType::try_from(piece_type.to_char()), Ok(piece_type));
+            assert_eq!(PieceType::try_from(piece_type.to_char().to_ascii_lowercase()),
+                       Ok(piece_type));
+            assert_eq!(piece_type.to_string().parse(), Ok(piece_type));
+            assert_eq!(piece_type.to_char().to_string().parse(), Ok(piece_type));
+        }
+
+        assert_eq!(Pawn.to_string(), "");
+        assert_eq!(Knight.to_string(), "N");
+        assert_eq!(Piece::new(Knight, Black).to_string(), "n");
+        assert_eq!("q".parse(), Ok(Piece::new(Queen, Black)));
+        assert_eq!("Q".parse(), Ok(Piece::new(Queen, White)));
+        assert_eq!("q".parse(), Ok(Queen));
+
+        assert_eq!(Piece::try_from('x'), Err(PieceParseError::InvalidLetter('x')));
+        assert_eq!(PieceType::try_from('1'), Err(PieceParseError::InvalidLetter('1')));
+        assert_eq!(PieceType::try_from('♔'), Err(PieceParseError::InvalidLetter('♔')));
+        assert_eq!("".parse::<Piece>(), Err(PieceParseError::WrongLength));
+        assert_eq!("Qq".parse::<Piece>(), Err(PieceParseError::WrongLength));
+        assert_eq!("Qq".parse::<PieceType>(), Err(PieceParseError::WrongLength));
+        assert_eq!("z".parse::<PieceType>(), Err(PieceParseError::InvalidLetter('z')));
+    }
+
+    #[test]
+    fn player_color() {
+        assert_eq!(!White, Black);
+        assert_eq!(!Black, White);
+        assert_eq!(!!White, White);
+        assert_eq!(White.to_string(), "White");
+        assert_eq!(Black.to_string(), "Black");
+
+        assert_eq!("w".parse(), Ok(White));
+        assert_eq!("White".parse(), Ok(White));
+        assert_eq!("B".parse(), Ok(Black));
+        assert_eq!("black".parse(), Ok(Black));
+        assert_eq!("".parse::<PlayerColor>(), Err(PlayerColorParseError));
+        assert_eq!("whit".parse::<PlayerColor>(), Err(PlayerColorParseError));
+        assert_eq!("red".parse::<PlayerColor>(), Err(PlayerColorParseError));
+
+        assert_eq!(White.back_rank().get(), 0);
+        assert_eq!(Black.back_rank().get(), 7);
+        assert_eq!(White.pawn_start_rank().get(), 1);
+        assert_eq!(Black.pawn_start_rank().get(), 6);
+        assert_eq!(White.promotion_rank().get(), 7);
+        assert_eq!(Black.promotion_rank().get(), 0);
+        for color in [White, Black] {
+            assert_eq!(color.pawn_start_rank().checked_add_signed(-color.pawn_direction()),
+                       Some(color.back_rank()));
+            assert_eq!(color.pawn_direction(), -(!color).pawn_direction());
         }
     }
 }
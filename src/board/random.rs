@@ -0,0 +1,359 @@
+//! Generation of random legal positions, for example for property testing move generation. Only
+//! available with the `rand` feature.
+
+use core::ops::RangeInclusive;
+use rand::{Rng, RngExt};
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::chess::variant::Variant;
+use crate::moves;
+use crate::moves::CastlingRights;
+
+/// The number of random placements tried by [Board::random_position] before giving up.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Constraints for the positions generated by [Board::random_position].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionConstraints {
+    /// The range of the total number of pieces on the board, including the kings. Ignored if
+    /// `material` is set.
+    pub piece_count: RangeInclusive<usize>,
+    /// The exact pieces of white and black, respectively, each including one king.
+    pub material: Option<(Vec<PieceType>, Vec<PieceType>)>,
+    /// The player to move, or `None` for a random player.
+    pub active_player: Option<PlayerColor>,
+    /// Whether to give the players random castling rights where the king and rook are on their
+    /// starting squares. Otherwise, no player has castling rights.
+    pub castling_rights: bool,
+    /// Whether to set an en passant target at random where a pawn of the player who is not to
+    /// move could just have made a double move. Otherwise, there is no en passant target.
+    pub en_passant: bool,
+}
+
+impl Default for PositionConstraints {
+    fn default() -> Self {
+        PositionConstraints {
+            piece_count: 2..=32,
+            material: None,
+            active_player: None,
+            castling_rights: true,
+            en_passant: true,
+        }
+    }
+}
+
+impl PositionConstraints {
+    /// Instantiate constraints forcing specific material, for example `("KRP", "KR")`, with the
+    /// other constraints set to their defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `white`: The pieces of white, using the letters of algebraic notation (see
+    ///   [PieceType::to_char]). Must contain exactly one king.
+    /// * `black`: The pieces of black, in the same format as `white`.
+    ///
+    /// returns: `Some(PositionConstraints)` if both strings are valid, otherwise `None`.
+    pub fn with_material(white: &str, black: &str) -> Option<PositionConstraints> {
+        let parse = |string: &str| -> Option<Vec<PieceType>> {
+            let pieces = string.chars()
+                .map(|ch| PieceType::try_from(ch).ok())
+                .collect::<Option<Vec<PieceType>>>()?;
+            let kings = pieces.iter().filter(|&&piece_type| piece_type == PieceType::King).count();
+            (kings == 1).then_some(pieces)
+        };
+        Some(PositionConstraints {
+            material: Some((parse(white)?, parse(black)?)),
+            ..PositionConstraints::default()
+        })
+    }
+}
+
+/// A random position generated by [Board::random_position], which may be set up in a game with
+/// [ChessGame::set_position](crate::chess::ChessGame::set_position).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RandomPosition {
+    /// The board, which passes [Board::validate].
+    pub board: Board,
+    /// The player to move. The other player is not in check.
+    pub active_player: PlayerColor,
+    /// The castling rights of white and black, respectively.
+    pub castling_rights: (CastlingRights, CastlingRights),
+    /// The square behind a pawn of the player who is not to move which has just made a double
+    /// move, if any.
+    pub en_passant_target: Option<BoardPosition>,
+}
+
+/// returns: Random piece types for one player, including the king, or `None` if the player
+///          cannot have `count` pieces.
+fn random_material<R: Rng + ?Sized>(rng: &mut R, count: usize) -> Option<Vec<PieceType>> {
+    if !(1..=16).contains(&count) {
+        return None;
+    }
+    let mut pieces = vec![PieceType::King];
+    let mut pawns = 0;
+    while pieces.len() < count {
+        let piece_type = PieceType::ALL[rng.random_range(0..5)];
+        if piece_type == PieceType::Pawn {
+            if pawns == 8 {
+                continue;
+            }
+            pawns += 1;
+        }
+        pieces.push(piece_type);
+    }
+    Some(pieces)
+}
+
+/// returns: A board with the pieces placed on random squares, which may be invalid. If
+///          `castling_setup` is set, the king and rooks of each player are placed on their
+///          starting squares with some probability, so that castling rights are possible.
+fn random_placement<R: Rng + ?Sized>(rng: &mut R, white: &[PieceType], black: &[PieceType],
+                                     castling_setup: bool) -> Option<Board>
+{
+    let mut board = Board::empty_board();
+    let mut empty_squares: Vec<BoardPosition> = BoardPosition::all().collect();
+    let mut pieces: Vec<Piece> = white.iter()
+        .map(|&piece_type| Piece::new(piece_type, PlayerColor::White))
+        .chain(black.iter().map(|&piece_type| Piece::new(piece_type, PlayerColor::Black)))
+        .collect();
+    if castling_setup {
+        for player in [PlayerColor::White, PlayerColor::Black] {
+            if !rng.random_bool(0.25) {
+                continue;
+            }
+            let rank = player.back_rank().get();
+            for (piece_type, file) in [(PieceType::King, 4), (PieceType::Rook, 0),
+                                       (PieceType::Rook, 7)] {
+                let piece = Piece::new(piece_type, player);
+                let Some(i) = pieces.iter().position(|&other| other == piece) else {
+                    continue;
+                };
+                let pos = BoardPosition::try_from((file, rank)).ok()?;
+                let Some(j) = empty_squares.iter().position(|&other| other == pos) else {
+                    continue;
+                };
+                board.set_piece(empty_squares.swap_remove(j), Some(pieces.swap_remove(i)));
+            }
+        }
+    }
+    for piece in pieces {
+        let candidates: Vec<usize> = (0..empty_squares.len())
+            .filter(|&i| {
+                let rank = empty_squares[i].rank.get();
+                piece.piece_type != PieceType::Pawn || (1..=6).contains(&rank)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let i = candidates[rng.random_range(0..candidates.len())];
+        board.set_piece(empty_squares.swap_remove(i), Some(piece));
+    }
+    Some(board)
+}
+
+/// returns: Random castling rights for a player, where the king and rook are on their starting
+///          squares.
+fn random_castling_rights<R: Rng + ?Sized>(rng: &mut R, board: &Board,
+                                           player: PlayerColor) -> CastlingRights
+{
+    let rank = player.back_rank().get();
+    let has_piece = |file: u8, piece_type: PieceType| {
+        BoardPosition::try_from((file, rank))
+            .is_ok_and(|pos| board.get_piece(pos) == Some(Piece::new(piece_type, player)))
+    };
+    let has_king = has_piece(4, PieceType::King);
+    CastlingRights {
+        queenside: has_king && has_piece(0, PieceType::Rook) && rng.random_bool(0.5),
+        kingside: has_king && has_piece(7, PieceType::Rook) && rng.random_bool(0.5),
+    }
+}
+
+/// returns: A random en passant target behind a pawn of the player who is not to move, which
+///          could just have made a double move, or `None`.
+fn random_en_passant_target<R: Rng + ?Sized>(rng: &mut R, board: &Board,
+                                             active_player: PlayerColor) -> Option<BoardPosition>
+{
+    let other_player = active_player.other_player();
+    let direction = other_player.pawn_direction();
+    let candidates: Vec<BoardPosition> = BoardPosition::all()
+        .filter(|&target| {
+            target.rank.checked_add_signed(-direction) == Some(other_player.pawn_start_rank())
+                && board.get_piece(target).is_none()
+                && target.add((0, -direction)).is_some_and(|pos| board.get_piece(pos).is_none())
+                && target.add((0, direction)).is_some_and(|pos| {
+                    board.get_piece(pos) == Some(Piece::new(PieceType::Pawn, other_player))
+                })
+        })
+        .collect();
+    if candidates.is_empty() || rng.random_bool(0.5) {
+        return None;
+    }
+    Some(candidates[rng.random_range(0..candidates.len())])
+}
+
+impl Board {
+    /// Instantiate a board with a uniformly random
+    /// [Chess960 starting position](Board::chess960_start).
+    pub fn random_chess960_start<R: Rng + ?Sized>(rng: &mut R) -> Board {
+        let index = rng.random_range(0..Board::CHESS960_POSITIONS);
+        // every index below the number of positions is valid
+        Board::chess960_start(index).unwrap_or_else(Board::default_board)
+    }
+
+    /// Generates a random legal position: the board passes [Board::validate], and the player who
+    /// is not to move is not in check. The position only depends on the state of `rng`, so a
+    /// seeded generator always generates the same position. Positions are not guaranteed to be
+    /// reachable from the starting position.
+    ///
+    /// returns: `Some(RandomPosition)`, or `None` if no position satisfying the constraints was
+    ///          found, for example because the requested material does not fit on the board.
+    pub fn random_position<R: Rng + ?Sized>(rng: &mut R,
+                                            constraints: &PositionConstraints)
+                                            -> Option<RandomPosition>
+    {
+        for _ in 0..MAX_ATTEMPTS {
+            let active_player = constraints.active_player.unwrap_or_else(|| {
+                if rng.random_bool(0.5) { PlayerColor::White } else { PlayerColor::Black }
+            });
+            let (white, black) = match &constraints.material {
+                Some((white, black)) => (white.clone(), black.clone()),
+                None => {
+                    let min = (*constraints.piece_count.start()).max(2);
+                    let max = (*constraints.piece_count.end()).min(32);
+                    if min > max {
+                        return None;
+                    }
+                    let count = rng.random_range(min..=max);
+                    let white_count = rng.random_range(count.saturating_sub(16).max(1)
+                                                       ..=(count - 1).min(16));
+                    (random_material(rng, white_count)?, random_material(rng, count - white_count)?)
+                }
+            };
+            let Some(board) = random_placement(rng, &white, &black, constraints.castling_rights)
+            else {
+                continue;
+            };
+            if board.validate().is_err()
+                || moves::is_in_check(&board, active_player.other_player(), Variant::Standard)
+            {
+                continue;
+            }
+            let castling_rights = if constraints.castling_rights {
+                (random_castling_rights(rng, &board, PlayerColor::White),
+                 random_castling_rights(rng, &board, PlayerColor::Black))
+            } else {
+                (CastlingRights::NONE, CastlingRights::NONE)
+            };
+            let en_passant_target = if constraints.en_passant {
+                random_en_passant_target(rng, &board, active_player)
+            } else {
+                None
+            };
+            return Some(RandomPosition { board, active_player, castling_rights, en_passant_target });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::chess::ChessGame;
+    use super::*;
+
+    fn piece_types(board: &Board, player: PlayerColor) -> Vec<PieceType> {
+        let mut pieces: Vec<PieceType> = board.into_iter()
+            .filter_map(|(_, piece)| piece)
+            .filter(|piece| piece.player == player)
+            .map(|piece| piece.piece_type)
+            .collect();
+        pieces.sort_by_key(|piece_type| *piece_type as u8);
+        pieces
+    }
+
+    #[test]
+    fn random_positions_are_legal() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut game = ChessGame::new(Board::default_board());
+        let constraints = PositionConstraints::default();
+        let mut en_passant_targets = 0;
+        let mut castling_rights = 0;
+        for _ in 0..2000 {
+            let position = Board::random_position(&mut rng, &constraints).unwrap();
+            let pieces = position.board.into_iter().filter(|(_, piece)| piece.is_some()).count();
+            assert!(constraints.piece_count.contains(&pieces));
+            game.set_position(position.board, position.active_player, position.castling_rights,
+                              position.en_passant_target).unwrap();
+            en_passant_targets += position.en_passant_target.is_some() as usize;
+            castling_rights += (position.castling_rights.0 != CastlingRights::NONE) as usize;
+        }
+        assert!(en_passant_targets > 0);
+        assert!(castling_rights > 0);
+    }
+
+    #[test]
+    fn pieces_match_full_iteration() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..200 {
+            let board = Board::random_position(&mut rng, &PositionConstraints::default())
+                .unwrap().board;
+            let all: Vec<(BoardPosition, Piece)> = board.into_iter()
+                .filter_map(|(pos, piece)| Some((pos, piece?)))
+                .collect();
+            assert_eq!(board.pieces().collect::<Vec<_>>(), all);
+            for player in [PlayerColor::White, PlayerColor::Black] {
+                let own: Vec<(BoardPosition, Piece)> = all.iter().copied()
+                    .filter(|(_, piece)| piece.player == player)
+                    .collect();
+                assert_eq!(board.pieces_of(player).collect::<Vec<_>>(), own);
+            }
+        }
+    }
+
+    #[test]
+    fn random_positions_honor_constraints() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let constraints = PositionConstraints {
+            piece_count: 5..=8,
+            active_player: Some(PlayerColor::Black),
+            castling_rights: false,
+            en_passant: false,
+            ..PositionConstraints::default()
+        };
+        for _ in 0..1000 {
+            let position = Board::random_position(&mut rng, &constraints).unwrap();
+            let pieces = position.board.into_iter().filter(|(_, piece)| piece.is_some()).count();
+            assert!((5..=8).contains(&pieces));
+            assert_eq!(position.active_player, PlayerColor::Black);
+            assert_eq!(position.castling_rights, (CastlingRights::NONE, CastlingRights::NONE));
+            assert_eq!(position.en_passant_target, None);
+        }
+
+        let constraints = PositionConstraints::with_material("KRP", "KR").unwrap();
+        for _ in 0..1000 {
+            let position = Board::random_position(&mut rng, &constraints).unwrap();
+            position.board.validate().unwrap();
+            assert_eq!(piece_types(&position.board, PlayerColor::White),
+                       [PieceType::Pawn, PieceType::Rook, PieceType::King]);
+            assert_eq!(piece_types(&position.board, PlayerColor::Black),
+                       [PieceType::Rook, PieceType::King]);
+        }
+
+        assert_eq!(PositionConstraints::with_material("KRX", "K"), None);
+        assert_eq!(PositionConstraints::with_material("RP", "K"), None);
+        let impossible = PositionConstraints::with_material(&format!("K{}", "P".repeat(49)), "K")
+            .unwrap();
+        assert_eq!(Board::random_position(&mut rng, &impossible), None);
+    }
+
+    #[test]
+    fn random_chess960_starts() {
+        let mut rng = StdRng::seed_from_u64(960);
+        let boards: Vec<Board> = (0..20).map(|_| Board::random_chess960_start(&mut rng)).collect();
+        assert!(boards.iter().all(|board| board.validate().is_ok()));
+        assert!(boards.iter().any(|board| *board != boards[0]));
+    }
+}
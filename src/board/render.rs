@@ -0,0 +1,362 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::board::Board;
+use crate::board::board_pos::{BoardPosition, SquareColor};
+use crate::board::piece::PlayerColor;
+use crate::moves::util::BoardBitmap;
+
+/// How [Board::render] draws the empty squares of the board.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EmptySquareStyle {
+    /// Empty squares are drawn as spaces.
+    #[default]
+    Blank,
+    /// Empty squares are drawn as `.`.
+    Dots,
+    /// Empty squares are drawn as `░` for light squares and `▓` for dark squares.
+    Shaded,
+}
+
+/// The border which [Board::render] draws around the squares of the board.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum BorderStyle {
+    /// No border.
+    #[default]
+    None,
+    /// A border of `+`, `-` and `|` characters.
+    Ascii,
+    /// A border of Unicode box-drawing characters, such as `┌`, `─` and `│`.
+    Unicode,
+}
+
+/// Options for drawing a board as a text diagram with [Board::render]. The default options give
+/// the same diagram as the [Display](core::fmt::Display) implementation of [Board].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BoardDisplayOptions {
+    /// Whether to draw the pieces as Unicode chess symbols, such as `♔`, instead of FEN letters.
+    pub unicode_pieces: bool,
+    /// How to draw the empty squares.
+    pub empty_squares: EmptySquareStyle,
+    /// The player whose pieces are at the bottom of the diagram. From black's perspective, rank 1
+    /// is at the top and the h-file is on the left.
+    pub perspective: PlayerColor,
+    /// Whether to draw a rank label before each rank and a row of file labels at the bottom.
+    pub labels: bool,
+    /// The border around the squares.
+    pub border: BorderStyle,
+}
+
+impl Default for BoardDisplayOptions {
+    fn default() -> Self {
+        BoardDisplayOptions {
+            unicode_pieces: false,
+            empty_squares: EmptySquareStyle::Blank,
+            perspective: PlayerColor::White,
+            labels: true,
+            border: BorderStyle::None,
+        }
+    }
+}
+
+impl BoardDisplayOptions {
+    /// returns: The options of the alternate [Display](core::fmt::Display) format of [Board]
+    ///          (`{:#}`), with Unicode pieces and shaded empty squares.
+    pub fn unicode() -> BoardDisplayOptions {
+        BoardDisplayOptions {
+            unicode_pieces: true,
+            empty_squares: EmptySquareStyle::Shaded,
+            ..BoardDisplayOptions::default()
+        }
+    }
+}
+
+impl Board {
+    /// Draws the board as a text diagram, one line per rank, with the squares separated by
+    /// spaces. The diagram has no trailing line break.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::{Board, BoardDisplayOptions, BorderStyle, EmptySquareStyle};
+    ///
+    /// let options = BoardDisplayOptions {
+    ///     empty_squares: EmptySquareStyle::Dots,
+    ///     border: BorderStyle::Ascii,
+    ///     ..BoardDisplayOptions::default()
+    /// };
+    /// println!("{}", Board::default_board().render(&options));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `options`: How to draw the board.
+    ///
+    /// returns: The diagram.
+    pub fn render(&self, options: &BoardDisplayOptions) -> String {
+        self.render_with_highlights(options, &[])
+    }
+
+    /// Draws the board like [render](Board::render), with some squares highlighted, for example
+    /// the legal moves of a piece or the squares of the last move. Highlighted empty squares are
+    /// drawn as the character of their highlight, and highlighted pieces are wrapped in
+    /// brackets, such as `[n]`. The brackets take the place of the spaces between the squares, so
+    /// the squares stay aligned, and two highlighted pieces next to each other are separated by a
+    /// single `|`, such as `[n|b]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use leben_chess::board::{Board, BoardDisplayOptions};
+    /// use leben_chess::chess::ChessGame;
+    ///
+    /// let game = ChessGame::new(Board::default_board());
+    /// let knight_moves = game.available_moves("g1".parse().unwrap());
+    /// let diagram = game.board().render_with_highlights(&BoardDisplayOptions::default(),
+    ///                                                   &[(knight_moves, '*')]);
+    /// assert!(diagram.contains("\n3           *   *\n"));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `options`: How to draw the board.
+    /// * `highlights`: The highlighted squares, each with the character drawn on the squares if
+    ///   they are empty. If a square is in several bitmaps, the character of the first one is
+    ///   used.
+    ///
+    /// returns: The diagram.
+    pub fn render_with_highlights(&self, options: &BoardDisplayOptions,
+                                  highlights: &[(BoardBitmap, char)]) -> String
+    {
+        // in reading order, from the top left corner of the diagram
+        let all_squares: Vec<BoardPosition> =
+            super::squares_from_perspective(options.perspective).collect();
+        let label_width = if options.labels { 2 } else { 0 };
+        let (top, bottom, side) = match options.border {
+            BorderStyle::None => (None, None, ""),
+            BorderStyle::Ascii => (Some(('+', '-', '+')), Some(('+', '-', '+')), "|"),
+            BorderStyle::Unicode => (Some(('┌', '─', '┐')), Some(('└', '─', '┘')), "│"),
+        };
+        // the squares and the spaces between them, and a space on each side inside a border
+        let inner_width = if side.is_empty() { 15 } else { 17 };
+        let border_line = |(left, line, right): (char, char, char)| {
+            let mut row: String = " ".repeat(label_width);
+            row.push(left);
+            row.extend(core::iter::repeat_n(line, inner_width));
+            row.push(right);
+            row
+        };
+
+        let mut lines = Vec::new();
+        lines.extend(top.map(border_line));
+        for squares in all_squares.chunks(8) {
+            let mut row = String::new();
+            if options.labels {
+                row.push((b'1' + squares[0].rank.get()) as char);
+                if !side.is_empty() {
+                    row.push(' ');
+                }
+            }
+            row.push_str(side);
+            let highlight = |pos: BoardPosition| {
+                highlights.iter().find(|(bitmap, _)| bitmap.get(pos)).map(|&(_, mark)| mark)
+            };
+            let bracketed = |i: usize| {
+                squares.get(i).is_some_and(|&pos| {
+                    self.get_piece(pos).is_some() && highlight(pos).is_some()
+                })
+            };
+            // the separators before, between and after the squares
+            for i in 0..=8 {
+                let spaced = match i {
+                    0 => options.labels || !side.is_empty(),
+                    8 => !side.is_empty(),
+                    _ => true,
+                };
+                match (i > 0 && bracketed(i - 1), bracketed(i)) {
+                    (true, true) => row.push('|'),
+                    (true, false) => row.push(']'),
+                    (false, true) => row.push('['),
+                    (false, false) if spaced => row.push(' '),
+                    (false, false) => {}
+                }
+                if let Some(&pos) = squares.get(i) {
+                    match highlight(pos) {
+                        Some(mark) if self.get_piece(pos).is_none() => row.push(mark),
+                        _ => row.push_str(self.render_square(pos, options)),
+                    }
+                }
+            }
+            row.push_str(side);
+            lines.push(row);
+        }
+        lines.extend(bottom.map(border_line));
+        if options.labels {
+            let mut row: String = " ".repeat(label_width + if side.is_empty() { 0 } else { 2 });
+            for (i, pos) in all_squares[..8].iter().enumerate() {
+                if i > 0 {
+                    row.push(' ');
+                }
+                row.push((b'a' + pos.file.get()) as char);
+            }
+            lines.push(row);
+        }
+        lines.join("\n")
+    }
+
+    /// returns: The symbol drawn for a square of the board.
+    fn render_square(&self, pos: BoardPosition, options: &BoardDisplayOptions) -> &'static str {
+        match (self.get_piece(pos), options.empty_squares) {
+            (Some(piece), _) if options.unicode_pieces => piece.get_unicode_char(),
+            (Some(piece), _) => piece.get_char(),
+            (None, EmptySquareStyle::Blank) => " ",
+            (None, EmptySquareStyle::Dots) => ".",
+            (None, EmptySquareStyle::Shaded) => match pos.square_color() {
+                SquareColor::Light => "░",
+                SquareColor::Dark => "▓",
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chess::ChessGame;
+    use super::*;
+
+    fn position() -> Board {
+        Board::from_fen_string("r3k2r/pp3ppp/8/8/4P3/8/PP3PPP/R3K2R").unwrap()
+    }
+
+    #[test]
+    fn default_rendering() {
+        let board = position();
+        let expected = concat!(
+            "8 r       k     r\n",
+            "7 p p       p p p\n",
+            "6                \n",
+            "5                \n",
+            "4         P      \n",
+            "3                \n",
+            "2 P P       P P P\n",
+            "1 R       K     R\n",
+            "  a b c d e f g h",
+        );
+        assert_eq!(board.render(&BoardDisplayOptions::default()), expected);
+        assert_eq!(alloc::format!("\n{}", expected), alloc::format!("{}", board));
+        assert_eq!(alloc::format!("\n{}", board.render(&BoardDisplayOptions::unicode())),
+                   alloc::format!("{:#}", board));
+    }
+
+    #[test]
+    fn rendering_options() {
+        let board = position();
+        let dots = BoardDisplayOptions {
+            empty_squares: EmptySquareStyle::Dots,
+            labels: false,
+            ..BoardDisplayOptions::default()
+        };
+        assert_eq!(board.render(&dots), concat!(
+            "r . . . k . . r\n",
+            "p p . . . p p p\n",
+            ". . . . . . . .\n",
+            ". . . . . . . .\n",
+            ". . . . P . . .\n",
+            ". . . . . . . .\n",
+            "P P . . . P P P\n",
+            "R . . . K . . R",
+        ));
+
+        let flipped_unicode = BoardDisplayOptions {
+            perspective: PlayerColor::Black,
+            ..BoardDisplayOptions::unicode()
+        };
+        assert_eq!(board.render(&flipped_unicode), concat!(
+            "1 ♖ ▓ ░ ♔ ░ ▓ ░ ♖\n",
+            "2 ♙ ♙ ♙ ░ ▓ ░ ♙ ♙\n",
+            "3 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "4 ▓ ░ ▓ ♙ ▓ ░ ▓ ░\n",
+            "5 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "6 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "7 ♟ ♟ ♟ ▓ ░ ▓ ♟ ♟\n",
+            "8 ♜ ░ ▓ ♚ ▓ ░ ▓ ♜\n",
+            "  h g f e d c b a",
+        ));
+
+        let ascii_border = BoardDisplayOptions {
+            empty_squares: EmptySquareStyle::Dots,
+            border: BorderStyle::Ascii,
+            ..BoardDisplayOptions::default()
+        };
+        assert_eq!(board.render(&ascii_border), concat!(
+            "  +-----------------+\n",
+            "8 | r . . . k . . r |\n",
+            "7 | p p . . . p p p |\n",
+            "6 | . . . . . . . . |\n",
+            "5 | . . . . . . . . |\n",
+            "4 | . . . . P . . . |\n",
+            "3 | . . . . . . . . |\n",
+            "2 | P P . . . P P P |\n",
+            "1 | R . . . K . . R |\n",
+            "  +-----------------+\n",
+            "    a b c d e f g h",
+        ));
+
+        let unicode_border = BoardDisplayOptions {
+            labels: false,
+            border: BorderStyle::Unicode,
+            ..BoardDisplayOptions::unicode()
+        };
+        let rendered = board.render(&unicode_border);
+        assert!(rendered.starts_with("┌─────────────────┐\n│ ♜ ▓ ░ ▓ ♚ ▓ ░ ♜ │\n"), "{}", rendered);
+        assert!(rendered.ends_with("\n│ ♖ ░ ▓ ░ ♔ ░ ▓ ♖ │\n└─────────────────┘"), "{}", rendered);
+        assert_eq!(rendered.lines().count(), 10);
+    }
+
+    #[test]
+    fn highlighted_squares() {
+        let game = ChessGame::from_fen("4k3/8/8/4p3/8/5N2/8/4K3 w - - 0 1").unwrap();
+        let knight_moves = game.available_moves("f3".parse().unwrap());
+        let board = game.board();
+        assert_eq!(board.render_with_highlights(&BoardDisplayOptions::default(),
+                                                &[(knight_moves, '*')]), concat!(
+            "8         k      \n",
+            "7                \n",
+            "6                \n",
+            "5        [p]  *  \n",
+            "4       *       *\n",
+            "3           N    \n",
+            "2       *       *\n",
+            "1         K   *  \n",
+            "  a b c d e f g h",
+        ));
+
+        // the last move, composed with the other options
+        let last_move = BoardBitmap::from_square_list("f3,g1").unwrap();
+        let options = BoardDisplayOptions {
+            perspective: PlayerColor::Black,
+            empty_squares: EmptySquareStyle::Dots,
+            labels: false,
+            ..BoardDisplayOptions::unicode()
+        };
+        let highlights = [(last_move, '+'), (knight_moves, '*')];
+        assert_eq!(board.render_with_highlights(&options, &highlights), concat!(
+            ". + . ♔ . . . .\n",
+            "* . . . * . . .\n",
+            ". .[♘]. . . . .\n",
+            "* . . . * . . .\n",
+            ". * .[♟]. . . .\n",
+            ". . . . . . . .\n",
+            ". . . . . . . .\n",
+            ". . . ♚ . . . .",
+        ));
+
+        // adjacent highlighted pieces share a separator
+        let board = Board::from_fen_string("8/8/8/8/8/8/8/RNBQKBNR").unwrap();
+        let pieces = BoardBitmap::from_square_list("a1,b1,c1,h1").unwrap();
+        let rendered = board.render_with_highlights(&BoardDisplayOptions::default(),
+                                                    &[(pieces, '*')]);
+        assert!(rendered.ends_with("\n1[R|N|B]Q K B N[R]\n  a b c d e f g h"), "{}", rendered);
+        assert_eq!(board.render_with_highlights(&BoardDisplayOptions::default(), &[]),
+                   board.render(&BoardDisplayOptions::default()));
+    }
+}
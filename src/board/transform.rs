@@ -0,0 +1,119 @@
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::Piece;
+use crate::util::U3;
+
+impl Board {
+    /// returns: A copy of the board with the ranks reversed, so that the piece on a1 is moved to
+    ///          a8, the piece on b2 to b7, and so on, keeping the colors of the pieces. See also the
+    ///          [bitmap transform](crate::moves::util::BoardBitmap::flip_vertical).
+    pub fn flip_vertical(&self) -> Board {
+        self.map_squares(flip_rank, |piece| piece)
+    }
+
+    /// returns: A copy of the board with the files reversed, so that the piece on a1 is moved to
+    ///          h1, the piece on b2 to g2, and so on. See also the
+    ///          [bitmap transform](crate::moves::util::BoardBitmap::mirror_horizontal).
+    pub fn mirror_horizontal(&self) -> Board {
+        self.map_squares(flip_file, |piece| piece)
+    }
+
+    /// Swaps the sides of the players, for example to evaluate a position from black's
+    /// perspective as if it were white's. The ranks are reversed like in
+    /// [flip_vertical](Board::flip_vertical), and every piece changes color, so that the position
+    /// is the same with the other player in each role. The board has no castling rights, en
+    /// passant target or player to move, so when swapping a whole position, those have to be
+    /// swapped by the caller too: the castling rights of white become those of black and the
+    /// other way around, the en passant target is flipped vertically, and the other player moves.
+    ///
+    /// returns: A copy of the board with the colors swapped.
+    pub fn swap_colors(&self) -> Board {
+        self.map_squares(flip_rank, |piece| {
+            Piece::new(piece.piece_type, piece.player.other_player())
+        })
+    }
+
+    /// returns: A copy of the board where the piece on each square is moved to a new square and
+    ///          changed, given that the squares are moved one-to-one.
+    fn map_squares(&self, square: impl Fn(BoardPosition) -> BoardPosition,
+                   piece: impl Fn(Piece) -> Piece) -> Board
+    {
+        let mut board = Board::empty_board();
+        for (pos, old_piece) in self.pieces() {
+            board.set_piece(square(pos), Some(piece(old_piece)));
+        }
+        board
+    }
+}
+
+/// returns: The square on the same file and on the rank counted from the other side.
+fn flip_rank(pos: BoardPosition) -> BoardPosition {
+    BoardPosition { file: pos.file, rank: U3::from_low_bits(7 - pos.rank.get()) }
+}
+
+/// returns: The square on the same rank and on the file counted from the other side.
+fn flip_file(pos: BoardPosition) -> BoardPosition {
+    BoardPosition { file: U3::from_low_bits(7 - pos.file.get()), rank: pos.rank }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use crate::moves::util::BoardBitmap;
+    use super::*;
+
+    const FENS: [&str; 3] = [
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR",
+        "r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR",
+        "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8",
+    ];
+
+    #[test]
+    fn flipped_boards() {
+        for fen in FENS {
+            let board = Board::from_fen_string(fen).unwrap();
+            let reversed: Vec<&str> = fen.split('/').rev().collect();
+            assert_eq!(board.flip_vertical().to_fen_string(), reversed.join("/"));
+            let mirrored: Vec<String> = fen.split('/').map(|rank| rank.chars().rev().collect())
+                .collect();
+            assert_eq!(board.mirror_horizontal().to_fen_string(), mirrored.join("/"));
+            let swapped: String = reversed.join("/").chars()
+                .map(|ch| if ch.is_ascii_uppercase() {
+                    ch.to_ascii_lowercase()
+                } else {
+                    ch.to_ascii_uppercase()
+                })
+                .collect();
+            assert_eq!(board.swap_colors().to_fen_string(), swapped);
+
+            assert_eq!(board.flip_vertical().flip_vertical(), board);
+            assert_eq!(board.mirror_horizontal().mirror_horizontal(), board);
+            assert_eq!(board.swap_colors().swap_colors(), board);
+        }
+        let board = Board::default_board();
+        assert_eq!(board.swap_colors(), board);
+        assert_eq!(board.mirror_horizontal().to_fen_string(),
+                   "rnbkqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR");
+    }
+
+    #[test]
+    fn bitmap_transforms() {
+        let board = Board::from_fen_string(FENS[1]).unwrap();
+        let occupied = |board: &Board| {
+            let mut bitmap = BoardBitmap::all_zeros();
+            for (pos, _) in board.pieces() {
+                bitmap.set(pos, true);
+            }
+            bitmap
+        };
+        assert_eq!(occupied(&board.flip_vertical()), occupied(&board).flip_vertical());
+        assert_eq!(occupied(&board.mirror_horizontal()), occupied(&board).mirror_horizontal());
+        assert_eq!(occupied(&board.swap_colors()), occupied(&board).flip_vertical());
+
+        let bitmap = BoardBitmap::from_square_list("a1,b2,c8,h5").unwrap();
+        assert_eq!(bitmap.flip_vertical().to_square_list(), "a8,b7,c1,h4");
+        assert_eq!(bitmap.mirror_horizontal().to_square_list(), "a5,f8,g2,h1");
+        assert_eq!(bitmap.flip_vertical().flip_vertical(), bitmap);
+    }
+}
@@ -0,0 +1,368 @@
+//! Opening books in the Polyglot format.
+//!
+//! A Polyglot book is a file of 16-byte entries sorted by position key. Each entry consists of
+//! the 64-bit key of a position, a 16-bit encoded move, a 16-bit weight and a 32-bit learn value,
+//! all big-endian.
+//!
+//! The key of a position is computed by combining pseudorandom numbers from a published table of
+//! 781 numbers with XOR:
+//! - one number for each piece on the board, at index `64 * kind + 8 * rank + file`, where
+//!   `kind` is 0 for a black pawn, 1 for a white pawn, 2 for a black knight and so on up to 11
+//!   for a white king,
+//! - indices 768 to 771 for white kingside, white queenside, black kingside and black queenside
+//!   castling rights,
+//! - index `772 + file` for the file of the en passant target square, only if a pawn of the active
+//!   player could capture en passant,
+//! - index 780 if white is to move.
+//!
+//! see: [Polyglot book format](http://hgm.nubati.net/book_format.html)
+
+mod random;
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::path::Path;
+use thiserror::Error;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::chess::ChessGame;
+use crate::moves::{ChessMove, PieceMovement, PromotionType};
+use crate::util::U3;
+
+/// An entry of a Polyglot opening book. See [the module documentation](self).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BookEntry {
+    /// The Polyglot key of the position. See [ChessGame::polyglot_key].
+    pub key: u64,
+    /// The move in Polyglot's 16-bit encoding. See [decode_move].
+    pub raw_move: u16,
+    /// The relative weight of the move among the moves for the position.
+    pub weight: u16,
+    /// Data used by engines for learning, not interpreted by this crate.
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// The size of an encoded entry in bytes.
+    pub const SIZE: usize = 16;
+
+    /// returns: The entry decoded from its big-endian binary representation.
+    pub fn from_bytes(bytes: [u8; BookEntry::SIZE]) -> BookEntry {
+        let [k0, k1, k2, k3, k4, k5, k6, k7, m0, m1, w0, w1, l0, l1, l2, l3] = bytes;
+        BookEntry {
+            key: u64::from_be_bytes([k0, k1, k2, k3, k4, k5, k6, k7]),
+            raw_move: u16::from_be_bytes([m0, m1]),
+            weight: u16::from_be_bytes([w0, w1]),
+            learn: u32::from_be_bytes([l0, l1, l2, l3]),
+        }
+    }
+
+    /// returns: The big-endian binary representation of the entry.
+    pub fn to_bytes(&self) -> [u8; BookEntry::SIZE] {
+        let mut bytes = [0; BookEntry::SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/// An error caused by attempting to load an invalid opening book.
+#[derive(Error, Debug)]
+pub enum BookError {
+    /// The book could not be read.
+    #[cfg(feature = "std")]
+    #[error("could not read book: {0}")]
+    Io(#[from] std::io::Error),
+    /// The size of the book is not a multiple of the size of an entry.
+    #[error("book size {0} is not a multiple of 16 bytes")]
+    InvalidSize(usize),
+}
+
+/// A Polyglot opening book, loaded into memory. See [the module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    /// returns: A book with the given entries, which do not need to be sorted.
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Book {
+        entries.sort_by_key(|entry| entry.key);
+        Book { entries }
+    }
+
+    /// returns: The book decoded from the contents of a Polyglot `.bin` file, or
+    ///          [InvalidSize](BookError::InvalidSize) if the contents are not a whole number of
+    ///          entries.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, BookError> {
+        if !bytes.len().is_multiple_of(BookEntry::SIZE) {
+            return Err(BookError::InvalidSize(bytes.len()));
+        }
+        let (chunks, _) = bytes.as_chunks::<{ BookEntry::SIZE }>();
+        let entries = chunks.iter().map(|&chunk| BookEntry::from_bytes(chunk)).collect();
+        Ok(Book::from_entries(entries))
+    }
+
+    /// returns: The book read from a Polyglot `.bin` file.
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Book, BookError> {
+        Book::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// returns: All entries of the book, sorted by key.
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    /// returns: The entries for the position with the given Polyglot key, found by binary
+    ///          search.
+    pub fn entries_for(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// Picks one of the book moves for the current position of a game at random, with
+    /// probabilities proportional to the moves' weights. Requires the `rand` feature.
+    ///
+    /// returns: `Some(ChessMove)` of the picked move, or `None` if the book has no legal moves
+    ///          with a positive weight for the position.
+    #[cfg(feature = "rand")]
+    pub fn pick_weighted<R: rand::Rng + ?Sized>(&self, game: &ChessGame,
+                                                rng: &mut R) -> Option<ChessMove>
+    {
+        use rand::RngExt;
+        let moves = game.book_moves(self);
+        let total: u32 = moves.iter().map(|(_, weight)| *weight as u32).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut choice = rng.random_range(0..total);
+        for (chess_move, weight) in moves {
+            if choice < weight as u32 {
+                return Some(chess_move);
+            }
+            choice -= weight as u32;
+        }
+        None
+    }
+}
+
+/// Decodes a move in Polyglot's 16-bit encoding: bits 0-5 are the destination square and bits
+/// 6-11 the origin square (each as `8 * rank + file`), and bits 12-14 the promotion piece (1 for
+/// knight up to 4 for queen, or 0 for none). Castling is encoded as the king moving to the
+/// square of the rook, so the returned move needs to be converted with the game position in mind
+/// (as [ChessGame::book_moves] does).
+///
+/// returns: The decoded move, or `None` if the promotion piece is invalid.
+pub fn decode_move(raw_move: u16) -> Option<ChessMove> {
+    let square = |bits: u16| BoardPosition {
+        file: U3::from_low_bits(bits as u8),
+        rank: U3::from_low_bits((bits >> 3) as u8),
+    };
+    let promotion = match (raw_move >> 12) & 7 {
+        0 => None,
+        1 => Some(PromotionType::Knight),
+        2 => Some(PromotionType::Bishop),
+        3 => Some(PromotionType::Rook),
+        4 => Some(PromotionType::Queen),
+        _ => return None,
+    };
+    Some(ChessMove {
+        piece_movement: PieceMovement { from: square(raw_move >> 6), to: square(raw_move) },
+        promotion,
+        drop: None,
+    })
+}
+
+impl ChessGame {
+    /// Computes the Polyglot key of the current position with the published random numbers of
+    /// the Polyglot book format, so that it matches the keys of Polyglot books and of other
+    /// tools. As in the format, the en passant file is only part of the key if a pawn of the
+    /// active player stands next to the pawn which has just made a double move, whether or not
+    /// the capture would be legal.
+    ///
+    /// see: [Polyglot book format](http://hgm.nubati.net/book_format.html)
+    ///
+    /// returns: The Polyglot key of the current position. See [the book module](crate::book).
+    pub fn polyglot_key(&self) -> u64 {
+        let mut key = 0;
+        for (pos, piece) in self.board() {
+            if let Some(piece) = piece {
+                let kind = 2 * piece.piece_type as usize
+                    + (piece.player == PlayerColor::White) as usize;
+                let square = 8 * pos.rank.get() as usize + pos.file.get() as usize;
+                key ^= random::RANDOM64[64 * kind + square];
+            }
+        }
+        let castling_rights = [
+            self.castling_rights(PlayerColor::White).kingside,
+            self.castling_rights(PlayerColor::White).queenside,
+            self.castling_rights(PlayerColor::Black).kingside,
+            self.castling_rights(PlayerColor::Black).queenside,
+        ];
+        for (i, right) in castling_rights.into_iter().enumerate() {
+            if right {
+                key ^= random::RANDOM64[768 + i];
+            }
+        }
+        if let Some(target) = self.en_passant_target() {
+            let pawn = Some(Piece::new(PieceType::Pawn, self.active_player()));
+            let pawn_rank = target.rank.checked_add_signed(-self.active_player().pawn_direction());
+            let can_capture = [-1, 1].into_iter()
+                .filter_map(|offset| target.file.checked_add_signed(offset))
+                .filter_map(|file| Some(BoardPosition { file, rank: pawn_rank? }))
+                .any(|pos| self.board().get_piece(pos) == pawn);
+            if can_capture {
+                key ^= random::RANDOM64[772 + target.file.get() as usize];
+            }
+        }
+        if self.active_player() == PlayerColor::White {
+            key ^= random::RANDOM64[780];
+        }
+        key
+    }
+
+    /// Looks up the current position in an opening book.
+    ///
+    /// returns: The legal moves the book lists for the current position together with their
+    ///          weights, in the order of the book.
+    pub fn book_moves(&self, book: &Book) -> Vec<(ChessMove, u16)> {
+        book.entries_for(self.polyglot_key()).iter()
+            .filter_map(|entry| {
+                let mut chess_move = decode_move(entry.raw_move)?;
+                let PieceMovement { from, to } = chess_move.piece_movement;
+                let king = Piece::new(PieceType::King, self.active_player());
+                let rook = Piece::new(PieceType::Rook, self.active_player());
+                if self.board().get_piece(from) == Some(king)
+                    && self.board().get_piece(to) == Some(rook)
+                {
+                    // castling is encoded as the king capturing its own rook
+                    let file = if to.file > from.file { 6 } else { 2 };
+                    chess_move.piece_movement.to = BoardPosition::try_from((file, to.rank.get()))
+                        .ok()?;
+                }
+                let legal = self.available_moves(from).get(chess_move.piece_movement.to)
+                    && self.expects_promotion_move(from) == chess_move.promotion.is_some();
+                legal.then_some((chess_move, entry.weight))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use super::*;
+
+    fn raw_move(from: &str, to: &str) -> u16 {
+        let from: BoardPosition = from.parse().unwrap();
+        let to: BoardPosition = to.parse().unwrap();
+        let square = |pos: BoardPosition| (8 * pos.rank.get() + pos.file.get()) as u16;
+        square(from) << 6 | square(to)
+    }
+
+    fn test_book() -> Book {
+        let entry = |key, from, to, weight| BookEntry {
+            key,
+            raw_move: raw_move(from, to),
+            weight,
+            learn: 0,
+        };
+        let entries = [
+            entry(0x823C9B50FD114196, "e7", "e5", 4),
+            entry(0x463B96181691FC9C, "e2", "e4", 10),
+            entry(0x463B96181691FC9C, "d2", "d4", 5),
+            entry(0x823C9B50FD114196, "c7", "c5", 6),
+            entry(0x463B96181691FC9C, "e2", "e5", 100),
+            entry(0x0123456789ABCDEF, "a2", "a3", 1),
+        ];
+        let bytes: Vec<u8> = entries.iter().flat_map(BookEntry::to_bytes).collect();
+        Book::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn polyglot_keys() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.polyglot_key(), 0x463B96181691FC9C);
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x823C9B50FD114196);
+        game.do_move(ChessMove::from_coords("d7", "d5").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x0756B94461C50FB0);
+        game.do_move(ChessMove::from_coords("e4", "e5").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x662FAFB965DB29D4);
+        game.do_move(ChessMove::from_coords("f7", "f5").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x22A48B5A8E47FF78);
+        game.do_move(ChessMove::from_coords("e1", "e2").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x652A607CA3F242C1);
+        game.do_move(ChessMove::from_coords("e8", "f7").unwrap()).unwrap();
+        assert_eq!(game.polyglot_key(), 0x00FDD303C946BDD9);
+
+        // the remaining test positions of the Polyglot specification
+        let fen = "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3";
+        assert_eq!(ChessGame::from_fen(fen).unwrap().polyglot_key(), 0x3C8123EA7B067637);
+        let fen = "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4";
+        assert_eq!(ChessGame::from_fen(fen).unwrap().polyglot_key(), 0x5C3F9B829B279560);
+        // the en passant file is only hashed if a pawn can capture en passant, so the key after
+        // 1. e4 is the same without the en passant target
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        assert_eq!(ChessGame::from_fen(fen).unwrap().polyglot_key(), 0x823C9B50FD114196);
+    }
+
+    #[test]
+    fn book_lookup() {
+        let book = test_book();
+        assert_eq!(book.entries().len(), 6);
+        assert!(Book::from_bytes(&[0; 17]).is_err());
+
+        let mut game = ChessGame::new(Board::default_board());
+        // the illegal move e2e5 is skipped
+        assert_eq!(game.book_moves(&book), vec![
+            (ChessMove::from_coords("e2", "e4").unwrap(), 10),
+            (ChessMove::from_coords("d2", "d4").unwrap(), 5),
+        ]);
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(game.book_moves(&book).len(), 2);
+        game.do_move(ChessMove::from_coords("c7", "c5").unwrap()).unwrap();
+        assert!(game.book_moves(&book).is_empty());
+    }
+
+    #[test]
+    fn book_castling_and_promotion() {
+        let mut game = ChessGame::new(Board::from_fen_string("4k3/1P6/8/8/8/8/8/4K2R").unwrap());
+        let rights = (crate::moves::CastlingRights { queenside: false, kingside: true },
+                      crate::moves::CastlingRights::NONE);
+        game.set_position(game.board().clone(), PlayerColor::White, rights, None).unwrap();
+        let key = game.polyglot_key();
+        let book = Book::from_entries(vec![
+            BookEntry { key, raw_move: raw_move("e1", "h1"), weight: 1, learn: 0 },
+            BookEntry { key, raw_move: raw_move("b7", "b8") | 4 << 12, weight: 2, learn: 0 },
+            BookEntry { key, raw_move: raw_move("b7", "b8"), weight: 3, learn: 0 },
+        ]);
+        assert_eq!(game.book_moves(&book), vec![
+            (ChessMove::from_coords("e1", "g1").unwrap(), 1),
+            (ChessMove::promoting("b7".parse().unwrap(), "b8".parse().unwrap(),
+                                  PromotionType::Queen), 2),
+        ]);
+        assert_eq!(decode_move(raw_move("a1", "a2") | 5 << 12), None);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn book_pick_weighted() {
+        use rand::SeedableRng;
+        let book = test_book();
+        let game = ChessGame::new(Board::default_board());
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let picks: Vec<ChessMove> = (0..300)
+            .map(|_| book.pick_weighted(&game, &mut rng).unwrap())
+            .collect();
+        let e4 = picks.iter()
+            .filter(|&&chess_move| chess_move == ChessMove::from_coords("e2", "e4").unwrap())
+            .count();
+        assert!((150..250).contains(&e4));
+        assert!(picks.contains(&ChessMove::from_coords("d2", "d4").unwrap()));
+    }
+}
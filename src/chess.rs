@@ -10,33 +10,145 @@
 //! - [game_status](ChessGame::game_status): Returns the current [status](GameStatus) of the game.
 //! - [active_player](ChessGame::active_player): Returns which player's turn it is.
 //!
-//! Also see [ChessGame::new] for creating a new [ChessGame] object.
+//! Also see [ChessGame::new] for creating a new [ChessGame] object, and the [observer] module for
+//! reacting to game events as they happen.
 
-use std::fmt::{Display, Formatter};
+pub mod analysis;
+mod control;
+mod explore;
+pub mod crazyhouse;
+pub mod fog;
+pub mod handicap;
+pub mod observer;
+#[cfg(feature = "std")]
+pub mod shared;
+pub mod series;
+pub mod variant;
+mod integrity;
+mod make_unmake;
+mod notation;
+mod playout;
+mod premove;
+mod repetition;
+mod statistics;
+mod takeback;
+mod termination;
+mod timeout;
+#[cfg(feature = "rand")]
+mod random;
+
+pub use control::ControlMap;
+pub use integrity::IntegrityError;
+pub use playout::PlayOutError;
+pub use statistics::MoveStatistics;
+pub use takeback::TakebackRequest;
+pub use termination::{GameTermination, FIFTY_MOVE_PLIES};
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::fmt::{Display, Formatter};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
-use crate::board::Board;
+use crate::board::{Board, PositionError};
+use crate::board::board_pos;
 use crate::board::board_pos::BoardPosition;
-use crate::board::piece::PlayerColor;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::eval::PieceValues;
 use crate::moves;
-use crate::moves::{CastlingRights, ChessMove, MoveContext, MoveResult};
+use crate::moves::{CastlingRights, ChessMove, MoveContext, MoveResult, PieceMovement,
+                   PromotionType};
 use crate::moves::util::BoardBitmap;
+use crate::util::U3;
+use crate::chess::analysis::MoveAnalysis;
+use crate::chess::crazyhouse::Hand;
+use crate::chess::handicap::Handicap;
+use crate::chess::observer::{GameObserver, ObserverSlot};
+use crate::chess::takeback::UndoState;
+use crate::chess::variant::Variant;
+#[cfg(feature = "std")]
+use crate::search::cache::AnalysisCache;
 
 /// A valid reason for a chess game to end in a draw.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum DrawReason {
     Stalemate,
     DrawByAgreement,
+    /// Both players were left with only their king in [Shatranj](Variant::Shatranj).
+    BareKings,
+    /// An arbiter declared the game drawn, see [ChessGame::adjudicate].
+    Adjudication,
+    /// A player ran out of time, but the other player could not have checkmated them. See
+    /// [ChessGame::flag].
+    TimeoutVsInsufficientMaterial,
+    /// A player claimed a draw after fifty moves of each player without a capture or a pawn
+    /// move, see [ChessGame::claim_fifty_move_draw].
+    FiftyMoveRule,
 }
 
 /// A valid reason for a chess game to end in a win for either player.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum WinReason {
     Checkmate,
     Resignation,
+    /// The player's king reached the center in [King of the Hill](Variant::KingOfTheHill).
+    KingInCenter,
+    /// The other player lost all of their pieces in [Horde](Variant::Horde).
+    AllPiecesCaptured,
+    /// The other player was stalemated in [Shatranj](Variant::Shatranj).
+    Stalemate,
+    /// The other player was left with only their king in [Shatranj](Variant::Shatranj).
+    BareKing,
+    /// An arbiter declared the player the winner of a game in progress, see
+    /// [ChessGame::adjudicate].
+    Adjudication,
+    /// An arbiter declared the player the winner before any move was made, for example because
+    /// the other player did not show up. See [ChessGame::adjudicate].
+    Forfeit,
+    /// The other player reached the [illegal move limit](ChessGame::set_illegal_move_limit).
+    IllegalMoveForfeit,
+    /// The other player ran out of time, see [ChessGame::flag].
+    Timeout,
+}
+
+/// The result of a game which has ended, without the reason it ended. See
+/// [GameStatus::outcome].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    /// The game ended in a win for the player.
+    Win(PlayerColor),
+    /// The game ended in a draw.
+    Draw,
+}
+
+/// The result an arbiter declares for a game, see [ChessGame::adjudicate].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AdjudicatedResult {
+    /// The game is declared won for the player.
+    WinFor(PlayerColor),
+    /// The game is declared drawn.
+    Draw,
+}
+
+impl Outcome {
+    /// returns: The points scored by the player: 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn score(&self, player: PlayerColor) -> f64 {
+        match self {
+            Outcome::Win(winner) if *winner == player => 1.0,
+            Outcome::Win(_) => 0.0,
+            Outcome::Draw => 0.5,
+        }
+    }
 }
 
 /// The status of a given chess game.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum GameStatus {
     /// No player has made a move yet.
     NotYetStarted,
@@ -48,13 +160,69 @@ pub enum GameStatus {
     Win(PlayerColor, WinReason),
 }
 
+impl GameStatus {
+    /// returns: Whether the game has ended, either in a draw or in a win for one of the players.
+    pub fn is_over(&self) -> bool {
+        self.is_draw() || self.is_win()
+    }
+
+    /// returns: Whether the game has ended in a draw.
+    pub fn is_draw(&self) -> bool {
+        matches!(self, GameStatus::Draw(..))
+    }
+
+    /// returns: Whether the game has ended in a win for one of the players.
+    pub fn is_win(&self) -> bool {
+        matches!(self, GameStatus::Win(..))
+    }
+
+    /// returns: `Some(PlayerColor)` of the player who won the game, or `None` if the game has not
+    /// ended in a win.
+    pub fn winner(&self) -> Option<PlayerColor> {
+        match self {
+            GameStatus::Win(player, _) => Some(*player),
+            _ => None,
+        }
+    }
+
+    /// returns: `Some(WinReason)` if the game has ended in a win, otherwise `None`.
+    pub fn win_reason(&self) -> Option<WinReason> {
+        match self {
+            GameStatus::Win(_, reason) => Some(*reason),
+            _ => None,
+        }
+    }
+
+    /// returns: `Some(Outcome)` of the game if it has ended, otherwise `None`.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self {
+            GameStatus::Win(player, _) => Some(Outcome::Win(*player)),
+            GameStatus::Draw(_) => Some(Outcome::Draw),
+            GameStatus::NotYetStarted | GameStatus::Normal => None,
+        }
+    }
+
+    /// returns: `Some(DrawReason)` if the game has ended in a draw, otherwise `None`.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        match self {
+            GameStatus::Draw(reason) => Some(*reason),
+            _ => None,
+        }
+    }
+}
+
 impl Display for GameStatus {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let string = match self {
             GameStatus::NotYetStarted => "Game not yet started",
             GameStatus::Normal => "Normal play",
             GameStatus::Draw(DrawReason::Stalemate) => "Draw by stalemate",
             GameStatus::Draw(DrawReason::DrawByAgreement) => "Draw by agreement",
+            GameStatus::Draw(DrawReason::BareKings) => "Draw by bare kings",
+            GameStatus::Draw(DrawReason::Adjudication) => "Draw by adjudication",
+            GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+                => "Draw by timeout vs insufficient material",
+            GameStatus::Draw(DrawReason::FiftyMoveRule) => "Draw by the fifty-move rule",
             GameStatus::Win(PlayerColor::White, WinReason::Checkmate)
                 => "White won by checkmate",
             GameStatus::Win(PlayerColor::White, WinReason::Resignation)
@@ -63,11 +231,136 @@ impl Display for GameStatus {
                 => "Black won by checkmate",
             GameStatus::Win(PlayerColor::Black, WinReason::Resignation)
                 => "Black won by resignation",
+            GameStatus::Win(PlayerColor::White, WinReason::KingInCenter)
+                => "White won by king in the center",
+            GameStatus::Win(PlayerColor::Black, WinReason::KingInCenter)
+                => "Black won by king in the center",
+            GameStatus::Win(PlayerColor::White, WinReason::AllPiecesCaptured)
+                => "White won by capturing all pieces",
+            GameStatus::Win(PlayerColor::Black, WinReason::AllPiecesCaptured)
+                => "Black won by capturing all pieces",
+            GameStatus::Win(PlayerColor::White, WinReason::Stalemate)
+                => "White won by stalemate",
+            GameStatus::Win(PlayerColor::Black, WinReason::Stalemate)
+                => "Black won by stalemate",
+            GameStatus::Win(PlayerColor::White, WinReason::BareKing)
+                => "White won by baring the king",
+            GameStatus::Win(PlayerColor::Black, WinReason::BareKing)
+                => "Black won by baring the king",
+            GameStatus::Win(PlayerColor::White, WinReason::Adjudication)
+                => "White won by adjudication",
+            GameStatus::Win(PlayerColor::Black, WinReason::Adjudication)
+                => "Black won by adjudication",
+            GameStatus::Win(PlayerColor::White, WinReason::Forfeit)
+                => "White won by forfeit",
+            GameStatus::Win(PlayerColor::Black, WinReason::Forfeit)
+                => "Black won by forfeit",
+            GameStatus::Win(PlayerColor::White, WinReason::IllegalMoveForfeit)
+                => "White won by illegal moves of the opponent",
+            GameStatus::Win(PlayerColor::Black, WinReason::IllegalMoveForfeit)
+                => "Black won by illegal moves of the opponent",
+            GameStatus::Win(PlayerColor::White, WinReason::Timeout) => "White won on time",
+            GameStatus::Win(PlayerColor::Black, WinReason::Timeout) => "Black won on time",
         };
         write!(f, "{}", string)
     }
 }
 
+/// Details about a move which has been performed, as returned by [ChessGame::do_move].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MoveDetails {
+    /// The move that was performed.
+    pub chess_move: ChessMove,
+    /// The piece that was moved, before any promotion.
+    pub piece: Piece,
+    /// The piece that was captured by the move, if any.
+    pub captured_piece: Option<Piece>,
+    /// The movement of the rook if the move was a castling move, otherwise `None`.
+    pub secondary_movement: Option<PieceMovement>,
+    /// The piece that was captured by the move together with the square it was removed from,
+    /// which differs from the destination square for captures en passant.
+    pub removed_piece: Option<(BoardPosition, Piece)>,
+    /// The engine analysis of the move, if it has been added with
+    /// [annotate_history](ChessGame::annotate_history).
+    pub analysis: Option<MoveAnalysis>,
+    /// Whether the move was a [drop](crazyhouse::DropMove) of `piece` in Crazyhouse, in which
+    /// case `chess_move` is the drop, see [ChessMove::dropping].
+    pub is_drop: bool,
+}
+
+impl MoveDetails {
+    /// returns: The details of a move which is not a drop, given the result of performing it.
+    fn of_move(chess_move: ChessMove, piece: Piece, move_result: &MoveResult) -> MoveDetails {
+        MoveDetails {
+            chess_move,
+            piece,
+            captured_piece: move_result.removed_piece.map(|(_, piece)| piece),
+            secondary_movement: move_result.secondary_movement,
+            removed_piece: move_result.removed_piece,
+            analysis: None,
+            is_drop: false,
+        }
+    }
+}
+
+/// A legal destination square for a piece, together with the kind of move it is. See
+/// [ChessGame::move_targets] and [ChessGame::drop_targets].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MoveTarget {
+    /// The destination square.
+    pub to: BoardPosition,
+    /// Whether the move captures a piece, including captures en passant.
+    pub is_capture: bool,
+    /// Whether the move is a pawn promotion, which requires a [PromotionType] to be performed.
+    ///
+    /// [PromotionType]: crate::moves::PromotionType
+    pub is_promotion: bool,
+    /// Whether the move is a castling move, which also moves a rook.
+    pub is_castling: bool,
+    /// Whether the move captures a pawn en passant, removing it from a square other than `to`.
+    pub is_en_passant: bool,
+}
+
+/// The squares a board widget typically highlights in the current position. See
+/// [ChessGame::highlight_info].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HighlightInfo {
+    /// The origin and destination squares of the last move, if any move has been made.
+    pub last_move: Option<PieceMovement>,
+    /// The square of the active player's king if it is in check, otherwise `None`.
+    pub king_in_check: Option<BoardPosition>,
+    /// The squares of the pieces giving check, in square index order. Empty if the active player
+    /// is not in check.
+    pub checkers: Vec<BoardPosition>,
+}
+
+/// The legal moves getting the active player out of check, classified by how they resolve the
+/// check. See [ChessGame::check_resolutions].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckResolutions {
+    /// The squares the king can move to, including by capturing the checking piece.
+    pub king_moves: BoardBitmap,
+    /// The moves of other pieces capturing the checking piece, including captures en passant, in
+    /// the [canonical order](ChessMove#canonical-order).
+    pub capturing_moves: Vec<ChessMove>,
+    /// The moves of other pieces to a square between the checking piece and the king, in the
+    /// [canonical order](ChessMove#canonical-order).
+    pub blocking_moves: Vec<ChessMove>,
+}
+
+/// The position a game's move history starts from.
+#[derive(Clone, Debug)]
+struct StartingPosition {
+    board: Board,
+    active_player: PlayerColor,
+    castling_rights: (CastlingRights, CastlingRights),
+    en_passant_target: Option<BoardPosition>,
+    extra_moves: u32,
+    /// The halfmove clock and the fullmove number of the position, as given in its FEN string.
+    halfmove_clock: usize,
+    fullmove_number: usize,
+}
+
 /// Represents a chess game played according to the standard chess rules. See
 /// [the module documentation](self) for more information.
 #[derive(Clone, Debug)]
@@ -79,11 +372,44 @@ pub struct ChessGame {
     available_moves: [[BoardBitmap; 8]; 8],
     castling_rights: (CastlingRights, CastlingRights),
     en_passant_target: Option<BoardPosition>,
+
+    starting_position: StartingPosition,
+    history: Vec<MoveDetails>,
+    observer: ObserverSlot,
+    variant: Variant,
+    hands: (Hand, Hand),
+    promoted: BoardBitmap,
+    handicap: Option<(Handicap, PlayerColor)>,
+    extra_moves: u32,
+    draw_odds: Option<PlayerColor>,
+    termination: Option<String>,
+    game_termination: Option<GameTermination>,
+    /// The Polyglot keys of the positions since the last irreversible move, oldest first, not
+    /// including the current position.
+    position_keys: Vec<u64>,
+    /// The state before each move in the history which is needed to take the move back.
+    undo_stack: Vec<UndoState>,
+    time_source: fn() -> Option<Duration>,
+    illegal_move_limit: Option<u32>,
+    reset_illegal_moves: bool,
+    illegal_moves: (u32, u32),
+    takeback_request: Option<TakebackRequest>,
+    premoves: (Option<ChessMove>, Option<ChessMove>),
+    piece_values: PieceValues,
+    #[cfg(feature = "std")]
+    pub(crate) analysis_cache: Option<Arc<Mutex<AnalysisCache>>>,
 }
 
+// a game may be shared between threads, see [shared::SharedChessGame]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ChessGame>();
+};
+
 /// An error caused by attempting to perform an illegal move or other invalid operation on a
-/// [ChessGame] object.
-#[derive(Error, Debug)]
+/// [ChessGame] object. The errors caused by a move carry the move, which is written in UCI
+/// coordinate notation in the error messages.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ChessError {
     /// The game has not been started yet.
     #[error("game not started")]
@@ -91,42 +417,389 @@ pub enum ChessError {
     /// The game has already ended.
     #[error("game has already ended")]
     GameAlreadyEnded,
-    /// An illegal move was attempted.
-    #[error("illegal move")]
-    IllegalMove,
+    /// An illegal move was attempted. An illegal [drop](crazyhouse::DropMove) is given as a
+    /// move from and to the drop square, like in the [history](ChessGame::history).
+    #[error("illegal move {0}")]
+    IllegalMove(ChessMove),
     /// A move involving moving the other player's piece was attempted.
-    #[error("it is the other player's turn")]
-    WrongTurn,
+    #[error("cannot move {0}: it is the other player's turn")]
+    WrongTurn(ChessMove),
     /// `None` was passed as promotion type, when the move was in fact a promotion move. See
     /// [do_move](ChessGame::do_move).
-    #[error("missing promotion type")]
-    MissingPromotionType,
+    #[error("missing promotion type for {0}")]
+    MissingPromotionType(ChessMove),
     /// `Some(PromotionType` was passed, when the move was in fact not a promotion move. See
     /// [do_move](ChessGame::do_move).
-    #[error("expected `None` as promotion type: move is not a promotion move")]
-    UnexpectedPromotionType,
+    #[error("expected `None` as promotion type: {0} is not a promotion move")]
+    UnexpectedPromotionType(ChessMove),
+    /// A takeback was requested for no moves or for more moves than have been played, or a
+    /// takeback request was answered when there was none or by the requesting player. See
+    /// [request_takeback](ChessGame::request_takeback).
+    #[error("invalid takeback")]
+    InvalidTakeback,
+    /// A premove was queued on the player's own turn, or is not pseudo-legal. See
+    /// [queue_premove](ChessGame::queue_premove).
+    #[error("invalid premove {0}")]
+    InvalidPremove(ChessMove),
+    /// A draw was claimed when the rule it was claimed by did not apply. See
+    /// [claim_fifty_move_draw](ChessGame::claim_fifty_move_draw).
+    #[error("invalid draw claim")]
+    InvalidDrawClaim,
+}
+
+impl ChessError {
+    /// returns: A numeric code identifying the kind of error, for example for mapping errors to
+    ///          responses over FFI or HTTP. The codes are stable: a code is never reused for a
+    ///          different kind of error.
+    ///
+    /// | Error                                                          | Code |
+    /// |----------------------------------------------------------------|------|
+    /// | [GameNotStarted](ChessError::GameNotStarted)                   | 1    |
+    /// | [GameAlreadyEnded](ChessError::GameAlreadyEnded)               | 2    |
+    /// | [IllegalMove](ChessError::IllegalMove)                         | 3    |
+    /// | [WrongTurn](ChessError::WrongTurn)                             | 4    |
+    /// | [MissingPromotionType](ChessError::MissingPromotionType)       | 5    |
+    /// | [UnexpectedPromotionType](ChessError::UnexpectedPromotionType) | 6    |
+    /// | [InvalidTakeback](ChessError::InvalidTakeback)                 | 7    |
+    /// | [InvalidPremove](ChessError::InvalidPremove)                   | 8    |
+    /// | [InvalidDrawClaim](ChessError::InvalidDrawClaim)               | 9    |
+    pub fn code(&self) -> u16 {
+        match self {
+            ChessError::GameNotStarted => 1,
+            ChessError::GameAlreadyEnded => 2,
+            ChessError::IllegalMove(_) => 3,
+            ChessError::WrongTurn(_) => 4,
+            ChessError::MissingPromotionType(_) => 5,
+            ChessError::UnexpectedPromotionType(_) => 6,
+            ChessError::InvalidTakeback => 7,
+            ChessError::InvalidPremove(_) => 8,
+            ChessError::InvalidDrawClaim => 9,
+        }
+    }
+
+    /// returns: The move which caused the error, or `None` if the error was not caused by a move.
+    pub fn chess_move(&self) -> Option<ChessMove> {
+        match self {
+            ChessError::GameNotStarted | ChessError::GameAlreadyEnded
+            | ChessError::InvalidTakeback | ChessError::InvalidDrawClaim => None,
+            ChessError::IllegalMove(chess_move)
+            | ChessError::WrongTurn(chess_move)
+            | ChessError::MissingPromotionType(chess_move)
+            | ChessError::UnexpectedPromotionType(chess_move)
+            | ChessError::InvalidPremove(chess_move) => Some(*chess_move),
+        }
+    }
+
+    /// returns: The square of the piece the move causing the error tried to move, or `None` if
+    ///          the error was not caused by a move.
+    pub fn square(&self) -> Option<BoardPosition> {
+        self.chess_move().map(|chess_move| chess_move.piece_movement.from)
+    }
+}
+
+/// The reason a string could not be interpreted as a legal move in a given notation. See
+/// [ChessGame::parse_san] and [ChessGame::parse_coordinate_move].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NotationError {
+    /// The string is not a move in the notation.
+    #[error("invalid syntax")]
+    InvalidSyntax,
+    /// No legal move in the current position matches the string.
+    #[error("no legal move matches")]
+    IllegalMove,
+    /// Several legal moves in the current position match the string.
+    #[error("ambiguous move")]
+    AmbiguousMove,
+}
+
+/// An error caused by a FEN string which does not describe a valid position. See
+/// [ChessGame::from_fen].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FenError {
+    /// The string does not follow the FEN syntax.
+    #[error("invalid FEN syntax")]
+    InvalidSyntax,
+    /// The string follows the FEN syntax, but the position is not valid.
+    #[error(transparent)]
+    InvalidPosition(#[from] PositionError),
+}
+
+/// An error caused by attempting to perform a move given as a string. See
+/// [ChessGame::try_move_str].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MoveInputError {
+    /// The string could not be interpreted as a legal move in any supported notation.
+    #[error("could not interpret move (as SAN: {san}; as coordinates: {coordinates})")]
+    Uninterpretable {
+        san: NotationError,
+        coordinates: NotationError,
+    },
+    /// The string was interpreted as a move, but the move could not be performed.
+    #[error(transparent)]
+    Game(#[from] ChessError),
+}
+
+/// returns: `Ok(())` if each player's king and rooks are on their starting squares for the
+///          sides they may castle on, otherwise
+///          [InvalidCastlingRights](PositionError::InvalidCastlingRights).
+fn check_castling_rights(board: &Board, castling_rights: (CastlingRights, CastlingRights))
+                         -> Result<(), PositionError>
+{
+    for (player, rights) in [(PlayerColor::White, castling_rights.0),
+                             (PlayerColor::Black, castling_rights.1)] {
+        let possible = possible_castling_rights(board, player);
+        if rights.queenside && !possible.queenside || rights.kingside && !possible.kingside {
+            return Err(PositionError::InvalidCastlingRights(player));
+        }
+    }
+    Ok(())
+}
+
+/// returns: The castling rights on the sides where the player's king and rook are on their
+///          starting squares.
+fn possible_castling_rights(board: &Board, player: PlayerColor) -> CastlingRights {
+    let rank = player.back_rank();
+    let has_piece = |file: u8, piece_type: PieceType| {
+        let pos = BoardPosition { file: U3::from_low_bits(file), rank };
+        board.get_piece(pos) == Some(Piece::new(piece_type, player))
+    };
+    let has_king = has_piece(4, PieceType::King);
+    CastlingRights {
+        queenside: has_king && has_piece(0, PieceType::Rook),
+        kingside: has_king && has_piece(7, PieceType::Rook),
+    }
+}
+
+/// returns: `Ok(())` if the en passant target lies behind a pawn of the player who is not to
+///          move, which could have just made a double move in the variant, otherwise
+///          [InvalidEnPassantTarget](PositionError::InvalidEnPassantTarget).
+fn check_en_passant_target(board: &Board, active_player: PlayerColor, target: BoardPosition,
+                           variant: Variant) -> Result<(), PositionError>
+{
+    let other_player = active_player.other_player();
+    let direction = other_player.pawn_direction();
+    let valid = board.get_piece(target).is_none()
+        && target.add((0, -direction)).is_some_and(|pos| {
+            board.get_piece(pos).is_none() && moves::has_double_move(other_player, pos, variant)
+        })
+        && target.add((0, direction)).is_some_and(|pos| {
+            board.get_piece(pos) == Some(Piece::new(PieceType::Pawn, other_player))
+        });
+    if !valid {
+        return Err(PositionError::InvalidEnPassantTarget(target));
+    }
+    Ok(())
+}
+
+/// Formats the game as its [FEN string](ChessGame::fen), padded to the width and alignment
+/// options if given, such as `{:>60}`. The alternate flag (`{:#}`) instead formats the board with
+/// Unicode pieces and shaded squares (see the [Display] implementation of [Board]), followed by
+/// the FEN string on a line of its own.
+impl Display for ChessGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#}\n{}", self.board, self.fen());
+        }
+        f.pad(&self.fen())
+    }
 }
 
 impl ChessGame {
-    /// returns: A new [ChessGame] object with the given starting board configuration.
+    /// returns: A new [ChessGame] object with the given starting board configuration. Each player
+    ///          may castle on the sides where their king and rook are on their starting squares.
     pub fn new(starting_board: Board) -> ChessGame {
+        let castling_rights = (possible_castling_rights(&starting_board, PlayerColor::White),
+                               possible_castling_rights(&starting_board, PlayerColor::Black));
         let mut game = ChessGame {
             game_status: GameStatus::NotYetStarted,
             active_player: PlayerColor::White,
-            board: starting_board,
+            board: starting_board.clone(),
             available_moves: [[BoardBitmap::all_zeros(); 8]; 8],
-            castling_rights: (CastlingRights::default(), CastlingRights::default()),
+            castling_rights,
             en_passant_target: None,
+            starting_position: StartingPosition {
+                board: starting_board,
+                active_player: PlayerColor::White,
+                castling_rights,
+                en_passant_target: None,
+                extra_moves: 0,
+                halfmove_clock: 0,
+                fullmove_number: 1,
+            },
+            history: Vec::new(),
+            observer: ObserverSlot::default(),
+            variant: Variant::Standard,
+            hands: (Hand::new(), Hand::new()),
+            promoted: BoardBitmap::all_zeros(),
+            handicap: None,
+            extra_moves: 0,
+            draw_odds: None,
+            termination: None,
+            game_termination: None,
+            position_keys: Vec::new(),
+            undo_stack: Vec::new(),
+            time_source: termination::system_time,
+            illegal_move_limit: None,
+            reset_illegal_moves: false,
+            illegal_moves: (0, 0),
+            takeback_request: None,
+            premoves: (None, None),
+            piece_values: PieceValues::default(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
         };
         game.recalculate_available_moves();
         game
     }
 
+    /// Instantiate a new chess game like [ChessGame::new], played with the rules of the given
+    /// [Variant]. In [Horde](Variant::Horde), white has no castling rights, and in
+    /// [Shatranj](Variant::Shatranj), neither player has castling rights.
+    pub fn with_variant(starting_board: Board, variant: Variant) -> ChessGame {
+        let mut game = ChessGame::new(starting_board);
+        game.variant = variant;
+        match variant {
+            Variant::Horde => game.castling_rights.0 = CastlingRights::NONE,
+            Variant::Shatranj => {
+                game.castling_rights = (CastlingRights::NONE, CastlingRights::NONE);
+            }
+            _ => {}
+        }
+        game.starting_position.castling_rights = game.castling_rights;
+        game.recalculate_available_moves();
+        game
+    }
+
+    /// returns: The [Variant] of the rules the game is played with.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
     /// returns: The current game status. See [GameStatus].
     pub fn game_status(&self) -> &GameStatus {
         &self.game_status
     }
 
+    /// returns: `Some(Outcome)` of the game on the board if it has ended, otherwise `None`. See
+    ///          [match_outcome](ChessGame::match_outcome) for the outcome with draw odds applied.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.game_status.outcome()
+    }
+
+    /// Gives draw odds to a player, as in Armageddon tiebreak games where a draw counts as a win
+    /// for black. The [game status](ChessGame::game_status), [outcome](ChessGame::outcome) and
+    /// PGN result still give the result on the board, while the
+    /// [match outcome](ChessGame::match_outcome) counts a draw as a win for the player.
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player with draw odds, or `None` to remove draw odds.
+    pub fn set_draw_odds(&mut self, player: Option<PlayerColor>) {
+        self.draw_odds = player;
+    }
+
+    /// returns: `Some(PlayerColor)` of the player with draw odds, see
+    ///          [set_draw_odds](ChessGame::set_draw_odds), or `None` if no player has draw odds.
+    pub fn draw_odds(&self) -> Option<PlayerColor> {
+        self.draw_odds
+    }
+
+    /// returns: `Some(Outcome)` of the game for the purpose of a match if it has ended, which is
+    ///          the [outcome](ChessGame::outcome) except that a draw is a win for the player with
+    ///          [draw odds](ChessGame::set_draw_odds), if any. Otherwise `None`.
+    pub fn match_outcome(&self) -> Option<Outcome> {
+        match (self.outcome()?, self.draw_odds) {
+            (Outcome::Draw, Some(player)) => Some(Outcome::Win(player)),
+            (outcome, _) => Some(outcome),
+        }
+    }
+
+    /// Sets the number of illegal move attempts after which a player forfeits the game, for
+    /// servers which do not trust their clients. Every error returned by
+    /// [do_move](ChessGame::do_move) or [do_drop](ChessGame::do_drop), except
+    /// [GameAlreadyEnded](ChessError::GameAlreadyEnded), counts as an attempt of the active
+    /// player, including an attempt to move a piece of the other player. When a player's count
+    /// reaches the limit, the other player wins by
+    /// [IllegalMoveForfeit](WinReason::IllegalMoveForfeit). By default there is no limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit`: The number of attempts a player forfeits after, or `None` for no limit.
+    pub fn set_illegal_move_limit(&mut self, limit: Option<u32>) {
+        self.illegal_move_limit = limit;
+    }
+
+    /// returns: The limit set with [set_illegal_move_limit](ChessGame::set_illegal_move_limit),
+    ///          or `None` if there is no limit.
+    pub fn illegal_move_limit(&self) -> Option<u32> {
+        self.illegal_move_limit
+    }
+
+    /// Sets whether a player's count of [illegal move attempts](ChessGame::illegal_moves) is reset
+    /// to zero when the player makes a legal move, so that only attempts in a row count towards
+    /// the [limit](ChessGame::set_illegal_move_limit). By default the attempts are counted for
+    /// the whole game.
+    ///
+    /// # Arguments
+    ///
+    /// * `reset`: Whether to reset the count after a legal move.
+    pub fn set_illegal_move_reset(&mut self, reset: bool) {
+        self.reset_illegal_moves = reset;
+    }
+
+    /// Sets the values of the pieces used to count material when the game is
+    /// [evaluated](ChessGame::evaluate), by the [search](crate::search) and in
+    /// [static exchanges](crate::search::static_exchange). By default the classic values are
+    /// used, see [PieceValues::default].
+    ///
+    /// # Arguments
+    ///
+    /// * `values`: The values of the pieces.
+    pub fn set_piece_values(&mut self, values: PieceValues) {
+        self.piece_values = values;
+    }
+
+    /// returns: The piece values set with [set_piece_values](ChessGame::set_piece_values).
+    pub fn piece_values(&self) -> &PieceValues {
+        &self.piece_values
+    }
+
+    /// returns: The number of illegal move attempts counted for the player, see
+    ///          [set_illegal_move_limit](ChessGame::set_illegal_move_limit). Attempts are counted
+    ///          even when there is no limit.
+    pub fn illegal_moves(&self, player: PlayerColor) -> u32 {
+        match player {
+            PlayerColor::White => self.illegal_moves.0,
+            PlayerColor::Black => self.illegal_moves.1,
+        }
+    }
+
+    /// Counts the result of a move attempt of the player towards the
+    /// [illegal move limit](ChessGame::set_illegal_move_limit), ending the game if the limit is
+    /// reached. A performed move voids the [pending takeback](ChessGame::pending_takeback).
+    fn after_move_attempt(&mut self, player: PlayerColor,
+                          result: &Result<MoveDetails, ChessError>)
+    {
+        let count = match player {
+            PlayerColor::White => &mut self.illegal_moves.0,
+            PlayerColor::Black => &mut self.illegal_moves.1,
+        };
+        if result.is_ok() {
+            self.takeback_request = None;
+        }
+        match result {
+            Ok(_) if self.reset_illegal_moves => *count = 0,
+            Ok(_) | Err(ChessError::GameAlreadyEnded) => {}
+            Err(_) => {
+                *count = count.saturating_add(1);
+                if self.illegal_move_limit.is_some_and(|limit| *count >= limit) {
+                    self.set_game_status(GameStatus::Win(player.other_player(),
+                                                         WinReason::IllegalMoveForfeit));
+                }
+            }
+        }
+    }
+
     /// returns: Whose turn it is.
     pub fn active_player(&self) -> PlayerColor {
         self.active_player
@@ -137,6 +810,296 @@ impl ChessGame {
         &self.board
     }
 
+    /// returns: The moves performed in this game so far, in the order they were played, starting
+    ///          from the [starting position](ChessGame::starting_position).
+    pub fn history(&self) -> &[MoveDetails] {
+        &self.history
+    }
+
+    /// returns: The last move performed, or `None` if no moves have been made since the
+    ///          [starting position](ChessGame::starting_position).
+    pub fn last_move(&self) -> Option<&MoveDetails> {
+        self.history.last()
+    }
+
+    /// returns: Whether the active player is in check.
+    pub fn is_check(&self) -> bool {
+        moves::is_in_check(&self.board, self.active_player, self.variant)
+    }
+
+    /// returns: Whether the move is legal and puts the other player in check.
+    pub fn gives_check(&self, chess_move: ChessMove) -> bool {
+        let PieceMovement { from, to } = chess_move.piece_movement;
+        let mut board = self.board.clone();
+        if let Some(drop) = crazyhouse::DropMove::from_move(chess_move) {
+            board.set_piece(to, Some(Piece::new(drop.piece, self.active_player)));
+            return self.is_legal_drop(drop)
+                && moves::is_in_check(&board, self.active_player.other_player(), self.variant);
+        }
+        if self.game_status.is_over() || !self.available_moves(from).get(to) {
+            return false;
+        }
+        moves::do_move(&mut board, self.active_player, chess_move, self.move_context()).is_ok()
+            && moves::is_in_check(&board, self.active_player.other_player(), self.variant)
+    }
+
+    /// returns: The square of the given player's king, or `None` if the player has no king.
+    pub fn king_position(&self, player: PlayerColor) -> Option<BoardPosition> {
+        moves::find_king(&self.board, player)
+    }
+
+    /// returns: A [BoardBitmap] of the squares of the pieces giving check to the active player.
+    ///          Empty if the active player is not in check.
+    pub fn checkers(&self) -> BoardBitmap {
+        match self.king_position(self.active_player) {
+            Some(pos) => moves::attacking_pieces_in_variant(&self.board, self.active_player, pos,
+                                                            self.variant),
+            None => BoardBitmap::all_zeros(),
+        }
+    }
+
+    /// Collects the squares a board widget typically highlights: the last move, and the king in
+    /// check together with the pieces giving check. See [HighlightInfo].
+    pub fn highlight_info(&self) -> HighlightInfo {
+        let checker_bitmap = self.checkers();
+        let checkers: Vec<BoardPosition> = BoardPosition::all()
+            .filter(|&pos| checker_bitmap.get(pos))
+            .collect();
+        let king_in_check = if checkers.is_empty() {
+            None
+        } else {
+            self.king_position(self.active_player)
+        };
+        HighlightInfo {
+            last_move: self.last_move().map(|details| details.chess_move.piece_movement),
+            king_in_check,
+            checkers,
+        }
+    }
+
+    /// Classifies the legal moves of the active player when in check by how they resolve the
+    /// check: moving the king, capturing the checking piece, or blocking the line between a
+    /// checking slider and the king. In double check, only the king can move, so the capturing
+    /// and blocking moves are always empty. [Drops](crazyhouse::DropMove) are not included.
+    ///
+    /// returns: The [CheckResolutions] of the current position, which are all empty if the
+    ///          active player is not in check. The moves are in the same order as in
+    ///          [legal_moves](ChessGame::legal_moves).
+    pub fn check_resolutions(&self) -> CheckResolutions {
+        let mut resolutions = CheckResolutions {
+            king_moves: BoardBitmap::all_zeros(),
+            capturing_moves: Vec::new(),
+            blocking_moves: Vec::new(),
+        };
+        let checkers = self.checkers();
+        let Some(king) = self.king_position(self.active_player) else {
+            return resolutions;
+        };
+        if checkers.is_all_zeros() {
+            return resolutions;
+        }
+        resolutions.king_moves = self.available_moves(king);
+        let mut checker_squares = BoardPosition::all().filter(|&pos| checkers.get(pos));
+        let (Some(checker), None) = (checker_squares.next(), checker_squares.next()) else {
+            // double check
+            return resolutions;
+        };
+        let blocking_squares = board_pos::between(checker, king);
+        for chess_move in self.legal_moves() {
+            let PieceMovement { from, to } = chess_move.piece_movement;
+            if from == king {
+                continue;
+            }
+            let is_en_passant_capture = Some(to) == self.en_passant_target
+                && self.board.get_piece(from).is_some_and(|piece| piece.piece_type == PieceType::Pawn)
+                && to.add((0, -self.active_player.pawn_direction())) == Some(checker);
+            if to == checker || is_en_passant_capture {
+                resolutions.capturing_moves.push(chess_move);
+            } else if blocking_squares.get(to) {
+                resolutions.blocking_moves.push(chess_move);
+            }
+        }
+        resolutions
+    }
+
+    /// returns: A new game in the position this game's [history](ChessGame::history) starts
+    ///          from, that is, the position the game was created with, or the position last set
+    ///          with [set_position](ChessGame::set_position).
+    pub fn starting_position(&self) -> ChessGame {
+        let start = &self.starting_position;
+        let mut game = ChessGame::with_variant(start.board.clone(), self.variant);
+        game.active_player = start.active_player;
+        game.castling_rights = start.castling_rights;
+        game.en_passant_target = start.en_passant_target;
+        game.extra_moves = start.extra_moves;
+        game.starting_position.halfmove_clock = start.halfmove_clock;
+        game.starting_position.fullmove_number = start.fullmove_number;
+        game.handicap = self.handicap;
+        game.draw_odds = self.draw_odds;
+        game.illegal_move_limit = self.illegal_move_limit;
+        game.reset_illegal_moves = self.reset_illegal_moves;
+        game.piece_values = self.piece_values;
+        game.time_source = self.time_source;
+        game.recalculate_available_moves();
+        game.update_game_status();
+        game
+    }
+
+    /// Instantiates a new chess game like [ChessGame::new], starting from the position given by a
+    /// FEN string, such as `"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"`. The
+    /// halfmove clock and fullmove number fields may be omitted, in which case they are 0 and 1,
+    /// and otherwise the [halfmove clock](ChessGame::halfmove_clock) and the move numbers of the
+    /// game continue from them. The position is validated like in
+    /// [set_position](ChessGame::set_position).
+    ///
+    /// returns: The new game, or the [FenError] found while parsing the string.
+    pub fn from_fen(fen: &str) -> Result<ChessGame, FenError> {
+        ChessGame::from_fen_with_variant(fen, Variant::Standard)
+    }
+
+    /// Instantiates a new chess game like [from_fen](ChessGame::from_fen), with the rules of a
+    /// variant like [with_variant](ChessGame::with_variant). Crazyhouse hands can not be given
+    /// in the FEN string, so both hands start empty.
+    ///
+    /// returns: The new game, or the [FenError] found while parsing the string.
+    pub fn from_fen_with_variant(fen: &str, variant: Variant) -> Result<ChessGame, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let [placement, active_player, castling, en_passant, counters @ ..] = fields.as_slice()
+        else {
+            return Err(FenError::InvalidSyntax);
+        };
+        let (halfmove_clock, fullmove_number) = match counters {
+            [] => (0, 1),
+            [halfmove_clock, fullmove_number] => {
+                let halfmove_clock = halfmove_clock.parse()
+                    .map_err(|_| FenError::InvalidSyntax)?;
+                let fullmove_number: usize = fullmove_number.parse()
+                    .map_err(|_| FenError::InvalidSyntax)?;
+                // some programs write a fullmove number of 0 for the starting position
+                (halfmove_clock, fullmove_number.max(1))
+            }
+            _ => return Err(FenError::InvalidSyntax),
+        };
+        let board = Board::from_fen_string(placement).ok_or(FenError::InvalidSyntax)?;
+        let active_player = match *active_player {
+            "w" => PlayerColor::White,
+            "b" => PlayerColor::Black,
+            _ => return Err(FenError::InvalidSyntax),
+        };
+        let mut castling_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        if *castling != "-" {
+            for ch in castling.chars() {
+                let piece = Piece::try_from(ch).map_err(|_| FenError::InvalidSyntax)?;
+                let rights = match piece.player {
+                    PlayerColor::White => &mut castling_rights.0,
+                    PlayerColor::Black => &mut castling_rights.1,
+                };
+                match piece.piece_type {
+                    PieceType::King => rights.kingside = true,
+                    PieceType::Queen => rights.queenside = true,
+                    _ => return Err(FenError::InvalidSyntax),
+                }
+            }
+        }
+        let en_passant_target = match *en_passant {
+            "-" => None,
+            square => Some(square.parse().map_err(|_| FenError::InvalidSyntax)?),
+        };
+        let mut game = ChessGame::with_variant(Board::default_board(), variant);
+        game.set_position(board, active_player, castling_rights, en_passant_target)?;
+        game.set_starting_counters(halfmove_clock, fullmove_number);
+        Ok(game)
+    }
+
+    /// Sets the halfmove clock and the fullmove number of the starting position, which the
+    /// [halfmove clock](ChessGame::halfmove_clock) and the
+    /// [fullmove number](ChessGame::fullmove_number) of the game count on from.
+    pub(crate) fn set_starting_counters(&mut self, halfmove_clock: usize, fullmove_number: usize) {
+        self.starting_position.halfmove_clock = halfmove_clock;
+        self.starting_position.fullmove_number = fullmove_number;
+    }
+
+    /// returns: The number of the current full move, which starts at 1 (or at the number given
+    ///          to [from_fen](ChessGame::from_fen)) and is incremented after each move of black,
+    ///          saturating at [usize::MAX].
+    pub fn fullmove_number(&self) -> usize {
+        let plies = self.history.len()
+            + (self.starting_position.active_player == PlayerColor::Black) as usize;
+        self.starting_position.fullmove_number.saturating_add(plies / 2)
+    }
+
+    /// Returns the FEN string of the current position. The halfmove clock and the fullmove
+    /// number are counted on from those of the [starting position](ChessGame::starting_position),
+    /// which are 0 and 1 unless the game was created with [from_fen](ChessGame::from_fen).
+    ///
+    /// see: [Forsyth–Edwards Notation - Wikipedia](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation#Definition)
+    ///
+    /// returns: The FEN string of the current position.
+    pub fn fen(&self) -> String {
+        let active_player = match self.active_player {
+            PlayerColor::White => 'w',
+            PlayerColor::Black => 'b',
+        };
+        let mut castling = String::new();
+        for (rights, player) in [(self.castling_rights.0, PlayerColor::White),
+                                 (self.castling_rights.1, PlayerColor::Black)] {
+            if rights.kingside {
+                castling.push(Piece::new(PieceType::King, player).to_char());
+            }
+            if rights.queenside {
+                castling.push(Piece::new(PieceType::Queen, player).to_char());
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant_target = self.en_passant_target
+            .map_or("-".to_string(), |pos| pos.to_string());
+        format!("{} {} {} {} {} {}", self.board.to_fen_string(), active_player, castling,
+                en_passant_target, self.halfmove_clock(), self.fullmove_number())
+    }
+
+    /// Sets the observer which is notified of the events of this game, replacing the previous
+    /// observer, if any. See the [observer] module. Clones of the game do not share the observer.
+    ///
+    /// returns: The previous observer, if any.
+    pub fn set_observer(&mut self, observer: Box<dyn GameObserver>)
+                        -> Option<Box<dyn GameObserver>>
+    {
+        self.observer.set(Some(observer))
+    }
+
+    /// Removes the observer of this game, if any.
+    ///
+    /// returns: The removed observer, if any.
+    pub fn remove_observer(&mut self) -> Option<Box<dyn GameObserver>> {
+        self.observer.set(None)
+    }
+
+    pub(crate) fn set_game_status(&mut self, game_status: GameStatus) {
+        self.end_game(game_status, None);
+    }
+
+    /// Announces that the active player offers a draw, notifying the observer (see
+    /// [GameObserver::on_draw_offer]). The game does not keep track of pending offers; an
+    /// accepted offer is recorded with [draw_by_agreement](ChessGame::draw_by_agreement).
+    ///
+    /// returns: `Ok(())` if the offer was made.
+    ///          [GameNotStarted](ChessError::GameNotStarted) if neither player has made a move yet.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn offer_draw(&mut self) -> Result<(), ChessError> {
+        match self.game_status {
+            GameStatus::Normal => {
+                let player = self.active_player;
+                self.observer.notify(|observer| observer.on_draw_offer(player));
+                Ok(())
+            }
+            GameStatus::NotYetStarted => Err(ChessError::GameNotStarted),
+            GameStatus::Draw(..) | GameStatus::Win(..) => Err(ChessError::GameAlreadyEnded),
+        }
+    }
+
     /// Ends the game by draw by agreement.
     ///
     /// returns: `Ok(())` if the game was successfully drawn.
@@ -147,7 +1110,7 @@ impl ChessGame {
     pub fn draw_by_agreement(&mut self) -> Result<(), ChessError> {
         match self.game_status {
             GameStatus::Normal => {
-                self.game_status = GameStatus::Draw(DrawReason::DrawByAgreement);
+                self.set_game_status(GameStatus::Draw(DrawReason::DrawByAgreement));
                 Ok(())
             }
             GameStatus::NotYetStarted => Err(ChessError::GameNotStarted),
@@ -165,8 +1128,8 @@ impl ChessGame {
     pub fn resign(&mut self) -> Result<(), ChessError> {
         match self.game_status {
             GameStatus::Normal => {
-                self.game_status = GameStatus::Win(self.active_player.other_player(),
-                                                   WinReason::Resignation);
+                self.set_game_status(GameStatus::Win(self.active_player.other_player(),
+                                                     WinReason::Resignation));
                 Ok(())
             }
             GameStatus::NotYetStarted => Err(ChessError::GameNotStarted),
@@ -174,6 +1137,47 @@ impl ChessGame {
         }
     }
 
+    /// Ends the game with a result declared by an arbiter, for example when a tournament
+    /// director adjudicates an unfinished game. Unlike the other ways of ending a game, this is
+    /// allowed before any move has been made: a win declared then is a
+    /// [forfeit](WinReason::Forfeit), such as when a player does not show up, and any other win
+    /// is an [adjudication](WinReason::Adjudication). The result is final, so no further moves
+    /// are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `result`: The declared result.
+    /// * `reason`: A free-text description of why the game ended, which is written as the
+    ///   `Termination` tag when the game is [exported to PGN](crate::pgn::write_game). See
+    ///   [termination](ChessGame::termination).
+    ///
+    /// returns: `Ok(())` if the game was ended.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn adjudicate(&mut self, result: AdjudicatedResult, reason: &str)
+                      -> Result<(), ChessError>
+    {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        let status = match result {
+            AdjudicatedResult::WinFor(player) if self.game_status == GameStatus::NotYetStarted => {
+                GameStatus::Win(player, WinReason::Forfeit)
+            }
+            AdjudicatedResult::WinFor(player) => GameStatus::Win(player, WinReason::Adjudication),
+            AdjudicatedResult::Draw => GameStatus::Draw(DrawReason::Adjudication),
+        };
+        self.termination = Some(reason.to_string());
+        self.set_game_status(status);
+        Ok(())
+    }
+
+    /// returns: The reason given when the game was [adjudicated](ChessGame::adjudicate), or
+    ///          `None` if it was not.
+    pub fn termination(&self) -> Option<&str> {
+        self.termination.as_deref()
+    }
+
     /// returns: Whether there is a piece on the given square that belongs to the active player.
     pub fn active_piece(&self, pos: BoardPosition) -> bool {
         if let Some(piece) = self.board.get_piece(pos) {
@@ -183,44 +1187,346 @@ impl ChessGame {
         }
     }
 
-    fn castling_rights(&self, player: PlayerColor) -> CastlingRights {
+    /// returns: The given player's current [CastlingRights].
+    pub fn castling_rights(&self, player: PlayerColor) -> CastlingRights {
         match player {
             PlayerColor::White => self.castling_rights.0,
             PlayerColor::Black => self.castling_rights.1,
         }
     }
 
+    /// returns: The square which a pawn may capture en passant on this turn, if any.
+    pub fn en_passant_target(&self) -> Option<BoardPosition> {
+        self.en_passant_target
+    }
+
+    /// Replaces the current position with the given one, for example when an arbiter corrects a
+    /// game. Unlike creating a new game with [ChessGame::new], the game keeps its status: a game
+    /// which has not yet started remains not started, and a started game continues in normal play
+    /// (unless the new position is checkmate or stalemate). The position is validated before
+    /// anything is changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `board`: The new board. Must pass [Board::validate].
+    /// * `active_player`: The player whose turn it is in the new position. The other player may
+    ///   not be in check.
+    /// * `castling_rights`: The castling rights of white and black, respectively. A player may
+    ///   only have castling rights on a side if their king and rook are on their starting
+    ///   squares.
+    /// * `en_passant_target`: The square behind a pawn of the other player which has just made a
+    ///   double move, if any.
+    ///
+    /// The [move history](ChessGame::history) is truncated, so that it starts from the new
+    /// position, and the [hands](ChessGame::hand) of both players are emptied.
+    ///
+    /// returns: `Ok(())` if the position was set, otherwise the [PositionError] found while
+    ///          validating it, in which case the game is left unchanged.
+    pub fn set_position(&mut self, board: Board, active_player: PlayerColor,
+                        castling_rights: (CastlingRights, CastlingRights),
+                        en_passant_target: Option<BoardPosition>) -> Result<(), PositionError>
+    {
+        board.validate()?;
+        if moves::is_in_check(&board, active_player.other_player(), self.variant) {
+            return Err(PositionError::InactivePlayerInCheck);
+        }
+        check_castling_rights(&board, castling_rights)?;
+        if let Some(target) = en_passant_target {
+            check_en_passant_target(&board, active_player, target, self.variant)?;
+        }
+
+        self.starting_position = StartingPosition {
+            board: board.clone(),
+            active_player,
+            castling_rights,
+            en_passant_target,
+            extra_moves: 0,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        };
+        self.history.clear();
+        self.position_keys.clear();
+        self.undo_stack.clear();
+        self.takeback_request = None;
+        self.premoves = (None, None);
+        self.extra_moves = 0;
+        self.hands = (Hand::new(), Hand::new());
+        self.promoted = BoardBitmap::all_zeros();
+        self.board = board;
+        self.active_player = active_player;
+        self.castling_rights = castling_rights;
+        self.en_passant_target = en_passant_target;
+        if self.game_status.is_over() {
+            self.set_game_status(GameStatus::Normal);
+        }
+        self.recalculate_available_moves();
+        self.update_game_status();
+        Ok(())
+    }
+
     fn move_context(&self) -> MoveContext {
         MoveContext {
             castling_rights: self.castling_rights(self.active_player),
             en_passant_target: self.en_passant_target,
+            variant: self.variant,
         }
     }
 
     fn recalculate_available_moves(&mut self) {
-        for file in 0..8 {
-            for rank in 0..8 {
-                let pos = BoardPosition::try_from((file, rank)).unwrap();
-                let move_context = self.move_context();
-                let bitmap = moves::get_available_moves(&mut self.board, self.active_player, pos,
-                                                        move_context);
-                self.available_moves[file as usize][rank as usize] = bitmap;
-            }
+        for pos in BoardPosition::all() {
+            let move_context = self.move_context();
+            let bitmap = moves::get_available_moves(&mut self.board, self.active_player, pos,
+                                                    move_context);
+            self.available_moves[pos.file.get() as usize][pos.rank.get() as usize] = bitmap;
         }
+        self.remove_checks_during_extra_moves();
+    }
+
+    /// Recalculates the available moves like
+    /// [recalculate_available_moves](ChessGame::recalculate_available_moves), unless the
+    /// attached [AnalysisCache] holds the available moves of the position, which are then copied
+    /// from the cache instead. Positions where the active player has
+    /// [extra moves](ChessGame::extra_moves) left are not cached, since their keys do not tell
+    /// them apart from the same positions without extra moves.
+    fn update_available_moves(&mut self) {
+        #[cfg(feature = "std")]
+        if let Some(cache) = self.analysis_cache.clone() && self.extra_moves == 0 {
+            let key = self.polyglot_key();
+            self.available_moves = cache.lock().unwrap_or_else(PoisonError::into_inner)
+                .get_or_compute(key, |entry| &mut entry.available_moves, || {
+                    self.recalculate_available_moves();
+                    self.available_moves
+                });
+            return;
+        }
+        self.recalculate_available_moves();
     }
 
     /// returns: A [BoardBitmap] representing the set of legal moves for the piece on a given
     /// square. Returns an empty bitmap ([BoardBitmap::all_zeros]) if there is no piece on the
     /// provided square, or if the piece has no legal moves.
-    pub fn available_moves(&mut self, pos: BoardPosition) -> BoardBitmap {
+    pub fn available_moves(&self, pos: BoardPosition) -> BoardBitmap {
         self.available_moves[pos.file.get() as usize][pos.rank.get() as usize]
     }
 
     /// returns: Whether moving the piece at `pos` would result in a promotion move
-    pub fn expects_promotion_move(&mut self, pos: BoardPosition) -> bool {
+    pub fn expects_promotion_move(&self, pos: BoardPosition) -> bool {
         moves::expects_promotion_type(self.board(), self.active_player, pos)
     }
 
+    /// Returns the legal destination squares of the piece on a given square, classified by the
+    /// kind of move, for example for highlighting the squares when the piece is picked up in a
+    /// graphical interface. The destinations are taken from the cache of available moves (see
+    /// [available_moves](ChessGame::available_moves)). The squares a piece in the hand may be
+    /// dropped on in Crazyhouse are given by [drop_targets](ChessGame::drop_targets).
+    ///
+    /// returns: The [MoveTarget]s in ascending [index](BoardPosition::index) order of the
+    ///          destination square, like the moves of the piece in the
+    ///          [canonical order](ChessMove#canonical-order), or an empty `Vec` if there is no
+    ///          piece of the active player on the square.
+    pub fn move_targets(&self, from: BoardPosition) -> Vec<MoveTarget> {
+        let Some(piece) = self.board.get_piece(from) else {
+            return Vec::new();
+        };
+        let is_promotion = self.expects_promotion_move(from);
+        BoardPosition::all()
+            .filter(|&to| self.available_moves(from).get(to))
+            .map(|to| {
+                let is_en_passant = piece.piece_type == PieceType::Pawn
+                    && Some(to) == self.en_passant_target;
+                MoveTarget {
+                    to,
+                    is_capture: self.board.get_piece(to).is_some() || is_en_passant,
+                    is_promotion,
+                    is_castling: piece.piece_type == PieceType::King
+                        && from.file.get().abs_diff(to.file.get()) == 2,
+                    is_en_passant,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the squares a piece of the given type in the active player's hand may be dropped
+    /// on in [Crazyhouse](Variant::Crazyhouse), like [move_targets](ChessGame::move_targets) for
+    /// a piece picked up from the hand. A drop never captures, promotes or castles.
+    ///
+    /// returns: The [MoveTarget]s in ascending [index](BoardPosition::index) order of the square,
+    ///          like the [legal drops](ChessGame::legal_drops) of the piece type, or an empty
+    ///          `Vec` if the active player holds no piece of the type.
+    pub fn drop_targets(&self, piece_type: PieceType) -> Vec<MoveTarget> {
+        BoardPosition::all()
+            .filter(|&to| self.is_legal_drop(crazyhouse::DropMove::new(piece_type, to)))
+            .map(|to| MoveTarget {
+                to,
+                is_capture: false,
+                is_promotion: false,
+                is_castling: false,
+                is_en_passant: false,
+            })
+            .collect()
+    }
+
+    /// returns: The types a pawn may promote to, in the order knight, bishop, rook, queen: all
+    ///          [PromotionType]s, except in [Shatranj](Variant::Shatranj), where pawns only
+    ///          promote to a fers, represented by a queen.
+    pub fn promotion_types(&self) -> &'static [PromotionType] {
+        match self.variant {
+            Variant::Shatranj => &[PromotionType::Queen],
+            _ => &PromotionType::ALL,
+        }
+    }
+
+    /// returns: All legal moves of the active player, in the
+    ///          [canonical order](ChessMove#canonical-order). A promotion is included once for
+    ///          each of the [promotion types](ChessGame::promotion_types). Returns an empty `Vec`
+    ///          if the game is over. In Crazyhouse, the [legal drops](ChessGame::legal_drops)
+    ///          follow the other moves as [drop moves](ChessMove::dropping).
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        if self.game_status.is_over() {
+            return Vec::new();
+        }
+        let mut legal_moves = Vec::new();
+        for from in BoardPosition::all() {
+            let available_moves = self.available_moves(from);
+            if available_moves.is_all_zeros() {
+                continue;
+            }
+            let is_promotion = self.expects_promotion_move(from);
+            for to in BoardPosition::all().filter(|&to| available_moves.get(to)) {
+                if is_promotion {
+                    legal_moves.extend(self.promotion_types().iter().map(|&promotion| {
+                        ChessMove::promoting(from, to, promotion)
+                    }));
+                } else {
+                    legal_moves.push(ChessMove::new(from, to));
+                }
+            }
+        }
+        legal_moves.extend(self.legal_drops().into_iter().map(ChessMove::from));
+        legal_moves
+    }
+
+    /// Generates the legal captures of the active player, including captures en passant and
+    /// promotions which capture a piece, with each of the
+    /// [promotion types](ChessGame::promotion_types). These are exactly the
+    /// [legal moves](ChessGame::legal_moves) which capture a piece, but finding them is cheaper
+    /// than classifying each legal move, since only the squares of enemy pieces are considered
+    /// as destinations. Promotions which do not capture are not included.
+    ///
+    /// returns: The moves in the [canonical order](ChessMove#canonical-order), like in
+    ///          [legal_moves](ChessGame::legal_moves). Returns an empty `Vec` if the game is over.
+    pub fn legal_captures(&self) -> Vec<ChessMove> {
+        if self.game_status.is_over() {
+            return Vec::new();
+        }
+        let mut enemy_pieces = BoardBitmap::all_zeros();
+        for (pos, piece) in &self.board {
+            if piece.is_some_and(|piece| piece.player != self.active_player) {
+                enemy_pieces.set(pos, true);
+            }
+        }
+        let mut captures = Vec::new();
+        for from in BoardPosition::all() {
+            let available_moves = self.available_moves(from);
+            if available_moves.is_all_zeros() {
+                continue;
+            }
+            let is_promotion = self.expects_promotion_move(from);
+            let mut targets = available_moves.to_u64() & enemy_pieces.to_u64();
+            let is_pawn = self.board.get_piece(from)
+                .is_some_and(|piece| piece.piece_type == PieceType::Pawn);
+            if is_pawn && let Some(target) = self.en_passant_target
+                && available_moves.get(target)
+            {
+                targets |= 1 << target.index();
+            }
+            while targets != 0 {
+                let index = targets.trailing_zeros() as u8;
+                targets &= targets - 1;
+                let Some(to) = BoardPosition::from_index(index) else {
+                    continue;
+                };
+                if is_promotion {
+                    captures.extend(self.promotion_types().iter().map(|&promotion| {
+                        ChessMove::promoting(from, to, promotion)
+                    }));
+                } else {
+                    captures.push(ChessMove::new(from, to));
+                }
+            }
+        }
+        captures
+    }
+
+    /// Generates the legal moves of the active player which [give check](ChessGame::gives_check),
+    /// for example for tactics trainers. Only moves which could give check are tried on a copy
+    /// of the board: moves of a piece to a square on a line or a knight's move away from the
+    /// enemy king, moves from a square on a line from the king which may uncover an attack, and
+    /// king moves and captures en passant, which may uncover attacks in other ways.
+    ///
+    /// returns: The moves in the [canonical order](ChessMove#canonical-order), like in
+    ///          [legal_moves](ChessGame::legal_moves). Returns an empty `Vec` if the game is over
+    ///          or the other player has no king.
+    pub fn legal_checking_moves(&self) -> Vec<ChessMove> {
+        let Some(king) = self.king_position(self.active_player.other_player()) else {
+            return Vec::new();
+        };
+        // whether a piece on the square could attack the king, directly or by moving away
+        let in_reach = |pos: BoardPosition| {
+            let files = pos.file.get().abs_diff(king.file.get());
+            let ranks = pos.rank.get().abs_diff(king.rank.get());
+            files == 0 || ranks == 0 || files == ranks || files * ranks == 2
+        };
+        self.legal_moves().into_iter()
+            .filter(|chess_move| {
+                let PieceMovement { from, to } = chess_move.piece_movement;
+                let special = self.board.get_piece(from).is_some_and(|piece| {
+                    piece.piece_type == PieceType::King
+                        || (piece.piece_type == PieceType::Pawn
+                            && Some(to) == self.en_passant_target)
+                });
+                special || in_reach(from) || in_reach(to)
+            })
+            .filter(|&chess_move| self.gives_check(chess_move))
+            .collect()
+    }
+
+    /// Checks many candidate moves for legality at once, for example the moves suggested by an
+    /// opening book or another external source. A candidate is legal if it is one of the
+    /// [legal moves](ChessGame::legal_moves): its destination has to be in the cache of
+    /// [available moves](ChessGame::available_moves) of its origin, it has to have a
+    /// [promotion type](ChessGame::promotion_types) if and only if it is a promotion, and
+    /// castling has to be given as the move of the king by two squares. The checks shared by all
+    /// candidates are only made once, which makes this cheaper than checking each candidate on
+    /// its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidates`: The moves to check.
+    ///
+    /// returns: Whether each candidate is legal, in the order of `candidates`. Every candidate is
+    ///          illegal if the game is over.
+    pub fn filter_legal(&self, candidates: &[ChessMove]) -> Vec<bool> {
+        if self.game_status.is_over() {
+            return alloc::vec![false; candidates.len()];
+        }
+        let promotion_types = self.promotion_types();
+        candidates.iter()
+            .map(|&chess_move| {
+                if let Some(drop) = crazyhouse::DropMove::from_move(chess_move) {
+                    return self.is_legal_drop(drop);
+                }
+                let PieceMovement { from, to } = chess_move.piece_movement;
+                self.available_moves(from).get(to) && match chess_move.promotion {
+                    Some(promotion) => {
+                        promotion_types.contains(&promotion) && self.expects_promotion_move(from)
+                    }
+                    None => !self.expects_promotion_move(from),
+                }
+            })
+            .collect()
+    }
+
     fn after_move(&mut self, move_result: MoveResult) {
         // determine en passant target
         self.en_passant_target = move_result.new_en_passant_target;
@@ -238,25 +1544,61 @@ impl ChessGame {
                 PlayerColor::Black => self.castling_rights.1.kingside = false,
             }
         }
+        // a captured rook can no longer castle
+        if let Some((pos, piece)) = move_result.removed_piece
+            && piece.piece_type == PieceType::Rook
+            && pos.rank == piece.player.back_rank()
+        {
+            let rights = match piece.player {
+                PlayerColor::White => &mut self.castling_rights.0,
+                PlayerColor::Black => &mut self.castling_rights.1,
+            };
+            match pos.file.get() {
+                0 => rights.queenside = false,
+                7 => rights.kingside = false,
+                _ => {}
+            }
+        }
 
-        // change active player
-        self.active_player = self.active_player.other_player();
+        // change active player, unless the player has extra moves left
+        if self.extra_moves > 0 {
+            self.extra_moves -= 1;
+            self.en_passant_target = None;
+        } else {
+            self.active_player = self.active_player.other_player();
+        }
 
         // recalculate available moves
-        self.recalculate_available_moves();
+        self.update_available_moves();
+    }
 
-        // determine game status
+    fn update_game_status(&mut self) {
+        if let Some(status) = self.position_status() {
+            self.set_game_status(status);
+        }
+    }
+
+    /// returns: The status of the game decided by the position after a move, such as a
+    ///          checkmate or a stalemate, or `None` if the game goes on.
+    fn position_status(&self) -> Option<GameStatus> {
+        let last_player = self.active_player.other_player();
+        if let Some(reason) = self.variant.variant_win(&self.board, last_player) {
+            return Some(GameStatus::Win(last_player, reason));
+        }
         let has_available_moves = self.available_moves.iter()
             .flatten()
-            .any(|bitset| !bitset.is_all_zeros());
+            .any(|bitset| !bitset.is_all_zeros())
+            || self.has_legal_drop();
         if !has_available_moves {
-            let check = moves::is_in_check(&self.board, self.active_player);
-            if check {
-                self.game_status = GameStatus::Win(self.active_player.other_player(),
-                                                   WinReason::Checkmate);
+            if self.is_check() {
+                Some(GameStatus::Win(last_player, WinReason::Checkmate))
+            } else if self.variant == Variant::Shatranj {
+                Some(GameStatus::Win(last_player, WinReason::Stalemate))
             } else {
-                self.game_status = GameStatus::Draw(DrawReason::Stalemate);
+                Some(GameStatus::Draw(DrawReason::Stalemate))
             }
+        } else {
+            self.bare_king_status()
         }
     }
 
@@ -264,7 +1606,7 @@ impl ChessGame {
     /// `chess_move` has to be set to `Some(PromotionType)` if the move involves a pawn promotion,
     /// and has to be set to `None` otherwise. A move involves a pawn promotion if and only if:
     /// - The piece being moves is a [pawn](crate::board::piece::PieceType), and
-    /// - The piece is moved to its highest rank (rank 1 for white, and rank 7 for black)
+    /// - The piece is moved to its highest rank (rank 8 for white, and rank 1 for black)
     ///
     /// If the move is performed successfully, a set of actions are performed afterward:
     /// - En passant target is updated
@@ -273,22 +1615,863 @@ impl ChessGame {
     /// - The cache of available moves for each piece is updated
     /// - The game status is updated (checks for checkmate/stalemate)
     ///
-    /// returns: `Ok(())` if the move was performed successfully, and `Err(ChessError)` otherwise.
-    ///          See [ChessError].
-    pub fn do_move(&mut self, chess_move: ChessMove) -> Result<(), ChessError> {
-        match self.game_status {
-            GameStatus::Normal => {}
-            GameStatus::NotYetStarted => self.game_status = GameStatus::Normal,
-            GameStatus::Draw(..) | GameStatus::Win(..) => return Err(ChessError::GameAlreadyEnded),
+    /// The observer, if any, is notified of the move and then of any change in game status. In
+    /// debug builds, the game is then checked with [verify_integrity](ChessGame::verify_integrity).
+    /// An illegal move counts towards the
+    /// [illegal move limit](ChessGame::set_illegal_move_limit), if any, and a legal move is
+    /// followed by the other player's [premove](ChessGame::queue_premove), if any.
+    ///
+    /// returns: `Ok(MoveDetails)` if the move was performed successfully, and `Err(ChessError)`
+    ///          otherwise. See [ChessError].
+    pub fn do_move(&mut self, chess_move: ChessMove) -> Result<MoveDetails, ChessError> {
+        let player = self.active_player;
+        let result = self.perform_move(chess_move);
+        self.after_move_attempt(player, &result);
+        if result.is_ok() {
+            self.play_premove();
         }
-        let available_moves = self.available_moves(chess_move.piece_movement.from);
-        if !available_moves.get(chess_move.piece_movement.to) {
-            return Err(ChessError::IllegalMove);
+        result
+    }
+
+    fn perform_move(&mut self, chess_move: ChessMove) -> Result<MoveDetails, ChessError> {
+        if let Some(drop) = crazyhouse::DropMove::from_move(chess_move) {
+            return self.perform_drop(drop);
         }
+        let piece = self.check_move(chess_move)?;
+        let previous_key = self.polyglot_key();
+        let previous_castling_rights = self.castling_rights;
+        let mut undo_state = self.undo_state();
         let move_context = self.move_context();
         let move_result = moves::do_move(&mut self.board, self.active_player, chess_move,
                                          move_context)?;
+        let details = MoveDetails::of_move(chess_move, piece, &move_result);
+        self.track_crazyhouse_move(&details);
         self.after_move(move_result);
-        Ok(())
+        undo_state.forgotten_position_keys
+            = self.record_position(previous_key, previous_castling_rights, &details);
+        self.history.push(details);
+        self.undo_stack.push(undo_state);
+        self.observer.notify(|observer| observer.on_move(&details));
+        if self.game_status == GameStatus::NotYetStarted {
+            self.set_game_status(GameStatus::Normal);
+        }
+        self.update_game_status();
+        debug_assert_eq!(self.verify_integrity(), Ok(()));
+        Ok(details)
+    }
+
+    /// returns: `Ok(Piece)` of the piece moved by a move, if the move is legal.
+    ///          `Err(ChessError)` otherwise, like for [do_move](ChessGame::do_move).
+    fn check_move(&self, chess_move: ChessMove) -> Result<Piece, ChessError> {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        let piece = self.board.get_piece(chess_move.piece_movement.from)
+            .ok_or(ChessError::IllegalMove(chess_move))?;
+        if piece.player != self.active_player {
+            return Err(ChessError::WrongTurn(chess_move));
+        }
+        let PieceMovement { from, to } = chess_move.piece_movement;
+        let available_moves = self.available_moves(from);
+        if !available_moves.get(to) {
+            return Err(ChessError::IllegalMove(chess_move));
+        }
+        // the cached moves are not trusted with the one move removing two blocking pieces
+        if piece.piece_type == PieceType::Pawn && Some(to) == self.en_passant_target
+            && !moves::is_en_passant_legal_in_variant(&self.board, self.active_player, from, to,
+                                                      self.variant)
+        {
+            return Err(ChessError::IllegalMove(chess_move));
+        }
+        Ok(piece)
+    }
+
+    /// returns: The [drop](crazyhouse::DropMove) recorded in a history entry, or `None` if the
+    ///          entry is a normal move.
+    fn recorded_drop(details: &MoveDetails) -> Option<crazyhouse::DropMove> {
+        details.is_drop.then(|| {
+            let to = details.chess_move.piece_movement.to;
+            crazyhouse::DropMove::new(details.piece.piece_type, to)
+        })
+    }
+
+    /// Performs a move recorded in the [history](ChessGame::history) of a game in the same
+    /// position, which is either a move or a [drop](crazyhouse::DropMove).
+    pub(crate) fn replay(&mut self, details: &MoveDetails) -> Result<MoveDetails, ChessError> {
+        match ChessGame::recorded_drop(details) {
+            Some(drop) => self.do_drop(drop),
+            None => self.do_move(details.chess_move),
+        }
+    }
+
+    /// returns: The standard algebraic notation of a move recorded in the
+    ///          [history](ChessGame::history) of a game in the same position, or `None` if the
+    ///          move is not legal.
+    pub(crate) fn recorded_move_to_san(&self, details: &MoveDetails) -> Option<String> {
+        match ChessGame::recorded_drop(details) {
+            Some(drop) => self.drop_to_san(drop),
+            None => self.move_to_san(details.chess_move),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_board_eq;
+    #[cfg(feature = "std")]
+    use crate::chess::observer::GameEvent;
+
+    #[test]
+    fn game_status_accessors() {
+        let not_started = GameStatus::NotYetStarted;
+        let normal = GameStatus::Normal;
+        let stalemate = GameStatus::Draw(DrawReason::Stalemate);
+        let agreement = GameStatus::Draw(DrawReason::DrawByAgreement);
+        let checkmate = GameStatus::Win(PlayerColor::White, WinReason::Checkmate);
+        let resignation = GameStatus::Win(PlayerColor::Black, WinReason::Resignation);
+
+        for status in [not_started, normal] {
+            assert!(!status.is_over());
+            assert!(!status.is_draw());
+            assert!(!status.is_win());
+            assert_eq!(status.winner(), None);
+            assert_eq!(status.win_reason(), None);
+            assert_eq!(status.draw_reason(), None);
+            assert_eq!(status.outcome(), None);
+        }
+        for (status, reason) in [(stalemate, DrawReason::Stalemate),
+                                 (agreement, DrawReason::DrawByAgreement)] {
+            assert!(status.is_over());
+            assert!(status.is_draw());
+            assert!(!status.is_win());
+            assert_eq!(status.winner(), None);
+            assert_eq!(status.win_reason(), None);
+            assert_eq!(status.draw_reason(), Some(reason));
+            assert_eq!(status.outcome(), Some(Outcome::Draw));
+        }
+        for (status, player, reason) in [
+            (checkmate, PlayerColor::White, WinReason::Checkmate),
+            (resignation, PlayerColor::Black, WinReason::Resignation),
+        ] {
+            assert!(status.is_over());
+            assert!(!status.is_draw());
+            assert!(status.is_win());
+            assert_eq!(status.winner(), Some(player));
+            assert_eq!(status.win_reason(), Some(reason));
+            assert_eq!(status.draw_reason(), None);
+            assert_eq!(status.outcome(), Some(Outcome::Win(player)));
+            assert_eq!(Outcome::Win(player).score(player), 1.0);
+            assert_eq!(Outcome::Win(player).score(player.other_player()), 0.0);
+        }
+        assert_ne!(checkmate, GameStatus::Win(PlayerColor::Black, WinReason::Checkmate));
+        assert_ne!(stalemate, agreement);
+    }
+
+    #[test]
+    fn from_fen() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let game = ChessGame::from_fen(fen).unwrap();
+        assert_eq!(game.fen(), fen);
+        assert_eq!(ChessGame::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap().fen(),
+                   "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        for fen in ["", "4k3/8/8/8/8/8/8/4K3 w -", "4k3/8/8/8/8/8/8/4K3 x - -",
+                    "4k3/8/8/8/8/8/8/4K3 w KX -", "4k3/8/8/8/8/8/8/4K3 w - e9"] {
+            assert_eq!(ChessGame::from_fen(fen).unwrap_err(), FenError::InvalidSyntax);
+        }
+        assert_eq!(ChessGame::from_fen("4k3/8/8/8/8/8/8/4K3 w K -").unwrap_err(),
+                   FenError::InvalidPosition(PositionError::InvalidCastlingRights(
+                       PlayerColor::White)));
+    }
+
+    #[test]
+    fn from_fen_counters() {
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/4P3/4K1N1 b - - 37 52").unwrap();
+        assert_eq!(game.halfmove_clock(), 37);
+        assert_eq!(game.fen(), "4k3/8/8/8/8/8/4P3/4K1N1 b - - 37 52");
+        game.try_move_str("e8d7").unwrap();
+        assert_eq!(game.halfmove_clock(), 38);
+        assert_eq!(game.fen(), "8/3k4/8/8/8/8/4P3/4K1N1 w - - 38 53");
+        game.try_move_str("g1f3").unwrap();
+        assert_eq!(game.fen(), "8/3k4/8/8/8/5N2/4P3/4K3 b - - 39 53");
+        game.try_move_str("d7d6").unwrap();
+        game.try_move_str("e2e4").unwrap();
+        assert_eq!(game.halfmove_clock(), 0);
+        assert_eq!(game.fen(), "8/8/3k4/8/4P3/5N2/8/4K3 b - e3 0 54");
+        assert_eq!(game.starting_position().fen(), "4k3/8/8/8/8/8/4P3/4K1N1 b - - 37 52");
+
+        // a fifty-move draw can be claimed with the plies before the starting position
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K1N1 w - - 99 80").unwrap();
+        game.try_move_str("g1f3").unwrap();
+        assert_eq!(game.claim_fifty_move_draw(), Ok(()));
+
+        assert_eq!(ChessGame::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0").unwrap().fen(),
+                   "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+
+        // the counters saturate instead of overflowing
+        let mut game = ChessGame::from_fen(&format!("4k3/8/8/8/8/8/8/4K1N1 b - - {} {}",
+                                                    usize::MAX, usize::MAX)).unwrap();
+        game.try_move_str("e8d7").unwrap();
+        game.try_move_str("g1f3").unwrap();
+        assert_eq!(game.fen(),
+                   format!("8/3k4/8/8/8/5N2/8/4K3 b - - {} {}", usize::MAX, usize::MAX));
+        assert_eq!(game.movetext(), format!("{}... Kd7 {}. Nf3", usize::MAX, usize::MAX));
+        for fen in ["4k3/8/8/8/8/8/8/4K3 w - - 0", "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+                    "4k3/8/8/8/8/8/8/4K3 w - - 0 -1", "4k3/8/8/8/8/8/8/4K3 w - - 0 1 2"] {
+            assert_eq!(ChessGame::from_fen(fen).unwrap_err(), FenError::InvalidSyntax, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn new_game_castling_rights() {
+        let game = ChessGame::new(Board::default_board());
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::default());
+        assert_eq!(game.castling_rights(PlayerColor::Black), CastlingRights::default());
+
+        let game = ChessGame::new(Board::from_fen_string("r3k3/8/8/8/8/8/8/1R2K2R").unwrap());
+        assert_eq!(game.castling_rights(PlayerColor::White),
+                   CastlingRights { queenside: false, kingside: true });
+        assert_eq!(game.castling_rights(PlayerColor::Black),
+                   CastlingRights { queenside: true, kingside: false });
+        assert_eq!(game.fen(), "r3k3/8/8/8/8/8/8/1R2K2R w Kq - 0 1");
+        assert!(ChessGame::from_fen(&game.fen()).is_ok());
+
+        let game = ChessGame::new(Board::from_fen_string("r6r/4k3/8/8/8/8/8/R2K3R").unwrap());
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::NONE);
+        assert_eq!(game.castling_rights(PlayerColor::Black), CastlingRights::NONE);
+        assert_eq!(game.fen(), "r6r/4k3/8/8/8/8/8/R2K3R w - - 0 1");
+    }
+
+    #[test]
+    fn capturing_rook_removes_castling_rights() {
+        let mut game = ChessGame::from_fen("r3k3/6b1/8/8/8/8/8/R3K3 b Qq - 0 1").unwrap();
+        game.do_move(ChessMove::from_coords("g7", "a1").unwrap()).unwrap();
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::NONE);
+        assert_eq!(game.fen(), "r3k3/8/8/8/8/8/8/b3K3 w q - 0 2");
+        assert!(ChessGame::from_fen(&game.fen()).is_ok());
+    }
+
+    #[test]
+    fn en_passant_capture_of_checking_pawn() {
+        // the pawn on d4 has just made a double move and gives check
+        let mut game = ChessGame::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        let pos = BoardPosition::try_from("e4").unwrap();
+        assert!(game.available_moves(pos).get(BoardPosition::try_from("d3").unwrap()));
+        game.do_move(ChessMove::from_coords("e4", "d3").unwrap()).unwrap();
+        assert_eq!(game.fen(), "8/8/8/2k5/8/3p4/8/4K3 w - - 0 2");
+
+        // removing the pawn uncovers a check by the bishop instead
+        let game = ChessGame::from_fen("8/8/8/2k5/3Pp3/8/8/4K1B1 b - d3 0 1").unwrap();
+        assert!(!game.available_moves(pos).get(BoardPosition::try_from("d3").unwrap()));
+    }
+
+    #[test]
+    fn set_position_mid_game() {
+        let mut game = ChessGame::new(Board::default_board());
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        game.do_move(ChessMove::from_coords("e7", "e5").unwrap()).unwrap();
+
+        // the arbiter decides that black actually played d7d5
+        let board = Board::from_fen_string("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR")
+            .unwrap();
+        let d6 = "d6".parse().unwrap();
+        let rights = (CastlingRights::default(), CastlingRights::default());
+        game.set_position(board.clone(), PlayerColor::White, rights, Some(d6)).unwrap();
+        assert_board_eq!(*game.board(), board);
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert_eq!(game.en_passant_target(), Some(d6));
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+        assert!(game.available_moves("e4".parse().unwrap()).get("d5".parse().unwrap()));
+
+        game.do_move(ChessMove::from_coords("e4", "d5").unwrap()).unwrap();
+        game.do_move(ChessMove::from_coords("d8", "d5").unwrap()).unwrap();
+        assert_eq!(game.active_player(), PlayerColor::White);
+        let e4e5 = ChessMove::from_coords("e4", "e5").unwrap();
+        assert_eq!(game.do_move(e4e5), Err(ChessError::IllegalMove(e4e5)));
+    }
+
+    #[test]
+    fn set_position_validation() {
+        let mut game = ChessGame::new(Board::default_board());
+        let rights = (CastlingRights::default(), CastlingRights::default());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        let board = |fen| Board::from_fen_string(fen).unwrap();
+
+        assert_eq!(game.set_position(board("8/8/8/8/8/8/8/K7"), PlayerColor::White,
+                                     no_rights, None),
+                   Err(PositionError::InvalidKingCount(PlayerColor::Black)));
+        assert_eq!(game.set_position(board("k7/8/8/8/8/8/8/K5P1"), PlayerColor::White,
+                                     no_rights, None),
+                   Err(PositionError::PawnOnBackRank("g1".parse().unwrap())));
+        assert_eq!(game.set_position(board("k7/8/8/8/8/8/8/K6r"), PlayerColor::Black,
+                                     no_rights, None),
+                   Err(PositionError::InactivePlayerInCheck));
+        assert_eq!(game.set_position(board("r3k2r/8/8/8/8/8/8/R3K1R1"), PlayerColor::White,
+                                     rights, None),
+                   Err(PositionError::InvalidCastlingRights(PlayerColor::White)));
+        assert_eq!(game.set_position(board("r3k2r/8/8/8/8/8/8/R3KR2"), PlayerColor::White,
+                                     (CastlingRights { queenside: true, kingside: false },
+                                      CastlingRights::default()), None),
+                   Ok(()));
+        assert_eq!(game.set_position(board("4k3/8/8/8/4P3/8/8/4K3"), PlayerColor::Black,
+                                     no_rights, Some("e6".parse().unwrap())),
+                   Err(PositionError::InvalidEnPassantTarget("e6".parse().unwrap())));
+        assert_eq!(game.set_position(board("4k3/8/8/8/4P3/8/8/4K3"), PlayerColor::Black,
+                                     no_rights, Some("e3".parse().unwrap())),
+                   Ok(()));
+
+        // a failed correction leaves the game unchanged
+        let before = game.board().clone();
+        assert!(game.set_position(board("8/8/8/8/8/8/8/8"), PlayerColor::White,
+                                  no_rights, None).is_err());
+        assert_board_eq!(*game.board(), before);
+        assert_eq!(game.active_player(), PlayerColor::Black);
+    }
+
+    #[test]
+    fn set_position_checkmate() {
+        let mut game = ChessGame::new(Board::default_board());
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string("k7/1Q6/1K6/8/8/8/8/8").unwrap(),
+                          PlayerColor::Black, no_rights, None).unwrap();
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::Checkmate));
+    }
+
+    #[test]
+    fn history_and_fen() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let moves = [("e2", "e4"), ("c7", "c5"), ("g1", "f3")];
+        for (from, to) in moves {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        assert_eq!(game.fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        let history: Vec<ChessMove> = game.history().iter()
+            .map(|details| details.chess_move)
+            .collect();
+        let expected: Vec<ChessMove> = moves.iter()
+            .map(|(from, to)| ChessMove::from_coords(from, to).unwrap())
+            .collect();
+        assert_eq!(history, expected);
+        assert_eq!(game.starting_position().fen(), ChessGame::new(Board::default_board()).fen());
+
+        let board = Board::from_fen_string("4k3/8/8/8/4P3/8/8/4K3").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::Black, no_rights, Some("e3".parse().unwrap()))
+            .unwrap();
+        assert!(game.history().is_empty());
+        assert_eq!(game.fen(), "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+        game.do_move(ChessMove::from_coords("e8", "d7").unwrap()).unwrap();
+        assert_eq!(game.fen(), "8/3k4/8/8/4P3/8/8/4K3 w - - 1 2");
+        assert_eq!(game.starting_position().fen(), "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+    }
+
+    #[test]
+    fn move_targets() {
+        let mut game = ChessGame::new(Board::default_board());
+        let white_rights = (CastlingRights::default(), CastlingRights::NONE);
+        let board = Board::from_fen_string("2k2n2/6P1/8/3pP3/8/8/8/R3K2R").unwrap();
+        game.set_position(board, PlayerColor::White, white_rights, Some("d6".parse().unwrap()))
+            .unwrap();
+        let target = |to: &str, is_capture, is_promotion, is_castling, is_en_passant| {
+            MoveTarget { to: to.parse().unwrap(), is_capture, is_promotion, is_castling,
+                         is_en_passant }
+        };
+
+        assert_eq!(game.move_targets("g7".parse().unwrap()), vec![
+            target("f8", true, true, false, false),
+            target("g8", false, true, false, false),
+        ]);
+        assert_eq!(game.move_targets("e5".parse().unwrap()), vec![
+            target("d6", true, false, false, true),
+            target("e6", false, false, false, false),
+        ]);
+        let king_targets = game.move_targets("e1".parse().unwrap());
+        assert_eq!(king_targets.len(), 7);
+        assert!(king_targets.contains(&target("c1", false, false, true, false)));
+        assert!(king_targets.contains(&target("g1", false, false, true, false)));
+        assert!(king_targets.contains(&target("d1", false, false, false, false)));
+
+        assert!(game.move_targets("c8".parse().unwrap()).is_empty());
+        assert!(game.move_targets("e4".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn legal_moves() {
+        let mut game = ChessGame::new(Board::default_board());
+        let legal_moves = game.legal_moves();
+        assert_eq!(legal_moves.len(), 20);
+        assert_eq!(legal_moves[0], ChessMove::from_coords("a2", "a3").unwrap());
+
+        let board = Board::from_fen_string("4k3/1P6/8/8/8/8/8/4K3").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        let legal_moves = game.legal_moves();
+        assert_eq!(legal_moves.len(), 5 + 4);
+        let b7 = "b7".parse().unwrap();
+        let b8 = "b8".parse().unwrap();
+        for promotion in PromotionType::ALL {
+            assert!(legal_moves.contains(&ChessMove::promoting(b7, b8, promotion)));
+        }
+
+        game.do_move(ChessMove::from_coords("e1", "d1").unwrap()).unwrap();
+        game.resign().unwrap();
+        assert!(game.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn canonical_move_order() {
+        fn uci(moves: &[ChessMove]) -> String {
+            moves.iter().map(|chess_move| chess_move.to_string()).collect::<Vec<_>>().join(" ")
+        }
+
+        let game = ChessGame::new(Board::default_board());
+        assert_eq!(uci(&game.legal_moves()),
+                   "a2a3 a2a4 b1a3 b1c3 b2b3 b2b4 c2c3 c2c4 d2d3 d2d4 e2e3 e2e4 f2f3 f2f4 g1f3 \
+                    g1h3 g2g3 g2g4 h2h3 h2h4");
+
+        let game = ChessGame::from_fen("r3k2n/1P4P1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let legal_moves = game.legal_moves();
+        assert_eq!(uci(&legal_moves),
+                   "b7a8n b7a8b b7a8r b7a8q b7b8n b7b8b b7b8r b7b8q e1d1 e1d2 e1e2 e1f1 e1f2 \
+                    g7g8n g7g8b g7g8r g7g8q g7h8n g7h8b g7h8r g7h8q");
+        assert_eq!(uci(&game.legal_captures()), "b7a8n b7a8b b7a8r b7a8q g7h8n g7h8b g7h8r g7h8q");
+        let targets: Vec<BoardPosition> = game.move_targets("e1".parse().unwrap()).iter()
+            .map(|target| target.to)
+            .collect();
+        assert_eq!(targets, ["d1", "d2", "e2", "f1", "f2"].map(|pos| pos.parse().unwrap()));
+
+        let mut shuffled = legal_moves.clone();
+        shuffled.reverse();
+        shuffled.swap(3, 11);
+        moves::sort_moves_canonical(&mut shuffled);
+        assert_eq!(shuffled, legal_moves);
+    }
+
+    #[test]
+    fn legal_captures() {
+        fn filtered_captures(game: &ChessGame) -> Vec<ChessMove> {
+            game.legal_moves().into_iter()
+                .filter(|chess_move| {
+                    let PieceMovement { from, to } = chess_move.piece_movement;
+                    game.move_targets(from).iter()
+                        .any(|target| target.to == to && target.is_capture)
+                })
+                .collect()
+        }
+
+        let positions = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR", PlayerColor::White, None),
+            ("r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR", PlayerColor::White, Some("e6")),
+            ("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR", PlayerColor::White, None),
+            ("2r1k3/1P6/8/3pP3/2b5/8/5n2/R3K3", PlayerColor::White, Some("d6")),
+            ("4k3/8/8/8/2pP4/8/6p1/4KR2", PlayerColor::Black, Some("d3")),
+            ("4k3/8/8/1b6/8/8/4R3/4K3", PlayerColor::Black, None),
+        ];
+        let mut game = ChessGame::new(Board::default_board());
+        for (fen, active_player, en_passant_target) in positions {
+            let board = Board::from_fen_string(fen).unwrap();
+            let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+            let en_passant_target = en_passant_target.map(|square| square.parse().unwrap());
+            game.set_position(board, active_player, no_rights, en_passant_target).unwrap();
+            assert_eq!(game.legal_captures(), filtered_captures(&game), "{}", fen);
+        }
+        assert_eq!(game.legal_captures(), [ChessMove::from_coords("b5", "e2").unwrap()]);
+    }
+
+    #[test]
+    fn legal_checking_moves() {
+        fn filtered_checks(game: &ChessGame) -> Vec<ChessMove> {
+            game.legal_moves().into_iter()
+                .filter(|&chess_move| game.gives_check(chess_move))
+                .collect()
+        }
+
+        let positions = [
+            // castling with check, and promotions with and without check
+            "1r3k2/P7/8/8/8/8/8/4K2R w K - 0 1",
+            // discovered checks, including by capturing en passant
+            "8/8/8/1k1pP2R/8/8/8/4K1B1 w - d6 0 1",
+            "4k3/8/8/8/8/8/4N3/4R1K1 w - - 0 1",
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        ];
+        for fen in positions {
+            let game = ChessGame::from_fen(fen).unwrap();
+            assert_eq!(game.legal_checking_moves(), filtered_checks(&game), "{}", fen);
+        }
+        let game = ChessGame::from_fen(positions[0]).unwrap();
+        let checks: Vec<String> = game.legal_checking_moves().iter()
+            .map(ChessMove::to_string)
+            .collect();
+        assert_eq!(checks, ["a7b8r", "a7b8q", "e1g1", "h1f1", "h1h8"]);
+        let game = ChessGame::from_fen(positions[1]).unwrap();
+        assert!(game.legal_checking_moves().contains(&ChessMove::from_coords("e5", "d6").unwrap()));
+
+        // along a whole game, for both players
+        let mut game = ChessGame::new(Board::default_board());
+        for chess_move in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O", "Bg4",
+                           "h3", "h5", "hxg4", "hxg4", "Nxe5", "Qh4", "Nxg4", "Qxg4"] {
+            assert_eq!(game.legal_checking_moves(), filtered_checks(&game), "{}", game.fen());
+            game.try_move_str(chess_move).unwrap();
+        }
+        assert_eq!(game.legal_checking_moves(), filtered_checks(&game));
+    }
+
+    #[test]
+    fn filter_legal() {
+        let game = ChessGame::from_fen("r2rk3/1P6/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let promoting = |from: &str, to: &str, promotion| {
+            ChessMove::promoting(from.parse().unwrap(), to.parse().unwrap(), promotion)
+        };
+        let candidates = [
+            ChessMove::from_coords("e1", "g1").unwrap(),
+            // castling written as the king capturing its own rook
+            ChessMove::from_coords("e1", "h1").unwrap(),
+            // pseudo-legal, but the king would pass through or stand in check on the d-file
+            ChessMove::from_coords("e1", "c1").unwrap(),
+            ChessMove::from_coords("e1", "d2").unwrap(),
+            ChessMove::from_coords("e1", "f2").unwrap(),
+            ChessMove::from_coords("a1", "a8").unwrap(),
+            ChessMove::from_coords("b7", "b8").unwrap(),
+            promoting("b7", "b8", PromotionType::Knight),
+            promoting("b7", "a8", PromotionType::Queen),
+            promoting("h1", "h8", PromotionType::Queen),
+            ChessMove::from_coords("a8", "a1").unwrap(),
+            ChessMove::from_coords("c3", "c4").unwrap(),
+            ChessMove::from_coords("h1", "h1").unwrap(),
+        ];
+        let expected = [true, false, false, false, true, true, false, true, true, false, false,
+                        false, false];
+        assert_eq!(game.filter_legal(&candidates), expected);
+        for (candidate, legal) in candidates.iter().zip(expected) {
+            assert_eq!(game.legal_moves().contains(candidate), legal, "{}", candidate);
+        }
+        let mated = ChessGame::from_fen("k6R/8/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(mated.filter_legal(&[ChessMove::from_coords("a8", "a7").unwrap()]), [false]);
+    }
+
+    #[test]
+    fn move_details_side_effects() {
+        let mut game = ChessGame::new(Board::default_board());
+        let rights = (CastlingRights::default(), CastlingRights::default());
+        let board = Board::from_fen_string("r3k2r/8/8/8/8/8/8/R3K2R").unwrap();
+        let castle = |game: &mut ChessGame, player, from: &str, to: &str| {
+            game.set_position(board.clone(), player, rights, None).unwrap();
+            let details = game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+            let after = game.board().clone();
+            assert_eq!(game.undo_move(), Some(details));
+            assert_board_eq!(*game.board(), board);
+            assert_eq!(game.castling_rights(player), rights.0);
+            game.do_move(details.chess_move).unwrap();
+            assert_board_eq!(*game.board(), after);
+            details.secondary_movement
+        };
+        let movement = |from, to| Some(PieceMovement::new(from, to).unwrap());
+
+        assert_eq!(castle(&mut game, PlayerColor::White, "e1", "g1"), movement("h1", "f1"));
+        assert_eq!(castle(&mut game, PlayerColor::White, "e1", "c1"), movement("a1", "d1"));
+        assert_eq!(castle(&mut game, PlayerColor::Black, "e8", "g8"), movement("h8", "f8"));
+        assert_eq!(castle(&mut game, PlayerColor::Black, "e8", "c8"), movement("a8", "d8"));
+        assert_eq!(castle(&mut game, PlayerColor::White, "e1", "f1"), None);
+        castle(&mut game, PlayerColor::White, "e1", "c1");
+        assert_board_eq!(*game.board(),
+                         Board::from_fen_string("r3k2r/8/8/8/8/8/8/2KR3R").unwrap());
+
+        let board = Board::from_fen_string("4k3/8/8/3pP3/8/8/8/4K3").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, Some("d6".parse().unwrap()))
+            .unwrap();
+        let details = game.do_move(ChessMove::from_coords("e5", "d6").unwrap()).unwrap();
+        let black_pawn = Piece::from_char('p').unwrap();
+        assert_eq!(details.captured_piece, Some(black_pawn));
+        assert_eq!(details.removed_piece, Some(("d5".parse().unwrap(), black_pawn)));
+        assert_eq!(details.secondary_movement, None);
+        assert_board_eq!(*game.board(),
+                         Board::from_fen_string("4k3/8/3P4/8/8/8/8/4K3").unwrap());
+        game.undo_move().unwrap();
+        assert_board_eq!(*game.board(), Board::from_fen_string("4k3/8/8/3pP3/8/8/8/4K3").unwrap());
+        assert_eq!(game.en_passant_target(), Some("d6".parse().unwrap()));
+
+        // the promoted piece turns back into a pawn
+        let board = Board::from_fen_string("1n2k3/P7/8/8/8/8/8/4K3").unwrap();
+        game.set_position(board.clone(), PlayerColor::White, no_rights, None).unwrap();
+        let a7 = "a7".parse().unwrap();
+        let b8 = "b8".parse().unwrap();
+        let details = game.do_move(ChessMove::promoting(a7, b8, PromotionType::Rook)).unwrap();
+        assert_eq!(details.piece, Piece::from_char('P').unwrap());
+        assert_eq!(details.captured_piece, Piece::from_char('n'));
+        game.undo_move().unwrap();
+        assert_board_eq!(*game.board(), board);
+    }
+
+    #[test]
+    fn check_resolutions() {
+        let game = ChessGame::from_fen("4r2k/8/5N2/8/8/8/8/4KB2 w - - 0 1").unwrap();
+        let resolutions = game.check_resolutions();
+        let king_moves: Vec<BoardPosition> = BoardPosition::all()
+            .filter(|&pos| resolutions.king_moves.get(pos))
+            .collect();
+        let squares = |squares: &[&str]| -> Vec<BoardPosition> {
+            squares.iter().map(|square| square.parse().unwrap()).collect()
+        };
+        assert_eq!(king_moves, squares(&["d1", "d2", "f2"]));
+        assert_eq!(resolutions.capturing_moves, [ChessMove::from_coords("f6", "e8").unwrap()]);
+        assert_eq!(resolutions.blocking_moves, [ChessMove::from_coords("f1", "e2").unwrap(),
+                                                ChessMove::from_coords("f6", "e4").unwrap()]);
+
+        // double check with a rook and a knight, where the bishop could capture or block either
+        let game = ChessGame::from_fen("4r2k/8/8/8/8/3n4/8/R3KB2 w - - 0 1").unwrap();
+        let resolutions = game.check_resolutions();
+        assert_eq!(resolutions.king_moves.to_u64().count_ones(), 2);
+        assert!(resolutions.king_moves.get("d1".parse().unwrap()));
+        assert!(resolutions.king_moves.get("d2".parse().unwrap()));
+        assert!(resolutions.capturing_moves.is_empty());
+        assert!(resolutions.blocking_moves.is_empty());
+
+        // capturing a checking pawn en passant
+        let game = ChessGame::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        let resolutions = game.check_resolutions();
+        assert!(resolutions.king_moves.get("d4".parse().unwrap()));
+        assert_eq!(resolutions.capturing_moves, [ChessMove::from_coords("e4", "d3").unwrap()]);
+
+        let quiet = ChessGame::new(Board::default_board()).check_resolutions();
+        assert!(quiet.king_moves.is_all_zeros() && quiet.capturing_moves.is_empty());
+    }
+
+    #[test]
+    fn highlight_info() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.highlight_info(), HighlightInfo {
+            last_move: None,
+            king_in_check: None,
+            checkers: Vec::new(),
+        });
+
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(game.highlight_info(), HighlightInfo {
+            last_move: Some(PieceMovement::new("e2", "e4").unwrap()),
+            king_in_check: None,
+            checkers: Vec::new(),
+        });
+
+        // double check with a knight and a rook
+        let board = Board::from_fen_string("4k3/8/8/8/4N3/8/8/4RK2").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        game.do_move(ChessMove::from_coords("e4", "f6").unwrap()).unwrap();
+        assert!(game.is_check());
+        assert_eq!(game.king_position(PlayerColor::Black), Some("e8".parse().unwrap()));
+        assert_eq!(game.highlight_info(), HighlightInfo {
+            last_move: Some(PieceMovement::new("e4", "f6").unwrap()),
+            king_in_check: Some("e8".parse().unwrap()),
+            checkers: vec!["e1".parse().unwrap(), "f6".parse().unwrap()],
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn observer_events() {
+        let mut game = ChessGame::new(Board::default_board());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        assert!(game.set_observer(Box::new(sender)).is_none());
+
+        let moves = [("f2", "f3"), ("e7", "e5"), ("g2", "g4")];
+        for (from, to) in moves {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        // illegal moves are not reported
+        assert!(game.do_move(ChessMove::from_coords("a2", "a5").unwrap()).is_err());
+        game.offer_draw().unwrap();
+        game.do_move(ChessMove::from_coords("d8", "h4").unwrap()).unwrap();
+        assert!(game.resign().is_err());
+
+        let piece = |ch| Piece::from_char(ch).unwrap();
+        let move_event = |from, to, ch| GameEvent::Move(MoveDetails {
+            chess_move: ChessMove::from_coords(from, to).unwrap(),
+            piece: piece(ch),
+            captured_piece: None,
+            secondary_movement: None,
+            removed_piece: None,
+            analysis: None,
+            is_drop: false,
+        });
+        let events: Vec<GameEvent> = receiver.try_iter().collect();
+        assert_eq!(events, vec![
+            move_event("f2", "f3", 'P'),
+            GameEvent::StatusChange(GameStatus::Normal),
+            move_event("e7", "e5", 'p'),
+            move_event("g2", "g4", 'P'),
+            GameEvent::DrawOffer(PlayerColor::Black),
+            move_event("d8", "h4", 'q'),
+            GameEvent::StatusChange(GameStatus::Win(PlayerColor::Black, WinReason::Checkmate)),
+            GameEvent::Termination(*game.game_termination().unwrap()),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn observer_not_cloned() {
+        let mut game = ChessGame::new(Board::default_board());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        game.set_observer(Box::new(sender));
+        let mut clone = game.clone();
+        clone.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(receiver.try_iter().count(), 0);
+        assert!(game.remove_observer().is_some());
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(receiver.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn game_display() {
+        let game = ChessGame::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(format!("{}", game), "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+        assert_eq!(format!("[{:<34}]", game), "[4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1  ]");
+        assert_eq!(format!("{:#}", game), concat!(
+            "\n",
+            "8 ░ ▓ ░ ▓ ♚ ▓ ░ ▓\n",
+            "7 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "6 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "5 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "4 ░ ▓ ░ ▓ ♙ ▓ ░ ▓\n",
+            "3 ▓ ░ ▓ ░ ▓ ░ ▓ ░\n",
+            "2 ░ ▓ ░ ▓ ░ ▓ ░ ▓\n",
+            "1 ▓ ░ ▓ ░ ♔ ░ ▓ ░\n",
+            "  a b c d e f g h\n",
+            "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"
+        ));
+    }
+
+    #[test]
+    fn errors_carry_the_move() {
+        let mut game = ChessGame::from_fen("4k3/P7/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let wrong_turn = ChessMove::from_coords("e8", "d8").unwrap();
+        assert_eq!(game.do_move(wrong_turn), Err(ChessError::WrongTurn(wrong_turn)));
+        let missing = ChessMove::from_coords("a7", "a8").unwrap();
+        assert_eq!(game.do_move(missing), Err(ChessError::MissingPromotionType(missing)));
+        let unexpected = ChessMove::promoting("h1".parse().unwrap(), "h8".parse().unwrap(),
+                                              PromotionType::Queen);
+        assert_eq!(game.do_move(unexpected), Err(ChessError::UnexpectedPromotionType(unexpected)));
+        let empty_square = ChessMove::from_coords("c3", "c4").unwrap();
+        let error = game.do_move(empty_square).unwrap_err();
+        assert_eq!(error, ChessError::IllegalMove(empty_square));
+        assert_eq!(error.chess_move(), Some(empty_square));
+        assert_eq!(error.square(), Some("c3".parse().unwrap()));
+        assert_eq!(error.code(), 3);
+        assert_eq!(error.to_string(), "illegal move c3c4");
+        assert_eq!(ChessError::MissingPromotionType(missing).to_string(),
+                   "missing promotion type for a7a8");
+        assert_eq!(ChessError::UnexpectedPromotionType(unexpected).to_string(),
+                   "expected `None` as promotion type: h1h8q is not a promotion move");
+        // the game is unchanged by the failed moves
+        assert_eq!(game.fen(), "4k3/P7/8/8/8/8/8/4K2R w K - 0 1");
+
+        game.do_move(ChessMove::from_coords("h1", "h2").unwrap()).unwrap();
+        game.resign().unwrap();
+        let error = game.do_move(missing).unwrap_err();
+        assert_eq!(error, ChessError::GameAlreadyEnded);
+        assert_eq!((error.chess_move(), error.code()), (None, 2));
+        assert_eq!(ChessError::GameNotStarted.code(), 1);
+    }
+
+    #[test]
+    fn game_status_after_resignation() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(*game.game_status(), GameStatus::NotYetStarted);
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+        game.resign().unwrap();
+        assert_eq!(*game.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::Resignation));
+    }
+
+    #[test]
+    fn forfeit_for_no_show() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.termination(), None);
+        game.adjudicate(AdjudicatedResult::WinFor(PlayerColor::Black), "White did not show up")
+            .unwrap();
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::Black, WinReason::Forfeit));
+        assert_eq!(game.termination(), Some("White did not show up"));
+        assert_eq!(game.game_status().to_string(), "Black won by forfeit");
+        assert_eq!(game.verify_integrity(), Ok(()));
+        assert_eq!(game.do_move(ChessMove::from_coords("e2", "e4").unwrap()),
+                   Err(ChessError::GameAlreadyEnded));
+        assert_eq!(game.adjudicate(AdjudicatedResult::Draw, "mistake"),
+                   Err(ChessError::GameAlreadyEnded));
+        assert_eq!(game.termination(), Some("White did not show up"));
+    }
+
+    #[test]
+    fn adjudicated_draw() {
+        let mut game = ChessGame::new(Board::default_board());
+        game.try_move_str("e2e4").unwrap();
+        game.try_move_str("e7e5").unwrap();
+        game.adjudicate(AdjudicatedResult::Draw, "time control reached").unwrap();
+        assert_eq!(*game.game_status(), GameStatus::Draw(DrawReason::Adjudication));
+        assert_eq!(game.verify_integrity(), Ok(()));
+        assert_eq!(game.do_move(ChessMove::from_coords("g1", "f3").unwrap()),
+                   Err(ChessError::GameAlreadyEnded));
+        assert_eq!(crate::pgn::write_game(&game, &[("Result", "1/2-1/2")]), concat!(
+            "[Result \"1/2-1/2\"]\n",
+            "[Termination \"time control reached\"]\n",
+            "\n",
+            "1. e4 e5 1/2-1/2\n",
+        ));
+
+        let mut adjudicated = ChessGame::new(Board::default_board());
+        adjudicated.try_move_str("e2e4").unwrap();
+        adjudicated.adjudicate(AdjudicatedResult::WinFor(PlayerColor::White), "illness").unwrap();
+        assert_eq!(*adjudicated.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::Adjudication));
+    }
+
+    #[test]
+    fn illegal_move_limit() {
+        let mut game = ChessGame::new(Board::default_board());
+        game.set_illegal_move_limit(Some(3));
+        game.try_move_str("e2e4").unwrap();
+        let bad_moves = [("e7", "e4"), ("e4", "e5"), ("e8", "e6")];
+        for (index, (from, to)) in bad_moves.into_iter().enumerate() {
+            assert_eq!(*game.game_status(), GameStatus::Normal);
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap_err();
+            assert_eq!(game.illegal_moves(PlayerColor::Black), index as u32 + 1);
+        }
+        assert_eq!(*game.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::IllegalMoveForfeit));
+        assert_eq!(game.illegal_moves(PlayerColor::White), 0);
+        // attempts after the end of the game are not counted
+        game.do_move(ChessMove::from_coords("e7", "e4").unwrap()).unwrap_err();
+        assert_eq!(game.illegal_moves(PlayerColor::Black), 3);
+
+        let mut resetting = ChessGame::new(Board::default_board());
+        resetting.set_illegal_move_limit(Some(2));
+        resetting.set_illegal_move_reset(true);
+        resetting.do_move(ChessMove::from_coords("e2", "e5").unwrap()).unwrap_err();
+        resetting.try_move_str("e2e4").unwrap();
+        assert_eq!(resetting.illegal_moves(PlayerColor::White), 0);
+        resetting.do_move(ChessMove::from_coords("e7", "e4").unwrap()).unwrap_err();
+        resetting.try_move_str("e7e5").unwrap();
+        resetting.do_move(ChessMove::from_coords("e4", "e5").unwrap()).unwrap_err();
+        assert_eq!(*resetting.game_status(), GameStatus::Normal);
+        resetting.do_move(ChessMove::from_coords("d1", "d8").unwrap()).unwrap_err();
+        assert_eq!(*resetting.game_status(),
+                   GameStatus::Win(PlayerColor::Black, WinReason::IllegalMoveForfeit));
+    }
+
+    #[test]
+    fn illegal_move_limit_disabled_by_default() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.illegal_move_limit(), None);
+        for _ in 0..10 {
+            game.do_move(ChessMove::from_coords("e2", "e5").unwrap()).unwrap_err();
+        }
+        assert_eq!(game.illegal_moves(PlayerColor::White), 10);
+        assert_eq!(*game.game_status(), GameStatus::NotYetStarted);
+        game.try_move_str("e2e4").unwrap();
+        assert_eq!(game.illegal_moves(PlayerColor::White), 10);
     }
 }
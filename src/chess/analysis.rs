@@ -0,0 +1,205 @@
+//! Engine analysis of the moves in a game's history, see [ChessGame::annotate_history]. The
+//! analysis of a move is stored in [MoveDetails::analysis] and can be exported in PGN with
+//! [write_annotated_game](crate::pgn::write_annotated_game).
+
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use crate::board::piece::PlayerColor;
+#[cfg(feature = "std")]
+use crate::chess::{ChessGame, MoveDetails};
+use crate::eval::{is_mate_score, MATE_SCORE};
+use crate::moves::ChessMove;
+#[cfg(feature = "std")]
+use crate::search::{best_move, game_over_score};
+
+/// The centipawn loss from which a move is annotated as an
+/// [inaccuracy](MoveAnnotation::Inaccuracy).
+pub const INACCURACY_THRESHOLD: i32 = 50;
+/// The centipawn loss from which a move is annotated as a [mistake](MoveAnnotation::Mistake).
+pub const MISTAKE_THRESHOLD: i32 = 100;
+/// The centipawn loss from which a move is annotated as a [blunder](MoveAnnotation::Blunder).
+pub const BLUNDER_THRESHOLD: i32 = 300;
+
+/// A judgement of a move, derived from how many centipawns worse it is than the best move.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MoveAnnotation {
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveAnnotation {
+    /// returns: The annotation for a move with the given centipawn loss, see
+    ///          [INACCURACY_THRESHOLD], [MISTAKE_THRESHOLD] and [BLUNDER_THRESHOLD].
+    pub fn from_centipawn_loss(centipawn_loss: i32) -> MoveAnnotation {
+        if centipawn_loss >= BLUNDER_THRESHOLD {
+            MoveAnnotation::Blunder
+        } else if centipawn_loss >= MISTAKE_THRESHOLD {
+            MoveAnnotation::Mistake
+        } else if centipawn_loss >= INACCURACY_THRESHOLD {
+            MoveAnnotation::Inaccuracy
+        } else {
+            MoveAnnotation::Good
+        }
+    }
+
+    /// returns: The Numeric Annotation Glyph of the annotation used in PGN: `4` (`??`) for a
+    ///          blunder, `2` (`?`) for a mistake, `6` (`?!`) for an inaccuracy, and `None` for a
+    ///          good move.
+    pub fn nag(&self) -> Option<u8> {
+        match self {
+            MoveAnnotation::Good => None,
+            MoveAnnotation::Inaccuracy => Some(6),
+            MoveAnnotation::Mistake => Some(2),
+            MoveAnnotation::Blunder => Some(4),
+        }
+    }
+}
+
+/// The engine analysis of a move, see [ChessGame::annotate_history]. Evaluations are in
+/// centipawns from the perspective of white, where checkmates are scored as in the
+/// [search](crate::eval::MATE_SCORE).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MoveAnalysis {
+    /// The evaluation of the position before the move.
+    pub eval_before: i32,
+    /// The evaluation of the position after the move. `MATE_SCORE` or `-MATE_SCORE` if the move
+    /// checkmates, and `0` if the game is drawn after the move.
+    pub eval_after: i32,
+    /// The best move found in the position before the move, which may be the move itself.
+    pub best_move: ChessMove,
+    /// How many centipawns worse the move is than the best move, for the player making it. Never
+    /// negative.
+    pub centipawn_loss: i32,
+    /// The annotation derived from the centipawn loss.
+    pub annotation: MoveAnnotation,
+}
+
+impl MoveAnalysis {
+    /// returns: The evaluation after the move as written in a PGN `[%eval ...]` comment: in pawns
+    ///          with two decimals, such as `"0.35"` or `"-1.20"`, or `#` followed by the number
+    ///          of moves until checkmate, such as `"#3"` or `"#-2"` if black is mating.
+    pub fn eval_string(&self) -> String {
+        let score = self.eval_after;
+        if is_mate_score(score) {
+            let moves = (MATE_SCORE - score.abs() + 1) / 2;
+            if score > 0 {
+                format!("#{}", moves)
+            } else {
+                format!("#-{}", moves)
+            }
+        } else {
+            format!("{:.2}", score as f64 / 100.0)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// returns: A score from the perspective of the player to move converted to the perspective of
+///          white.
+fn white_perspective(score: i32, active_player: PlayerColor) -> i32 {
+    match active_player {
+        PlayerColor::White => score,
+        PlayerColor::Black => -score,
+    }
+}
+
+#[cfg(feature = "std")]
+/// returns: The analysis of a move played in the given position.
+fn analyze_move(position: &ChessGame, details: &MoveDetails, depth: u32) -> Option<MoveAnalysis> {
+    let (best, best_score) = best_move(position, depth)?;
+    let mut child = position.clone();
+    child.replay(details).ok()?;
+    let (score, eval_after) = match best_move(&child, depth - 1) {
+        Some((_, reply_score)) => {
+            (-reply_score, white_perspective(reply_score, child.active_player()))
+        }
+        None => {
+            let final_score = game_over_score(&child, 0);
+            (-game_over_score(&child, 1), white_perspective(final_score, child.active_player()))
+        }
+    };
+    let centipawn_loss = if details.chess_move == best {
+        0
+    } else {
+        (best_score - score).max(0)
+    };
+    Some(MoveAnalysis {
+        eval_before: white_perspective(best_score, position.active_player()),
+        eval_after,
+        best_move: best,
+        centipawn_loss,
+        annotation: MoveAnnotation::from_centipawn_loss(centipawn_loss),
+    })
+}
+
+#[cfg(feature = "std")]
+impl ChessGame {
+    /// Analyzes every move in the game's [history](ChessGame::history) by running a
+    /// [search](crate::search::best_move) on the positions before and after the move, and stores
+    /// the results in [MoveDetails::analysis], replacing any earlier analysis. Each move takes
+    /// two searches, so this is slow for long games or high depths. Observers are not notified.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth`: The depth in plies of the searches in the positions before the moves. Depths
+    ///   below 2 are treated as 2.
+    pub fn annotate_history(&mut self, depth: u32) {
+        let depth = depth.max(2);
+        let mut position = self.starting_position();
+        for details in &mut self.history {
+            details.analysis = analyze_move(&position, details, depth);
+            // the history only contains legal moves, so this always succeeds
+            let _ = position.replay(details);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn blunder_is_flagged() {
+        let mut game = ChessGame::new(Board::default_board());
+        let moves = [("e2", "e4"), ("e7", "e5"), ("d1", "h5"), ("b8", "c6"), ("f1", "c4"),
+            ("g8", "f6"), ("h5", "f7")];
+        for (from, to) in moves {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        assert!(game.history().iter().all(|details| details.analysis.is_none()));
+        game.annotate_history(2);
+
+        let analyses: Vec<MoveAnalysis> = game.history().iter()
+            .map(|details| details.analysis.unwrap())
+            .collect();
+        // Nf6 allows Qxf7#
+        let blunder = analyses[5];
+        assert_eq!(blunder.annotation, MoveAnnotation::Blunder);
+        assert_ne!(blunder.best_move, ChessMove::from_coords("g8", "f6").unwrap());
+        assert_eq!(blunder.eval_string(), "#1");
+        assert_eq!(blunder.eval_after, MATE_SCORE - 1);
+
+        let mate = analyses[6];
+        assert_eq!(mate.annotation, MoveAnnotation::Good);
+        assert_eq!(mate.best_move, ChessMove::from_coords("h5", "f7").unwrap());
+        assert_eq!(mate.centipawn_loss, 0);
+        assert_eq!(mate.eval_after, MATE_SCORE);
+        assert_eq!(mate.eval_before, MATE_SCORE - 1);
+        assert!(analyses[..5].iter().all(|analysis| !is_mate_score(analysis.eval_after)));
+    }
+
+    #[test]
+    fn annotation_thresholds() {
+        assert_eq!(MoveAnnotation::from_centipawn_loss(0), MoveAnnotation::Good);
+        assert_eq!(MoveAnnotation::from_centipawn_loss(49), MoveAnnotation::Good);
+        assert_eq!(MoveAnnotation::from_centipawn_loss(50), MoveAnnotation::Inaccuracy);
+        assert_eq!(MoveAnnotation::from_centipawn_loss(150), MoveAnnotation::Mistake);
+        assert_eq!(MoveAnnotation::from_centipawn_loss(300), MoveAnnotation::Blunder);
+        assert_eq!(MoveAnnotation::Blunder.nag(), Some(4));
+        assert_eq!(MoveAnnotation::Good.nag(), None);
+    }
+}
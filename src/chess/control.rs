@@ -0,0 +1,142 @@
+use alloc::vec::Vec;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::ChessGame;
+use crate::moves;
+
+/// The number of pieces of a player attacking each square of the board, for example for showing
+/// a heatmap of the squares the player controls. See [ChessGame::control_map].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ControlMap {
+    /// The counts indexed by file and then by rank, like [BoardPosition] coordinates.
+    counts: [[u8; 8]; 8],
+}
+
+impl ControlMap {
+    /// returns: The number of the player's pieces attacking the square.
+    pub fn get(&self, pos: BoardPosition) -> u8 {
+        self.counts[pos.file.get() as usize][pos.rank.get() as usize]
+    }
+
+    /// returns: The counts indexed by file and then by rank, so that `a1` is `[0][0]` and `h1`
+    ///          is `[7][0]`.
+    pub fn to_array(&self) -> [[u8; 8]; 8] {
+        self.counts
+    }
+
+    /// returns: The sum of the counts of all squares.
+    pub fn total(&self) -> u32 {
+        self.counts.iter().flatten().map(|&count| count as u32).sum()
+    }
+}
+
+impl ChessGame {
+    /// Counts for each square how many of the player's pieces attack it, whether the square is
+    /// empty, holds an enemy piece, or holds one of the player's own pieces, which then counts as
+    /// defended. Pawns only attack the squares they capture on. Pieces lined up behind an
+    /// attacker on the same line also count if they would attack the square once the pieces in
+    /// front of them have moved there, such as a rook behind a queen, or a queen behind a bishop
+    /// or a pawn (a battery).
+    ///
+    /// returns: The [ControlMap] of the player.
+    pub fn control_map(&self, player: PlayerColor) -> ControlMap {
+        let mut map = ControlMap::default();
+        let mut board = self.board().clone();
+        for pos in BoardPosition::all() {
+            let mut count = 0;
+            let mut removed = Vec::new();
+            loop {
+                // the attackers of the player are enemy pieces of the other player
+                let attackers = moves::attacking_pieces_in_variant(&board, player.other_player(),
+                                                                   pos, self.variant());
+                if attackers.is_all_zeros() {
+                    break;
+                }
+                // removing the attackers uncovers the pieces lined up behind them
+                for attacker in BoardPosition::all().filter(|&square| attackers.get(square)) {
+                    count += 1;
+                    removed.push((attacker, board.get_piece(attacker)));
+                    board.set_piece(attacker, None);
+                }
+            }
+            for (square, piece) in removed {
+                board.set_piece(square, piece);
+            }
+            map.counts[pos.file.get() as usize][pos.rank.get() as usize] = count;
+        }
+        map
+    }
+
+    /// returns: The difference of the [control maps](ChessGame::control_map) of white and black
+    ///          for each square, indexed by file and then by rank, which is positive where white
+    ///          has more attackers than black.
+    pub fn control_balance(&self) -> [[i8; 8]; 8] {
+        let white = self.control_map(PlayerColor::White).to_array();
+        let black = self.control_map(PlayerColor::Black).to_array();
+        let mut balance = [[0; 8]; 8];
+        for file in 0..8 {
+            for rank in 0..8 {
+                balance[file][rank] = white[file][rank] as i8 - black[file][rank] as i8;
+            }
+        }
+        balance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use super::*;
+
+    fn square(pos: &str) -> BoardPosition {
+        pos.parse().unwrap()
+    }
+
+    #[test]
+    fn starting_position_control() {
+        let game = ChessGame::new(Board::default_board());
+        let white = game.control_map(PlayerColor::White);
+        let black = game.control_map(PlayerColor::Black);
+        assert_eq!(white.get(square("e4")), 0);
+        assert_eq!(white.get(square("d4")), 0);
+        // two pawns, and the bishop behind the pawn on d2
+        assert_eq!(white.get(square("e3")), 3);
+        // two pawns, the knight, and the queen behind the pawn on e2
+        assert_eq!(white.get(square("f3")), 4);
+        // the king, the queen, the bishop and the knight defend the pawn
+        assert_eq!(white.get(square("e2")), 4);
+        assert_eq!(black.get(square("f6")), 4);
+        assert_eq!(black.get(square("e5")), 0);
+        assert_eq!(white.total(), black.total());
+        // the position is symmetric, so each square has the opposite balance of its mirror
+        let balance = game.control_balance();
+        assert_eq!(balance[4][2], 3);
+        assert!((0..64).all(|i| balance[i / 8][i % 8] == -balance[i / 8][7 - i % 8]));
+
+        let mut game = game;
+        for chess_move in ["e4", "e5", "d4"] {
+            game.try_move_str(chess_move).unwrap();
+        }
+        assert_eq!(game.control_map(PlayerColor::White).get(square("e5")), 1);
+        assert_eq!(game.control_map(PlayerColor::White).get(square("d4")), 1);
+        assert_eq!(game.control_map(PlayerColor::Black).get(square("d4")), 1);
+        assert_eq!(game.control_balance()[3][3], 0);
+    }
+
+    #[test]
+    fn battery_control() {
+        // a queen and two rooks doubled on the d-file, and a queen behind a pawn
+        let game = ChessGame::from_fen("3r2k1/8/8/8/2P5/3Q4/1B1R4/3RK3 w - - 0 1").unwrap();
+        let white = game.control_map(PlayerColor::White);
+        assert_eq!(white.get(square("d8")), 3);
+        assert_eq!(white.get(square("d5")), 4);
+        assert_eq!(white.get(square("b5")), 2);
+        // the black rook attacks the queen, and the rooks behind it are blocked
+        let black = game.control_map(PlayerColor::Black);
+        assert_eq!(black.get(square("d3")), 1);
+        assert_eq!(black.get(square("d2")), 0);
+        assert_eq!(game.control_balance()[3][7], 3);
+        // the queen is defended by both rooks
+        assert_eq!(game.control_balance()[3][2], 2 - 1);
+    }
+}
@@ -0,0 +1,537 @@
+//! Piece drops for [Crazyhouse](Variant::Crazyhouse): captured pieces change color and go into
+//! the capturing player's [Hand], and instead of moving a piece, a player may use their turn to
+//! drop a piece from their hand on any empty square with [ChessGame::do_drop]. Drops are also
+//! [ChessMove]s (see [ChessMove::dropping]), so they are included in the
+//! [legal moves](ChessGame::legal_moves) and may be performed with
+//! [do_move](ChessGame::do_move).
+//!
+//! A promoted piece turns back into a pawn when it is captured. Pawns may not be dropped on the
+//! first or last rank, and drops may give check and checkmate.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::chess::{ChessError, ChessGame, GameStatus, MoveDetails, NotationError, WinReason};
+use crate::chess::variant::Variant;
+use crate::moves;
+use crate::moves::{ChessMove, MoveResult};
+
+/// The pieces a player holds in Crazyhouse, which are available for dropping. Kings can never be
+/// held.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Hand {
+    counts: [u8; 5],
+}
+
+impl Hand {
+    /// Instantiate an empty hand
+    pub fn new() -> Hand {
+        Hand::default()
+    }
+
+    /// returns: How many pieces of the given type are held.
+    pub fn count(&self, piece_type: PieceType) -> u8 {
+        self.counts.get(piece_type as usize).copied().unwrap_or(0)
+    }
+
+    /// returns: Whether no pieces are held.
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+
+    /// returns: The types of the pieces held, in the order pawn, knight, bishop, rook, queen,
+    ///          each repeated as many times as it is held.
+    pub fn pieces(&self) -> Vec<PieceType> {
+        PieceType::ALL.into_iter()
+            .flat_map(|piece_type| {
+                core::iter::repeat_n(piece_type, self.count(piece_type) as usize)
+            })
+            .collect()
+    }
+
+    fn add(&mut self, piece_type: PieceType) {
+        if let Some(count) = self.counts.get_mut(piece_type as usize) {
+            *count += 1;
+        }
+    }
+
+    fn remove(&mut self, piece_type: PieceType) {
+        if let Some(count) = self.counts.get_mut(piece_type as usize) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+/// A move which drops a piece from the active player's [Hand] on an empty square.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DropMove {
+    /// The type of the dropped piece.
+    pub piece: PieceType,
+    /// The square the piece is dropped on.
+    pub to: BoardPosition,
+}
+
+impl DropMove {
+    /// Instantiate a new drop move
+    pub fn new(piece: PieceType, to: BoardPosition) -> DropMove {
+        DropMove { piece, to }
+    }
+
+    /// returns: The drop a [ChessMove] represents, or `None` if it is not a
+    ///          [drop](ChessMove::dropping).
+    pub fn from_move(chess_move: ChessMove) -> Option<DropMove> {
+        chess_move.drop.map(|piece| DropMove::new(piece, chess_move.piece_movement.to))
+    }
+}
+
+impl From<DropMove> for ChessMove {
+    fn from(drop: DropMove) -> Self {
+        ChessMove::dropping(drop.piece, drop.to)
+    }
+}
+
+impl Display for DropMove {
+    /// Formats the drop as the piece letter followed by `@` and the square, for example `N@f3` or
+    /// `P@e6`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}@{}", self.piece.to_char(), self.to)
+    }
+}
+
+impl FromStr for DropMove {
+    type Err = NotationError;
+
+    /// Parses a drop written like its [Display] form, such as `"N@f3"`, which is also how drops
+    /// are written in SAN and in UCI. The piece letter may be lowercase, and a pawn drop may
+    /// leave it out, as in `"@e4"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (piece, to) = s.split_once('@').ok_or(NotationError::InvalidSyntax)?;
+        let piece: PieceType = piece.parse().map_err(|_| NotationError::InvalidSyntax)?;
+        let to = to.parse().map_err(|_| NotationError::InvalidSyntax)?;
+        if piece == PieceType::King {
+            return Err(NotationError::InvalidSyntax);
+        }
+        Ok(DropMove::new(piece, to))
+    }
+}
+
+impl ChessGame {
+    /// returns: The pieces held by a player in [Crazyhouse](Variant::Crazyhouse). Always empty in
+    ///          other variants.
+    pub fn hand(&self, player: PlayerColor) -> &Hand {
+        match player {
+            PlayerColor::White => &self.hands.0,
+            PlayerColor::Black => &self.hands.1,
+        }
+    }
+
+    fn hand_mut(&mut self, player: PlayerColor) -> &mut Hand {
+        match player {
+            PlayerColor::White => &mut self.hands.0,
+            PlayerColor::Black => &mut self.hands.1,
+        }
+    }
+
+    /// returns: Whether a drop is legal for the active player: the game is a Crazyhouse game
+    ///          which is not over, the piece is in the player's hand, the square is empty, a pawn
+    ///          is not dropped on the first or last rank, and the player is not in check after
+    ///          the drop.
+    pub fn is_legal_drop(&self, drop: DropMove) -> bool {
+        if self.variant != Variant::Crazyhouse || self.game_status.is_over()
+            || self.hand(self.active_player).count(drop.piece) == 0
+            || self.board.get_piece(drop.to).is_some()
+        {
+            return false;
+        }
+        if drop.piece == PieceType::Pawn && matches!(drop.to.rank.get(), 0 | 7) {
+            return false;
+        }
+        if !self.is_check() {
+            return true;
+        }
+        let mut board = self.board.clone();
+        board.set_piece(drop.to, Some(Piece::new(drop.piece, self.active_player)));
+        !moves::is_in_check(&board, self.active_player, self.variant)
+    }
+
+    /// returns: All legal drops of the active player, ordered by piece type and then by square in
+    ///          the order of [BoardPosition::all]. Empty outside of Crazyhouse.
+    pub fn legal_drops(&self) -> Vec<DropMove> {
+        let hand = self.hand(self.active_player);
+        PieceType::ALL.into_iter()
+            .filter(|&piece_type| hand.count(piece_type) > 0)
+            .flat_map(|piece_type| {
+                BoardPosition::all().map(move |to| DropMove::new(piece_type, to))
+            })
+            .filter(|&drop| self.is_legal_drop(drop))
+            .collect()
+    }
+
+    /// returns: Whether the active player has a legal drop.
+    pub(super) fn has_legal_drop(&self) -> bool {
+        self.variant == Variant::Crazyhouse && !self.hand(self.active_player).is_empty()
+            && !self.legal_drops().is_empty()
+    }
+
+    /// Drops a piece from the active player's hand, if legal (see
+    /// [is_legal_drop](ChessGame::is_legal_drop)), like performing the drop as a move with
+    /// [do_move](ChessGame::do_move): the turn is given to the other player, the game status is
+    /// updated and the observer is notified.
+    ///
+    /// The drop is recorded in the [history](ChessGame::history) with
+    /// [is_drop](MoveDetails::is_drop) set, the dropped piece as the moved piece, and the drop as
+    /// the move (see [ChessMove::dropping]). An illegal drop counts towards the
+    /// [illegal move limit](ChessGame::set_illegal_move_limit), if any, and a legal drop is
+    /// followed by the other player's [premove](ChessGame::queue_premove), if any.
+    ///
+    /// returns: `Ok(MoveDetails)` if the piece was dropped, otherwise
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is over, or
+    ///          [IllegalMove](ChessError::IllegalMove) if the drop is not legal.
+    pub fn do_drop(&mut self, drop: DropMove) -> Result<MoveDetails, ChessError> {
+        self.do_move(drop.into())
+    }
+
+    /// returns: `Ok(())` if a drop is legal, and `Err(ChessError)` otherwise, like for
+    ///          [do_drop](ChessGame::do_drop).
+    pub(super) fn check_drop(&self, drop: DropMove) -> Result<(), ChessError> {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        if !self.is_legal_drop(drop) {
+            return Err(ChessError::IllegalMove(drop.into()));
+        }
+        Ok(())
+    }
+
+    /// Puts a dropped piece on the board and takes it from the hand, after the drop has been
+    /// checked with [check_drop](ChessGame::check_drop), and gives the turn to the other player.
+    ///
+    /// returns: The details of the drop for the history.
+    pub(super) fn place_drop(&mut self, drop: DropMove) -> MoveDetails {
+        let piece = Piece::new(drop.piece, self.active_player);
+        self.board.set_piece(drop.to, Some(piece));
+        self.hand_mut(self.active_player).remove(drop.piece);
+        self.promoted.set(drop.to, false);
+        self.after_move(MoveResult {
+            removed_piece: None,
+            secondary_movement: None,
+            new_en_passant_target: None,
+            removes_queenside_castling_rights: false,
+            removes_kingside_castling_rights: false,
+        });
+        MoveDetails {
+            chess_move: drop.into(),
+            piece,
+            captured_piece: None,
+            secondary_movement: None,
+            removed_piece: None,
+            analysis: None,
+            is_drop: true,
+        }
+    }
+
+    pub(super) fn perform_drop(&mut self, drop: DropMove) -> Result<MoveDetails, ChessError> {
+        self.check_drop(drop)?;
+        let previous_key = self.polyglot_key();
+        let previous_castling_rights = self.castling_rights;
+        let mut undo_state = self.undo_state();
+        let details = self.place_drop(drop);
+        undo_state.forgotten_position_keys
+            = self.record_position(previous_key, previous_castling_rights, &details);
+        self.history.push(details);
+        self.undo_stack.push(undo_state);
+        self.observer.notify(|observer| observer.on_move(&details));
+        if self.game_status == GameStatus::NotYetStarted {
+            self.set_game_status(GameStatus::Normal);
+        }
+        self.update_game_status();
+        debug_assert_eq!(self.verify_integrity(), Ok(()));
+        Ok(details)
+    }
+
+    /// Returns the standard algebraic notation of a drop in the current position, for example
+    /// `"N@f3"`, `"P@e6+"` or `"Q@g7#"`.
+    ///
+    /// returns: `Some(String)` if the drop is legal, otherwise `None`.
+    pub fn drop_to_san(&self, drop: DropMove) -> Option<String> {
+        let mut game_after_drop = self.analysis_copy();
+        game_after_drop.do_drop(drop).ok()?;
+        let mut san = drop.to_string();
+        if *game_after_drop.game_status()
+            == GameStatus::Win(self.active_player, WinReason::Checkmate)
+        {
+            san.push('#');
+        } else if game_after_drop.is_check() {
+            san.push('+');
+        }
+        Some(san)
+    }
+
+    /// Interprets a drop given in standard algebraic notation in the current position, such as
+    /// `"N@f3"` or `"Q@g7#"`. Like with [parse_san](ChessGame::parse_san), check and mate
+    /// markers and annotations are ignored.
+    ///
+    /// returns: `Ok(DropMove)` of the legal drop the notation refers to, otherwise a
+    ///          [NotationError].
+    pub fn parse_san_drop(&self, san: &str) -> Result<DropMove, NotationError> {
+        let drop: DropMove = san.trim().trim_end_matches(['+', '#', '!', '?']).parse()?;
+        if !self.is_legal_drop(drop) {
+            return Err(NotationError::IllegalMove);
+        }
+        Ok(drop)
+    }
+
+    /// Updates the hands and the squares of promoted pieces after a move of the active player
+    /// has been performed on the board, before the turn changes.
+    pub(super) fn track_crazyhouse_move(&mut self, details: &MoveDetails) {
+        if self.variant != Variant::Crazyhouse {
+            return;
+        }
+        if let Some((pos, piece)) = details.removed_piece {
+            let piece_type = if self.promoted.get(pos) {
+                PieceType::Pawn
+            } else {
+                piece.piece_type
+            };
+            self.promoted.set(pos, false);
+            self.hand_mut(self.active_player).add(piece_type);
+        }
+        let movement = details.chess_move.piece_movement;
+        let promoted = self.promoted.get(movement.from) || details.chess_move.promotion.is_some();
+        self.promoted.set(movement.from, false);
+        self.promoted.set(movement.to, promoted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::moves::{CastlingRights, PromotionType};
+
+    fn position(fen: &str, active_player: PlayerColor) -> ChessGame {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string(fen).unwrap(), active_player, no_rights, None)
+            .unwrap();
+        game
+    }
+
+    fn play(game: &mut ChessGame, coords: &[(&str, &str)]) {
+        for (from, to) in coords {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+    }
+
+    fn drop(piece: PieceType, to: &str) -> DropMove {
+        DropMove::new(piece, to.parse().unwrap())
+    }
+
+    #[test]
+    fn captures_fill_hands() {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        play(&mut game, &[("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("d8", "d5")]);
+        assert_eq!(game.hand(PlayerColor::White).pieces(), [PieceType::Pawn]);
+        assert_eq!(game.hand(PlayerColor::Black).pieces(), [PieceType::Pawn]);
+
+        let details = game.do_drop(drop(PieceType::Pawn, "e6")).unwrap();
+        assert!(details.is_drop);
+        assert_eq!(details.piece, Piece::new(PieceType::Pawn, PlayerColor::White));
+        assert!(game.hand(PlayerColor::White).is_empty());
+        assert_eq!(game.active_player(), PlayerColor::Black);
+        assert_eq!(game.board().get_piece("e6".parse().unwrap()), Some(details.piece));
+        // the piece is no longer in the hand
+        play(&mut game, &[("d5", "a5")]);
+        let e5 = "e5".parse().unwrap();
+        assert_eq!(game.do_drop(drop(PieceType::Pawn, "e5")),
+                   Err(ChessError::IllegalMove(ChessMove::dropping(PieceType::Pawn, e5))));
+
+        // replaying the history from the starting position restores the hands
+        let mut replay = game.starting_position();
+        for details in game.history() {
+            replay.replay(details).unwrap();
+        }
+        assert_eq!(replay.hand(PlayerColor::White), game.hand(PlayerColor::White));
+        assert_eq!(replay.hand(PlayerColor::Black), game.hand(PlayerColor::Black));
+        assert_eq!(replay.fen(), game.fen());
+        assert_eq!(replay.history(), game.history());
+        let pgn = crate::pgn::write_game(&game, &[]);
+        assert!(pgn.contains("2. exd5 Qxd5 3. P@e6 Qa5 *"));
+
+        // drops are only possible in Crazyhouse
+        let mut standard = ChessGame::new(Board::default_board());
+        play(&mut standard, &[("e2", "e4"), ("d7", "d5"), ("e4", "d5")]);
+        assert!(standard.hand(PlayerColor::White).is_empty());
+        assert!(standard.legal_drops().is_empty());
+    }
+
+    #[test]
+    fn drop_legality() {
+        let mut game = position("4k3/8/8/8/8/8/8/R3K3", PlayerColor::White);
+        game.hand_mut(PlayerColor::White).add(PieceType::Pawn);
+        game.hand_mut(PlayerColor::White).add(PieceType::Knight);
+        // pawns may not be dropped on the first or last rank, or on occupied squares
+        assert!(!game.is_legal_drop(drop(PieceType::Pawn, "b8")));
+        assert!(!game.is_legal_drop(drop(PieceType::Pawn, "b1")));
+        assert!(!game.is_legal_drop(drop(PieceType::Knight, "a1")));
+        assert!(!game.is_legal_drop(drop(PieceType::Queen, "d4")));
+        assert!(game.is_legal_drop(drop(PieceType::Pawn, "b7")));
+        assert!(game.is_legal_drop(drop(PieceType::Knight, "b8")));
+        assert_eq!(game.legal_drops().len(), 48 + 61);
+        assert_eq!(game.drop_to_san(drop(PieceType::Knight, "d6")), Some("N@d6+".to_string()));
+
+        // in check, a drop has to block the check
+        let mut game = position("4k3/8/8/8/8/8/8/r3K3", PlayerColor::White);
+        game.hand_mut(PlayerColor::White).add(PieceType::Knight);
+        let drops: Vec<String> = game.legal_drops().iter().map(|drop| drop.to_string()).collect();
+        assert_eq!(drops, ["N@b1", "N@c1", "N@d1"]);
+    }
+
+    #[test]
+    fn drop_checkmate() {
+        // the king can not escape and no piece can take the queen on g7
+        let mut game = position("6k1/5p1p/7P/8/8/8/8/4K3", PlayerColor::White);
+        game.hand_mut(PlayerColor::White).add(PieceType::Queen);
+        assert_eq!(game.drop_to_san(drop(PieceType::Queen, "g7")), Some("Q@g7#".to_string()));
+        let mate = ChessMove::dropping(PieceType::Queen, "g7".parse().unwrap());
+        assert_eq!(game.move_to_san(mate), Some("Q@g7#".to_string()));
+        assert!(game.gives_check(mate));
+        assert!(game.legal_checking_moves().contains(&mate));
+        game.do_drop(drop(PieceType::Queen, "g7")).unwrap();
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::Checkmate));
+
+        // a drop which blocks the check prevents checkmate
+        let mut game = position("4k3/8/4K3/8/8/8/8/R7", PlayerColor::White);
+        game.hand_mut(PlayerColor::Black).add(PieceType::Bishop);
+        play(&mut game, &[("a1", "a8")]);
+        assert!(!game.game_status().is_over());
+        assert_eq!(game.legal_drops().len(), 3);
+    }
+
+    #[test]
+    fn drop_notation() {
+        for notation in ["N@f3", "P@e4", "Q@h8"] {
+            let parsed: DropMove = notation.parse().unwrap();
+            assert_eq!(parsed.to_string(), notation);
+        }
+        assert_eq!("n@f3".parse(), Ok(drop(PieceType::Knight, "f3")));
+        assert_eq!("@e4".parse(), Ok(drop(PieceType::Pawn, "e4")));
+        for invalid in ["K@e4", "N@", "Nf3", "X@f3", "N@f9", "NB@f3", ""] {
+            assert_eq!(invalid.parse::<DropMove>(), Err(NotationError::InvalidSyntax),
+                       "{}", invalid);
+        }
+
+        let mut game = position("6k1/5p1p/7P/8/8/8/8/4K3", PlayerColor::White);
+        game.hand_mut(PlayerColor::White).add(PieceType::Queen);
+        assert_eq!(game.parse_san_drop("N@f3"), Err(NotationError::IllegalMove));
+        assert_eq!(game.parse_san_drop("Q@h6"), Err(NotationError::IllegalMove));
+        let mate = game.parse_san_drop("Q@g7#").unwrap();
+        assert_eq!(game.drop_to_san(mate), Some("Q@g7#".to_string()));
+        let g7 = "g7".parse().unwrap();
+        assert_eq!(game.try_move_str("Q@g7"), Ok(ChessMove::dropping(PieceType::Queen, g7)));
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::Checkmate));
+        assert!(game.history()[0].is_drop);
+    }
+
+    #[test]
+    fn drops_are_legal_moves() {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        play(&mut game, &[("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("d8", "d5")]);
+        let legal_moves = game.legal_moves();
+        let drops: Vec<ChessMove> = game.legal_drops().into_iter().map(ChessMove::from).collect();
+        assert_eq!(drops.len(), 33);
+        assert_eq!(legal_moves[legal_moves.len() - drops.len()..], drops);
+        let mut sorted = legal_moves.clone();
+        moves::sort_moves_canonical(&mut sorted);
+        assert_eq!(sorted, legal_moves);
+        assert_eq!(game.perft(1), legal_moves.len() as u64);
+        let targets: Vec<BoardPosition> = game.drop_targets(PieceType::Pawn).iter()
+            .map(|target| target.to)
+            .collect();
+        assert_eq!(targets, drops.iter().map(|drop| drop.piece_movement.to).collect::<Vec<_>>());
+        assert!(game.drop_targets(PieceType::Knight).is_empty());
+
+        let e6 = ChessMove::dropping(PieceType::Pawn, "e6".parse().unwrap());
+        let e1 = ChessMove::dropping(PieceType::Pawn, "e1".parse().unwrap());
+        let a6 = ChessMove::dropping(PieceType::Knight, "a6".parse().unwrap());
+        assert_eq!(game.filter_legal(&[e6, e1, a6]), [true, false, false]);
+        assert_eq!(DropMove::from_move(e6), Some(drop(PieceType::Pawn, "e6")));
+        assert_eq!(DropMove::from_move(ChessMove::from_coords("e6", "e6").unwrap()), None);
+        // the drop is written and read like `P@e6` in every notation
+        assert_eq!(game.move_to_san(e6), Some("P@e6".to_string()));
+        assert_eq!(e6.to_string(), "P@e6");
+        assert_eq!(game.parse_san("P@e6"), Ok(e6));
+        assert_eq!(game.parse_coordinate_move("P@e6"), Ok(e6));
+        assert_eq!(game.parse_coordinate_move("P@e1"), Err(NotationError::IllegalMove));
+
+        let details = game.do_move(e6).unwrap();
+        assert!(details.is_drop);
+        assert_eq!(details.chess_move, e6);
+        assert!(game.hand(PlayerColor::White).is_empty());
+        // black also holds a pawn, but the square is taken
+        assert_eq!(game.do_move(e6), Err(ChessError::IllegalMove(e6)));
+        game.undo_move().unwrap();
+        assert_eq!(game.legal_moves(), legal_moves);
+    }
+
+    #[test]
+    fn captured_promoted_pieces_become_pawns() {
+        let mut game = position("1r2k3/P7/8/8/8/8/8/4K3", PlayerColor::White);
+        let a7 = "a7".parse().unwrap();
+        let b8 = "b8".parse().unwrap();
+        game.do_move(ChessMove::promoting(a7, b8, PromotionType::Queen)).unwrap();
+        assert_eq!(game.hand(PlayerColor::White).pieces(), [PieceType::Rook]);
+        play(&mut game, &[("e8", "d7"), ("b8", "c7"), ("d7", "c7")]);
+        assert_eq!(game.hand(PlayerColor::Black).pieces(), [PieceType::Pawn]);
+    }
+
+    #[test]
+    fn undo_restores_hands() {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        play(&mut game, &[("e2", "e4"), ("d7", "d5"), ("e4", "d5")]);
+        let fen = game.fen();
+        play(&mut game, &[("d8", "d5")]);
+        let legal_drops = game.legal_drops();
+        game.do_drop(drop(PieceType::Pawn, "e6")).unwrap();
+        assert!(game.hand(PlayerColor::White).is_empty());
+
+        // the dropped pawn goes back into the hand
+        let details = game.undo_move().unwrap();
+        assert!(details.is_drop);
+        assert_eq!(game.board().get_piece("e6".parse().unwrap()), None);
+        assert_eq!(game.hand(PlayerColor::White).pieces(), [PieceType::Pawn]);
+        assert_eq!(game.hand(PlayerColor::Black).pieces(), [PieceType::Pawn]);
+        assert_eq!(game.legal_drops(), legal_drops);
+        // the captured pawn leaves the hand and goes back on the board
+        game.undo_move().unwrap();
+        assert!(game.hand(PlayerColor::Black).is_empty());
+        assert_eq!(game.fen(), fen);
+        assert_eq!(game.verify_integrity(), Ok(()));
+
+        // a captured promoted piece leaves the hand as a pawn and goes back as the piece
+        let mut game = position("1r2k3/P7/8/8/8/8/8/4K3", PlayerColor::White);
+        let a7 = "a7".parse().unwrap();
+        let b8 = "b8".parse().unwrap();
+        game.do_move(ChessMove::promoting(a7, b8, PromotionType::Queen)).unwrap();
+        play(&mut game, &[("e8", "d7"), ("b8", "c7")]);
+        let fen = game.fen();
+        play(&mut game, &[("d7", "c7")]);
+        game.undo_move().unwrap();
+        assert!(game.hand(PlayerColor::Black).is_empty());
+        assert_eq!(game.fen(), fen);
+        play(&mut game, &[("d7", "c7")]);
+        assert_eq!(game.hand(PlayerColor::Black).pieces(), [PieceType::Pawn]);
+        for _ in 0..4 {
+            game.undo_move().unwrap();
+        }
+        assert!(game.hand(PlayerColor::White).is_empty());
+        assert_eq!(game.board().get_piece(a7),
+                   Some(Piece::new(PieceType::Pawn, PlayerColor::White)));
+        assert_eq!(game.board().get_piece(b8),
+                   Some(Piece::new(PieceType::Rook, PlayerColor::Black)));
+        assert_eq!(game.verify_integrity(), Ok(()));
+    }
+}
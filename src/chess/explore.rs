@@ -0,0 +1,86 @@
+use crate::chess::{ChessError, ChessGame};
+use crate::moves::ChessMove;
+
+impl ChessGame {
+    /// Returns a copy of the game as it was after the first `ply` moves of its
+    /// [history](ChessGame::history), for example for showing earlier positions while stepping
+    /// through a game. The copy is made by taking back the later moves like
+    /// [undo_move](ChessGame::undo_move), so it keeps the settings and the annotations of the
+    /// game, but not its observer, a pending takeback request or queued premoves. The game itself
+    /// is not modified.
+    ///
+    /// If an [AnalysisCache](crate::search::cache::AnalysisCache) is attached to the game, the
+    /// copy shares it, and the available moves of the position are taken from the cache if it
+    /// has been seen before, instead of being recalculated.
+    ///
+    /// # Arguments
+    ///
+    /// * `ply`: The number of moves of the history to keep, from 0 for the starting position to
+    ///   the length of the history for the current position.
+    ///
+    /// returns: `Some(ChessGame)` in the position after `ply` moves, or `None` if the history has
+    ///          fewer moves.
+    pub fn position_at(&self, ply: usize) -> Option<ChessGame> {
+        let plies = self.history.len().checked_sub(ply)?;
+        let mut game = self.clone();
+        game.takeback_request = None;
+        game.premoves = (None, None);
+        if plies > 0 {
+            game.rewind(plies);
+        }
+        Some(game)
+    }
+
+    /// Returns a copy of the game after a move, for example for previewing a move before making
+    /// it or for exploring variations. The move is performed in the copy like with
+    /// [do_move](ChessGame::do_move), without its observer and queued premoves, and the game
+    /// itself is not modified.
+    ///
+    /// If an [AnalysisCache](crate::search::cache::AnalysisCache) is attached to the game, the
+    /// copy shares it, and the available moves of the position after the move are taken from the
+    /// cache if it has been seen before, instead of being recalculated.
+    ///
+    /// returns: `Ok(ChessGame)` in the position after the move, and `Err(ChessError)` if the move
+    ///          could not be performed, see [do_move](ChessGame::do_move).
+    pub fn peek_move(&self, chess_move: ChessMove) -> Result<ChessGame, ChessError> {
+        let mut game = self.analysis_copy();
+        game.do_move(chess_move)?;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use crate::board::Board;
+    use super::*;
+
+    #[test]
+    fn positions_of_the_history() {
+        let mut game = ChessGame::new(Board::default_board());
+        let mut positions = vec![game.clone()];
+        for (from, to) in [("e2", "e4"), ("e7", "e5"), ("g1", "f3"), ("b8", "c6")] {
+            let chess_move = ChessMove::from_coords(from, to).unwrap();
+            let peeked = game.peek_move(chess_move).unwrap();
+            game.do_move(chess_move).unwrap();
+            assert_eq!(peeked.fen(), game.fen());
+            assert_eq!(peeked.history(), game.history());
+            positions.push(game.clone());
+        }
+        let fen = game.fen();
+        for (ply, position) in positions.iter().enumerate() {
+            let at_ply = game.position_at(ply).unwrap();
+            assert_eq!(at_ply.fen(), position.fen());
+            assert_eq!(at_ply.game_status(), position.game_status());
+            assert_eq!(at_ply.legal_moves(), position.legal_moves());
+            assert_eq!(at_ply.history(), position.history());
+        }
+        assert!(game.position_at(5).is_none());
+        assert_eq!(game.fen(), fen);
+
+        assert_eq!(game.peek_move(ChessMove::from_coords("e4", "e5").unwrap()).unwrap_err(),
+                   ChessError::IllegalMove(ChessMove::from_coords("e4", "e5").unwrap()));
+        assert_eq!(game.fen(), fen);
+        assert_eq!(game.illegal_moves(game.active_player()), 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! Per-player views of a game for fog of war (dark chess), where each player only sees their own
+//! pieces and the squares their pieces could move to or capture on. A server for a fog of war
+//! game should only ever send a player their [visible board](ChessGame::visible_board), so that
+//! no information about hidden pieces leaks to the client. The game itself still enforces the
+//! normal rules of its [variant](crate::chess::variant::Variant).
+
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::ChessGame;
+use crate::moves;
+use crate::moves::MoveContext;
+use crate::moves::util::BoardBitmap;
+
+impl ChessGame {
+    /// Computes the squares visible to a player in fog of war: the squares of the player's own
+    /// pieces, and every square one of the pieces could move to or capture on, ignoring whether
+    /// the move would leave the player's king in check. A pawn sees the squares in front of it
+    /// it could move to, and the diagonal squares only when it could capture there.
+    ///
+    /// returns: A [BoardBitmap] of the visible squares.
+    pub fn visible_squares(&self, viewer: PlayerColor) -> BoardBitmap {
+        let move_context = MoveContext {
+            castling_rights: self.castling_rights(viewer),
+            en_passant_target: self.en_passant_target.filter(|_| viewer == self.active_player),
+            variant: self.variant,
+        };
+        let mut board = self.board.clone();
+        let mut visible = BoardBitmap::all_zeros();
+        for pos in BoardPosition::all() {
+            if !board.get_piece(pos).is_some_and(|piece| piece.player == viewer) {
+                continue;
+            }
+            visible.set(pos, true);
+            let targets = moves::get_pseudo_legal_moves(&mut board, viewer, pos, move_context);
+            visible = BoardBitmap::from_u64(visible.to_u64() | targets.to_u64());
+        }
+        visible
+    }
+
+    /// returns: The board as seen by a player in fog of war: the pieces on the
+    ///          [visible squares](ChessGame::visible_squares), with all other squares empty.
+    pub fn visible_board(&self, viewer: PlayerColor) -> Board {
+        let visible = self.visible_squares(viewer);
+        let mut board = Board::empty_board();
+        for pos in BoardPosition::all().filter(|&pos| visible.get(pos)) {
+            board.set_piece(pos, self.board.get_piece(pos));
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::ChessMove;
+
+    #[test]
+    fn hidden_pieces_are_absent() {
+        let game = ChessGame::new(Board::default_board());
+        for viewer in [PlayerColor::White, PlayerColor::Black] {
+            let board = game.visible_board(viewer);
+            assert!(board.into_iter().filter_map(|(_, piece)| piece)
+                .all(|piece| piece.player == viewer));
+            // the own pieces, the third and the fourth rank from the player's side
+            assert_eq!(game.visible_squares(viewer).to_u64().count_ones(), 32);
+        }
+        assert_eq!(game.visible_board(PlayerColor::White).to_fen_string(),
+                   "8/8/8/8/8/8/PPPPPPPP/RNBQKBNR");
+    }
+
+    #[test]
+    fn visibility_updates_after_moves() {
+        let mut game = ChessGame::new(Board::default_board());
+        let d5 = "d5".parse().unwrap();
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert!(!game.visible_squares(PlayerColor::White).get(d5));
+        // the bishop on f1 and the queen now see along the opened diagonals
+        let visible = game.visible_squares(PlayerColor::White);
+        assert!(visible.get("a6".parse().unwrap()));
+        assert!(visible.get("h5".parse().unwrap()));
+
+        game.do_move(ChessMove::from_coords("d7", "d5").unwrap()).unwrap();
+        // the pawn on e4 can capture on d5
+        assert!(game.visible_squares(PlayerColor::White).get(d5));
+        let board = game.visible_board(PlayerColor::White);
+        assert_eq!(board.get_piece(d5), game.board().get_piece(d5));
+        assert_eq!(board.get_piece("d8".parse().unwrap()), None);
+        // black's pawn on d5 sees the pawn on e4 in return
+        let board = game.visible_board(PlayerColor::Black);
+        let e4 = "e4".parse().unwrap();
+        assert_eq!(board.get_piece(e4), game.board().get_piece(e4));
+        assert_eq!(board.get_piece("e1".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,289 @@
+//! Setup of games with material odds, where the stronger player gives up a piece, or the first
+//! moves of the game, to the weaker player. See [ChessGameBuilder::with_handicap].
+//!
+//! see: [Handicap (chess) - Wikipedia](https://en.wikipedia.org/wiki/Handicap_(chess))
+
+use core::fmt::{Display, Formatter};
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::ChessGame;
+use crate::chess::variant::Variant;
+use crate::moves;
+use crate::moves::{ChessMove, PromotionType};
+use crate::util::U3;
+
+/// A traditional handicap, given by the stronger player to the weaker player.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Handicap {
+    /// The stronger player plays without the f-pawn, and the weaker player moves first.
+    PawnAndMove,
+    /// The stronger player plays without the f-pawn, and the weaker player makes the first two
+    /// moves of the game.
+    PawnAndTwoMoves,
+    /// The stronger player plays without the queenside knight.
+    KnightOdds,
+    /// The stronger player plays without the queenside rook.
+    RookOdds,
+    /// The stronger player plays without the queen.
+    QueenOdds,
+}
+
+impl Handicap {
+    /// returns: The square of the piece the player giving the handicap plays without, in the
+    ///          standard starting position.
+    pub fn removed_square(&self, giver: PlayerColor) -> BoardPosition {
+        let (file, rank) = match self {
+            Handicap::PawnAndMove | Handicap::PawnAndTwoMoves => (5, giver.pawn_start_rank()),
+            Handicap::KnightOdds => (1, giver.back_rank()),
+            Handicap::RookOdds => (0, giver.back_rank()),
+            Handicap::QueenOdds => (3, giver.back_rank()),
+        };
+        BoardPosition { file: U3::from_low_bits(file), rank }
+    }
+
+    /// returns: The number of moves the player receiving the handicap makes in a row at the start
+    ///          of the game. Zero if the handicap does not include any moves, in which case white
+    ///          moves first as usual.
+    pub fn first_moves(&self) -> u32 {
+        match self {
+            Handicap::PawnAndMove => 1,
+            Handicap::PawnAndTwoMoves => 2,
+            Handicap::KnightOdds | Handicap::RookOdds | Handicap::QueenOdds => 0,
+        }
+    }
+}
+
+impl Display for Handicap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", match self {
+            Handicap::PawnAndMove => "Pawn and move",
+            Handicap::PawnAndTwoMoves => "Pawn and two moves",
+            Handicap::KnightOdds => "Knight odds",
+            Handicap::RookOdds => "Rook odds",
+            Handicap::QueenOdds => "Queen odds",
+        })
+    }
+}
+
+/// A builder for [ChessGame] objects starting from a modified position, such as a game with a
+/// [Handicap].
+///
+/// # Example
+///
+/// ```rust
+/// use leben_chess::board::piece::PlayerColor;
+/// use leben_chess::chess::handicap::{ChessGameBuilder, Handicap};
+///
+/// let game = ChessGameBuilder::new()
+///     .with_handicap(Handicap::KnightOdds, PlayerColor::White)
+///     .build();
+/// assert_eq!(game.board().to_fen_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChessGameBuilder {
+    board: Board,
+    variant: Variant,
+    handicap: Option<(Handicap, PlayerColor)>,
+    first_moves: (PlayerColor, u32),
+}
+
+impl Default for ChessGameBuilder {
+    fn default() -> Self {
+        ChessGameBuilder::new()
+    }
+}
+
+impl ChessGameBuilder {
+    /// returns: A builder for a standard game from the
+    ///          [default starting board](Board::default_board).
+    pub fn new() -> ChessGameBuilder {
+        ChessGameBuilder {
+            board: Board::default_board(),
+            variant: Variant::Standard,
+            handicap: None,
+            first_moves: (PlayerColor::White, 1),
+        }
+    }
+
+    /// Sets the starting board, which any handicap is applied to.
+    pub fn board(mut self, board: Board) -> ChessGameBuilder {
+        self.board = board;
+        self
+    }
+
+    /// Sets the [Variant] of the rules the game is played with, see [ChessGame::with_variant].
+    pub fn variant(mut self, variant: Variant) -> ChessGameBuilder {
+        self.variant = variant;
+        self
+    }
+
+    /// Sets up the game with a [Handicap], removing the piece the giving player plays without
+    /// from the starting board, and letting the other player make the
+    /// [first moves](Handicap::first_moves) included in the handicap. The handicap is recorded in
+    /// the game, see [ChessGame::handicap].
+    ///
+    /// # Arguments
+    ///
+    /// * `handicap`: The handicap to give.
+    /// * `giver`: The stronger player, who gives the handicap.
+    pub fn with_handicap(mut self, handicap: Handicap, giver: PlayerColor) -> ChessGameBuilder {
+        self.handicap = Some((handicap, giver));
+        if handicap.first_moves() > 0 {
+            self.first_moves = (giver.other_player(), handicap.first_moves());
+        }
+        self
+    }
+
+    /// Lets a player make the first moves of the game in a row, before the other player moves for
+    /// the first time. While the player has moves left in a row, moves which would give check
+    /// are not legal, and a promotion is not legal if promoting to a queen would give check.
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player moving first.
+    /// * `count`: The number of moves the player makes in a row. Counts below 1 are treated as 1.
+    pub fn first_moves(mut self, player: PlayerColor, count: u32) -> ChessGameBuilder {
+        self.first_moves = (player, count.max(1));
+        self
+    }
+
+    /// returns: The game set up by the builder. Castling rights are removed for every king or
+    ///          rook which is not on its starting square on the starting board.
+    pub fn build(self) -> ChessGame {
+        let mut board = self.board;
+        if let Some((handicap, giver)) = self.handicap {
+            board.set_piece(handicap.removed_square(giver), None);
+        }
+        // castling rights are only given for kings and rooks on their starting squares
+        let mut game = ChessGame::with_variant(board, self.variant);
+        let (player, count) = self.first_moves;
+        game.active_player = player;
+        game.extra_moves = count - 1;
+        game.handicap = self.handicap;
+        game.starting_position.active_player = player;
+        game.starting_position.castling_rights = game.castling_rights;
+        game.starting_position.extra_moves = game.extra_moves;
+        game.recalculate_available_moves();
+        game.update_game_status();
+        game
+    }
+}
+
+impl ChessGame {
+    /// returns: The [Handicap] the game was set up with using
+    ///          [with_handicap](ChessGameBuilder::with_handicap) and the player giving it, or
+    ///          `None` if the game was set up without a handicap.
+    pub fn handicap(&self) -> Option<(Handicap, PlayerColor)> {
+        self.handicap
+    }
+
+    /// returns: The number of moves the active player still makes in a row after the current
+    ///          move, see [ChessGameBuilder::first_moves].
+    pub fn extra_moves(&self) -> u32 {
+        self.extra_moves
+    }
+
+    /// Removes the moves which would give check from the cache of available moves while the
+    /// active player has [extra moves](ChessGame::extra_moves) left.
+    pub(super) fn remove_checks_during_extra_moves(&mut self) {
+        if self.extra_moves == 0 {
+            return;
+        }
+        let opponent = self.active_player.other_player();
+        for from in BoardPosition::all() {
+            let targets = self.available_moves(from);
+            let promotion = self.expects_promotion_move(from).then_some(PromotionType::Queen);
+            for to in BoardPosition::all().filter(|&to| targets.get(to)) {
+                let chess_move = ChessMove { promotion, ..ChessMove::new(from, to) };
+                let mut board = self.board.clone();
+                let gives_check = moves::do_move(&mut board, self.active_player, chess_move,
+                                                 self.move_context()).is_ok()
+                    && moves::is_in_check(&board, opponent, self.variant);
+                if gives_check {
+                    self.available_moves[from.file.get() as usize][from.rank.get() as usize]
+                        .set(to, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::CastlingRights;
+    use crate::pgn;
+
+    #[test]
+    fn knight_odds_board() {
+        let game = ChessGameBuilder::new()
+            .with_handicap(Handicap::KnightOdds, PlayerColor::White)
+            .build();
+        assert_eq!(game.board().to_fen_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR");
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::default());
+        assert_eq!(game.handicap(), Some((Handicap::KnightOdds, PlayerColor::White)));
+
+        let game = ChessGameBuilder::new()
+            .with_handicap(Handicap::QueenOdds, PlayerColor::Black)
+            .build();
+        assert_eq!(game.board().to_fen_string(), "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+    }
+
+    #[test]
+    fn rook_odds_castling_rights() {
+        let game = ChessGameBuilder::new()
+            .with_handicap(Handicap::RookOdds, PlayerColor::White)
+            .build();
+        assert_eq!(game.board().to_fen_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR");
+        assert_eq!(game.castling_rights(PlayerColor::White),
+                   CastlingRights { queenside: false, kingside: true });
+        assert_eq!(game.castling_rights(PlayerColor::Black), CastlingRights::default());
+        assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1");
+    }
+
+    #[test]
+    fn pawn_and_two_moves() {
+        let mut game = ChessGameBuilder::new()
+            .with_handicap(Handicap::PawnAndTwoMoves, PlayerColor::White)
+            .build();
+        assert_eq!(game.board().to_fen_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPP1PP/RNBQKBNR");
+        assert_eq!(game.active_player(), PlayerColor::Black);
+        assert_eq!(game.extra_moves(), 1);
+        // the first move may not give check
+        assert!(game.do_move(ChessMove::from_coords("e7", "e6").unwrap()).is_ok());
+        assert_eq!(game.active_player(), PlayerColor::Black);
+        assert_eq!(game.extra_moves(), 0);
+        assert!(game.do_move(ChessMove::from_coords("d8", "h4").unwrap()).is_ok());
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert!(game.is_check());
+
+        let mut game = ChessGameBuilder::new()
+            .with_handicap(Handicap::PawnAndTwoMoves, PlayerColor::White)
+            .build();
+        game.do_move(ChessMove::from_coords("e7", "e6").unwrap()).unwrap();
+        game.do_move(ChessMove::from_coords("d7", "d5").unwrap()).unwrap();
+        game.do_move(ChessMove::from_coords("g1", "f3").unwrap()).unwrap();
+        assert_eq!(game.active_player(), PlayerColor::Black);
+
+        let pgn = pgn::write_game(&game, &[]);
+        assert!(pgn.starts_with("[Handicap \"Pawn and two moves\"]\n[SetUp \"1\"]\n"));
+        assert!(pgn.ends_with("\n1... e6 2... d5 3. Nf3 *\n"));
+    }
+
+    #[test]
+    fn extra_moves_exclude_checks() {
+        let mut game = ChessGameBuilder::new()
+            .with_handicap(Handicap::PawnAndMove, PlayerColor::White)
+            .first_moves(PlayerColor::Black, 3)
+            .build();
+        game.do_move(ChessMove::from_coords("e7", "e6").unwrap()).unwrap();
+        assert!(!game.available_moves("d8".parse().unwrap()).get("h4".parse().unwrap()));
+        let check = ChessMove::from_coords("d8", "h4").unwrap();
+        assert_eq!(game.do_move(check), Err(crate::chess::ChessError::IllegalMove(check)));
+        game.do_move(ChessMove::from_coords("d8", "g5").unwrap()).unwrap();
+        assert_eq!(game.starting_position().extra_moves(), 2);
+    }
+}
@@ -0,0 +1,160 @@
+use thiserror::Error;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::board::PositionError;
+use crate::chess::{check_castling_rights, check_en_passant_target, ChessGame, DrawReason,
+                   GameStatus, WinReason, FIFTY_MOVE_PLIES};
+
+/// An inconsistency between the cached state of a [ChessGame] and its board, found by
+/// [ChessGame::verify_integrity]. Each variant names the inconsistent component, together with
+/// the square or player it was found for.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IntegrityError {
+    /// The cached [available moves](ChessGame::available_moves) of the piece on the square differ
+    /// from the moves generated from the board.
+    #[error("cached available moves from {0} do not match the board")]
+    AvailableMoves(BoardPosition),
+    /// The player has castling rights, but the king or the rook is not on its starting square.
+    #[error("{0} has castling rights without the king and rook on their starting squares")]
+    CastlingRights(PlayerColor),
+    /// The en passant target square does not lie behind a pawn which could have just made a
+    /// double move.
+    #[error("implausible en passant target {0}")]
+    EnPassantTarget(BoardPosition),
+    /// The game status is not the status the position leads to, with the cached status.
+    #[error("game status `{0}` does not match the position")]
+    GameStatus(GameStatus),
+}
+
+impl ChessGame {
+    /// Recomputes the state cached by the game from its board and compares it with the cache,
+    /// for finding bugs where the two get out of sync. The following components are checked, in
+    /// order:
+    /// - The [available moves](ChessGame::available_moves) of every square, in square index
+    ///   order.
+    /// - The castling rights of white and black, against the placement of the kings and rooks.
+    /// - The [en passant target](ChessGame::en_passant_target), which has to lie behind a pawn of
+    ///   the player who is not to move.
+    /// - The [game status](ChessGame::game_status), which has to be the status the position
+    ///   leads to, unless the game ended by resignation, agreement, adjudication, a forfeit or
+    ///   a timeout. A game which has not started has no moves in its history.
+    ///
+    /// This is called after every move in debug builds, and may be called by fuzzers and other
+    /// tests after any operation on a game.
+    ///
+    /// returns: `Ok(())` if the game is consistent, otherwise the [IntegrityError] of the first
+    ///          inconsistent component.
+    pub fn verify_integrity(&self) -> Result<(), IntegrityError> {
+        let mut expected = self.clone();
+        expected.recalculate_available_moves();
+        if let Some(pos) = BoardPosition::all()
+            .find(|&pos| self.available_moves(pos) != expected.available_moves(pos))
+        {
+            return Err(IntegrityError::AvailableMoves(pos));
+        }
+        if let Err(PositionError::InvalidCastlingRights(player))
+            = check_castling_rights(&self.board, self.castling_rights)
+        {
+            return Err(IntegrityError::CastlingRights(player));
+        }
+        if let Some(target) = self.en_passant_target
+            && check_en_passant_target(&self.board, self.active_player, target, self.variant)
+                .is_err()
+        {
+            return Err(IntegrityError::EnPassantTarget(target));
+        }
+
+        expected.game_status = GameStatus::Normal;
+        expected.update_game_status();
+        let consistent = match self.game_status {
+            GameStatus::NotYetStarted => {
+                self.history.is_empty() && expected.game_status == GameStatus::Normal
+            }
+            GameStatus::Win(_, WinReason::Resignation | WinReason::Adjudication
+                               | WinReason::Forfeit | WinReason::IllegalMoveForfeit
+                               | WinReason::Timeout)
+            | GameStatus::Draw(DrawReason::DrawByAgreement | DrawReason::Adjudication
+                               | DrawReason::TimeoutVsInsufficientMaterial) => true,
+            GameStatus::Draw(DrawReason::FiftyMoveRule) => {
+                self.halfmove_clock() >= FIFTY_MOVE_PLIES
+            }
+            status => expected.game_status == status,
+        };
+        if !consistent {
+            return Err(IntegrityError::GameStatus(self.game_status));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::piece::{Piece, PieceType};
+    use crate::moves::CastlingRights;
+    use super::*;
+
+    fn pos(pos: &str) -> BoardPosition {
+        BoardPosition::try_from(pos).unwrap()
+    }
+
+    #[test]
+    fn consistent_games() {
+        let mut game = ChessGame::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(game.verify_integrity(), Ok(()));
+        for san in ["a2a4", "b4a3", "e1g1", "a3b2"] {
+            game.try_move_str(san).unwrap();
+            assert_eq!(game.verify_integrity(), Ok(()), "{}", san);
+        }
+        let mut mated = ChessGame::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        mated.try_move_str("h1h8").unwrap();
+        assert!(mated.game_status().is_win());
+        assert_eq!(mated.verify_integrity(), Ok(()));
+        mated.resign().unwrap_err();
+        let mut resigned = ChessGame::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        resigned.try_move_str("h1h2").unwrap();
+        resigned.resign().unwrap();
+        assert_eq!(resigned.verify_integrity(), Ok(()));
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let game = ChessGame::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+
+        let mut corrupted = game.clone();
+        corrupted.board.set_piece(pos("c3"), Some(Piece::new(PieceType::Knight,
+                                                                PlayerColor::White)));
+        assert_eq!(corrupted.verify_integrity(), Err(IntegrityError::AvailableMoves(pos("c3"))));
+
+        let mut corrupted = game.clone();
+        corrupted.available_moves[0][0].set(pos("a8"), false);
+        assert_eq!(corrupted.verify_integrity(), Err(IntegrityError::AvailableMoves(pos("a1"))));
+
+        let mut corrupted = game.clone();
+        corrupted.board.set_piece(pos("h8"), None);
+        corrupted.board.set_piece(pos("h7"), Some(Piece::new(PieceType::Rook,
+                                                                PlayerColor::Black)));
+        corrupted.recalculate_available_moves();
+        assert_eq!(corrupted.verify_integrity(),
+                   Err(IntegrityError::CastlingRights(PlayerColor::Black)));
+
+        let mut corrupted = game.clone();
+        corrupted.en_passant_target = Some(pos("c6"));
+        corrupted.recalculate_available_moves();
+        assert_eq!(corrupted.verify_integrity(), Err(IntegrityError::EnPassantTarget(pos("c6"))));
+
+        let mut corrupted = game.clone();
+        corrupted.castling_rights.0 = CastlingRights::NONE;
+        assert_eq!(corrupted.verify_integrity(), Err(IntegrityError::AvailableMoves(pos("e1"))));
+
+        let mut corrupted = game;
+        corrupted.game_status = GameStatus::Draw(DrawReason::Stalemate);
+        assert_eq!(corrupted.verify_integrity(),
+                   Err(IntegrityError::GameStatus(GameStatus::Draw(DrawReason::Stalemate))));
+        corrupted.game_status = GameStatus::NotYetStarted;
+        corrupted.try_move_str("e1g1").unwrap();
+        corrupted.game_status = GameStatus::NotYetStarted;
+        assert_eq!(corrupted.verify_integrity(),
+                   Err(IntegrityError::GameStatus(GameStatus::NotYetStarted)));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::chess::{ChessError, ChessGame, GameStatus, MoveDetails};
+use crate::chess::crazyhouse::DropMove;
+use crate::chess::takeback::UndoState;
+use crate::moves;
+use crate::moves::ChessMove;
+use crate::moves::util::BoardBitmap;
+
+/// A move made with [make_move](ChessGame::make_move), together with the state of the game
+/// before it which is needed to [unmake](ChessGame::unmake_move) it.
+#[derive(Clone, Debug)]
+pub(crate) struct MadeMove {
+    details: MoveDetails,
+    state: UndoState,
+    available_moves: [[BoardBitmap; 8]; 8],
+}
+
+impl ChessGame {
+    /// Performs a legal move or drop like [do_move](ChessGame::do_move), changing only the
+    /// position and the game status decided by it, for searching the game tree in one game
+    /// instead of copying the game for each position. The move is not added to the history or to the positions for
+    /// detecting repetitions, the observer is not notified, and premoves, illegal move counts and
+    /// the time source are not used. The game is not checked with
+    /// [verify_integrity](ChessGame::verify_integrity) either.
+    ///
+    /// returns: `Ok(MadeMove)` for unmaking the move with [unmake_move](ChessGame::unmake_move),
+    ///          and `Err(ChessError)` if the move is not legal, like for
+    ///          [do_move](ChessGame::do_move).
+    pub(crate) fn make_move(&mut self, chess_move: ChessMove) -> Result<MadeMove, ChessError> {
+        let state = self.undo_state();
+        let available_moves = self.available_moves;
+        let details = match DropMove::from_move(chess_move) {
+            Some(drop) => {
+                self.check_drop(drop)?;
+                self.place_drop(drop)
+            }
+            None => {
+                let piece = self.check_move(chess_move)?;
+                let move_context = self.move_context();
+                let move_result = moves::do_move(&mut self.board, self.active_player, chess_move,
+                                                 move_context)?;
+                let details = MoveDetails::of_move(chess_move, piece, &move_result);
+                self.track_crazyhouse_move(&details);
+                self.after_move(move_result);
+                details
+            }
+        };
+        self.game_status = self.position_status().unwrap_or(GameStatus::Normal);
+        Ok(MadeMove { details, state, available_moves })
+    }
+
+    /// Takes back a move made with [make_move](ChessGame::make_move), which has to be the last
+    /// move made in the game. The available moves before the move are restored instead of being
+    /// recalculated.
+    pub(crate) fn unmake_move(&mut self, made_move: MadeMove) {
+        self.unplay(&made_move.details, &made_move.state);
+        self.available_moves = made_move.available_moves;
+        self.game_status = made_move.state.game_status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::board::piece::PlayerColor;
+    use crate::chess::handicap::ChessGameBuilder;
+    use crate::chess::variant::Variant;
+    use super::*;
+
+    /// Checks that making each legal move gives the same position as performing it, and that
+    /// unmaking it restores the position, down to the given depth.
+    fn check_make_unmake(game: &mut ChessGame, depth: u32) {
+        let key = game.polyglot_key();
+        let legal_moves = game.legal_moves();
+        let status = *game.game_status();
+        for &chess_move in &legal_moves {
+            let mut expected = game.analysis_copy();
+            expected.do_move(chess_move).unwrap();
+            let made_move = game.make_move(chess_move).unwrap();
+            assert_eq!(game.board(), expected.board(), "{}", chess_move);
+            assert_eq!(game.polyglot_key(), expected.polyglot_key(), "{}", chess_move);
+            assert_eq!(game.game_status(), expected.game_status(), "{}", chess_move);
+            assert_eq!(game.legal_moves(), expected.legal_moves(), "{}", chess_move);
+            assert_eq!(game.extra_moves(), expected.extra_moves(), "{}", chess_move);
+            for player in [PlayerColor::White, PlayerColor::Black] {
+                assert_eq!(game.hand(player), expected.hand(player), "{}", chess_move);
+            }
+            if depth > 1 {
+                check_make_unmake(game, depth - 1);
+            }
+            game.unmake_move(made_move);
+            assert_eq!(game.polyglot_key(), key, "{}", chess_move);
+            assert_eq!(game.legal_moves(), legal_moves, "{}", chess_move);
+            assert_eq!(*game.game_status(), status, "{}", chess_move);
+        }
+    }
+
+    #[test]
+    fn make_unmake_matches_do_move() {
+        let mut kiwipete = ChessGame::new(Board::KIWIPETE);
+        check_make_unmake(&mut kiwipete, 1);
+        assert!(kiwipete.history().is_empty());
+
+        let mut mate_in_one = ChessGame::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        check_make_unmake(&mut mate_in_one, 2);
+
+        let mut crazyhouse = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        for (from, to) in [("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("d8", "d5")] {
+            crazyhouse.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        check_make_unmake(&mut crazyhouse, 1);
+
+        let mut first_moves = ChessGameBuilder::new().first_moves(PlayerColor::White, 2).build();
+        check_make_unmake(&mut first_moves, 2);
+
+        let mut shatranj = ChessGame::with_variant(Board::default_board(), Variant::Shatranj);
+        check_make_unmake(&mut shatranj, 1);
+    }
+}
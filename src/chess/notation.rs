@@ -0,0 +1,428 @@
+//! Standard algebraic notation (SAN) and coordinate notation for the moves of a [ChessGame].
+//!
+//! see: [Algebraic notation (chess) - Wikipedia](https://en.wikipedia.org/wiki/Algebraic_notation_(chess))
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType};
+use crate::chess::{ChessGame, GameStatus, MoveInputError, NotationError, WinReason};
+use crate::chess::crazyhouse::DropMove;
+use crate::moves::{ChessMove, PieceMovement, PromotionType};
+use crate::util::U3;
+
+impl ChessGame {
+    /// Returns the standard algebraic notation (SAN) of a move in the current position, for
+    /// example `"Nf3"`, `"exd5"`, `"O-O"`, `"e8=Q+"` or `"Qxf7#"`. Pieces are disambiguated by
+    /// file, rank, or both, only when another piece of the same type could move to the same
+    /// square. Drops are written like in [drop_to_san](ChessGame::drop_to_san), such as `"N@f3"`.
+    ///
+    /// returns: `Some(String)` if the move is legal, otherwise `None`.
+    pub fn move_to_san(&self, chess_move: ChessMove) -> Option<String> {
+        if let Some(drop) = DropMove::from_move(chess_move) {
+            return self.drop_to_san(drop);
+        }
+        let from = chess_move.piece_movement.from;
+        let to = chess_move.piece_movement.to;
+        if !self.available_moves(from).get(to) {
+            return None;
+        }
+        let piece = self.board().get_piece(from)?;
+
+        let mut san = String::new();
+        if piece.piece_type == PieceType::King && from.file.get().abs_diff(to.file.get()) == 2 {
+            san += if to.file.get() > from.file.get() { "O-O" } else { "O-O-O" };
+        } else {
+            let capture = self.board().get_piece(to).is_some()
+                || piece.piece_type == PieceType::Pawn && from.file != to.file;
+            if piece.piece_type == PieceType::Pawn {
+                if capture {
+                    san.push(from.to_string().remove(0));
+                }
+            } else {
+                san += &piece.piece_type.to_string();
+                san += &self.disambiguation(from, to);
+            }
+            if capture {
+                san.push('x');
+            }
+            san += &to.to_string();
+            if let Some(promotion) = chess_move.promotion {
+                san.push('=');
+                san.push(promotion.to_char());
+            }
+        }
+
+        let game_after_move = self.peek_move(chess_move).ok()?;
+        if *game_after_move.game_status()
+            == GameStatus::Win(self.active_player(), WinReason::Checkmate)
+        {
+            san.push('#');
+        } else if game_after_move.is_check() {
+            san.push('+');
+        }
+        Some(san)
+    }
+
+    /// returns: The standard algebraic notation of each move in the [history](ChessGame::history),
+    ///          in the position it was played from, like [move_to_san](ChessGame::move_to_san).
+    ///          [Drops](crate::chess::crazyhouse::DropMove) are written like `"N@f3"`.
+    pub fn san_history(&self) -> Vec<String> {
+        let mut position = self.starting_position();
+        let mut sans = Vec::with_capacity(self.history().len());
+        for details in self.history() {
+            // the history only contains legal moves, so these always succeed
+            if let Some(san) = position.recorded_move_to_san(details) {
+                sans.push(san);
+            }
+            let _ = position.replay(details);
+        }
+        sans
+    }
+
+    /// Formats the [history](ChessGame::history) as a numbered move list, such as
+    /// `"1. e4 e5 2. Nf3 Nc6"`, followed by the [result token](crate::pgn::result_token) if the
+    /// game is over, like the movetext of [exported PGN](crate::pgn::write_game) without line
+    /// breaks. Move numbers start from 1, and a history starting with a move of black starts with
+    /// `"1..."`.
+    ///
+    /// returns: The move list, which is empty if no moves have been made in an unfinished game.
+    pub fn movetext(&self) -> String {
+        let mut tokens = crate::pgn::movetext_tokens(self, false);
+        if self.game_status().is_over() {
+            tokens.push(crate::pgn::result_token(self.game_status()).to_string());
+        }
+        tokens.join(" ")
+    }
+
+    /// Interprets a move given in standard algebraic notation (SAN) in the current position. The
+    /// notation is read leniently: check and mate markers and annotations (`+`, `#`, `!`, `?`)
+    /// are ignored, castling may be written with zeros (`0-0`), the `=` before a promotion piece
+    /// may be left out, and redundant disambiguation is accepted. A move which could refer to
+    /// several pieces is never guessed, however. In Crazyhouse, drops such as `"N@f3"` are read
+    /// like in [parse_san_drop](ChessGame::parse_san_drop).
+    ///
+    /// returns: `Ok(ChessMove)` of the legal move the notation refers to, otherwise a
+    ///          [NotationError].
+    pub fn parse_san(&self, san: &str) -> Result<ChessMove, NotationError> {
+        if san.contains('@') {
+            return self.parse_san_drop(san).map(ChessMove::from);
+        }
+        let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let castling_file = match san {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(file) = castling_file {
+            let rank = self.active_player().back_rank();
+            let from = BoardPosition { file: U3::from_low_bits(4), rank };
+            let to = BoardPosition { file: U3::from_low_bits(file), rank };
+            let king = Piece::new(PieceType::King, self.active_player());
+            return if self.board().get_piece(from) == Some(king)
+                && self.available_moves(from).get(to)
+            {
+                Ok(ChessMove::new(from, to))
+            } else {
+                Err(NotationError::IllegalMove)
+            };
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+        let piece_type = match chars.first() {
+            Some(&ch) if "NBRQK".contains(ch) => {
+                chars.remove(0);
+                PieceType::try_from(ch).map_err(|_| NotationError::InvalidSyntax)?
+            }
+            _ => PieceType::Pawn,
+        };
+        let mut promotion = None;
+        if let Some(&ch) = chars.last()
+            && let Ok(promotion_type) = PromotionType::try_from(ch)
+        {
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+            promotion = Some(promotion_type);
+        }
+        let [rest @ .., to_file, to_rank] = chars.as_slice() else {
+            return Err(NotationError::InvalidSyntax);
+        };
+        let to = BoardPosition::try_from((*to_file, *to_rank))
+            .map_err(|_| NotationError::InvalidSyntax)?;
+        let (rest, capture) = match rest {
+            [rest @ .., 'x'] => (rest, true),
+            rest => (rest, false),
+        };
+        let (mut from_file, mut from_rank) = (None, None);
+        for &ch in rest {
+            match ch {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(ch as u8 - b'a');
+                }
+                '1'..='8' if from_rank.is_none() => from_rank = Some(ch as u8 - b'1'),
+                _ => return Err(NotationError::InvalidSyntax),
+            }
+        }
+        if piece_type == PieceType::Pawn && (from_rank.is_some() || capture != from_file.is_some())
+        {
+            return Err(NotationError::InvalidSyntax);
+        }
+        if capture && piece_type != PieceType::Pawn && self.board().get_piece(to).is_none() {
+            return Err(NotationError::IllegalMove);
+        }
+
+        let piece = Piece::new(piece_type, self.active_player());
+        let candidates: Vec<ChessMove> = BoardPosition::all()
+            .filter(|&from| self.board().get_piece(from) == Some(piece))
+            .filter(|from| from_file.is_none_or(|file| from.file.get() == file))
+            .filter(|from| from_rank.is_none_or(|rank| from.rank.get() == rank))
+            .filter(|&from| self.available_moves(from).get(to))
+            .filter(|&from| piece_type != PieceType::Pawn || capture == (from.file != to.file))
+            .filter(|&from| self.expects_promotion_move(from) == promotion.is_some())
+            .map(|from| ChessMove { promotion, ..ChessMove::new(from, to) })
+            .collect();
+        match candidates.as_slice() {
+            [] => Err(NotationError::IllegalMove),
+            [chess_move] => Ok(*chess_move),
+            _ => Err(NotationError::AmbiguousMove),
+        }
+    }
+
+    /// Interprets a move given in coordinate notation in the current position: the origin and
+    /// destination squares, optionally separated by `-`, followed by the promotion piece for
+    /// promotion moves, optionally preceded by `=`. For example `"e2e4"`, `"e2-e4"`, `"e7e8q"`
+    /// (as in the Universal Chess Interface) or `"e7e8=Q"`. Drops are written like `"N@f3"`, as
+    /// in the Universal Chess Interface for Crazyhouse.
+    ///
+    /// returns: `Ok(ChessMove)` if the notation refers to a legal move, otherwise a
+    ///          [NotationError].
+    pub fn parse_coordinate_move(&self, string: &str) -> Result<ChessMove, NotationError> {
+        let string = string.trim();
+        if string.contains('@') {
+            let drop: DropMove = string.parse()?;
+            if !self.is_legal_drop(drop) {
+                return Err(NotationError::IllegalMove);
+            }
+            return Ok(drop.into());
+        }
+        let movement_length = if string.chars().nth(2) == Some('-') { 5 } else { 4 };
+        if !string.is_ascii() || string.len() < movement_length {
+            return Err(NotationError::InvalidSyntax);
+        }
+        let (movement, promotion) = string.split_at(movement_length);
+        let piece_movement: PieceMovement = movement.parse()
+            .map_err(|_| NotationError::InvalidSyntax)?;
+        let promotion = promotion.strip_prefix('=').unwrap_or(promotion);
+        let promotion = match promotion.chars().collect::<Vec<char>>().as_slice() {
+            [] => None,
+            [ch] => Some(PromotionType::try_from(*ch).map_err(|_| NotationError::InvalidSyntax)?),
+            _ => return Err(NotationError::InvalidSyntax),
+        };
+        let PieceMovement { from, to } = piece_movement;
+        if !self.available_moves(from).get(to)
+            || self.expects_promotion_move(from) != promotion.is_some()
+        {
+            return Err(NotationError::IllegalMove);
+        }
+        Ok(ChessMove { piece_movement, promotion, drop: None })
+    }
+
+    /// Performs a move given as a string, as typed by a user. The string is first interpreted
+    /// as standard algebraic notation (see [parse_san](ChessGame::parse_san)), and then as
+    /// coordinate notation (see [parse_coordinate_move](ChessGame::parse_coordinate_move)); the
+    /// first interpretation which is a legal move is performed. In crazyhouse, both notations
+    /// write drops like `"N@f3"`.
+    ///
+    /// returns: `Ok(ChessMove)` of the performed move, which is a
+    ///          [drop move](ChessMove::dropping) for a drop.
+    ///          [Uninterpretable](MoveInputError::Uninterpretable) with the reason each
+    ///          interpretation failed, if neither is a legal move.
+    ///          [Game](MoveInputError::Game) if the move could not be performed, for example
+    ///          because the game has already ended.
+    pub fn try_move_str(&mut self, string: &str) -> Result<ChessMove, MoveInputError> {
+        let chess_move = match self.parse_san(string) {
+            Ok(chess_move) => chess_move,
+            Err(san) => match self.parse_coordinate_move(string) {
+                Ok(chess_move) => chess_move,
+                Err(coordinates) => {
+                    return Err(MoveInputError::Uninterpretable { san, coordinates });
+                }
+            },
+        };
+        self.do_move(chess_move)?;
+        Ok(chess_move)
+    }
+
+    /// returns: The file, rank, or square of `from` needed to tell the piece on it apart from
+    ///          other pieces of the same type which may also move to `to`.
+    fn disambiguation(&self, from: BoardPosition, to: BoardPosition) -> String {
+        let piece = self.board().get_piece(from);
+        let others: Vec<BoardPosition> = BoardPosition::all()
+            .filter(|&pos| pos != from && self.board().get_piece(pos) == piece)
+            .filter(|&pos| self.available_moves(pos).get(to))
+            .collect();
+        let square = from.to_string();
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|pos| pos.file != from.file) {
+            square[..1].to_string()
+        } else if others.iter().all(|pos| pos.rank != from.rank) {
+            square[1..].to_string()
+        } else {
+            square
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::moves::{CastlingRights, PromotionType};
+    use crate::board::piece::PlayerColor;
+    use super::*;
+
+    fn san(game: &ChessGame, from: &str, to: &str) -> Option<String> {
+        game.move_to_san(ChessMove::from_coords(from, to).unwrap())
+    }
+
+    #[test]
+    fn san_generation() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(san(&game, "e2", "e4").as_deref(), Some("e4"));
+        assert_eq!(san(&game, "g1", "f3").as_deref(), Some("Nf3"));
+        assert_eq!(san(&game, "e2", "e5"), None);
+        for (from, to) in [("e2", "e4"), ("d7", "d5"), ("f1", "b5")] {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        assert_eq!(san(&game, "c7", "c6").as_deref(), Some("c6"));
+        assert_eq!(san(&game, "c8", "d7").as_deref(), Some("Bd7"));
+        game.do_move(ChessMove::from_coords("c7", "c6").unwrap()).unwrap();
+        assert_eq!(san(&game, "e4", "d5").as_deref(), Some("exd5"));
+        assert_eq!(san(&game, "b5", "c6").as_deref(), Some("Bxc6+"));
+    }
+
+    #[test]
+    fn movetext() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.movetext(), "");
+        for input in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"] {
+            game.try_move_str(input).unwrap();
+        }
+        assert_eq!(game.san_history(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(game.movetext(), "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6");
+        game.resign().unwrap();
+        assert_eq!(game.movetext(), "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 0-1");
+
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/4P3/R3K3 b Q - 0 1").unwrap();
+        for input in ["Kd7", "O-O-O+", "Kc6", "e4"] {
+            game.try_move_str(input).unwrap();
+        }
+        assert_eq!(game.san_history(), ["Kd7", "O-O-O+", "Kc6", "e4"]);
+        assert_eq!(game.movetext(), "1... Kd7 2. O-O-O+ Kc6 3. e4");
+    }
+
+    #[test]
+    fn san_round_trip() {
+        let mut game = ChessGame::new(Board::default_board());
+        let moves = [
+            ("e2", "e4"), ("e7", "e5"), ("g1", "f3"), ("b8", "c6"), ("f1", "c4"), ("g8", "f6"),
+            ("e1", "g1"), ("f6", "e4"), ("f1", "e1"), ("d7", "d5"), ("c4", "d5"), ("d8", "d5"),
+            ("b1", "c3"), ("d5", "a5"), ("c3", "e4"),
+        ];
+        for (from, to) in moves {
+            let chess_move = ChessMove::from_coords(from, to).unwrap();
+            let san = game.move_to_san(chess_move).unwrap();
+            assert_eq!(game.parse_san(&san), Ok(chess_move), "{}", san);
+            game.do_move(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn parse_san_errors() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.parse_san("e5"), Err(NotationError::IllegalMove));
+        assert_eq!(game.parse_san("Nxf3"), Err(NotationError::IllegalMove));
+        assert_eq!(game.parse_san("O-O"), Err(NotationError::IllegalMove));
+        assert_eq!(game.parse_san("Zf3"), Err(NotationError::InvalidSyntax));
+        assert_eq!(game.parse_san("e2e4"), Err(NotationError::InvalidSyntax));
+        assert_eq!(game.parse_san(""), Err(NotationError::InvalidSyntax));
+
+        let board = Board::from_fen_string("k7/8/8/8/8/8/8/KN3N2").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        assert_eq!(game.parse_san("Nd2"), Err(NotationError::AmbiguousMove));
+        assert_eq!(game.parse_san("Nbd2"), Ok(ChessMove::from_coords("b1", "d2").unwrap()));
+        assert_eq!(game.parse_san("Nf1d2+"), Ok(ChessMove::from_coords("f1", "d2").unwrap()));
+    }
+
+    #[test]
+    fn try_move_str() {
+        let mut game = ChessGame::new(Board::default_board());
+        for input in ["e4", "e7e5", "Nf3", "b8-c6", "Bc4", "Bc5", "O-O", "g8f6"] {
+            game.try_move_str(input).unwrap();
+        }
+        assert_eq!(game.king_position(PlayerColor::White), Some("g1".parse().unwrap()));
+        assert!(matches!(
+            game.try_move_str("Ke2"),
+            Err(MoveInputError::Uninterpretable {
+                san: NotationError::IllegalMove,
+                coordinates: NotationError::InvalidSyntax,
+            })
+        ));
+        assert!(matches!(
+            game.try_move_str("e2e5"),
+            Err(MoveInputError::Uninterpretable {
+                san: NotationError::InvalidSyntax,
+                coordinates: NotationError::IllegalMove,
+            })
+        ));
+
+        let board = Board::from_fen_string("8/4P3/8/8/8/8/k3K3/2R3R1").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board.clone(), PlayerColor::White, no_rights, None).unwrap();
+        assert!(matches!(
+            game.try_move_str("Re1"),
+            Err(MoveInputError::Uninterpretable { san: NotationError::AmbiguousMove, .. })
+        ));
+        assert_eq!(game.try_move_str("e8=Q").unwrap(),
+                   ChessMove::promoting("e7".parse().unwrap(), "e8".parse().unwrap(),
+                                        PromotionType::Queen));
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        assert_eq!(game.try_move_str("e7e8n").unwrap().promotion, Some(PromotionType::Knight));
+        game.resign().unwrap();
+        assert_eq!(game.try_move_str("Kb3"),
+                   Err(MoveInputError::Game(crate::chess::ChessError::GameAlreadyEnded)));
+    }
+
+    #[test]
+    fn san_special_moves() {
+        let mut game = ChessGame::new(Board::default_board());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        let white_rights = (CastlingRights::default(), CastlingRights::NONE);
+
+        // castling and mate
+        let board = Board::from_fen_string("6k1/5ppp/8/8/8/8/8/R3K2R").unwrap();
+        game.set_position(board, PlayerColor::White, white_rights, None).unwrap();
+        assert_eq!(san(&game, "e1", "g1").as_deref(), Some("O-O"));
+        assert_eq!(san(&game, "e1", "c1").as_deref(), Some("O-O-O"));
+        assert_eq!(san(&game, "a1", "a8").as_deref(), Some("Ra8#"));
+
+        // disambiguation by file, rank and square
+        let board = Board::from_fen_string("k7/8/8/8/1Q1Q4/8/1Q6/K7").unwrap();
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        assert_eq!(san(&game, "d4", "c3").as_deref(), Some("Qdc3"));
+        assert_eq!(san(&game, "b2", "b3").as_deref(), Some("Q2b3"));
+        assert_eq!(san(&game, "b4", "c3").as_deref(), Some("Qb4c3"));
+
+        // en passant and promotion
+        let board = Board::from_fen_string("4k3/6P1/8/3pP3/8/8/8/4K3").unwrap();
+        game.set_position(board, PlayerColor::White, no_rights, Some("d6".parse().unwrap()))
+            .unwrap();
+        assert_eq!(san(&game, "e5", "d6").as_deref(), Some("exd6"));
+        let promotion = ChessMove::promoting("g7".parse().unwrap(), "g8".parse().unwrap(),
+                                            PromotionType::Queen);
+        assert_eq!(game.move_to_san(promotion).as_deref(), Some("g8=Q+"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Types for observing the events of a [ChessGame](super::ChessGame) as they happen, without
+//! having to poll the game state. See [ChessGame::set_observer](super::ChessGame::set_observer).
+//!
+//! # Reentrancy
+//!
+//! Observers are invoked synchronously while the game is being mutated, so they are not given
+//! access to the game and must not attempt to call back into it (for example through a shared
+//! reference-counted handle). Observers which need to act on an event should forward it elsewhere
+//! instead, for example through a channel (see the [GameObserver] implementation for
+//! [Sender<GameEvent>](Sender)).
+
+use core::fmt::{Debug, Formatter};
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::mpsc::Sender;
+use crate::board::piece::PlayerColor;
+use crate::chess::{GameStatus, GameTermination, MoveDetails, TakebackRequest};
+use crate::moves::ChessMove;
+
+/// A receiver of the events of a [ChessGame](super::ChessGame). All methods have empty default
+/// implementations, so implementors only need to override the events they are interested in.
+pub trait GameObserver: Send + Sync {
+    /// Called after a move has been performed.
+    fn on_move(&mut self, _details: &MoveDetails) {}
+
+    /// Called after the status of the game has changed, with the new status.
+    fn on_status_change(&mut self, _status: &GameStatus) {}
+
+    /// Called after the game has ended, following the status change, with the record of how and
+    /// when it ended. See [ChessGame::game_termination](super::ChessGame::game_termination).
+    fn on_termination(&mut self, _termination: &GameTermination) {}
+
+    /// Called after a player has offered a draw.
+    fn on_draw_offer(&mut self, _player: PlayerColor) {}
+
+    /// Called after a player has requested a takeback.
+    fn on_takeback_request(&mut self, _request: &TakebackRequest) {}
+
+    /// Called after a takeback has been accepted, with the number of plies taken back. Any
+    /// resulting change in game status is reported afterward.
+    fn on_takeback(&mut self, _plies: usize) {}
+
+    /// Called after a player's [premove](super::ChessGame::queue_premove) has been discarded
+    /// because it was illegal when it was to be performed.
+    fn on_premove_discarded(&mut self, _player: PlayerColor, _premove: ChessMove) {}
+}
+
+/// An event of a [ChessGame](super::ChessGame), as sent by the [GameObserver] implementation for
+/// [Sender<GameEvent>](Sender).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GameEvent {
+    /// See [GameObserver::on_move].
+    Move(MoveDetails),
+    /// See [GameObserver::on_status_change].
+    StatusChange(GameStatus),
+    /// See [GameObserver::on_termination].
+    Termination(GameTermination),
+    /// See [GameObserver::on_draw_offer].
+    DrawOffer(PlayerColor),
+    /// See [GameObserver::on_takeback_request].
+    TakebackRequest(TakebackRequest),
+    /// See [GameObserver::on_takeback].
+    Takeback(usize),
+    /// See [GameObserver::on_premove_discarded].
+    PremoveDiscarded(PlayerColor, ChessMove),
+}
+
+#[cfg(feature = "std")]
+/// Forwards each event to the channel. Events are silently dropped if the receiver has been
+/// disconnected.
+impl GameObserver for Sender<GameEvent> {
+    fn on_move(&mut self, details: &MoveDetails) {
+        let _ = self.send(GameEvent::Move(*details));
+    }
+
+    fn on_status_change(&mut self, status: &GameStatus) {
+        let _ = self.send(GameEvent::StatusChange(*status));
+    }
+
+    fn on_termination(&mut self, termination: &GameTermination) {
+        let _ = self.send(GameEvent::Termination(*termination));
+    }
+
+    fn on_draw_offer(&mut self, player: PlayerColor) {
+        let _ = self.send(GameEvent::DrawOffer(player));
+    }
+
+    fn on_takeback_request(&mut self, request: &TakebackRequest) {
+        let _ = self.send(GameEvent::TakebackRequest(*request));
+    }
+
+    fn on_takeback(&mut self, plies: usize) {
+        let _ = self.send(GameEvent::Takeback(plies));
+    }
+
+    fn on_premove_discarded(&mut self, player: PlayerColor, premove: ChessMove) {
+        let _ = self.send(GameEvent::PremoveDiscarded(player, premove));
+    }
+}
+
+/// Holds the optional observer of a game. Cloning a game does not clone its observer, since the
+/// clone is a separate game whose events the observer has not subscribed to.
+#[derive(Default)]
+pub(crate) struct ObserverSlot(Option<Box<dyn GameObserver>>);
+
+impl ObserverSlot {
+    pub fn set(&mut self, observer: Option<Box<dyn GameObserver>>)
+               -> Option<Box<dyn GameObserver>>
+    {
+        core::mem::replace(&mut self.0, observer)
+    }
+
+    pub fn notify(&mut self, f: impl FnOnce(&mut dyn GameObserver)) {
+        if let Some(observer) = &mut self.0 {
+            f(observer.as_mut());
+        }
+    }
+}
+
+impl Clone for ObserverSlot {
+    fn clone(&self) -> Self {
+        ObserverSlot(None)
+    }
+}
+
+impl Debug for ObserverSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Some(<observer>)"),
+            None => write!(f, "None"),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use thiserror::Error;
+use crate::chess::{ChessError, ChessGame, GameStatus};
+use crate::moves::ChessMove;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "std")]
+use crate::clock::ChessClock;
+
+/// A move of the policy of a playout which could not be performed. See [ChessGame::play_out].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+#[error("the move at ply {ply} of the playout failed: {error}")]
+pub struct PlayOutError {
+    /// The ply of the playout at which the move was returned, counted from 0 for the first move
+    /// of the playout.
+    pub ply: usize,
+    /// The error returned by [do_move](ChessGame::do_move).
+    pub error: ChessError,
+}
+
+impl ChessGame {
+    /// Plays the game out by repeatedly asking a policy for the move of the player to move, for
+    /// example a search for engine matches, a random move picker, or a fixed list of moves for a
+    /// scripted game. The playout stops when the game is over, when `max_plies` moves have been
+    /// played, or when the policy returns `None`. Since the game is not drawn automatically by
+    /// repetition or by the fifty-move rule, the ply limit is what guarantees that the playout
+    /// terminates with a policy that never gives up.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy`: Returns the move to play in the current position, or `None` to stop.
+    /// * `max_plies`: The highest number of moves to play.
+    ///
+    /// returns: `Ok(GameStatus)` of the game after the playout.
+    ///          [PlayOutError] if a move of the policy could not be performed, with the ply of the
+    ///          move. The moves before it remain played.
+    pub fn play_out(&mut self, mut policy: impl FnMut(&ChessGame) -> Option<ChessMove>,
+                    max_plies: usize) -> Result<GameStatus, PlayOutError>
+    {
+        for ply in 0..max_plies {
+            if self.game_status.is_over() || !self.play_out_ply(ply, &mut policy)? {
+                break;
+            }
+        }
+        Ok(self.game_status)
+    }
+
+    /// Plays the game out like [play_out](ChessGame::play_out), charging each move's thinking
+    /// time to the player on a [ChessClock]. The clock is driven with simulated time, starting
+    /// now: each move takes `think_time`, after which the clock is
+    /// [switched](ChessClock::on_move) to the other player. If a player runs out of time before
+    /// completing a move, the clock stops and the game ends with [flag](ChessGame::flag). Only
+    /// available with the `std` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock`: The clock of the game, which is started for the player to move if it is not
+    ///   already running for them.
+    /// * `think_time`: The time each move takes.
+    /// * `policy`: Returns the move to play in the current position, or `None` to stop.
+    /// * `max_plies`: The highest number of moves to play.
+    ///
+    /// returns: `Ok(GameStatus)` of the game after the playout.
+    ///          [PlayOutError] if a move of the policy could not be performed, with the ply of the
+    ///          move.
+    #[cfg(feature = "std")]
+    pub fn play_out_timed(&mut self, clock: &mut ChessClock, think_time: Duration,
+                          mut policy: impl FnMut(&ChessGame) -> Option<ChessMove>,
+                          max_plies: usize) -> Result<GameStatus, PlayOutError>
+    {
+        let mut now = Instant::now();
+        if clock.running_player() != Some(self.active_player) {
+            clock.start(self.active_player, now);
+        }
+        for ply in 0..max_plies {
+            if self.game_status.is_over() {
+                break;
+            }
+            now += think_time;
+            if let Some(player) = clock.flagged_player(now) {
+                clock.stop(now);
+                // the game is not over, so this always succeeds
+                let _ = self.flag(player);
+                break;
+            }
+            if !self.play_out_ply(ply, &mut policy)? {
+                break;
+            }
+            // the running player has time left, so this always succeeds
+            let _ = clock.on_move(now);
+        }
+        if self.game_status.is_over() {
+            clock.stop(now);
+        }
+        Ok(self.game_status)
+    }
+
+    /// Plays the move of the policy at a ply of a playout.
+    ///
+    /// returns: `Ok(false)` if the policy returned `None`.
+    fn play_out_ply(&mut self, ply: usize,
+                    policy: &mut impl FnMut(&ChessGame) -> Option<ChessMove>)
+                    -> Result<bool, PlayOutError>
+    {
+        let Some(chess_move) = policy(self) else {
+            return Ok(false);
+        };
+        self.do_move(chess_move).map_err(|error| PlayOutError { ply, error })?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use crate::board::Board;
+    use crate::board::piece::PlayerColor;
+    use crate::chess::WinReason;
+    use super::*;
+
+    fn coords(chess_move: &str) -> ChessMove {
+        ChessMove::from_coords(&chess_move[..2], &chess_move[2..]).unwrap()
+    }
+
+    /// returns: A policy playing the given moves in order, and then `None`.
+    fn scripted(moves: &[&str]) -> impl FnMut(&ChessGame) -> Option<ChessMove> {
+        let mut moves: Vec<ChessMove> = moves.iter().map(|chess_move| coords(chess_move)).collect();
+        moves.reverse();
+        move |_| moves.pop()
+    }
+
+    #[test]
+    fn scripted_mate() {
+        let mut game = ChessGame::new(Board::default_board());
+        let policy = scripted(&["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7", "a7a6"]);
+        assert_eq!(game.play_out(policy, 100),
+                   Ok(GameStatus::Win(PlayerColor::White, WinReason::Checkmate)));
+        assert_eq!(game.history().len(), 7);
+
+        // the playout stops at the ply limit, and when the policy gives up
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.play_out(scripted(&["e2e4", "e7e5", "g1f3"]), 2),
+                   Ok(GameStatus::Normal));
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.play_out(scripted(&["g1f3"]), 10), Ok(GameStatus::Normal));
+        assert_eq!(game.history().len(), 3);
+    }
+
+    #[test]
+    fn illegal_policy_move() {
+        let mut game = ChessGame::new(Board::default_board());
+        let illegal = coords("e5e3");
+        assert_eq!(game.play_out(scripted(&["e2e4", "e7e5", "g1f3", "e5e3"]), 10),
+                   Err(PlayOutError { ply: 3, error: ChessError::IllegalMove(illegal) }));
+        assert_eq!(game.history().len(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timed_playout() {
+        let mut game = ChessGame::new(Board::default_board());
+        let mut clock = ChessClock::new("60+0".parse().unwrap()).unwrap();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut ply = 0;
+        let policy = |_: &ChessGame| {
+            ply += 1;
+            Some(coords(shuffle[(ply - 1) % shuffle.len()]))
+        };
+        // each player has time for five moves of 11 seconds
+        let status = game.play_out_timed(&mut clock, Duration::from_secs(11), policy, 100);
+        assert_eq!(status, Ok(GameStatus::Win(PlayerColor::Black, WinReason::Timeout)));
+        assert_eq!(game.history().len(), 10);
+        assert_eq!(clock.running_player(), None);
+        assert_eq!(clock.moves_made(PlayerColor::White), 5);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessError, ChessGame};
+use crate::moves;
+use crate::moves::{ChessMove, MoveContext};
+
+impl ChessGame {
+    /// Queues a move for the player who is not to move, to be performed as soon as the other
+    /// player has moved, like premoves in bullet chess. At most one premove is kept per player,
+    /// so a new premove replaces the queued one.
+    ///
+    /// The premove is only checked to be pseudo-legal when it is queued: the piece has to belong
+    /// to the player and be able to move to the destination square in the current position, not
+    /// considering whether the move would leave the player in check. Right after the other player
+    /// makes a move with [do_move](ChessGame::do_move) or [do_drop](ChessGame::do_drop), the
+    /// premove is performed like a move passed to [do_move](ChessGame::do_move). If it is illegal
+    /// by then, it is discarded, and the observer is notified with
+    /// [on_premove_discarded](crate::chess::observer::GameObserver::on_premove_discarded)
+    /// instead. A discarded premove does not count towards the
+    /// [illegal move limit](ChessGame::set_illegal_move_limit).
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player queueing the premove.
+    /// * `chess_move`: The move to perform on the player's next turn.
+    ///
+    /// returns: `Ok(())` if the premove was queued.
+    ///          [InvalidPremove](ChessError::InvalidPremove) if it is the player's turn, or if the
+    ///          move is not pseudo-legal.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn queue_premove(&mut self, player: PlayerColor, chess_move: ChessMove)
+                         -> Result<(), ChessError>
+    {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        let move_context = MoveContext {
+            castling_rights: self.castling_rights(player),
+            en_passant_target: None,
+            variant: self.variant,
+        };
+        let movement = chess_move.piece_movement;
+        let mut board = self.board.clone();
+        let is_pseudo_legal = moves::get_pseudo_legal_moves(&mut board, player, movement.from,
+                                                            move_context)
+            .get(movement.to);
+        if player == self.active_player || !is_pseudo_legal {
+            return Err(ChessError::InvalidPremove(chess_move));
+        }
+        *self.premove_mut(player) = Some(chess_move);
+        Ok(())
+    }
+
+    /// Removes the player's queued premove, see [queue_premove](ChessGame::queue_premove).
+    ///
+    /// returns: The removed premove, or `None` if the player had no premove queued.
+    pub fn cancel_premove(&mut self, player: PlayerColor) -> Option<ChessMove> {
+        self.premove_mut(player).take()
+    }
+
+    /// returns: The player's queued premove, see [queue_premove](ChessGame::queue_premove), or
+    ///          `None` if the player has no premove queued.
+    pub fn pending_premove(&self, player: PlayerColor) -> Option<ChessMove> {
+        match player {
+            PlayerColor::White => self.premoves.0,
+            PlayerColor::Black => self.premoves.1,
+        }
+    }
+
+    fn premove_mut(&mut self, player: PlayerColor) -> &mut Option<ChessMove> {
+        match player {
+            PlayerColor::White => &mut self.premoves.0,
+            PlayerColor::Black => &mut self.premoves.1,
+        }
+    }
+
+    /// Performs the premove of the active player, if any. The other player cannot have a premove
+    /// queued, since it was their turn until the last move.
+    pub(super) fn play_premove(&mut self) {
+        let player = self.active_player;
+        if let Some(premove) = self.premove_mut(player).take() {
+            let result = self.perform_move(premove);
+            match result {
+                Ok(_) => self.after_move_attempt(player, &result),
+                Err(_) => {
+                    self.observer.notify(|observer| observer.on_premove_discarded(player, premove))
+                }
+            }
+        }
+    }
+
+    /// returns: A copy of the game without any queued premoves, for looking ahead from the
+    ///          current position, for example to find the notation of a move or to search for the
+    ///          best move, so that the moves made on the copy do not trigger the premoves.
+    pub(crate) fn analysis_copy(&self) -> ChessGame {
+        let mut copy = self.clone();
+        copy.premoves = (None, None);
+        copy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::board::piece::PieceType;
+    use crate::chess::GameStatus;
+    use super::*;
+
+    fn coords(from: &str, to: &str) -> ChessMove {
+        ChessMove::from_coords(from, to).unwrap()
+    }
+
+    #[test]
+    fn premove_executes() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.queue_premove(PlayerColor::White, coords("e2", "e4")),
+                   Err(ChessError::InvalidPremove(coords("e2", "e4"))));
+        game.queue_premove(PlayerColor::Black, coords("e7", "e5")).unwrap();
+        assert_eq!(game.pending_premove(PlayerColor::Black), Some(coords("e7", "e5")));
+        game.do_move(coords("g1", "f3")).unwrap();
+        assert_eq!(game.pending_premove(PlayerColor::Black), None);
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.history()[1].chess_move, coords("e7", "e5"));
+        assert_eq!(game.verify_integrity(), Ok(()));
+
+        // a premove replaces the queued one
+        game.do_move(coords("d2", "d3")).unwrap();
+        game.queue_premove(PlayerColor::White, coords("f3", "g5")).unwrap();
+        game.queue_premove(PlayerColor::White, coords("f3", "e5")).unwrap();
+        game.do_move(coords("b8", "c6")).unwrap();
+        assert_eq!(game.history().len(), 5);
+        assert_eq!(game.history()[4].chess_move, coords("f3", "e5"));
+        assert_eq!(game.history()[4].captured_piece.map(|piece| piece.piece_type),
+                   Some(PieceType::Pawn));
+        assert_eq!(game.active_player(), PlayerColor::Black);
+    }
+
+    #[test]
+    fn invalidated_premove_is_discarded() {
+        let mut game = ChessGame::new(Board::default_board());
+        game.do_move(coords("e2", "e4")).unwrap();
+        game.do_move(coords("e7", "e5")).unwrap();
+        game.do_move(coords("d1", "h5")).unwrap();
+        assert_eq!(game.queue_premove(PlayerColor::White, coords("h5", "h8")),
+                   Err(ChessError::InvalidPremove(coords("h5", "h8"))));
+        game.queue_premove(PlayerColor::White, coords("h5", "f7")).unwrap();
+        assert_eq!(game.cancel_premove(PlayerColor::White), Some(coords("h5", "f7")));
+        assert_eq!(game.cancel_premove(PlayerColor::White), None);
+        game.queue_premove(PlayerColor::White, coords("h5", "f7")).unwrap();
+        // the reply blocks the queen's path to f7
+        game.set_illegal_move_limit(Some(1));
+        game.do_move(coords("g7", "g6")).unwrap();
+        assert_eq!(game.pending_premove(PlayerColor::White), None);
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert_eq!(game.history().len(), 4);
+        assert_eq!(game.illegal_moves(PlayerColor::White), 0);
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+    }
+
+    #[test]
+    fn premoves_not_played_in_notation() {
+        use crate::chess::crazyhouse::DropMove;
+        use crate::chess::variant::Variant;
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        for (from, to) in [("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("d8", "d5")] {
+            game.do_move(coords(from, to)).unwrap();
+        }
+        // both premoves would answer the check if they were played after the move
+        game.queue_premove(PlayerColor::Black, coords("c7", "c6")).unwrap();
+        assert_eq!(game.move_to_san(coords("f1", "b5")).as_deref(), Some("Bb5+"));
+        game.queue_premove(PlayerColor::Black, coords("e8", "d7")).unwrap();
+        let drop = DropMove::new(PieceType::Pawn, "d7".parse().unwrap());
+        assert_eq!(game.drop_to_san(drop).as_deref(), Some("P@d7+"));
+        assert_eq!(game.pending_premove(PlayerColor::Black), Some(coords("e8", "d7")));
+        assert_eq!(game.history().len(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn premoves_not_played_in_search() {
+        use crate::moves::CastlingRights;
+        let mut game = ChessGame::new(Board::default_board());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string("4k3/8/8/3q4/8/8/8/4K3").unwrap(),
+                          PlayerColor::White, no_rights, None).unwrap();
+        let expected = game.best_move(3);
+        // the premove would lose the queen to the king
+        game.queue_premove(PlayerColor::Black, coords("d5", "d2")).unwrap();
+        assert_eq!(game.best_move(3), expected);
+        assert_eq!(game.pending_premove(PlayerColor::Black), Some(coords("d5", "d2")));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn discarded_premove_event() {
+        use crate::chess::observer::GameEvent;
+        let mut game = ChessGame::new(Board::default_board());
+        game.do_move(coords("e2", "e4")).unwrap();
+        game.queue_premove(PlayerColor::White, coords("e4", "e5")).unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        game.set_observer(alloc::boxed::Box::new(sender));
+        game.do_move(coords("e7", "e5")).unwrap();
+        let events: alloc::vec::Vec<GameEvent> = receiver.try_iter().collect();
+        assert_eq!(events.last(),
+                   Some(&GameEvent::PremoveDiscarded(PlayerColor::White, coords("e4", "e5"))));
+    }
+}
@@ -0,0 +1,75 @@
+//! Random move selection, for example for Monte Carlo experiments or for fuzzing user interfaces.
+//! Only available with the `rand` feature.
+
+use rand::{Rng, RngExt};
+use crate::chess::{ChessGame, GameStatus};
+use crate::moves::ChessMove;
+
+impl ChessGame {
+    /// Picks one of the [legal moves](ChessGame::legal_moves) uniformly at random, where each
+    /// promotion type counts as a separate move. The pick only depends on the state of `rng`, so
+    /// a seeded generator always picks the same move in the same position.
+    ///
+    /// returns: `Some(ChessMove)` of the picked move, or `None` if there are no legal moves.
+    pub fn random_legal_move<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<ChessMove> {
+        let legal_moves = self.legal_moves();
+        if legal_moves.is_empty() {
+            return None;
+        }
+        Some(legal_moves[rng.random_range(0..legal_moves.len())])
+    }
+
+    /// [Plays out](ChessGame::play_out) the game with
+    /// [random legal moves](ChessGame::random_legal_move) until the game is over or `max_plies`
+    /// moves have been played, whichever happens first. Since the game is not drawn
+    /// automatically by repetition or by the fifty-move rule, the ply limit is what guarantees
+    /// that the playout terminates.
+    ///
+    /// returns: The [GameStatus] of the game after the playout.
+    pub fn play_random_game<R: Rng + ?Sized>(&mut self, rng: &mut R,
+                                             max_plies: usize) -> GameStatus
+    {
+        // the moves are legal, so the playout never fails
+        self.play_out(|game| game.random_legal_move(rng), max_plies)
+            .unwrap_or_else(|_| *self.game_status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::board::Board;
+    use super::*;
+
+    fn playout(seed: u64, max_plies: usize) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        let mut rng = StdRng::seed_from_u64(seed);
+        game.play_random_game(&mut rng, max_plies);
+        game
+    }
+
+    #[test]
+    fn seeded_playouts_are_stable() {
+        let moves = |game: &ChessGame| -> Vec<ChessMove> {
+            game.history().iter().map(|details| details.chess_move).collect()
+        };
+        let game = playout(7, 60);
+        assert_eq!(moves(&game), moves(&playout(7, 60)));
+        assert_ne!(moves(&game), moves(&playout(8, 60)));
+        // a shorter playout with the same seed is a prefix of the longer one
+        assert_eq!(moves(&game)[..20], moves(&playout(7, 20))[..]);
+    }
+
+    #[test]
+    fn playouts_terminate() {
+        for seed in 0..1000 {
+            let game = playout(seed, 10);
+            assert!(game.history().len() <= 10);
+            if game.history().len() < 10 {
+                assert!(game.game_status().is_over());
+                assert_eq!(game.random_legal_move(&mut StdRng::seed_from_u64(seed)), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+use crate::board::piece::PieceType;
+use crate::chess::{ChessGame, MoveDetails};
+use crate::moves::CastlingRights;
+
+impl ChessGame {
+    /// returns: The number of plies since the last irreversible move, that is, a pawn move, a
+    ///          capture, a [drop](crate::chess::crazyhouse::DropMove) or a move changing the
+    ///          castling rights, or since the position was set up if there has been none. No
+    ///          position before an irreversible move can occur again, so only the positions in
+    ///          this window are kept for detecting repetitions.
+    pub fn reversible_ply_count(&self) -> usize {
+        self.position_keys.len()
+    }
+
+    /// Counts how many times the current position has occurred since the last irreversible move
+    /// (see [reversible_ply_count](ChessGame::reversible_ply_count)). Positions are compared by
+    /// their [Polyglot keys](ChessGame::polyglot_key), so two positions are the same if they have
+    /// the same pieces on the same squares, the same player to move, the same castling rights
+    /// and the same possibility of capturing en passant.
+    ///
+    /// returns: The number of occurrences, including the current one, so at least 1.
+    pub fn repetition_count(&self) -> usize {
+        let key = self.polyglot_key();
+        1 + self.position_keys.iter().filter(|&&earlier| earlier == key).count()
+    }
+
+    /// returns: Whether the current position has occurred at least three times, see
+    ///          [repetition_count](ChessGame::repetition_count).
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// Records the position a move was made from for detecting repetitions, or forgets all
+    /// earlier positions if the move was irreversible, since none of them can occur again.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_key`: The [Polyglot key](ChessGame::polyglot_key) of the position before the
+    ///   move.
+    /// * `previous_castling_rights`: The castling rights before the move.
+    /// * `details`: The move.
+    ///
+    /// returns: The forgotten positions if the move was irreversible, otherwise `None`.
+    pub(super) fn record_position(&mut self, previous_key: u64,
+                                  previous_castling_rights: (CastlingRights, CastlingRights),
+                                  details: &MoveDetails) -> Option<Vec<u64>>
+    {
+        let irreversible = details.is_drop
+            || details.piece.piece_type == PieceType::Pawn
+            || details.captured_piece.is_some()
+            || previous_castling_rights != self.castling_rights;
+        if irreversible {
+            Some(core::mem::take(&mut self.position_keys))
+        } else {
+            self.position_keys.push(previous_key);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use super::*;
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for chess_move in moves {
+            game.try_move_str(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.repetition_count(), 1);
+        play(&mut game, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+        assert_eq!(game.reversible_ply_count(), 4);
+        assert_eq!(game.repetition_count(), 2);
+        play(&mut game, &["Nf3"]);
+        assert_eq!(game.repetition_count(), 2);
+        play(&mut game, &["Nf6", "Ng1", "Ng8"]);
+        assert!(game.is_threefold_repetition());
+
+        // a pawn move makes the earlier positions unrepeatable
+        // and so does losing castling rights, which the fifty-move rule does not count
+        play(&mut game, &["e4", "e5", "Ke2", "Ke7", "Ke1", "Ke8"]);
+        assert_eq!(game.reversible_ply_count(), 2);
+        assert_eq!(game.halfmove_clock(), 4);
+        assert_eq!(game.repetition_count(), 1);
+        play(&mut game, &["Ke2", "Ke7", "Ke1", "Ke8"]);
+        assert_eq!(game.reversible_ply_count(), 6);
+        assert_eq!(game.repetition_count(), 2);
+
+        // taking back a move restores the window
+        game.undo_move().unwrap();
+        assert_eq!(game.reversible_ply_count(), 5);
+        assert_eq!(game.repetition_count(), 2);
+    }
+
+    #[test]
+    fn bounded_window() {
+        let mut game = ChessGame::new(Board::default_board());
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+        let white_pawns = ["a3", "a4", "b3", "b4", "c3", "c4", "d3", "d4", "h3", "h4"];
+        let black_pawns = ["a6", "a5", "b6", "b5", "c6", "c5", "d6", "d5", "h6", "h5"];
+        for ply in 0..500 {
+            // a pair of pawn moves after every 48 plies of shuffling
+            let cycle = ply % 50;
+            if cycle == 48 {
+                play(&mut game, &[white_pawns[ply / 50]]);
+            } else if cycle == 49 {
+                play(&mut game, &[black_pawns[ply / 50]]);
+            } else {
+                play(&mut game, &[shuffle[cycle % shuffle.len()]]);
+            }
+            assert_eq!(game.reversible_ply_count(), game.halfmove_clock());
+            assert!(game.position_keys.len() <= 48);
+            if cycle < 48 && cycle % 4 == 3 {
+                // the position after the last pawn moves has repeated once per shuffle cycle
+                assert_eq!(game.repetition_count(), cycle / 4 + 2, "ply {}", ply);
+            }
+        }
+        assert_eq!(game.history().len(), 500);
+    }
+}
@@ -0,0 +1,288 @@
+//! Bookkeeping for matches of several games between two players. See [Match].
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
+use crate::board::Board;
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessGame, Outcome};
+use crate::pgn;
+
+/// The length of a [Match].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MatchFormat {
+    /// At most the given number of games are played, but the match is decided as soon as one
+    /// player has more than half of the points available.
+    BestOf(u32),
+    /// Exactly the given number of games are played.
+    FixedLength(u32),
+}
+
+impl MatchFormat {
+    /// returns: The maximum number of games in a match of this format.
+    pub fn max_games(&self) -> u32 {
+        match self {
+            MatchFormat::BestOf(games) | MatchFormat::FixedLength(games) => *games,
+        }
+    }
+}
+
+/// A match of several games between two players, who alternate colors each game: the first
+/// player has the white pieces in the first game, the second player in the second game, and so
+/// on. Each game is created from the match's starting positions (the standard starting position
+/// by default), which are used in turn. The caller performs the moves of the current game
+/// through [current_game_mut](Match::current_game_mut), and the match keeps track of the
+/// results: a win is worth one point, and a draw half a point. A drawn game counts as a win for
+/// a player with [draw odds](ChessGame::set_draw_odds), which are kept by the games created from
+/// a starting position with draw odds, such as in an Armageddon tiebreak.
+#[derive(Clone, Debug)]
+pub struct Match {
+    players: [String; 2],
+    format: MatchFormat,
+    starting_positions: Vec<ChessGame>,
+    games: Vec<ChessGame>,
+}
+
+impl Match {
+    /// returns: A new match of the given format between two players, identified by name.
+    pub fn new(first_player: impl Into<String>, second_player: impl Into<String>,
+               format: MatchFormat) -> Match
+    {
+        Match {
+            players: [first_player.into(), second_player.into()],
+            format,
+            starting_positions: vec![ChessGame::new(Board::default_board())],
+            games: Vec::new(),
+        }
+    }
+
+    /// Sets the positions the games of the match start from, for example a set of openings.
+    /// Game `n` (counting from zero) starts from position `n % starting_positions.len()`. A
+    /// starting position which already has a move history keeps it in each game created from it.
+    /// Games which have already been created are not affected.
+    ///
+    /// returns: The match with the given starting positions, or unchanged if
+    ///          `starting_positions` is empty.
+    pub fn with_starting_positions(mut self, starting_positions: Vec<ChessGame>) -> Match {
+        if !starting_positions.is_empty() {
+            self.starting_positions = starting_positions;
+        }
+        self
+    }
+
+    /// returns: The names of the first and the second player.
+    pub fn players(&self) -> (&str, &str) {
+        (&self.players[0], &self.players[1])
+    }
+
+    /// returns: The format of the match.
+    pub fn format(&self) -> MatchFormat {
+        self.format
+    }
+
+    /// returns: The name of the player with the white pieces in the game with the given index
+    ///          (counting from zero).
+    pub fn white_player(&self, game_index: usize) -> &str {
+        &self.players[game_index % 2]
+    }
+
+    /// returns: The name of the player with the black pieces in the game with the given index
+    ///          (counting from zero).
+    pub fn black_player(&self, game_index: usize) -> &str {
+        &self.players[(game_index + 1) % 2]
+    }
+
+    /// returns: The games of the match created so far, including the current game if it has not
+    ///          ended yet.
+    pub fn games(&self) -> &[ChessGame] {
+        &self.games
+    }
+
+    /// Returns the game currently being played. If the previous game has ended and the match is
+    /// not yet decided, the next game is created.
+    ///
+    /// returns: `Some(&mut ChessGame)` of the current game, or `None` if the match is decided.
+    pub fn current_game_mut(&mut self) -> Option<&mut ChessGame> {
+        let needs_new_game = self.games.last().is_none_or(|game| game.game_status().is_over());
+        if needs_new_game {
+            if self.is_decided() {
+                return None;
+            }
+            let starting_position = &self.starting_positions[
+                self.games.len() % self.starting_positions.len()];
+            self.games.push(starting_position.clone());
+        }
+        self.games.last_mut()
+    }
+
+    /// returns: The points scored by the first and the second player in the finished games.
+    pub fn score(&self) -> (f32, f32) {
+        let mut score = (0.0, 0.0);
+        for (game_index, game) in self.games.iter().enumerate() {
+            let (white_points, black_points) = match game.match_outcome() {
+                Some(Outcome::Win(PlayerColor::White)) => (1.0, 0.0),
+                Some(Outcome::Win(PlayerColor::Black)) => (0.0, 1.0),
+                Some(Outcome::Draw) => (0.5, 0.5),
+                None => (0.0, 0.0),
+            };
+            if game_index % 2 == 0 {
+                score.0 += white_points;
+                score.1 += black_points;
+            } else {
+                score.0 += black_points;
+                score.1 += white_points;
+            }
+        }
+        score
+    }
+
+    /// returns: The number of games of the match which have ended.
+    pub fn finished_games(&self) -> usize {
+        self.games.iter().filter(|game| game.game_status().is_over()).count()
+    }
+
+    /// returns: Whether the result of the match is known, meaning that no more games will be
+    ///          played.
+    pub fn is_decided(&self) -> bool {
+        let max_games = self.format.max_games();
+        if self.finished_games() >= max_games as usize {
+            return true;
+        }
+        match self.format {
+            MatchFormat::BestOf(_) => {
+                let (first, second) = self.score();
+                2.0 * first.max(second) > max_games as f32
+            }
+            MatchFormat::FixedLength(_) => false,
+        }
+    }
+
+    /// returns: `Some(&str)` with the name of the player who won the match, or `None` if the
+    ///          match is not decided yet or has ended in a tie.
+    pub fn winner(&self) -> Option<&str> {
+        if !self.is_decided() {
+            return None;
+        }
+        let (first, second) = self.score();
+        if first > second {
+            Some(&self.players[0])
+        } else if second > first {
+            Some(&self.players[1])
+        } else {
+            None
+        }
+    }
+
+    /// Exports all games of the match in PGN, one after another separated by blank lines. Each
+    /// game has the seven tag roster, with the game's number as the round.
+    ///
+    /// # Arguments
+    ///
+    /// * `event`: The name of the match, used as the `Event` tag of each game.
+    ///
+    /// returns: The games in PGN.
+    pub fn to_pgn(&self, event: &str) -> String {
+        let games: Vec<String> = self.games.iter().enumerate()
+            .map(|(game_index, game)| {
+                let round = (game_index + 1).to_string();
+                pgn::write_game(game, &[
+                    ("Event", event),
+                    ("Site", "?"),
+                    ("Date", "????.??.??"),
+                    ("Round", &round),
+                    ("White", self.white_player(game_index)),
+                    ("Black", self.black_player(game_index)),
+                    ("Result", pgn::result_token(game.game_status())),
+                ])
+            })
+            .collect();
+        games.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::ChessMove;
+    use super::*;
+
+    fn play(game: &mut ChessGame, moves: &[(&str, &str)]) {
+        for (from, to) in moves {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+    }
+
+    const FOOLS_MATE: [(&str, &str); 4] = [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")];
+
+    #[test]
+    fn fixed_length_match() {
+        let mut chess_match = Match::new("Alice", "Bob", MatchFormat::FixedLength(4));
+
+        // game 1: Alice is white and gets mated
+        play(chess_match.current_game_mut().unwrap(), &FOOLS_MATE);
+        assert_eq!(chess_match.score(), (0.0, 1.0));
+        // game 2: Alice is black and mates
+        play(chess_match.current_game_mut().unwrap(), &FOOLS_MATE);
+        assert_eq!(chess_match.score(), (1.0, 1.0));
+        // games 3 and 4 are drawn
+        for _ in 0..2 {
+            let game = chess_match.current_game_mut().unwrap();
+            play(game, &[("e2", "e4")]);
+            game.draw_by_agreement().unwrap();
+        }
+
+        assert_eq!(chess_match.games().len(), 4);
+        assert_eq!(chess_match.score(), (2.0, 2.0));
+        assert!(chess_match.is_decided());
+        assert_eq!(chess_match.winner(), None);
+        assert!(chess_match.current_game_mut().is_none());
+        let white_players: Vec<&str> = (0..4).map(|i| chess_match.white_player(i)).collect();
+        assert_eq!(white_players, ["Alice", "Bob", "Alice", "Bob"]);
+
+        let pgn = chess_match.to_pgn("Club match");
+        assert_eq!(pgn.matches("[Event \"Club match\"]").count(), 4);
+        assert!(pgn.contains(concat!(
+            "[Round \"2\"]\n[White \"Bob\"]\n[Black \"Alice\"]\n[Result \"0-1\"]\n",
+            "\n1. f3 e5 2. g4 Qh4# 0-1\n",
+        )));
+        assert!(pgn.contains("[Round \"4\"]\n[White \"Bob\"]\n[Black \"Alice\"]\n"));
+        assert!(pgn.ends_with("\n1. e4 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn best_of_match() {
+        let mut chess_match = Match::new("Alice", "Bob", MatchFormat::BestOf(3));
+        let mut opening = ChessGame::new(Board::default_board());
+        play(&mut opening, &[("f2", "f3")]);
+        chess_match = chess_match.with_starting_positions(vec![opening]);
+
+        // Bob wins both games as black and white, deciding the match early
+        let game = chess_match.current_game_mut().unwrap();
+        play(game, &FOOLS_MATE[1..]);
+        assert!(!chess_match.is_decided());
+        let game = chess_match.current_game_mut().unwrap();
+        play(game, &[("e7", "e6"), ("g2", "g4")]);
+        game.resign().unwrap();
+        assert_eq!(chess_match.score(), (0.0, 2.0));
+        assert!(chess_match.is_decided());
+        assert_eq!(chess_match.winner(), Some("Bob"));
+        assert!(chess_match.current_game_mut().is_none());
+    }
+
+    #[test]
+    fn armageddon_stalemate() {
+        let mut armageddon = ChessGame::from_fen("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1").unwrap();
+        armageddon.set_draw_odds(Some(PlayerColor::Black));
+        let mut chess_match = Match::new("Alice", "Bob", MatchFormat::FixedLength(1))
+            .with_starting_positions(vec![armageddon]);
+
+        let game = chess_match.current_game_mut().unwrap();
+        play(game, &[("f1", "f7")]);
+        assert!(game.game_status().is_draw());
+        assert_eq!(game.outcome(), Some(Outcome::Draw));
+        assert_eq!(game.match_outcome(), Some(Outcome::Win(PlayerColor::Black)));
+        assert_eq!(game.starting_position().draw_odds(), Some(PlayerColor::Black));
+        assert_eq!(chess_match.score(), (0.0, 1.0));
+        assert_eq!(chess_match.winner(), Some("Bob"));
+        assert!(chess_match.to_pgn("Tiebreak").contains("[Result \"1/2-1/2\"]"));
+    }
+}
@@ -0,0 +1,149 @@
+//! A thread-safe handle to a [ChessGame], for sharing a game between threads or asynchronous
+//! tasks. See [SharedChessGame].
+
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessError, ChessGame, GameStatus, MoveDetails};
+use crate::moves::ChessMove;
+use crate::moves::util::BoardBitmap;
+
+/// A cloneable, thread-safe handle to a [ChessGame]. All clones of a handle refer to the same
+/// game.
+///
+/// # Blocking
+///
+/// The game is protected by a [RwLock]. The query methods (such as
+/// [available_moves](SharedChessGame::available_moves)) take a read lock, and may run
+/// concurrently with each other, but block while a mutation is in progress. The mutation methods
+/// (such as [do_move](SharedChessGame::do_move)) take a write lock, and block until all ongoing
+/// queries and mutations have finished. Locks are only held for the duration of a single method
+/// call, so consecutive calls may observe different states of the game; use
+/// [read](SharedChessGame::read) or [write](SharedChessGame::write) to perform several operations
+/// atomically.
+#[derive(Clone, Debug)]
+pub struct SharedChessGame {
+    game: Arc<RwLock<ChessGame>>,
+}
+
+impl From<ChessGame> for SharedChessGame {
+    fn from(game: ChessGame) -> Self {
+        SharedChessGame { game: Arc::new(RwLock::new(game)) }
+    }
+}
+
+impl SharedChessGame {
+    /// returns: A handle to a new [ChessGame] with the given starting board configuration.
+    pub fn new(starting_board: Board) -> SharedChessGame {
+        ChessGame::new(starting_board).into()
+    }
+
+    /// Locks the game for reading, blocking until no mutation is in progress. A game mutation
+    /// cannot leave the game in an inconsistent state when interrupted by a panic, so a poisoned
+    /// lock is simply recovered.
+    ///
+    /// returns: A guard which gives shared access to the game until dropped.
+    pub fn read(&self) -> RwLockReadGuard<'_, ChessGame> {
+        self.game.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the game for writing, blocking until all other queries and mutations have finished.
+    ///
+    /// returns: A guard which gives exclusive access to the game until dropped.
+    pub fn write(&self) -> RwLockWriteGuard<'_, ChessGame> {
+        self.game.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// See [ChessGame::game_status]. Takes a read lock.
+    pub fn game_status(&self) -> GameStatus {
+        *self.read().game_status()
+    }
+
+    /// See [ChessGame::active_player]. Takes a read lock.
+    pub fn active_player(&self) -> PlayerColor {
+        self.read().active_player()
+    }
+
+    /// See [ChessGame::board]. Takes a read lock.
+    ///
+    /// returns: A copy of the current board state.
+    pub fn board(&self) -> Board {
+        self.read().board().clone()
+    }
+
+    /// See [ChessGame::available_moves]. Takes a read lock.
+    pub fn available_moves(&self, pos: BoardPosition) -> BoardBitmap {
+        self.read().available_moves(pos)
+    }
+
+    /// See [ChessGame::expects_promotion_move]. Takes a read lock.
+    pub fn expects_promotion_move(&self, pos: BoardPosition) -> bool {
+        self.read().expects_promotion_move(pos)
+    }
+
+    /// See [ChessGame::do_move]. Takes a write lock.
+    pub fn do_move(&self, chess_move: ChessMove) -> Result<MoveDetails, ChessError> {
+        self.write().do_move(chess_move)
+    }
+
+    /// See [ChessGame::offer_draw]. Takes a write lock.
+    pub fn offer_draw(&self) -> Result<(), ChessError> {
+        self.write().offer_draw()
+    }
+
+    /// See [ChessGame::draw_by_agreement]. Takes a write lock.
+    pub fn draw_by_agreement(&self) -> Result<(), ChessError> {
+        self.write().draw_by_agreement()
+    }
+
+    /// See [ChessGame::resign]. Takes a write lock.
+    pub fn resign(&self) -> Result<(), ChessError> {
+        self.write().resign()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use crate::board::piece::Piece;
+    use super::*;
+
+    #[test]
+    fn concurrent_queries() {
+        let game = SharedChessGame::new(Board::default_board());
+        let done = AtomicBool::new(false);
+        let moves = [
+            ("e2", "e4"), ("e7", "e5"), ("g1", "f3"), ("b8", "c6"), ("f1", "b5"), ("a7", "a6"),
+            ("b5", "a4"), ("g8", "f6"), ("e1", "g1"), ("f8", "e7"),
+        ];
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                let game = game.clone();
+                let done = &done;
+                scope.spawn(move || {
+                    while !done.load(Ordering::Relaxed) {
+                        // every snapshot must be consistent: all available moves belong to
+                        // pieces of the active player
+                        let snapshot = game.read();
+                        for pos in BoardPosition::all() {
+                            if !snapshot.available_moves(pos).is_all_zeros() {
+                                assert!(snapshot.active_piece(pos));
+                            }
+                        }
+                        drop(snapshot);
+                        game.available_moves("d2".parse().unwrap());
+                    }
+                });
+            }
+            for (from, to) in moves {
+                game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(game.active_player(), PlayerColor::White);
+        assert_eq!(game.game_status(), GameStatus::Normal);
+        assert_eq!(game.board().get_piece("g1".parse().unwrap()), Piece::from_char('K'));
+    }
+}
@@ -0,0 +1,252 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PieceType;
+use crate::chess::ChessGame;
+use crate::moves::ChessMove;
+
+/// A summary of the legal moves of the active player, for example for prompting the player in a
+/// teaching interface. See [ChessGame::move_statistics]. A promotion is counted once for each
+/// [promotion type](ChessGame::promotion_types), like in [legal_moves](ChessGame::legal_moves),
+/// and so are Crazyhouse [drops](crate::moves::ChessMove::dropping), which are not moves of any
+/// piece on the board.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveStatistics {
+    /// The number of legal moves.
+    pub legal_moves: usize,
+    /// The number of legal moves of each piece of the active player which can move, keyed by
+    /// the square of the piece.
+    pub moves_per_piece: BTreeMap<BoardPosition, usize>,
+    /// The number of legal moves which capture a piece, including captures en passant.
+    pub captures: usize,
+    /// The number of legal moves which give check, or `None` if checks were not counted.
+    pub checks: Option<usize>,
+    /// The number of pieces of the active player without any legal moves.
+    pub immobile_pieces: usize,
+}
+
+impl ChessGame {
+    /// Summarizes the legal moves of the active player, including the number of moves giving
+    /// check, which requires simulating every move. See
+    /// [move_statistics_without_checks](ChessGame::move_statistics_without_checks) for leaving
+    /// that part out.
+    ///
+    /// returns: The [MoveStatistics] of the current position.
+    pub fn move_statistics(&self) -> MoveStatistics {
+        self.statistics(true)
+    }
+
+    /// Summarizes the legal moves of the active player like
+    /// [move_statistics](ChessGame::move_statistics), without counting the moves giving check.
+    ///
+    /// returns: The [MoveStatistics] of the current position, with `checks` set to `None`.
+    pub fn move_statistics_without_checks(&self) -> MoveStatistics {
+        self.statistics(false)
+    }
+
+    /// Counts the sequences of legal moves of the given length from the current position, which
+    /// is used to test move generators against the known counts of standard positions. Moves are
+    /// counted like in [legal_moves](ChessGame::legal_moves), and a sequence ends early when the
+    /// game is over.
+    ///
+    /// see: [Perft - Chessprogramming wiki](https://www.chessprogramming.org/Perft)
+    ///
+    /// returns: The number of move sequences of length `depth`. Returns 1 if `depth` is 0.
+    pub fn perft(&self, depth: u32) -> u64 {
+        match depth {
+            0 => 1,
+            1 => self.legal_moves().len() as u64,
+            _ => self.perft_divide(depth).iter().map(|(_, count)| count).sum(),
+        }
+    }
+
+    /// Counts the move sequences of the given length like [perft](ChessGame::perft), separately
+    /// for each legal move, for finding the moves whose counts differ from those of another move
+    /// generator.
+    ///
+    /// returns: Each legal move in the [canonical order](ChessMove#canonical-order), together with
+    ///          the number of move sequences of length `depth` starting with it. Returns an empty
+    ///          `Vec` if `depth` is 0 or the game is over.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(ChessMove, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut position = self.clone();
+        self.legal_moves().into_iter()
+            .map(|chess_move| (chess_move, position.perft_after(chess_move, depth - 1)))
+            .collect()
+    }
+
+    /// Counts the move sequences of length `depth` after a move like [perft](ChessGame::perft),
+    /// [making](ChessGame::make_move) the moves in the game and unmaking them again.
+    ///
+    /// returns: The number of move sequences, or 0 if the move is not legal.
+    fn perft_after(&mut self, chess_move: ChessMove, depth: u32) -> u64 {
+        let Ok(made_move) = self.make_move(chess_move) else {
+            return 0;
+        };
+        let count = match depth {
+            0 => 1,
+            1 => self.legal_moves().len() as u64,
+            _ => self.legal_moves().into_iter()
+                .map(|chess_move| self.perft_after(chess_move, depth - 1))
+                .sum(),
+        };
+        self.unmake_move(made_move);
+        count
+    }
+
+    fn statistics(&self, count_checks: bool) -> MoveStatistics {
+        let legal_moves = self.legal_moves();
+        let mut moves_per_piece = BTreeMap::new();
+        let mut captures = 0;
+        let mut checks = 0;
+        for chess_move in &legal_moves {
+            let movement = chess_move.piece_movement;
+            if !chess_move.is_drop() {
+                *moves_per_piece.entry(movement.from).or_insert(0) += 1;
+            }
+            let is_en_passant = Some(movement.to) == self.en_passant_target()
+                && self.board().get_piece(movement.from)
+                    .is_some_and(|piece| piece.piece_type == PieceType::Pawn);
+            if self.board().get_piece(movement.to).is_some() || is_en_passant {
+                captures += 1;
+            }
+            if count_checks && self.gives_check(*chess_move) {
+                checks += 1;
+            }
+        }
+        let pieces = self.board().pieces_of(self.active_player()).count();
+        MoveStatistics {
+            legal_moves: legal_moves.len(),
+            immobile_pieces: pieces - moves_per_piece.len(),
+            moves_per_piece,
+            captures,
+            checks: count_checks.then_some(checks),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use crate::board::Board;
+    use super::*;
+
+    fn pos(pos: &str) -> BoardPosition {
+        BoardPosition::try_from(pos).unwrap()
+    }
+
+    #[test]
+    fn initial_position() {
+        let game = ChessGame::new(Board::default_board());
+        let statistics = game.move_statistics();
+        assert_eq!(statistics.legal_moves, 20);
+        assert_eq!(statistics.captures, 0);
+        assert_eq!(statistics.checks, Some(0));
+        // the pawns and knights can move
+        assert_eq!(statistics.moves_per_piece.len(), 10);
+        assert_eq!(statistics.moves_per_piece[&pos("g1")], 2);
+        assert_eq!(statistics.moves_per_piece[&pos("e2")], 2);
+        assert_eq!(statistics.immobile_pieces, 6);
+    }
+
+    #[test]
+    fn kiwipete() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let game = ChessGame::from_fen(fen).unwrap();
+        let statistics = game.move_statistics();
+        // the counts of a perft to depth 1
+        assert_eq!(statistics.legal_moves, 48);
+        assert_eq!(statistics.captures, 8);
+        assert_eq!(statistics.checks, Some(0));
+        // the pawns on c2, f2, h2 and e4 are blocked
+        assert_eq!(statistics.immobile_pieces, 4);
+        assert_eq!(statistics.moves_per_piece[&pos("e1")], 4);
+        assert_eq!(statistics.moves_per_piece[&pos("e5")], 7);
+        assert_eq!(statistics.moves_per_piece.values().sum::<usize>(), 48);
+    }
+
+    #[test]
+    fn perft_counts() {
+        let game = ChessGame::new(Board::default_board());
+        assert_eq!(game.perft(0), 1);
+        assert_eq!(game.perft(1), 20);
+        assert_eq!(game.perft(2), 400);
+        assert_eq!(game.perft(3), 8902);
+        assert_eq!(game.perft_divide(0), []);
+        let divide = game.perft_divide(2);
+        assert_eq!(divide.len(), 20);
+        assert!(divide.iter().all(|&(_, count)| count == 20));
+        let divide = game.perft_divide(3);
+        let count = |from, to| {
+            let chess_move = ChessMove::from_coords(from, to).unwrap();
+            divide.iter().find(|(divided, _)| *divided == chess_move).map(|(_, count)| *count)
+        };
+        assert_eq!(count("e2", "e4"), Some(600));
+        assert_eq!(count("g1", "f3"), Some(440));
+        assert_eq!(count("a2", "a3"), Some(380));
+        assert_eq!(count("e2", "e5"), None);
+
+        let position_3 = ChessGame::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(position_3.perft(1), 14);
+        assert_eq!(position_3.perft(2), 191);
+        assert_eq!(position_3.perft(3), 2812);
+        let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+        let position_4 = ChessGame::from_fen(fen).unwrap();
+        assert_eq!(position_4.perft(1), 6);
+        assert_eq!(position_4.perft(2), 264);
+        assert_eq!(position_4.perft(3), 9467);
+    }
+
+    #[test]
+    fn kiwipete_divide() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let game = ChessGame::from_fen(fen).unwrap();
+        // the counts of the standard perft to depth 2
+        let expected = [
+            ("a2a3", 44), ("b2b3", 42), ("g2g3", 42), ("d5d6", 41), ("a2a4", 44), ("g2g4", 42),
+            ("g2h3", 43), ("d5e6", 46), ("c3b1", 42), ("c3d1", 42), ("c3a4", 42), ("c3b5", 39),
+            ("e5d3", 43), ("e5c4", 42), ("e5g4", 44), ("e5c6", 41), ("e5g6", 42), ("e5d7", 45),
+            ("e5f7", 44), ("d2c1", 43), ("d2e3", 43), ("d2f4", 43), ("d2g5", 42), ("d2h6", 41),
+            ("e2d1", 44), ("e2f1", 44), ("e2d3", 42), ("e2c4", 41), ("e2b5", 39), ("e2a6", 36),
+            ("a1b1", 43), ("a1c1", 43), ("a1d1", 43), ("h1f1", 43), ("h1g1", 43), ("f3d3", 42),
+            ("f3e3", 43), ("f3g3", 43), ("f3h3", 43), ("f3f4", 43), ("f3g4", 43), ("f3f5", 45),
+            ("f3h5", 43), ("f3f6", 39), ("e1d1", 43), ("e1f1", 43), ("e1g1", 43), ("e1c1", 43),
+        ];
+        let divide = game.perft_divide(2);
+        let mut moves: Vec<ChessMove> = divide.iter().map(|&(chess_move, _)| chess_move).collect();
+        crate::moves::sort_moves_canonical(&mut moves);
+        assert!(divide.iter().map(|&(chess_move, _)| chess_move).eq(moves));
+        let mut counts: Vec<(String, u64)> = divide.iter()
+            .map(|(chess_move, count)| (chess_move.to_string(), *count))
+            .collect();
+        counts.sort();
+        let mut expected: Vec<(String, u64)> = expected.iter()
+            .map(|&(uci, count)| (String::from(uci), count))
+            .collect();
+        expected.sort();
+        assert_eq!(counts, expected);
+        assert_eq!(game.perft(2), 2039);
+    }
+
+    #[test]
+    fn checks_and_captures() {
+        // the king can capture the rook on d2, and the rook can give check on a8
+        let game = ChessGame::from_fen("4k3/8/8/8/8/8/3r4/R3K3 w Q - 0 1").unwrap();
+        let statistics = game.move_statistics();
+        assert_eq!(statistics.legal_moves, 12);
+        assert_eq!(statistics.captures, 1);
+        assert_eq!(statistics.checks, Some(1));
+        assert_eq!(statistics.moves_per_piece[&pos("a1")], 10);
+        assert_eq!(statistics.moves_per_piece[&pos("e1")], 2);
+        assert_eq!(statistics.immobile_pieces, 0);
+
+        let without_checks = game.move_statistics_without_checks();
+        assert_eq!(without_checks.checks, None);
+        assert_eq!(without_checks.legal_moves, 12);
+
+        let en_passant = ChessGame::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(en_passant.move_statistics().captures, 1);
+    }
+}
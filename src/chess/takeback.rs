@@ -0,0 +1,350 @@
+use alloc::vec::Vec;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessError, ChessGame, GameStatus, MoveDetails};
+use crate::chess::crazyhouse::Hand;
+use crate::moves;
+use crate::moves::CastlingRights;
+use crate::moves::util::BoardBitmap;
+
+/// A request of a player to take back moves, which is pending until the other player answers
+/// it or a move is made. See [ChessGame::request_takeback].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TakebackRequest {
+    /// The player requesting the takeback.
+    pub player: PlayerColor,
+    /// The number of plies (moves of either player) to take back.
+    pub plies: u8,
+}
+
+/// The state of a game before a move which cannot be derived from the [MoveDetails] of the
+/// move, kept for taking the move back without replaying the game from the starting position.
+#[derive(Clone, Debug)]
+pub(super) struct UndoState {
+    pub(super) game_status: GameStatus,
+    castling_rights: (CastlingRights, CastlingRights),
+    en_passant_target: Option<BoardPosition>,
+    extra_moves: u32,
+    hands: (Hand, Hand),
+    promoted: BoardBitmap,
+    /// The positions forgotten for detecting repetitions, if the move was irreversible.
+    pub(super) forgotten_position_keys: Option<Vec<u64>>,
+}
+
+impl ChessGame {
+    /// Requests to take back the last moves of the game, notifying the
+    /// [observer](crate::chess::observer::GameObserver::on_takeback_request). Like a draw offer,
+    /// the request has to be answered by the other player, with
+    /// [accept_takeback](ChessGame::accept_takeback) or
+    /// [decline_takeback](ChessGame::decline_takeback), and it is void once a move is made. A
+    /// new request replaces a pending one.
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player requesting the takeback.
+    /// * `plies`: The number of plies to take back, or `None` to go back to the last time it was
+    ///   the player's turn: 1 ply if the player has just moved, and 2 plies if it is the player's
+    ///   turn.
+    ///
+    /// returns: `Ok(TakebackRequest)` of the pending request.
+    ///          [InvalidTakeback](ChessError::InvalidTakeback) if the number of plies is zero or
+    ///          more than the number of moves in the [history](ChessGame::history).
+    ///          [GameNotStarted](ChessError::GameNotStarted) if neither player has made a move yet.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn request_takeback(&mut self, player: PlayerColor, plies: Option<u8>)
+                            -> Result<TakebackRequest, ChessError>
+    {
+        match self.game_status {
+            GameStatus::Normal => {}
+            GameStatus::NotYetStarted => return Err(ChessError::GameNotStarted),
+            GameStatus::Draw(..) | GameStatus::Win(..) => {
+                return Err(ChessError::GameAlreadyEnded);
+            }
+        }
+        let plies = plies.unwrap_or(if player == self.active_player { 2 } else { 1 });
+        if plies == 0 || plies as usize > self.history.len() {
+            return Err(ChessError::InvalidTakeback);
+        }
+        let request = TakebackRequest { player, plies };
+        self.takeback_request = Some(request);
+        self.observer.notify(|observer| observer.on_takeback_request(&request));
+        Ok(request)
+    }
+
+    /// returns: The pending [TakebackRequest], if any.
+    pub fn pending_takeback(&self) -> Option<TakebackRequest> {
+        self.takeback_request
+    }
+
+    /// Accepts the pending takeback request, taking back the requested number of plies. The
+    /// position, the [history](ChessGame::history) and the game status are restored to what they
+    /// were before the first of the moves, and the
+    /// [observer](crate::chess::observer::GameObserver::on_takeback) is notified before any change
+    /// in game status. Queued [premoves](ChessGame::queue_premove) are removed, and clocks are
+    /// not adjusted.
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player accepting the request, who has to be the other player than the one
+    ///   who made it.
+    ///
+    /// returns: `Ok(TakebackRequest)` of the accepted request.
+    ///          [InvalidTakeback](ChessError::InvalidTakeback) if there is no pending request, or
+    ///          if it was made by `player`.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn accept_takeback(&mut self, player: PlayerColor) -> Result<TakebackRequest, ChessError> {
+        let request = self.answerable_takeback(player)?;
+        self.takeback_request = None;
+        self.rewind(request.plies as usize);
+        debug_assert_eq!(self.verify_integrity(), Ok(()));
+        Ok(request)
+    }
+
+    /// Declines the pending takeback request, leaving the game unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player declining the request, who has to be the other player than the one
+    ///   who made it.
+    ///
+    /// returns: `Ok(TakebackRequest)` of the declined request.
+    ///          [InvalidTakeback](ChessError::InvalidTakeback) if there is no pending request, or
+    ///          if it was made by `player`.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn decline_takeback(&mut self, player: PlayerColor)
+                            -> Result<TakebackRequest, ChessError>
+    {
+        let request = self.answerable_takeback(player)?;
+        self.takeback_request = None;
+        Ok(request)
+    }
+
+    /// Takes back the last move of the history directly, without a takeback request, for example
+    /// for analysis or for stepping through a game. Unlike a takeback, this is also possible after
+    /// the game has ended: taking back the move which ended the game, or any move before a
+    /// resignation or another way of ending the game without a move, lets the game continue and
+    /// removes its [termination record](ChessGame::game_termination). A pending takeback request
+    /// and queued [premoves](ChessGame::queue_premove) are removed. In
+    /// [Crazyhouse](crate::chess::variant::Variant::Crazyhouse), a dropped piece goes back into
+    /// the hand of its player, and a captured piece leaves the hand of the capturing player.
+    ///
+    /// returns: The [MoveDetails] of the move taken back, or `None` if the history is empty.
+    pub fn undo_move(&mut self) -> Option<MoveDetails> {
+        let details = *self.history.last()?;
+        self.takeback_request = None;
+        self.rewind(1);
+        debug_assert_eq!(self.verify_integrity(), Ok(()));
+        Some(details)
+    }
+
+    /// returns: The pending request if the player may answer it.
+    fn answerable_takeback(&self, player: PlayerColor) -> Result<TakebackRequest, ChessError> {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        self.takeback_request
+            .filter(|request| request.player != player)
+            .ok_or(ChessError::InvalidTakeback)
+    }
+
+    /// returns: The state to restore when taking back a move made in the current position, with
+    ///          no forgotten positions, which are only known once the move has been made.
+    pub(super) fn undo_state(&self) -> UndoState {
+        UndoState {
+            game_status: self.game_status,
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            extra_moves: self.extra_moves,
+            hands: self.hands,
+            promoted: self.promoted,
+            forgotten_position_keys: None,
+        }
+    }
+
+    /// Takes back the last plies of the history one by one with
+    /// [undo_last_ply](ChessGame::undo_last_ply), keeping the settings, the observer and the
+    /// annotations of the game.
+    pub(super) fn rewind(&mut self, plies: usize) {
+        let mut game_status = self.game_status;
+        for _ in 0..plies {
+            match self.undo_last_ply() {
+                Some(previous_status) => game_status = previous_status,
+                None => break,
+            }
+        }
+        self.premoves = (None, None);
+        self.update_available_moves();
+        self.observer.notify(|observer| observer.on_takeback(plies));
+        self.set_game_status(game_status);
+    }
+
+    /// Takes back the last move of the history with [unplay](ChessGame::unplay), and forgets
+    /// its position for detecting repetitions. The available moves are not recalculated.
+    ///
+    /// returns: The game status before the move, or `None` if the history is empty.
+    fn undo_last_ply(&mut self) -> Option<GameStatus> {
+        let details = self.history.pop()?;
+        let state = self.undo_stack.pop()?;
+        self.unplay(&details, &state);
+        match state.forgotten_position_keys {
+            Some(position_keys) => self.position_keys = position_keys,
+            None => {
+                self.position_keys.pop();
+            }
+        }
+        Some(state.game_status)
+    }
+
+    /// Reverses the changes of a move to the position, which has to be the last move made: the
+    /// moved piece is moved back as it was before any promotion, together with a rook moved by
+    /// castling, and a captured piece is put back, or a dropped piece is removed. The rest of the
+    /// position before the move is restored from its [UndoState]. The game status, the history
+    /// and the available moves are left unchanged.
+    pub(super) fn unplay(&mut self, details: &MoveDetails, state: &UndoState) {
+        let movement = details.chess_move.piece_movement;
+        if details.is_drop {
+            self.board.set_piece(movement.to, None);
+        } else {
+            moves::undo_move(&mut self.board, movement, details.piece, details.removed_piece,
+                             details.secondary_movement);
+        }
+        self.active_player = details.piece.player;
+        self.castling_rights = state.castling_rights;
+        self.en_passant_target = state.en_passant_target;
+        self.extra_moves = state.extra_moves;
+        self.hands = state.hands;
+        self.promoted = state.promoted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use crate::assert_board_eq;
+    use crate::board::Board;
+    use crate::moves::ChessMove;
+    use super::*;
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for chess_move in moves {
+            game.try_move_str(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn accepted_takeback() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4", "e7e5", "g1f3", "b8c6"]);
+        let fen = game.fen();
+        let board = game.board().clone();
+        play(&mut game, &["f3e5"]);
+
+        // white has just moved, so only that move is taken back
+        let request = game.request_takeback(PlayerColor::White, None).unwrap();
+        assert_eq!(request, TakebackRequest { player: PlayerColor::White, plies: 1 });
+        assert_eq!(game.pending_takeback(), Some(request));
+        assert_eq!(game.accept_takeback(PlayerColor::White), Err(ChessError::InvalidTakeback));
+        assert_eq!(game.accept_takeback(PlayerColor::Black), Ok(request));
+        assert_board_eq!(*game.board(), board);
+        assert_eq!(game.fen(), fen);
+        assert_eq!(game.history().len(), 4);
+        assert_eq!(game.pending_takeback(), None);
+        assert_eq!(game.accept_takeback(PlayerColor::Black), Err(ChessError::InvalidTakeback));
+
+        // black is to move, so black's last move and white's reply are taken back
+        play(&mut game, &["f1b5"]);
+        assert_eq!(game.request_takeback(PlayerColor::Black, None).map(|request| request.plies),
+                   Ok(2));
+        game.accept_takeback(PlayerColor::White).unwrap();
+        assert_eq!(game.history().len(), 3);
+        assert_eq!(game.active_player(), PlayerColor::Black);
+        assert_eq!(game.verify_integrity(), Ok(()));
+
+        game.request_takeback(PlayerColor::White, Some(3)).unwrap();
+        game.accept_takeback(PlayerColor::Black).unwrap();
+        assert_board_eq!(*game.board(), Board::default_board());
+        assert_eq!(game.fen(), ChessGame::new(Board::default_board()).fen());
+        assert!(game.history().is_empty());
+        assert_eq!(*game.game_status(), GameStatus::NotYetStarted);
+    }
+
+    #[test]
+    fn undo_restores_every_position() {
+        let mut game = ChessGame::from_fen("r3k2r/1P6/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").unwrap();
+        let moves = ["e2e4", "d4e3", "e1c1", "e8g8", "b7b8q", "f8b8", "d1d2", "b8b7", "d2d1",
+                     "b7b8"];
+        let mut positions = Vec::new();
+        for chess_move in moves {
+            positions.push((game.fen(), game.polyglot_key(), game.repetition_count(),
+                            game.legal_moves()));
+            game.try_move_str(chess_move).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+        while let Some((fen, key, repetitions, legal_moves)) = positions.pop() {
+            let details = game.undo_move().unwrap();
+            assert_eq!(details.chess_move.to_string(), moves[positions.len()]);
+            assert_eq!(game.fen(), fen);
+            assert_eq!(game.polyglot_key(), key);
+            assert_eq!(game.repetition_count(), repetitions);
+            assert_eq!(game.legal_moves(), legal_moves);
+            assert_eq!(game.history().len(), positions.len());
+        }
+        assert_eq!(game.undo_move(), None);
+        assert_eq!(*game.game_status(), GameStatus::NotYetStarted);
+    }
+
+    #[test]
+    fn declined_takeback() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.request_takeback(PlayerColor::White, None),
+                   Err(ChessError::GameNotStarted));
+        play(&mut game, &["e2e4"]);
+        assert_eq!(game.request_takeback(PlayerColor::Black, None),
+                   Err(ChessError::InvalidTakeback));
+        assert_eq!(game.request_takeback(PlayerColor::White, Some(0)),
+                   Err(ChessError::InvalidTakeback));
+        let request = game.request_takeback(PlayerColor::White, None).unwrap();
+        let fen = game.fen();
+        assert_eq!(game.decline_takeback(PlayerColor::White), Err(ChessError::InvalidTakeback));
+        assert_eq!(game.decline_takeback(PlayerColor::Black), Ok(request));
+        assert_eq!(game.pending_takeback(), None);
+        assert_eq!(game.fen(), fen);
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    fn takeback_expires_on_move() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e2e4"]);
+        game.request_takeback(PlayerColor::White, None).unwrap();
+        // an illegal move does not void the request
+        game.do_move(ChessMove::from_coords("e7", "e4").unwrap()).unwrap_err();
+        assert!(game.pending_takeback().is_some());
+        play(&mut game, &["e7e5"]);
+        assert_eq!(game.pending_takeback(), None);
+        assert_eq!(game.accept_takeback(PlayerColor::Black), Err(ChessError::InvalidTakeback));
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn takeback_restores_status_and_hands() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["f2f3", "e7e5", "g2g4"]);
+        game.request_takeback(PlayerColor::White, None).unwrap();
+        play(&mut game, &["d8h4"]);
+        assert!(game.game_status().is_over());
+        assert_eq!(game.request_takeback(PlayerColor::Black, None),
+                   Err(ChessError::GameAlreadyEnded));
+
+        let mut crazyhouse = ChessGame::with_variant(Board::default_board(),
+                                                     crate::chess::variant::Variant::Crazyhouse);
+        play(&mut crazyhouse, &["e2e4", "d7d5", "e4d5"]);
+        crazyhouse.request_takeback(PlayerColor::Black, Some(1)).unwrap();
+        crazyhouse.accept_takeback(PlayerColor::White).unwrap();
+        assert!(crazyhouse.hand(PlayerColor::White).is_empty());
+        assert_eq!(crazyhouse.verify_integrity(), Ok(()));
+    }
+}
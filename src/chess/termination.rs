@@ -0,0 +1,253 @@
+use core::time::Duration;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::{ChessError, ChessGame, DrawReason, GameStatus, WinReason};
+
+/// The number of plies without a capture or a pawn move after which a player may claim a draw by
+/// the fifty-move rule.
+pub const FIFTY_MOVE_PLIES: usize = 100;
+
+/// A record of how and when a game ended, made when the status of the game becomes a draw or a
+/// win. See [ChessGame::game_termination].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GameTermination {
+    /// The number of plies in the [history](ChessGame::history) when the game ended. If the game
+    /// was ended by a move, this is the ply of that move, counted from 1.
+    pub ply: usize,
+    /// The status the game ended with.
+    pub status: GameStatus,
+    /// Whether the game was ended by the move at [ply](GameTermination::ply), such as by
+    /// checkmate, rather than by an action without a move, such as a resignation.
+    pub by_move: bool,
+    /// The time the game ended, as the duration since the Unix epoch, given by the
+    /// [time source](ChessGame::set_time_source) of the game.
+    pub timestamp: Option<Duration>,
+    /// The player who claimed the draw, for a draw which has to be claimed, such as by the
+    /// [fifty-move rule](ChessGame::claim_fifty_move_draw).
+    pub claimed_by: Option<PlayerColor>,
+}
+
+impl GameTermination {
+    /// returns: The value of the PGN `Termination` tag for the way the game ended: `"time
+    ///          forfeit"` for a win on time, `"rules infraction"` for a win by the
+    ///          [illegal move limit](ChessGame::set_illegal_move_limit), and `"adjudication"` for
+    ///          a result declared by an arbiter. `None` for a game which ended normally, for which
+    ///          the tag is left out.
+    pub fn pgn_termination(&self) -> Option<&'static str> {
+        match self.status {
+            GameStatus::Win(_, WinReason::Timeout) => Some("time forfeit"),
+            GameStatus::Win(_, WinReason::IllegalMoveForfeit) => Some("rules infraction"),
+            GameStatus::Win(_, WinReason::Adjudication | WinReason::Forfeit)
+            | GameStatus::Draw(DrawReason::Adjudication) => Some("adjudication"),
+            _ => None,
+        }
+    }
+}
+
+/// returns: The current system time as the duration since the Unix epoch, or `None` without the
+///          `std` feature or if the system time is before the epoch.
+pub(super) fn system_time() -> Option<Duration> {
+    #[cfg(feature = "std")]
+    {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+impl ChessGame {
+    /// returns: The record of how and when the game ended, or `None` if the game is not over.
+    ///          The record is removed when the game continues again, for example after
+    ///          [undo_move](ChessGame::undo_move) takes back the move which ended it.
+    pub fn game_termination(&self) -> Option<&GameTermination> {
+        self.game_termination.as_ref()
+    }
+
+    /// Sets the function giving the [timestamp](GameTermination::timestamp) of the game's
+    /// termination record, as the duration since the Unix epoch. By default, the system time is
+    /// used with the `std` feature, and no timestamp is recorded without it.
+    pub fn set_time_source(&mut self, time_source: fn() -> Option<Duration>) {
+        self.time_source = time_source;
+    }
+
+    /// returns: The number of plies since the last capture or pawn move. If there is none in the
+    ///          [history](ChessGame::history), the plies are counted on from the halfmove clock
+    ///          of the [starting position](ChessGame::starting_position), saturating at
+    ///          [usize::MAX].
+    pub fn halfmove_clock(&self) -> usize {
+        let plies = self.history.iter().rev()
+            .take_while(|details| {
+                details.piece.piece_type != PieceType::Pawn && details.captured_piece.is_none()
+            })
+            .count();
+        if plies == self.history.len() {
+            self.starting_position.halfmove_clock.saturating_add(plies)
+        } else {
+            plies
+        }
+    }
+
+    /// Ends the game in a draw claimed by the active player under the fifty-move rule, which
+    /// allows a claim once fifty moves of each player have been made without a capture or a pawn
+    /// move (see [halfmove_clock](ChessGame::halfmove_clock)). The claiming player is recorded in
+    /// the [termination record](ChessGame::game_termination).
+    ///
+    /// returns: `Ok(())` if the game was drawn.
+    ///          [InvalidDrawClaim](ChessError::InvalidDrawClaim) if fewer than
+    ///          [FIFTY_MOVE_PLIES] plies have been made without a capture or pawn move.
+    ///          [GameNotStarted](ChessError::GameNotStarted) if neither player has made a move yet.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn claim_fifty_move_draw(&mut self) -> Result<(), ChessError> {
+        match self.game_status {
+            GameStatus::Normal => {}
+            GameStatus::NotYetStarted => return Err(ChessError::GameNotStarted),
+            GameStatus::Draw(..) | GameStatus::Win(..) => {
+                return Err(ChessError::GameAlreadyEnded);
+            }
+        }
+        if self.halfmove_clock() < FIFTY_MOVE_PLIES {
+            return Err(ChessError::InvalidDrawClaim);
+        }
+        let claimant = self.active_player;
+        self.end_game(GameStatus::Draw(DrawReason::FiftyMoveRule), Some(claimant));
+        Ok(())
+    }
+
+    /// Changes the game status like [set_game_status](ChessGame::set_game_status), recording the
+    /// termination of the game if it ends, and notifying the observer.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_status`: The new status.
+    /// * `claimed_by`: The player claiming the draw, if the game ends in a claimed draw.
+    pub(super) fn end_game(&mut self, game_status: GameStatus, claimed_by: Option<PlayerColor>) {
+        if self.game_status == game_status {
+            return;
+        }
+        self.game_status = game_status;
+        self.game_termination = game_status.is_over().then(|| GameTermination {
+            ply: self.history.len(),
+            status: game_status,
+            by_move: !self.history.is_empty() && is_decided_by_position(game_status),
+            timestamp: (self.time_source)(),
+            claimed_by,
+        });
+        if !game_status.is_over() {
+            self.termination = None;
+        }
+        self.observer.notify(|observer| observer.on_status_change(&game_status));
+        if let Some(termination) = self.game_termination {
+            self.observer.notify(|observer| observer.on_termination(&termination));
+        }
+    }
+}
+
+/// returns: Whether the status is decided by the position on the board, so that a game which
+///          ends with it is ended by the last move.
+fn is_decided_by_position(status: GameStatus) -> bool {
+    matches!(status,
+        GameStatus::Win(_, WinReason::Checkmate | WinReason::KingInCenter
+                           | WinReason::AllPiecesCaptured | WinReason::Stalemate
+                           | WinReason::BareKing)
+        | GameStatus::Draw(DrawReason::Stalemate | DrawReason::BareKings))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use super::*;
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for chess_move in moves {
+            game.try_move_str(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn checkmate_termination() {
+        use std::sync::mpsc;
+        use crate::chess::observer::GameEvent;
+
+        fn fixed_time() -> Option<Duration> {
+            Some(Duration::from_secs(1_700_000_000))
+        }
+
+        let mut game = ChessGame::new(Board::default_board());
+        game.set_time_source(fixed_time);
+        let (sender, receiver) = mpsc::channel();
+        game.set_observer(alloc::boxed::Box::new(sender));
+        play(&mut game, &["f3", "e5", "g4"]);
+        assert_eq!(game.game_termination(), None);
+        play(&mut game, &["Qh4#"]);
+        let termination = GameTermination {
+            ply: 4,
+            status: GameStatus::Win(PlayerColor::Black, WinReason::Checkmate),
+            by_move: true,
+            timestamp: fixed_time(),
+            claimed_by: None,
+        };
+        assert_eq!(game.game_termination(), Some(&termination));
+        assert_eq!(termination.pgn_termination(), None);
+        let events: Vec<GameEvent> = receiver.try_iter().collect();
+        assert_eq!(events.last(), Some(&GameEvent::Termination(termination)));
+
+        // taking back the mating move clears the record
+        assert!(game.undo_move().is_some());
+        assert_eq!(game.game_termination(), None);
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+        assert_eq!(game.history().len(), 3);
+    }
+
+    #[test]
+    fn resignation_termination() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["e4", "e5"]);
+        game.resign().unwrap();
+        let termination = *game.game_termination().unwrap();
+        assert_eq!(termination.ply, 2);
+        assert!(!termination.by_move);
+        assert_eq!(termination.claimed_by, None);
+        assert_eq!(termination.status, GameStatus::Win(PlayerColor::Black, WinReason::Resignation));
+        #[cfg(feature = "std")]
+        assert!(termination.timestamp.is_some());
+
+        // undoing the move before the resignation also undoes the resignation
+        game.undo_move().unwrap();
+        assert_eq!(game.game_termination(), None);
+        assert_eq!(game.history().len(), 1);
+
+        // a forfeit by the illegal move limit is not a normal termination
+        game.set_illegal_move_limit(Some(1));
+        let illegal = crate::moves::ChessMove::from_coords("e7", "e4").unwrap();
+        assert!(game.do_move(illegal).is_err());
+        let termination = *game.game_termination().unwrap();
+        assert_eq!(termination.pgn_termination(), Some("rules infraction"));
+        assert!(!termination.by_move);
+        let pgn = crate::pgn::write_game(&game, &[]);
+        assert!(pgn.starts_with("[Termination \"rules infraction\"]\n"));
+    }
+
+    #[test]
+    fn claimed_fifty_move_draw() {
+        let mut game = ChessGame::new(Board::default_board());
+        play(&mut game, &["Nf3", "Nf6"]);
+        assert_eq!(game.claim_fifty_move_draw(), Err(ChessError::InvalidDrawClaim));
+        let shuffle = ["Ng1", "Ng8", "Nf3", "Nf6"];
+        for ply in 0..FIFTY_MOVE_PLIES - 2 {
+            play(&mut game, &[shuffle[ply % shuffle.len()]]);
+        }
+        assert_eq!(game.halfmove_clock(), FIFTY_MOVE_PLIES);
+        assert_eq!(game.active_player(), PlayerColor::White);
+        game.claim_fifty_move_draw().unwrap();
+        let termination = game.game_termination().unwrap();
+        assert_eq!(termination.status, GameStatus::Draw(DrawReason::FiftyMoveRule));
+        assert_eq!(termination.claimed_by, Some(PlayerColor::White));
+        assert_eq!(termination.ply, FIFTY_MOVE_PLIES);
+        assert!(!termination.by_move);
+        assert_eq!(game.claim_fifty_move_draw(), Err(ChessError::GameAlreadyEnded));
+        assert_eq!(game.verify_integrity(), Ok(()));
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::string::ToString;
+use crate::board::board_pos::SquareColor;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::{ChessError, ChessGame, DrawReason, GameStatus, WinReason};
+use crate::chess::variant::Variant;
+
+impl ChessGame {
+    /// Ends the game because the player ran out of time, for example when a
+    /// [ChessClock](crate::clock::ChessClock) reports the player as
+    /// [flagged](crate::clock::ChessClock::flagged_player). The other player wins on time, unless
+    /// they [cannot checkmate](ChessGame::can_checkmate) the player by any sequence of legal
+    /// moves, in which case the game is a draw. A win on time is written with the `Termination`
+    /// tag `time forfeit` when the game is [exported to PGN](crate::pgn::write_game).
+    ///
+    /// # Arguments
+    ///
+    /// * `player`: The player who ran out of time.
+    ///
+    /// returns: `Ok(GameStatus)` of the ended game: a [Timeout](WinReason::Timeout) win for the
+    ///          other player, or a
+    ///          [TimeoutVsInsufficientMaterial](DrawReason::TimeoutVsInsufficientMaterial) draw.
+    ///          [GameAlreadyEnded](ChessError::GameAlreadyEnded) if the game is already ended by
+    ///          draw or win.
+    pub fn flag(&mut self, player: PlayerColor) -> Result<GameStatus, ChessError> {
+        if self.game_status.is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        let winner = player.other_player();
+        let status = if self.can_checkmate(winner) {
+            self.termination = Some("time forfeit".to_string());
+            GameStatus::Win(winner, WinReason::Timeout)
+        } else {
+            GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+        };
+        self.set_game_status(status);
+        Ok(status)
+    }
+
+    /// Determines from the material on the board whether the player could checkmate the other
+    /// player by some sequence of legal moves, if the other player cooperates (a helpmate). The
+    /// player can checkmate if they have a pawn, a rook or a queen, two knights, a knight and a
+    /// bishop, or bishops on both square colors. Otherwise:
+    /// - With only their king, the player cannot checkmate.
+    /// - With a single knight, the player can checkmate if the other player has a piece to block
+    ///   their own king with, which is any piece except a queen, since a queen next to the king
+    ///   can always capture the knight.
+    /// - With bishops on a single square color, the player can checkmate if the other player has
+    ///   a pawn, a knight or a bishop on the other square color.
+    ///
+    /// Positions where the material would suffice but the pieces can never reach a mating
+    /// pattern, such as with locked pawns, are not detected. In variants other than standard
+    /// chess, the player is always considered able to win.
+    ///
+    /// returns: Whether the player could checkmate the other player.
+    pub fn can_checkmate(&self, player: PlayerColor) -> bool {
+        if self.variant != Variant::Standard {
+            return true;
+        }
+        let mut knights = 0;
+        // the number of bishops on light and dark squares
+        let mut bishops = [0; 2];
+        // whether the other player has any piece besides the king and queens
+        let mut opponent_blockers = false;
+        let mut opponent_pawns_or_knights = false;
+        let mut opponent_bishops = [0; 2];
+        for (pos, piece) in self.board.pieces() {
+            let color = match pos.square_color() {
+                SquareColor::Light => 0,
+                SquareColor::Dark => 1,
+            };
+            if piece.player == player {
+                match piece.piece_type {
+                    PieceType::King => {}
+                    PieceType::Knight => knights += 1,
+                    PieceType::Bishop => bishops[color] += 1,
+                    PieceType::Pawn | PieceType::Rook | PieceType::Queen => return true,
+                }
+            } else {
+                match piece.piece_type {
+                    PieceType::King | PieceType::Queen => continue,
+                    PieceType::Bishop => opponent_bishops[color] += 1,
+                    PieceType::Pawn | PieceType::Knight => opponent_pawns_or_knights = true,
+                    PieceType::Rook => {}
+                }
+                opponent_blockers = true;
+            }
+        }
+        let minor_pieces = knights + bishops[0] + bishops[1];
+        if knights >= 2 || (knights >= 1 && minor_pieces >= 2)
+            || (bishops[0] >= 1 && bishops[1] >= 1)
+        {
+            return true;
+        }
+        if knights == 1 {
+            return opponent_blockers;
+        }
+        if minor_pieces == 0 {
+            return false;
+        }
+        // only bishops on one square color, which cannot be blocked against by a rook or by
+        // bishops on the same color
+        let other_color = if bishops[0] > 0 { 1 } else { 0 };
+        opponent_pawns_or_knights || opponent_bishops[other_color] > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns: The status after the player without the move runs out of time in the position.
+    fn flag_fall(fen: &str, player: PlayerColor) -> GameStatus {
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        game.flag(player).unwrap()
+    }
+
+    #[test]
+    fn timeout_against_material() {
+        let win = GameStatus::Win(PlayerColor::White, WinReason::Timeout);
+        let draw = GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial);
+        // white's material against black's material, with black running out of time
+        let table = [
+            ("4k3/8/8/8/8/8/8/R3K3", win),     // K+R vs K
+            ("4k3/8/8/8/8/8/4P3/4K3", win),    // K+P vs K
+            ("4k3/8/8/8/8/8/8/4K3", draw),     // K vs K
+            ("4k3/8/8/8/8/8/8/1N2K3", draw),   // K+N vs K
+            ("1n2k3/8/8/8/8/8/8/1N2K3", win),  // K+N vs K+N
+            ("3qk3/8/8/8/8/8/8/1N2K3", draw),  // K+N vs K+Q
+            ("r3k3/8/8/8/8/8/8/1N2K3", win),   // K+N vs K+R
+            ("2b1k3/8/8/8/8/8/8/1N2K3", win),  // K+N vs K+B
+            ("4k3/p7/8/8/8/8/8/1N2K3", win),   // K+N vs K+P
+            ("4k3/8/8/8/8/8/8/1NN1K3", win),   // K+N+N vs K
+            ("4k3/8/8/8/8/8/8/2B1K3", draw),   // K+B vs K
+            ("4k3/8/8/8/8/8/8/1NB1K3", win),   // K+B+N vs K
+            ("4k3/8/8/8/8/8/8/2BBK3", win),    // K+B+B vs K, opposite colors
+            ("4k3/8/8/8/8/8/1B6/2B1K3", draw), // K+B+B vs K, same color
+            ("4kb2/8/8/8/8/8/8/2B1K3", draw),  // K+B vs K+B, same color
+            ("2b1k3/8/8/8/8/8/8/2B1K3", win),  // K+B vs K+B, opposite colors
+            ("r3k3/8/8/8/8/8/8/2B1K3", draw),  // K+B vs K+R
+            ("1n2k3/8/8/8/8/8/8/2B1K3", win),  // K+B vs K+N
+            ("4k3/7p/8/8/8/8/8/2B1K3", win),   // K+B vs K+P
+        ];
+        for (board, expected) in table {
+            let fen = format!("{} w - - 0 1", board);
+            assert_eq!(flag_fall(&fen, PlayerColor::Black), expected, "{}", board);
+        }
+    }
+
+    #[test]
+    fn flagging() {
+        // the player with the rook runs out of time against a lone king
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(game.flag(PlayerColor::White),
+                   Ok(GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)));
+        assert_eq!(game.flag(PlayerColor::Black), Err(ChessError::GameAlreadyEnded));
+        assert_eq!(game.termination(), None);
+
+        let mut game = ChessGame::new(crate::board::Board::default_board());
+        game.try_move_str("e2e4").unwrap();
+        assert_eq!(game.flag(PlayerColor::Black),
+                   Ok(GameStatus::Win(PlayerColor::White, WinReason::Timeout)));
+        assert_eq!(game.termination(), Some("time forfeit"));
+        assert_eq!(game.verify_integrity(), Ok(()));
+        assert!(game.try_move_str("e7e5").is_err());
+    }
+}
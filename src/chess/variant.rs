@@ -0,0 +1,293 @@
+//! Variants of the rules of chess. See [Variant].
+
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter};
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::{ChessGame, DrawReason, GameStatus, WinReason};
+use crate::moves;
+
+/// The rules a [ChessGame](crate::chess::ChessGame) is played with, see
+/// [ChessGame::with_variant](crate::chess::ChessGame::with_variant). All variants keep the
+/// normal rules of chess unless stated otherwise.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Variant {
+    /// Standard chess.
+    #[default]
+    Standard,
+    /// King of the Hill: a player also wins when their king reaches one of the four center
+    /// squares, see [HILL_SQUARES]. This takes precedence over checkmate and stalemate.
+    KingOfTheHill,
+    /// Horde: white starts with 36 pawns and no king (see [Board::horde_board]), and white's
+    /// pawns on the first rank may also make a double move. White wins by checkmate, and black
+    /// wins by capturing all of white's pieces.
+    Horde,
+    /// Crazyhouse: captured pieces go into the capturing player's hand, from which they can be
+    /// dropped on the board instead of moving a piece. See the [crazyhouse] module.
+    ///
+    /// [crazyhouse]: crate::chess::crazyhouse
+    Crazyhouse,
+    /// Shatranj, the historical predecessor of chess: the queen is replaced by the fers, which
+    /// moves one square diagonally, and the bishop by the alfil, which jumps exactly two squares
+    /// diagonally. Pawns never make double moves and only promote to a fers, and there is no
+    /// castling or en passant. Stalemating the other player wins, as does baring their king, that
+    /// is, capturing all of their other pieces, unless they can bare the king in return with
+    /// their next move, which leads to a draw.
+    Shatranj,
+}
+
+/// The squares d4, d5, e4 and e5, where a king wins the game in
+/// [King of the Hill](Variant::KingOfTheHill).
+pub const HILL_SQUARES: [&str; 4] = ["d4", "d5", "e4", "e5"];
+
+/// returns: Whether a square is one of the [HILL_SQUARES].
+pub fn is_hill_square(pos: BoardPosition) -> bool {
+    moves::zones::CENTER.get(pos)
+}
+
+impl Display for Variant {
+    /// Writes the name of the variant as used in the `Variant` tag of PGN.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", match self {
+            Variant::Standard => "Standard",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::Horde => "Horde",
+            Variant::Crazyhouse => "Crazyhouse",
+            Variant::Shatranj => "Shatranj",
+        })
+    }
+}
+
+impl Variant {
+    /// returns: The variant with the given name as written by [Display], ignoring case, spaces
+    ///          and dashes, so that both `"King of the Hill"` and `"kingOfTheHill"` are
+    ///          understood. `"Chess"` is also understood as standard chess. `None` if the name is
+    ///          unknown.
+    pub fn from_name(name: &str) -> Option<Variant> {
+        let normalized = |name: &str| -> String {
+            name.chars()
+                .filter(|ch| !ch.is_whitespace() && *ch != '-')
+                .map(|ch| ch.to_ascii_lowercase())
+                .collect()
+        };
+        let name = normalized(name);
+        if name == "chess" {
+            return Some(Variant::Standard);
+        }
+        [Variant::Standard, Variant::KingOfTheHill, Variant::Horde, Variant::Crazyhouse,
+            Variant::Shatranj]
+            .into_iter()
+            .find(|variant| normalized(&variant.to_string()) == name)
+    }
+
+    /// returns: The reason `player` has won by a rule of the variant on the given board, in
+    ///          addition to the normal ways of winning, or `None` if the player has not won.
+    pub(crate) fn variant_win(&self, board: &Board, player: PlayerColor) -> Option<WinReason> {
+        match self {
+            Variant::Standard | Variant::Crazyhouse | Variant::Shatranj => None,
+            Variant::KingOfTheHill => moves::find_king(board, player)
+                .is_some_and(is_hill_square)
+                .then_some(WinReason::KingInCenter),
+            Variant::Horde => {
+                let has_white_pieces = board.pieces_of(PlayerColor::White).next().is_some();
+                (player == PlayerColor::Black && !has_white_pieces)
+                    .then_some(WinReason::AllPiecesCaptured)
+            }
+        }
+    }
+}
+
+/// returns: The number of pieces of `player` other than their king.
+fn count_non_king_pieces(board: &Board, player: PlayerColor) -> usize {
+    board.pieces_of(player)
+        .filter(|(_, piece)| piece.piece_type != PieceType::King)
+        .count()
+}
+
+impl ChessGame {
+    /// returns: The status of a [Shatranj](Variant::Shatranj) game after the last move if a king
+    ///          has been bared, or `None` if the game continues or is played in another variant.
+    pub(super) fn bare_king_status(&self) -> Option<GameStatus> {
+        if self.variant != Variant::Shatranj {
+            return None;
+        }
+        let last_player = self.active_player.other_player();
+        let remaining = count_non_king_pieces(&self.board, last_player);
+        let is_bare = count_non_king_pieces(&self.board, self.active_player) == 0;
+        match (is_bare, remaining) {
+            (true, 0) => Some(GameStatus::Draw(DrawReason::BareKings)),
+            // the last player did not use their chance to bare the king in return
+            (false, 0) => Some(GameStatus::Win(self.active_player, WinReason::BareKing)),
+            (true, 1) => {
+                let can_bare_in_return = self.legal_moves().into_iter().any(|chess_move| {
+                    self.board.get_piece(chess_move.piece_movement.to).is_some()
+                });
+                (!can_bare_in_return).then_some(GameStatus::Win(last_player, WinReason::BareKing))
+            }
+            (true, _) => Some(GameStatus::Win(last_player, WinReason::BareKing)),
+            (false, _) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::{ChessError, ChessGame, GameStatus};
+    use crate::moves::{CastlingRights, ChessMove, PromotionType};
+
+    fn play(game: &mut ChessGame, coords: &[(&str, &str)]) {
+        for (from, to) in coords {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn hill_squares() {
+        for square in HILL_SQUARES {
+            assert!(is_hill_square(square.parse().unwrap()));
+        }
+        assert_eq!(BoardPosition::all().filter(|&pos| is_hill_square(pos)).count(), 4);
+    }
+
+    #[test]
+    fn king_of_the_hill() {
+        let walk = [("e2", "e3"), ("a7", "a6"), ("e1", "e2"), ("a6", "a5"), ("e2", "d3"),
+            ("a5", "a4"), ("d3", "e4")];
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::KingOfTheHill);
+        assert_eq!(game.variant(), Variant::KingOfTheHill);
+        play(&mut game, &walk);
+        assert_eq!(*game.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::KingInCenter));
+        assert_eq!(game.game_status().to_string(), "White won by king in the center");
+        assert_eq!(game.starting_position().variant(), Variant::KingOfTheHill);
+
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(game.variant(), Variant::Standard);
+        play(&mut game, &walk);
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+    }
+
+    #[test]
+    fn center_takes_precedence_over_stalemate() {
+        // Ke5 leaves black without legal moves, but reaching the center wins first
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::KingOfTheHill);
+        let board = Board::from_fen_string("7k/8/6Q1/8/5K2/8/8/8").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        play(&mut game, &[("f4", "e5")]);
+        assert_eq!(*game.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::KingInCenter));
+    }
+
+    #[test]
+    fn horde_double_moves() {
+        let board = Board::horde_board();
+        assert_eq!(board.to_fen_string(),
+                   "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP");
+        let game = ChessGame::with_variant(board, Variant::Horde);
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::NONE);
+        // only the pawns on the fifth rank and the a, d, e and h files of the fourth rank can move
+        assert_eq!(game.legal_moves().len(), 8);
+
+        let board = Board::from_fen_string("4k3/8/8/8/8/8/8/P3P3").unwrap();
+        let mut game = ChessGame::with_variant(board.clone(), Variant::Horde);
+        let targets = game.available_moves("a1".parse().unwrap());
+        assert_eq!(targets.to_square_list(), "a2,a3");
+        play(&mut game, &[("a1", "a3")]);
+        assert_eq!(game.en_passant_target(), Some("a2".parse().unwrap()));
+
+        // not in standard chess
+        let game = ChessGame::new(board);
+        assert_eq!(game.available_moves("a1".parse().unwrap()).to_square_list(), "a2");
+    }
+
+    #[test]
+    fn horde_wins() {
+        let board = Board::from_fen_string("4k3/8/8/8/8/8/3P4/8").unwrap();
+        let mut game = ChessGame::with_variant(board.clone(), Variant::Horde);
+        play(&mut game, &[("d2", "d3"), ("e8", "e7"), ("d3", "d4"), ("e7", "e6"), ("d4", "d5"),
+            ("e6", "d5")]);
+        assert_eq!(*game.game_status(),
+                   GameStatus::Win(PlayerColor::Black, WinReason::AllPiecesCaptured));
+        assert_eq!(game.game_status().to_string(), "Black won by capturing all pieces");
+
+        // white wins by checkmate as usual
+        let board = Board::from_fen_string("7k/2p3pp/8/8/8/8/P7/1R6").unwrap();
+        let mut game = ChessGame::with_variant(board, Variant::Horde);
+        play(&mut game, &[("a2", "a3"), ("c7", "c6"), ("b1", "b8")]);
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::Checkmate));
+    }
+
+    fn shatranj_position(fen: &str) -> ChessGame {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Shatranj);
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string(fen).unwrap(), PlayerColor::White, no_rights,
+                          None).unwrap();
+        game
+    }
+
+    #[test]
+    fn shatranj_moves() {
+        let game = ChessGame::with_variant(Board::default_board(), Variant::Shatranj);
+        assert_eq!(game.castling_rights(PlayerColor::White), CastlingRights::NONE);
+        // the alfil jumps over the pawns, and pawns make no double moves
+        let moves_from = |square: &str| game.available_moves(square.parse().unwrap())
+            .to_square_list();
+        assert_eq!(moves_from("c1"), "a3,e3");
+        assert_eq!(moves_from("e2"), "e3");
+        assert_eq!(moves_from("d1"), "");
+        assert_eq!(game.legal_moves().len(), 8 + 4 + 4);
+
+        // the fers moves one square diagonally, and gives check like one
+        let game = shatranj_position("4k3/7p/8/8/3Q4/8/8/4K3");
+        assert_eq!(game.available_moves("d4".parse().unwrap()).to_square_list(), "c3,c5,e3,e5");
+        let game = shatranj_position("4k3/4Q2p/8/8/8/8/8/4K3");
+        assert!(!game.is_check());
+        let mut game = shatranj_position("4k3/7p/2Q5/8/8/8/8/4K3");
+        play(&mut game, &[("c6", "d7")]);
+        assert!(game.is_check());
+    }
+
+    #[test]
+    fn shatranj_promotion() {
+        let mut game = shatranj_position("4k3/P6p/8/8/8/8/8/4K3");
+        let (a7, a8) = ("a7".parse().unwrap(), "a8".parse().unwrap());
+        let promotions: Vec<ChessMove> = game.legal_moves().into_iter()
+            .filter(|chess_move| chess_move.piece_movement.from == a7)
+            .collect();
+        assert_eq!(promotions, [ChessMove::promoting(a7, a8, PromotionType::Queen)]);
+        let rook_promotion = ChessMove::promoting(a7, a8, PromotionType::Rook);
+        assert_eq!(game.do_move(rook_promotion), Err(ChessError::IllegalMove(rook_promotion)));
+        game.do_move(ChessMove::promoting(a7, a8, PromotionType::Queen)).unwrap();
+        // the promoted fers can not reach e8
+        assert!(!game.is_check());
+    }
+
+    #[test]
+    fn shatranj_termination() {
+        // stalemate wins
+        let mut game = shatranj_position("k1K5/8/8/8/8/3p4/3P4/7R");
+        play(&mut game, &[("h1", "h7")]);
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::Stalemate));
+        assert_eq!(game.game_status().to_string(), "White won by stalemate");
+
+        // baring the king wins, unless the king can bare back
+        let mut game = shatranj_position("4k3/4n3/5K2/8/8/8/8/4R3");
+        play(&mut game, &[("e1", "e7")]);
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::BareKing));
+
+        let mut game = shatranj_position("4k3/4n3/8/8/8/8/8/4R2K");
+        play(&mut game, &[("e1", "e7")]);
+        assert_eq!(*game.game_status(), GameStatus::Normal);
+        play(&mut game, &[("e8", "e7")]);
+        assert_eq!(*game.game_status(), GameStatus::Draw(DrawReason::BareKings));
+
+        // the bared king loses if it does not bare back
+        let mut game = shatranj_position("4k3/4n3/8/8/8/8/8/4R2K");
+        play(&mut game, &[("e1", "e7"), ("e8", "d8")]);
+        assert_eq!(*game.game_status(), GameStatus::Win(PlayerColor::White, WinReason::BareKing));
+    }
+}
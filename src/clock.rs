@@ -0,0 +1,450 @@
+//! Chess clocks and time controls for timed games. Only available with the `std` feature.
+//!
+//! A [ChessClock] does not read the system time itself: every operation is given the current
+//! time as an [Instant], so that the clock can be driven by any time source, for example
+//! `Instant::now()` in an application, or fixed instants in tests.
+
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use crate::board::piece::PlayerColor;
+
+mod time_control;
+
+pub use time_control::{DelayMode, TimeControl, TimeControlParseError, TimeControlStage};
+
+/// An error caused by operating a [ChessClock].
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClockError {
+    /// Neither player's clock is running.
+    #[error("the clock is not running")]
+    NotRunning,
+    /// The player ran out of time, and the clock has stopped.
+    #[error("{0} ran out of time")]
+    Flagged(PlayerColor),
+}
+
+/// A chess clock keeping the remaining time of both players according to a [TimeControl].
+///
+/// When a player completes a move with [on_move](ChessClock::on_move), the time since their
+/// clock was started is deducted from their remaining time, the increment or delay of the current
+/// stage of the time control is applied according to its [DelayMode], and the time of the next
+/// stage is added if the move completed the current stage. The other player's clock is then
+/// started.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use leben_chess::board::piece::PlayerColor;
+/// use leben_chess::clock::{ChessClock, TimeControl};
+///
+/// let mut clock = ChessClock::new("180+2".parse().unwrap()).unwrap();
+/// let start = Instant::now();
+/// clock.start(PlayerColor::White, start);
+/// clock.on_move(start + Duration::from_secs(10)).unwrap();
+/// assert_eq!(clock.remaining_time(PlayerColor::White, start + Duration::from_secs(10)),
+///            Duration::from_secs(172));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChessClock {
+    time_control: TimeControl,
+    /// The remaining time of white and black when their clock was last stopped.
+    remaining: [Duration; 2],
+    /// The number of moves completed by white and black.
+    moves_made: [u32; 2],
+    /// The time white and black have spent on their current move when their clock was last
+    /// stopped, for applying delays.
+    spent: [Duration; 2],
+    /// The player whose clock is running, and since when.
+    running: Option<(PlayerColor, Instant)>,
+    flagged: Option<PlayerColor>,
+}
+
+fn player_index(player: PlayerColor) -> usize {
+    match player {
+        PlayerColor::White => 0,
+        PlayerColor::Black => 1,
+    }
+}
+
+/// returns: The index of the stage in which the move after `moves_made` moves is made.
+fn stage_index(stages: &[TimeControlStage], moves_made: u32) -> usize {
+    let mut stage_end = 0;
+    for (i, stage) in stages.iter().enumerate() {
+        match stage.moves {
+            Some(moves) => {
+                stage_end += moves;
+                if moves_made < stage_end {
+                    return i;
+                }
+            }
+            None => return i,
+        }
+    }
+    // the last stage is repeated
+    stages.len() - 1
+}
+
+/// returns: Whether a stage ends after `moves_made` moves, such that the time of the next stage is
+///          added.
+fn completes_stage(stages: &[TimeControlStage], moves_made: u32) -> bool {
+    let mut stage_end = 0;
+    for stage in stages {
+        match stage.moves {
+            Some(moves) => {
+                stage_end += moves;
+                if moves_made <= stage_end {
+                    return moves_made == stage_end;
+                }
+            }
+            None => return false,
+        }
+    }
+    // the last stage is repeated
+    match stages.last().and_then(|stage| stage.moves) {
+        Some(moves) => (moves_made - stage_end).is_multiple_of(moves),
+        None => false,
+    }
+}
+
+impl ChessClock {
+    /// returns: A stopped clock with both players' time set by the time control, or `None` if
+    ///          the time control is [unknown](TimeControl::Unknown), [untimed](TimeControl::Untimed)
+    ///          or has no stages.
+    pub fn new(time_control: TimeControl) -> Option<ChessClock> {
+        let time = match &time_control {
+            TimeControl::Staged(stages) => stages.first()?.time,
+            TimeControl::Correspondence { days_per_move } => {
+                Duration::from_secs(*days_per_move as u64 * 24 * 60 * 60)
+            }
+            TimeControl::Unknown | TimeControl::Untimed => return None,
+        };
+        Some(ChessClock {
+            time_control,
+            remaining: [time; 2],
+            moves_made: [0; 2],
+            spent: [Duration::ZERO; 2],
+            running: None,
+            flagged: None,
+        })
+    }
+
+    /// Sets the player's time at the start of the game, such as less time for black in an
+    /// Armageddon game with [draw odds](crate::chess::ChessGame::set_draw_odds) for black. The
+    /// times of later stages of the time control are not affected.
+    ///
+    /// returns: The clock with the given initial time for the player.
+    pub fn with_initial_time(mut self, player: PlayerColor, time: Duration) -> ChessClock {
+        self.remaining[player_index(player)] = time;
+        self
+    }
+
+    /// returns: The time control of the clock.
+    pub fn time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+
+    /// returns: The player whose clock is running, or `None` if the clock is stopped.
+    pub fn running_player(&self) -> Option<PlayerColor> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// returns: The number of moves the player has completed with [on_move](ChessClock::on_move).
+    pub fn moves_made(&self, player: PlayerColor) -> u32 {
+        self.moves_made[player_index(player)]
+    }
+
+    /// returns: The index of the [stage](TimeControlStage) of the time control the player's next
+    ///          move is made in, or 0 for time controls without stages.
+    pub fn current_stage(&self, player: PlayerColor) -> usize {
+        match &self.time_control {
+            TimeControl::Staged(stages) => stage_index(stages, self.moves_made(player)),
+            _ => 0,
+        }
+    }
+
+    /// returns: The [DelayMode] of the stage the player's next move is made in, which is
+    ///          [None](DelayMode::None) for time controls without stages.
+    pub fn delay_mode(&self, player: PlayerColor) -> DelayMode {
+        match &self.time_control {
+            TimeControl::Staged(stages) => stages[self.current_stage(player)].delay,
+            _ => DelayMode::None,
+        }
+    }
+
+    /// returns: The time deducted from the player's main time for their current move when they
+    ///          have spent `after` on it, after `before` was deducted for.
+    fn charged_time(&self, player: PlayerColor, before: Duration, after: Duration) -> Duration {
+        match self.delay_mode(player) {
+            DelayMode::SimpleDelay(delay) => {
+                after.saturating_sub(delay) - before.saturating_sub(delay)
+            }
+            _ => after - before,
+        }
+    }
+
+    /// returns: The time the player has spent on their current move at the given time.
+    fn spent_time(&self, player: PlayerColor, now: Instant) -> Duration {
+        let spent = self.spent[player_index(player)];
+        match self.running {
+            Some((running, since)) if running == player => {
+                spent + now.saturating_duration_since(since)
+            }
+            _ => spent,
+        }
+    }
+
+    /// Starts the player's clock, stopping the other player's clock if it is running. Does
+    /// nothing once a player has run out of time.
+    pub fn start(&mut self, player: PlayerColor, now: Instant) {
+        self.stop(now);
+        if self.flagged.is_none() {
+            self.running = Some((player, now));
+        }
+    }
+
+    /// Stops the running clock, deducting the time since it was started. With a
+    /// [simple delay](DelayMode::SimpleDelay), the part of the delay not used yet is kept for
+    /// when the clock is started again during the same move.
+    pub fn stop(&mut self, now: Instant) {
+        if let Some((player, _)) = self.running {
+            let index = player_index(player);
+            let spent = self.spent_time(player, now);
+            let charged = self.charged_time(player, self.spent[index], spent);
+            self.running = None;
+            self.spent[index] = spent;
+            let remaining = &mut self.remaining[index];
+            *remaining = remaining.saturating_sub(charged);
+            if remaining.is_zero() {
+                self.flagged = Some(player);
+            }
+        }
+    }
+
+    /// Completes a move of the player whose clock is running, and starts the other player's clock.
+    ///
+    /// returns: [Flagged](ClockError::Flagged) if the player ran out of time before completing
+    ///          the move, in which case the clock stops, or [NotRunning](ClockError::NotRunning)
+    ///          if no clock is running.
+    pub fn on_move(&mut self, now: Instant) -> Result<(), ClockError> {
+        let (player, _) = self.running.ok_or(ClockError::NotRunning)?;
+        self.stop(now);
+        if self.flagged.is_some() {
+            return Err(ClockError::Flagged(player));
+        }
+        let index = player_index(player);
+        let moves_made = self.moves_made[index];
+        let spent = std::mem::take(&mut self.spent[index]);
+        match &self.time_control {
+            TimeControl::Staged(stages) => {
+                match stages[stage_index(stages, moves_made)].delay {
+                    DelayMode::Fischer(increment) => self.remaining[index] += increment,
+                    DelayMode::Bronstein(increment) => {
+                        self.remaining[index] += spent.min(increment);
+                    }
+                    DelayMode::None | DelayMode::SimpleDelay(_) => {}
+                }
+                if completes_stage(stages, moves_made + 1) {
+                    self.remaining[index] += stages[stage_index(stages, moves_made + 1)].time;
+                }
+            }
+            TimeControl::Correspondence { days_per_move } => {
+                self.remaining[index] = Duration::from_secs(*days_per_move as u64 * 24 * 60 * 60);
+            }
+            TimeControl::Unknown | TimeControl::Untimed => {}
+        }
+        self.moves_made[index] += 1;
+        self.running = Some((player.other_player(), now));
+        Ok(())
+    }
+
+    /// returns: The remaining time of the player at the given time, which is zero if the player
+    ///          has run out of time. While the player's clock is running with a
+    ///          [simple delay](DelayMode::SimpleDelay), this is the main time plus the
+    ///          [unused delay](ChessClock::delay_remaining), which is the time until the player
+    ///          runs out of time.
+    pub fn remaining_time(&self, player: PlayerColor, now: Instant) -> Duration {
+        let index = player_index(player);
+        let charged = self.charged_time(player, self.spent[index], self.spent_time(player, now));
+        let main_time = self.remaining[index].saturating_sub(charged);
+        match main_time.is_zero() {
+            true => Duration::ZERO,
+            false => main_time + self.delay_remaining(player, now),
+        }
+    }
+
+    /// returns: The part of the [simple delay](DelayMode::SimpleDelay) of the player's current
+    ///          move which has not been used at the given time, before time is deducted from the
+    ///          player's main time. Zero if the player's clock is not running, or if there is no
+    ///          simple delay.
+    pub fn delay_remaining(&self, player: PlayerColor, now: Instant) -> Duration {
+        match (self.running, self.delay_mode(player)) {
+            (Some((running, _)), DelayMode::SimpleDelay(delay)) if running == player => {
+                delay.saturating_sub(self.spent_time(player, now))
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// returns: `Some(PlayerColor)` of the player who has run out of time at the given time, or
+    ///          `None` if both players have time left.
+    pub fn flagged_player(&self, now: Instant) -> Option<PlayerColor> {
+        self.flagged.or_else(|| {
+            let (player, _) = self.running?;
+            self.remaining_time(player, now).is_zero().then_some(player)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    /// Plays `moves` moves for each player, where each move takes `seconds`.
+    fn play_moves(clock: &mut ChessClock, now: &mut Instant, moves: u32, seconds: u64) {
+        for _ in 0..moves * 2 {
+            *now += secs(seconds);
+            clock.on_move(*now).unwrap();
+        }
+    }
+
+    #[test]
+    fn increment() {
+        let mut clock = ChessClock::new(TimeControl::increment(secs(180), secs(2))).unwrap();
+        let mut now = Instant::now();
+        assert_eq!(clock.on_move(now), Err(ClockError::NotRunning));
+        clock.start(PlayerColor::White, now);
+        play_moves(&mut clock, &mut now, 5, 10);
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(140));
+        assert_eq!(clock.remaining_time(PlayerColor::Black, now), secs(140));
+        assert_eq!(clock.running_player(), Some(PlayerColor::White));
+        assert_eq!(clock.remaining_time(PlayerColor::White, now + secs(40)), secs(100));
+        assert_eq!(clock.moves_made(PlayerColor::Black), 5);
+    }
+
+    #[test]
+    fn stage_transition_at_move_40() {
+        let time_control = "40/7200:1800+30".parse().unwrap();
+        let mut clock = ChessClock::new(time_control).unwrap();
+        let mut now = Instant::now();
+        clock.start(PlayerColor::White, now);
+        play_moves(&mut clock, &mut now, 39, 100);
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(3300));
+        assert_eq!(clock.current_stage(PlayerColor::White), 0);
+
+        // the 40th move completes the first stage, without increment
+        now += secs(100);
+        clock.on_move(now).unwrap();
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(3200 + 1800));
+        assert_eq!(clock.current_stage(PlayerColor::White), 1);
+        assert_eq!(clock.current_stage(PlayerColor::Black), 0);
+        now += secs(100);
+        clock.on_move(now).unwrap();
+
+        // moves in the second stage gain the increment
+        play_moves(&mut clock, &mut now, 1, 100);
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(5000 - 100 + 30));
+    }
+
+    #[test]
+    fn repeated_stage() {
+        let mut clock = ChessClock::new("2/60".parse().unwrap()).unwrap();
+        let mut now = Instant::now();
+        clock.start(PlayerColor::White, now);
+        play_moves(&mut clock, &mut now, 4, 10);
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(60 + 60 + 60 - 40));
+        assert_eq!(clock.current_stage(PlayerColor::White), 0);
+    }
+
+    #[test]
+    fn correspondence() {
+        let time_control = TimeControl::Correspondence { days_per_move: 1 };
+        let mut clock = ChessClock::new(time_control).unwrap();
+        let mut now = Instant::now();
+        clock.start(PlayerColor::White, now);
+        play_moves(&mut clock, &mut now, 3, 3600);
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(86400));
+        assert_eq!(clock.remaining_time(PlayerColor::White, now + secs(3600)), secs(82800));
+        assert!(ChessClock::new(TimeControl::Untimed).is_none());
+        assert!(ChessClock::new(TimeControl::Staged(Vec::new())).is_none());
+    }
+
+    #[test]
+    fn asymmetric_initial_times() {
+        let clock = ChessClock::new(TimeControl::increment(secs(300), secs(2))).unwrap()
+            .with_initial_time(PlayerColor::Black, secs(240));
+        let now = Instant::now();
+        assert_eq!(clock.remaining_time(PlayerColor::White, now), secs(300));
+        assert_eq!(clock.remaining_time(PlayerColor::Black, now), secs(240));
+    }
+
+    /// Plays a move of white and a move of black, returning white's remaining time after the
+    /// move.
+    fn move_taking(clock: &mut ChessClock, now: &mut Instant, seconds: u64) -> Duration {
+        *now += secs(seconds);
+        clock.on_move(*now).unwrap();
+        let remaining = clock.remaining_time(PlayerColor::White, *now);
+        *now += secs(1);
+        clock.on_move(*now).unwrap();
+        remaining
+    }
+
+    #[test]
+    fn delay_modes() {
+        let modes = [
+            (DelayMode::None, [297, 289]),
+            (DelayMode::Fischer(secs(5)), [302, 299]),
+            (DelayMode::Bronstein(secs(5)), [300, 297]),
+            (DelayMode::SimpleDelay(secs(5)), [300, 297]),
+        ];
+        for (mode, expected) in modes {
+            let mut clock = ChessClock::new(TimeControl::with_delay(secs(300), mode)).unwrap();
+            assert_eq!(clock.delay_mode(PlayerColor::White), mode);
+            let mut now = Instant::now();
+            clock.start(PlayerColor::White, now);
+            // a move shorter than the delay, then a move longer than the delay
+            assert_eq!(move_taking(&mut clock, &mut now, 3), secs(expected[0]), "{:?}", mode);
+            assert_eq!(move_taking(&mut clock, &mut now, 8), secs(expected[1]), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn simple_delay_remaining_time() {
+        let time_control = TimeControl::with_delay(secs(10), DelayMode::SimpleDelay(secs(5)));
+        let mut clock = ChessClock::new(time_control).unwrap();
+        let start = Instant::now();
+        assert_eq!(clock.remaining_time(PlayerColor::White, start), secs(10));
+        clock.start(PlayerColor::White, start);
+        assert_eq!(clock.remaining_time(PlayerColor::White, start + secs(2)), secs(13));
+        assert_eq!(clock.delay_remaining(PlayerColor::White, start + secs(2)), secs(3));
+        assert_eq!(clock.delay_remaining(PlayerColor::Black, start + secs(2)), Duration::ZERO);
+
+        // the unused delay is kept while the clock is stopped during the move
+        clock.stop(start + secs(2));
+        assert_eq!(clock.remaining_time(PlayerColor::White, start + secs(2)), secs(10));
+        clock.start(PlayerColor::White, start + secs(60));
+        assert_eq!(clock.remaining_time(PlayerColor::White, start + secs(64)), secs(9));
+        assert_eq!(clock.delay_remaining(PlayerColor::White, start + secs(64)), Duration::ZERO);
+        assert_eq!(clock.flagged_player(start + secs(72)), None);
+        assert_eq!(clock.flagged_player(start + secs(73)), Some(PlayerColor::White));
+    }
+
+    #[test]
+    fn flagging() {
+        let mut clock = ChessClock::new(TimeControl::sudden_death(secs(60))).unwrap();
+        let start = Instant::now();
+        clock.start(PlayerColor::White, start);
+        clock.on_move(start + secs(20)).unwrap();
+        assert_eq!(clock.flagged_player(start + secs(79)), None);
+        assert_eq!(clock.flagged_player(start + secs(80)), Some(PlayerColor::Black));
+        assert_eq!(clock.on_move(start + secs(90)), Err(ClockError::Flagged(PlayerColor::Black)));
+        assert_eq!(clock.running_player(), None);
+        assert_eq!(clock.remaining_time(PlayerColor::Black, start + secs(100)), Duration::ZERO);
+        clock.start(PlayerColor::White, start + secs(100));
+        assert_eq!(clock.running_player(), None);
+    }
+}
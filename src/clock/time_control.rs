@@ -0,0 +1,329 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The number of seconds in a day, the unit of correspondence time controls.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How a player's clock is compensated for the time spent on each move of a
+/// [stage](TimeControlStage).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum DelayMode {
+    /// The time spent on a move is deducted in full.
+    #[default]
+    None,
+    /// The given increment is added to the player's clock after each of their moves.
+    Fischer(Duration),
+    /// The time spent on a move is given back after the move, up to the given increment, so a
+    /// player can never gain time.
+    Bronstein(Duration),
+    /// The clock only starts deducting time from the player's main time once the given delay
+    /// has passed on each move. Unused delay is lost.
+    SimpleDelay(Duration),
+}
+
+impl DelayMode {
+    /// returns: [Fischer](DelayMode::Fischer) with the given increment, or
+    ///          [None](DelayMode::None) if the increment is zero.
+    pub fn fischer(increment: Duration) -> DelayMode {
+        match increment.is_zero() {
+            true => DelayMode::None,
+            false => DelayMode::Fischer(increment),
+        }
+    }
+
+    /// returns: The increment or delay of the mode, which is the most time a player can be
+    ///          compensated for a move, or zero for [None](DelayMode::None).
+    pub fn duration(&self) -> Duration {
+        match self {
+            DelayMode::None => Duration::ZERO,
+            DelayMode::Fischer(duration)
+            | DelayMode::Bronstein(duration)
+            | DelayMode::SimpleDelay(duration) => *duration,
+        }
+    }
+}
+
+/// A period of a [staged](TimeControl::Staged) time control.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TimeControlStage {
+    /// The number of moves each player has to make in the stage, or `None` if the stage lasts
+    /// for the rest of the game.
+    pub moves: Option<u32>,
+    /// The time added to each player's clock at the start of the stage.
+    pub time: Duration,
+    /// The increment or delay applied to each of a player's moves in the stage.
+    pub delay: DelayMode,
+}
+
+impl TimeControlStage {
+    /// returns: A stage lasting for the rest of the game, with the given time and delay mode.
+    pub fn sudden_death(time: Duration, delay: DelayMode) -> TimeControlStage {
+        TimeControlStage { moves: None, time, delay }
+    }
+}
+
+impl Display for TimeControlStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(moves) = self.moves {
+            write!(f, "{}/", moves)?;
+        }
+        write!(f, "{}", self.time.as_secs())?;
+        if !self.delay.duration().is_zero() {
+            write!(f, "+{}", self.delay.duration().as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+/// The time control of a game.
+///
+/// Time controls are written in the format of the PGN `TimeControl` tag, which gives times in
+/// seconds, with the stages of the time control separated by colons. For example, `"300"` is
+/// five minutes sudden death, `"180+2"` is three minutes with a two second increment, and
+/// `"40/7200:1800+30"` is two hours for 40 moves, followed by 30 minutes for the rest of the game
+/// with a 30 second increment. Increments in this format are [Fischer](DelayMode::Fischer)
+/// increments.
+///
+/// The US notation for sudden death with a time in minutes is also accepted when parsing, such as
+/// `"G/5"`, optionally followed by a delay or an increment in seconds after a space or a `;`, such
+/// as `"G/30 d5"` for a [simple delay](DelayMode::SimpleDelay) of five seconds or `"G/90;inc30"`
+/// for a Fischer increment. PGN has no notation for delays, so a sudden death stage with a simple
+/// delay is written in the US notation if its time is in whole minutes, and other delays are
+/// written like increments, which gives the same most time a player can be compensated per move.
+///
+/// see: [PGN standard, section 9.6](https://www.thechessdrum.net/PGN_Reference.txt)
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use leben_chess::clock::{DelayMode, TimeControl};
+///
+/// let time_control: TimeControl = "40/7200:1800+30".parse().unwrap();
+/// let TimeControl::Staged(stages) = &time_control else { unreachable!() };
+/// assert_eq!(stages[0].moves, Some(40));
+/// assert_eq!(stages[1].delay, DelayMode::Fischer(Duration::from_secs(30)));
+/// assert_eq!(time_control.to_string(), "40/7200:1800+30");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TimeControl {
+    /// The time control is not known, written as `"?"`.
+    Unknown,
+    /// The game is played without a clock, written as `"-"`.
+    Untimed,
+    /// The game is played with the given stages, in order. If the last stage has a number of
+    /// moves, it is repeated for the rest of the game.
+    Staged(Vec<TimeControlStage>),
+    /// Each player has the given number of days for every move, and unused time is not carried
+    /// over to the next move. Written as one move per the number of seconds in the given number
+    /// of days, such as `"1/259200"` for three days per move.
+    Correspondence {
+        days_per_move: u32,
+    },
+}
+
+impl TimeControl {
+    /// returns: A time control where each player has the given time for the whole game.
+    pub fn sudden_death(time: Duration) -> TimeControl {
+        TimeControl::Staged(vec![TimeControlStage::sudden_death(time, DelayMode::None)])
+    }
+
+    /// returns: A time control where each player has the given time for the whole game, and
+    ///          gains `increment` after each move.
+    pub fn increment(time: Duration, increment: Duration) -> TimeControl {
+        TimeControl::with_delay(time, DelayMode::fischer(increment))
+    }
+
+    /// returns: A time control where each player has the given time for the whole game, with the
+    ///          given [delay mode](DelayMode) for every move.
+    pub fn with_delay(time: Duration, delay: DelayMode) -> TimeControl {
+        TimeControl::Staged(vec![TimeControlStage::sudden_death(time, delay)])
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControl::Unknown => write!(f, "?"),
+            TimeControl::Untimed => write!(f, "-"),
+            TimeControl::Staged(stages) => {
+                if let [TimeControlStage {
+                    moves: None,
+                    time,
+                    delay: DelayMode::SimpleDelay(delay),
+                }] = stages.as_slice()
+                    && time.as_secs().is_multiple_of(60)
+                {
+                    return write!(f, "G/{} d{}", time.as_secs() / 60, delay.as_secs());
+                }
+                for (i, stage) in stages.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ":")?;
+                    }
+                    write!(f, "{}", stage)?;
+                }
+                Ok(())
+            }
+            TimeControl::Correspondence { days_per_move } => {
+                write!(f, "1/{}", *days_per_move as u64 * SECONDS_PER_DAY)
+            }
+        }
+    }
+}
+
+/// An error caused by attempting to parse an invalid string as a [TimeControl].
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum TimeControlParseError {
+    /// A stage of the time control is not valid, with the stage.
+    #[error("invalid time control stage `{0}`")]
+    InvalidStage(String),
+    /// The time control uses a sandclock (hourglass), which is not supported.
+    #[error("sandclock time controls are not supported")]
+    Sandclock,
+}
+
+impl FromStr for TimeControl {
+    type Err = TimeControlParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "?" => return Ok(TimeControl::Unknown),
+            "-" => return Ok(TimeControl::Untimed),
+            _ => {}
+        }
+        if let Some(rest) = s.strip_prefix("G/") {
+            let (minutes, delay) = match rest.split_once([' ', ';']) {
+                Some((minutes, delay)) => (minutes, parse_us_delay(delay.trim_start(), s)?),
+                None => (rest, DelayMode::None),
+            };
+            let minutes: u64 = parse_number(minutes, s)?;
+            let seconds = minutes.checked_mul(60)
+                .ok_or_else(|| TimeControlParseError::InvalidStage(s.to_string()))?;
+            return Ok(TimeControl::with_delay(Duration::from_secs(seconds), delay));
+        }
+        let stages = s.split(':')
+            .map(parse_stage)
+            .collect::<Result<Vec<TimeControlStage>, TimeControlParseError>>()?;
+        if let [TimeControlStage { moves: Some(1), time, delay: DelayMode::None }]
+            = stages.as_slice()
+            && time.as_secs() > 0
+            && time.as_secs() % SECONDS_PER_DAY == 0
+            && let Ok(days_per_move) = u32::try_from(time.as_secs() / SECONDS_PER_DAY)
+        {
+            return Ok(TimeControl::Correspondence { days_per_move });
+        }
+        Ok(TimeControl::Staged(stages))
+    }
+}
+
+fn parse_number<T: FromStr>(number: &str, stage: &str) -> Result<T, TimeControlParseError> {
+    // reject signs, which FromStr for integers accepts
+    if !number.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(TimeControlParseError::InvalidStage(stage.to_string()));
+    }
+    number.parse().map_err(|_| TimeControlParseError::InvalidStage(stage.to_string()))
+}
+
+/// returns: The delay mode of a US notation delay `d<seconds>` or increment `inc<seconds>`.
+fn parse_us_delay(delay: &str, time_control: &str) -> Result<DelayMode, TimeControlParseError> {
+    if let Some(seconds) = delay.strip_prefix("inc") {
+        return Ok(DelayMode::fischer(Duration::from_secs(parse_number(seconds, time_control)?)));
+    }
+    match delay.strip_prefix('d') {
+        Some(seconds) => {
+            Ok(DelayMode::SimpleDelay(Duration::from_secs(parse_number(seconds, time_control)?)))
+        }
+        None => Err(TimeControlParseError::InvalidStage(time_control.to_string())),
+    }
+}
+
+/// returns: A stage in the format `[moves/]seconds[+increment]`.
+fn parse_stage(stage: &str) -> Result<TimeControlStage, TimeControlParseError> {
+    if stage.starts_with('*') {
+        return Err(TimeControlParseError::Sandclock);
+    }
+    let (moves, rest) = match stage.split_once('/') {
+        Some((moves, rest)) => (Some(parse_number(moves, stage)?), rest),
+        None => (None, stage),
+    };
+    let (time, increment) = match rest.split_once('+') {
+        Some((time, increment)) => (time, parse_number(increment, stage)?),
+        None => (rest, 0),
+    };
+    if moves == Some(0) {
+        return Err(TimeControlParseError::InvalidStage(stage.to_string()));
+    }
+    Ok(TimeControlStage {
+        moves,
+        time: Duration::from_secs(parse_number(time, stage)?),
+        delay: DelayMode::fischer(Duration::from_secs(increment)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn parse_each_form() {
+        assert_eq!("300".parse(), Ok(TimeControl::sudden_death(secs(300))));
+        assert_eq!("G/5".parse(), Ok(TimeControl::sudden_death(secs(300))));
+        assert_eq!("180+2".parse(), Ok(TimeControl::increment(secs(180), secs(2))));
+        assert_eq!("40/7200:1800+30".parse(), Ok(TimeControl::Staged(vec![
+            TimeControlStage { moves: Some(40), time: secs(7200), delay: DelayMode::None },
+            TimeControlStage::sudden_death(secs(1800), DelayMode::Fischer(secs(30))),
+        ])));
+        assert_eq!("G/30 d5".parse(),
+                   Ok(TimeControl::with_delay(secs(1800), DelayMode::SimpleDelay(secs(5)))));
+        assert_eq!("G/90;d10".parse(),
+                   Ok(TimeControl::with_delay(secs(5400), DelayMode::SimpleDelay(secs(10)))));
+        assert_eq!("G/90;inc30".parse(), Ok(TimeControl::increment(secs(5400), secs(30))));
+        assert_eq!("300+0".parse(), Ok(TimeControl::sudden_death(secs(300))));
+        assert_eq!("1/259200".parse(), Ok(TimeControl::Correspondence { days_per_move: 3 }));
+        assert_eq!("?".parse(), Ok(TimeControl::Unknown));
+        assert_eq!("-".parse(), Ok(TimeControl::Untimed));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!("*180".parse::<TimeControl>(), Err(TimeControlParseError::Sandclock));
+        for invalid in ["", "5m", "40/", "/300", "0/300", "300+", "+2", "-300", "G/x", "300:",
+                        "G/30 x5", "G/30 d", "G/30 d-5"] {
+            assert!(invalid.parse::<TimeControl>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parse_huge_minutes() {
+        let max_minutes = u64::MAX / 60;
+        assert_eq!(format!("G/{}", max_minutes).parse(),
+                   Ok(TimeControl::sudden_death(secs(max_minutes * 60))));
+        for minutes in [max_minutes + 1, u64::MAX] {
+            let time_control = format!("G/{}", minutes);
+            assert_eq!(time_control.parse::<TimeControl>(),
+                       Err(TimeControlParseError::InvalidStage(time_control.clone())));
+        }
+    }
+
+    #[test]
+    fn format_back_to_spec_strings() {
+        for spec in ["?", "-", "300", "180+2", "40/7200:1800+30", "40/9000", "1/86400",
+                     "40/5400+30:1800+30"] {
+            assert_eq!(spec.parse::<TimeControl>().unwrap().to_string(), spec);
+        }
+        assert_eq!("G/5".parse::<TimeControl>().unwrap().to_string(), "300");
+        assert_eq!("G/30 d5".parse::<TimeControl>().unwrap().to_string(), "G/30 d5");
+        assert_eq!(TimeControl::with_delay(secs(90), DelayMode::SimpleDelay(secs(5))).to_string(),
+                   "90+5");
+        assert_eq!(TimeControl::with_delay(secs(300), DelayMode::Bronstein(secs(3))).to_string(),
+                   "300+3");
+        assert_eq!(TimeControl::Correspondence { days_per_move: 2 }.to_string(), "1/172800");
+    }
+}
@@ -0,0 +1,438 @@
+//! Static evaluation of chess positions.
+//!
+//! A position is evaluated in centipawns from white's perspective, so positive scores favor white
+//! and negative scores favor black. The evaluation consists of:
+//! - material, counted with [PieceValues],
+//! - piece-square tables, interpolated between a middlegame and an endgame table according to
+//!   the [game phase](game_phase),
+//! - penalties for doubled and isolated pawns,
+//! - a middlegame bonus for pawns shielding the king.
+//!
+//! The tables and weights are public constants. The piece-square tables are laid out as seen from
+//! white's side of the board, with the first row being rank 8 and the last row rank 1, and are
+//! mirrored vertically for black pieces.
+//!
+//! The [search](crate::search) uses this evaluation through the [Evaluator] trait, as
+//! [StandardEvaluator], and can be given another [Evaluator] instead, such as
+//! [MaterialEvaluator]. Both evaluators, as well as [ChessGame::evaluate] and the
+//! [static exchange evaluation](crate::search::static_exchange), count material with the
+//! [PieceValues] set on the game with [set_piece_values](ChessGame::set_piece_values).
+//!
+//! see: [Simplified Evaluation Function - Chessprogramming wiki](https://www.chessprogramming.org/Simplified_Evaluation_Function)
+
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::chess::ChessGame;
+
+/// The number of centipawns per unit of [PieceType::piece_value].
+pub const CENTIPAWNS_PER_PAWN: i32 = 100;
+
+/// The values of the pieces in centipawns, used to count material in the [evaluation](self) and
+/// in [static exchanges](crate::search::static_exchange). The [default](PieceValues::default) is
+/// the classic 1-3-3-5-9 scale of [PieceType::piece_value] without a bishop pair bonus. The king
+/// has no value, since it can never be captured.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PieceValues {
+    pub pawn: i32,
+    pub knight: i32,
+    pub bishop: i32,
+    pub rook: i32,
+    pub queen: i32,
+    /// The bonus for a player having at least two bishops.
+    pub bishop_pair: i32,
+}
+
+impl Default for PieceValues {
+    fn default() -> Self {
+        PieceValues {
+            pawn: CENTIPAWNS_PER_PAWN,
+            knight: 3 * CENTIPAWNS_PER_PAWN,
+            bishop: 3 * CENTIPAWNS_PER_PAWN,
+            rook: 5 * CENTIPAWNS_PER_PAWN,
+            queen: 9 * CENTIPAWNS_PER_PAWN,
+            bishop_pair: 0,
+        }
+    }
+}
+
+impl PieceValues {
+    /// returns: The value of a piece type in centipawns, or `None` for the king.
+    pub fn value(&self, piece_type: PieceType) -> Option<i32> {
+        match piece_type {
+            PieceType::Pawn => Some(self.pawn),
+            PieceType::Knight => Some(self.knight),
+            PieceType::Bishop => Some(self.bishop),
+            PieceType::Rook => Some(self.rook),
+            PieceType::Queen => Some(self.queen),
+            PieceType::King => None,
+        }
+    }
+
+    /// Counts the material of a player on a board, including the bishop pair bonus.
+    ///
+    /// returns: The total value of the player's pieces in centipawns.
+    pub fn material(&self, board: &Board, player: PlayerColor) -> i32 {
+        let mut material = 0;
+        let mut bishops = 0;
+        for (_, piece) in board.pieces_of(player) {
+            if piece.piece_type == PieceType::Bishop {
+                bishops += 1;
+            }
+            material += self.value(piece.piece_type).unwrap_or(0);
+        }
+        if bishops >= 2 {
+            material += self.bishop_pair;
+        }
+        material
+    }
+
+    /// returns: The difference in [material](PieceValues::material) between the players in
+    ///          centipawns, from the perspective of `player`.
+    pub fn material_balance(&self, board: &Board, player: PlayerColor) -> i32 {
+        self.material(board, player) - self.material(board, player.other_player())
+    }
+}
+
+/// The score of a position where the player to move has checkmated the other player. Scores of
+/// checkmates found by the [search](crate::search) are reduced by one for each ply until the
+/// checkmate.
+pub const MATE_SCORE: i32 = 1_000_000;
+
+/// The highest number of plies by which a checkmate score may be reduced. Scores with an absolute
+/// value above `MATE_SCORE - MAX_MATE_PLY` are checkmate scores.
+pub const MAX_MATE_PLY: i32 = 1000;
+
+/// returns: Whether a score returned by the [search](crate::search) is a checkmate score.
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() > MATE_SCORE - MAX_MATE_PLY
+}
+
+#[rustfmt::skip]
+pub const PAWN_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+pub const PAWN_ENDGAME_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    20,  20,  20,  20,  20,  20,  20,  20,
+    10,  10,  10,  10,  10,  10,  10,  10,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+pub const KNIGHT_TABLE: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+pub const BISHOP_TABLE: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+pub const ROOK_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+pub const QUEEN_TABLE: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+pub const KING_TABLE: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+pub const KING_ENDGAME_TABLE: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// The middlegame piece-square tables, indexed by [PieceType] in the order pawn, knight, bishop,
+/// rook, queen, king.
+pub const MIDDLEGAME_TABLES: [[i32; 64]; 6] = [
+    PAWN_TABLE, KNIGHT_TABLE, BISHOP_TABLE, ROOK_TABLE, QUEEN_TABLE, KING_TABLE,
+];
+
+/// The endgame piece-square tables, indexed like [MIDDLEGAME_TABLES].
+pub const ENDGAME_TABLES: [[i32; 64]; 6] = [
+    PAWN_ENDGAME_TABLE, KNIGHT_TABLE, BISHOP_TABLE, ROOK_TABLE, QUEEN_TABLE, KING_ENDGAME_TABLE,
+];
+
+/// The contribution of each piece type to the [game phase](game_phase), indexed like
+/// [MIDDLEGAME_TABLES].
+pub const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+/// The game phase of the starting position, which is the highest phase considered.
+pub const MAX_PHASE: i32 = 24;
+
+/// The penalty for each pawn on a file beyond the first pawn of the same color.
+pub const DOUBLED_PAWN_PENALTY: i32 = 10;
+
+/// The penalty for each pawn with no pawns of the same color on the adjacent files.
+pub const ISOLATED_PAWN_PENALTY: i32 = 15;
+
+/// The middlegame bonus for each pawn on the three squares directly in front of the king of the
+/// same color.
+pub const KING_SHIELD_BONUS: i32 = 10;
+
+/// Computes the game phase of a board from the remaining pieces, weighted by [PHASE_WEIGHTS]. The
+/// phase ranges from [MAX_PHASE] in the starting position (pure middlegame) to 0 when only kings
+/// and pawns remain (pure endgame).
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = board.pieces()
+        .map(|(_, piece)| PHASE_WEIGHTS[piece.piece_type as usize])
+        .sum();
+    phase.min(MAX_PHASE)
+}
+
+/// returns: The index into a piece-square table of a piece of the given player on a square.
+fn table_index(pos: BoardPosition, player: PlayerColor) -> usize {
+    let row = match player {
+        PlayerColor::White => 7 - pos.rank.get(),
+        PlayerColor::Black => pos.rank.get(),
+    };
+    8 * row as usize + pos.file.get() as usize
+}
+
+/// returns: The sign of a player's contribution to an evaluation from white's perspective.
+fn sign(player: PlayerColor) -> i32 {
+    match player {
+        PlayerColor::White => 1,
+        PlayerColor::Black => -1,
+    }
+}
+
+/// returns: The pawn structure penalties and the king shield bonus, from white's perspective.
+fn pawn_terms(board: &Board) -> (i32, i32) {
+    let mut structure = 0;
+    let mut king_shield = 0;
+    for player in [PlayerColor::White, PlayerColor::Black] {
+        let pawn = Some(Piece::new(PieceType::Pawn, player));
+        let mut pawns_per_file = [0; 8];
+        for (pos, piece) in board {
+            if piece == pawn {
+                pawns_per_file[pos.file.get() as usize] += 1;
+            }
+        }
+        for file in 0..8 {
+            let pawns = pawns_per_file[file];
+            if pawns > 1 {
+                structure -= sign(player) * (pawns - 1) * DOUBLED_PAWN_PENALTY;
+            }
+            let has_neighbors = (file > 0 && pawns_per_file[file - 1] > 0)
+                || (file < 7 && pawns_per_file[file + 1] > 0);
+            if !has_neighbors {
+                structure -= sign(player) * pawns * ISOLATED_PAWN_PENALTY;
+            }
+        }
+
+        if let Some(king_pos) = board.find_one(PieceType::King, player) {
+            let shield_pawns = [-1, 0, 1].into_iter()
+                .filter_map(|file_offset| king_pos.add((file_offset, player.pawn_direction())))
+                .filter(|&pos| board.get_piece(pos) == pawn)
+                .count() as i32;
+            king_shield += sign(player) * shield_pawns * KING_SHIELD_BONUS;
+        }
+    }
+    (structure, king_shield)
+}
+
+/// Statically evaluates a board with the default [PieceValues], without considering whose turn
+/// it is or whether any player is in check. See [the module documentation](self).
+///
+/// returns: The evaluation in centipawns from white's perspective.
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_with_values(board, &PieceValues::default())
+}
+
+/// Statically evaluates a board like [evaluate], counting material with the given piece values.
+///
+/// # Arguments
+///
+/// * `board`: The board to evaluate.
+/// * `values`: The values of the pieces.
+///
+/// returns: The evaluation in centipawns from white's perspective.
+pub fn evaluate_with_values(board: &Board, values: &PieceValues) -> i32 {
+    let material = values.material_balance(board, PlayerColor::White);
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for (pos, piece) in board {
+        let Some(piece) = piece else {
+            continue;
+        };
+        let sign = sign(piece.player);
+        let index = table_index(pos, piece.player);
+        middlegame += sign * MIDDLEGAME_TABLES[piece.piece_type as usize][index];
+        endgame += sign * ENDGAME_TABLES[piece.piece_type as usize][index];
+    }
+    let (structure, king_shield) = pawn_terms(board);
+    middlegame += king_shield;
+    let phase = game_phase(board);
+    let tapered = (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+    material + tapered + structure
+}
+
+/// A static evaluation of positions, such as the one used at the leaves of the
+/// [search](crate::search). Implement this trait to search with a custom evaluation, see
+/// [search_with](crate::search::search_with).
+pub trait Evaluator {
+    /// Statically evaluates the current position of a game, which is not over.
+    ///
+    /// returns: The evaluation in centipawns from white's perspective.
+    fn evaluate(&self, game: &ChessGame) -> i32;
+}
+
+/// The [Evaluator] using [evaluate], with material, piece-square tables and pawn structure, and
+/// the [piece values](ChessGame::piece_values) of the game. This is the evaluation the search
+/// uses by default.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StandardEvaluator;
+
+impl Evaluator for StandardEvaluator {
+    fn evaluate(&self, game: &ChessGame) -> i32 {
+        evaluate_with_values(game.board(), game.piece_values())
+    }
+}
+
+/// An [Evaluator] counting only material, with the [piece values](ChessGame::piece_values) of
+/// the game.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MaterialEvaluator;
+
+impl Evaluator for MaterialEvaluator {
+    fn evaluate(&self, game: &ChessGame) -> i32 {
+        game.piece_values().material_balance(game.board(), PlayerColor::White)
+    }
+}
+
+impl ChessGame {
+    /// Statically evaluates the current board with the [piece values](ChessGame::piece_values)
+    /// of the game. See [evaluate].
+    ///
+    /// returns: The evaluation in centipawns from white's perspective.
+    pub fn evaluate(&self) -> i32 {
+        evaluate_with_values(self.board(), self.piece_values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetric_positions() {
+        assert_eq!(evaluate(&Board::default_board()), 0);
+        let board = Board::from_fen_string("r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R")
+            .unwrap();
+        assert_eq!(evaluate(&board), 0);
+        assert_eq!(game_phase(&Board::default_board()), MAX_PHASE);
+        assert_eq!(game_phase(&Board::from_fen_string("4k3/pppp4/8/8/8/8/8/4K3").unwrap()), 0);
+    }
+
+    #[test]
+    fn material_advantage() {
+        let board = Board::from_fen_string("1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap();
+        assert!((450..=550).contains(&evaluate(&board)), "{}", evaluate(&board));
+        let board = Board::from_fen_string("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR").unwrap();
+        assert!((-950..=-850).contains(&evaluate(&board)), "{}", evaluate(&board));
+    }
+
+    #[test]
+    fn mirrored_positions() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR",
+            "r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR",
+            "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8",
+            "6k1/5ppp/8/8/8/2Q5/5PPP/6K1",
+        ];
+        for fen in fens {
+            let board = Board::from_fen_string(fen).unwrap();
+            assert_eq!(evaluate(&board.swap_colors()), -evaluate(&board), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn custom_piece_values() {
+        let board = Board::from_fen_string("4k3/pppp4/8/8/8/8/8/2B1KB2").unwrap();
+        let values = PieceValues::default();
+        assert_eq!(values.material_balance(&board, PlayerColor::White), 200);
+        assert_eq!(evaluate_with_values(&board, &values), evaluate(&board));
+        let values = PieceValues { bishop_pair: 50, pawn: 50, ..PieceValues::default() };
+        assert_eq!(values.material(&board, PlayerColor::White), 650);
+        assert_eq!(values.material_balance(&board, PlayerColor::Black), -450);
+        assert_eq!(evaluate_with_values(&board, &values), evaluate(&board) + 250);
+
+        let mut game = ChessGame::from_fen("4k3/pppp4/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        assert_eq!(MaterialEvaluator.evaluate(&game), 200);
+        game.set_piece_values(values);
+        assert_eq!(MaterialEvaluator.evaluate(&game), 450);
+        assert_eq!(StandardEvaluator.evaluate(&game), game.evaluate());
+        assert_eq!(game.evaluate(), evaluate(&board) + 250);
+    }
+
+    #[test]
+    fn pawn_structure() {
+        // doubled isolated pawns are worse than connected pawns
+        let doubled = Board::from_fen_string("4k3/8/8/8/8/3P4/3P4/4K3").unwrap();
+        let connected = Board::from_fen_string("4k3/8/8/8/8/3P4/4P3/4K3").unwrap();
+        assert!(evaluate(&doubled) < evaluate(&connected));
+    }
+}
@@ -0,0 +1,7 @@
+//! Conversions between the types of this library and the types of other chess libraries, so that
+//! the game management of this library can be combined with their move generation and tools.
+//! Each library is supported behind a feature of the same name:
+//! - [shakmaty](https://crates.io/crates/shakmaty) (enable the `shakmaty` feature)
+
+#[cfg(feature = "shakmaty")]
+pub mod shakmaty;
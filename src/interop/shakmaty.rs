@@ -0,0 +1,350 @@
+//! Conversions between the types of this library and the types of the
+//! [shakmaty](https://docs.rs/shakmaty) crate (enable the `shakmaty` feature).
+//!
+//! Squares, colors, pieces and boards convert in both directions with [From]. Moves convert
+//! through shakmaty's [UciMove], since a shakmaty [Move] needs the position it is played in:
+//! convert a [ChessMove] into a [UciMove] and use [UciMove::to_move] with the position. Whole
+//! positions, including the player to move, castling rights and the en passant square, convert
+//! between a [ChessGame] and shakmaty's [Setup] and [Chess]. Only standard chess positions are
+//! supported, and castling rights are always in standard (not Chess960) mode.
+
+use ::shakmaty::uci::UciMove;
+use ::shakmaty::{CastlingMode, Chess, Color, EnPassantMode, File, FromSetup, Move, Position,
+                 Rank, Role, Setup, Square};
+use core::num::NonZeroU32;
+use thiserror::Error;
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::board::{Board, PositionError};
+use crate::chess::ChessGame;
+use crate::chess::variant::Variant;
+use crate::moves::{CastlingRights, ChessMove, PromotionType};
+use crate::util::U3;
+
+/// An error converting between a type of this library and a type of shakmaty.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum ShakmatyError {
+    /// The move is a null move or a drop of a king, which have no equivalent [ChessMove].
+    #[error("null moves and king drops have no equivalent move")]
+    UnsupportedMove,
+    /// The game is not played with the standard rules.
+    #[error("only standard chess positions can be converted")]
+    UnsupportedVariant,
+    /// A castling right refers to a rook which is not in a corner of the board.
+    #[error("castling rights with a rook which is not in a corner")]
+    UnsupportedCastlingRights,
+    /// The position is not valid in this library.
+    #[error(transparent)]
+    InvalidPosition(#[from] PositionError),
+    /// The position is not valid in shakmaty, with shakmaty's description of the problem.
+    #[error("{0}")]
+    Rejected(String),
+}
+
+impl From<PlayerColor> for Color {
+    fn from(value: PlayerColor) -> Self {
+        match value {
+            PlayerColor::White => Color::White,
+            PlayerColor::Black => Color::Black,
+        }
+    }
+}
+
+impl From<Color> for PlayerColor {
+    fn from(value: Color) -> Self {
+        match value {
+            Color::White => PlayerColor::White,
+            Color::Black => PlayerColor::Black,
+        }
+    }
+}
+
+impl From<PieceType> for Role {
+    fn from(value: PieceType) -> Self {
+        match value {
+            PieceType::Pawn => Role::Pawn,
+            PieceType::Knight => Role::Knight,
+            PieceType::Bishop => Role::Bishop,
+            PieceType::Rook => Role::Rook,
+            PieceType::Queen => Role::Queen,
+            PieceType::King => Role::King,
+        }
+    }
+}
+
+impl From<Role> for PieceType {
+    fn from(value: Role) -> Self {
+        match value {
+            Role::Pawn => PieceType::Pawn,
+            Role::Knight => PieceType::Knight,
+            Role::Bishop => PieceType::Bishop,
+            Role::Rook => PieceType::Rook,
+            Role::Queen => PieceType::Queen,
+            Role::King => PieceType::King,
+        }
+    }
+}
+
+impl From<Piece> for ::shakmaty::Piece {
+    fn from(value: Piece) -> Self {
+        ::shakmaty::Piece { color: value.player.into(), role: value.piece_type.into() }
+    }
+}
+
+impl From<::shakmaty::Piece> for Piece {
+    fn from(value: ::shakmaty::Piece) -> Self {
+        Piece::new(value.role.into(), value.color.into())
+    }
+}
+
+impl From<BoardPosition> for Square {
+    fn from(value: BoardPosition) -> Self {
+        Square::from_coords(File::new(value.file.get() as u32), Rank::new(value.rank.get() as u32))
+    }
+}
+
+impl From<Square> for BoardPosition {
+    fn from(value: Square) -> Self {
+        // files and ranks of squares are always in the range 0 to 7
+        BoardPosition {
+            file: U3::from_low_bits(value.file().to_u32() as u8),
+            rank: U3::from_low_bits(value.rank().to_u32() as u8),
+        }
+    }
+}
+
+impl From<&Board> for ::shakmaty::Board {
+    fn from(value: &Board) -> Self {
+        let mut board = ::shakmaty::Board::empty();
+        for (pos, piece) in value {
+            if let Some(piece) = piece {
+                board.set_piece_at(pos.into(), piece.into());
+            }
+        }
+        board
+    }
+}
+
+impl From<&::shakmaty::Board> for Board {
+    fn from(value: &::shakmaty::Board) -> Self {
+        let mut board = Board::empty_board();
+        for pos in BoardPosition::all() {
+            board.set_piece(pos, value.piece_at(pos.into()).map(Piece::from));
+        }
+        board
+    }
+}
+
+/// Converts a move into the UCI notation of shakmaty, where castling is a move of the king by two
+/// squares and a drop is a [Put](UciMove::Put).
+impl From<ChessMove> for UciMove {
+    fn from(value: ChessMove) -> Self {
+        if let Some(piece_type) = value.drop {
+            return UciMove::Put { role: piece_type.into(), to: value.piece_movement.to.into() };
+        }
+        UciMove::Normal {
+            from: value.piece_movement.from.into(),
+            to: value.piece_movement.to.into(),
+            promotion: value.promotion.map(|promotion| PieceType::from(promotion).into()),
+        }
+    }
+}
+
+impl TryFrom<UciMove> for ChessMove {
+    type Error = ShakmatyError;
+    fn try_from(value: UciMove) -> Result<Self, Self::Error> {
+        let (from, to, promotion) = match value {
+            UciMove::Normal { from, to, promotion } => (from, to, promotion),
+            UciMove::Put { role: Role::King, .. } | UciMove::Null => {
+                return Err(ShakmatyError::UnsupportedMove);
+            }
+            UciMove::Put { role, to } => return Ok(ChessMove::dropping(role.into(), to.into())),
+        };
+        let promotion = match promotion {
+            Some(role) => Some(PromotionType::try_from(PieceType::from(role))
+                .map_err(|_| ShakmatyError::UnsupportedMove)?),
+            None => None,
+        };
+        Ok(ChessMove { promotion, ..ChessMove::new(from.into(), to.into()) })
+    }
+}
+
+/// Converts a legal shakmaty move, where castling is a move of the king by two squares.
+impl TryFrom<Move> for ChessMove {
+    type Error = ShakmatyError;
+    fn try_from(value: Move) -> Result<Self, Self::Error> {
+        UciMove::from_standard(value).try_into()
+    }
+}
+
+impl TryFrom<&ChessGame> for Setup {
+    type Error = ShakmatyError;
+    fn try_from(value: &ChessGame) -> Result<Self, Self::Error> {
+        if value.variant() != Variant::Standard {
+            return Err(ShakmatyError::UnsupportedVariant);
+        }
+        let mut setup = Setup::empty();
+        setup.board = value.board().into();
+        setup.turn = value.active_player().into();
+        for player in [PlayerColor::White, PlayerColor::Black] {
+            let rights = value.castling_rights(player);
+            let rank = player.back_rank();
+            for (file, has_right) in [(0, rights.queenside), (7, rights.kingside)] {
+                if has_right {
+                    let rook = BoardPosition { file: U3::from_low_bits(file), rank };
+                    setup.castling_rights = setup.castling_rights.with(Square::from(rook));
+                }
+            }
+        }
+        setup.ep_square = value.en_passant_target().map(Square::from);
+        setup.halfmoves = value.halfmove_clock() as u32;
+        setup.fullmoves = u32::try_from(value.fullmove_number()).ok()
+            .and_then(NonZeroU32::new)
+            .unwrap_or(setup.fullmoves);
+        Ok(setup)
+    }
+}
+
+impl TryFrom<&ChessGame> for Chess {
+    type Error = ShakmatyError;
+    fn try_from(value: &ChessGame) -> Result<Self, Self::Error> {
+        Chess::from_setup(Setup::try_from(value)?, CastlingMode::Standard)
+            .map_err(|error| ShakmatyError::Rejected(error.to_string()))
+    }
+}
+
+/// Converts a position into a new game starting from it, validated like in
+/// [set_position](ChessGame::set_position), keeping the halfmove clock and fullmove number.
+impl TryFrom<&Setup> for ChessGame {
+    type Error = ShakmatyError;
+    fn try_from(value: &Setup) -> Result<Self, Self::Error> {
+        let mut castling_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        for rook in value.castling_rights {
+            let rook = BoardPosition::from(rook);
+            let rights = match rook.rank.get() {
+                0 => &mut castling_rights.0,
+                7 => &mut castling_rights.1,
+                _ => return Err(ShakmatyError::UnsupportedCastlingRights),
+            };
+            match rook.file.get() {
+                0 => rights.queenside = true,
+                7 => rights.kingside = true,
+                _ => return Err(ShakmatyError::UnsupportedCastlingRights),
+            }
+        }
+        let mut game = ChessGame::new(Board::default_board());
+        game.set_position((&value.board).into(), value.turn.into(), castling_rights,
+                          value.ep_square.map(BoardPosition::from))?;
+        game.set_starting_counters(value.halfmoves as usize, value.fullmoves.get() as usize);
+        Ok(game)
+    }
+}
+
+impl TryFrom<&Chess> for ChessGame {
+    type Error = ShakmatyError;
+    fn try_from(value: &Chess) -> Result<Self, Self::Error> {
+        ChessGame::try_from(&value.to_setup(EnPassantMode::Always))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use ::shakmaty::Bitboard;
+    use super::*;
+
+    // `UciMove::from` is an inherent method of shakmaty returning the origin square
+    fn uci(chess_move: ChessMove) -> UciMove {
+        chess_move.into()
+    }
+
+    const POSITIONS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ];
+
+    fn our_moves(game: &ChessGame) -> BTreeSet<String> {
+        game.legal_moves().into_iter()
+            .map(|chess_move| uci(chess_move).to_string())
+            .collect()
+    }
+
+    fn shakmaty_moves(position: &Chess) -> BTreeSet<String> {
+        position.legal_moves().into_iter()
+            .map(|m| UciMove::from_standard(m).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn simple_conversions() {
+        for pos in BoardPosition::all() {
+            assert_eq!(BoardPosition::from(Square::from(pos)), pos);
+            assert_eq!(Square::from(pos).to_string(), pos.to_string());
+        }
+        for player in [PlayerColor::White, PlayerColor::Black] {
+            assert_eq!(PlayerColor::from(Color::from(player)), player);
+        }
+        for ch in "PNBRQKpnbrqk".chars() {
+            let piece = Piece::try_from(ch).unwrap();
+            assert_eq!(::shakmaty::Piece::from(piece).char(), ch);
+            assert_eq!(Piece::from(::shakmaty::Piece::from(piece)), piece);
+        }
+        let board = Board::default_board();
+        assert_eq!(::shakmaty::Board::from(&board), ::shakmaty::Board::new());
+        assert_eq!(Board::from(&::shakmaty::Board::new()), board);
+
+        let promotion = ChessMove::promoting("b7".parse().unwrap(), "a8".parse().unwrap(),
+                                             PromotionType::Knight);
+        assert_eq!(uci(promotion).to_string(), "b7a8n");
+        assert_eq!(ChessMove::try_from(uci(promotion)), Ok(promotion));
+        let drop: UciMove = "Q@f7".parse().unwrap();
+        assert_eq!(ChessMove::try_from(drop), Ok(ChessMove::dropping(PieceType::Queen,
+                                                                     "f7".parse().unwrap())));
+        assert_eq!(uci(ChessMove::try_from(drop).unwrap()), drop);
+        assert_eq!(ChessMove::try_from(UciMove::Null), Err(ShakmatyError::UnsupportedMove));
+        let castle = Move::Castle { king: Square::E1, rook: Square::H1 };
+        assert_eq!(ChessMove::try_from(castle), Ok(ChessMove::from_coords("e1", "g1").unwrap()));
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        for fen in POSITIONS {
+            let game = ChessGame::from_fen(fen).unwrap();
+            let position = Chess::try_from(&game).unwrap();
+            let shakmaty_fen = ::shakmaty::fen::Fen::from_position(&position,
+                                                                    EnPassantMode::Always);
+            assert_eq!(shakmaty_fen.to_string(), fen);
+            let round_trip = ChessGame::try_from(&position).unwrap();
+            assert_eq!(round_trip.fen(), fen);
+        }
+        let horde = ChessGame::with_variant(Board::horde_board(), Variant::Horde);
+        assert_eq!(Chess::try_from(&horde), Err(ShakmatyError::UnsupportedVariant));
+        let setup = Setup { castling_rights: Bitboard::from_square(Square::D1), ..Setup::default() };
+        assert_eq!(ChessGame::try_from(&setup).err(),
+                   Some(ShakmatyError::UnsupportedCastlingRights));
+    }
+
+    #[test]
+    fn legal_moves_agree() {
+        // the positions and the positions after following a few of their moves
+        for fen in POSITIONS {
+            let mut game = ChessGame::from_fen(fen).unwrap();
+            let mut position = Chess::try_from(&game).unwrap();
+            for ply in 0..6 {
+                let moves = our_moves(&game);
+                assert_eq!(moves, shakmaty_moves(&position), "position {}", game.fen());
+                let Some(string) = moves.iter().nth(ply * 7 % moves.len().max(1)) else {
+                    break;
+                };
+                let uci_move: UciMove = string.parse().unwrap();
+                game.do_move(ChessMove::try_from(uci_move).unwrap()).unwrap();
+                let m = uci_move.to_move(&position).unwrap();
+                position = position.play(m).unwrap();
+            }
+        }
+    }
+}
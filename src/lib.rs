@@ -6,19 +6,15 @@
 //! ```rust
 //! use leben_chess::board::Board;
 //! use leben_chess::board::board_pos::BoardPosition;
-//! use leben_chess::board::piece::PlayerColor;
 //! use leben_chess::chess::{ChessError, ChessGame};
-//! use leben_chess::moves::{ChessMove, PieceMovement};
+//! use leben_chess::moves::ChessMove;
 //!
 //! fn main() -> Result<(), ChessError> {
 //!     let mut game = ChessGame::new(Board::default_board());
-//!     game.do_move(ChessMove {
-//!         piece_movement: PieceMovement {
-//!             from: BoardPosition::try_from("d2").unwrap(),
-//!             to: BoardPosition::try_from("d4").unwrap()
-//!         },
-//!         promotion: None,
-//!     })?;
+//!     game.do_move(ChessMove::new(
+//!         BoardPosition::try_from("d2").unwrap(),
+//!         BoardPosition::try_from("d4").unwrap(),
+//!     ))?;
 //!
 //!     println!("{}", game.game_status());
 //!     println!("{}", game.board());
@@ -31,8 +27,46 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # `no_std` support
+//!
+//! The rules engine only needs the `alloc` crate. Disabling the default `std` feature builds the
+//! library as `no_std`, leaving out the modules which need threads, clocks, files, hash maps or
+//! floating point functions: [clock], [openings], [puzzles], [ratings], [search], [tournament],
+//! [uci], [chess::shared] and the file loading of [book]. The optional features all require `std`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
+
+extern crate alloc;
 
+pub mod archive;
 pub mod board;
+pub mod book;
 pub mod chess;
+#[cfg(feature = "std")]
+pub mod clock;
+pub mod eval;
+pub mod interop;
+mod macros;
 pub mod moves;
+#[cfg(feature = "std")]
+pub mod openings;
+pub mod pgn;
+#[cfg(feature = "proptest")]
+pub mod proptest_support;
+#[cfg(feature = "std")]
+pub mod puzzles;
+#[cfg(feature = "std")]
+pub mod ratings;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "syzygy")]
+pub mod tablebase;
+#[cfg(feature = "std")]
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod uci;
 pub mod util;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,158 @@
+//! Macros for writing positions as visual diagrams and comparing boards, mainly for tests.
+
+/// Creates a [Board](crate::board::Board) from a visual 8x8 layout, evaluated at compile time.
+///
+/// The ranks are given from 8 to 1 as string literals separated by `/`, each with the squares
+/// from the a-file to the h-file as FEN piece letters, or `.` for an empty square, optionally
+/// separated by spaces. See [Board::const_from_rows](crate::board::Board::const_from_rows).
+///
+/// # Examples
+///
+/// ```rust
+/// use leben_chess::board;
+/// use leben_chess::board::Board;
+///
+/// let board = board! {
+///     "r . . . k . . r" /
+///     "p p p . . p p p" /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     "P P P . . P P P" /
+///     "R . . . K . . R"
+/// };
+/// assert_eq!(Some(board), Board::from_fen_string("r3k2r/ppp2ppp/8/8/8/8/PPP2PPP/R3K2R"));
+/// ```
+///
+/// A layout with the wrong number of ranks or squares, or an invalid letter, does not compile:
+///
+/// ```rust,compile_fail
+/// let board = leben_chess::board! {
+///     "r . . . k . . r" / "p p p . . p p p" / ". . . . . . . ." / ". . . . . . . ." /
+///     ". . . . . . . ." / "P P P . . P P P" / "R . . . K . . R"
+/// };
+/// ```
+///
+/// ```rust,compile_fail
+/// let board = leben_chess::board! {
+///     "r . . . k . . r" / "p p p . . p p p" / ". . . . . . . ." / ". . . . . . . ." /
+///     ". . . . . . . ." / ". . . . . . ." / "P P P . . P P P" / "R . . . K . . R"
+/// };
+/// ```
+///
+/// ```rust,compile_fail
+/// let board = leben_chess::board! {
+///     "r . . . k . . r" / "p p p . . p p p" / ". . . . . . . ." / ". . . . x . . ." /
+///     ". . . . . . . ." / ". . . . . . . ." / "P P P . . P P P" / "R . . . K . . R"
+/// };
+/// ```
+#[macro_export]
+macro_rules! board {
+    ($r8:literal / $r7:literal / $r6:literal / $r5:literal /
+     $r4:literal / $r3:literal / $r2:literal / $r1:literal $(,)?) => {
+        {
+            // a named constant is also evaluated by `cargo check`, unlike an inline const block
+            const BOARD: $crate::board::Board
+                = $crate::board::Board::const_from_rows([$r8, $r7, $r6, $r5, $r4, $r3, $r2, $r1]);
+            BOARD
+        }
+    };
+}
+
+/// Creates a [ChessGame](crate::chess::ChessGame) from a visual 8x8 layout like
+/// [board!](crate::board!), followed by a `;`, the player to move (`White` or `Black`), and the
+/// castling field of a FEN string, such as `"KQkq"` or `"-"`. There is no en passant target.
+///
+/// # Panics
+///
+/// Panics if the position is not valid, see [set_position](crate::chess::ChessGame::set_position).
+/// The layout and the castling field are checked at compile time.
+///
+/// # Examples
+///
+/// ```rust
+/// use leben_chess::game;
+/// use leben_chess::board::piece::PlayerColor;
+///
+/// let game = game! {
+///     ". . . . k . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     "R . . . K . . R";
+///     Black, "KQ"
+/// };
+/// assert_eq!(game.active_player(), PlayerColor::Black);
+/// assert_eq!(game.fen(), "4k3/8/8/8/8/8/8/R3K2R b KQ - 0 1");
+/// ```
+#[macro_export]
+macro_rules! game {
+    ($r8:literal / $r7:literal / $r6:literal / $r5:literal /
+     $r4:literal / $r3:literal / $r2:literal / $r1:literal; $player:ident, $castling:literal) => {{
+        let mut game = $crate::chess::ChessGame::new($crate::board::Board::default_board());
+        game.set_position(
+            $crate::board!($r8 / $r7 / $r6 / $r5 / $r4 / $r3 / $r2 / $r1),
+            $crate::board::piece::PlayerColor::$player,
+            {
+                const CASTLING_RIGHTS: ($crate::moves::CastlingRights,
+                                        $crate::moves::CastlingRights)
+                    = $crate::moves::CastlingRights::const_from_fen($castling);
+                CASTLING_RIGHTS
+            },
+            None,
+        ).expect("invalid position in game!");
+        game
+    }};
+}
+
+/// Asserts that two [Boards](crate::board::Board) are equal, like [assert_eq!]. On failure, the
+/// panic message shows both boards side by side and lists every differing square with its
+/// contents on the left and the right board, see
+/// [board_diff_report](crate::board::board_diff_report). A format string and arguments may be
+/// given after the boards, to be included in the message.
+///
+/// # Panics
+///
+/// Panics if the boards are not equal.
+///
+/// # Examples
+///
+/// ```rust
+/// use leben_chess::{assert_board_eq, board};
+/// use leben_chess::chess::ChessGame;
+///
+/// let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+/// game.try_move_str("e2e4").unwrap();
+/// assert_board_eq!(*game.board(), board! {
+///     ". . . . k . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . P . . ." /
+///     ". . . . . . . ." /
+///     ". . . . . . . ." /
+///     ". . . . K . . ."
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_board_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let ::core::option::Option::Some(report)
+            = $crate::board::board_diff_report(&$left, &$right)
+        {
+            ::core::panic!("assertion `left == right` failed\n{}", report);
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let ::core::option::Option::Some(report)
+            = $crate::board::board_diff_report(&$left, &$right)
+        {
+            ::core::panic!("assertion `left == right` failed: {}\n{}",
+                           ::core::format_args!($($arg)+), report);
+        }
+    };
+}
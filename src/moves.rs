@@ -1,16 +1,23 @@
 //! Functions and types for determining, querying and performing legal chess moves.
 
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
 use crate::board::{Board, OccupantState};
-use crate::board::board_pos::{BoardPosition, BoardLineIterator, CaptureType};
-use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::board::board_pos::{BoardPosition, BoardLineIterator, CaptureType, PositionParseError};
+use crate::board::piece::{Piece, PieceParseError, PieceType, PlayerColor};
 use crate::chess::ChessError;
+use crate::chess::variant::Variant;
 use crate::moves::util::BoardBitmap;
+use crate::util::U3;
 
 pub mod util;
-mod move_patterns;
+pub mod zones;
+pub(crate) mod move_patterns;
 
 /// Represents a valid piece type which a pawn may promote to.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum PromotionType {
     Knight,
     Bishop,
@@ -18,9 +25,47 @@ pub enum PromotionType {
     Queen,
 }
 
-impl Into<PieceType> for PromotionType {
-    fn into(self) -> PieceType {
-        match self {
+impl PromotionType {
+    /// All four promotion types, in the order knight, bishop, rook, queen.
+    pub const ALL: [PromotionType; 4] = [
+        PromotionType::Knight,
+        PromotionType::Bishop,
+        PromotionType::Rook,
+        PromotionType::Queen,
+    ];
+
+    /// returns: The promotion type's uppercase letter, as used in SAN (e.g. `e8=Q`).
+    pub fn to_char(&self) -> char {
+        PieceType::from(*self).to_char()
+    }
+
+    /// returns: The piece of the given color that a pawn promotes to.
+    pub fn to_piece(&self, player: PlayerColor) -> Piece {
+        Piece::new((*self).into(), player)
+    }
+}
+
+/// Displays the promotion type's uppercase letter, as used in SAN (e.g. `e8=Q`).
+impl Display for PromotionType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// Parses a promotion type from its letter, ignoring case, so both SAN (`Q`) and UCI (`q`) letters
+/// are accepted.
+impl TryFrom<char> for PromotionType {
+    type Error = PieceParseError;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        PieceType::try_from(value)?
+            .try_into()
+            .map_err(|_| PieceParseError::InvalidLetter(value))
+    }
+}
+
+impl From<PromotionType> for PieceType {
+    fn from(value: PromotionType) -> Self {
+        match value {
             PromotionType::Knight => PieceType::Knight,
             PromotionType::Bishop => PieceType::Bishop,
             PromotionType::Rook => PieceType::Rook,
@@ -44,13 +89,73 @@ impl TryFrom<PieceType> for PromotionType {
 }
 
 /// Represents the movement of a piece from one square to another, without any additional
-/// information.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// information. Movements are ordered first by origin square and then by destination square.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct PieceMovement {
     pub from: BoardPosition,
     pub to: BoardPosition,
 }
 
+impl PieceMovement {
+    /// Creates a movement from two square names, such as `PieceMovement::new("e2", "e4")`.
+    ///
+    /// returns: `Ok(PieceMovement)` if both squares were parsed successfully, otherwise the
+    ///          [PositionParseError] of the first invalid square.
+    pub fn new(from: &str, to: &str) -> Result<PieceMovement, PositionParseError> {
+        Ok(PieceMovement {
+            from: from.parse()?,
+            to: to.parse()?,
+        })
+    }
+
+    /// returns: The movement going the opposite way, from `to` to `from`.
+    pub fn reversed(&self) -> PieceMovement {
+        PieceMovement {
+            from: self.to,
+            to: self.from,
+        }
+    }
+}
+
+/// Displays the movement in coordinate notation, e.g. `e2e4`.
+/// Formats the move in the coordinate notation used by UCI, such as `e2e4` or `e7e8q`.
+impl Display for ChessMove {
+    /// Formats the move in coordinate notation, such as `e2e4` or `e7e8q`, or a drop as the piece
+    /// letter followed by `@` and the square, such as `N@f3`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some(piece_type) = self.drop {
+            return write!(f, "{}@{}", piece_type.to_char(), self.piece_movement.to);
+        }
+        write!(f, "{}", self.piece_movement)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.to_char().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for PieceMovement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.from, self.to)
+    }
+}
+
+/// Parses a movement in coordinate notation, either without a separator (`e2e4`) or with a dash
+/// (`e2-e4`).
+impl FromStr for PieceMovement {
+    type Err = PositionParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        match chars.as_slice() {
+            [a, b, c, d] | [a, b, '-', c, d] => Ok(PieceMovement {
+                from: BoardPosition::try_from((*a, *b))?,
+                to: BoardPosition::try_from((*c, *d))?,
+            }),
+            _ => Err(PositionParseError::WrongLength),
+        }
+    }
+}
+
 impl TryFrom<((u8, u8), (u8, u8))> for PieceMovement {
     type Error = ();
     fn try_from(value: ((u8, u8), (u8, u8))) -> Result<Self, Self::Error> {
@@ -62,19 +167,120 @@ impl TryFrom<((u8, u8), (u8, u8))> for PieceMovement {
 }
 
 /// Represents any chess move, which includes the movement from one square to another, and may
-/// include a pawn promotion type (see [PromotionType]).
-#[derive(Copy, Clone, Debug)]
+/// include a pawn promotion type (see [PromotionType]). A
+/// [Crazyhouse](crate::chess::variant::Variant::Crazyhouse) drop of a piece from the hand is
+/// represented as a move from and to the drop square with the type of the dropped piece, see
+/// [ChessMove::dropping].
+///
+/// # Canonical order
+///
+/// Every API enumerating moves, such as [legal_moves](crate::chess::ChessGame::legal_moves),
+/// returns them in the canonical order: ascending by the [index](BoardPosition::index) of the
+/// origin square, then by the index of the destination square, and then by promotion type in
+/// the order knight, bishop, rook, queen. Drops come after all other moves, ordered by the type
+/// of the dropped piece in the order pawn, knight, bishop, rook, queen, and then by the index of
+/// the square. See [sort_moves_canonical].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ChessMove {
     pub piece_movement: PieceMovement,
     pub promotion: Option<PromotionType>,
+    /// The type of the dropped piece, if the move is a drop.
+    pub drop: Option<PieceType>,
 }
 
-#[derive(Copy, Clone, Debug)]
-pub(crate) struct CastlingRights {
+impl ChessMove {
+    /// returns: A move from one square to another which is not a promotion move.
+    pub fn new(from: BoardPosition, to: BoardPosition) -> ChessMove {
+        ChessMove {
+            piece_movement: PieceMovement { from, to },
+            promotion: None,
+            drop: None,
+        }
+    }
+
+    /// returns: A pawn move from one square to another, promoting to the given piece type.
+    pub fn promoting(from: BoardPosition, to: BoardPosition, promotion: PromotionType) -> ChessMove {
+        ChessMove {
+            piece_movement: PieceMovement { from, to },
+            promotion: Some(promotion),
+            drop: None,
+        }
+    }
+
+    /// returns: A Crazyhouse drop of a piece of the given type from the hand on a square, which
+    ///          is represented as a move from and to the square.
+    pub fn dropping(piece_type: PieceType, to: BoardPosition) -> ChessMove {
+        ChessMove {
+            piece_movement: PieceMovement { from: to, to },
+            promotion: None,
+            drop: Some(piece_type),
+        }
+    }
+
+    /// returns: Whether the move is a [drop](ChessMove::dropping).
+    pub fn is_drop(&self) -> bool {
+        self.drop.is_some()
+    }
+
+    /// Creates a move which is not a promotion move from two square names, such as
+    /// `ChessMove::from_coords("e2", "e4")`.
+    ///
+    /// returns: `Ok(ChessMove)` if both squares were parsed successfully, otherwise the
+    ///          [PositionParseError] of the first invalid square.
+    pub fn from_coords(from: &str, to: &str) -> Result<ChessMove, PositionParseError> {
+        Ok(ChessMove::new(from.parse()?, to.parse()?))
+    }
+}
+
+/// Sorts moves into the [canonical order](ChessMove#canonical-order) used by the APIs enumerating
+/// moves.
+pub fn sort_moves_canonical(moves: &mut [ChessMove]) {
+    moves.sort_by_key(|chess_move| {
+        let drop = chess_move.drop.map(|piece_type| piece_type as usize);
+        (drop, chess_move.piece_movement.from, chess_move.piece_movement.to, chess_move.promotion)
+    });
+}
+
+/// Represents whether a player may still castle on either side. Castling rights are lost when the
+/// king or the corresponding rook moves.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CastlingRights {
     pub queenside: bool,
     pub kingside: bool,
 }
 
+impl CastlingRights {
+    /// Castling rights with castling on both sides disallowed.
+    pub const NONE: CastlingRights = CastlingRights { queenside: false, kingside: false };
+
+    /// Parses the castling field of a FEN string, such as `"KQkq"` or `"-"`, usable in `const`
+    /// items.
+    ///
+    /// returns: The castling rights of white and black.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field contains a character other than `K`, `Q`, `k`, `q` or `-`.
+    pub const fn const_from_fen(field: &str) -> (CastlingRights, CastlingRights) {
+        let mut white = CastlingRights::NONE;
+        let mut black = CastlingRights::NONE;
+        let bytes = field.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'K' => white.kingside = true,
+                b'Q' => white.queenside = true,
+                b'k' => black.kingside = true,
+                b'q' => black.queenside = true,
+                b'-' => {}
+                _ => panic!("invalid character in the castling field"),
+            }
+            i += 1;
+        }
+        (white, black)
+    }
+}
+
 impl Default for CastlingRights {
     fn default() -> Self {
         CastlingRights {
@@ -88,55 +294,66 @@ impl Default for CastlingRights {
 pub(crate) struct MoveContext {
     pub castling_rights: CastlingRights,
     pub en_passant_target: Option<BoardPosition>,
+    pub variant: Variant,
 }
 
 fn find_kings(board: &Board, active_player: PlayerColor) -> impl Iterator<Item=BoardPosition> {
-    let own_king_predicate = move |piece: Piece|
-        piece.player == active_player
-        && matches!(piece.piece_type, PieceType::King);
-    let square_predicate = move |(_, square): &(BoardPosition, Option<Piece>)|
-        square.map_or(false, own_king_predicate);
-    board.into_iter()
-        .filter(square_predicate)
-        .map(|(pos, _)| pos)
-}
-
-pub(crate) fn is_in_check(board: &Board, player: PlayerColor) -> bool {
-    find_kings(board, player).any(|pos| {
-        let king_check_board_lines = match player {
-            PlayerColor::White => move_patterns::WHITE_KING_CHECK_BOARD_LINES,
-            PlayerColor::Black => move_patterns::BLACK_KING_CHECK_BOARD_LINES,
-        };
-        for (piece_type, board_lines) in king_check_board_lines {
-            // try to find enemy pieces of a certain type
-            let mut iter = BoardLineIterator::new(pos, board_lines);
-            while let Some(target_square) = iter.next() {
-                // return true if target_square contains an enemy piece of the right type
-                match board.get_occupant_state(target_square.position, player) {
-                    OccupantState::Empty => continue,
-                    OccupantState::Friendly => {}
-                    OccupantState::Enemy => {
-                        if matches!(
-                            target_square.capture_type,
-                            CaptureType::Normal | CaptureType::CaptureOnly
-                        ) {
-                            if let Some(piece) = board.get_piece(target_square.position) {
-                                if piece.piece_type == *piece_type {
-                                    return true;
-                                }
-                            }
-                        }
+    let kings = board.find(PieceType::King, active_player);
+    BoardPosition::all().filter(move |&pos| kings.get(pos))
+}
+
+pub(crate) fn find_king(board: &Board, player: PlayerColor) -> Option<BoardPosition> {
+    board.find_one(PieceType::King, player)
+}
+
+pub(crate) fn is_in_check(board: &Board, player: PlayerColor, variant: Variant) -> bool {
+    find_kings(board, player)
+        .any(|pos| !attacking_pieces_in_variant(board, player, pos, variant).is_all_zeros())
+}
+
+/// returns: The squares of the enemy pieces of `player` that attack the square `pos`, assuming it
+///          contains a piece of `player`.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn attacking_pieces(board: &Board, player: PlayerColor,
+                               pos: BoardPosition) -> BoardBitmap
+{
+    attacking_pieces_in_variant(board, player, pos, Variant::Standard)
+}
+
+/// returns: The squares of the enemy pieces of `player` that attack the square `pos`, assuming it
+///          contains a piece of `player`, where the pieces move as in the given variant.
+pub(crate) fn attacking_pieces_in_variant(board: &Board, player: PlayerColor, pos: BoardPosition,
+                                          variant: Variant) -> BoardBitmap
+{
+    let mut bitmap = BoardBitmap::all_zeros();
+    let king_check_board_lines = move_patterns::get_king_check_board_lines(player, variant);
+    for (piece_type, board_lines) in king_check_board_lines {
+        // try to find enemy pieces of a certain type
+        let mut iter = BoardLineIterator::new(pos, board_lines);
+        while let Some(target_square) = iter.next() {
+            // mark target_square if it contains an enemy piece of the right type
+            match board.get_occupant_state(target_square.position, player) {
+                OccupantState::Empty => continue,
+                OccupantState::Friendly => {}
+                OccupantState::Enemy => {
+                    if matches!(
+                        target_square.capture_type,
+                        CaptureType::Normal | CaptureType::CaptureOnly
+                    ) && let Some(piece) = board.get_piece(target_square.position)
+                        && piece.piece_type == *piece_type
+                    {
+                        bitmap.set(target_square.position, true);
                     }
                 }
-                iter.skip_line()
             }
+            iter.skip_line()
         }
-        false
-    })
+    }
+    bitmap
 }
 
 fn leads_to_check(board: &mut Board, active_player: PlayerColor,
-                  piece_movement: PieceMovement) -> bool
+                  piece_movement: PieceMovement, variant: Variant) -> bool
 {
     let moved_piece = board.get_piece(piece_movement.from);
     let replaced_piece = board.get_piece(piece_movement.to);
@@ -144,7 +361,7 @@ fn leads_to_check(board: &mut Board, active_player: PlayerColor,
     // test whether this move would put the active player in check
     board.set_piece(piece_movement.from, None);
     board.set_piece(piece_movement.to, moved_piece);
-    let in_check = is_in_check(board, active_player);
+    let in_check = is_in_check(board, active_player, variant);
 
     // undo move
     board.set_piece(piece_movement.from, moved_piece);
@@ -153,24 +370,30 @@ fn leads_to_check(board: &mut Board, active_player: PlayerColor,
     in_check
 }
 
-fn create_en_passant_target(active_player: PlayerColor,
-                            piece_movement: PieceMovement) -> Option<BoardPosition>
+/// returns: Whether a pawn of `active_player` on `pos` may make a double move, if the squares in
+///          front of it are empty. In [Horde](Variant::Horde), white's pawns on the first rank
+///          may also make a double move, and in [Shatranj](Variant::Shatranj), pawns never make
+///          double moves.
+pub(crate) fn has_double_move(active_player: PlayerColor, pos: BoardPosition, variant: Variant)
+                               -> bool
 {
-    let pawn_start_rank = match active_player {
-        PlayerColor::White => 1,
-        PlayerColor::Black => 6,
-    };
-    let double_move_rank = match active_player {
-        PlayerColor::White => 3,
-        PlayerColor::Black => 4,
-    };
-    if piece_movement.from.rank.get() == pawn_start_rank
-        && piece_movement.to.rank.get() == double_move_rank {
-        let offset = match active_player {
-            PlayerColor::White => (0, 1),
-            PlayerColor::Black => (0, -1),
-        };
-        piece_movement.from.add(offset)
+    if variant == Variant::Shatranj {
+        return false;
+    }
+    pos.rank == active_player.pawn_start_rank()
+        || variant == Variant::Horde
+            && active_player == PlayerColor::White
+            && pos.rank == active_player.back_rank()
+}
+
+fn create_en_passant_target(active_player: PlayerColor, piece_movement: PieceMovement,
+                            variant: Variant) -> Option<BoardPosition>
+{
+    let direction = active_player.pawn_direction();
+    let double_move_rank = piece_movement.from.rank.checked_add_signed(2 * direction);
+    if has_double_move(active_player, piece_movement.from, variant)
+        && Some(piece_movement.to.rank) == double_move_rank {
+        piece_movement.from.add((0, direction))
     } else {
         None
     }
@@ -179,71 +402,75 @@ fn create_en_passant_target(active_player: PlayerColor,
 fn get_en_passant_pos(active_player: PlayerColor,
                       en_passant_target: BoardPosition) -> Option<BoardPosition>
 {
-    let offset = match active_player {
-        PlayerColor::White => (0, -1),
-        PlayerColor::Black => (0, 1),
-    };
-    en_passant_target.add(offset)
+    en_passant_target.add((0, -active_player.pawn_direction()))
 }
 
-fn is_first_move_pawn(active_player: PlayerColor,
-                      pos: BoardPosition) -> Option<(BoardPosition, BoardPosition)>
+fn is_first_move_pawn(active_player: PlayerColor, pos: BoardPosition,
+                      variant: Variant) -> Option<(BoardPosition, BoardPosition)>
 {
-    match active_player {
-        PlayerColor::White => if pos.rank.get() == 1 {
-            Some((pos.add((0, 1)).unwrap(), pos.add((0, 2)).unwrap()))
-        } else { None },
-        PlayerColor::Black => if pos.rank.get() == 6 {
-            Some((pos.add((0, -1)).unwrap(), pos.add((0, -2)).unwrap()))
-        } else { None },
+    if !has_double_move(active_player, pos, variant) {
+        return None;
     }
+    let direction = active_player.pawn_direction();
+    Some((pos.add((0, direction))?, pos.add((0, 2 * direction))?))
+}
+
+/// Determines whether a pawn may capture en passant, which is the one move where two pieces
+/// leave their squares at once: the capturing pawn and the captured pawn may both have been
+/// blocking an attack on the king, for example by a rook along the rank of the pawns. The move is
+/// simulated on the board to see whether it leaves the player's king attacked.
+///
+/// # Arguments
+///
+/// * `board`: The board before the capture.
+/// * `active_player`: The player capturing en passant.
+/// * `pawn_from`: The square of the capturing pawn.
+/// * `en_passant_target`: The en passant target square, which the pawn moves to.
+///
+/// returns: Whether there is a pawn of `active_player` on `pawn_from` which may legally capture
+///          en passant on `en_passant_target`, with a pawn of the other player behind the empty
+///          target square.
+pub fn is_en_passant_legal(board: &Board, active_player: PlayerColor, pawn_from: BoardPosition,
+                           en_passant_target: BoardPosition) -> bool
+{
+    is_en_passant_legal_in_variant(board, active_player, pawn_from, en_passant_target,
+                                   Variant::Standard)
 }
 
-fn add_en_passant_moves(board: &mut Board, active_player: PlayerColor, pos: BoardPosition,
-                        en_passant_target: BoardPosition, bitmap: &mut BoardBitmap)
+/// returns: Whether the pawn on `pawn_from` may capture en passant like in
+///          [is_en_passant_legal], where the pieces move as in the given variant.
+pub(crate) fn is_en_passant_legal_in_variant(board: &Board, active_player: PlayerColor,
+                                             pawn_from: BoardPosition,
+                                             en_passant_target: BoardPosition,
+                                             variant: Variant) -> bool
 {
+    let pawn = |player| Some(Piece::new(PieceType::Pawn, player));
     // check that the target square is actually capturable by the pawn
-    let capture_offsets = match active_player {
-        PlayerColor::White => ((-1, 1), (1, 1)),
-        PlayerColor::Black => ((-1, -1), (1, -1)),
+    let direction = active_player.pawn_direction();
+    let is_capture_square = [-1, 1].into_iter()
+        .any(|file_offset| pawn_from.add((file_offset, direction)) == Some(en_passant_target));
+    let Some(en_passanted_pos) = get_en_passant_pos(active_player, en_passant_target) else {
+        return false;
     };
-    let capture_squares = (
-        pos.add(capture_offsets.0),
-        pos.add(capture_offsets.1)
-    );
-    if Some(en_passant_target) != capture_squares.0 && Some(en_passant_target) != capture_squares.1 {
-        return;
+    if !is_capture_square || board.get_piece(pawn_from) != pawn(active_player)
+        || board.get_piece(en_passant_target).is_some()
+        || board.get_piece(en_passanted_pos) != pawn(active_player.other_player())
+    {
+        return false;
     }
 
-    let en_passanted_pos = match get_en_passant_pos(active_player, en_passant_target) {
-        Some(pos) => pos,
-        None => return
-    };
-
     // check for the special case where the captured pawn blocked check
-    let moved_piece = board.get_piece(pos);
-    let destination_piece = board.get_piece(en_passant_target);
-    let en_passanted_piece = board.get_piece(en_passanted_pos);
-
-    board.set_piece(pos, None);
-    board.set_piece(en_passant_target, moved_piece);
+    let mut board = board.clone();
+    board.set_piece(pawn_from, None);
+    board.set_piece(en_passant_target, pawn(active_player));
     board.set_piece(en_passanted_pos, None);
-
-    // if move is legal, add to bitmap
-    if !is_in_check(board, active_player) {
-        bitmap.set(en_passant_target, true);
-    }
-
-    // undo move
-    board.set_piece(pos, moved_piece);
-    board.set_piece(en_passant_target, destination_piece);
-    board.set_piece(en_passanted_pos, en_passanted_piece);
+    !is_in_check(&board, active_player, variant)
 }
 
 fn add_castling_moves(board: &mut Board, active_player: PlayerColor,
-                      castling_rights: CastlingRights, bitmap: &mut BoardBitmap)
+                      castling_rights: CastlingRights, variant: Variant, bitmap: &mut BoardBitmap)
 {
-    if is_in_check(&board, active_player) {
+    if is_in_check(board, active_player, variant) {
         return;
     }
     let mut add_on_side = |rook_pos: BoardPosition, king_moves_from: BoardPosition,
@@ -257,11 +484,11 @@ fn add_castling_moves(board: &mut Board, active_player: PlayerColor,
         };
         if !matches!(piece.piece_type, PieceType::Rook) { return; }
         for square in must_be_empty {
-            if !matches!(board.get_piece(*square), None) { return; }
+            if board.get_piece(*square).is_some() { return; }
         }
         for square in passes_through {
             if leads_to_check(board, active_player,
-                              PieceMovement { from: king_moves_from, to: *square })
+                              PieceMovement { from: king_moves_from, to: *square }, variant)
             {
                 return;
             }
@@ -269,47 +496,47 @@ fn add_castling_moves(board: &mut Board, active_player: PlayerColor,
         bitmap.set(king_moves_to, true);
     };
 
-    let rank = match active_player {
-        PlayerColor::White => 0,
-        PlayerColor::Black => 7,
-    };
-    let king_moves_from = BoardPosition::try_from((4, rank)).unwrap();
+    let rank = active_player.back_rank();
+    let square = |file: u8| BoardPosition { file: U3::from_low_bits(file), rank };
+    let king_moves_from = square(4);
     if castling_rights.queenside {
-        let rook_pos = BoardPosition::try_from((0, rank)).unwrap();
-        let king_moves_to = BoardPosition::try_from((2, rank)).unwrap();
+        let rook_pos = square(0);
+        let king_moves_to = square(2);
         let must_be_empty = &[
-            BoardPosition::try_from((1, rank)).unwrap(),
-            BoardPosition::try_from((2, rank)).unwrap(),
-            BoardPosition::try_from((3, rank)).unwrap(),
+            square(1),
+            square(2),
+            square(3),
         ];
         let passes_through = &[
-            BoardPosition::try_from((2, rank)).unwrap(),
-            BoardPosition::try_from((3, rank)).unwrap(),
+            square(2),
+            square(3),
         ];
         add_on_side(rook_pos, king_moves_from, king_moves_to, must_be_empty, passes_through);
     }
     if castling_rights.kingside {
-        let rook_pos = BoardPosition::try_from((7, rank)).unwrap();
-        let king_moves_to = BoardPosition::try_from((6, rank)).unwrap();
+        let rook_pos = square(7);
+        let king_moves_to = square(6);
         let must_be_empty = &[
-            BoardPosition::try_from((5, rank)).unwrap(),
-            BoardPosition::try_from((6, rank)).unwrap(),
+            square(5),
+            square(6),
         ];
         let passes_through = &[
-            BoardPosition::try_from((5, rank)).unwrap(),
-            BoardPosition::try_from((6, rank)).unwrap(),
+            square(5),
+            square(6),
         ];
         add_on_side(rook_pos, king_moves_from, king_moves_to, must_be_empty, passes_through);
     }
 }
 
-pub(crate) fn get_available_moves(board: &mut Board, active_player: PlayerColor, pos: BoardPosition,
-                                  move_context: MoveContext) -> BoardBitmap
+/// returns: The destination squares of the moves of the piece on `pos` if it belongs to
+///          `active_player`, without checking whether the moves leave the player in check.
+pub(crate) fn get_pseudo_legal_moves(board: &mut Board, active_player: PlayerColor,
+                                     pos: BoardPosition, move_context: MoveContext) -> BoardBitmap
 {
     let mut bitmap = BoardBitmap::all_zeros();
     if let Some(piece) = board.get_piece(pos) {
         if piece.player != active_player { return bitmap; }
-        let board_lines = move_patterns::get_board_lines(piece);
+        let board_lines = move_patterns::get_board_lines(piece, move_context.variant);
         let mut iter = BoardLineIterator::new(pos, board_lines);
         while let Some(target_square) = iter.next() {
             match board.get_occupant_state(target_square.position, active_player) {
@@ -333,11 +560,14 @@ pub(crate) fn get_available_moves(board: &mut Board, active_player: PlayerColor,
         }
         match piece.piece_type {
             PieceType::Pawn => {
-                if let Some(en_passant_target) = move_context.en_passant_target {
-                    add_en_passant_moves(board, active_player, pos, en_passant_target, &mut bitmap);
+                if let Some(en_passant_target) = move_context.en_passant_target
+                    && is_en_passant_legal_in_variant(board, active_player, pos,
+                                                      en_passant_target, move_context.variant)
+                {
+                    bitmap.set(en_passant_target, true);
                 }
                 if let Some((forward_move_pos, double_move_pos)) =
-                    is_first_move_pawn(active_player, pos)
+                    is_first_move_pawn(active_player, pos, move_context.variant)
                 {
                     let occupant_forward = board.get_occupant_state(
                         forward_move_pos,
@@ -345,33 +575,41 @@ pub(crate) fn get_available_moves(board: &mut Board, active_player: PlayerColor,
                     let occupant_double_move = board.get_occupant_state(
                         double_move_pos,
                         active_player);
-                    match (occupant_forward, occupant_double_move) {
-                        (OccupantState::Empty, OccupantState::Empty)
-                            => bitmap.set(double_move_pos, true),
-                        _ => {}
+                    if let (OccupantState::Empty, OccupantState::Empty)
+                        = (occupant_forward, occupant_double_move)
+                    {
+                        bitmap.set(double_move_pos, true);
                     }
                 }
             }
             PieceType::King => add_castling_moves(board, active_player,
-                                                  move_context.castling_rights, &mut bitmap),
+                                                  move_context.castling_rights,
+                                                  move_context.variant, &mut bitmap),
             _ => {}
         }
-    } else {
-        return bitmap;
-    }
-    for file in 0..8 {
-        for rank in 0..8 {
-            let move_to = BoardPosition::try_from((file, rank)).unwrap();
-            if bitmap.get(move_to) {
-                let leads_to_check = leads_to_check(
-                    board, active_player,
-                    PieceMovement {
-                        from: pos,
-                        to: move_to,
-                    });
-                if leads_to_check {
-                    bitmap.set(move_to, false);
-                }
+    }
+    bitmap
+}
+
+pub(crate) fn get_available_moves(board: &mut Board, active_player: PlayerColor, pos: BoardPosition,
+                                  move_context: MoveContext) -> BoardBitmap
+{
+    let mut bitmap = get_pseudo_legal_moves(board, active_player, pos, move_context);
+    // a capture en passant also removes the captured pawn, so it is checked separately when it is
+    // added, by is_en_passant_legal
+    let is_pawn = board.get_piece(pos).is_some_and(|piece| piece.piece_type == PieceType::Pawn);
+    let en_passant_target = move_context.en_passant_target.filter(|_| is_pawn);
+    for move_to in BoardPosition::all() {
+        if bitmap.get(move_to) && Some(move_to) != en_passant_target {
+            let leads_to_check = leads_to_check(
+                board, active_player,
+                PieceMovement {
+                    from: pos,
+                    to: move_to,
+                },
+                move_context.variant);
+            if leads_to_check {
+                bitmap.set(move_to, false);
             }
         }
     }
@@ -380,7 +618,8 @@ pub(crate) fn get_available_moves(board: &mut Board, active_player: PlayerColor,
 
 #[derive(Clone, Debug)]
 pub(crate) struct MoveResult {
-    pub captured_piece: Option<Piece>,
+    pub removed_piece: Option<(BoardPosition, Piece)>,
+    pub secondary_movement: Option<PieceMovement>,
     pub new_en_passant_target: Option<BoardPosition>,
     pub removes_queenside_castling_rights: bool,
     pub removes_kingside_castling_rights: bool,
@@ -389,11 +628,9 @@ pub(crate) struct MoveResult {
 pub(crate) fn expects_promotion_type(board: &Board, active_player: PlayerColor,
                                      move_from: BoardPosition) -> bool
 {
-    let up_for_promotion_rank = match active_player {
-        PlayerColor::White => 6,
-        PlayerColor::Black => 1,
-    };
-    move_from.rank.get() == up_for_promotion_rank
+    let up_for_promotion_rank = active_player.promotion_rank()
+        .checked_add_signed(-active_player.pawn_direction());
+    Some(move_from.rank) == up_for_promotion_rank
         && board.get_piece(move_from).is_some_and(|piece|
             matches!(piece.piece_type, PieceType::Pawn)
             && piece.player == active_player)
@@ -407,95 +644,105 @@ pub(crate) fn do_move(board: &mut Board, active_player: PlayerColor, chess_move:
                       move_context: MoveContext) -> Result<MoveResult, ChessError>
 {
     let mut result = MoveResult {
-        captured_piece: None,
+        removed_piece: None,
+        secondary_movement: None,
         new_en_passant_target: None,
         removes_queenside_castling_rights: false,
         removes_kingside_castling_rights: false,
     };
     if let Some(moved_piece) = board.get_piece(chess_move.piece_movement.from) {
         if !matches!(moved_piece.piece_type, PieceType::Pawn)
-            && matches!(chess_move.promotion, Some(_))
+            && chess_move.promotion.is_some()
         {
-            return Err(ChessError::UnexpectedPromotionType);
+            return Err(ChessError::UnexpectedPromotionType(chess_move));
         }
         let mut piece_after_move = moved_piece;
-        result.captured_piece = board.get_piece(chess_move.piece_movement.to);
+        result.removed_piece = board.get_piece(chess_move.piece_movement.to)
+            .map(|piece| (chess_move.piece_movement.to, piece));
         match moved_piece.piece_type {
             PieceType::Pawn => {
                 // double move creates en passant target
-                result.new_en_passant_target = create_en_passant_target(active_player, chess_move.piece_movement);
+                result.new_en_passant_target = create_en_passant_target(
+                    active_player, chess_move.piece_movement, move_context.variant);
 
                 // promotion
                 if expects_promotion_type(board, active_player, chess_move.piece_movement.from) {
                     if let Some(promotion) = chess_move.promotion {
+                        // pawns only promote to a fers in shatranj
+                        if move_context.variant == Variant::Shatranj
+                            && promotion != PromotionType::Queen
+                        {
+                            return Err(ChessError::IllegalMove(chess_move));
+                        }
                         piece_after_move = Piece {
                             piece_type: promotion.into(),
                             player: active_player,
                         };
                     } else {
-                        return Err(ChessError::MissingPromotionType);
-                    }
-                } else {
-                    if matches!(chess_move.promotion, Some(_)) {
-                        return Err(ChessError::UnexpectedPromotionType);
+                        return Err(ChessError::MissingPromotionType(chess_move));
                     }
+                } else if chess_move.promotion.is_some() {
+                    return Err(ChessError::UnexpectedPromotionType(chess_move));
                 }
 
                 // capture en passant
-                if let Some(en_passant_target) = move_context.en_passant_target {
-                    if chess_move.piece_movement.to == en_passant_target {
-                        if let Some(en_passant_pos) = get_en_passant_pos(active_player,
-                                                                         en_passant_target)
-                        {
-                            result.captured_piece = board.get_piece(en_passant_pos);
-                            // at this point, if the function is gonna fail, it has already
-                            // happened. therefore, we can safely mutate the board
-                            board.set_piece(en_passant_pos, None);
-                        }
-                    }
+                if let Some(en_passant_target) = move_context.en_passant_target
+                    && chess_move.piece_movement.to == en_passant_target
+                    && let Some(en_passant_pos) = get_en_passant_pos(active_player,
+                                                                     en_passant_target)
+                {
+                    result.removed_piece = board.get_piece(en_passant_pos)
+                        .map(|piece| (en_passant_pos, piece));
+                    // at this point, if the function is gonna fail, it has already
+                    // happened. therefore, we can safely mutate the board
+                    board.set_piece(en_passant_pos, None);
                 }
             }
             PieceType::King => {
-                let rank = match active_player {
-                    PlayerColor::White => 0,
-                    PlayerColor::Black => 7,
-                };
+                let rank = active_player.back_rank();
+                let square = |file: u8| BoardPosition { file: U3::from_low_bits(file), rank };
                 let (queenside_move, kingside_move) = (
                     PieceMovement {
-                        from: BoardPosition::try_from((4, rank)).unwrap(),
-                        to: BoardPosition::try_from((2, rank)).unwrap(),
+                        from: square(4),
+                        to: square(2),
                     },
                     PieceMovement {
-                        from: BoardPosition::try_from((4, rank)).unwrap(),
-                        to: BoardPosition::try_from((6, rank)).unwrap(),
+                        from: square(4),
+                        to: square(6),
                     },
                 );
                 if chess_move.piece_movement == queenside_move {
-                    let rook_from = BoardPosition::try_from((0, rank)).unwrap();
-                    let rook_to = BoardPosition::try_from((3, rank)).unwrap();
+                    let rook_from = square(0);
+                    let rook_to = square(3);
                     let rook = board.get_piece(rook_from);
                     board.set_piece(rook_from, None);
                     board.set_piece(rook_to, rook);
+                    result.secondary_movement = Some(PieceMovement {
+                        from: rook_from,
+                        to: rook_to,
+                    });
                 } else if chess_move.piece_movement == kingside_move {
-                    let rook_from = BoardPosition::try_from((7, rank)).unwrap();
-                    let rook_to = BoardPosition::try_from((5, rank)).unwrap();
+                    let rook_from = square(7);
+                    let rook_to = square(5);
                     let rook = board.get_piece(rook_from);
                     board.set_piece(rook_from, None);
                     board.set_piece(rook_to, rook);
+                    result.secondary_movement = Some(PieceMovement {
+                        from: rook_from,
+                        to: rook_to,
+                    });
                 }
                 result.removes_queenside_castling_rights = true;
                 result.removes_kingside_castling_rights = true;
             }
             PieceType::Rook => {
-                let rank = match active_player {
-                    PlayerColor::White => 0,
-                    PlayerColor::Black => 7,
-                };
-                if chess_move.piece_movement.from == BoardPosition::try_from((0, rank)).unwrap() {
-                    result.removes_queenside_castling_rights;
+                let rank = active_player.back_rank();
+                let square = |file: u8| BoardPosition { file: U3::from_low_bits(file), rank };
+                if chess_move.piece_movement.from == square(0) {
+                    result.removes_queenside_castling_rights = true;
                 }
-                if chess_move.piece_movement.from == BoardPosition::try_from((7, rank)).unwrap() {
-                    result.removes_kingside_castling_rights;
+                if chess_move.piece_movement.from == square(7) {
+                    result.removes_kingside_castling_rights = true;
                 }
             }
             _ => {}
@@ -506,40 +753,241 @@ pub(crate) fn do_move(board: &mut Board, active_player: PlayerColor, chess_move:
     Ok(result)
 }
 
+/// Reverses a chess move performed with [do_move], restoring the board to what it was before the
+/// move.
+///
+/// # Arguments
+///
+/// * `board`: The board after the move.
+/// * `piece_movement`: The movement of the moved piece.
+/// * `moved_piece`: The moved piece as it was before the move, which is a pawn if the move was a
+///   promotion.
+/// * `removed_piece`: The piece removed by the move and its square, see [MoveResult].
+/// * `secondary_movement`: The movement of a rook when castling, see [MoveResult].
+pub(crate) fn undo_move(board: &mut Board, piece_movement: PieceMovement, moved_piece: Piece,
+                        removed_piece: Option<(BoardPosition, Piece)>,
+                        secondary_movement: Option<PieceMovement>)
+{
+    board.set_piece(piece_movement.to, None);
+    board.set_piece(piece_movement.from, Some(moved_piece));
+    if let Some(PieceMovement { from, to }) = secondary_movement {
+        let rook = board.get_piece(to);
+        board.set_piece(to, None);
+        board.set_piece(from, rook);
+    }
+    if let Some((pos, piece)) = removed_piece {
+        board.set_piece(pos, Some(piece));
+    }
+}
+
+/// Finds a shortest sequence of moves of a piece from one square to another, for example for
+/// maneuvering exercises such as bringing a knight to a given square in the fewest moves. Only
+/// the piece moves: the other pieces stay where they are, and it is not checked whether the
+/// moves leave the player in check. Castling and captures en passant are not considered, and a
+/// pawn neither promotes nor captures on its way, but may make a double move from its starting
+/// rank.
+///
+/// # Arguments
+///
+/// * `board`: The board the piece moves on. Any piece on `from` is ignored.
+/// * `piece`: The piece to move.
+/// * `from`: The square the piece starts on.
+/// * `to`: The square the piece should reach.
+/// * `ignore_pieces`: Whether to treat the board as empty. Otherwise, occupied squares block the
+///   piece, which may not move onto its own player's pieces, and may only capture an enemy piece
+///   on `to`.
+///
+/// returns: The squares the piece stands on after each move, starting with `from` and ending
+///          with `to`, so that the number of moves is one less than the length. `None` if the
+///          piece cannot reach `to`.
+pub fn shortest_path(board: &Board, piece: Piece, from: BoardPosition, to: BoardPosition,
+                     ignore_pieces: bool) -> Option<Vec<BoardPosition>>
+{
+    let mut board = if ignore_pieces { Board::empty_board() } else { board.clone() };
+    board.set_piece(from, None);
+    // the square each visited square was first reached from, by index
+    let mut previous: [Option<BoardPosition>; 64] = [None; 64];
+    let mut visited = BoardBitmap::all_zeros();
+    visited.set(from, true);
+    let mut queue = VecDeque::from([from]);
+    while let Some(pos) = queue.pop_front() {
+        if pos == to {
+            let mut path = alloc::vec![to];
+            let mut current = to;
+            while let Some(square) = previous[current.index() as usize] {
+                path.push(square);
+                current = square;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for target in path_steps(&board, piece, pos, to) {
+            if !visited.get(target) {
+                visited.set(target, true);
+                previous[target.index() as usize] = Some(pos);
+                queue.push_back(target);
+            }
+        }
+    }
+    None
+}
+
+/// returns: The squares a piece on `pos` may move to in one move of a [shortest_path] to `to`.
+fn path_steps(board: &Board, piece: Piece, pos: BoardPosition,
+              to: BoardPosition) -> Vec<BoardPosition>
+{
+    let mut steps = Vec::new();
+    let mut iter = BoardLineIterator::new(pos, move_patterns::get_board_lines(piece,
+                                                                              Variant::Standard));
+    while let Some(target_square) = iter.next() {
+        let target = target_square.position;
+        match board.get_occupant_state(target, piece.player) {
+            OccupantState::Empty => if matches!(
+                target_square.capture_type,
+                CaptureType::Normal | CaptureType::MoveOnly
+            ) {
+                steps.push(target);
+            },
+            OccupantState::Friendly => iter.skip_line(),
+            OccupantState::Enemy => {
+                if target == to && piece.piece_type != PieceType::Pawn {
+                    steps.push(target);
+                }
+                iter.skip_line();
+            }
+        }
+    }
+    if piece.piece_type == PieceType::Pawn
+        && let Some((forward, double_move)) = is_first_move_pawn(piece.player, pos,
+                                                                 Variant::Standard)
+        && board.get_piece(forward).is_none() && board.get_piece(double_move).is_none()
+    {
+        steps.push(double_move);
+    }
+    steps
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert_board_eq;
+
+    fn path(board: &Board, piece: char, from: &str, to: &str,
+            ignore_pieces: bool) -> Option<Vec<String>>
+    {
+        let piece = Piece::from_char(piece).unwrap();
+        shortest_path(board, piece, from.parse().unwrap(), to.parse().unwrap(), ignore_pieces)
+            .map(|path| path.iter().map(BoardPosition::to_string).collect())
+    }
 
     #[test]
+    fn shortest_paths() {
+        let empty = Board::empty_board();
+        let knight = path(&empty, 'N', "a1", "h8", true).unwrap();
+        assert_eq!(knight.len(), 7);
+        assert_eq!((knight[0].as_str(), knight[6].as_str()), ("a1", "h8"));
+        assert_eq!(path(&empty, 'n', "e4", "e4", false), Some(vec!["e4".to_string()]));
+        assert_eq!(path(&empty, 'B', "a1", "h1", true), None);
+        assert_eq!(path(&empty, 'B', "a1", "a3", true).map(|path| path.len()), Some(3));
+        assert_eq!(path(&empty, 'Q', "a1", "h8", true), Some(vec!["a1".into(), "h8".into()]));
+        assert_eq!(path(&empty, 'K', "a1", "h8", true).map(|path| path.len()), Some(8));
+
+        // the rook has to go around its own pawn, but may capture the enemy rook on a8
+        let board = Board::from_fen_string("r7/8/8/8/P7/8/8/R7").unwrap();
+        assert_eq!(path(&board, 'R', "a1", "a8", true), Some(vec!["a1".into(), "a8".into()]));
+        assert_eq!(path(&board, 'R', "a1", "a8", false).map(|path| path.len()), Some(4));
+        assert_eq!(path(&board, 'R', "a1", "a4", false), None);
+        assert_eq!(path(&board, 'r', "a8", "a1", false).map(|path| path.len()), Some(4));
+        let boxed_in = Board::from_fen_string("8/8/8/8/8/8/PP6/RN6").unwrap();
+        assert_eq!(path(&boxed_in, 'R', "a1", "h8", false), None);
+        assert_eq!(path(&boxed_in, 'R', "a1", "h8", true).map(|path| path.len()), Some(3));
+
+        // pawns only move forward, with a double move from their starting rank
+        let board = Board::from_fen_string("8/8/8/8/8/3p4/8/8").unwrap();
+        assert_eq!(path(&board, 'P', "e2", "e5", false),
+                   Some(vec!["e2".into(), "e4".into(), "e5".into()]));
+        assert_eq!(path(&board, 'P', "d2", "d4", false), None);
+        assert_eq!(path(&board, 'P', "d2", "d4", true), Some(vec!["d2".into(), "d4".into()]));
+        assert_eq!(path(&board, 'P', "e4", "e3", true), None);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn is_in_check_test() {
-        assert_eq!(is_in_check(&Board::default_board(), PlayerColor::White), false);
+        assert_eq!(is_in_check(&Board::default_board(), PlayerColor::White, Variant::Standard),
+                   false);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "rnbqkbnr/ppp2ppp/4p3/1B1p4/4P1Q1/8/PPPP1PPP/RNB1K1NR"
-        ).unwrap(), PlayerColor::Black), true);
+        ).unwrap(), PlayerColor::Black, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/8/8/8/8/2Kk4/8/8"
-        ).unwrap(), PlayerColor::White), true);
+        ).unwrap(), PlayerColor::White, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/8/8/8/8/2Kk4/8/8"
-        ).unwrap(), PlayerColor::Black), true);
+        ).unwrap(), PlayerColor::Black, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "1n3qrb/p3pppp/1np1k3/2KQ1P2/1pbr4/8/PPP1PPPP/NNR1B1RB"
-        ).unwrap(), PlayerColor::White), false);
+        ).unwrap(), PlayerColor::White, Variant::Standard), false);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "1n3qrb/p3pppp/1np1k3/1K1Q1P2/1pbr4/8/PPP1PPPP/NNR1B1RB"
-        ).unwrap(), PlayerColor::White), true);
+        ).unwrap(), PlayerColor::White, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/8/8/2kn4/8/2K5/8/8"
-        ).unwrap(), PlayerColor::White), true);
+        ).unwrap(), PlayerColor::White, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/4n3/8/2k5/8/2K5/8/8"
-        ).unwrap(), PlayerColor::White), false);
+        ).unwrap(), PlayerColor::White, Variant::Standard), false);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/8/2k5/8/2KN4/8/8/8"
-        ).unwrap(), PlayerColor::Black), true);
+        ).unwrap(), PlayerColor::Black, Variant::Standard), true);
         assert_eq!(is_in_check(&Board::from_fen_string(
             "8/8/2k5/8/2K5/8/4N3/8"
-        ).unwrap(), PlayerColor::Black), false);
+        ).unwrap(), PlayerColor::Black, Variant::Standard), false);
+    }
+
+    #[test]
+    fn rook_move_removes_castling_rights() {
+        fn test_move(active_player: PlayerColor, from: (u8, u8), to: (u8, u8),
+                     removes_queenside: bool, removes_kingside: bool)
+        {
+            let mut board = Board::from_fen_string("r3k2r/8/8/8/8/8/8/R3K2R").unwrap();
+            let chess_move = ChessMove {
+                piece_movement: PieceMovement::try_from((from, to)).unwrap(),
+                promotion: None,
+                drop: None,
+            };
+            let move_context = MoveContext {
+                castling_rights: CastlingRights::default(),
+                en_passant_target: None,
+                variant: Variant::Standard,
+            };
+            let result = do_move(&mut board, active_player, chess_move, move_context).unwrap();
+            assert_eq!(result.removes_queenside_castling_rights, removes_queenside);
+            assert_eq!(result.removes_kingside_castling_rights, removes_kingside);
+        }
+        test_move(PlayerColor::White, (0, 0), (0, 5), true, false);
+        test_move(PlayerColor::White, (7, 0), (6, 0), false, true);
+        test_move(PlayerColor::Black, (0, 7), (1, 7), true, false);
+        test_move(PlayerColor::Black, (7, 7), (7, 1), false, true);
+        test_move(PlayerColor::White, (4, 0), (3, 1), true, true);
+    }
+
+    #[test]
+    fn rook_returning_to_its_corner_can_not_castle() {
+        let board = Board::from_fen_string("r3k2r/8/8/8/8/8/8/R3K2R").unwrap();
+        let mut game = crate::chess::ChessGame::new(board);
+        // both queenside rooks move away and back
+        let moves = [((0, 0), (0, 1)), ((0, 7), (0, 6)), ((0, 1), (0, 0)), ((0, 6), (0, 7))];
+        for (from, to) in moves {
+            game.do_move(ChessMove {
+                piece_movement: PieceMovement::try_from((from, to)).unwrap(),
+                promotion: None,
+                drop: None,
+            }).unwrap();
+        }
+        let king_moves = game.available_moves(BoardPosition::try_from((4, 0)).unwrap());
+        assert!(!king_moves.get(BoardPosition::try_from((2, 0)).unwrap()));
+        assert!(king_moves.get(BoardPosition::try_from((6, 0)).unwrap()));
     }
 
     #[test]
@@ -548,9 +996,10 @@ mod tests {
                       expected_value: bool)
         {
             let mut cloned_board = board.clone();
-            assert_eq!(leads_to_check(&mut cloned_board, active_player, piece_movement),
+            assert_eq!(leads_to_check(&mut cloned_board, active_player, piece_movement,
+                                      Variant::Standard),
                        expected_value);
-            assert_eq!(cloned_board, board);
+            assert_board_eq!(cloned_board, board);
         }
 
         test_board(Board::default_board(), PlayerColor::White,
@@ -558,8 +1007,16 @@ mod tests {
         test_board(Board::from_fen_string("rnbq1bnr/pppppppp/4k3/8/3P4/8/PPP1PPPP/RNBQKBNR")
                        .unwrap(), PlayerColor::Black,
                    PieceMovement::try_from(((4, 5), (4, 4))).unwrap(), true);
-        test_board(Board::from_fen_string("8/2b1n3/3R1r2/4K3/6k1/8/8/8")
-                       .unwrap(), PlayerColor::White,
+        test_board(crate::board! {
+                       ". . . . . . . ." /
+                       ". . b . n . . ." /
+                       ". . . R . r . ." /
+                       ". . . . K . . ." /
+                       ". . . . . . k ." /
+                       ". . . . . . . ." /
+                       ". . . . . . . ." /
+                       ". . . . . . . ."
+                   }, PlayerColor::White,
                    PieceMovement::try_from(((3, 5), (5, 5))).unwrap(), true);
         test_board(Board::from_fen_string("8/2b1n3/2R2r2/4K3/6k1/8/8/8")
                        .unwrap(), PlayerColor::White,
@@ -579,6 +1036,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn get_available_moves_test() {
         fn test_board(mut board: Board, active_player: PlayerColor, pos: &str,
                       move_context: Option<MoveContext>, squares: &[&str])
@@ -587,6 +1045,7 @@ mod tests {
             let move_context = move_context.unwrap_or(MoveContext {
                 castling_rights: CastlingRights::default(),
                 en_passant_target: None,
+                variant: Variant::Standard,
             });
             let mut bitmap = BoardBitmap::all_zeros();
             for square in squares {
@@ -711,6 +1170,7 @@ mod tests {
         let context_2 = Some(MoveContext {
             castling_rights: CastlingRights::default(),
             en_passant_target: Some(BoardPosition::try_from("d6").unwrap()),
+            variant: Variant::Standard,
         });
         test_board(board_2.clone(), PlayerColor::White, "a1", context_2,
                    &["b1", "c1", "d1"],
@@ -757,26 +1217,56 @@ mod tests {
 
         // en passant
         test_board(
-            Board::from_fen_string("k7/8/8/8/8/4Pp2/8/K7").unwrap(),
+            crate::board! {
+                "k . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . P p . ." /
+                ". . . . . . . ." /
+                "K . . . . . . ."
+            },
             PlayerColor::Black, "f3", Some(MoveContext {
                 castling_rights: Default::default(),
                 en_passant_target: Some(BoardPosition::try_from("e2").unwrap()),
+                variant: Variant::Standard,
             }),
             &["e2", "f2"],
         );
         test_board(
-            Board::from_fen_string("8/8/8/8/8/3RPpk1/8/K7").unwrap(),
+            crate::board! {
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . R P p k ." /
+                ". . . . . . . ." /
+                "K . . . . . . ."
+            },
             PlayerColor::Black, "f3", Some(MoveContext {
                 castling_rights: Default::default(),
                 en_passant_target: Some(BoardPosition::try_from("e2").unwrap()),
+                variant: Variant::Standard,
             }),
             &["f2"],
         );
         test_board(
-            Board::from_fen_string("8/8/8/8/8/4Ppk1/6R1/K7").unwrap(),
+            crate::board! {
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . . . . ." /
+                ". . . . P p k ." /
+                ". . . . . . R ." /
+                "K . . . . . . ."
+            },
             PlayerColor::Black, "f3", Some(MoveContext {
                 castling_rights: Default::default(),
                 en_passant_target: Some(BoardPosition::try_from("e2").unwrap()),
+                variant: Variant::Standard,
             }),
             &["g2"],
         );
@@ -790,6 +1280,7 @@ mod tests {
                     kingside: false,
                 },
                 en_passant_target: None,
+                variant: Variant::Standard,
             }),
             &["d1", "d2", "e2", "f1", "f2"],
         );
@@ -801,6 +1292,7 @@ mod tests {
                     kingside: false,
                 },
                 en_passant_target: None,
+                variant: Variant::Standard,
             }),
             &["c1", "d1", "d2", "e2", "f1", "f2"],
         );
@@ -812,6 +1304,7 @@ mod tests {
                     kingside: true,
                 },
                 en_passant_target: None,
+                variant: Variant::Standard,
             }),
             &["d1", "d2", "e2", "f1", "f2"],
         );
@@ -823,6 +1316,7 @@ mod tests {
                     kingside: true,
                 },
                 en_passant_target: None,
+                variant: Variant::Standard,
             }),
             &["c1", "d1", "d2", "e2", "f1", "f2"],
         );
@@ -880,8 +1374,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn promotion_type_conversions() {
+        for promotion in PromotionType::ALL {
+            assert_eq!(PromotionType::try_from(promotion.to_char()), Ok(promotion));
+            assert_eq!(PromotionType::try_from(promotion.to_char().to_ascii_lowercase()),
+                       Ok(promotion));
+            assert_eq!(PromotionType::try_from(PieceType::from(promotion)), Ok(promotion));
+            assert_eq!(promotion.to_string(), promotion.to_char().to_string());
+            for player in [PlayerColor::White, PlayerColor::Black] {
+                let piece = promotion.to_piece(player);
+                assert_eq!(piece.player, player);
+                assert_eq!(PromotionType::try_from(piece.piece_type), Ok(promotion));
+            }
+        }
+        assert_eq!(PromotionType::Queen.to_string(), "Q");
+        assert_eq!(PromotionType::try_from('n'), Ok(PromotionType::Knight));
+        assert_eq!(PromotionType::try_from('K'), Err(PieceParseError::InvalidLetter('K')));
+        assert_eq!(PromotionType::try_from('p'), Err(PieceParseError::InvalidLetter('p')));
+        assert_eq!(PromotionType::try_from('x'), Err(PieceParseError::InvalidLetter('x')));
+        assert_eq!(PromotionType::try_from(PieceType::King), Err(()));
+
+        // fails to compile if a variant is added without updating this match
+        let letters: String = PromotionType::ALL.iter().map(|promotion| match promotion {
+            PromotionType::Knight => 'N',
+            PromotionType::Bishop => 'B',
+            PromotionType::Rook => 'R',
+            PromotionType::Queen => 'Q',
+        }).collect();
+        assert_eq!(letters, "NBRQ");
+    }
+
+    #[test]
+    fn piece_movement_parse() {
+        let e2e4 = PieceMovement::new("e2", "e4").unwrap();
+        assert_eq!(e2e4, PieceMovement::try_from(((4, 1), (4, 3))).unwrap());
+        assert_eq!(e2e4.to_string(), "e2e4");
+        assert_eq!("e2e4".parse(), Ok(e2e4));
+        assert_eq!("e2-e4".parse(), Ok(e2e4));
+        assert_eq!("E2E4".parse(), Ok(e2e4));
+        for s in ["a1h8", "h8a1", "b7b8", "d4d4"] {
+            assert_eq!(s.parse::<PieceMovement>().unwrap().to_string(), s);
+        }
+
+        assert_eq!(e2e4.reversed(), PieceMovement::new("e4", "e2").unwrap());
+        assert_eq!(e2e4.reversed().reversed(), e2e4);
+
+        assert_eq!(PieceMovement::new("e2", "e9"), Err(PositionParseError::InvalidRank('9')));
+        assert_eq!(PieceMovement::new("x2", "e9"), Err(PositionParseError::InvalidFile('x')));
+        assert_eq!(PieceMovement::new("e2", ""), Err(PositionParseError::WrongLength));
+        assert_eq!("e2e".parse::<PieceMovement>(), Err(PositionParseError::WrongLength));
+        assert_eq!("e2e4e".parse::<PieceMovement>(), Err(PositionParseError::WrongLength));
+        assert_eq!("e2+e4".parse::<PieceMovement>(), Err(PositionParseError::WrongLength));
+        assert_eq!("e2e0".parse::<PieceMovement>(), Err(PositionParseError::InvalidRank('0')));
+        assert_eq!("".parse::<PieceMovement>(), Err(PositionParseError::WrongLength));
+        assert_eq!("é2e4".parse::<PieceMovement>(), Err(PositionParseError::InvalidFile('é')));
+    }
+
+    #[test]
+    fn chess_move_constructors() {
+        let e2: BoardPosition = "e2".parse().unwrap();
+        let e4: BoardPosition = "e4".parse().unwrap();
+        assert_eq!(ChessMove::new(e2, e4), ChessMove {
+            piece_movement: PieceMovement { from: e2, to: e4 },
+            promotion: None,
+            drop: None,
+        });
+        assert_eq!(ChessMove::from_coords("e2", "e4"), Ok(ChessMove::new(e2, e4)));
+        assert_eq!(ChessMove::from_coords("e2", "e9"), Err(PositionParseError::InvalidRank('9')));
+        assert_eq!(ChessMove::promoting(e2, e4, PromotionType::Rook), ChessMove {
+            piece_movement: PieceMovement { from: e2, to: e4 },
+            promotion: Some(PromotionType::Rook),
+            drop: None,
+        });
+        assert_ne!(ChessMove::promoting(e2, e4, PromotionType::Rook), ChessMove::new(e2, e4));
+        let drop = ChessMove::dropping(PieceType::Knight, e4);
+        assert_eq!(drop.piece_movement, PieceMovement { from: e4, to: e4 });
+        assert!(drop.is_drop() && !ChessMove::new(e4, e4).is_drop());
+        assert_eq!(drop.to_string(), "N@e4");
+    }
+
+    #[test]
+    fn moves_as_keys() {
+        let e2e4 = ChessMove::from_coords("e2", "e4").unwrap();
+        let g7 = "g7".parse().unwrap();
+        let g8 = "g8".parse().unwrap();
+        let g7g8q = ChessMove::promoting(g7, g8, PromotionType::Queen);
+        let g7g8n = ChessMove::promoting(g7, g8, PromotionType::Knight);
+
+        let mut counts = std::collections::HashMap::new();
+        for chess_move in [e2e4, g7g8q, e2e4, g7g8n, e2e4] {
+            *counts.entry(chess_move).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&e2e4], 3);
+        assert_eq!(counts[&g7g8q], 1);
+        assert_eq!(counts[&g7g8n], 1);
+
+        let mut movements = vec![
+            g7g8q.piece_movement,
+            PieceMovement::try_from(((4, 1), (4, 2))).unwrap(),
+            e2e4.piece_movement,
+            PieceMovement::try_from(((1, 0), (2, 2))).unwrap(),
+        ];
+        movements.sort();
+        assert_eq!(movements, [
+            PieceMovement::try_from(((1, 0), (2, 2))).unwrap(),
+            PieceMovement::try_from(((4, 1), (4, 2))).unwrap(),
+            e2e4.piece_movement,
+            g7g8q.piece_movement,
+        ]);
+    }
+
     #[test]
     fn do_move_test() {
+        #[allow(clippy::too_many_arguments)]
         fn test_board(board_before: &str, board_after: &str, active_player: PlayerColor, from: &str,
                       to: &str, captured_piece_expected: Option<char>,
                       en_passant_target: Option<&str>, promotion: Option<PromotionType>)
@@ -897,23 +1504,24 @@ mod tests {
             let move_result = do_move(
                 &mut board,
                 active_player,
-                ChessMove { piece_movement, promotion },
-                MoveContext { castling_rights: CastlingRights::default(), en_passant_target }
+                ChessMove { piece_movement, promotion, drop: None },
+                MoveContext {
+                    castling_rights: CastlingRights::default(),
+                    en_passant_target,
+                    variant: Variant::Standard,
+                }
             ).unwrap();
-            let captured_piece = move_result.captured_piece;
-            assert_eq!(
-                board, expected,
-                "from: {}, to: {},\nbefore: {},\nexpected: {},\ngot: {}",
-                from,
-                to,
-                before,
-                expected,
-                board,
-            );
+            let captured_piece = move_result.removed_piece.map(|(_, piece)| piece);
+            assert_board_eq!(board, expected, "from: {}, to: {}", from, to);
             assert_eq!(
                 captured_piece,
                 captured_piece_expected.map(|s| Piece::from_char(s).unwrap()),
             );
+
+            let moved_piece = before.get_piece(piece_movement.from).unwrap();
+            undo_move(&mut board, piece_movement, moved_piece, move_result.removed_piece,
+                      move_result.secondary_movement);
+            assert_board_eq!(board, before, "undo from: {}, to: {}", from, to);
         }
 
         test_board(
@@ -967,4 +1575,85 @@ mod tests {
             "2kr1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
             PlayerColor::Black, "e8", "c8", None, None, None);
     }
+
+    #[test]
+    fn en_passant_legality() {
+        use alloc::string::String;
+        use crate::chess::ChessGame;
+
+        /// returns: The FEN of the position mirrored vertically with the colors swapped, and the
+        ///          mirrored square.
+        fn mirrored(fen: &str, pos: BoardPosition) -> (String, BoardPosition) {
+            let fields: Vec<&str> = fen.split(' ').collect();
+            let swap_case = |c: char| if c.is_ascii_uppercase() {
+                c.to_ascii_lowercase()
+            } else {
+                c.to_ascii_uppercase()
+            };
+            let placement: Vec<String> = fields[0].split('/').rev()
+                .map(|rank| rank.chars().map(swap_case).collect())
+                .collect();
+            let side = if fields[1] == "w" { "b" } else { "w" };
+            let ep = BoardPosition::try_from(fields[3]).unwrap();
+            let flip = |pos: BoardPosition| BoardPosition {
+                file: pos.file,
+                rank: U3::from_low_bits(7 - pos.rank.get()),
+            };
+            let fen = alloc::format!("{} {} - {} 0 1", placement.join("/"), side, flip(ep));
+            (fen, flip(pos))
+        }
+
+        fn check(fen: &str, from: BoardPosition, expected: bool) {
+            let mut game = ChessGame::from_fen(fen).unwrap();
+            let player = game.active_player();
+            let target = game.en_passant_target().unwrap();
+            assert_eq!(is_en_passant_legal(game.board(), player, from, target), expected,
+                       "{}", fen);
+            assert_eq!(game.available_moves(from).get(target), expected, "{}", fen);
+            assert_eq!(game.do_move(ChessMove::new(from, target)).is_ok(), expected, "{}", fen);
+        }
+
+        // white to move after black's d7d5, capturing with the pawn on the given square
+        let table = [
+            // the king and a rook or queen on the fifth rank, with both pawns between them
+            ("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1", "e5", false),
+            ("8/8/8/K2pP2q/8/8/8/7k w - d6 0 1", "e5", false),
+            ("8/8/8/r2pP2K/8/8/8/k7 w - d6 0 1", "e5", false),
+            ("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", "b5", false),
+            // another piece on the rank still blocks, and a bishop does not attack along it
+            ("8/8/8/K1NpP2r/8/8/8/7k w - d6 0 1", "e5", true),
+            ("8/8/8/K2pPP1r/8/8/8/7k w - d6 0 1", "e5", true),
+            ("8/8/8/K2pP2b/8/8/8/7k w - d6 0 1", "e5", true),
+            // a diagonal through the square of the captured pawn
+            ("8/5b2/8/3pP3/8/1K6/8/7k w - d6 0 1", "e5", false),
+            ("8/5q2/8/3pP3/8/1K6/8/7k w - d6 0 1", "e5", false),
+            // the capturing pawn pinned along a diagonal, which it may only move along
+            ("8/6b1/8/3pP3/8/2K5/8/7k w - d6 0 1", "e5", false),
+            ("1b6/8/8/3pP3/5K2/8/8/7k w - d6 0 1", "e5", true),
+            // the double move gave check, which the capture removes
+            ("8/8/8/3pP3/4K3/8/8/7k w - d6 0 1", "e5", true),
+            // a check from a rook, which the capture does not remove
+            ("8/8/8/3pP3/8/8/8/k2rK3 w - d6 0 1", "e5", false),
+            ("r7/8/8/3pP3/K7/8/8/7k w - d6 0 1", "e5", false),
+            // a discovered check along the diagonal behind the pawn, which the capture blocks
+            ("7k/8/8/3pP3/8/8/8/Kb6 w - d6 0 1", "e5", true),
+            // a pawn not next to the captured pawn
+            ("8/8/8/3p1P2/8/8/8/K6k w - d6 0 1", "f5", false),
+        ];
+        for (fen, from, expected) in table {
+            let from = BoardPosition::try_from(from).unwrap();
+            check(fen, from, expected);
+            let (fen, from) = mirrored(fen, from);
+            check(&fen, from, expected);
+        }
+
+        // positions which are not an en passant capture at all
+        let board = Board::from_fen_string("8/8/8/K2pP3/8/8/8/7k").unwrap();
+        let pos = |s: &str| BoardPosition::try_from(s).unwrap();
+        assert!(is_en_passant_legal(&board, PlayerColor::White, pos("e5"), pos("d6")));
+        assert!(!is_en_passant_legal(&board, PlayerColor::Black, pos("e5"), pos("d6")));
+        assert!(!is_en_passant_legal(&board, PlayerColor::White, pos("e5"), pos("f6")));
+        assert!(!is_en_passant_legal(&board, PlayerColor::White, pos("d5"), pos("c6")));
+        assert!(!is_en_passant_legal(&board, PlayerColor::White, pos("e5"), pos("d4")));
+    }
 }
@@ -1,63 +1,83 @@
 use crate::board::board_pos::{BoardLine, CaptureType};
+use crate::board::board_pos::Direction::*;
+use crate::board::board_pos::KnightDirection::*;
 use crate::board::piece::{Piece, PieceType, PlayerColor};
+use crate::chess::variant::Variant;
 
 pub const WHITE_PAWN_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (0, 1), max_length: 1, capture_type: CaptureType::MoveOnly },
-    BoardLine { offset: (1, 1), max_length: 1, capture_type: CaptureType::CaptureOnly },
-    BoardLine { offset: (-1, 1), max_length: 1, capture_type: CaptureType::CaptureOnly },
+    BoardLine { offset: North.offset(), max_length: 1, capture_type: CaptureType::MoveOnly },
+    BoardLine { offset: NorthEast.offset(), max_length: 1, capture_type: CaptureType::CaptureOnly },
+    BoardLine { offset: NorthWest.offset(), max_length: 1, capture_type: CaptureType::CaptureOnly },
 ];
 
 pub const BLACK_PAWN_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (0, -1), max_length: 1, capture_type: CaptureType::MoveOnly },
-    BoardLine { offset: (1, -1), max_length: 1, capture_type: CaptureType::CaptureOnly },
-    BoardLine { offset: (-1, -1), max_length: 1, capture_type: CaptureType::CaptureOnly },
+    BoardLine { offset: South.offset(), max_length: 1, capture_type: CaptureType::MoveOnly },
+    BoardLine { offset: SouthEast.offset(), max_length: 1, capture_type: CaptureType::CaptureOnly },
+    BoardLine { offset: SouthWest.offset(), max_length: 1, capture_type: CaptureType::CaptureOnly },
 ];
 
 pub const ROOK_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (1, 0), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 0), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, -1), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: East.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: North.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: West.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: South.offset(), max_length: 7, capture_type: CaptureType::Normal },
 ];
 
 pub const KNIGHT_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (1, 2), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 2), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-2, 1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-2, -1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, -2), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, -2), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (2, -1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (2, 1), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthNorthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthNorthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: WestNorthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: WestSouthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthSouthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthSouthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: EastSouthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: EastNorthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
 ];
 
 pub const BISHOP_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (1, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, -1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, -1), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthEast.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthWest.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthWest.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthEast.offset(), max_length: 7, capture_type: CaptureType::Normal },
 ];
 
 pub const QUEEN_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (1, 0), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 0), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, -1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, -1), max_length: 7, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, -1), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: East.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: North.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: West.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: South.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthEast.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthWest.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthWest.offset(), max_length: 7, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthEast.offset(), max_length: 7, capture_type: CaptureType::Normal },
 ];
 
 pub const KING_BOARD_LINES: &[BoardLine] = &[
-    BoardLine { offset: (1, 0), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, 1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 0), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (0, -1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, 1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, 1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (-1, -1), max_length: 1, capture_type: CaptureType::Normal },
-    BoardLine { offset: (1, -1), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: East.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: North.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: West.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: South.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+];
+
+/// The moves of the fers, which replaces the queen in [Shatranj](Variant::Shatranj).
+pub const FERS_BOARD_LINES: &[BoardLine] = &[
+    BoardLine { offset: NorthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: NorthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthWest.offset(), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: SouthEast.offset(), max_length: 1, capture_type: CaptureType::Normal },
+];
+
+/// The moves of the alfil, which replaces the bishop in [Shatranj](Variant::Shatranj). The alfil
+/// jumps exactly two squares diagonally.
+pub const ALFIL_BOARD_LINES: &[BoardLine] = &[
+    BoardLine { offset: (2, 2), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: (-2, 2), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: (-2, -2), max_length: 1, capture_type: CaptureType::Normal },
+    BoardLine { offset: (2, -2), max_length: 1, capture_type: CaptureType::Normal },
 ];
 
 pub const WHITE_KING_CHECK_BOARD_LINES: &[(PieceType, &[BoardLine])] = &[
@@ -78,7 +98,43 @@ pub const BLACK_KING_CHECK_BOARD_LINES: &[(PieceType, &[BoardLine])] = &[
     (PieceType::King, KING_BOARD_LINES),
 ];
 
-pub(crate) fn get_board_lines(piece: Piece) -> &'static [BoardLine] {
+pub const WHITE_SHATRANJ_KING_CHECK_BOARD_LINES: &[(PieceType, &[BoardLine])] = &[
+    (PieceType::Pawn, WHITE_PAWN_BOARD_LINES),
+    (PieceType::Rook, ROOK_BOARD_LINES),
+    (PieceType::Knight, KNIGHT_BOARD_LINES),
+    (PieceType::Bishop, ALFIL_BOARD_LINES),
+    (PieceType::Queen, FERS_BOARD_LINES),
+    (PieceType::King, KING_BOARD_LINES),
+];
+
+pub const BLACK_SHATRANJ_KING_CHECK_BOARD_LINES: &[(PieceType, &[BoardLine])] = &[
+    (PieceType::Pawn, BLACK_PAWN_BOARD_LINES),
+    (PieceType::Rook, ROOK_BOARD_LINES),
+    (PieceType::Knight, KNIGHT_BOARD_LINES),
+    (PieceType::Bishop, ALFIL_BOARD_LINES),
+    (PieceType::Queen, FERS_BOARD_LINES),
+    (PieceType::King, KING_BOARD_LINES),
+];
+
+/// returns: The board lines to search for enemy pieces of each type which attack a square
+///          occupied by `player`.
+pub(crate) fn get_king_check_board_lines(player: PlayerColor, variant: Variant)
+                                         -> &'static [(PieceType, &'static [BoardLine])]
+{
+    match (player, variant) {
+        (PlayerColor::White, Variant::Shatranj) => WHITE_SHATRANJ_KING_CHECK_BOARD_LINES,
+        (PlayerColor::Black, Variant::Shatranj) => BLACK_SHATRANJ_KING_CHECK_BOARD_LINES,
+        (PlayerColor::White, _) => WHITE_KING_CHECK_BOARD_LINES,
+        (PlayerColor::Black, _) => BLACK_KING_CHECK_BOARD_LINES,
+    }
+}
+
+pub(crate) fn get_board_lines(piece: Piece, variant: Variant) -> &'static [BoardLine] {
+    match (piece.piece_type, variant) {
+        (PieceType::Bishop, Variant::Shatranj) => return ALFIL_BOARD_LINES,
+        (PieceType::Queen, Variant::Shatranj) => return FERS_BOARD_LINES,
+        _ => {}
+    }
     match piece {
         Piece { piece_type: PieceType::Pawn, player: PlayerColor::White } => WHITE_PAWN_BOARD_LINES,
         Piece { piece_type: PieceType::Pawn, player: PlayerColor::Black } => BLACK_PAWN_BOARD_LINES,
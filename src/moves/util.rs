@@ -1,8 +1,13 @@
 //! Utility bitmap types used in the `moves` module.
 
-use std::fmt::{Debug, Display, Formatter};
-use crate::board::board_pos::BoardPosition;
-use crate::util::U6;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::fmt::{Debug, Display, Formatter};
+use core::str::FromStr;
+use thiserror::Error;
+use crate::board::board_pos::{BoardPosition, PositionParseError, SquareColor};
+use crate::util::{U3, U6};
 
 #[derive(Copy, Clone, Eq, PartialEq, Default)]
 struct Bitmap64 {
@@ -34,7 +39,7 @@ impl Bitmap64 {
 }
 
 impl Debug for Bitmap64 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:064b}", self.data)
     }
 }
@@ -48,7 +53,9 @@ pub struct BoardBitmap {
 impl BoardBitmap {
     /// returns: A bitmap with `false` assigned to each square.
     pub fn all_zeros() -> BoardBitmap {
-        BoardBitmap::default()
+        BoardBitmap {
+            bitmap: Bitmap64::all_zeros()
+        }
     }
 
     /// returns: A bitmap with `true` assigned to each square.
@@ -58,6 +65,25 @@ impl BoardBitmap {
         }
     }
 
+    /// returns: A bitmap with `true` assigned to each light square.
+    pub fn light_squares() -> BoardBitmap {
+        BoardBitmap::squares_of_color(SquareColor::Light)
+    }
+
+    /// returns: A bitmap with `true` assigned to each dark square.
+    pub fn dark_squares() -> BoardBitmap {
+        BoardBitmap::squares_of_color(SquareColor::Dark)
+    }
+
+    /// returns: A bitmap with `true` assigned to each square of the given color.
+    pub fn squares_of_color(color: SquareColor) -> BoardBitmap {
+        let mut bitmap = BoardBitmap::all_zeros();
+        for pos in BoardPosition::all() {
+            bitmap.set(pos, pos.square_color() == color);
+        }
+        bitmap
+    }
+
     /// returns: The boolean value which a given square maps to.
     pub fn get(&self, index: BoardPosition) -> bool {
         self.bitmap.get(index.into())
@@ -72,15 +98,125 @@ impl BoardBitmap {
     pub fn is_all_zeros(&self) -> bool {
         self.bitmap.data == 0x0000_0000_0000_0000
     }
+
+    /// returns: The underlying `u64` value, where bit `n` corresponds to the square with file
+    /// `n / 8` and rank `n % 8`.
+    pub fn to_u64(&self) -> u64 {
+        self.bitmap.data
+    }
+
+    /// returns: A bitmap from its underlying `u64` value. See [to_u64](BoardBitmap::to_u64).
+    pub const fn from_u64(data: u64) -> BoardBitmap {
+        BoardBitmap {
+            bitmap: Bitmap64 { data }
+        }
+    }
+
+    /// returns: The bitmap with the ranks reversed, so that the value of a1 is moved to a8, b2 to
+    ///          b7, and so on, like [Board::flip_vertical](crate::board::Board::flip_vertical).
+    pub fn flip_vertical(&self) -> BoardBitmap {
+        // each byte holds the ranks of one file
+        BoardBitmap::from_u64(self.bitmap.data.reverse_bits().swap_bytes())
+    }
+
+    /// returns: The bitmap with the files reversed, so that the value of a1 is moved to h1, b2 to
+    ///          g2, and so on, like
+    ///          [Board::mirror_horizontal](crate::board::Board::mirror_horizontal).
+    pub fn mirror_horizontal(&self) -> BoardBitmap {
+        BoardBitmap::from_u64(self.bitmap.data.swap_bytes())
+    }
+
+    /// returns: The bitmap as a hexadecimal string of the underlying `u64` value, e.g.
+    /// `"0x0000000000000081"`.
+    pub fn to_hex(&self) -> String {
+        format!("0x{:016X}", self.bitmap.data)
+    }
+
+    /// Parses a bitmap from the format produced by [to_hex](BoardBitmap::to_hex). The `0x` prefix
+    /// is required, but leading zeros may be left out, and both uppercase and lowercase digits are
+    /// accepted.
+    ///
+    /// returns: `Ok(BoardBitmap)` if the string was parsed successfully, otherwise
+    ///          [InvalidHex](BitmapParseError::InvalidHex).
+    pub fn from_hex(string: &str) -> Result<BoardBitmap, BitmapParseError> {
+        let digits = string.strip_prefix("0x").or_else(|| string.strip_prefix("0X"))
+            .ok_or(BitmapParseError::InvalidHex)?;
+        if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(BitmapParseError::InvalidHex);
+        }
+        u64::from_str_radix(digits, 16)
+            .map(BoardBitmap::from_u64)
+            .map_err(|_| BitmapParseError::InvalidHex)
+    }
+
+    /// returns: The squares mapped to `true` as a comma-separated list, e.g. `"a1,e4,d5"`. The
+    /// squares are listed in the same order as the bits of the underlying `u64` value, that is,
+    /// first by file and then by rank.
+    pub fn to_square_list(&self) -> String {
+        let squares: Vec<String> = BoardPosition::all()
+            .filter(|pos| self.get(*pos))
+            .map(|pos| pos.to_string())
+            .collect();
+        squares.join(",")
+    }
+
+    /// Parses a bitmap from the format produced by [to_square_list](BoardBitmap::to_square_list).
+    /// The squares may be listed in any order, and whitespace around each square is ignored. An
+    /// empty string results in an empty bitmap.
+    ///
+    /// returns: `Ok(BoardBitmap)` if the string was parsed successfully, otherwise
+    ///          [InvalidSquare](BitmapParseError::InvalidSquare) for the first invalid square.
+    pub fn from_square_list(string: &str) -> Result<BoardBitmap, BitmapParseError> {
+        let mut bitmap = BoardBitmap::all_zeros();
+        if string.trim().is_empty() {
+            return Ok(bitmap);
+        }
+        for square in string.split(',') {
+            let pos = square.trim().parse()?;
+            bitmap.set(pos, true);
+        }
+        Ok(bitmap)
+    }
+}
+
+/// An error caused by attempting to parse an invalid string as a [BoardBitmap], in either of
+/// its compact string forms.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BitmapParseError {
+    /// The string started with `0x`, but was not followed by 1 to 16 hexadecimal digits.
+    #[error("expected `0x` followed by 1 to 16 hexadecimal digits")]
+    InvalidHex,
+    /// An entry of the comma-separated square list was not a square name.
+    #[error("invalid square in list: {0}")]
+    InvalidSquare(#[from] PositionParseError),
+}
+
+/// Parses either of the compact string forms of a bitmap: the hexadecimal form if the string
+/// starts with `0x`, and the square list form otherwise.
+impl FromStr for BoardBitmap {
+    type Err = BitmapParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("0x") || s.starts_with("0X") {
+            BoardBitmap::from_hex(s)
+        } else {
+            BoardBitmap::from_square_list(s)
+        }
+    }
 }
 
+/// Displays the bitmap as an 8x8 grid of `0`s and `1`s. The alternate flag (`{:#}`) instead
+/// displays the compact square list (see [to_square_list](BoardBitmap::to_square_list)), padded
+/// to the width and alignment options if given.
 impl Display for BoardBitmap {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for rank in (0u8..8).rev() {
-            write!(f, "\n{}", rank + 1)?;
-            for file in 0u8..8 {
-                let pos = BoardPosition::try_from((file, rank)).unwrap().into();
-                let value = self.bitmap.get(pos);
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return f.pad(&self.to_square_list());
+        }
+        for rank in U3::all().rev() {
+            write!(f, "\n{}", rank.get() + 1)?;
+            for file in U3::all() {
+                let value = self.bitmap.get(U6::from_parts(file, rank));
                 write!(f, " {}", if value { "1" } else { "0" })?;
             }
         }
@@ -89,9 +225,52 @@ impl Display for BoardBitmap {
     }
 }
 
+/// Serializes the bitmap as its hexadecimal string form in human-readable formats, and as its
+/// underlying `u64` value in binary formats.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BoardBitmap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_u64(self.bitmap.data)
+        }
+    }
+}
+
+/// Deserializes the bitmap from either of its compact string forms in human-readable formats,
+/// and from its underlying `u64` value in binary formats.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BoardBitmap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoardBitmapVisitor;
+
+        impl serde::de::Visitor<'_> for BoardBitmapVisitor {
+            type Value = BoardBitmap;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                write!(f, "a hexadecimal string, a comma-separated square list or a u64")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(BoardBitmap::from_u64(v))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BoardBitmapVisitor)
+        } else {
+            deserializer.deserialize_u64(BoardBitmapVisitor)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::util::U3;
     use super::*;
 
     const TEST_POSITION_SET: [BoardPosition; 8] = [
@@ -142,4 +321,70 @@ mod tests {
         ).to_string();
         assert_eq!(format!("{}", bitmap), expected);
     }
+
+    #[test]
+    fn board_bitmap_compact_forms() {
+        let mut bitmap = BoardBitmap::all_zeros();
+        for p in TEST_POSITION_SET {
+            bitmap.set(p, true);
+        }
+
+        assert_eq!(bitmap.to_hex(), "0x1000030021808008");
+        assert_eq!(bitmap.to_square_list(), "a4,b8,c8,d1,d6,f1,f2,h5");
+        assert_eq!(format!("{:#}", bitmap), "a4,b8,c8,d1,d6,f1,f2,h5");
+        assert_eq!(format!("[{:>#26}]", bitmap), "[   a4,b8,c8,d1,d6,f1,f2,h5]");
+        assert_eq!(BoardBitmap::all_zeros().to_square_list(), "");
+
+        assert_eq!(BoardBitmap::from_hex(&bitmap.to_hex()), Ok(bitmap));
+        assert_eq!(BoardBitmap::from_square_list(&bitmap.to_square_list()), Ok(bitmap));
+        assert_eq!(bitmap.to_hex().parse(), Ok(bitmap));
+        assert_eq!(bitmap.to_square_list().parse(), Ok(bitmap));
+        assert_eq!("h5, a4,f2,d6,c8,f1,b8,d1".parse(), Ok(bitmap));
+        assert_eq!("0xff".parse(), Ok(BoardBitmap::from_u64(0xff)));
+        assert_eq!("".parse(), Ok(BoardBitmap::all_zeros()));
+
+        assert_eq!(BoardBitmap::from_hex("0x"), Err(BitmapParseError::InvalidHex));
+        assert_eq!(BoardBitmap::from_hex("ff"), Err(BitmapParseError::InvalidHex));
+        assert_eq!(BoardBitmap::from_hex("0x+ff"), Err(BitmapParseError::InvalidHex));
+        assert_eq!(BoardBitmap::from_hex("0x1ffffffffffffffff"), Err(BitmapParseError::InvalidHex));
+        assert_eq!(BoardBitmap::from_square_list("e4,,d5"),
+                   Err(BitmapParseError::InvalidSquare(PositionParseError::WrongLength)));
+        assert_eq!(BoardBitmap::from_square_list("e4,d9"),
+                   Err(BitmapParseError::InvalidSquare(PositionParseError::InvalidRank('9'))));
+        assert_eq!("0xfg".parse::<BoardBitmap>(), Err(BitmapParseError::InvalidHex));
+        assert_eq!("e4, i5".parse::<BoardBitmap>(),
+                   Err(BitmapParseError::InvalidSquare(PositionParseError::InvalidFile('i'))));
+        assert_eq!("e4, i5".parse::<BoardBitmap>().unwrap_err().to_string(),
+                   "invalid square in list: invalid file `i`: expected a letter a-h");
+    }
+
+    #[test]
+    fn board_bitmap_square_colors() {
+        let light = BoardBitmap::light_squares();
+        let dark = BoardBitmap::dark_squares();
+        assert_eq!(light.to_u64().count_ones(), 32);
+        assert_eq!(dark.to_u64().count_ones(), 32);
+        assert_eq!(light.to_u64() & dark.to_u64(), 0);
+        assert_eq!(light.to_u64() | dark.to_u64(), BoardBitmap::all_ones().to_u64());
+        assert!(dark.get("a1".parse().unwrap()));
+        assert!(light.get("h1".parse().unwrap()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn board_bitmap_serde() {
+        let mut bitmap = BoardBitmap::all_zeros();
+        for p in TEST_POSITION_SET {
+            bitmap.set(p, true);
+        }
+
+        let json = serde_json::to_string(&bitmap).unwrap();
+        assert_eq!(json, "\"0x1000030021808008\"");
+        assert_eq!(serde_json::from_str::<BoardBitmap>(&json).unwrap(), bitmap);
+        assert_eq!(
+            serde_json::from_str::<BoardBitmap>("\"a4,b8,c8,d1,d6,f1,f2,h5\"").unwrap(),
+            bitmap
+        );
+        assert!(serde_json::from_str::<BoardBitmap>("\"e9\"").is_err());
+    }
 }
@@ -0,0 +1,149 @@
+//! Named regions of the board as [BoardBitmap]s, for evaluation, king safety and overlays.
+
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::moves::util::BoardBitmap;
+
+/// The four central squares d4, d5, e4 and e5.
+pub const CENTER: BoardBitmap = region(3, 4, 3, 4);
+
+/// The sixteen squares from c3 to f6, including the [CENTER].
+pub const EXTENDED_CENTER: BoardBitmap = region(2, 5, 2, 5);
+
+/// The files e to h, on the side where the kings castle short.
+pub const KINGSIDE: BoardBitmap = region(4, 7, 0, 7);
+
+/// The files a to d, on the side where the kings castle long.
+pub const QUEENSIDE: BoardBitmap = region(0, 3, 0, 7);
+
+/// The ranks 1 to 4, on white's side of the board.
+pub const WHITE_HALF: BoardBitmap = region(0, 7, 0, 3);
+
+/// The ranks 5 to 8, on black's side of the board.
+pub const BLACK_HALF: BoardBitmap = region(0, 7, 4, 7);
+
+/// returns: The bitmap of the squares in the given files and ranks, both inclusive, as indices
+///          from 0 to 7.
+const fn region(min_file: u8, max_file: u8, min_rank: u8, max_rank: u8) -> BoardBitmap {
+    let mut data = 0;
+    let mut file = min_file;
+    while file <= max_file {
+        let mut rank = min_rank;
+        while rank <= max_rank {
+            data |= 1 << (file * 8 + rank);
+            rank += 1;
+        }
+        file += 1;
+    }
+    BoardBitmap::from_u64(data)
+}
+
+/// returns: The bitmap of the squares at the given file offsets and rank offsets from a square,
+///          where the rank offsets are counted toward the opponent of the player. Offsets
+///          leading off the board are left out.
+fn offset_region(player: PlayerColor, pos: BoardPosition, file_offsets: (i8, i8),
+                 rank_offsets: (i8, i8)) -> BoardBitmap
+{
+    let forward = match player {
+        PlayerColor::White => 1,
+        PlayerColor::Black => -1,
+    };
+    let mut bitmap = BoardBitmap::all_zeros();
+    for file_offset in file_offsets.0..=file_offsets.1 {
+        for rank_offset in rank_offsets.0..=rank_offsets.1 {
+            let file = u8::try_from(pos.file.get() as i8 + file_offset);
+            let rank = u8::try_from(pos.rank.get() as i8 + rank_offset * forward);
+            if let (Ok(file), Ok(rank)) = (file, rank)
+                && let Ok(square) = BoardPosition::try_from((file, rank))
+            {
+                bitmap.set(square, true);
+            }
+        }
+    }
+    bitmap
+}
+
+/// The squares around a king which an attack on it usually targets: the king's square and the
+/// squares next to it, together with the squares two ranks in front of it, toward the opponent.
+/// Near the edges of the board, only the squares on the board are included.
+///
+/// # Arguments
+///
+/// * `player`: The player whose king it is, which decides which direction is forward.
+/// * `king_pos`: The square of the king.
+///
+/// returns: The bitmap of the king zone, with between 4 and 12 squares.
+pub fn king_zone(player: PlayerColor, king_pos: BoardPosition) -> BoardBitmap {
+    offset_region(player, king_pos, (-1, 1), (-1, 2))
+}
+
+/// The squares where the pawns sheltering a king stand: the squares on the king's file and the
+/// files next to it, one and two ranks in front of the king, toward the opponent. Near the edges
+/// of the board, only the squares on the board are included.
+///
+/// # Arguments
+///
+/// * `player`: The player whose king it is, which decides which direction is forward.
+/// * `king_pos`: The square of the king.
+///
+/// returns: The bitmap of the pawn shield squares, with at most 6 squares.
+pub fn pawn_shield_squares(player: PlayerColor, king_pos: BoardPosition) -> BoardBitmap {
+    offset_region(player, king_pos, (-1, 1), (1, 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squares(list: &str) -> BoardBitmap {
+        BoardBitmap::from_square_list(list).unwrap()
+    }
+
+    fn square(pos: &str) -> BoardPosition {
+        pos.parse().unwrap()
+    }
+
+    #[test]
+    fn named_regions() {
+        assert_eq!(CENTER, squares("d4,d5,e4,e5"));
+        assert_eq!(CENTER.to_hex(), "0x0000001818000000");
+        assert_eq!(EXTENDED_CENTER, squares("c3,c4,c5,c6,d3,d4,d5,d6,e3,e4,e5,e6,f3,f4,f5,f6"));
+        for pos in BoardPosition::all() {
+            let (file, rank) = (pos.file.get(), pos.rank.get());
+            assert_eq!(CENTER.get(pos), (3..=4).contains(&file) && (3..=4).contains(&rank));
+            assert_eq!(EXTENDED_CENTER.get(pos),
+                       (2..=5).contains(&file) && (2..=5).contains(&rank), "{}", pos);
+            assert_eq!(KINGSIDE.get(pos), file >= 4, "{}", pos);
+            assert_eq!(QUEENSIDE.get(pos), file < 4, "{}", pos);
+            assert_eq!(WHITE_HALF.get(pos), rank < 4, "{}", pos);
+            assert_eq!(BLACK_HALF.get(pos), rank >= 4, "{}", pos);
+        }
+        assert_eq!(KINGSIDE.to_u64() | QUEENSIDE.to_u64(), BoardBitmap::all_ones().to_u64());
+        assert_eq!(WHITE_HALF.to_u64() & BLACK_HALF.to_u64(), 0);
+        assert_eq!(QUEENSIDE.to_hex(), "0x00000000FFFFFFFF");
+        assert_eq!(WHITE_HALF.to_hex(), "0x0F0F0F0F0F0F0F0F");
+    }
+
+    #[test]
+    fn king_zones() {
+        assert_eq!(king_zone(PlayerColor::White, square("e4")),
+                   squares("d3,d4,d5,d6,e3,e4,e5,e6,f3,f4,f5,f6"));
+        assert_eq!(king_zone(PlayerColor::Black, square("e5")),
+                   squares("d3,d4,d5,d6,e3,e4,e5,e6,f3,f4,f5,f6"));
+        // clamped at the edges and corners
+        assert_eq!(king_zone(PlayerColor::White, square("g1")),
+                   squares("f1,f2,f3,g1,g2,g3,h1,h2,h3"));
+        assert_eq!(king_zone(PlayerColor::White, square("a1")), squares("a1,a2,a3,b1,b2,b3"));
+        assert_eq!(king_zone(PlayerColor::Black, square("h8")), squares("g6,g7,g8,h6,h7,h8"));
+        assert_eq!(king_zone(PlayerColor::White, square("h8")), squares("g7,g8,h7,h8"));
+        assert_eq!(king_zone(PlayerColor::White, square("a7")), squares("a6,a7,a8,b6,b7,b8"));
+        assert_eq!(king_zone(PlayerColor::Black, square("d1")), squares("c1,c2,d1,d2,e1,e2"));
+
+        assert_eq!(pawn_shield_squares(PlayerColor::White, square("g1")),
+                   squares("f2,f3,g2,g3,h2,h3"));
+        assert_eq!(pawn_shield_squares(PlayerColor::Black, square("c8")),
+                   squares("b6,b7,c6,c7,d6,d7"));
+        assert_eq!(pawn_shield_squares(PlayerColor::White, square("a7")), squares("a8,b8"));
+        assert!(pawn_shield_squares(PlayerColor::White, square("e8")).is_all_zeros());
+    }
+}
@@ -0,0 +1,168 @@
+//! Classification of chess openings by ECO (Encyclopaedia of Chess Openings) code.
+//!
+//! The embedded table covers the main lines of the most common openings, rather than the full
+//! ECO classification. Lines are given as moves in standard algebraic notation, and are indexed
+//! by the [Polyglot key](ChessGame::polyglot_key) of the position they lead to, so that a game
+//! is classified correctly even if it reached the position through another move order.
+//!
+//! An [OpeningTree] collects the moves played in one's own games, with their results.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::board::Board;
+use crate::chess::ChessGame;
+
+mod tree;
+
+pub use tree::{MoveStats, OpeningTree};
+
+/// An opening as classified by [ChessGame::identify_opening].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OpeningInfo {
+    /// The ECO code of the opening, for example `"B90"`.
+    pub eco: &'static str,
+    /// The name of the opening, for example `"Sicilian Defense"`.
+    pub name: &'static str,
+    /// The name of the variation, for example `"Najdorf Variation"`, if any.
+    pub variation: Option<&'static str>,
+    /// The number of plies from the start of the game to the last position of the game found in
+    /// the table.
+    pub last_book_ply: usize,
+}
+
+/// The ECO code, name, variation and moves of each line in the table.
+const LINES: &[(&str, &str, Option<&str>, &str)] = &[
+    ("A00", "Polish Opening", None, "b4"),
+    ("A01", "Nimzo-Larsen Attack", None, "b3"),
+    ("A02", "Bird's Opening", None, "f4"),
+    ("A04", "Réti Opening", None, "Nf3"),
+    ("A10", "English Opening", None, "c4"),
+    ("A40", "Queen's Pawn Game", None, "d4"),
+    ("A45", "Indian Defense", None, "d4 Nf6"),
+    ("A80", "Dutch Defense", None, "d4 f5"),
+    ("B00", "King's Pawn Game", None, "e4"),
+    ("B01", "Scandinavian Defense", None, "e4 d5"),
+    ("B02", "Alekhine's Defense", None, "e4 Nf6"),
+    ("B06", "Modern Defense", None, "e4 g6"),
+    ("B07", "Pirc Defense", None, "e4 d6 d4 Nf6"),
+    ("B10", "Caro-Kann Defense", None, "e4 c6"),
+    ("B20", "Sicilian Defense", None, "e4 c5"),
+    ("B70", "Sicilian Defense", Some("Dragon Variation"), "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 g6"),
+    ("B90", "Sicilian Defense", Some("Najdorf Variation"), "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6"),
+    ("C00", "French Defense", None, "e4 e6"),
+    ("C20", "King's Pawn Game", None, "e4 e5"),
+    ("C30", "King's Gambit", None, "e4 e5 f4"),
+    ("C40", "King's Knight Opening", None, "e4 e5 Nf3"),
+    ("C41", "Philidor Defense", None, "e4 e5 Nf3 d6"),
+    ("C42", "Petrov's Defense", None, "e4 e5 Nf3 Nf6"),
+    ("C44", "King's Pawn Game", None, "e4 e5 Nf3 Nc6"),
+    ("C45", "Scotch Game", None, "e4 e5 Nf3 Nc6 d4 exd4 Nxd4"),
+    ("C50", "Italian Game", None, "e4 e5 Nf3 Nc6 Bc4"),
+    ("C50", "Italian Game", Some("Giuoco Piano"), "e4 e5 Nf3 Nc6 Bc4 Bc5"),
+    ("C55", "Italian Game", Some("Two Knights Defense"), "e4 e5 Nf3 Nc6 Bc4 Nf6"),
+    ("C60", "Ruy Lopez", None, "e4 e5 Nf3 Nc6 Bb5"),
+    ("C65", "Ruy Lopez", Some("Berlin Defense"), "e4 e5 Nf3 Nc6 Bb5 Nf6"),
+    ("C68", "Ruy Lopez", Some("Exchange Variation"), "e4 e5 Nf3 Nc6 Bb5 a6 Bxc6"),
+    ("C70", "Ruy Lopez", Some("Morphy Defense"), "e4 e5 Nf3 Nc6 Bb5 a6 Ba4"),
+    ("D00", "Queen's Pawn Game", None, "d4 d5"),
+    ("D06", "Queen's Gambit", None, "d4 d5 c4"),
+    ("D10", "Queen's Gambit Declined", Some("Slav Defense"), "d4 d5 c4 c6"),
+    ("D20", "Queen's Gambit Accepted", None, "d4 d5 c4 dxc4"),
+    ("D30", "Queen's Gambit Declined", None, "d4 d5 c4 e6"),
+    ("D80", "Grünfeld Defense", None, "d4 Nf6 c4 g6 Nc3 d5"),
+    ("E12", "Queen's Indian Defense", None, "d4 Nf6 c4 e6 Nf3 b6"),
+    ("E20", "Nimzo-Indian Defense", None, "d4 Nf6 c4 e6 Nc3 Bb4"),
+    ("E60", "King's Indian Defense", None, "d4 Nf6 c4 g6"),
+];
+
+/// returns: The index into [LINES] of the line leading to each position in the table, by
+///          Polyglot key.
+fn table() -> &'static HashMap<u64, usize> {
+    static TABLE: OnceLock<HashMap<u64, usize>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for (i, (_, _, _, moves)) in LINES.iter().enumerate() {
+            let mut game = ChessGame::new(Board::default_board());
+            for san in moves.split_whitespace() {
+                // the lines are known to be legal
+                let _ = game.try_move_str(san);
+            }
+            table.insert(game.polyglot_key(), i);
+        }
+        table
+    })
+}
+
+impl ChessGame {
+    /// Classifies the opening of the game by looking up each position of the game's
+    /// [history](ChessGame::history) in the embedded table of openings (see
+    /// [the openings module](crate::openings)).
+    ///
+    /// returns: `Some(OpeningInfo)` of the last position of the game found in the table, or
+    ///          `None` if no position was found.
+    pub fn identify_opening(&self) -> Option<OpeningInfo> {
+        let table = table();
+        let mut position = self.starting_position();
+        let mut opening = None;
+        for ply in 0..=self.history().len() {
+            if let Some(&i) = table.get(&position.polyglot_key()) {
+                let (eco, name, variation, _) = LINES[i];
+                opening = Some(OpeningInfo { eco, name, variation, last_book_ply: ply });
+            }
+            if let Some(details) = self.history().get(ply) {
+                let _ = position.replay(details);
+            }
+        }
+        opening
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(moves: &str) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        for san in moves.split_whitespace() {
+            game.try_move_str(san).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn table_lines_are_legal() {
+        for (_, _, _, moves) in LINES {
+            assert_eq!(play(moves).history().len(), moves.split_whitespace().count());
+        }
+        assert_eq!(table().len(), LINES.len());
+    }
+
+    #[test]
+    fn identify_opening() {
+        assert_eq!(play("").identify_opening(), None);
+        assert_eq!(play("a4").identify_opening(), None);
+
+        let najdorf = play("e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6 Be3 e5");
+        assert_eq!(najdorf.identify_opening(), Some(OpeningInfo {
+            eco: "B90",
+            name: "Sicilian Defense",
+            variation: Some("Najdorf Variation"),
+            last_book_ply: 10,
+        }));
+
+        let ruy_lopez = play("e4 e5 Nf3 Nc6 Bb5 Nd4");
+        let opening = ruy_lopez.identify_opening().unwrap();
+        assert_eq!((opening.eco, opening.variation, opening.last_book_ply), ("C60", None, 5));
+    }
+
+    #[test]
+    fn identify_opening_transposition() {
+        let nimzo = play("c4 e6 Nc3 Nf6 d4 Bb4 e3");
+        let opening = nimzo.identify_opening().unwrap();
+        assert_eq!((opening.eco, opening.name, opening.last_book_ply),
+                   ("E20", "Nimzo-Indian Defense", 6));
+
+        let scotch = play("Nf3 Nc6 e4 e5 d4 exd4 Nxd4");
+        assert_eq!(scotch.identify_opening().unwrap().eco, "C45");
+    }
+}
@@ -0,0 +1,283 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessGame, GameStatus};
+use crate::moves::ChessMove;
+use crate::pgn::{PgnError, PgnGame};
+
+/// Statistics of the games in an [OpeningTree] in which a move was played in a position.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MoveStats {
+    /// The move that was played.
+    pub chess_move: ChessMove,
+    /// The number of games in which the move was played in the position.
+    pub games: u32,
+    /// The number of those games won by white.
+    pub white_wins: u32,
+    /// The number of those games which ended in a draw.
+    pub draws: u32,
+    /// The number of those games won by black.
+    pub black_wins: u32,
+}
+
+impl MoveStats {
+    /// returns: The percentage of the games won by white, or 0 if there are no games. Games
+    ///          without a result count towards the total.
+    pub fn white_win_percentage(&self) -> f64 {
+        self.percentage(self.white_wins)
+    }
+
+    /// returns: The percentage of the games which ended in a draw, like
+    ///          [white_win_percentage](MoveStats::white_win_percentage).
+    pub fn draw_percentage(&self) -> f64 {
+        self.percentage(self.draws)
+    }
+
+    /// returns: The percentage of the games won by black, like
+    ///          [white_win_percentage](MoveStats::white_win_percentage).
+    pub fn black_win_percentage(&self) -> f64 {
+        self.percentage(self.black_wins)
+    }
+
+    fn percentage(&self, count: u32) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        100.0 * count as f64 / self.games as f64
+    }
+}
+
+/// A tree of the moves played in a collection of games, for opening explorers and for building
+/// opening books from one's own games.
+///
+/// For each position, indexed by its [Polyglot key](ChessGame::polyglot_key), the tree counts
+/// how often each move was played in it and how the games continuing with the move ended. Since
+/// positions are indexed by key, transpositions are merged. Only the first `max_plies` moves of
+/// each game are added, which bounds the size of the tree, and rarely played moves can be
+/// removed afterwards with [prune](OpeningTree::prune). A game's line also ends at its first
+/// [drop](crate::chess::crazyhouse::DropMove).
+///
+/// # Example
+///
+/// ```rust
+/// use leben_chess::board::Board;
+/// use leben_chess::chess::ChessGame;
+/// use leben_chess::openings::OpeningTree;
+///
+/// let mut game = ChessGame::new(Board::default_board());
+/// game.try_move_str("e4").unwrap();
+/// let mut tree = OpeningTree::new(10);
+/// tree.add_game(&game);
+///
+/// let root = ChessGame::new(Board::default_board());
+/// let moves = tree.moves_after(&root);
+/// assert_eq!(root.move_to_san(moves[0].chess_move).unwrap(), "e4");
+/// assert_eq!(moves[0].games, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpeningTree {
+    max_plies: usize,
+    positions: HashMap<u64, Vec<MoveStats>>,
+}
+
+impl OpeningTree {
+    /// returns: An empty tree which records the first `max_plies` moves of each game added.
+    pub fn new(max_plies: usize) -> OpeningTree {
+        OpeningTree {
+            max_plies,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// returns: The number of moves of each game recorded in the tree.
+    pub fn max_plies(&self) -> usize {
+        self.max_plies
+    }
+
+    /// returns: The number of positions in the tree in which at least one move was recorded.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// returns: `true` if no moves have been recorded in the tree.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Adds the moves of the game's [history](ChessGame::history), with the result given by its
+    /// [game status](ChessGame::game_status). Games which have not ended count towards the
+    /// number of games, but not towards any result.
+    pub fn add_game(&mut self, game: &ChessGame) {
+        let winner = match game.game_status() {
+            GameStatus::Win(player, _) => Some(Some(*player)),
+            GameStatus::Draw(_) => Some(None),
+            GameStatus::NotYetStarted | GameStatus::Normal => None,
+        };
+        self.add(game, winner);
+    }
+
+    /// Adds the moves of a game read from a PGN database, with the result given by its `Result`
+    /// tag, which is taken into account even if the movetext does not end the game.
+    ///
+    /// returns: The error from [PgnGame::to_game] if the moves could not be replayed, in which
+    ///          case the tree is left unchanged.
+    pub fn add_pgn_game(&mut self, pgn_game: &PgnGame) -> Result<(), PgnError> {
+        let game = pgn_game.to_game()?;
+        let winner = match pgn_game.tag("Result") {
+            Some("1-0") => Some(Some(PlayerColor::White)),
+            Some("0-1") => Some(Some(PlayerColor::Black)),
+            Some("1/2-1/2") => Some(None),
+            _ => None,
+        };
+        self.add(&game, winner);
+        Ok(())
+    }
+
+    /// Adds the moves of a game.
+    ///
+    /// # Arguments
+    ///
+    /// * `game`: The game whose history is added.
+    /// * `winner`: `Some(Some(player))` for a win, `Some(None)` for a draw, and `None` for a game
+    ///   without a result.
+    fn add(&mut self, game: &ChessGame, winner: Option<Option<PlayerColor>>) {
+        let mut position = game.starting_position();
+        for details in game.history().iter().take(self.max_plies) {
+            if details.is_drop {
+                break;
+            }
+            let moves = self.positions.entry(position.polyglot_key()).or_default();
+            let index = match moves.iter().position(|stats| stats.chess_move == details.chess_move) {
+                Some(index) => index,
+                None => {
+                    moves.push(MoveStats {
+                        chess_move: details.chess_move,
+                        games: 0,
+                        white_wins: 0,
+                        draws: 0,
+                        black_wins: 0,
+                    });
+                    moves.len() - 1
+                }
+            };
+            let stats = &mut moves[index];
+            stats.games += 1;
+            match winner {
+                Some(Some(PlayerColor::White)) => stats.white_wins += 1,
+                Some(Some(PlayerColor::Black)) => stats.black_wins += 1,
+                Some(None) => stats.draws += 1,
+                None => {}
+            }
+            // the move is from the history of the game, so it is legal
+            if position.replay(details).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Removes the moves played in fewer than `min_games` games, and the positions left without
+    /// moves.
+    pub fn prune(&mut self, min_games: u32) {
+        self.positions.retain(|_, moves| {
+            moves.retain(|stats| stats.games >= min_games);
+            !moves.is_empty()
+        });
+    }
+
+    /// returns: The statistics of the moves played in the current position of the game, ordered
+    ///          from the most to the least played move, and otherwise in the order the moves
+    ///          were first added, or an empty list if the position is not in the tree.
+    pub fn moves_after(&self, game: &ChessGame) -> Vec<MoveStats> {
+        let mut moves = self.positions.get(&game.polyglot_key()).cloned().unwrap_or_default();
+        moves.sort_by_key(|stats| Reverse(stats.games));
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::pgn::PgnReader;
+    use super::*;
+
+    fn play(moves: &str) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        for san in moves.split_whitespace() {
+            game.try_move_str(san).unwrap();
+        }
+        game
+    }
+
+    fn san_counts(tree: &OpeningTree, game: &ChessGame) -> Vec<(String, u32)> {
+        tree.moves_after(game).into_iter()
+            .map(|stats| (game.move_to_san(stats.chess_move).unwrap(), stats.games))
+            .collect()
+    }
+
+    fn sample_tree() -> OpeningTree {
+        let mut tree = OpeningTree::new(4);
+        let mut white_win = play("e4 e5 Nf3 Nc6 Bb5");
+        white_win.resign().unwrap();
+        let mut black_win = play("e4 c5 Nf3 d6");
+        black_win.resign().unwrap();
+        let mut draw = play("e4 e5 Nf3 Nf6");
+        draw.draw_by_agreement().unwrap();
+        tree.add_game(&white_win);
+        tree.add_game(&black_win);
+        tree.add_game(&draw);
+        tree.add_game(&play("d4 d5"));
+        tree
+    }
+
+    #[test]
+    fn root_and_first_move_statistics() {
+        let tree = sample_tree();
+        let root = ChessGame::new(Board::default_board());
+        let moves = tree.moves_after(&root);
+        assert_eq!(san_counts(&tree, &root), [("e4".to_string(), 3), ("d4".to_string(), 1)]);
+        let e4 = moves[0];
+        assert_eq!((e4.white_wins, e4.draws, e4.black_wins), (1, 1, 1));
+        assert!((e4.white_win_percentage() - 100.0 / 3.0).abs() < 1e-9);
+        assert!((e4.draw_percentage() - 100.0 / 3.0).abs() < 1e-9);
+        let d4 = moves[1];
+        assert_eq!((d4.white_wins, d4.draws, d4.black_wins), (0, 0, 0));
+        assert_eq!(d4.white_win_percentage(), 0.0);
+
+        let after_e4 = play("e4");
+        let moves = tree.moves_after(&after_e4);
+        assert_eq!(san_counts(&tree, &after_e4), [("e5".to_string(), 2), ("c5".to_string(), 1)]);
+        assert_eq!(moves[0].white_win_percentage(), 50.0);
+        assert_eq!(moves[0].draw_percentage(), 50.0);
+        assert_eq!(moves[1].black_win_percentage(), 100.0);
+    }
+
+    #[test]
+    fn ply_cutoff_and_pruning() {
+        let mut tree = sample_tree();
+        // Bb5 is the fifth move of its game, beyond the cutoff
+        assert!(tree.moves_after(&play("e4 e5 Nf3 Nc6")).is_empty());
+        assert_eq!(tree.moves_after(&play("e4 e5 Nf3")).len(), 2);
+        assert_eq!(tree.len(), 7);
+
+        tree.prune(2);
+        assert_eq!(san_counts(&tree, &play("")), [("e4".to_string(), 3)]);
+        assert_eq!(san_counts(&tree, &play("e4 e5")), [("Nf3".to_string(), 2)]);
+        assert!(tree.moves_after(&play("e4 e5 Nf3")).is_empty());
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn transpositions_and_pgn() {
+        let database = "[Result \"1-0\"]\n1. Nf3 d5 2. d4 Nf6 1-0\n\
+                        [Result \"0-1\"]\n1. d4 d5 2. Nf3 Nf6 0-1\n";
+        let mut tree = OpeningTree::new(10);
+        for pgn_game in PgnReader::new(database.as_bytes()) {
+            tree.add_pgn_game(&pgn_game.unwrap()).unwrap();
+        }
+        let moves = tree.moves_after(&play("d4 d5 Nf3"));
+        assert_eq!(moves.len(), 1);
+        assert_eq!((moves[0].games, moves[0].white_wins, moves[0].black_wins), (2, 1, 1));
+        let moves = tree.moves_after(&play("d4 d5"));
+        assert_eq!((moves[0].games, moves[0].black_wins), (1, 1));
+    }
+}
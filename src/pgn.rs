@@ -0,0 +1,226 @@
+//! Export and import of games in Portable Game Notation (PGN). Importing is done with a
+//! [PgnReader], which is only available with the `std` feature.
+//!
+//! see: [Portable Game Notation - Wikipedia](https://en.wikipedia.org/wiki/Portable_Game_Notation)
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use crate::board::Board;
+use crate::board::piece::PlayerColor;
+use crate::chess::{ChessGame, GameStatus, GameTermination};
+use crate::chess::variant::Variant;
+
+#[cfg(feature = "std")]
+mod reader;
+
+#[cfg(feature = "std")]
+pub use reader::{PgnError, PgnGame, PgnReader};
+
+/// The maximum length of a line of movetext in exported PGN.
+const LINE_LENGTH: usize = 80;
+
+/// returns: The PGN game termination marker for the given status: `"1-0"` or `"0-1"` for a win
+///          for white or black, `"1/2-1/2"` for a draw, and `"*"` for a game in progress.
+pub fn result_token(status: &GameStatus) -> &'static str {
+    match status {
+        GameStatus::Win(PlayerColor::White, _) => "1-0",
+        GameStatus::Win(PlayerColor::Black, _) => "0-1",
+        GameStatus::Draw(_) => "1/2-1/2",
+        GameStatus::NotYetStarted | GameStatus::Normal => "*",
+    }
+}
+
+/// Exports a game in PGN, consisting of the given tag pairs followed by the game's
+/// [move history](ChessGame::history) in standard algebraic notation, with crazyhouse drops
+/// written like `N@f3`. The [Variant](crate::chess::variant::Variant) of a game played with other
+/// rules than standard chess is added as a `Variant` tag. If the history does not start from the
+/// standard starting position, `SetUp` and `FEN` tags are added after the given tags, preceded
+/// by a `Handicap` tag if the game was set up with a
+/// [handicap](crate::chess::handicap::Handicap). The reason of an
+/// [adjudicated](ChessGame::adjudicate) game, or otherwise the
+/// [PGN termination](GameTermination::pgn_termination) of a game which did not end normally,
+/// such as by the illegal move limit, is added as a `Termination` tag, unless one is given. Tag
+/// values are escaped as needed.
+///
+/// # Arguments
+///
+/// * `game`: The game to export.
+/// * `tags`: Tag names and values, in the order they should be written. PGN readers expect the
+///   seven tag roster (`Event`, `Site`, `Date`, `Round`, `White`, `Black` and `Result`) first.
+///
+/// returns: The game in PGN, ending with a newline.
+pub fn write_game(game: &ChessGame, tags: &[(&str, &str)]) -> String {
+    write(game, tags, false)
+}
+
+/// Exports a game in PGN like [write_game], additionally writing the
+/// [analysis](crate::chess::MoveDetails::analysis) of each move added with
+/// [annotate_history](ChessGame::annotate_history): the Numeric Annotation Glyph of the move's
+/// [annotation](crate::chess::analysis::MoveAnnotation::nag), if any, followed by a
+/// `{[%eval ...]}` comment with the
+/// [evaluation after the move](crate::chess::analysis::MoveAnalysis::eval_string), unless the
+/// game ended with the move.
+///
+/// returns: The game in PGN, ending with a newline.
+pub fn write_annotated_game(game: &ChessGame, tags: &[(&str, &str)]) -> String {
+    write(game, tags, true)
+}
+
+/// returns: A PGN tag pair with the value escaped, followed by a newline.
+fn tag_pair(name: &str, value: &str) -> String {
+    format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn write(game: &ChessGame, tags: &[(&str, &str)], annotated: bool) -> String {
+    let mut pgn = String::new();
+    for (name, value) in tags {
+        pgn += &tag_pair(name, value);
+    }
+    let termination = game.termination()
+        .or(game.game_termination().and_then(GameTermination::pgn_termination));
+    if let Some(termination) = termination
+        && !tags.iter().any(|(name, _)| *name == "Termination")
+    {
+        pgn += &tag_pair("Termination", termination);
+    }
+    if game.variant() != Variant::Standard && !tags.iter().any(|(name, _)| *name == "Variant") {
+        pgn += &tag_pair("Variant", &game.variant().to_string());
+    }
+    if let Some((handicap, _)) = game.handicap() {
+        pgn += &format!("[Handicap \"{}\"]\n", handicap);
+    }
+    let starting_position = game.starting_position();
+    let fen = starting_position.fen();
+    if fen != ChessGame::new(Board::default_board()).fen() {
+        pgn += &format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen);
+    }
+    pgn.push('\n');
+
+    let mut tokens = movetext_tokens(game, annotated);
+    tokens.push(result_token(game.game_status()).to_string());
+
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > LINE_LENGTH {
+            pgn.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            pgn.push(' ');
+            line_length += 1;
+        }
+        line_length += token.len();
+        pgn += &token;
+    }
+    pgn.push('\n');
+    pgn
+}
+
+/// returns: The move numbers and the moves of the game's history in standard algebraic notation,
+///          followed by their annotations if `annotated` is set, as separate tokens without the
+///          result token.
+pub(crate) fn movetext_tokens(game: &ChessGame, annotated: bool) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut position = game.starting_position();
+    let mut fullmove_number = position.fullmove_number();
+    // a move of black needs its own move number at the start and after a comment
+    let mut needs_move_number = true;
+    for details in game.history() {
+        match position.active_player() {
+            PlayerColor::White => tokens.push(format!("{}.", fullmove_number)),
+            PlayerColor::Black if needs_move_number => {
+                tokens.push(format!("{}...", fullmove_number))
+            }
+            PlayerColor::Black => {}
+        }
+        needs_move_number = false;
+        // the history only contains legal moves, so this always succeeds
+        if let Some(san) = position.recorded_move_to_san(details) {
+            tokens.push(san);
+        }
+        let mover = position.active_player();
+        if mover == PlayerColor::Black {
+            fullmove_number = fullmove_number.saturating_add(1);
+        }
+        let _ = position.replay(details);
+        // a player making several moves in a row gets a new move number for each of them
+        if position.active_player() == mover {
+            if mover == PlayerColor::White {
+                fullmove_number = fullmove_number.saturating_add(1);
+            }
+            needs_move_number = true;
+        }
+        if annotated && let Some(analysis) = details.analysis {
+            if let Some(nag) = analysis.annotation.nag() {
+                tokens.push(format!("${}", nag));
+            }
+            if !position.game_status().is_over() {
+                tokens.push(format!("{{[%eval {}]}}", analysis.eval_string()));
+                needs_move_number = true;
+            }
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::{CastlingRights, ChessMove};
+    use super::*;
+
+    #[test]
+    fn write_pgn() {
+        let mut game = ChessGame::new(Board::default_board());
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        let pgn = write_game(&game, &[("Event", "\"Fool's\" mate"), ("Result", "0-1")]);
+        assert_eq!(pgn, concat!(
+            "[Event \"\\\"Fool's\\\" mate\"]\n",
+            "[Result \"0-1\"]\n",
+            "\n",
+            "1. f3 e5 2. g4 Qh4# 0-1\n",
+        ));
+
+        let board = Board::from_fen_string("4k3/8/8/8/8/8/8/4K3").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::Black, no_rights, None).unwrap();
+        for _ in 0..20 {
+            for (from, to) in [("e8", "d8"), ("e1", "d1"), ("d8", "e8"), ("d1", "e1")] {
+                game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+            }
+        }
+        let pgn = write_game(&game, &[]);
+        assert!(pgn.starts_with("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K3 b - - 0 1\"]\n\n"));
+        assert!(pgn.contains("\n1... Kd8 2. Kd1 Ke8 3. Ke1 Kd8 "));
+        assert!(pgn.ends_with(" 41. Ke1\n*\n"));
+        assert!(pgn.lines().all(|line| line.len() <= LINE_LENGTH));
+
+        // the move numbers continue from the fullmove number of the FEN string
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 10 32").unwrap();
+        for (from, to) in [("e8", "d8"), ("e1", "d1")] {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        assert_eq!(write_game(&game, &[]), concat!(
+            "[SetUp \"1\"]\n",
+            "[FEN \"4k3/8/8/8/8/8/8/4K3 b - - 10 32\"]\n",
+            "\n",
+            "32... Kd8 33. Kd1 *\n",
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_annotated_pgn() {
+        let mut game = ChessGame::new(Board::default_board());
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        assert_eq!(write_annotated_game(&game, &[]), write_game(&game, &[]));
+        game.annotate_history(2);
+        let pgn = write_annotated_game(&game, &[]);
+        assert!(pgn.starts_with("\n1. f3 $6 {[%eval "));
+        // the evaluation is not written after the checkmate
+        assert!(pgn.contains(" 2. g4 $4 {[%eval #-1]} 2...\nQh4# 0-1\n"));
+    }
+}
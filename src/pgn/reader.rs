@@ -0,0 +1,413 @@
+//! Streaming import of PGN databases, see [PgnReader]. Only available with the `std` feature.
+
+use std::io;
+use std::io::BufRead;
+use thiserror::Error;
+use crate::board::Board;
+use crate::chess::{ChessGame, FenError, NotationError};
+use crate::chess::variant::Variant;
+use crate::clock::{TimeControl, TimeControlParseError};
+
+/// An error caused by reading a game from a PGN database.
+#[derive(Error, Debug)]
+pub enum PgnError {
+    /// The database could not be read.
+    #[error("could not read PGN: {0}")]
+    Io(#[from] io::Error),
+    /// A line in the tag section is not a valid tag pair, with the line number, counted from 1.
+    #[error("invalid tag pair on line {0}")]
+    InvalidTag(usize),
+    /// The movetext contains an unterminated comment or variation, with the line number the game
+    /// starts on.
+    #[error("unterminated comment or variation in the game starting on line {0}")]
+    UnterminatedMovetext(usize),
+    /// The `FEN` tag is not a valid position.
+    #[error("invalid FEN tag: {0}")]
+    InvalidFen(#[from] FenError),
+    /// The `Variant` tag names a variant which is not supported, with the name.
+    #[error("unsupported variant {0}")]
+    UnsupportedVariant(String),
+    /// A move of the movetext is not a legal move in standard algebraic notation, with the
+    /// number of the move counted in plies from 1.
+    #[error("invalid move {san} at ply {ply}: {error}")]
+    InvalidMove {
+        san: String,
+        ply: usize,
+        error: NotationError,
+    },
+}
+
+/// A game read from a PGN database, consisting of its tag pairs and its unparsed movetext. The
+/// moves are only interpreted when [to_game](PgnGame::to_game) is called.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PgnGame {
+    /// The tag pairs in the order they appear in the database, with escapes resolved.
+    pub tags: Vec<(String, String)>,
+    /// The movetext, with lines joined by newlines, including the termination marker.
+    pub movetext: String,
+    /// The number of the line the game starts on, counted from 1.
+    pub line: usize,
+}
+
+impl PgnGame {
+    /// returns: The value of the first tag with the given name, or `None` if there is no such tag.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// returns: The time control given by the `TimeControl` tag, or `None` if there is no such
+    ///          tag.
+    pub fn time_control(&self) -> Option<Result<TimeControl, TimeControlParseError>> {
+        self.tag("TimeControl").map(str::parse)
+    }
+
+    /// returns: The moves of the main line of the movetext in standard algebraic notation,
+    ///          without move numbers, comments, Numeric Annotation Glyphs, variations and the
+    ///          termination marker, or [UnterminatedMovetext](PgnError::UnterminatedMovetext) if
+    ///          a comment or variation is not closed.
+    pub fn san_moves(&self) -> Result<Vec<&str>, PgnError> {
+        let mut moves = Vec::new();
+        let mut rest = self.movetext.as_str();
+        let unterminated = || PgnError::UnterminatedMovetext(self.line);
+        while let Some(start) = rest.find(|ch: char| !ch.is_whitespace()) {
+            rest = &rest[start..];
+            if let Some(comment) = rest.strip_prefix('{') {
+                let end = comment.find('}').ok_or_else(unterminated)?;
+                rest = &comment[end + 1..];
+            } else if let Some(comment) = rest.strip_prefix(';') {
+                rest = comment.find('\n').map_or("", |end| &comment[end..]);
+            } else if rest.starts_with('(') {
+                rest = skip_variation(rest).ok_or_else(unterminated)?;
+            } else {
+                let end = rest.find(|ch: char| ch.is_whitespace() || "{};()".contains(ch))
+                    .unwrap_or(rest.len());
+                let (token, after) = rest.split_at(end);
+                rest = after;
+                if is_result_token(token) {
+                    continue;
+                }
+                // move numbers may be written directly before the move, such as in "1.e4"
+                let token = token.trim_start_matches(|ch: char| ch.is_ascii_digit() || ch == '.');
+                if !token.is_empty() && !token.starts_with('$') {
+                    moves.push(token);
+                }
+            }
+        }
+        Ok(moves)
+    }
+
+    /// Replays the movetext from the position given by the `FEN` tag, or from the standard
+    /// starting position if there is none, with the rules of the variant given by the `Variant`
+    /// tag, or standard chess if there is none. Crazyhouse drops are written like `N@f3`. The
+    /// termination marker is not taken into account.
+    ///
+    /// returns: The game after the moves of the main line, or the [PgnError] describing the
+    ///          first problem found.
+    pub fn to_game(&self) -> Result<ChessGame, PgnError> {
+        let variant = match self.tag("Variant") {
+            Some(name) => Variant::from_name(name)
+                .ok_or_else(|| PgnError::UnsupportedVariant(name.to_string()))?,
+            None => Variant::Standard,
+        };
+        let mut game = match self.tag("FEN") {
+            Some(fen) => ChessGame::from_fen_with_variant(fen, variant)?,
+            None => ChessGame::with_variant(Board::default_board(), variant),
+        };
+        for (index, san) in self.san_moves()?.into_iter().enumerate() {
+            let invalid_move = |error| PgnError::InvalidMove {
+                san: san.to_string(),
+                ply: index + 1,
+                error,
+            };
+            let chess_move = game.parse_san(san).map_err(invalid_move)?;
+            game.do_move(chess_move).map_err(|_| invalid_move(NotationError::IllegalMove))?;
+        }
+        Ok(game)
+    }
+}
+
+/// returns: The rest of the movetext after the variation it starts with, or `None` if the
+///          variation is not closed.
+fn skip_variation(movetext: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut in_comment = false;
+    for (index, ch) in movetext.char_indices() {
+        match ch {
+            '{' => in_comment = true,
+            '}' => in_comment = false,
+            '(' if !in_comment => depth += 1,
+            ')' if !in_comment => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&movetext[index + 1..]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_result_token(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// returns: The name and value of a tag pair line such as `[Event "Casual game"]`, or `None` if
+///          the line is not a valid tag pair.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.trim_end().strip_suffix(']')?;
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    if name.is_empty() || !name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_') {
+        return None;
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.push(chars.next()?),
+            '"' => return None,
+            ch => unescaped.push(ch),
+        }
+    }
+    Some((name.to_string(), unescaped))
+}
+
+/// Reads the games of a PGN database one at a time, without loading the whole database into
+/// memory. The reader tolerates Windows line endings, a byte order mark, missing blank lines
+/// between games and games without tags.
+///
+/// Each game is yielded as a [PgnGame] without interpreting its moves, which is fast enough for
+/// scanning large databases, unless validation is enabled with
+/// [validate_moves](PgnReader::validate_moves). A malformed game is yielded as an error, after
+/// which reading continues with the next game. An error reading from the underlying reader is
+/// yielded once, after which the iterator ends.
+///
+/// # Example
+///
+/// ```rust
+/// use leben_chess::pgn::PgnReader;
+///
+/// let database = "[Event \"First\"]\n\n1. e4 e5 1-0\n\n[Event \"Second\"]\n\n1. d4 *\n";
+/// let events: Vec<String> = PgnReader::new(database.as_bytes())
+///     .filter_map(Result::ok)
+///     .map(|game| game.tag("Event").unwrap().to_string())
+///     .collect();
+/// assert_eq!(events, ["First", "Second"]);
+/// ```
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    line_number: usize,
+    buffer: Vec<u8>,
+    /// A line read past the end of the previous game, which starts the next game.
+    pending_line: Option<String>,
+    validate: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// returns: A reader of the games in the given database, which does not validate moves.
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            reader,
+            line_number: 0,
+            buffer: Vec::new(),
+            pending_line: None,
+            validate: false,
+            finished: false,
+        }
+    }
+
+    /// Sets whether the moves of each game are validated by replaying them with
+    /// [PgnGame::to_game] before the game is yielded, in which case games with illegal moves are
+    /// yielded as errors.
+    pub fn validate_moves(mut self, validate: bool) -> PgnReader<R> {
+        self.validate = validate;
+        self
+    }
+
+    /// returns: The next line without its line ending, or `None` at the end of the database.
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        if let Some(line) = self.pending_line.take() {
+            return Ok(Some(line));
+        }
+        self.buffer.clear();
+        if self.reader.read_until(b'\n', &mut self.buffer)? == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        let line = String::from_utf8_lossy(&self.buffer);
+        let line = line.strip_prefix('\u{feff}').unwrap_or(&line);
+        Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+    }
+
+    /// returns: The next game, or `None` at the end of the database.
+    fn read_game(&mut self) -> io::Result<Option<Result<PgnGame, PgnError>>> {
+        let mut game = PgnGame::default();
+        let mut error = None;
+        let mut in_movetext = false;
+        let mut in_comment = false;
+        while let Some(line) = self.next_line()? {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('%') && !in_comment {
+                // blank lines only matter for the number of the first line of a game
+                continue;
+            }
+            if game.line == 0 {
+                game.line = self.line_number;
+            }
+            if !in_comment && trimmed.starts_with('[') {
+                if in_movetext {
+                    // a tag of the next game, which was not separated by a blank line
+                    self.pending_line = Some(line);
+                    break;
+                }
+                match parse_tag(trimmed) {
+                    Some(tag) => game.tags.push(tag),
+                    None => {
+                        error.get_or_insert(PgnError::InvalidTag(self.line_number));
+                    }
+                }
+                continue;
+            }
+            in_movetext = true;
+            for ch in trimmed.chars() {
+                match ch {
+                    '{' => in_comment = true,
+                    '}' => in_comment = false,
+                    _ => {}
+                }
+            }
+            if !game.movetext.is_empty() {
+                game.movetext.push('\n');
+            }
+            game.movetext.push_str(trimmed);
+            let ends_with_result = trimmed.rsplit(char::is_whitespace).next()
+                .is_some_and(is_result_token);
+            if !in_comment && ends_with_result {
+                break;
+            }
+        }
+        if game.line == 0 {
+            return Ok(None);
+        }
+        if let Some(error) = error {
+            return Ok(Some(Err(error)));
+        }
+        if self.validate && let Err(error) = game.to_game() {
+            return Ok(Some(Err(error)));
+        }
+        Ok(Some(Ok(game)))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_game() {
+            Ok(Some(result)) => Some(result),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(PgnError::Io(error)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+    use crate::board::piece::PlayerColor;
+    use crate::chess::{GameStatus, WinReason};
+    use super::*;
+
+    fn fixture() -> PgnReader<BufReader<File>> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/database.pgn");
+        PgnReader::new(BufReader::new(File::open(path).unwrap()))
+    }
+
+    #[test]
+    fn read_database() {
+        let results: Vec<Result<PgnGame, PgnError>> = fixture().collect();
+        assert_eq!(results.len(), 5);
+        assert!(matches!(results[2], Err(PgnError::InvalidTag(_))));
+        let games: Vec<&PgnGame> = results.iter().filter_map(|result| result.as_ref().ok())
+            .collect();
+        let events: Vec<&str> = games.iter().map(|game| game.tag("Event").unwrap()).collect();
+        assert_eq!(events, ["Windows line endings", "No blank line", "Comments", "\"Quoted\""]);
+
+        let first = games[0].to_game().unwrap();
+        assert_eq!(games[0].line, 1);
+        assert_eq!(first.game_status(), &GameStatus::Win(PlayerColor::Black, WinReason::Checkmate));
+        let second = games[1].to_game().unwrap();
+        assert_eq!(second.history().len(), 3);
+        assert_eq!(games[2].san_moves().unwrap(), ["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+        let fourth = games[3].to_game().unwrap();
+        assert_eq!(fourth.fen(), "4k3/8/8/8/8/8/4PK2/8 b - - 1 1");
+    }
+
+    #[test]
+    fn time_control_round_trip() {
+        let time_control: TimeControl = "40/7200:1800+30".parse().unwrap();
+        let tag = time_control.to_string();
+        let pgn = crate::pgn::write_game(&ChessGame::new(Board::default_board()),
+                                         &[("Event", "Classical"), ("TimeControl", &tag)]);
+        let game = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(game.time_control(), Some(Ok(time_control)));
+        assert_eq!(game.tag("TimeControl"), Some("40/7200:1800+30"));
+        assert_eq!(PgnGame::default().time_control(), None);
+    }
+
+    #[test]
+    fn crazyhouse_round_trip() {
+        let mut game = ChessGame::with_variant(Board::default_board(), Variant::Crazyhouse);
+        for chess_move in ["e4", "d5", "exd5", "Qxd5", "P@e6", "Qa5", "exf7+", "Kd8", "P@e6"] {
+            game.try_move_str(chess_move).unwrap();
+        }
+        let pgn = crate::pgn::write_game(&game, &[("Event", "Crazyhouse")]);
+        assert!(pgn.contains("[Variant \"Crazyhouse\"]\n"));
+        assert!(pgn.contains("3. P@e6 Qa5 4. exf7+ Kd8 5. P@e6 *"));
+        let read = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+        let replayed = read.to_game().unwrap();
+        assert_eq!(replayed.variant(), Variant::Crazyhouse);
+        assert_eq!(replayed.fen(), game.fen());
+        assert_eq!(replayed.history(), game.history());
+        assert_eq!(replayed.hand(PlayerColor::Black), game.hand(PlayerColor::Black));
+
+        // drops are illegal in standard chess, and unknown variants are rejected
+        let standard = "[Event \"Standard\"]\n1. e4 d5 2. exd5 Qxd5 3. P@e6 *\n";
+        let error = PgnReader::new(standard.as_bytes()).next().unwrap().unwrap().to_game();
+        assert!(matches!(error, Err(PgnError::InvalidMove { ply: 5, .. })));
+        let unknown = "[Variant \"Atomic\"]\n1. e4 *\n";
+        let error = PgnReader::new(unknown.as_bytes()).next().unwrap().unwrap().to_game();
+        assert!(matches!(error, Err(PgnError::UnsupportedVariant(ref name)) if name == "Atomic"));
+    }
+
+    #[test]
+    fn validation() {
+        let database = "[Event \"Legal\"]\n1. e4 e5 *\n[Event \"Illegal\"]\n1. e4 e4 *\n1. d4 *\n";
+        let lazy: Vec<Result<PgnGame, PgnError>> = PgnReader::new(database.as_bytes()).collect();
+        assert_eq!(lazy.len(), 3);
+        assert!(lazy.iter().all(Result::is_ok));
+        let error = lazy[1].as_ref().unwrap().to_game().unwrap_err();
+        assert!(matches!(error, PgnError::InvalidMove { ref san, ply: 2, .. } if san == "e4"));
+        assert_eq!(lazy[2].as_ref().unwrap().tags, []);
+
+        let validated: Vec<Result<PgnGame, PgnError>> = PgnReader::new(database.as_bytes())
+            .validate_moves(true)
+            .collect();
+        assert_eq!(validated.len(), 3);
+        assert!(validated[0].is_ok() && validated[1].is_err() && validated[2].is_ok());
+    }
+}
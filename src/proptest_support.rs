@@ -0,0 +1,325 @@
+//! [proptest](https://docs.rs/proptest) strategies for generating legal chess positions and
+//! moves, for property testing code built on this library. Only available with the `proptest`
+//! feature.
+//!
+//! Positions are generated by playing a sequence of legal moves from the standard starting
+//! position, where each move is picked by an index into the [legal moves](ChessGame::legal_moves)
+//! of the position. Every generated position is therefore reachable in a real game, and failing
+//! cases shrink towards shorter games and towards earlier moves in the move order.
+//!
+//! # Example
+//!
+//! ```rust
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//! use leben_chess::proptest_support::legal_game;
+//!
+//! let mut runner = TestRunner::default();
+//! runner.run(&legal_game(10), |game| {
+//!     prop_assert!(game.history().len() <= 10);
+//!     Ok(())
+//! }).unwrap();
+//! ```
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use crate::board::Board;
+use crate::chess::ChessGame;
+use crate::moves::ChessMove;
+
+/// returns: A game from the standard starting position after playing the moves picked by
+///          `picks`, stopping early if the game ends.
+fn play_picks(picks: &[Index]) -> ChessGame {
+    let mut game = ChessGame::new(Board::default_board());
+    for pick in picks {
+        let legal_moves = game.legal_moves();
+        if legal_moves.is_empty() {
+            break;
+        }
+        // the move is legal, so this always succeeds
+        let _ = game.do_move(*pick.get(&legal_moves));
+    }
+    game
+}
+
+/// returns: A strategy for games started from the standard starting position, followed by up to
+///          `max_plies` random legal moves. Fewer moves are played if the game ends.
+pub fn legal_game(max_plies: usize) -> impl Strategy<Value = ChessGame> {
+    vec(any::<Index>(), 0..=max_plies).prop_map(|picks| play_picks(&picks))
+}
+
+/// returns: A strategy for the boards of the positions generated by [legal_game].
+pub fn legal_board(max_plies: usize) -> impl Strategy<Value = Board> {
+    legal_game(max_plies).prop_map(|game| game.board().clone())
+}
+
+/// returns: A strategy for games generated by [legal_game] together with one of the legal moves
+///          in their current position. Games which have ended are not generated.
+pub fn game_with_legal_move(max_plies: usize) -> impl Strategy<Value = (ChessGame, ChessMove)> {
+    (legal_game(max_plies), any::<Index>()).prop_filter_map("game has ended", |(game, pick)| {
+        let legal_moves = game.legal_moves();
+        if legal_moves.is_empty() {
+            return None;
+        }
+        let chess_move = *pick.get(&legal_moves);
+        Some((game, chess_move))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use crate::archive::GameArchive;
+    use crate::board::board_pos::BoardPosition;
+    use crate::board::piece::{Piece, PieceType, PlayerColor};
+    use crate::book::{self, Book};
+    use crate::clock::TimeControl;
+    use crate::moves;
+    use crate::moves::PieceMovement;
+    use crate::moves::util::BoardBitmap;
+    use crate::pgn::PgnReader;
+    use crate::uci;
+    use crate::uci::client::AnalysisInfo;
+    use super::*;
+
+    /// returns: The number of move sequences of length `depth` from the position, playing the
+    ///          moves on copies of the game.
+    fn perft(game: &ChessGame, depth: u32) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        game.legal_moves().into_iter()
+            .map(|chess_move| {
+                let mut child = game.clone();
+                child.do_move(chess_move).unwrap();
+                perft(&child, depth - 1)
+            })
+            .sum()
+    }
+
+    /// returns: The number of move sequences of length `depth` from the position, like [perft],
+    ///          but recomputing every position from scratch from its FEN string.
+    fn perft_from_fen(game: &ChessGame, depth: u32) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        let game = ChessGame::from_fen(&game.fen()).unwrap();
+        game.legal_moves().into_iter()
+            .map(|chess_move| {
+                let mut child = game.clone();
+                child.do_move(chess_move).unwrap();
+                perft_from_fen(&child, depth - 1)
+            })
+            .sum()
+    }
+
+    fn position_fields(fen: &str) -> Vec<&str> {
+        fen.split(' ').take(4).collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn undoing_a_move_restores_the_position((game, chess_move) in game_with_legal_move(20)) {
+            let mut undone = game.clone();
+            let details = undone.do_move(chess_move).unwrap();
+            prop_assert_eq!(undone.undo_move(), Some(details));
+            prop_assert_eq!(undone.fen(), game.fen());
+            prop_assert_eq!(undone.game_status(), game.game_status());
+            prop_assert_eq!(undone.legal_moves(), game.legal_moves());
+            prop_assert_eq!(undone.polyglot_key(), game.polyglot_key());
+            prop_assert_eq!(undone.repetition_count(), game.repetition_count());
+            prop_assert_eq!(undone.reversible_ply_count(), game.reversible_ply_count());
+            prop_assert_eq!(undone.history(), game.history());
+        }
+
+        #[test]
+        fn legal_moves_never_leave_the_king_in_check(game in legal_game(30)) {
+            let player = game.active_player();
+            for chess_move in game.legal_moves() {
+                let mut child = game.clone();
+                child.do_move(chess_move).unwrap();
+                prop_assert!(!moves::is_in_check(child.board(), player, game.variant()),
+                             "{:?} leaves the king in check in {}", chess_move, game.fen());
+            }
+        }
+
+        #[test]
+        fn fen_round_trips(game in legal_game(40)) {
+            let fen = game.fen();
+            let parsed = ChessGame::from_fen(&fen).unwrap();
+            let parsed_fen = parsed.fen();
+            prop_assert_eq!(position_fields(&parsed_fen), position_fields(&fen));
+            prop_assert_eq!(parsed.board(), game.board());
+            prop_assert_eq!(parsed.legal_moves(), game.legal_moves());
+        }
+
+        #[test]
+        fn equal_positions_have_equal_keys(game in legal_game(40)) {
+            let parsed = ChessGame::from_fen(&game.fen()).unwrap();
+            prop_assert_eq!(parsed.polyglot_key(), game.polyglot_key());
+            prop_assert_eq!(game.starting_position().polyglot_key(), 0x463B96181691FC9C);
+        }
+
+        #[test]
+        fn generated_moves_are_legal((game, chess_move) in game_with_legal_move(20)) {
+            prop_assert!(game.legal_moves().contains(&chess_move));
+            prop_assert!(game.clone().do_move(chess_move).is_ok());
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn perft_matches_recomputation(game in legal_game(20)) {
+            prop_assert_eq!(perft(&game, 2), perft_from_fen(&game, 2));
+        }
+
+        #[test]
+        fn legal_boards_have_both_kings(board in legal_board(20)) {
+            let kings = (&board).into_iter()
+                .filter(|(_, piece)| piece.is_some_and(|piece| piece.piece_type == PieceType::King))
+                .count();
+            prop_assert_eq!(kings, 2);
+        }
+
+        #[test]
+        fn board_transforms_are_involutions(board in legal_board(20)) {
+            prop_assert_eq!(board.flip_vertical().flip_vertical(), board.clone());
+            prop_assert_eq!(board.mirror_horizontal().mirror_horizontal(), board.clone());
+            prop_assert_eq!(board.swap_colors().swap_colors(), board.clone());
+            prop_assert_eq!(board.swap_colors().pieces().count(), board.pieces().count());
+        }
+    }
+
+    /// Parses a string with every parser taking a string, ignoring the results.
+    fn parse_str(game: &ChessGame, string: &str) {
+        let _ = string.parse::<BoardPosition>();
+        let _ = string.parse::<Piece>();
+        let _ = string.parse::<PieceType>();
+        let _ = string.parse::<PlayerColor>();
+        let _ = string.parse::<PieceMovement>();
+        let _ = string.parse::<BoardBitmap>();
+        let _ = string.parse::<TimeControl>();
+        let _ = Board::from_fen_string(string);
+        let _ = Board::from_diagram(string);
+        let _ = ChessGame::from_fen(string);
+        let _ = game.parse_san(string);
+        let _ = game.parse_coordinate_move(string);
+        let _ = game.clone().try_move_str(string);
+        let _ = uci::parse_move(string);
+        let _ = AnalysisInfo::parse(string);
+        let _ = PgnReader::new(Cursor::new(string.as_bytes())).count();
+    }
+
+    /// Decodes bytes with every decoder taking bytes, ignoring the results.
+    fn decode_bytes(bytes: &[u8]) {
+        let _ = Book::from_bytes(bytes);
+        let _ = GameArchive::decode(bytes);
+        let _ = GameArchive::decode_with_tags(bytes);
+        if let Ok(archive) = GameArchive::from_bytes(bytes.to_vec()) {
+            for index in 0..archive.len() {
+                let _ = archive.game(index);
+            }
+        }
+        let _ = PgnReader::new(Cursor::new(bytes)).count();
+    }
+
+    /// returns: A strategy for decimal numbers of any length, from single digits over numbers
+    ///          just below [u64::MAX] to numbers too long for any integer type.
+    fn long_number() -> impl Strategy<Value = String> {
+        prop_oneof![
+            (0..1000u64).prop_map(|number| number.to_string()),
+            (0..4u64).prop_map(|distance| (u64::MAX - distance).to_string()),
+            any::<u64>().prop_map(|number| number.to_string()),
+            "[0-9]{1,40}",
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn parsing_arbitrary_strings_never_panics(game in legal_game(10),
+                                                  string in any::<String>()) {
+            parse_str(&game, &string);
+        }
+
+        #[test]
+        fn parsing_notation_like_strings_never_panics(
+            game in legal_game(10),
+            string in "[a-hA-H1-9PNBRQKpnbrqkxX=O0+#@/:?*.G -]{0,40}",
+        ) {
+            parse_str(&game, &string);
+        }
+
+        #[test]
+        fn parsing_fen_like_strings_never_panics(
+            string in concat!("[1-9pnbrqkPNBRQK/]{0,72} [wbx] [KQkq-]{1,5} [a-h1-8-]{1,3} ",
+                              "[0-9-]{1,25} [0-9]{1,25}"),
+        ) {
+            parse_str(&ChessGame::new(Board::default_board()), &string);
+        }
+
+        #[test]
+        fn playing_from_fen_with_long_counters_never_panics(
+            picks in vec(any::<Index>(), 0..4),
+            game in legal_game(10),
+            halfmove_clock in long_number(),
+            fullmove_number in long_number(),
+        ) {
+            let position = game.fen().split(' ').take(4).collect::<Vec<_>>().join(" ");
+            let fen = format!("{} {} {}", position, halfmove_clock, fullmove_number);
+            if let Ok(mut game) = ChessGame::from_fen(&fen) {
+                for pick in picks {
+                    let legal_moves = game.legal_moves();
+                    if legal_moves.is_empty() {
+                        break;
+                    }
+                    let _ = game.do_move(*pick.get(&legal_moves));
+                }
+                let _ = game.fen();
+                let _ = game.movetext();
+                let _ = game.claim_fifty_move_draw();
+                let _ = GameArchive::decode(&GameArchive::encode(&game));
+            }
+        }
+
+        #[test]
+        fn parsing_time_controls_with_long_numbers_never_panics(
+            first in long_number(),
+            second in long_number(),
+            third in long_number(),
+        ) {
+            for string in [format!("G/{}", first), format!("G/{} d{}", first, second),
+                           format!("G/{};inc{}", first, second), format!("{}+{}", first, second),
+                           format!("{}/{}+{}", first, second, third),
+                           format!("{}/{}:{}", first, second, third)] {
+                if let Ok(time_control) = string.parse::<TimeControl>() {
+                    let _ = time_control.to_string().parse::<TimeControl>();
+                }
+            }
+        }
+
+        #[test]
+        fn decoding_arbitrary_bytes_never_panics(bytes in vec(any::<u8>(), 0..512),
+                                                 raw_move in any::<u16>()) {
+            decode_bytes(&bytes);
+            let _ = book::decode_move(raw_move);
+        }
+
+        #[test]
+        fn decoding_corrupted_archives_never_panics(game in legal_game(20),
+                                                    corruption in vec(any::<(Index, u8)>(), 1..4)) {
+            let mut bytes = GameArchive::encode_with_tags(&game, &[("Event", "Test")]);
+            for (index, byte) in corruption {
+                let index = index.index(bytes.len());
+                bytes[index] = byte;
+            }
+            decode_bytes(&bytes);
+        }
+    }
+}
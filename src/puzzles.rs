@@ -0,0 +1,251 @@
+//! Verification of chess puzzles with a forced solution, for example to check puzzles submitted
+//! by users for "cooks" (alternative solutions) and for defenses which refute the solution.
+//!
+//! A solution is the main line of the puzzle: the moves of the attacker, which is the player to
+//! move in the starting position, alternating with the replies of the defender, starting and
+//! ending with a move of the attacker. A solution is verified by exhaustively searching every
+//! reply of the defender, so the search time grows quickly with the length of the solution.
+
+use thiserror::Error;
+use crate::board::piece::PlayerColor;
+use crate::chess::ChessGame;
+use crate::moves::ChessMove;
+use crate::search::static_exchange;
+
+/// The goal the attacker has to reach within the moves of the solution.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PuzzleGoal {
+    /// The attacker checkmates the defender.
+    Mate,
+    /// The attacker gains at least the given number of centipawns of material, counting pieces
+    /// with the [piece values](ChessGame::piece_values) of the position. The material the
+    /// defender can win back immediately, according to [static_exchange], is subtracted.
+    /// Checkmating the defender also reaches this goal.
+    WinMaterial(i32),
+}
+
+/// An error found while verifying a puzzle. Plies are indices into the solution.
+#[derive(Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PuzzleError {
+    /// The move of the solution at the given ply is illegal.
+    #[error("the solution move at ply {0} is illegal")]
+    IllegalSolutionMove(usize),
+    /// A reply of the defender at the given ply, which may differ from the reply in the
+    /// solution, prevents the attacker from reaching the goal in the remaining moves.
+    #[error("the defending move {movement} at ply {0} refutes the solution",
+            movement = .1.piece_movement)]
+    DefenseRefutes(usize, ChessMove),
+    /// A move of the attacker other than the one in the solution also forces the goal in the
+    /// remaining moves.
+    #[error("the attacking move {movement} also solves the puzzle", movement = .0.piece_movement)]
+    AlternativeSolutionExists(ChessMove),
+    /// The solution is empty or ends with a move of the defender, or its last move does not
+    /// reach the goal.
+    #[error("the solution does not reach the goal of the puzzle")]
+    GoalNotReached,
+}
+
+struct Verifier {
+    attacker: PlayerColor,
+    goal: PuzzleGoal,
+    initial_balance: i32,
+}
+
+impl Verifier {
+    /// returns: Whether the goal has been reached in a position after a move of the attacker.
+    fn is_reached(&self, game: &ChessGame) -> bool {
+        if game.game_status().winner() == Some(self.attacker) {
+            return true;
+        }
+        match self.goal {
+            PuzzleGoal::Mate => false,
+            PuzzleGoal::WinMaterial(margin) => {
+                if game.game_status().is_over() {
+                    return false;
+                }
+                let recapture = game.legal_captures().into_iter()
+                    .map(|chess_move| static_exchange(game, chess_move))
+                    .max()
+                    .unwrap_or(0)
+                    .max(0);
+                let material = game.piece_values().material_balance(game.board(), self.attacker);
+                let balance = material - recapture;
+                balance - self.initial_balance >= margin
+            }
+        }
+    }
+
+    /// returns: Whether the attacker reaches the goal by playing `chess_move` in a position where
+    ///          it is the attacker's turn, and then at most `moves - 1` more moves, against any
+    ///          defense.
+    fn forces_goal_with(&self, game: &ChessGame, chess_move: ChessMove, moves: usize) -> bool {
+        let mut child = game.analysis_copy();
+        if child.do_move(chess_move).is_err() {
+            return false;
+        }
+        if self.is_reached(&child) {
+            return true;
+        }
+        if moves <= 1 || child.game_status().is_over() {
+            return false;
+        }
+        self.refutation(&child, moves - 1).is_none()
+    }
+
+    /// returns: A reply of the defender, in a position where it is the defender's turn, after
+    ///          which the attacker cannot force the goal in `moves` moves, if any.
+    fn refutation(&self, game: &ChessGame, moves: usize) -> Option<ChessMove> {
+        game.legal_moves().into_iter().find(|&reply| {
+            let mut child = game.analysis_copy();
+            child.do_move(reply).is_err() || !self.forces_goal(&child, moves)
+        })
+    }
+
+    /// returns: Whether the attacker can force the goal in `moves` moves, in a position where it
+    ///          is the attacker's turn.
+    fn forces_goal(&self, game: &ChessGame, moves: usize) -> bool {
+        game.legal_moves().into_iter()
+            .any(|chess_move| self.forces_goal_with(game, chess_move, moves))
+    }
+}
+
+/// Verifies that a puzzle has a unique forced solution leading to checkmate. See
+/// [verify_with_goal].
+pub fn verify(position: ChessGame, solution: &[ChessMove]) -> Result<(), PuzzleError> {
+    verify_with_goal(position, solution, PuzzleGoal::Mate)
+}
+
+/// Verifies that a puzzle has a unique forced solution:
+/// - every move of the solution is legal,
+/// - after each move of the attacker, every legal reply of the defender still allows the
+///   attacker to force the goal in the remaining moves of the solution,
+/// - no other move of the attacker forces the goal in the remaining moves, except for the last
+///   move of the solution, where any move reaching the goal is accepted, and
+/// - the last move of the solution reaches the goal.
+///
+/// # Arguments
+///
+/// * `position`: The starting position of the puzzle, with the attacker to move.
+/// * `solution`: The main line of the puzzle. See [the module documentation](self).
+/// * `goal`: The goal the attacker has to reach.
+///
+/// returns: `Ok(())` if the solution is verified, otherwise the first [PuzzleError] found
+///          along the main line.
+pub fn verify_with_goal(position: ChessGame, solution: &[ChessMove],
+                        goal: PuzzleGoal) -> Result<(), PuzzleError>
+{
+    if solution.len().is_multiple_of(2) {
+        return Err(PuzzleError::GoalNotReached);
+    }
+    let attacker = position.active_player();
+    let verifier = Verifier {
+        attacker,
+        goal,
+        initial_balance: position.piece_values().material_balance(position.board(), attacker),
+    };
+    let total_moves = solution.len().div_ceil(2);
+    let mut game = position;
+    for (ply, &chess_move) in solution.iter().enumerate() {
+        if !game.legal_moves().contains(&chess_move) {
+            return Err(PuzzleError::IllegalSolutionMove(ply));
+        }
+        if ply % 2 == 1 {
+            // the defender's reply in the main line, which has already been checked along with
+            // all other replies
+            let _ = game.do_move(chess_move);
+            continue;
+        }
+
+        let remaining_moves = total_moves - ply / 2;
+        let previous = game.clone();
+        let _ = game.do_move(chess_move);
+        if remaining_moves == 1 {
+            return if verifier.is_reached(&game) {
+                Ok(())
+            } else {
+                Err(PuzzleError::GoalNotReached)
+            };
+        }
+        if game.game_status().is_over() {
+            return Err(PuzzleError::IllegalSolutionMove(ply + 1));
+        }
+        if let Some(reply) = verifier.refutation(&game, remaining_moves - 1) {
+            return Err(PuzzleError::DefenseRefutes(ply + 1, reply));
+        }
+        let alternative = previous.legal_moves().into_iter()
+            .filter(|&other_move| other_move != chess_move)
+            .find(|&other_move| verifier.forces_goal_with(&previous, other_move, remaining_moves));
+        if let Some(alternative) = alternative {
+            return Err(PuzzleError::AlternativeSolutionExists(alternative));
+        }
+    }
+    Err(PuzzleError::GoalNotReached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::moves::CastlingRights;
+
+    fn position(fen: &str) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string(fen).unwrap(), PlayerColor::White, no_rights,
+                          None).unwrap();
+        game
+    }
+
+    fn moves(coords: &[(&str, &str)]) -> Vec<ChessMove> {
+        coords.iter().map(|(from, to)| ChessMove::from_coords(from, to).unwrap()).collect()
+    }
+
+    #[test]
+    fn mate_in_two() {
+        let game = position("7k/8/5K2/8/8/8/8/6R1");
+        let solution = moves(&[("f6", "f7"), ("h8", "h7"), ("g1", "h1")]);
+        assert_eq!(verify(game.clone(), &solution), Ok(()));
+
+        assert_eq!(verify(game.clone(), &solution[..1]), Err(PuzzleError::GoalNotReached));
+        assert_eq!(verify(game.clone(), &solution[..2]), Err(PuzzleError::GoalNotReached));
+        let illegal = moves(&[("f6", "f7"), ("h8", "g8"), ("g1", "h1")]);
+        assert_eq!(verify(game.clone(), &illegal), Err(PuzzleError::IllegalSolutionMove(1)));
+        let not_mate = moves(&[("f6", "f7"), ("h8", "h7"), ("g1", "g2")]);
+        assert_eq!(verify(game.clone(), &not_mate), Err(PuzzleError::GoalNotReached));
+    }
+
+    #[test]
+    fn refuted_solution() {
+        let game = position("7k/8/5K2/8/8/8/8/6R1");
+        let solution = moves(&[("g1", "h1"), ("h8", "g8"), ("h1", "h8")]);
+        let Err(PuzzleError::DefenseRefutes(1, _)) = verify(game, &solution) else {
+            panic!("expected a refutation at ply 1");
+        };
+    }
+
+    #[test]
+    fn cooked_puzzle() {
+        // the second rook allows more than one way to mate in two
+        let game = position("7k/8/5K2/8/8/8/8/R5R1");
+        let solution = moves(&[("f6", "f7"), ("h8", "h7"), ("g1", "h1")]);
+        let Err(PuzzleError::AlternativeSolutionExists(_)) = verify(game, &solution) else {
+            panic!("expected an alternative solution");
+        };
+    }
+
+    #[test]
+    fn win_material() {
+        // the knight forks the king and the queen
+        let game = position("4k3/1q6/8/8/4N3/8/8/4K3");
+        let solution = moves(&[("e4", "d6"), ("e8", "e7"), ("d6", "b7")]);
+        let goal = PuzzleGoal::WinMaterial(500);
+        assert_eq!(verify_with_goal(game.clone(), &solution, goal), Ok(()));
+        assert_eq!(verify_with_goal(game.clone(), &solution[..1], goal),
+                   Err(PuzzleError::GoalNotReached));
+        let Err(PuzzleError::DefenseRefutes(1, _)) =
+            verify_with_goal(game, &solution, PuzzleGoal::WinMaterial(1000))
+        else {
+            panic!("expected a refutation at ply 1");
+        };
+    }
+}
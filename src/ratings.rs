@@ -0,0 +1,237 @@
+//! Rating updates for game results, with the Elo and Glicko-2 rating systems. Only available with
+//! the `std` feature.
+//!
+//! Results are given as the [Outcome] of a game, as returned by [GameStatus::outcome].
+//!
+//! see: [Elo rating system - Wikipedia](https://en.wikipedia.org/wiki/Elo_rating_system)
+//!
+//! see: [Example of the Glicko-2 system](http://www.glicko.net/glicko/glicko2.pdf)
+//!
+//! [GameStatus::outcome]: crate::chess::GameStatus::outcome
+
+use std::f64::consts::PI;
+use crate::board::piece::PlayerColor;
+use crate::chess::Outcome;
+
+/// The factor converting between ratings on the Glicko scale and the internal Glicko-2 scale.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The convergence tolerance of the volatility iteration of Glicko-2.
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+
+/// returns: The expected score of a player with rating `rating` against a player with rating
+///          `opponent_rating` in the Elo system, between 0 and 1.
+pub fn elo_expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+/// Updates the Elo ratings of the two players of a game.
+///
+/// # Arguments
+///
+/// * `white_rating`: The rating of the player with the white pieces before the game.
+/// * `black_rating`: The rating of the player with the black pieces before the game.
+/// * `k`: The K-factor, the maximum rating change, for example `20.0`.
+/// * `outcome`: The outcome of the game.
+///
+/// returns: The new ratings of white and black.
+pub fn elo_update(white_rating: f64, black_rating: f64, k: f64, outcome: Outcome) -> (f64, f64) {
+    let expected = elo_expected_score(white_rating, black_rating);
+    let change = k * (outcome.score(PlayerColor::White) - expected);
+    (white_rating + change, black_rating - change)
+}
+
+/// A rating in the Glicko-2 system, given on the same scale as Elo ratings.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glicko2Rating {
+    /// The rating, 1500 for a new player.
+    pub rating: f64,
+    /// The rating deviation, the uncertainty of the rating, 350 for a new player.
+    pub deviation: f64,
+    /// The volatility, the expected fluctuation of the rating, 0.06 for a new player.
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Glicko2Rating::new(1500.0, 350.0, 0.06)
+    }
+}
+
+/// The opponent of a player in a game rated with [Glicko2Rating::update].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glicko2Opponent {
+    /// The rating of the opponent at the start of the rating period.
+    pub rating: Glicko2Rating,
+    /// The color the opponent played with, which decides who won an [Outcome].
+    pub color: PlayerColor,
+}
+
+impl Glicko2Opponent {
+    /// returns: An opponent with the rating who played with the given color.
+    pub fn new(rating: Glicko2Rating, color: PlayerColor) -> Glicko2Opponent {
+        Glicko2Opponent { rating, color }
+    }
+}
+
+impl Glicko2Rating {
+    /// The system constant τ used by [update](Glicko2Rating::update), which constrains the change
+    /// in volatility over time.
+    pub const DEFAULT_SYSTEM_CONSTANT: f64 = 0.5;
+
+    /// returns: A rating with the given rating, rating deviation and volatility, see the fields of
+    ///          [Glicko2Rating]. A new player starts with the [default](Glicko2Rating::default)
+    ///          of 1500, 350 and 0.06.
+    pub fn new(rating: f64, deviation: f64, volatility: f64) -> Glicko2Rating {
+        Glicko2Rating { rating, deviation, volatility }
+    }
+
+    /// Computes the rating after a rating period, following the published Glicko-2 algorithm
+    /// with the system constant [DEFAULT_SYSTEM_CONSTANT](Glicko2Rating::DEFAULT_SYSTEM_CONSTANT).
+    ///
+    /// # Arguments
+    ///
+    /// * `games`: The games played by the player during the rating period, with the opponent
+    ///   and the [Outcome] of the game, as returned by
+    ///   [GameStatus::outcome](crate::chess::GameStatus::outcome). The player plays with the
+    ///   other color than the opponent, and scores 1 for a win, 0.5 for a draw and 0 for a loss.
+    ///   If there are no games, only the deviation increases.
+    ///
+    /// returns: The rating of the player at the end of the rating period.
+    pub fn update(&self, games: &[(Glicko2Opponent, Outcome)]) -> Glicko2Rating {
+        self.update_with_system_constant(games, Glicko2Rating::DEFAULT_SYSTEM_CONSTANT)
+    }
+
+    /// Computes the rating after a rating period like [update](Glicko2Rating::update), with the
+    /// given system constant τ. Reasonable values are between 0.3 and 1.2.
+    pub fn update_with_system_constant(&self, games: &[(Glicko2Opponent, Outcome)],
+                                       system_constant: f64) -> Glicko2Rating {
+        let mu = (self.rating - 1500.0) / GLICKO2_SCALE;
+        let phi = self.deviation / GLICKO2_SCALE;
+        let sigma = self.volatility;
+        if games.is_empty() {
+            let deviation = (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE;
+            return Glicko2Rating { deviation, ..*self };
+        }
+
+        let mut variance_inverse = 0.0;
+        let mut score_sum = 0.0;
+        for (opponent, outcome) in games {
+            let score = outcome.score(opponent.color.other_player());
+            let opponent_mu = (opponent.rating.rating - 1500.0) / GLICKO2_SCALE;
+            let g = g(opponent.rating.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+            variance_inverse += g * g * expected * (1.0 - expected);
+            score_sum += g * (score - expected);
+        }
+        let variance = 1.0 / variance_inverse;
+        let delta = variance * score_sum;
+
+        let sigma = new_volatility(phi, sigma, variance, delta, system_constant);
+        let phi_star = (phi * phi + sigma * sigma).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+        let mu = mu + phi * phi * score_sum;
+        Glicko2Rating {
+            rating: mu * GLICKO2_SCALE + 1500.0,
+            deviation: phi * GLICKO2_SCALE,
+            volatility: sigma,
+        }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+/// returns: The new volatility, computed with the Illinois algorithm as in step 5 of the
+///          Glicko-2 algorithm.
+fn new_volatility(phi: f64, sigma: f64, variance: f64, delta: f64, tau: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denominator = phi * phi + variance + ex;
+        ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+            - (x - a) / (tau * tau)
+    };
+    let mut upper = a;
+    let mut lower = if delta * delta > phi * phi + variance {
+        (delta * delta - phi * phi - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+    let mut f_upper = f(upper);
+    let mut f_lower = f(lower);
+    while (lower - upper).abs() > CONVERGENCE_TOLERANCE {
+        let c = upper + (upper - lower) * f_upper / (f_lower - f_upper);
+        let f_c = f(c);
+        if f_c * f_lower <= 0.0 {
+            upper = lower;
+            f_upper = f_lower;
+        } else {
+            f_upper /= 2.0;
+        }
+        lower = c;
+        f_lower = f_c;
+    }
+    (upper / 2.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elo_expected_scores() {
+        assert_eq!(elo_expected_score(1500.0, 1500.0), 0.5);
+        assert!((elo_expected_score(1900.0, 1500.0) - 10.0 / 11.0).abs() < 1e-9);
+        assert!((elo_expected_score(1500.0, 1900.0) - 1.0 / 11.0).abs() < 1e-9);
+        assert!((elo_expected_score(1700.0, 1500.0) - 0.759747).abs() < 1e-6);
+        assert!((elo_expected_score(1600.0, 1500.0) - 0.640065).abs() < 1e-6);
+    }
+
+    #[test]
+    fn elo_updates() {
+        let (white, black) = elo_update(1500.0, 1500.0, 20.0, Outcome::Win(PlayerColor::White));
+        assert_eq!((white, black), (1510.0, 1490.0));
+        let (white, black) = elo_update(1500.0, 1500.0, 20.0, Outcome::Draw);
+        assert_eq!((white, black), (1500.0, 1500.0));
+        let (white, black) = elo_update(1900.0, 1500.0, 32.0, Outcome::Win(PlayerColor::Black));
+        assert!((white - (1900.0 - 32.0 * 10.0 / 11.0)).abs() < 1e-9);
+        assert!((black - (1500.0 + 32.0 * 10.0 / 11.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn glicko2_paper_example() {
+        let player = Glicko2Rating::new(1500.0, 200.0, 0.06);
+        let opponent = |rating, deviation, color| {
+            Glicko2Opponent::new(Glicko2Rating::new(rating, deviation, 0.06), color)
+        };
+        let games = [
+            (opponent(1400.0, 30.0, PlayerColor::Black), Outcome::Win(PlayerColor::White)),
+            (opponent(1550.0, 100.0, PlayerColor::White), Outcome::Win(PlayerColor::White)),
+            (opponent(1700.0, 300.0, PlayerColor::Black), Outcome::Win(PlayerColor::Black)),
+        ];
+        let updated = player.update(&games);
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001, "{:?}", updated);
+
+        // a draw scores half a point whatever the colors
+        let drawn = player.update(&[(opponent(1500.0, 100.0, PlayerColor::White), Outcome::Draw)]);
+        assert!((drawn.rating - 1500.0).abs() < 1e-9, "{:?}", drawn);
+    }
+
+    #[test]
+    fn glicko2_without_games() {
+        let player = Glicko2Rating::new(1500.0, 200.0, 0.06);
+        let updated = player.update(&[]);
+        assert_eq!((updated.rating, updated.volatility), (1500.0, 0.06));
+        assert!((updated.deviation - (200f64.powi(2) + (0.06 * GLICKO2_SCALE).powi(2)).sqrt())
+            .abs() < 1e-9);
+        assert_eq!(Glicko2Rating::default().deviation, 350.0);
+    }
+}
@@ -0,0 +1,786 @@
+//! A simple game tree search for finding good moves, for example for computer opponents or for
+//! suggesting moves to a player.
+//!
+//! The search is a negamax search with alpha-beta pruning over the
+//! [legal moves](ChessGame::legal_moves) of a game. At the leaves, a quiescence search follows
+//! the captures and promotions available until a quiet position is reached, which is evaluated
+//! with an [Evaluator], so that exchanges in progress are not misjudged. The [StandardEvaluator]
+//! using [evaluate](crate::eval::evaluate) is used unless another one is given, see
+//! [search_with]. Scores are given in centipawns from the perspective of the player to move. A
+//! checkmate is scored as [MATE_SCORE] minus the number of plies until the checkmate, so that
+//! faster checkmates are preferred, and a stalemate is scored as 0.
+//!
+//! [best_move] searches to a fixed depth, while [search] uses iterative deepening to search within
+//! given [SearchLimits], for example a time limit. Moves are searched in the order given by the
+//! [ordering] module. The search makes and unmakes its moves in a single copy of the game, which
+//! only keeps the position up to date, so that the history, the positions kept for detecting
+//! repetitions and the observer of the game are not touched for each node.
+//!
+//! see: [Alpha-Beta - Chessprogramming wiki](https://www.chessprogramming.org/Alpha-Beta)
+
+pub mod blunder;
+pub mod cache;
+pub mod ordering;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::ChessGame;
+use crate::eval::{Evaluator, PieceValues, StandardEvaluator, CENTIPAWNS_PER_PAWN};
+pub use crate::eval::{is_mate_score, MATE_SCORE, MAX_MATE_PLY};
+use crate::moves;
+use crate::moves::{ChessMove, PieceMovement, PromotionType};
+use crate::search::ordering::{is_quiet, order_moves, order_moves_with_killers, KillerMoves};
+
+/// returns: The score of a game which is over, from the perspective of the player to move.
+pub(crate) fn game_over_score(game: &ChessGame, ply: u32) -> i32 {
+    match game.game_status().winner() {
+        Some(winner) if winner == game.active_player() => MATE_SCORE - ply as i32,
+        Some(_) => -(MATE_SCORE - ply as i32),
+        None => 0,
+    }
+}
+
+/// returns: The static evaluation of a game from the perspective of the player to move.
+fn relative_evaluation<E: Evaluator + ?Sized>(evaluator: &E, game: &ChessGame) -> i32 {
+    match game.active_player() {
+        PlayerColor::White => evaluator.evaluate(game),
+        PlayerColor::Black => -evaluator.evaluate(game),
+    }
+}
+
+/// The value of a king in a [static exchange](static_exchange), which exceeds the value of all
+/// other pieces combined so that the king is only used to capture last.
+const KING_EXCHANGE_VALUE: i32 = 100 * CENTIPAWNS_PER_PAWN;
+
+fn exchange_value(values: &PieceValues, piece_type: PieceType) -> i32 {
+    values.value(piece_type).unwrap_or(KING_EXCHANGE_VALUE)
+}
+
+/// Statically evaluates the sequence of captures on the destination square of a move, assuming
+/// that both players recapture with their least valuable piece for as long as it gains material,
+/// and disregarding pins and checks. This is used to prune obviously losing captures from the
+/// quiescence search. Pieces are counted with the [piece values](ChessGame::piece_values) of the
+/// game.
+///
+/// see: [Static Exchange Evaluation - Chessprogramming wiki](https://www.chessprogramming.org/Static_Exchange_Evaluation)
+///
+/// returns: The material gained by the move in centipawns, from the perspective of the player
+///          making the move. Returns 0 if there is no piece on the origin square.
+pub fn static_exchange(game: &ChessGame, chess_move: ChessMove) -> i32 {
+    let PieceMovement { from, to } = chess_move.piece_movement;
+    let values = game.piece_values();
+    let exchange_value = |piece_type| exchange_value(values, piece_type);
+    let mut board = game.board().clone();
+    let Some(piece) = board.get_piece(from) else {
+        return 0;
+    };
+    let mut captured_value = board.get_piece(to).map_or(0, |piece| exchange_value(piece.piece_type));
+    if piece.piece_type == PieceType::Pawn && Some(to) == game.en_passant_target()
+        && let Some(pos) = to.add((0, -piece.player.pawn_direction()))
+    {
+        board.set_piece(pos, None);
+        captured_value = exchange_value(PieceType::Pawn);
+    }
+    let mut moved_piece = piece;
+    if let Some(promotion) = chess_move.promotion {
+        moved_piece = promotion.to_piece(piece.player);
+        captured_value += exchange_value(moved_piece.piece_type) - exchange_value(PieceType::Pawn);
+    }
+    board.set_piece(from, None);
+    board.set_piece(to, Some(moved_piece));
+
+    let mut gains = vec![captured_value];
+    let mut value_on_square = exchange_value(moved_piece.piece_type);
+    let mut player = piece.player.other_player();
+    loop {
+        // the piece on the square belongs to the other player
+        let attackers = moves::attacking_pieces(&board, player.other_player(), to);
+        let least_valuable_attacker = BoardPosition::all()
+            .filter(|&pos| attackers.get(pos))
+            .filter_map(|pos| board.get_piece(pos).map(|piece| (pos, piece)))
+            .min_by_key(|(_, piece)| exchange_value(piece.piece_type));
+        let Some((pos, attacker)) = least_valuable_attacker else {
+            break;
+        };
+        let previous_gain = gains[gains.len() - 1];
+        gains.push(value_on_square - previous_gain);
+        value_on_square = exchange_value(attacker.piece_type);
+        board.set_piece(pos, None);
+        board.set_piece(to, Some(attacker));
+        player = player.other_player();
+    }
+    // each player may stop capturing when continuing would lose material
+    let mut gain = gains.pop().unwrap_or(captured_value);
+    while let Some(previous_gain) = gains.pop() {
+        gain = -(-previous_gain).max(gain);
+    }
+    gain
+}
+
+/// Limits for a [search], for example from the time control of a game or from commands sent to an
+/// engine. The search stops when any of the limits is reached. A search without any limits
+/// continues until [MAX_DEPTH] is reached, which may take a very long time.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// The highest depth in plies to search to.
+    pub max_depth: Option<u32>,
+    /// The highest number of nodes to search.
+    pub max_nodes: Option<u64>,
+    /// The longest time to search for.
+    pub max_time: Option<Duration>,
+    /// A flag which stops the search when set, for example from another thread.
+    pub stop_flag: Arc<AtomicBool>,
+}
+
+/// Statistics about a completed iteration of a [search].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IterationInfo {
+    /// The depth in plies searched in the iteration.
+    pub depth: u32,
+    /// The total number of nodes searched, including the nodes of earlier iterations.
+    pub nodes: u64,
+    /// The score of the best move found in the iteration.
+    pub score: i32,
+    /// The time elapsed since the search started.
+    pub elapsed: Duration,
+}
+
+/// The result of a [search].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchResult {
+    /// The best move found in the last completed iteration.
+    pub best_move: ChessMove,
+    /// The score of the best move from the perspective of the player to move.
+    pub score: i32,
+    /// The line of best play found in the last completed iteration, starting with `best_move`.
+    pub principal_variation: Vec<ChessMove>,
+    /// Statistics about each completed iteration, in order of depth.
+    pub iterations: Vec<IterationInfo>,
+}
+
+/// One of the lines found by [best_moves].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchLine {
+    /// The first move of the line.
+    pub root_move: ChessMove,
+    /// The score of the line from the perspective of the player to move.
+    pub score: i32,
+    /// The depth in plies of the last completed iteration of the search for the line.
+    pub depth: u32,
+    /// The line of best play after `root_move`, starting with `root_move`.
+    pub principal_variation: Vec<ChessMove>,
+}
+
+/// The highest depth in plies a [search] searches to.
+pub const MAX_DEPTH: u32 = 64;
+
+/// The number of nodes searched between checks of the time limit and the stop flag.
+const CHECK_INTERVAL: u64 = 2048;
+
+struct Searcher<'a, E: Evaluator + ?Sized> {
+    limits: &'a SearchLimits,
+    evaluator: &'a E,
+    excluded_root_moves: &'a [ChessMove],
+    start: Instant,
+    nodes: u64,
+    iteration_depth: u32,
+    aborted: bool,
+    previous_pv: Vec<ChessMove>,
+    follow_pv: bool,
+    killers: KillerMoves,
+}
+
+impl<E: Evaluator + ?Sized> Searcher<'_, E> {
+    /// returns: Whether the current iteration should be aborted. The first iteration is never
+    ///          aborted.
+    fn should_abort(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        if self.iteration_depth <= 1 {
+            return false;
+        }
+        if self.limits.max_nodes.is_some_and(|max_nodes| self.nodes >= max_nodes) {
+            self.aborted = true;
+        } else if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.aborted = self.limits.stop_flag.load(Ordering::Relaxed)
+                || self.limits.max_time.is_some_and(|max_time| self.start.elapsed() >= max_time);
+        }
+        self.aborted
+    }
+
+    /// Searches a node, storing the line of best play from the node in `pv`. While `follow_pv`
+    /// is set, the node lies on the principal variation of the previous iteration, and the next
+    /// move of that variation is searched first. The moves are made and unmade in `game`, which
+    /// is in the same position again when the search of the node returns.
+    fn negamax(&mut self, game: &mut ChessGame, depth: u32, ply: u32, mut alpha: i32, beta: i32,
+               pv: &mut Vec<ChessMove>) -> i32
+    {
+        pv.clear();
+        if depth == 0 {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+        self.nodes += 1;
+        if game.game_status().is_over() {
+            return game_over_score(game, ply);
+        }
+
+        let mut legal_moves = game.legal_moves();
+        if ply == 0 {
+            legal_moves.retain(|chess_move| !self.excluded_root_moves.contains(chess_move));
+        }
+        let pv_move = self.previous_pv.get(ply as usize).copied().filter(|_| self.follow_pv);
+        if pv_move.is_none_or(|pv_move| !legal_moves.contains(&pv_move)) {
+            self.follow_pv = false;
+        }
+        order_moves_with_killers(game, &mut legal_moves, pv_move, &self.killers.get(ply));
+
+        let mut best_score = -MATE_SCORE;
+        let mut child_pv = Vec::new();
+        for (i, chess_move) in legal_moves.into_iter().enumerate() {
+            if i > 0 {
+                self.follow_pv = false;
+            }
+            if self.should_abort() {
+                return 0;
+            }
+            let Ok(made_move) = game.make_move(chess_move) else {
+                continue;
+            };
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            game.unmake_move(made_move);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(chess_move);
+                    pv.extend_from_slice(&child_pv);
+                }
+            }
+            if alpha >= beta {
+                if is_quiet(game, chess_move) {
+                    self.killers.store(ply, chess_move);
+                }
+                break;
+            }
+        }
+        best_score
+    }
+
+    /// Searches the captures and promotions of a node until a quiet position is reached, so that
+    /// positions are not evaluated in the middle of an exchange. The player to move may also
+    /// choose not to capture, in which case the node is evaluated statically. Captures losing
+    /// material according to [static_exchange] and underpromotions are not searched. The moves
+    /// are made and unmade in `game` like in [negamax](Searcher::negamax).
+    fn quiescence(&mut self, game: &mut ChessGame, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if game.game_status().is_over() {
+            return game_over_score(game, ply);
+        }
+        let mut best_score = relative_evaluation(self.evaluator, game);
+        if best_score >= beta {
+            return best_score;
+        }
+        alpha = alpha.max(best_score);
+        let mut tactical_moves = game.legal_captures();
+        tactical_moves.extend(quiet_queen_promotions(game));
+        order_moves(game, &mut tactical_moves, None);
+        for chess_move in tactical_moves {
+            if chess_move.promotion.is_some_and(|promotion| promotion != PromotionType::Queen)
+                || static_exchange(game, chess_move) < 0
+            {
+                continue;
+            }
+            if self.should_abort() {
+                return 0;
+            }
+            let Ok(made_move) = game.make_move(chess_move) else {
+                continue;
+            };
+            let score = -self.quiescence(game, ply + 1, -beta, -alpha);
+            game.unmake_move(made_move);
+            if self.aborted {
+                return 0;
+            }
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+}
+
+/// returns: The legal promotions to a queen of the active player which do not capture a piece.
+///          Together with the [legal captures](ChessGame::legal_captures), these are the moves
+///          searched by the quiescence search.
+fn quiet_queen_promotions(game: &ChessGame) -> impl Iterator<Item = ChessMove> + '_ {
+    BoardPosition::all()
+        .filter(|&from| game.expects_promotion_move(from))
+        .flat_map(move |from| {
+            BoardPosition::all()
+                .filter(move |&to| game.available_moves(from).get(to)
+                    && game.board().get_piece(to).is_none())
+                .map(move |to| ChessMove::promoting(from, to, PromotionType::Queen))
+        })
+}
+
+/// Searches the game tree with iterative deepening to find the best move for the player to move:
+/// the game tree is searched to a depth of 1, 2, 3 and so on, until one of the `limits` is
+/// reached. The game itself is not modified. See [the module documentation](self).
+///
+/// The node and time limits and the stop flag are checked during the search, every few thousand
+/// nodes for the time limit and the stop flag. When a limit is reached during an iteration, the
+/// iteration is abandoned and the result of the previous iteration is returned. The first
+/// iteration is always completed, so that a move is found even if the search is stopped
+/// immediately. The search also stops early once a forced checkmate has been found.
+///
+/// returns: `Some(SearchResult)` of the last completed iteration, or `None` if the game is over.
+pub fn search(game: &ChessGame, limits: &SearchLimits) -> Option<SearchResult> {
+    search_with(game, limits, &StandardEvaluator)
+}
+
+/// Searches like [search], evaluating the positions at the leaves of the game tree with the
+/// given [Evaluator] instead of the [StandardEvaluator]. The limits are handled like in
+/// [search].
+///
+/// returns: `Some(SearchResult)` of the last completed iteration, or `None` if the game is over.
+pub fn search_with<E: Evaluator + ?Sized>(game: &ChessGame, limits: &SearchLimits, evaluator: &E)
+                                          -> Option<SearchResult>
+{
+    search_excluding(game, limits, evaluator, &[])
+}
+
+/// Searches like [search_with], without considering the given moves at the root.
+///
+/// returns: `Some(SearchResult)` of the last completed iteration, or `None` if the game is over
+///          or every legal move is excluded.
+fn search_excluding<E: Evaluator + ?Sized>(game: &ChessGame, limits: &SearchLimits,
+                                           evaluator: &E, excluded_root_moves: &[ChessMove])
+                                           -> Option<SearchResult>
+{
+    if game.game_status().is_over() {
+        return None;
+    }
+    let mut searcher = Searcher {
+        limits,
+        evaluator,
+        excluded_root_moves,
+        start: Instant::now(),
+        nodes: 0,
+        iteration_depth: 0,
+        aborted: false,
+        previous_pv: Vec::new(),
+        follow_pv: false,
+        killers: KillerMoves::new(),
+    };
+    // the moves of the search are made and unmade in one copy of the game, which does not fill
+    // an attached analysis cache with the positions of the search
+    let mut position = game.analysis_copy();
+    position.set_analysis_cache(None);
+    let max_depth = limits.max_depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let mut result: Option<SearchResult> = None;
+    for depth in 1..=max_depth {
+        searcher.iteration_depth = depth;
+        searcher.follow_pv = true;
+        let mut pv = Vec::new();
+        let score = searcher.negamax(&mut position, depth, 0, -MATE_SCORE, MATE_SCORE, &mut pv);
+        // an abandoned iteration unmakes its moves too
+        debug_assert_eq!(position.polyglot_key(), game.polyglot_key());
+        if searcher.aborted || pv.is_empty() {
+            break;
+        }
+        let iteration = IterationInfo {
+            depth,
+            nodes: searcher.nodes,
+            score,
+            elapsed: searcher.start.elapsed(),
+        };
+        let mut iterations = result.map(|result| result.iterations).unwrap_or_default();
+        iterations.push(iteration);
+        result = Some(SearchResult {
+            best_move: pv[0],
+            score,
+            principal_variation: pv.clone(),
+            iterations,
+        });
+        searcher.previous_pv = pv;
+
+        if is_mate_score(score)
+            || limits.stop_flag.load(Ordering::Relaxed)
+            || limits.max_nodes.is_some_and(|max_nodes| searcher.nodes >= max_nodes)
+            || limits.max_time.is_some_and(|max_time| searcher.start.elapsed() >= max_time)
+        {
+            break;
+        }
+    }
+    result
+}
+
+/// Finds the best lines for the player to move, each starting with a different move, like the
+/// multi-PV mode of chess engines. Each line is found with a [search] within the given `limits`
+/// which excludes the first moves of the lines found before it, so the limits apply to each line
+/// separately. The game itself is not modified.
+///
+/// # Arguments
+///
+/// * `game`: The game to find lines in.
+/// * `limits`: The limits of the search for each line.
+/// * `count`: The number of lines to find.
+///
+/// returns: At most `count` lines, sorted by score from the best line for the player to move to
+///          the worst. Fewer lines are returned if there are fewer legal moves, and none if the
+///          game is over.
+pub fn best_moves(game: &ChessGame, limits: &SearchLimits, count: usize) -> Vec<SearchLine> {
+    let mut lines: Vec<SearchLine> = Vec::new();
+    let mut excluded_root_moves = Vec::new();
+    while lines.len() < count {
+        let Some(result) = search_excluding(game, limits, &StandardEvaluator,
+                                            &excluded_root_moves) else {
+            break;
+        };
+        excluded_root_moves.push(result.best_move);
+        lines.push(SearchLine {
+            root_move: result.best_move,
+            score: result.score,
+            depth: result.iterations.last().map_or(0, |iteration| iteration.depth),
+            principal_variation: result.principal_variation,
+        });
+    }
+    // a later search may find a better score if it reached a greater depth
+    lines.sort_by_key(|line| core::cmp::Reverse(line.score));
+    lines
+}
+
+/// Searches the game tree to a fixed depth to find the best move for the player to move. The game
+/// itself is not modified. See [search] for searching with other limits.
+///
+/// # Arguments
+///
+/// * `game`: The game to find a move in.
+/// * `depth`: The number of plies to search. A depth of 0 is treated as a depth of 1.
+///
+/// returns: `Some((ChessMove, i32))` of the best move found and its score from the perspective of
+///          the player to move, or `None` if the game is over.
+pub fn best_move(game: &ChessGame, depth: u32) -> Option<(ChessMove, i32)> {
+    best_move_with(game, depth, &StandardEvaluator)
+}
+
+/// Searches the game tree to a fixed depth like [best_move], evaluating positions with the given
+/// [Evaluator].
+///
+/// returns: `Some((ChessMove, i32))` of the best move found and its score from the perspective of
+///          the player to move, or `None` if the game is over.
+pub fn best_move_with<E: Evaluator + ?Sized>(game: &ChessGame, depth: u32, evaluator: &E)
+                                             -> Option<(ChessMove, i32)>
+{
+    let limits = SearchLimits { max_depth: Some(depth), ..SearchLimits::default() };
+    search_with(game, &limits, evaluator).map(|result| (result.best_move, result.score))
+}
+
+impl ChessGame {
+    /// Searches for the best move for the player to move. See [best_move].
+    ///
+    /// returns: `Some((ChessMove, i32))` of the best move found and its score from the perspective
+    ///          of the player to move, or `None` if the game is over.
+    pub fn best_move(&self, depth: u32) -> Option<(ChessMove, i32)> {
+        best_move(self, depth)
+    }
+}
+
+/// A search algorithm which finds moves for an engine, for example one driven by the
+/// [UCI protocol](crate::uci::run_uci). Implement this trait to plug a custom search into the
+/// engine; [AlphaBetaBackend] uses the [search] of this module.
+pub trait SearchBackend: Send {
+    /// Searches for the best move for the player to move within the given limits, like
+    /// [search]. The search should stop soon after the limits' stop flag is set.
+    ///
+    /// returns: `Some(SearchResult)` of the search, or `None` if the game is over.
+    fn search(&mut self, game: &ChessGame, limits: &SearchLimits) -> Option<SearchResult>;
+
+    /// Called when the next searches belong to a new game, so that any state kept between
+    /// searches of a game can be cleared. Does nothing by default.
+    fn new_game(&mut self) {}
+}
+
+/// The [SearchBackend] using the alpha-beta [search] of this module.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AlphaBetaBackend;
+
+impl SearchBackend for AlphaBetaBackend {
+    fn search(&mut self, game: &ChessGame, limits: &SearchLimits) -> Option<SearchResult> {
+        search(game, limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::eval::{evaluate, MaterialEvaluator};
+    use crate::chess::GameStatus;
+    use crate::moves::CastlingRights;
+
+    fn position(fen: &str, active_player: PlayerColor) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string(fen).unwrap(), active_player, no_rights, None)
+            .unwrap();
+        game
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let game = position("6k1/5ppp/8/8/8/8/5PPP/R5K1", PlayerColor::White);
+        let (chess_move, score) = game.best_move(2).unwrap();
+        assert_eq!(chess_move, ChessMove::from_coords("a1", "a8").unwrap());
+        assert_eq!(score, MATE_SCORE - 1);
+        assert!(is_mate_score(score));
+    }
+
+    #[test]
+    fn prefers_queen_over_pawn() {
+        let game = position("4k3/8/8/3q3p/8/8/4B3/3RK3", PlayerColor::White);
+        let (chess_move, score) = game.best_move(2).unwrap();
+        assert_eq!(chess_move, ChessMove::from_coords("d1", "d5").unwrap());
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn depth_one_is_best_static_evaluation() {
+        // there are no captures for the quiescence search to consider
+        let game = position("4k3/8/8/p1p1p1p1/P1P1P1P1/8/8/4K3", PlayerColor::White);
+        let best_static = game.legal_moves().into_iter()
+            .map(|chess_move| {
+                let mut child = game.clone();
+                child.do_move(chess_move).unwrap();
+                evaluate(child.board())
+            })
+            .max()
+            .unwrap();
+        assert_eq!(game.best_move(1).unwrap().1, best_static);
+        assert_eq!(game.best_move(0).unwrap().1, best_static);
+    }
+
+    #[test]
+    fn game_not_modified() {
+        let game = ChessGame::new(Board::default_board());
+        let fen = game.fen();
+        assert!(game.best_move(2).is_some());
+        assert_eq!(game.fen(), fen);
+        assert!(game.history().is_empty());
+        assert_eq!(*game.game_status(), GameStatus::NotYetStarted);
+
+        let mated = position("R5k1/5ppp/8/8/8/8/5PPP/6K1", PlayerColor::Black);
+        assert_eq!(mated.best_move(2), None);
+    }
+
+    #[test]
+    fn iterative_deepening() {
+        let game = ChessGame::new(Board::default_board());
+        let limits = SearchLimits { max_depth: Some(3), ..SearchLimits::default() };
+        let result = search(&game, &limits).unwrap();
+        let depths: Vec<u32> = result.iterations.iter().map(|info| info.depth).collect();
+        assert_eq!(depths, [1, 2, 3]);
+        assert!(result.iterations.windows(2).all(|pair| pair[0].nodes < pair[1].nodes));
+        assert_eq!(result.iterations[2].score, result.score);
+        assert_eq!(result.principal_variation.len(), 3);
+        assert_eq!(result.principal_variation[0], result.best_move);
+        let mut line = game.clone();
+        for chess_move in &result.principal_variation {
+            line.do_move(*chess_move).unwrap();
+        }
+        assert_eq!(best_move(&game, 3), Some((result.best_move, result.score)));
+    }
+
+    #[test]
+    fn node_limit_keeps_previous_iteration() {
+        let game = position("r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR", PlayerColor::White);
+        let limits = SearchLimits { max_depth: Some(2), ..SearchLimits::default() };
+        let depth_two = search(&game, &limits).unwrap();
+        let nodes = depth_two.iterations[1].nodes;
+
+        // stopped in the middle of the third iteration
+        let limits = SearchLimits { max_nodes: Some(nodes + 100), ..SearchLimits::default() };
+        let result = search(&game, &limits).unwrap();
+        assert_eq!(result.best_move, depth_two.best_move);
+        assert_eq!(result.principal_variation, depth_two.principal_variation);
+        assert_eq!(result.iterations.len(), 2);
+        // the nodes of the completed iterations are counted the same way
+        let counts = |result: &SearchResult| -> Vec<(u32, u64, i32)> {
+            result.iterations.iter()
+                .map(|iteration| (iteration.depth, iteration.nodes, iteration.score))
+                .collect()
+        };
+        assert_eq!(counts(&result), counts(&depth_two));
+
+        // the first iteration is always completed
+        let limits = SearchLimits { max_nodes: Some(1), ..SearchLimits::default() };
+        assert_eq!(search(&game, &limits).unwrap().iterations.len(), 1);
+    }
+
+    #[test]
+    fn stop_flag() {
+        let game = ChessGame::new(Board::default_board());
+        let limits = SearchLimits::default();
+        limits.stop_flag.store(true, Ordering::Relaxed);
+        let result = search(&game, &limits).unwrap();
+        assert_eq!(result.iterations.len(), 1);
+
+        let limits = SearchLimits::default();
+        let stop_flag = limits.stop_flag.clone();
+        let handle = std::thread::spawn(move || search(&game, &limits));
+        std::thread::sleep(Duration::from_millis(50));
+        stop_flag.store(true, Ordering::Relaxed);
+        let result = handle.join().unwrap().unwrap();
+        assert!(!result.iterations.is_empty());
+    }
+
+    #[test]
+    fn static_exchange_evaluation() {
+        let game = position("6k1/8/4p3/3p4/8/8/8/3Q2K1", PlayerColor::White);
+        let queen_takes_pawn = ChessMove::from_coords("d1", "d5").unwrap();
+        assert_eq!(static_exchange(&game, queen_takes_pawn), 100 - 900);
+
+        let game = position("3r2k1/3r4/8/3n4/4P3/8/3R4/3R2K1", PlayerColor::White);
+        let pawn_takes_knight = ChessMove::from_coords("e4", "d5").unwrap();
+        assert_eq!(static_exchange(&game, pawn_takes_knight), 300);
+        let rook_takes_knight = ChessMove::from_coords("d2", "d5").unwrap();
+        assert_eq!(static_exchange(&game, rook_takes_knight), 300);
+        let game = position("3r2k1/3r4/8/3n4/8/8/3R4/3R2K1", PlayerColor::White);
+        assert_eq!(static_exchange(&game, rook_takes_knight), 300 - 500);
+
+        let mut game = position("4k3/8/8/3pP3/8/8/8/4K3", PlayerColor::White);
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(game.board().clone(), PlayerColor::White, no_rights,
+                          Some("d6".parse().unwrap())).unwrap();
+        let en_passant = ChessMove::from_coords("e5", "d6").unwrap();
+        assert_eq!(static_exchange(&game, en_passant), 100);
+    }
+
+    #[test]
+    fn static_exchange_with_piece_values() {
+        // the knight takes a pawn and is recaptured, and both players keep recapturing with pawns
+        // until white has taken three pawns for the knight and a pawn
+        let mut game = position("6k1/8/2p1p3/3p4/2P1P3/2N5/8/6K1", PlayerColor::White);
+        let knight_takes_pawn = ChessMove::from_coords("c3", "d5").unwrap();
+        assert_eq!(static_exchange(&game, knight_takes_pawn), 100 - 300 + 100);
+        game.set_piece_values(PieceValues { pawn: 200, ..PieceValues::default() });
+        assert_eq!(static_exchange(&game, knight_takes_pawn), 200 - 300 + 200);
+        let values = *game.piece_values();
+        assert_eq!(game.starting_position().piece_values(), &values);
+    }
+
+    #[test]
+    fn quiescence_sees_recapture() {
+        let game = position("6k1/8/4p3/3p4/8/8/8/3Q2K1", PlayerColor::White);
+        let queen_takes_pawn = ChessMove::from_coords("d1", "d5").unwrap();
+        let (naive_move, naive_score) = game.legal_moves().into_iter()
+            .map(|chess_move| {
+                let mut child = game.clone();
+                child.do_move(chess_move).unwrap();
+                (chess_move, evaluate(child.board()))
+            })
+            .max_by_key(|(_, score)| *score)
+            .unwrap();
+        // a fixed depth search without quiescence would take the defended pawn
+        assert_eq!(naive_move, queen_takes_pawn);
+
+        let (chess_move, score) = game.best_move(1).unwrap();
+        assert_ne!(chess_move, queen_takes_pawn);
+        assert!(score < naive_score);
+        let (chess_move, _) = game.best_move(2).unwrap();
+        assert_ne!(chess_move, queen_takes_pawn);
+    }
+
+    #[test]
+    fn quiescence_sees_promotion() {
+        // the rook has to reach the first rank or the a-file to stop the pawn from promoting
+        let game = position("4k3/7R/8/8/8/6K1/p7/8", PlayerColor::White);
+        let promotion = ChessMove::promoting("a2".parse().unwrap(), "a1".parse().unwrap(),
+                                             PromotionType::Queen);
+        let (chess_move, score) = game.best_move(1).unwrap();
+        let mut child = game.clone();
+        child.do_move(chess_move).unwrap();
+        assert!(!child.legal_moves().contains(&promotion)
+                    || static_exchange(&child, promotion) < 0, "{}", chess_move);
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn multiple_lines() {
+        let game = position("6k1/5ppp/8/8/2nn4/8/5PPP/R5K1", PlayerColor::White);
+        let limits = SearchLimits { max_depth: Some(2), ..SearchLimits::default() };
+        let lines = best_moves(&game, &limits, 3);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].root_move, ChessMove::from_coords("a1", "a8").unwrap());
+        assert_eq!(lines[0].score, MATE_SCORE - 1);
+        assert_eq!(lines[0].principal_variation, [lines[0].root_move]);
+        // without the checkmate, white is down material
+        assert!(lines[1].score < 0);
+        assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        for line in &lines {
+            assert_eq!(line.principal_variation[0], line.root_move);
+            assert_eq!(line.depth, if is_mate_score(line.score) { 1 } else { 2 });
+        }
+        assert_ne!(lines[1].root_move, lines[2].root_move);
+
+        let game = position("7k/8/8/8/8/8/P7/K7", PlayerColor::White);
+        let lines = best_moves(&game, &limits, 10);
+        let mut root_moves: Vec<ChessMove> = lines.iter().map(|line| line.root_move).collect();
+        let mut legal_moves = game.legal_moves();
+        moves::sort_moves_canonical(&mut root_moves);
+        moves::sort_moves_canonical(&mut legal_moves);
+        assert_eq!(root_moves, legal_moves);
+        assert_eq!(best_moves(&game, &limits, 0), []);
+    }
+
+    #[test]
+    fn custom_evaluator() {
+        // only the piece-square tables tell the developing move apart from other quiet moves
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1";
+        let game = ChessGame::from_fen(fen).unwrap();
+        let limits = SearchLimits { max_depth: Some(2), ..SearchLimits::default() };
+        let standard = search_with(&game, &limits, &StandardEvaluator).unwrap();
+        assert_eq!(search(&game, &limits).map(|result| result.principal_variation),
+                   Some(standard.principal_variation));
+        assert_eq!(standard.best_move, ChessMove::from_coords("b1", "c3").unwrap());
+        let material = search_with(&game, &limits, &MaterialEvaluator).unwrap();
+        assert_eq!(material.score, 0);
+        assert_ne!(material.best_move, standard.best_move);
+
+        // both evaluators win a knight for a pawn, in a position where white is a pawn up
+        let game = position("r3k2r/1p3pp1/p1n5/3Pp2p/8/8/PPP2PPP/R3K1NR", PlayerColor::White);
+        let evaluator: &dyn Evaluator = &MaterialEvaluator;
+        assert_eq!(evaluator.evaluate(&game), 100);
+        let capture = ChessMove::from_coords("d5", "c6").unwrap();
+        assert_eq!(best_move_with(&game, 2, evaluator), Some((capture, 300)));
+        assert_eq!(best_move(&game, 2).map(|(chess_move, _)| chess_move), Some(capture));
+    }
+
+    #[test]
+    fn pawn_sacrifice_with_cheap_pawns() {
+        let fen = "rnbqkb1r/ppp1p1pn/5p2/3p3p/3P1B2/P4P1P/1PP1P1P1/RN1QKBNR b KQkq - 1 7";
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        let sacrifice = ChessMove::from_coords("e7", "e5").unwrap();
+        // the pawn is lost to either capture on e5, which only the piece-square tables make up for
+        assert!(static_exchange(&game, sacrifice) < 0);
+        let (standard, _) = best_move(&game, 2).unwrap();
+        let (material, _) = best_move_with(&game, 2, &MaterialEvaluator).unwrap();
+        assert_ne!(standard, sacrifice);
+        assert_ne!(material, sacrifice);
+
+        game.set_piece_values(PieceValues { pawn: 30, ..PieceValues::default() });
+        assert_eq!(best_move(&game, 2).map(|(chess_move, _)| chess_move), Some(sacrifice));
+        let (material, _) = best_move_with(&game, 2, &MaterialEvaluator).unwrap();
+        assert_ne!(material, sacrifice);
+    }
+}
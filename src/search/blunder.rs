@@ -0,0 +1,136 @@
+//! Blunder checks, for example for warning beginners before they make a move which loses
+//! material. See [ChessGame::blunder_check].
+
+use crate::chess::{ChessError, ChessGame};
+use crate::moves;
+use crate::moves::ChessMove;
+use crate::search::{best_move, game_over_score, static_exchange};
+
+/// Options for [ChessGame::blunder_check_with].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BlunderOptions {
+    /// The depth in plies of the searches comparing the move with the best move. Depths below 2
+    /// are treated as 2.
+    pub depth: u32,
+    /// The loss in centipawns at which a move is considered a blunder.
+    pub threshold: i32,
+}
+
+impl Default for BlunderOptions {
+    fn default() -> Self {
+        BlunderOptions { depth: 2, threshold: 200 }
+    }
+}
+
+/// The result of a [blunder check](ChessGame::blunder_check).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BlunderReport {
+    /// The [static exchange evaluation](static_exchange) of the move in centipawns, if the move
+    /// is a capture or moves the piece to a square attacked by the other player, otherwise
+    /// `None`.
+    pub exchange: Option<i32>,
+    /// The best move found by the search, which may be the checked move itself.
+    pub best_move: ChessMove,
+    /// How many centipawns worse the move is than the best move, according to the search. Never
+    /// negative.
+    pub centipawn_loss: i32,
+    /// The best reply of the other player to the move, if the game is not over after the move.
+    pub refutation: Option<ChessMove>,
+    /// Whether the loss reaches the [threshold](BlunderOptions::threshold).
+    pub is_blunder: bool,
+}
+
+impl ChessGame {
+    /// Checks whether a move of the active player is a blunder, using the default
+    /// [BlunderOptions]. See [blunder_check_with](ChessGame::blunder_check_with).
+    pub fn blunder_check(&self, chess_move: ChessMove) -> Result<BlunderReport, ChessError> {
+        self.blunder_check_with(chess_move, &BlunderOptions::default())
+    }
+
+    /// Checks whether a move of the active player is a blunder, by comparing the score of the
+    /// move with the score of the best move, both found with a shallow
+    /// [search](crate::search::best_move). The game itself is not modified.
+    ///
+    /// returns: `Ok(BlunderReport)` if the move is legal, `Err(ChessError::IllegalMove)` if it is
+    ///          not, and `Err(ChessError::GameAlreadyEnded)` if the game is over.
+    pub fn blunder_check_with(&self, chess_move: ChessMove,
+                              options: &BlunderOptions) -> Result<BlunderReport, ChessError>
+    {
+        if self.game_status().is_over() {
+            return Err(ChessError::GameAlreadyEnded);
+        }
+        let mut child = self.analysis_copy();
+        child.do_move(chess_move)?;
+
+        let to = chess_move.piece_movement.to;
+        let is_capture = self.board().get_piece(to).is_some()
+            || child.last_move().is_some_and(|details| details.captured_piece.is_some());
+        let is_attacked = !moves::attacking_pieces(child.board(), self.active_player(), to)
+            .is_all_zeros();
+        let exchange = (is_capture || is_attacked).then(|| static_exchange(self, chess_move));
+
+        let depth = options.depth.max(2);
+        let (best, best_score) = best_move(self, depth).ok_or(ChessError::GameAlreadyEnded)?;
+        let (refutation, score) = match best_move(&child, depth - 1) {
+            Some((reply, reply_score)) => (Some(reply), -reply_score),
+            None => (None, -game_over_score(&child, 1)),
+        };
+        let centipawn_loss = if chess_move == best {
+            0
+        } else {
+            (best_score - score).max(0)
+        };
+        Ok(BlunderReport {
+            exchange,
+            best_move: best,
+            centipawn_loss,
+            refutation,
+            is_blunder: centipawn_loss >= options.threshold,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::board::piece::PlayerColor;
+    use crate::moves::CastlingRights;
+
+    fn position(fen: &str) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(Board::from_fen_string(fen).unwrap(), PlayerColor::White, no_rights,
+                          None).unwrap();
+        game
+    }
+
+    #[test]
+    fn hanging_queen_is_flagged() {
+        let game = position("r5k1/5ppp/4p3/8/8/8/5PPP/3Q2K1");
+        let fen = game.fen();
+        let queen_move = ChessMove::from_coords("d1", "d5").unwrap();
+        let report = game.blunder_check(queen_move).unwrap();
+        assert!(report.is_blunder);
+        assert_eq!(report.exchange, Some(-900));
+        assert_eq!(report.refutation, Some(ChessMove::from_coords("e6", "d5").unwrap()));
+        assert!(report.centipawn_loss > 500);
+        assert_eq!(game.fen(), fen);
+
+        let report = game.blunder_check(report.best_move).unwrap();
+        assert!(!report.is_blunder);
+        assert_eq!(report.centipawn_loss, 0);
+    }
+
+    #[test]
+    fn blunder_check_errors() {
+        let game = position("r5k1/5ppp/4p3/8/8/8/5PPP/3Q2K1");
+        let illegal_move = ChessMove::from_coords("d1", "e3").unwrap();
+        assert_eq!(game.blunder_check(illegal_move).unwrap_err(),
+                   ChessError::IllegalMove(illegal_move));
+        let quiet_move = ChessMove::from_coords("h2", "h3").unwrap();
+        let report = game.blunder_check(quiet_move).unwrap();
+        assert_eq!(report.exchange, None);
+        assert!(!report.is_blunder);
+    }
+}
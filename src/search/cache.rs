@@ -0,0 +1,350 @@
+//! A cache of analysis results keyed by position, for analysis interfaces which revisit the same
+//! positions while the user steps through a game. See [AnalysisCache].
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+use crate::board::board_pos::BoardPosition;
+use crate::board::piece::PlayerColor;
+use crate::chess::ChessGame;
+use crate::moves;
+use crate::moves::ChessMove;
+use crate::moves::util::BoardBitmap;
+use crate::search::SearchResult;
+
+/// The analysis results cached for a position. Each result is `None` until it is computed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CachedAnalysis {
+    /// The [available moves](ChessGame::available_moves) of the piece on each square, indexed by
+    /// file and rank, which a game with the cache attached looks up instead of recalculating
+    /// them.
+    pub available_moves: Option<[[BoardBitmap; 8]; 8]>,
+    /// The [legal moves](ChessGame::legal_moves) of the position.
+    pub legal_moves: Option<Vec<ChessMove>>,
+    /// The squares attacked by white and by black, see [ChessGame::attacked_squares].
+    pub attacked_squares: Option<[BoardBitmap; 2]>,
+    /// The [static evaluation](crate::eval::evaluate) of the position.
+    pub evaluation: Option<i32>,
+    /// The deepest [search](crate::search::search) result of the position.
+    pub search: Option<SearchResult>,
+}
+
+/// A cache mapping position hashes, such as [Polyglot keys](ChessGame::polyglot_key), to the
+/// [CachedAnalysis] of the position. A position never changes, so entries are never invalidated,
+/// but the cache holds at most a given number of positions, evicting the least recently used
+/// position when a new one is added.
+///
+/// A cache may be attached to a game with [ChessGame::set_analysis_cache]. The game then looks
+/// up the available moves of each position it reaches in the cache before recalculating them,
+/// and the same for its [attacked squares](ChessGame::attacked_squares), so that stepping
+/// through a game with [position_at](ChessGame::position_at) or exploring variations with
+/// [peek_move](ChessGame::peek_move) does not recompute the positions seen before.
+///
+/// # Example
+///
+/// ```rust
+/// use leben_chess::board::Board;
+/// use leben_chess::chess::ChessGame;
+/// use leben_chess::search::cache::AnalysisCache;
+///
+/// let game = ChessGame::new(Board::default_board());
+/// let mut cache = AnalysisCache::new(1000);
+/// let key = game.polyglot_key();
+/// let moves = cache.get_or_compute(key, |entry| &mut entry.legal_moves, || game.legal_moves());
+/// assert_eq!(moves.len(), 20);
+/// // the second query is answered from the cache
+/// cache.get_or_compute(key, |entry| &mut entry.legal_moves, || unreachable!());
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AnalysisCache {
+    capacity: usize,
+    /// The entries, with the time of their last use.
+    entries: HashMap<u64, (CachedAnalysis, u64)>,
+    /// The hashes of the entries by the time of their last use.
+    recency: BTreeMap<u64, u64>,
+    time: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// returns: The depth of the last completed iteration of a search result.
+fn search_depth(result: &SearchResult) -> u32 {
+    result.iterations.last().map_or(0, |iteration| iteration.depth)
+}
+
+impl AnalysisCache {
+    /// returns: An empty cache holding at most `capacity` positions. A cache with a capacity of
+    ///          zero stores nothing.
+    pub fn new(capacity: usize) -> AnalysisCache {
+        AnalysisCache {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            time: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// returns: The largest number of positions the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// returns: The number of positions in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// returns: Whether the cache holds no positions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// returns: The number of queries answered from the cache by
+    ///          [get_or_compute](AnalysisCache::get_or_compute), including the lookups of the
+    ///          games the cache is attached to.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// returns: The number of queries which had to be computed.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Removes every position from the cache, keeping the hit and miss counts.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// returns: The cached analysis of the position with the given hash, if any, without marking
+    ///          it as recently used.
+    pub fn peek(&self, hash: u64) -> Option<&CachedAnalysis> {
+        self.entries.get(&hash).map(|(entry, _)| entry)
+    }
+
+    /// Marks the position as the most recently used, adding an empty entry for it if it is not
+    /// in the cache, which evicts the least recently used position if the cache is full.
+    ///
+    /// returns: The entry of the position, or `None` if the capacity is zero.
+    fn entry(&mut self, hash: u64) -> Option<&mut CachedAnalysis> {
+        if self.capacity == 0 {
+            return None;
+        }
+        self.time += 1;
+        if let Some((_, last_use)) = self.entries.get_mut(&hash) {
+            self.recency.remove(last_use);
+            *last_use = self.time;
+        } else {
+            if self.entries.len() >= self.capacity
+                && let Some((_, evicted)) = self.recency.pop_first()
+            {
+                self.entries.remove(&evicted);
+            }
+            self.entries.insert(hash, (CachedAnalysis::default(), self.time));
+        }
+        self.recency.insert(self.time, hash);
+        self.entries.get_mut(&hash).map(|(entry, _)| entry)
+    }
+
+    /// Looks up one of the results of a position, computing and storing it if it is not cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash`: The hash of the position.
+    /// * `result`: Selects the result from the [CachedAnalysis] of the position, such as
+    ///   `|entry| &mut entry.legal_moves`.
+    /// * `compute`: Computes the result if it is not cached.
+    ///
+    /// returns: The cached or computed result.
+    pub fn get_or_compute<T: Clone>(&mut self, hash: u64,
+                                    result: fn(&mut CachedAnalysis) -> &mut Option<T>,
+                                    compute: impl FnOnce() -> T) -> T
+    {
+        if let Some(cached) = self.entry(hash).and_then(|entry| result(entry).clone()) {
+            self.hits += 1;
+            return cached;
+        }
+        self.misses += 1;
+        let computed = compute();
+        if let Some(entry) = self.entry(hash) {
+            *result(entry) = Some(computed.clone());
+        }
+        computed
+    }
+
+    /// Stores a search result of a position, unless a result of at least the same depth is
+    /// already cached.
+    ///
+    /// returns: Whether the result was stored.
+    pub fn store_search(&mut self, hash: u64, result: SearchResult) -> bool {
+        let Some(entry) = self.entry(hash) else {
+            return false;
+        };
+        if entry.search.as_ref()
+            .is_some_and(|cached| search_depth(cached) >= search_depth(&result))
+        {
+            return false;
+        }
+        entry.search = Some(result);
+        true
+    }
+
+    /// returns: The cached search result of the position with the given hash, if it was
+    ///          searched to at least `min_depth` plies.
+    pub fn search_result(&mut self, hash: u64, min_depth: u32) -> Option<SearchResult> {
+        self.entry(hash)?.search.clone()
+            .filter(|result| search_depth(result) >= min_depth)
+    }
+}
+
+impl ChessGame {
+    /// Attaches an [AnalysisCache] to the game, or detaches it with `None`. Clones of the game
+    /// share the attached cache, so that exploring the variations of a game through copies of it
+    /// reuses the analysis of positions seen before. Positions are looked up by their
+    /// [Polyglot key](ChessGame::polyglot_key), so a cache should only be shared between games of
+    /// the same [variant](crate::chess::variant::Variant).
+    pub fn set_analysis_cache(&mut self, cache: Option<Arc<Mutex<AnalysisCache>>>) {
+        self.analysis_cache = cache;
+    }
+
+    /// returns: The attached [AnalysisCache], if any.
+    pub fn analysis_cache(&self) -> Option<&Arc<Mutex<AnalysisCache>>> {
+        self.analysis_cache.as_ref()
+    }
+
+    /// returns: A [BoardBitmap] of the squares attacked by the player's pieces, whether or not
+    ///          they are occupied. The squares are taken from the attached [AnalysisCache] if the
+    ///          position is cached.
+    pub fn attacked_squares(&self, player: PlayerColor) -> BoardBitmap {
+        let Some(cache) = &self.analysis_cache else {
+            return self.calculate_attacked_squares(player);
+        };
+        let [white, black] = cache.lock().unwrap_or_else(PoisonError::into_inner)
+            .get_or_compute(self.polyglot_key(), |entry| &mut entry.attacked_squares, || {
+                [self.calculate_attacked_squares(PlayerColor::White),
+                 self.calculate_attacked_squares(PlayerColor::Black)]
+            });
+        match player {
+            PlayerColor::White => white,
+            PlayerColor::Black => black,
+        }
+    }
+
+    fn calculate_attacked_squares(&self, player: PlayerColor) -> BoardBitmap {
+        let mut attacked = BoardBitmap::all_zeros();
+        for pos in BoardPosition::all() {
+            let attackers = moves::attacking_pieces_in_variant(self.board(), player.other_player(),
+                                                               pos, self.variant());
+            if !attackers.is_all_zeros() {
+                attacked.set(pos, true);
+            }
+        }
+        attacked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use crate::board::Board;
+    use crate::search::{search, SearchLimits};
+    use super::*;
+
+    #[test]
+    fn repeated_queries_hit() {
+        let mut cache = AnalysisCache::new(10);
+        let computed = Cell::new(0);
+        let compute = || {
+            computed.set(computed.get() + 1);
+            42
+        };
+        assert_eq!(cache.get_or_compute(1, |entry| &mut entry.evaluation, compute), 42);
+        assert_eq!(cache.get_or_compute(1, |entry| &mut entry.evaluation, compute), 42);
+        assert_eq!(cache.get_or_compute(2, |entry| &mut entry.evaluation, compute), 42);
+        assert_eq!(computed.get(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    }
+
+    #[test]
+    fn exploring_a_game_hits() {
+        let mut game = ChessGame::new(Board::default_board());
+        let cache = Arc::new(Mutex::new(AnalysisCache::new(10)));
+        game.set_analysis_cache(Some(cache.clone()));
+        let counts = || {
+            let cache = cache.lock().unwrap();
+            (cache.hits(), cache.misses())
+        };
+        let e4 = ChessMove::from_coords("e2", "e4").unwrap();
+        let e5 = ChessMove::from_coords("e7", "e5").unwrap();
+        let peeked = game.peek_move(e4).unwrap();
+        assert_eq!(counts(), (0, 1));
+        game.do_move(e4).unwrap();
+        game.do_move(e5).unwrap();
+        assert_eq!(counts(), (1, 2));
+        assert_eq!(peeked.legal_moves(), game.position_at(1).unwrap().legal_moves());
+        assert_eq!(counts(), (2, 2));
+
+        // stepping back through the game and forward again only calculates the starting
+        // position, which was set up before the cache was attached
+        for ply in (0..=2).rev() {
+            let position = game.position_at(ply).unwrap();
+            assert_eq!(position.analysis_cache().map(Arc::as_ptr), Some(Arc::as_ptr(&cache)));
+            assert_eq!(position.history().len(), ply);
+        }
+        let start = game.position_at(0).unwrap();
+        assert_eq!(start.legal_moves().len(), 20);
+        assert_eq!(start.peek_move(e4).unwrap().peek_move(e5).unwrap().fen(), game.fen());
+        assert_eq!(counts(), (6, 3));
+
+        let [white, black] = [PlayerColor::White, PlayerColor::Black]
+            .map(|player| game.attacked_squares(player));
+        assert!(white.get("f3".parse().unwrap()) && !white.get("e4".parse().unwrap()));
+        assert!(black.get("f6".parse().unwrap()) && !black.get("f3".parse().unwrap()));
+        assert_eq!(counts(), (7, 4));
+        let mut uncached = game.clone();
+        uncached.set_analysis_cache(None);
+        assert_eq!(uncached.attacked_squares(PlayerColor::White), white);
+        assert_eq!(uncached.legal_moves(), game.legal_moves());
+    }
+
+    #[test]
+    fn lru_bound() {
+        let mut cache = AnalysisCache::new(2);
+        for hash in [1, 2, 1, 3] {
+            cache.get_or_compute(hash, |entry| &mut entry.evaluation, || hash as i32);
+        }
+        // 2 was the least recently used position when 3 was added
+        assert_eq!(cache.len(), 2);
+        assert!(cache.peek(1).is_some());
+        assert!(cache.peek(2).is_none());
+        assert_eq!(cache.peek(3).and_then(|entry| entry.evaluation), Some(3));
+
+        let mut empty = AnalysisCache::new(0);
+        empty.get_or_compute(1, |entry| &mut entry.evaluation, || 1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn deeper_searches_replace_shallower() {
+        let game = ChessGame::new(Board::default_board());
+        let key = game.polyglot_key();
+        let limits = |depth| SearchLimits { max_depth: Some(depth), ..SearchLimits::default() };
+        let mut cache = AnalysisCache::new(10);
+        let deep = search(&game, &limits(2)).unwrap();
+        assert!(cache.store_search(key, deep.clone()));
+        // a shallower query is answered with the deeper result
+        assert_eq!(cache.search_result(key, 1), Some(deep.clone()));
+        assert_eq!(cache.search_result(key, 3), None);
+
+        let shallow = search(&game, &limits(1)).unwrap();
+        assert!(!cache.store_search(key, shallow));
+        assert_eq!(cache.search_result(key, 0), Some(deep));
+        let deeper = search(&game, &limits(3)).unwrap();
+        assert!(cache.store_search(key, deeper.clone()));
+        assert_eq!(cache.search_result(key, 0), Some(deeper));
+    }
+}
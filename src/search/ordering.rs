@@ -0,0 +1,212 @@
+//! Move ordering for game tree searches. Alpha-beta pruning cuts off the most branches when the
+//! best moves are searched first, so moves are ordered by how promising they look:
+//! 1. the transposition table move, typically the best move found by an earlier search,
+//! 2. captures, by most valuable victim and then by least valuable attacker (MVV-LVA),
+//! 3. promotions which are not captures, by the value of the promoted piece,
+//! 4. killer moves, quiet moves which caused a cutoff in another node at the same ply,
+//! 5. all other moves, in the order they were given.
+//!
+//! see: [Move Ordering - Chessprogramming wiki](https://www.chessprogramming.org/Move_Ordering)
+
+use core::cmp::Reverse;
+use crate::board::piece::PieceType;
+use crate::chess::ChessGame;
+use crate::moves::{ChessMove, PieceMovement};
+
+/// The number of killer moves stored for each ply in [KillerMoves].
+pub const KILLER_SLOTS: usize = 2;
+
+/// A table of killer moves: quiet moves which caused a beta cutoff, stored by the ply they were
+/// played at, so that they can be searched early in other nodes at the same ply.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KillerMoves {
+    slots: Vec<[Option<ChessMove>; KILLER_SLOTS]>,
+}
+
+impl KillerMoves {
+    /// Instantiate an empty killer move table
+    pub fn new() -> KillerMoves {
+        KillerMoves::default()
+    }
+
+    /// Stores a killer move at a ply, replacing the oldest killer move stored at the ply if all
+    /// slots are in use. Storing a move which is already stored has no effect.
+    pub fn store(&mut self, ply: u32, chess_move: ChessMove) {
+        let ply = ply as usize;
+        if self.slots.len() <= ply {
+            self.slots.resize(ply + 1, [None; KILLER_SLOTS]);
+        }
+        let slots = &mut self.slots[ply];
+        if slots.contains(&Some(chess_move)) {
+            return;
+        }
+        slots.rotate_right(1);
+        slots[0] = Some(chess_move);
+    }
+
+    /// returns: The killer moves stored at a ply, most recent first.
+    pub fn get(&self, ply: u32) -> Vec<ChessMove> {
+        self.slots.get(ply as usize)
+            .map(|slots| slots.iter().flatten().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes all stored killer moves
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
+
+/// returns: The value of a piece type for ordering captures, where a king is worth more than any
+///          other piece.
+fn ordering_value(piece_type: PieceType) -> i32 {
+    piece_type.piece_value().map_or(10, i32::from)
+}
+
+/// returns: The type of the piece captured by a move, including captures en passant, or `None`
+///          if the move is not a capture.
+fn captured_piece_type(game: &ChessGame, chess_move: ChessMove) -> Option<PieceType> {
+    let PieceMovement { from, to } = chess_move.piece_movement;
+    match game.board().get_piece(to) {
+        Some(victim) => Some(victim.piece_type),
+        None if Some(to) == game.en_passant_target()
+            && game.board().get_piece(from).is_some_and(|piece| piece.piece_type == PieceType::Pawn)
+            => Some(PieceType::Pawn),
+        None => None,
+    }
+}
+
+/// returns: Whether a move is neither a capture nor a promotion, which are the moves that may be
+///          stored as [killer moves](KillerMoves).
+pub fn is_quiet(game: &ChessGame, chess_move: ChessMove) -> bool {
+    chess_move.promotion.is_none() && captured_piece_type(game, chess_move).is_none()
+}
+
+fn move_score(game: &ChessGame, chess_move: ChessMove, tt_move: Option<ChessMove>,
+              killers: &[ChessMove]) -> i32
+{
+    if Some(chess_move) == tt_move {
+        return i32::MAX;
+    }
+    let Some(piece) = game.board().get_piece(chess_move.piece_movement.from) else {
+        return 0;
+    };
+    let promotion_value = chess_move.promotion
+        .map_or(0, |promotion| ordering_value(promotion.to_piece(piece.player).piece_type));
+    if let Some(victim) = captured_piece_type(game, chess_move) {
+        3_000_000 + 1000 * ordering_value(victim) - ordering_value(piece.piece_type)
+            + promotion_value
+    } else if chess_move.promotion.is_some() {
+        2_000_000 + promotion_value
+    } else if let Some(i) = killers.iter().position(|&killer| killer == chess_move) {
+        1_000_000 - i as i32
+    } else {
+        0
+    }
+}
+
+/// Sorts moves for searching, as described in [the module documentation](self), without using
+/// killer moves. The moves are assumed to be legal in the game.
+///
+/// # Arguments
+///
+/// * `game`: The game the moves are played in.
+/// * `moves`: The moves to sort.
+/// * `tt_move`: A move to place first, for example from a transposition table.
+pub fn order_moves(game: &ChessGame, moves: &mut [ChessMove], tt_move: Option<ChessMove>) {
+    order_moves_with_killers(game, moves, tt_move, &[]);
+}
+
+/// Sorts moves for searching, as described in [the module documentation](self). The moves are
+/// assumed to be legal in the game.
+///
+/// # Arguments
+///
+/// * `game`: The game the moves are played in.
+/// * `moves`: The moves to sort.
+/// * `tt_move`: A move to place first, for example from a transposition table.
+/// * `killers`: The killer moves for the current ply, see [KillerMoves::get]. Earlier killer
+///   moves are placed first.
+pub fn order_moves_with_killers(game: &ChessGame, moves: &mut [ChessMove],
+                                tt_move: Option<ChessMove>, killers: &[ChessMove])
+{
+    moves.sort_by_cached_key(|&chess_move| {
+        Reverse(move_score(game, chess_move, tt_move, killers))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::board::piece::PlayerColor;
+    use crate::moves::{CastlingRights, PromotionType};
+
+    fn moves(coords: &[(&str, &str)]) -> Vec<ChessMove> {
+        coords.iter().map(|(from, to)| ChessMove::from_coords(from, to).unwrap()).collect()
+    }
+
+    #[test]
+    fn mvv_lva_ordering() {
+        let mut game = ChessGame::new(Board::default_board());
+        let board = Board::from_fen_string("4k3/8/4q3/1r1P4/p7/2N5/8/3Q3K").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+
+        let mut legal_moves = game.legal_moves();
+        order_moves(&game, &mut legal_moves, None);
+        assert_eq!(legal_moves[..4], moves(&[
+            ("d5", "e6"), // pawn takes queen
+            ("c3", "b5"), // knight takes rook
+            ("c3", "a4"), // knight takes pawn
+            ("d1", "a4"), // queen takes pawn
+        ])[..]);
+        assert_eq!(game.legal_captures().len(), 4);
+    }
+
+    #[test]
+    fn tt_move_first() {
+        let game = ChessGame::new(Board::default_board());
+        let mut legal_moves = game.legal_moves();
+        let tt_move = ChessMove::from_coords("g1", "f3").unwrap();
+        order_moves(&game, &mut legal_moves, Some(tt_move));
+        assert_eq!(legal_moves[0], tt_move);
+        assert_eq!(legal_moves.len(), 20);
+        // the remaining quiet moves keep their order
+        let mut rest = game.legal_moves();
+        rest.retain(|&chess_move| chess_move != tt_move);
+        assert_eq!(legal_moves[1..], rest[..]);
+    }
+
+    #[test]
+    fn promotions_and_killers() {
+        let mut game = ChessGame::new(Board::default_board());
+        let board = Board::from_fen_string("1r2k3/P7/8/8/8/8/8/4K2R").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+
+        let mut killers = KillerMoves::new();
+        let killer = ChessMove::from_coords("h1", "h7").unwrap();
+        killers.store(3, killer);
+        killers.store(3, killer);
+        assert_eq!(killers.get(3), [killer]);
+        assert!(killers.get(2).is_empty());
+
+        let mut legal_moves = game.legal_moves();
+        order_moves_with_killers(&game, &mut legal_moves, None, &killers.get(3));
+        let a7 = "a7".parse().unwrap();
+        let b8 = "b8".parse().unwrap();
+        let a8 = "a8".parse().unwrap();
+        assert_eq!(legal_moves[0], ChessMove::promoting(a7, b8, PromotionType::Queen));
+        assert!(legal_moves[..4].iter().all(|chess_move| chess_move.piece_movement.to == b8));
+        assert_eq!(legal_moves[4], ChessMove::promoting(a7, a8, PromotionType::Queen));
+        assert!(legal_moves[4..8].iter().all(|chess_move| chess_move.piece_movement.to == a8));
+        assert_eq!(legal_moves[8], killer);
+
+        killers.store(3, ChessMove::from_coords("h1", "h2").unwrap());
+        killers.store(3, ChessMove::from_coords("h1", "h3").unwrap());
+        assert_eq!(killers.get(3), moves(&[("h1", "h3"), ("h1", "h2")]));
+        killers.clear();
+        assert!(killers.get(3).is_empty());
+    }
+}
@@ -0,0 +1,605 @@
+//! Probing of Syzygy endgame tablebases.
+//!
+//! Syzygy tablebases store the perfect result of every endgame position with few pieces. For each
+//! material class, such as KRvK, a WDL file (`.rtbw`) stores whether the side to move wins, draws
+//! or loses, and a DTZ file (`.rtbz`) stores the distance to zeroing, that is, the number of plies
+//! to the next capture or pawn move on a fastest path to the result. The values take the
+//! fifty-move rule into account: a [cursed win](Wdl::CursedWin) is a win which can not be forced
+//! before the fifty-move rule allows the opponent to claim a draw.
+//!
+//! A [Tablebase] finds the table files in its directories when it is created and reads each file
+//! when a position of its material is first probed. Probing a position gives `None` when the
+//! files of the material or of the positions reachable by captures are missing or damaged, when
+//! the position has castling rights or more pieces than the
+//! [probe limit](Tablebase::max_pieces), and in variants other than standard chess. En passant
+//! captures, which the tables do not store, are searched before the tables are probed.
+//!
+//! see: [Syzygy tablebases](https://github.com/syzygy1/tb)
+
+mod table;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+use crate::board::piece::{PieceType, PlayerColor};
+use crate::chess::ChessGame;
+use crate::chess::variant::Variant;
+use crate::moves::{CastlingRights, ChessMove, PieceMovement};
+use crate::tablebase::table::{Table, TableKind, TableMaterial};
+
+/// The result of a position with perfect play, for the player to move.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Wdl {
+    /// The player to move loses.
+    Loss,
+    /// The player to move loses with perfect play, but the opponent can not force the win before
+    /// the fifty-move rule allows a draw claim.
+    BlessedLoss,
+    /// The game is drawn.
+    Draw,
+    /// The player to move wins with perfect play, but can not force the win before the fifty-move
+    /// rule allows the opponent a draw claim.
+    CursedWin,
+    /// The player to move wins.
+    Win,
+}
+
+impl Wdl {
+    /// returns: The result of a value from -2 for a loss to 2 for a win, as stored in the tables.
+    fn from_value(value: i32) -> Option<Wdl> {
+        match value {
+            -2 => Some(Wdl::Loss),
+            -1 => Some(Wdl::BlessedLoss),
+            0 => Some(Wdl::Draw),
+            1 => Some(Wdl::CursedWin),
+            2 => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+}
+
+/// An error caused by attempting to read invalid tablebase files.
+#[derive(Error, Debug)]
+pub enum TablebaseError {
+    /// A tablebase directory or file could not be read.
+    #[error("could not read tablebase: {0}")]
+    Io(#[from] std::io::Error),
+    /// A file does not start with the magic number of its kind of table.
+    #[error("table file has the wrong magic number")]
+    BadMagic,
+    /// A file is truncated or does not match the material of its name.
+    #[error("table file is corrupt")]
+    Corrupt,
+}
+
+/// A table file found in a tablebase directory, read on first use.
+#[derive(Default)]
+struct TableFile {
+    path: Option<PathBuf>,
+    table: OnceLock<Option<Table>>,
+}
+
+/// The WDL and DTZ files of a material class.
+struct MaterialTables {
+    material: TableMaterial,
+    wdl: TableFile,
+    dtz: TableFile,
+}
+
+impl MaterialTables {
+    /// returns: The table of the given kind, or `None` if its file is missing or invalid.
+    fn table(&self, kind: TableKind) -> Option<&Table> {
+        let file = match kind {
+            TableKind::Wdl => &self.wdl,
+            TableKind::Dtz => &self.dtz,
+        };
+        file.table.get_or_init(|| {
+            let bytes = std::fs::read(file.path.as_ref()?).ok()?;
+            Table::new(bytes, kind, self.material).ok()
+        }).as_ref()
+    }
+}
+
+/// A set of Syzygy table files. See [the module documentation](self).
+pub struct Tablebase {
+    tables: HashMap<String, MaterialTables>,
+    max_pieces: usize,
+    probe_limit: Option<usize>,
+}
+
+impl Tablebase {
+    /// Creates a tablebase of the table files in the given directories. The files are found by
+    /// their names, such as `KRvK.rtbw` and `KRvK.rtbz`, and read when they are first needed, so
+    /// a damaged file only makes the probes of its material fail.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths`: The directories containing the table files. Other files in them are ignored.
+    ///
+    /// returns: `Ok(Tablebase)` with the found tables, and [Io](TablebaseError::Io) if a
+    ///          directory could not be read.
+    pub fn new(paths: impl IntoIterator<Item=impl AsRef<Path>>)
+        -> Result<Tablebase, TablebaseError>
+    {
+        let mut tables: HashMap<String, MaterialTables> = HashMap::new();
+        for path in paths {
+            for entry in std::fs::read_dir(path)? {
+                let path = entry?.path();
+                let kind = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("rtbw") => TableKind::Wdl,
+                    Some("rtbz") => TableKind::Dtz,
+                    _ => continue,
+                };
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                let Some(material) = parse_material(name) else {
+                    continue;
+                };
+                let tables = tables.entry(String::from(name)).or_insert_with(|| MaterialTables {
+                    material,
+                    wdl: TableFile::default(),
+                    dtz: TableFile::default(),
+                });
+                match kind {
+                    TableKind::Wdl => tables.wdl.path = Some(path),
+                    TableKind::Dtz => tables.dtz.path = Some(path),
+                }
+            }
+        }
+        let max_pieces = tables.values()
+            .filter(|tables| tables.wdl.path.is_some())
+            .map(|tables| tables.material.piece_count)
+            .max()
+            .unwrap_or(0);
+        Ok(Tablebase { tables, max_pieces, probe_limit: None })
+    }
+
+    /// returns: The largest number of pieces, including kings, of the positions which are probed:
+    ///          the number of pieces of the largest WDL table found, or the
+    ///          [probe limit](Tablebase::set_probe_limit) if it is lower.
+    pub fn max_pieces(&self) -> usize {
+        self.probe_limit.map_or(self.max_pieces, |limit| limit.min(self.max_pieces))
+    }
+
+    /// Limits the probes to positions with at most the given number of pieces, including kings,
+    /// for example to avoid reading large tables during a search. `None` removes the limit.
+    pub fn set_probe_limit(&mut self, pieces: Option<usize>) {
+        self.probe_limit = pieces;
+    }
+
+    /// returns: The stored value of a position, `Some(None)` if a DTZ table only stores the other
+    ///          player to move, and `None` if the table is missing or invalid.
+    fn probe_table(&self, game: &ChessGame, kind: TableKind, wdl: i32) -> Option<Option<i32>> {
+        let mut pieces: Vec<(u8, u8)> = game.board().pieces()
+            .map(|(pos, piece)| {
+                let color = if piece.player == PlayerColor::Black { 8 } else { 0 };
+                (8 * pos.rank.get() + pos.file.get(), piece_code(piece.piece_type) | color)
+            })
+            .collect();
+        if pieces.len() == 2 {
+            return Some(Some(0));
+        }
+        pieces.sort_unstable();
+        let white = material_name(game, PlayerColor::White);
+        let black = material_name(game, PlayerColor::Black);
+        let (tables, black_stronger) = match self.tables.get(&format!("{white}v{black}")) {
+            Some(tables) => (tables, false),
+            None => (self.tables.get(&format!("{black}v{white}"))?, true),
+        };
+        let white_to_move = game.active_player() == PlayerColor::White;
+        tables.table(kind)?.probe(&pieces, white_to_move, black_stronger, wdl).ok()
+    }
+
+    /// Searches the zeroing moves of a position before probing its WDL table, since the tables do
+    /// not store en passant rights and may store any value for positions in which a capture is
+    /// the best move.
+    ///
+    /// # Arguments
+    ///
+    /// * `game`: The position, in which the searched moves are made and unmade.
+    /// * `with_pawn_moves`: Whether pawn moves are searched besides captures, for DTZ probes.
+    ///
+    /// returns: `Some((value, zeroing))` with the WDL value from -2 to 2 and whether a searched
+    ///          move is the best move, or `None` if a table is missing.
+    fn search(&self, game: &mut ChessGame, with_pawn_moves: bool) -> Option<(i32, bool)> {
+        let legal_moves = game.legal_moves();
+        let mut best = -2;
+        let mut searched = 0;
+        for &chess_move in &legal_moves {
+            let zeroing = is_capture(game, chess_move)
+                || (with_pawn_moves && is_pawn_move(game, chess_move));
+            if !zeroing {
+                continue;
+            }
+            searched += 1;
+            let made_move = game.make_move(chess_move).ok()?;
+            let result = self.search(game, false);
+            game.unmake_move(made_move);
+            let value = -result?.0;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Some((value, true));
+                }
+            }
+        }
+        let all_searched = searched > 0 && searched == legal_moves.len();
+        let value = if all_searched { best } else { self.probe_table(game, TableKind::Wdl, 0)?? };
+        if best >= value {
+            return Some((best, best > 0 || all_searched));
+        }
+        Some((value, false))
+    }
+
+    /// returns: The DTZ value of a position, see [probe_dtz](ChessGame::probe_dtz).
+    fn dtz(&self, game: &mut ChessGame) -> Option<i32> {
+        let (wdl, zeroing) = self.search(game, true)?;
+        if wdl == 0 {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        if let Some(dtz) = self.probe_table(game, TableKind::Dtz, wdl)? {
+            return Some((dtz + if wdl.abs() == 1 { 100 } else { 0 }) * wdl.signum());
+        }
+
+        // the table only stores the other player to move, so the moves are searched one ply deep
+        let mut min_dtz = i32::MAX;
+        for chess_move in game.legal_moves() {
+            let zeroing = is_capture(game, chess_move) || is_pawn_move(game, chess_move);
+            let made_move = game.make_move(chess_move).ok()?;
+            let result = if zeroing {
+                self.search(game, false).map(|(wdl, _)| -dtz_before_zeroing(wdl))
+            } else {
+                self.dtz(game).map(|dtz| -dtz)
+            };
+            let is_mate = game.is_check() && game.legal_moves().is_empty();
+            game.unmake_move(made_move);
+            let mut dtz = result?;
+            if dtz == 1 && is_mate {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+}
+
+/// returns: The material of a table from its name, such as `KRvK`, or `None` if the name is not
+///          the name of a table.
+fn parse_material(name: &str) -> Option<TableMaterial> {
+    let (white, black) = name.split_once('v')?;
+    let count = |side: &str, letter: char| side.chars().filter(|&ch| ch == letter).count();
+    let mut piece_count = 0;
+    let mut has_unique_pieces = false;
+    for side in [white, black] {
+        if !side.starts_with('K') || count(side, 'K') != 1
+            || side.chars().any(|ch| !"KQRBNP".contains(ch))
+        {
+            return None;
+        }
+        piece_count += side.len();
+        has_unique_pieces |= "QRBNP".chars().any(|letter| count(side, letter) == 1);
+    }
+    let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+    // the side with fewer pawns leads, if it has any
+    let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+    let pawn_count = if white_leads {
+        [white_pawns, black_pawns]
+    } else {
+        [black_pawns, white_pawns]
+    };
+    Some(TableMaterial { piece_count, pawn_count, symmetric: white == black, has_unique_pieces })
+}
+
+/// returns: The part of a table name for the pieces of a player, such as `KRP`.
+fn material_name(game: &ChessGame, player: PlayerColor) -> String {
+    let mut name = String::new();
+    for piece_type in [PieceType::King, PieceType::Queen, PieceType::Rook, PieceType::Bishop,
+                       PieceType::Knight, PieceType::Pawn]
+    {
+        for _ in 0..game.board().find(piece_type, player).to_u64().count_ones() {
+            name.push(piece_type.to_char());
+        }
+    }
+    name
+}
+
+/// returns: The code of a white piece in the tables, from 1 for a pawn to 6 for a king.
+fn piece_code(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    }
+}
+
+fn is_capture(game: &ChessGame, chess_move: ChessMove) -> bool {
+    let PieceMovement { to, .. } = chess_move.piece_movement;
+    game.board().get_piece(to).is_some()
+        || (Some(to) == game.en_passant_target() && is_pawn_move(game, chess_move))
+}
+
+fn is_pawn_move(game: &ChessGame, chess_move: ChessMove) -> bool {
+    game.board().get_piece(chess_move.piece_movement.from)
+        .is_some_and(|piece| piece.piece_type == PieceType::Pawn)
+}
+
+/// returns: The DTZ value of a position in which a zeroing move is the best move.
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+/// The rank of a move with the given DTZ value after it, for choosing the move which wins the
+/// fastest without running into the fifty-move rule, or loses the slowest.
+fn move_rank(dtz: i32, halfmove_clock: i32) -> (i32, i32) {
+    const MAX_RANK: i32 = 1000;
+    let rank = if dtz > 0 {
+        if dtz + halfmove_clock <= 99 { MAX_RANK } else { MAX_RANK - (dtz + halfmove_clock) }
+    } else if dtz < 0 {
+        if -dtz * 2 + halfmove_clock < 100 {
+            -MAX_RANK
+        } else {
+            -MAX_RANK + (-dtz + halfmove_clock)
+        }
+    } else {
+        0
+    };
+    (rank, -dtz)
+}
+
+impl ChessGame {
+    /// returns: Whether the position can be probed in the tablebase, see
+    ///          [the module documentation](crate::tablebase).
+    fn is_probeable(&self, tablebase: &Tablebase) -> bool {
+        self.variant() == Variant::Standard
+            && [PlayerColor::White, PlayerColor::Black].into_iter()
+                .all(|player| self.castling_rights(player) == CastlingRights::NONE)
+            && self.board().pieces().count() <= tablebase.max_pieces()
+    }
+
+    /// Probes the result of the position with perfect play in a Syzygy tablebase. The result does
+    /// not depend on the moves made so far, so the fifty-move rule is counted from the position.
+    ///
+    /// returns: `Some(Wdl)` for the active player, or `None` if the position can not be probed,
+    ///          see [the module documentation](crate::tablebase).
+    pub fn probe_wdl(&self, tablebase: &Tablebase) -> Option<Wdl> {
+        if !self.is_probeable(tablebase) {
+            return None;
+        }
+        let (value, _) = tablebase.search(&mut self.analysis_copy(), false)?;
+        Wdl::from_value(value)
+    }
+
+    /// Probes the distance to zeroing of the position in a Syzygy tablebase, which is the number
+    /// of plies to the next capture or pawn move with perfect play. The value is positive if the
+    /// active player wins and negative if they lose, and 100 is added to its magnitude for a
+    /// [cursed win](Wdl::CursedWin) or [blessed loss](Wdl::BlessedLoss). The tables may round
+    /// the distance up by one ply.
+    ///
+    /// returns: `Some(i32)` with the distance, 0 for a draw, or `None` if the position can not be
+    ///          probed, see [the module documentation](crate::tablebase).
+    pub fn probe_dtz(&self, tablebase: &Tablebase) -> Option<i32> {
+        if !self.is_probeable(tablebase) {
+            return None;
+        }
+        tablebase.dtz(&mut self.analysis_copy())
+    }
+
+    /// Chooses a move with the best result in a Syzygy tablebase. A win which can be completed
+    /// before the fifty-move rule applies, judged by the
+    /// [halfmove clock](ChessGame::halfmove_clock) and the distance to zeroing after each move,
+    /// is preferred over any other move, and among wins the move with the lowest distance to
+    /// zeroing is chosen, so that repeating the choice makes progress. A losing player chooses
+    /// the move which delays the loss the longest.
+    ///
+    /// returns: `Some(ChessMove)` with the best move, or `None` if the game is over or the
+    ///          position can not be probed, see [the module documentation](crate::tablebase).
+    pub fn best_tablebase_move(&self, tablebase: &Tablebase) -> Option<ChessMove> {
+        if !self.is_probeable(tablebase) {
+            return None;
+        }
+        let halfmove_clock = i32::try_from(self.halfmove_clock()).unwrap_or(i32::MAX);
+        let mut game = self.analysis_copy();
+        let mut best = None;
+        for chess_move in game.legal_moves() {
+            let zeroing = is_capture(&game, chess_move) || is_pawn_move(&game, chess_move);
+            let made_move = game.make_move(chess_move).ok()?;
+            let result = if zeroing {
+                tablebase.search(&mut game, false).map(|(wdl, _)| dtz_before_zeroing(-wdl))
+            } else {
+                tablebase.dtz(&mut game).map(|dtz| -dtz - dtz.signum())
+            };
+            let is_mate = game.is_check() && game.legal_moves().is_empty();
+            game.unmake_move(made_move);
+            let dtz = match result? {
+                2 if is_mate => 1,
+                dtz => dtz,
+            };
+            let rank = move_rank(dtz, if zeroing { 0 } else { halfmove_clock });
+            if best.is_none_or(|(best_rank, _)| rank > best_rank) {
+                best = Some((rank, chess_move));
+            }
+        }
+        best.map(|(_, chess_move)| chess_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::tablebase::table::write_table;
+    use super::*;
+
+    /// returns: A new temporary directory containing the given files.
+    fn table_directory(name: &str, files: &[(&str, Vec<u8>)]) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("leben-chess-tablebase-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, bytes) in files {
+            std::fs::write(directory.join(file), bytes).unwrap();
+        }
+        directory
+    }
+
+    /// returns: A KRvK tablebase in which white always wins with a distance to zeroing of 31
+    ///          plies, except that black draws by capturing the rook, and a damaged KBvK table.
+    fn krvk_tablebase(name: &str) -> (Tablebase, PathBuf) {
+        let (king, rook, bishop, black_king) = (6, 4, 3, 14);
+        let wdl = write_table(TableKind::Wdl, 1, &[king, rook, black_king], 0, &[vec![4], vec![0]]);
+        let dtz = write_table(TableKind::Dtz, 1, &[king, rook, black_king], 0, &[vec![15]]);
+        let damaged = write_table(TableKind::Dtz, 1, &[king, bishop, black_king], 0,
+                                  &[vec![2], vec![2]]);
+        let directory = table_directory(name, &[
+            ("KRvK.rtbw", wdl), ("KRvK.rtbz", dtz), ("KBvK.rtbw", damaged), ("README", vec![]),
+        ]);
+        (Tablebase::new([&directory]).unwrap(), directory)
+    }
+
+    /// returns: The three-piece tables in `tests/fixtures/syzygy`, written by the `generate.rs`
+    ///          program there.
+    fn fixture_tablebase() -> Tablebase {
+        Tablebase::new([concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/syzygy")]).unwrap()
+    }
+
+    /// returns: The WDL and DTZ values of the position of a FEN string.
+    fn probe(tablebase: &Tablebase, fen: &str) -> (Option<Wdl>, Option<i32>) {
+        let game = ChessGame::from_fen(fen).unwrap();
+        (game.probe_wdl(tablebase), game.probe_dtz(tablebase))
+    }
+
+    #[test]
+    fn probe_positions() {
+        let (mut tablebase, directory) = krvk_tablebase("probe");
+        assert_eq!(tablebase.max_pieces(), 3);
+
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/K6R w - - 0 1"), (Some(Wdl::Win), Some(31)));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/K6R b - - 0 1"),
+                   (Some(Wdl::Loss), Some(-32)));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2K5/8/k6r b - - 0 1"), (Some(Wdl::Win), Some(31)));
+        // the rook can be captured
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/3R4/K7 b - - 0 1"), (Some(Wdl::Draw), Some(0)));
+
+        // missing and damaged tables, castling rights and too many pieces
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/K6Q w - - 0 1"), (None, None));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/K6B w - - 0 1"), (None, None));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/4K2R w K - 0 1"), (None, None));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/7P/K6R w - - 0 1"), (None, None));
+        let crazyhouse = ChessGame::from_fen_with_variant("8/8/8/8/8/2k5/8/K6R w - - 0 1",
+                                                          Variant::Crazyhouse).unwrap();
+        assert_eq!(crazyhouse.probe_wdl(&tablebase), None);
+
+        tablebase.set_probe_limit(Some(2));
+        assert_eq!(tablebase.max_pieces(), 2);
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/K6R w - - 0 1"), (None, None));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn best_moves() {
+        let (tablebase, directory) = krvk_tablebase("best");
+        let game = ChessGame::from_fen("8/8/8/8/8/2k5/3R4/K7 w - - 0 1").unwrap();
+        let best = game.best_tablebase_move(&tablebase).unwrap();
+        assert_eq!(game.peek_move(best).unwrap().probe_wdl(&tablebase), Some(Wdl::Loss));
+
+        // the mate is preferred over the other winning moves
+        let mate = ChessGame::from_fen("k7/8/1K6/8/8/8/8/7R w - - 70 1").unwrap();
+        assert_eq!(mate.best_tablebase_move(&tablebase), ChessMove::from_coords("h1", "h8").ok());
+
+        let drawn = ChessGame::from_fen("8/8/8/8/8/2k5/3R4/K7 b - - 0 1").unwrap();
+        assert_eq!(drawn.best_tablebase_move(&tablebase), ChessMove::from_coords("c3", "d2").ok());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn rook_endgame_fixtures() {
+        let tablebase = fixture_tablebase();
+        assert_eq!(tablebase.max_pieces(), 3);
+        // mate in one, and mate in two with 1. Kb6 Kb8 2. Rh8#
+        assert_eq!(probe(&tablebase, "k7/8/1K6/8/8/8/8/7R w - - 0 1"), (Some(Wdl::Win), Some(1)));
+        assert_eq!(probe(&tablebase, "k7/8/2K5/8/8/8/8/7R w - - 0 1"), (Some(Wdl::Win), Some(3)));
+        assert_eq!(probe(&tablebase, "k7/8/1K6/8/8/8/8/7R b - - 0 1"),
+                   (Some(Wdl::Loss), Some(-2)));
+        assert_eq!(probe(&tablebase, "k6R/8/1K6/8/8/8/8/8 b - - 0 1"),
+                   (Some(Wdl::Loss), Some(-1)));
+        assert_eq!(probe(&tablebase, "7r/8/8/8/8/1k6/8/K7 b - - 0 1"), (Some(Wdl::Win), Some(1)));
+        // the longest rook endgames are mates in 16 moves
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/1R6/K7 w - - 0 1"),
+                   (Some(Wdl::Win), Some(31)));
+        // stalemate, and a rook which can be captured
+        assert_eq!(probe(&tablebase, "k7/1R6/1K6/8/8/8/8/8 b - - 0 1"), (Some(Wdl::Draw), Some(0)));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/8/1k6/1R2K3 b - - 0 1"),
+                   (Some(Wdl::Draw), Some(0)));
+
+        let game = ChessGame::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let best = game.best_tablebase_move(&tablebase).unwrap();
+        assert_eq!(game.peek_move(best).unwrap().probe_dtz(&tablebase), Some(-2));
+    }
+
+    #[test]
+    fn pawn_endgame_fixtures() {
+        let tablebase = fixture_tablebase();
+        // the king in front of the pawn wins with either player to move, as in 1. Kd6 Kd8 2. e6
+        assert_eq!(probe(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"),
+                   (Some(Wdl::Win), Some(3)));
+        assert_eq!(probe(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"),
+                   (Some(Wdl::Loss), Some(-4)));
+        assert_eq!(probe(&tablebase, "8/8/8/8/4p3/4k3/8/4K3 b - - 0 1"),
+                   (Some(Wdl::Win), Some(3)));
+        // 1. Kf6 Kd7 2. Kf7 and 3. e8=Q, but stalemate with black to move
+        assert_eq!(probe(&tablebase, "4k3/4P3/4K3/8/8/8/8/8 w - - 0 1"),
+                   (Some(Wdl::Win), Some(5)));
+        assert_eq!(probe(&tablebase, "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"),
+                   (Some(Wdl::Draw), Some(0)));
+        // the king stops the rook pawn, but is outside the square of the h pawn
+        assert_eq!(probe(&tablebase, "k7/8/8/8/8/8/P7/7K w - - 0 1"), (Some(Wdl::Draw), Some(0)));
+        assert_eq!(probe(&tablebase, "8/8/8/8/8/k7/7P/7K w - - 0 1"), (Some(Wdl::Win), Some(1)));
+
+        // the promotions are probed in the tables of the promoted pieces
+        let game = ChessGame::from_fen("8/4P3/8/8/8/k7/8/K7 w - - 0 1").unwrap();
+        assert_eq!(game.probe_dtz(&tablebase), Some(1));
+        let best = game.best_tablebase_move(&tablebase).unwrap();
+        assert_eq!(best.piece_movement, ChessMove::from_coords("e7", "e8").unwrap().piece_movement);
+        assert_eq!(game.peek_move(best).unwrap().probe_wdl(&tablebase), Some(Wdl::Loss));
+    }
+
+    #[test]
+    fn fifty_move_rule_ranks() {
+        assert!(move_rank(33, 0) > move_rank(35, 0));
+        assert_eq!(move_rank(33, 66).0, move_rank(1, 0).0);
+        assert!(move_rank(33, 67) < move_rank(33, 66));
+        assert!(move_rank(33, 67) > move_rank(0, 0));
+        assert!(move_rank(-33, 0) > move_rank(-31, 0));
+        assert!(move_rank(-33, 40) > move_rank(-33, 0));
+    }
+
+    #[test]
+    fn missing_files() {
+        let directory = table_directory("empty", &[]);
+        let tablebase = Tablebase::new([&directory]).unwrap();
+        assert_eq!(tablebase.max_pieces(), 0);
+        let game = ChessGame::from_fen("8/8/8/8/8/2k5/8/K6R w - - 0 1").unwrap();
+        assert_eq!(game.probe_wdl(&tablebase), None);
+        assert_eq!(game.best_tablebase_move(&tablebase), None);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(matches!(Tablebase::new([&directory]), Err(TablebaseError::Io(_))));
+    }
+}
@@ -0,0 +1,977 @@
+//! Reading and decompressing single Syzygy table files.
+//!
+//! A table file starts with a 4-byte magic number and a flags byte, followed by the order of the
+//! pieces used for the index, the Huffman coding parameters of each sub-table, the optional DTZ
+//! value map, the sparse index, the block lengths and finally the 64-byte aligned compressed
+//! blocks. Squares are numbered `8 * rank + file` here as in the files, unlike
+//! [BoardPosition::index](crate::board::board_pos::BoardPosition::index).
+//!
+//! see: [Syzygy tablebases](https://github.com/syzygy1/tb)
+
+use std::sync::LazyLock;
+use crate::tablebase::TablebaseError;
+
+/// The magic number of WDL files.
+pub(crate) const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+/// The magic number of DTZ files.
+pub(crate) const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+/// The table has a sub-table for each side to move.
+const SPLIT: u8 = 1;
+/// The table has pawns and a sub-table for each file of the leading pawn.
+const HAS_PAWNS: u8 = 2;
+
+/// DTZ flag: the stored side to move is black.
+const STM: u8 = 1;
+/// DTZ flag: the values are mapped through the value map.
+const MAPPED: u8 = 2;
+/// DTZ flag: the winning values are stored in plies instead of moves.
+const WIN_PLIES: u8 = 4;
+/// DTZ flag: the losing values are stored in plies instead of moves.
+const LOSS_PLIES: u8 = 8;
+/// DTZ flag: the value map has 16-bit entries.
+const WIDE: u8 = 16;
+/// All values of the sub-table are the same.
+const SINGLE_VALUE: u8 = 128;
+
+/// The maximum number of pieces of a table.
+const MAX_PIECES: usize = 7;
+
+/// The kind of values stored in a table file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum TableKind {
+    /// Win/draw/loss values, in `.rtbw` files.
+    Wdl,
+    /// Distance to zeroing values, in `.rtbz` files.
+    Dtz,
+}
+
+impl TableKind {
+    fn magic(self) -> [u8; 4] {
+        match self {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        }
+    }
+}
+
+/// The lookup tables for computing the index of a position, shared by all tables.
+struct IndexTables {
+    /// The index of a square in the b1-h1-h7 triangle below the a1-h8 diagonal.
+    map_b1h1h7: [u16; 64],
+    /// The index of a square in the a1-d1-d4 triangle, with the diagonal squares last.
+    map_a1d1d4: [u16; 64],
+    /// The index of two kings for tables without unique pieces, by the a1-d1-d4 index of the first
+    /// king and the square of the second.
+    map_kk: [[u16; 64]; 10],
+    /// `binomial[k][n]` is the number of ways to choose `k` of `n` elements.
+    binomial: [[u64; 64]; 6],
+    /// The index of a pawn square, ordered so that the leading pawn has the largest index.
+    map_pawns: [u16; 64],
+    /// The index of the leading pawns, by their number and the square of the leading pawn.
+    lead_pawn_idx: [[u64; 64]; 6],
+    /// The number of indices of the leading pawns, by their number and the file of the leading
+    /// pawn.
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+static INDEX_TABLES: LazyLock<IndexTables> = LazyLock::new(IndexTables::new);
+
+fn file_of(square: u8) -> u8 {
+    square & 7
+}
+
+fn rank_of(square: u8) -> u8 {
+    square >> 3
+}
+
+/// returns: The distance of the square above the a1-h8 diagonal, negative below it.
+fn off_diagonal(square: u8) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+fn flip_diagonal(square: u8) -> u8 {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+fn edge_distance(file: u8) -> u8 {
+    file.min(7 - file)
+}
+
+fn kings_adjacent(a: u8, b: u8) -> bool {
+    file_of(a).abs_diff(file_of(b)) <= 1 && rank_of(a).abs_diff(rank_of(b)) <= 1
+}
+
+impl IndexTables {
+    fn new() -> IndexTables {
+        let mut map_b1h1h7 = [0; 64];
+        let mut code = 0;
+        for square in (0..64).filter(|&square| off_diagonal(square) < 0) {
+            map_b1h1h7[square as usize] = code;
+            code += 1;
+        }
+
+        let mut map_a1d1d4 = [0; 64];
+        let mut diagonal = Vec::new();
+        code = 0;
+        for square in (0..28).filter(|&square| file_of(square) <= 3) {
+            if off_diagonal(square) < 0 {
+                map_a1d1d4[square as usize] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            map_a1d1d4[square as usize] = code;
+            code += 1;
+        }
+
+        // b1 is the only square of the triangle with index 0
+        let mut map_kk = [[0; 64]; 10];
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for first in 0..10 {
+            for square1 in 0..28 {
+                if map_a1d1d4[square1 as usize] != first || (first == 0 && square1 != 1) {
+                    continue;
+                }
+                for square2 in 0..64 {
+                    if kings_adjacent(square1, square2)
+                        || (off_diagonal(square1) == 0 && off_diagonal(square2) > 0)
+                    {
+                        continue;
+                    }
+                    if off_diagonal(square1) == 0 && off_diagonal(square2) == 0 {
+                        both_on_diagonal.push((first, square2));
+                    } else {
+                        map_kk[first as usize][square2 as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (first, square2) in both_on_diagonal {
+            map_kk[first as usize][square2 as usize] = code;
+            code += 1;
+        }
+
+        let mut binomial = [[0; 64]; 6];
+        binomial[0] = [1; 64];
+        for k in 1..6 {
+            for n in 1..64 {
+                binomial[k][n] = binomial[k - 1][n - 1] + binomial[k][n - 1];
+            }
+        }
+
+        let mut map_pawns = [0; 64];
+        let mut available = 48;
+        let mut lead_pawn_idx = [[0; 64]; 6];
+        let mut lead_pawns_size = [[0; 4]; 6];
+        for (lead_pawns, sizes) in lead_pawns_size.iter_mut().enumerate().skip(1) {
+            for (file, size) in sizes.iter_mut().enumerate() {
+                let mut index = 0;
+                for rank in 1..7 {
+                    let square = 8 * rank + file as u8;
+                    if lead_pawns == 1 {
+                        available -= 1;
+                        map_pawns[square as usize] = available;
+                        available -= 1;
+                        map_pawns[(square ^ 7) as usize] = available;
+                    }
+                    lead_pawn_idx[lead_pawns][square as usize] = index;
+                    index += binomial[lead_pawns - 1][map_pawns[square as usize] as usize];
+                }
+                *size = index;
+            }
+        }
+
+        IndexTables { map_b1h1h7, map_a1d1d4, map_kk, binomial, map_pawns, lead_pawn_idx,
+            lead_pawns_size }
+    }
+}
+
+/// The coding parameters of one sub-table, for one side to move and file of the leading pawn.
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    flags: u8,
+    block_size: usize,
+    span: usize,
+    num_blocks: usize,
+    min_sym_len: u8,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u32>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+}
+
+/// The material of a table, as given by its file name.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TableMaterial {
+    /// The number of pieces of both sides.
+    pub(crate) piece_count: usize,
+    /// The number of pawns of the stronger and the weaker side.
+    pub(crate) pawn_count: [usize; 2],
+    /// Whether both sides have the same material.
+    pub(crate) symmetric: bool,
+    /// Whether some piece other than a king is the only one of its color and type.
+    pub(crate) has_unique_pieces: bool,
+}
+
+/// A loaded table file.
+pub(crate) struct Table {
+    bytes: Vec<u8>,
+    kind: TableKind,
+    material: TableMaterial,
+    has_pawns: bool,
+    map: usize,
+    /// The sub-tables by file of the leading pawn and side to move.
+    pairs: Vec<[PairsData; 2]>,
+}
+
+/// A bounds checked view of the bytes of a table file.
+struct Bytes<'a>(&'a [u8]);
+
+impl Bytes<'_> {
+    fn u8(&self, offset: usize) -> Result<u8, TablebaseError> {
+        self.0.get(offset).copied().ok_or(TablebaseError::Corrupt)
+    }
+
+    fn slice<const N: usize>(&self, offset: usize) -> Result<[u8; N], TablebaseError> {
+        self.0.get(offset..offset + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(TablebaseError::Corrupt)
+    }
+
+    fn u16_le(&self, offset: usize) -> Result<u16, TablebaseError> {
+        Ok(u16::from_le_bytes(self.slice(offset)?))
+    }
+
+    fn u32_le(&self, offset: usize) -> Result<u32, TablebaseError> {
+        Ok(u32::from_le_bytes(self.slice(offset)?))
+    }
+
+    fn u32_be(&self, offset: usize) -> Result<u32, TablebaseError> {
+        Ok(u32::from_be_bytes(self.slice(offset)?))
+    }
+
+    fn u64_be(&self, offset: usize) -> Result<u64, TablebaseError> {
+        Ok(u64::from_be_bytes(self.slice(offset)?))
+    }
+}
+
+impl Table {
+    /// Parses the contents of a table file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the file.
+    /// * `kind`: Whether the file is a WDL or a DTZ file.
+    /// * `material`: The material of the table, from its file name.
+    ///
+    /// returns: `Ok(Table)` ready for probing, and `Err(TablebaseError)` if the magic number is
+    ///          wrong, the file does not match the material or it is truncated.
+    pub(crate) fn new(bytes: Vec<u8>, kind: TableKind, material: TableMaterial)
+        -> Result<Table, TablebaseError>
+    {
+        if bytes.get(..4) != Some(&kind.magic()[..]) {
+            return Err(TablebaseError::BadMagic);
+        }
+        if material.piece_count > MAX_PIECES {
+            return Err(TablebaseError::Corrupt);
+        }
+        let tables = &*INDEX_TABLES;
+        let reader = Bytes(&bytes);
+        let flags = reader.u8(4)?;
+        let has_pawns = flags & HAS_PAWNS != 0;
+        if has_pawns != (material.pawn_count[0] > 0) || (flags & SPLIT != 0) == material.symmetric {
+            return Err(TablebaseError::Corrupt);
+        }
+        let sides = if kind == TableKind::Wdl && !material.symmetric { 2 } else { 1 };
+        let files = if has_pawns { 4 } else { 1 };
+        let both_pawns = material.pawn_count[0] > 0 && material.pawn_count[1] > 0;
+        let mut offset = 5;
+        let mut pairs = vec![[PairsData::default(), PairsData::default()]; files];
+
+        for (file, file_pairs) in pairs.iter_mut().enumerate() {
+            let second = if both_pawns { reader.u8(offset + 1)? } else { 0xFF };
+            let order = [
+                [reader.u8(offset)? & 0xF, second & 0xF],
+                [reader.u8(offset)? >> 4, second >> 4],
+            ];
+            offset += 1 + both_pawns as usize;
+            for piece in 0..material.piece_count {
+                let byte = reader.u8(offset + piece)?;
+                file_pairs[0].pieces[piece] = byte & 0xF;
+                file_pairs[1].pieces[piece] = byte >> 4;
+            }
+            offset += material.piece_count;
+            for side in 0..sides {
+                set_groups(&mut file_pairs[side], tables, &material, has_pawns, order[side],
+                           file)?;
+            }
+        }
+        offset += offset & 1;
+
+        for file_pairs in pairs.iter_mut() {
+            for pairs_data in file_pairs.iter_mut().take(sides) {
+                offset = set_sizes(pairs_data, &reader, offset)?;
+            }
+        }
+
+        let mut map = 0;
+        if kind == TableKind::Dtz {
+            map = offset;
+            for file_pairs in pairs.iter_mut() {
+                let pairs_data = &mut file_pairs[0];
+                if pairs_data.flags & MAPPED == 0 {
+                    continue;
+                }
+                if pairs_data.flags & WIDE != 0 {
+                    offset += offset & 1;
+                    for map_idx in pairs_data.map_idx.iter_mut() {
+                        *map_idx = (offset - map) / 2 + 1;
+                        offset += 2 + 2 * reader.u16_le(offset)? as usize;
+                    }
+                } else {
+                    for map_idx in pairs_data.map_idx.iter_mut() {
+                        *map_idx = offset - map + 1;
+                        offset += 1 + reader.u8(offset)? as usize;
+                    }
+                }
+            }
+            offset += offset & 1;
+        }
+
+        for file_pairs in pairs.iter_mut() {
+            for pairs_data in file_pairs.iter_mut().take(sides) {
+                pairs_data.sparse_index = offset;
+                offset += pairs_data.sparse_index_size * 6;
+            }
+        }
+        for file_pairs in pairs.iter_mut() {
+            for pairs_data in file_pairs.iter_mut().take(sides) {
+                pairs_data.block_length = offset;
+                offset += pairs_data.block_length_size * 2;
+            }
+        }
+        // the files end after the last block, before the alignment of empty sub-tables
+        let mut end = offset;
+        for file_pairs in pairs.iter_mut() {
+            for pairs_data in file_pairs.iter_mut().take(sides) {
+                offset = (offset + 0x3F) & !0x3F;
+                pairs_data.data = offset;
+                offset += pairs_data.num_blocks * pairs_data.block_size;
+                if pairs_data.num_blocks > 0 {
+                    end = offset;
+                }
+            }
+        }
+        if end > bytes.len() {
+            return Err(TablebaseError::Corrupt);
+        }
+        if sides == 1 {
+            for file_pairs in pairs.iter_mut() {
+                file_pairs[1] = file_pairs[0].clone();
+            }
+        }
+
+        Ok(Table { bytes, kind, material, has_pawns, map, pairs })
+    }
+
+    /// Looks up the stored value of a position.
+    ///
+    /// # Arguments
+    ///
+    /// * `pieces`: The squares and codes of all pieces of the position, with the white pieces
+    ///   coded 1 to 6 from pawn to king and black pieces 9 to 14.
+    /// * `white_to_move`: Whether white is to move.
+    /// * `black_stronger`: Whether the first side of the table's material is black's.
+    /// * `wdl`: For DTZ tables, the WDL value of the position from -2 to 2.
+    ///
+    /// returns: `Ok(Some(value))` with the WDL value from -2 to 2 or the DTZ value of the position,
+    ///          `Ok(None)` if the DTZ table only stores the other side to move, and
+    ///          `Err(TablebaseError)` if the position does not match the table or the table is
+    ///          corrupt.
+    pub(crate) fn probe(&self, pieces: &[(u8, u8)], white_to_move: bool, black_stronger: bool,
+                        wdl: i32) -> Result<Option<i32>, TablebaseError>
+    {
+        if pieces.len() != self.material.piece_count {
+            return Err(TablebaseError::Corrupt);
+        }
+        let tables = &*INDEX_TABLES;
+        let flip = black_stronger || (self.material.symmetric && !white_to_move);
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let side = (!white_to_move ^ flip) as usize;
+
+        let mut squares = Vec::with_capacity(pieces.len());
+        let mut codes = Vec::with_capacity(pieces.len());
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        let lead_code = if self.has_pawns { self.pairs[0][0].pieces[0] ^ flip_color } else { 0 };
+        if self.has_pawns {
+            for &(square, code) in pieces.iter().filter(|&&(_, code)| code == lead_code) {
+                squares.push(square ^ flip_squares);
+                codes.push(code ^ flip_color);
+            }
+            lead_pawns = squares.len();
+            let lead = (0..lead_pawns)
+                .max_by_key(|&i| (tables.map_pawns[squares[i] as usize], usize::MAX - i))
+                .ok_or(TablebaseError::Corrupt)?;
+            squares.swap(0, lead);
+            file = edge_distance(file_of(squares[0])) as usize;
+        }
+
+        let pairs_data = &self.pairs[file][side];
+        let stores_side = (pairs_data.flags & STM != 0) == (side == 1)
+            || (self.material.symmetric && !self.has_pawns);
+        if self.kind == TableKind::Dtz && !stores_side {
+            return Ok(None);
+        }
+
+        let is_lead_pawn = |code| self.has_pawns && code == lead_code;
+        for &(square, code) in pieces.iter().filter(|&&(_, code)| !is_lead_pawn(code)) {
+            squares.push(square ^ flip_squares);
+            codes.push(code ^ flip_color);
+        }
+        for i in lead_pawns..codes.len().saturating_sub(1) {
+            if let Some(j) = (i + 1..codes.len()).find(|&j| codes[j] == pairs_data.pieces[i]) {
+                codes.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        let value = self.decompress(pairs_data, self.index(pairs_data, &mut squares, lead_pawns)?)?;
+        Ok(Some(self.map_score(pairs_data, value as i32, wdl)?))
+    }
+
+    /// returns: The index of the position with the given squares in the order of the sub-table's
+    ///          pieces, changing the squares to the symmetric position used for the index.
+    fn index(&self, pairs_data: &PairsData, squares: &mut [u8], lead_pawns: usize)
+        -> Result<u64, TablebaseError>
+    {
+        let tables = &*INDEX_TABLES;
+        let binomial = |k: usize, n: usize| {
+            tables.binomial.get(k).and_then(|row| row.get(n)).copied()
+                .ok_or(TablebaseError::Corrupt)
+        };
+        if file_of(squares[0]) > 3 {
+            squares.iter_mut().for_each(|square| *square ^= 7);
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = *tables.lead_pawn_idx.get(lead_pawns).ok_or(TablebaseError::Corrupt)?
+                .get(squares[0] as usize).ok_or(TablebaseError::Corrupt)?;
+            squares[1..lead_pawns].sort_by_key(|&square| tables.map_pawns[square as usize]);
+            for (i, &square) in squares[1..lead_pawns].iter().enumerate() {
+                idx += binomial(i + 1, tables.map_pawns[square as usize] as usize)?;
+            }
+        } else {
+            if rank_of(squares[0]) > 3 {
+                squares.iter_mut().for_each(|square| *square ^= 56);
+            }
+            for i in 0..pairs_data.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    squares[i..].iter_mut().for_each(|square| *square = flip_diagonal(*square));
+                }
+                break;
+            }
+            if self.material.has_unique_pieces {
+                let [s0, s1, s2] = [squares[0], squares[1], squares[2]];
+                let adjust1 = (s1 > s0) as u64;
+                let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+                let [r0, r1, r2] = [s0, s1, s2].map(|square| rank_of(square) as u64);
+                let [s0, s1, s2] = [s0, s1, s2].map(|square| square as u64);
+                // the unique pieces on the diagonal are indexed by their ranks
+                idx = if off_diagonal(squares[0]) != 0 {
+                    (tables.map_a1d1d4[s0 as usize] as u64 * 63 + s1 - adjust1) * 62 + s2 - adjust2
+                } else if off_diagonal(squares[1]) != 0 {
+                    (6 * 63 + r0 * 28 + tables.map_b1h1h7[s1 as usize] as u64) * 62 + s2 - adjust2
+                } else if off_diagonal(squares[2]) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + r0 * 7 * 28 + (r1 - adjust1) * 28
+                        + tables.map_b1h1h7[s2 as usize] as u64
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + r0 * 7 * 6 + (r1 - adjust1) * 6
+                        + r2 - adjust2
+                };
+            } else {
+                idx = tables.map_kk[tables.map_a1d1d4[squares[0] as usize] as usize]
+                    [squares[1] as usize] as u64;
+            }
+        }
+
+        idx *= pairs_data.group_idx[0];
+        let mut group_start = pairs_data.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.material.pawn_count[1] > 0;
+        let mut next = 1;
+        while pairs_data.group_len[next] != 0 {
+            let len = pairs_data.group_len[next];
+            let group = squares.get_mut(group_start..group_start + len)
+                .ok_or(TablebaseError::Corrupt)?;
+            group.sort_unstable();
+            let mut n = 0;
+            for i in 0..len {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start].iter().filter(|&&other| square > other).count();
+                let square_idx = (square as usize)
+                    .checked_sub(adjust + 8 * remaining_pawns as usize)
+                    .ok_or(TablebaseError::Corrupt)?;
+                n += binomial(i + 1, square_idx)?;
+            }
+            remaining_pawns = false;
+            idx += n * pairs_data.group_idx[next];
+            group_start += len;
+            next += 1;
+        }
+        Ok(idx)
+    }
+
+    /// returns: The value stored at the given index of the sub-table.
+    fn decompress(&self, d: &PairsData, idx: u64) -> Result<u16, TablebaseError> {
+        if d.flags & SINGLE_VALUE != 0 {
+            return Ok(d.min_sym_len as u16);
+        }
+        let reader = Bytes(&self.bytes);
+        let corrupt = TablebaseError::Corrupt;
+        let span = d.span as u64;
+        let entry = d.sparse_index + 6 * usize::try_from(idx / span).map_err(|_| corrupt)?;
+        let mut block = reader.u32_le(entry)? as usize;
+        let mut offset = reader.u16_le(entry + 4)? as i64 + (idx % span) as i64 - (span / 2) as i64;
+        let block_length = |block: usize| -> Result<i64, TablebaseError> {
+            if block >= d.block_length_size {
+                return Err(TablebaseError::Corrupt);
+            }
+            Ok(reader.u16_le(d.block_length + 2 * block)? as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1).ok_or(TablebaseError::Corrupt)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let symlen = |sym: usize| d.symlen.get(sym).map(|&len| len as i64)
+            .ok_or(TablebaseError::Corrupt);
+        let mut ptr = d.data + block * d.block_size;
+        let mut buf64 = reader.u64_be(ptr)?;
+        ptr += 8;
+        let mut buf64_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buf64 < *d.base64.get(len).ok_or(TablebaseError::Corrupt)? {
+                len += 1;
+            }
+            let shift = (64 - len as u32).saturating_sub(d.min_sym_len as u32);
+            sym = (buf64 - d.base64[len]).checked_shr(shift).unwrap_or(0) as usize;
+            sym += reader.u16_le(d.lowest_sym + 2 * len)? as usize;
+            if offset < symlen(sym)? + 1 {
+                break;
+            }
+            offset -= symlen(sym)? + 1;
+            let len = len as u32 + d.min_sym_len as u32;
+            buf64 = buf64.checked_shl(len).unwrap_or(0);
+            buf64_size -= len as i32;
+            if buf64_size <= 32 {
+                buf64_size += 32;
+                // the bits read past the end of the last block are never decoded
+                buf64 |= (reader.u32_be(ptr).unwrap_or(0) as u64) << (64 - buf64_size);
+                ptr += 4;
+            }
+        }
+        while symlen(sym)? != 0 {
+            let (left, right) = btree_entry(&reader, d.btree, sym)?;
+            if offset < symlen(left)? + 1 {
+                sym = left;
+            } else {
+                offset -= symlen(left)? + 1;
+                sym = right;
+            }
+        }
+        Ok(btree_entry(&reader, d.btree, sym)?.0 as u16)
+    }
+
+    /// returns: The WDL value from -2 to 2 or the DTZ value of a decompressed value.
+    fn map_score(&self, d: &PairsData, value: i32, wdl: i32)
+        -> Result<i32, TablebaseError>
+    {
+        if self.kind == TableKind::Wdl {
+            return Ok(value - 2);
+        }
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let mut value = value;
+        if d.flags & MAPPED != 0 {
+            let index = d.map_idx[WDL_MAP[(wdl + 2) as usize]] + value as usize;
+            let reader = Bytes(&self.bytes);
+            value = if d.flags & WIDE != 0 {
+                reader.u16_le(self.map + 2 * index)? as i32
+            } else {
+                reader.u8(self.map + index)? as i32
+            };
+        }
+        if (wdl == 2 && d.flags & WIN_PLIES == 0) || (wdl == -2 && d.flags & LOSS_PLIES == 0)
+            || wdl == 1 || wdl == -1
+        {
+            value *= 2;
+        }
+        Ok(value + 1)
+    }
+}
+
+/// returns: The left and right children of a symbol in the Huffman tree.
+fn btree_entry(reader: &Bytes, btree: usize, sym: usize) -> Result<(usize, usize), TablebaseError> {
+    let [b0, b1, b2] = reader.slice::<3>(btree + 3 * sym)?;
+    let left = ((b1 as usize & 0xF) << 8) | b0 as usize;
+    let right = ((b2 as usize) << 4) | (b1 as usize >> 4);
+    Ok((left, right))
+}
+
+/// Groups the pieces of a sub-table and computes the multipliers of the group indices.
+fn set_groups(d: &mut PairsData, tables: &IndexTables, material: &TableMaterial, has_pawns: bool,
+              order: [u8; 2], file: usize) -> Result<(), TablebaseError>
+{
+    let mut n = 0;
+    let mut first_len: i32 = if has_pawns { 0 } else if material.has_unique_pieces { 3 } else { 2 };
+    d.group_len = [0; MAX_PIECES + 1];
+    d.group_len[0] = 1;
+    for i in 1..material.piece_count {
+        first_len -= 1;
+        if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+            d.group_len[n] += 1;
+        } else {
+            n += 1;
+            d.group_len[n] = 1;
+        }
+    }
+    n += 1;
+    let both_pawns = has_pawns && material.pawn_count[1] > 0;
+    let mut next = if both_pawns { 2 } else { 1 };
+    let mut free: usize = 64 - d.group_len[0] - if both_pawns { d.group_len[1] } else { 0 };
+    let mut idx: u64 = 1;
+    let binomial = |k: usize, n: usize| {
+        tables.binomial.get(k).and_then(|row| row.get(n)).copied().ok_or(TablebaseError::Corrupt)
+    };
+    let mut k = 0;
+    while next < n || k == order[0] || k == order[1] {
+        if k >= 0xF {
+            return Err(TablebaseError::Corrupt);
+        }
+        if k == order[0] {
+            d.group_idx[0] = idx;
+            idx *= if has_pawns {
+                *tables.lead_pawns_size.get(d.group_len[0]).ok_or(TablebaseError::Corrupt)?
+                    .get(file).ok_or(TablebaseError::Corrupt)?
+            } else if material.has_unique_pieces {
+                31332
+            } else {
+                462
+            };
+        } else if k == order[1] {
+            d.group_idx[1] = idx;
+            idx *= binomial(d.group_len[1], 48usize.checked_sub(d.group_len[0])
+                .ok_or(TablebaseError::Corrupt)?)?;
+        } else {
+            d.group_idx[next] = idx;
+            idx *= binomial(d.group_len[next], free)?;
+            free = free.checked_sub(d.group_len[next]).ok_or(TablebaseError::Corrupt)?;
+            next += 1;
+        }
+        k += 1;
+    }
+    d.group_idx[n] = idx;
+    Ok(())
+}
+
+/// Reads the Huffman coding parameters of a sub-table.
+///
+/// returns: `Ok(usize)` with the offset after the parameters.
+fn set_sizes(d: &mut PairsData, reader: &Bytes, offset: usize) -> Result<usize, TablebaseError> {
+    let tb_size = d.group_len.iter().position(|&len| len == 0)
+        .map(|n| d.group_idx[n])
+        .ok_or(TablebaseError::Corrupt)?;
+    d.flags = reader.u8(offset)?;
+    if d.flags & SINGLE_VALUE != 0 {
+        d.num_blocks = 0;
+        d.block_length_size = 0;
+        d.span = 0;
+        d.sparse_index_size = 0;
+        d.min_sym_len = reader.u8(offset + 1)?;
+        return Ok(offset + 2);
+    }
+    let block_size_log = reader.u8(offset + 1)? as u32;
+    let span_log = reader.u8(offset + 2)? as u32;
+    if block_size_log >= 32 || span_log >= 32 {
+        return Err(TablebaseError::Corrupt);
+    }
+    d.block_size = 1 << block_size_log;
+    d.span = 1 << span_log;
+    d.sparse_index_size = tb_size.div_ceil(d.span as u64) as usize;
+    let padding = reader.u8(offset + 3)? as usize;
+    d.num_blocks = reader.u32_le(offset + 4)? as usize;
+    d.block_length_size = d.num_blocks + padding;
+    let max_sym_len = reader.u8(offset + 8)?;
+    d.min_sym_len = reader.u8(offset + 9)?;
+    if max_sym_len < d.min_sym_len || max_sym_len > 64 {
+        return Err(TablebaseError::Corrupt);
+    }
+    d.lowest_sym = offset + 10;
+    let len = (max_sym_len - d.min_sym_len) as usize + 1;
+    let lowest_sym = |i: usize| reader.u16_le(d.lowest_sym + 2 * i).map(|sym| sym as u64);
+    d.base64 = vec![0; len];
+    for i in (0..len - 1).rev() {
+        d.base64[i] = d.base64[i + 1].wrapping_add(lowest_sym(i)?).wrapping_sub(lowest_sym(i + 1)?)
+            / 2;
+    }
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        *base = base.checked_shl(64 - i as u32 - d.min_sym_len as u32).unwrap_or(0);
+    }
+
+    let offset = d.lowest_sym + 2 * len;
+    let symbols = reader.u16_le(offset)? as usize;
+    d.btree = offset + 2;
+    d.symlen = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for sym in 0..symbols {
+        if !visited[sym] {
+            set_symlen(d, reader, sym, &mut visited)?;
+        }
+    }
+    Ok(d.btree + 3 * symbols + (symbols & 1))
+}
+
+/// Computes the number of values represented by a symbol, minus one.
+fn set_symlen(d: &mut PairsData, reader: &Bytes, sym: usize, visited: &mut [bool])
+    -> Result<(), TablebaseError>
+{
+    // marking the symbol first stops the recursion on cycles of a corrupt tree
+    visited[sym] = true;
+    let (left, right) = btree_entry(reader, d.btree, sym)?;
+    if right == 0xFFF {
+        return Ok(());
+    }
+    for child in [left, right] {
+        if !*visited.get(child).ok_or(TablebaseError::Corrupt)? {
+            set_symlen(d, reader, child, visited)?;
+        }
+    }
+    d.symlen[sym] = d.symlen[left].saturating_add(d.symlen[right]).saturating_add(1);
+    Ok(())
+}
+
+/// Encodes a table file for tests, with the values of each sub-table coded in 3 bits each, or as
+/// a single value if a sub-table has only one value.
+///
+/// # Arguments
+///
+/// * `kind`: Whether the file is a WDL or a DTZ file.
+/// * `flags`: The flags byte of the file.
+/// * `pieces`: The piece codes in index order, the same for both sides to move.
+/// * `sub_flags`: The flags of each sub-table.
+/// * `sub_tables`: The values of the sub-tables by file of the leading pawn and side to move.
+#[cfg(test)]
+pub(crate) fn write_table(kind: TableKind, flags: u8, pieces: &[u8], sub_flags: u8,
+                          sub_tables: &[Vec<u8>]) -> Vec<u8>
+{
+    const VALUES_PER_BLOCK: usize = 100;
+    const SPAN_LOG: u8 = 6;
+    let span = 1 << SPAN_LOG;
+    let files = if flags & HAS_PAWNS != 0 { 4 } else { 1 };
+    let mut bytes = kind.magic().to_vec();
+    bytes.push(flags);
+    for _ in 0..files {
+        bytes.push(0);
+        bytes.extend(pieces.iter().map(|&piece| piece | piece << 4));
+    }
+    bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+    for values in sub_tables {
+        if let [value] = values[..] {
+            bytes.extend([sub_flags | SINGLE_VALUE, value]);
+            continue;
+        }
+        let blocks = values.len().div_ceil(VALUES_PER_BLOCK) as u32;
+        bytes.extend([sub_flags, 6, SPAN_LOG, 0]);
+        bytes.extend(blocks.to_le_bytes());
+        bytes.extend([3, 3, 0, 0, 8, 0]);
+        for sym in 0..8 {
+            bytes.extend([sym, 0xF0, 0xFF]);
+        }
+    }
+    if kind == TableKind::Dtz {
+        bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+    }
+    let multi_value = || sub_tables.iter().filter(|values| values.len() > 1);
+    for values in multi_value() {
+        let last_block = (values.len() - 1) / VALUES_PER_BLOCK;
+        for k in 0..values.len().div_ceil(span) {
+            let middle = k * span + span / 2;
+            let block = (middle / VALUES_PER_BLOCK).min(last_block);
+            bytes.extend((block as u32).to_le_bytes());
+            bytes.extend(((middle - block * VALUES_PER_BLOCK) as u16).to_le_bytes());
+        }
+    }
+    for values in multi_value() {
+        for block in values.chunks(VALUES_PER_BLOCK) {
+            bytes.extend((block.len() as u16 - 1).to_le_bytes());
+        }
+    }
+    for values in multi_value() {
+        for block in values.chunks(VALUES_PER_BLOCK) {
+            bytes.resize((bytes.len() + 0x3F) & !0x3F, 0);
+            let start = bytes.len();
+            bytes.resize(start + 64, 0);
+            for (i, &value) in block.iter().enumerate() {
+                for bit in 0..3 {
+                    if value & (4 >> bit) != 0 {
+                        let position = 3 * i + bit;
+                        bytes[start + position / 8] |= 0x80 >> (position % 8);
+                    }
+                }
+            }
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    const KING: u8 = 6;
+    const ROOK: u8 = 4;
+    const PAWN: u8 = 1;
+    const BLACK: u8 = 8;
+
+    const KRVK: TableMaterial = TableMaterial {
+        piece_count: 3, pawn_count: [0, 0], symmetric: false, has_unique_pieces: true,
+    };
+    const KPVK: TableMaterial = TableMaterial {
+        piece_count: 3, pawn_count: [1, 0], symmetric: false, has_unique_pieces: true,
+    };
+
+    /// returns: The square after one of the 8 symmetries of the board.
+    fn transform(square: u8, symmetry: u8) -> u8 {
+        let mut square = square;
+        if symmetry & 1 != 0 {
+            square ^= 7;
+        }
+        if symmetry & 2 != 0 {
+            square ^= 56;
+        }
+        if symmetry & 4 != 0 {
+            square = flip_diagonal(square);
+        }
+        square
+    }
+
+    #[test]
+    fn index_tables() {
+        let tables = &*INDEX_TABLES;
+        assert_eq!(tables.map_kk.iter().flatten().max(), Some(&461));
+        assert_eq!((0..64).map(|square| tables.map_b1h1h7[square]).max(), Some(27));
+        // b1, c1, d1, c2, d2, d3, then a1, b2, c3, d4 on the diagonal
+        let a1d1d4 = [1, 2, 3, 10, 11, 19, 0, 9, 18, 27].map(|square| tables.map_a1d1d4[square]);
+        assert_eq!(a1d1d4, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(tables.binomial[2][4], 6);
+        assert_eq!(tables.binomial[5][63], 7028847);
+        assert_eq!(tables.lead_pawns_size[1], [6, 6, 6, 6]);
+        assert_eq!(tables.lead_pawns_size[2].iter().sum::<u64>(), 252 + 180 + 108 + 36);
+        // a2, h2, a3, h7 and e7
+        assert_eq!([8, 15, 16, 55, 52].map(|square| tables.map_pawns[square]), [47, 46, 45, 36, 0]);
+    }
+
+    #[test]
+    fn unique_piece_index_is_invariant_under_symmetries() {
+        let bytes = write_table(TableKind::Wdl, SPLIT, &[KING, ROOK, KING | BLACK], 0,
+                                &[vec![4], vec![0]]);
+        let table = Table::new(bytes, TableKind::Wdl, KRVK).unwrap();
+        let pairs_data = &table.pairs[0][0];
+        let mut positions = HashMap::new();
+        for squares in (0..64 * 64 * 64).map(|i| [i / 4096, i / 64 % 64, i % 64].map(|s| s as u8)) {
+            if squares[0] == squares[1] || squares[0] == squares[2] || squares[1] == squares[2] {
+                continue;
+            }
+            let canonical = (0..8).map(|symmetry| squares.map(|s| transform(s, symmetry))).min()
+                .unwrap();
+            let idx = table.index(pairs_data, &mut squares.clone(), 0).unwrap();
+            assert!(idx < 31332, "{squares:?}");
+            assert_eq!(*positions.entry(idx).or_insert(canonical), canonical, "{squares:?}");
+        }
+    }
+
+    #[test]
+    fn pawn_index_is_invariant_under_mirroring() {
+        let bytes = write_table(TableKind::Wdl, SPLIT | HAS_PAWNS, &[PAWN, KING, KING | BLACK], 0,
+                                &vec![vec![4]; 8]);
+        let table = Table::new(bytes, TableKind::Wdl, KPVK).unwrap();
+        let mut positions = HashMap::new();
+        for squares in (0..64 * 64 * 64).map(|i| [i / 4096, i / 64 % 64, i % 64].map(|s| s as u8)) {
+            if !(8..56).contains(&squares[0]) || squares[0] == squares[1]
+                || squares[0] == squares[2] || squares[1] == squares[2]
+            {
+                continue;
+            }
+            let file = edge_distance(file_of(squares[0])) as usize;
+            let pairs_data = &table.pairs[file][0];
+            let canonical = squares.min(squares.map(|s| s ^ 7));
+            let idx = table.index(pairs_data, &mut squares.clone(), 1).unwrap();
+            assert!(idx < 6 * 63 * 62, "{squares:?}");
+            assert_eq!(*positions.entry((file, idx)).or_insert(canonical), canonical,
+                       "{squares:?}");
+        }
+        // no position is its own mirror image, so every index is used
+        assert_eq!(positions.len(), 4 * 6 * 63 * 62);
+    }
+
+    #[test]
+    fn decompress_all_values() {
+        let values = |seed: usize| {
+            (0..31332).map(|i| ((i * 7 + seed) % 5) as u8).collect::<Vec<_>>()
+        };
+        let bytes = write_table(TableKind::Wdl, SPLIT, &[KING, ROOK, KING | BLACK], 0,
+                                &[values(0), values(3)]);
+        let table = Table::new(bytes, TableKind::Wdl, KRVK).unwrap();
+        for (side, seed) in [(0, 0), (1, 3)] {
+            let pairs_data = &table.pairs[0][side];
+            for (idx, &value) in values(seed).iter().enumerate() {
+                assert_eq!(table.decompress(pairs_data, idx as u64).unwrap(), value as u16,
+                           "side {side} index {idx}");
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_files() {
+        let bytes = write_table(TableKind::Wdl, SPLIT, &[KING, ROOK, KING | BLACK], 0,
+                                &[vec![4], vec![0]]);
+        assert!(matches!(Table::new(bytes.clone(), TableKind::Dtz, KRVK),
+                         Err(TablebaseError::BadMagic)));
+        assert!(matches!(Table::new(bytes.clone(), TableKind::Wdl, KPVK),
+                         Err(TablebaseError::Corrupt)));
+        assert!(matches!(Table::new(bytes[..7].to_vec(), TableKind::Wdl, KRVK),
+                         Err(TablebaseError::Corrupt)));
+
+        let values = vec![2; 31332];
+        let bytes = write_table(TableKind::Wdl, SPLIT, &[KING, ROOK, KING | BLACK], 0,
+                                &[values.clone(), values]);
+        assert!(matches!(Table::new(bytes[..bytes.len() - 64].to_vec(), TableKind::Wdl, KRVK),
+                         Err(TablebaseError::Corrupt)));
+    }
+}
@@ -0,0 +1,675 @@
+//! Headless tournaments between engines, for example for measuring the progress of an engine
+//! against earlier versions. Only available with the `std` feature.
+//!
+//! A [Tournament] plays games between [MovePolicy]s, such as [SearchBackend]s, in a
+//! [round robin](TournamentFormat::RoundRobin) or a [gauntlet](TournamentFormat::Gauntlet). Each
+//! pair of engines plays every opening twice, once with each engine as white. The games are
+//! [played out](ChessGame::play_out) with the engines' moves, on a clock following the
+//! tournament's [TimeControl], and may be ended early by [resign](ResignAdjudication) and
+//! [draw](DrawAdjudication) adjudication. Several games may be played at the same time on
+//! separate threads, see [with_threads](Tournament::with_threads). The
+//! [results](TournamentResults) give a standings table and the games in PGN.
+
+use std::fmt::{Display, Formatter};
+use std::panic;
+use std::thread;
+use std::time::Instant;
+use crate::board::Board;
+use crate::board::piece::PlayerColor;
+use crate::chess::{AdjudicatedResult, ChessGame, Outcome};
+use crate::clock::{ChessClock, ClockError, TimeControl};
+use crate::moves::ChessMove;
+use crate::pgn;
+use crate::search::{SearchBackend, SearchLimits};
+
+/// A player of the games of a [Tournament], which chooses the moves of one side. Every
+/// [SearchBackend] is a policy playing the best move of its search, and [FnPolicy] turns a
+/// function into a policy, for example a random move picker or a fixed list of moves.
+pub trait MovePolicy: Send {
+    /// Chooses the move to play in a game which is not over.
+    ///
+    /// # Arguments
+    ///
+    /// * `game`: The game, with the policy's player to move.
+    /// * `limits`: The limits of a search for the move, for policies which search, with the time
+    ///   limited to a share of the player's remaining time on the clock.
+    ///
+    /// returns: `Some(PolicyMove)` of the chosen move, or `None` if the policy finds no move.
+    fn choose_move(&mut self, game: &ChessGame, limits: &SearchLimits) -> Option<PolicyMove>;
+
+    /// Called before each game the policy plays, so that any state kept between the moves of a
+    /// game can be cleared. Does nothing by default.
+    fn new_game(&mut self) {}
+}
+
+/// A move chosen by a [MovePolicy].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PolicyMove {
+    /// The move to play.
+    pub chess_move: ChessMove,
+    /// The score of the position in centipawns from the perspective of the policy's player, if
+    /// the policy evaluates positions. Moves without a score do not count toward
+    /// [resign](ResignAdjudication) or [draw](DrawAdjudication) adjudication.
+    pub score: Option<i32>,
+}
+
+impl<B: SearchBackend> MovePolicy for B {
+    fn choose_move(&mut self, game: &ChessGame, limits: &SearchLimits) -> Option<PolicyMove> {
+        let result = self.search(game, limits)?;
+        Some(PolicyMove { chess_move: result.best_move, score: Some(result.score) })
+    }
+
+    fn new_game(&mut self) {
+        SearchBackend::new_game(self);
+    }
+}
+
+/// A [MovePolicy] choosing moves with a function, like the policies of [ChessGame::play_out].
+/// The function is given the game, and the search limits are ignored. The positions are not
+/// scored.
+#[derive(Copy, Clone, Debug)]
+pub struct FnPolicy<F>(pub F);
+
+impl<F: FnMut(&ChessGame) -> Option<ChessMove> + Send> MovePolicy for FnPolicy<F> {
+    fn choose_move(&mut self, game: &ChessGame, _: &SearchLimits) -> Option<PolicyMove> {
+        let chess_move = (self.0)(game)?;
+        Some(PolicyMove { chess_move, score: None })
+    }
+}
+
+/// Which pairs of engines play each other in a [Tournament].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TournamentFormat {
+    /// Every engine plays every other engine.
+    RoundRobin,
+    /// The first engine added plays every other engine, and the other engines do not play each
+    /// other.
+    Gauntlet,
+}
+
+/// Ends a game of a [Tournament] as a loss for an engine which evaluates its position as lost.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ResignAdjudication {
+    /// The score in centipawns, from the engine's perspective, at or below minus which the
+    /// engine considers its position lost.
+    pub threshold: i32,
+    /// The number of the engine's moves in a row with a lost position after which it resigns.
+    pub moves: u32,
+}
+
+/// Ends a game of a [Tournament] as a draw when both engines evaluate the position as equal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DrawAdjudication {
+    /// The largest absolute score in centipawns which is considered equal.
+    pub margin: i32,
+    /// The number of moves of each engine in a row with an equal position after which the game
+    /// is drawn.
+    pub moves: u32,
+}
+
+/// A game of a [Tournament], with the engines given as indices in the order they were added.
+#[derive(Clone, Debug)]
+pub struct TournamentGame {
+    /// The engine with the white pieces.
+    pub white: usize,
+    /// The engine with the black pieces.
+    pub black: usize,
+    /// The game, which is over.
+    pub game: ChessGame,
+}
+
+/// The line of an engine in the standings of a tournament. See [TournamentResults::standings].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    /// The name of the engine.
+    pub name: String,
+    /// The points scored: 1 for a win and 0.5 for a draw.
+    pub points: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Standing {
+    /// returns: The number of games played by the engine.
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// The results of a [Tournament]. The [Display] implementation shows the
+/// [standings](TournamentResults::standings) as a table.
+#[derive(Clone, Debug)]
+pub struct TournamentResults {
+    names: Vec<String>,
+    games: Vec<TournamentGame>,
+}
+
+impl TournamentResults {
+    /// returns: The names of the engines, in the order they were added.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// returns: The games of the tournament, in the order they were played.
+    pub fn games(&self) -> &[TournamentGame] {
+        &self.games
+    }
+
+    /// returns: The points scored by each engine, in the order the engines were added.
+    pub fn scores(&self) -> Vec<f64> {
+        self.standings_in_order().into_iter().map(|standing| standing.points).collect()
+    }
+
+    /// returns: The [Standing] of each engine, ordered by points from the most to the least.
+    ///          Engines with the same points keep the order they were added in.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = self.standings_in_order();
+        standings.sort_by(|a, b| b.points.total_cmp(&a.points));
+        standings
+    }
+
+    fn standings_in_order(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self.names.iter()
+            .map(|name| Standing { name: name.clone(), points: 0.0, wins: 0, draws: 0, losses: 0 })
+            .collect();
+        for game in &self.games {
+            let Some(outcome) = game.game.match_outcome() else {
+                continue;
+            };
+            let players = [(game.white, PlayerColor::White), (game.black, PlayerColor::Black)];
+            for (engine, player) in players {
+                let standing = &mut standings[engine];
+                standing.points += outcome.score(player);
+                match outcome {
+                    Outcome::Win(winner) if winner == player => standing.wins += 1,
+                    Outcome::Win(_) => standing.losses += 1,
+                    Outcome::Draw => standing.draws += 1,
+                }
+            }
+        }
+        standings
+    }
+
+    /// Exports all games of the tournament in PGN, one after another separated by blank lines.
+    /// Each game has the seven tag roster, with the game's number as the round, and the
+    /// `Termination` tag of an adjudicated game.
+    ///
+    /// # Arguments
+    ///
+    /// * `event`: The name of the tournament, used as the `Event` tag of each game.
+    ///
+    /// returns: The games in PGN.
+    pub fn to_pgn(&self, event: &str) -> String {
+        let games: Vec<String> = self.games.iter().enumerate()
+            .map(|(game_index, game)| {
+                let round = (game_index + 1).to_string();
+                pgn::write_game(&game.game, &[
+                    ("Event", event),
+                    ("Site", "?"),
+                    ("Date", "????.??.??"),
+                    ("Round", &round),
+                    ("White", &self.names[game.white]),
+                    ("Black", &self.names[game.black]),
+                    ("Result", pgn::result_token(game.game.game_status())),
+                ])
+            })
+            .collect();
+        games.join("\n")
+    }
+}
+
+impl Display for TournamentResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name_width = self.names.iter().map(String::len).max().unwrap_or(0).max(4);
+        write!(f, "Rank  {:<name_width$}  Points  Games  Wins  Draws  Losses", "Name")?;
+        for (rank, standing) in self.standings().iter().enumerate() {
+            write!(f, "\n{:>4}  {:<name_width$}  {:>6.1}  {:>5}  {:>4}  {:>5}  {:>6}", rank + 1,
+                   standing.name, standing.points, standing.games(), standing.wins,
+                   standing.draws, standing.losses)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a game of a tournament was stopped before the engine to move played its move.
+enum Stop {
+    NoMove(PlayerColor),
+    Flagged(PlayerColor),
+    Resigned(PlayerColor),
+    Drawn,
+}
+
+/// A tournament between engines. Engines and settings are added with the `with_` methods, and
+/// [run](Tournament::run) plays all games.
+///
+/// # Example
+///
+/// ```rust
+/// use leben_chess::search::{AlphaBetaBackend, SearchLimits};
+/// use leben_chess::tournament::{Tournament, TournamentFormat};
+///
+/// let limits = SearchLimits { max_depth: Some(1), ..SearchLimits::default() };
+/// let mut tournament = Tournament::new(TournamentFormat::RoundRobin, "60+1".parse().unwrap())
+///     .with_engine("first", AlphaBetaBackend)
+///     .with_engine("second", AlphaBetaBackend)
+///     .with_search_limits(limits)
+///     .with_max_plies(20);
+/// let results = tournament.run();
+/// assert_eq!(results.games().len(), 2);
+/// println!("{}", results);
+/// ```
+pub struct Tournament {
+    format: TournamentFormat,
+    engines: Vec<(String, EngineFactory)>,
+    openings: Vec<ChessGame>,
+    settings: GameSettings,
+    threads: usize,
+}
+
+/// Makes a copy of an engine of a [Tournament] for a game.
+type EngineFactory = Box<dyn Fn() -> Box<dyn MovePolicy> + Send>;
+
+/// The settings shared by all games of a [Tournament].
+struct GameSettings {
+    time_control: TimeControl,
+    limits: SearchLimits,
+    resign: Option<ResignAdjudication>,
+    draw: Option<DrawAdjudication>,
+    max_plies: usize,
+}
+
+impl Tournament {
+    /// The highest number of plies played in a game by default, after which the game is
+    /// adjudicated as a draw.
+    pub const DEFAULT_MAX_PLIES: usize = 400;
+
+    /// returns: A new tournament without engines, where every game has the given time control
+    ///          and starts from the standard starting position.
+    pub fn new(format: TournamentFormat, time_control: TimeControl) -> Tournament {
+        Tournament {
+            format,
+            engines: Vec::new(),
+            openings: vec![ChessGame::new(Board::default_board())],
+            settings: GameSettings {
+                time_control,
+                limits: SearchLimits::default(),
+                resign: None,
+                draw: None,
+                max_plies: Tournament::DEFAULT_MAX_PLIES,
+            },
+            threads: 1,
+        }
+    }
+
+    /// Adds an engine, which is any [MovePolicy], such as a [SearchBackend]. Each game is played
+    /// with a new copy of the engine as it was added, so that games do not affect each other and
+    /// may be played at the same time.
+    ///
+    /// returns: The tournament with another engine, identified by name.
+    pub fn with_engine(mut self, name: impl Into<String>,
+                       engine: impl MovePolicy + Clone + 'static) -> Tournament
+    {
+        self.engines.push((name.into(), Box::new(move || Box::new(engine.clone()))));
+        self
+    }
+
+    /// Sets the positions the games start from, for example a set of openings. Each pair of
+    /// engines plays every opening twice, once with each engine as white. An opening which
+    /// already has a move history keeps it in each game played from it.
+    ///
+    /// returns: The tournament with the given openings, or unchanged if `openings` is empty.
+    pub fn with_openings(mut self, openings: Vec<ChessGame>) -> Tournament {
+        if !openings.is_empty() {
+            self.openings = openings;
+        }
+        self
+    }
+
+    /// Sets the limits of every search, such as a fixed depth. With a timed
+    /// [TimeControl], the time of a search is further limited to a share of the engine's
+    /// remaining time on the clock. The stop flag of the limits is shared by all searches.
+    ///
+    /// returns: The tournament with the given search limits.
+    pub fn with_search_limits(mut self, limits: SearchLimits) -> Tournament {
+        self.settings.limits = limits;
+        self
+    }
+
+    /// returns: The tournament with resign adjudication, see [ResignAdjudication].
+    pub fn with_resign_adjudication(mut self, resign: ResignAdjudication) -> Tournament {
+        self.settings.resign = Some(resign);
+        self
+    }
+
+    /// returns: The tournament with draw adjudication, see [DrawAdjudication].
+    pub fn with_draw_adjudication(mut self, draw: DrawAdjudication) -> Tournament {
+        self.settings.draw = Some(draw);
+        self
+    }
+
+    /// returns: The tournament where games still going after `max_plies` plies are adjudicated
+    ///          as a draw, [DEFAULT_MAX_PLIES](Tournament::DEFAULT_MAX_PLIES) by default.
+    pub fn with_max_plies(mut self, max_plies: usize) -> Tournament {
+        self.settings.max_plies = max_plies;
+        self
+    }
+
+    /// Sets the number of games played at the same time, each on its own thread. The results do
+    /// not depend on the number of threads, unless the engines are limited by time, since the
+    /// games are still listed in the same order.
+    ///
+    /// returns: The tournament playing `threads` games at a time, 1 by default. Counts below 1
+    ///          are treated as 1.
+    pub fn with_threads(mut self, threads: usize) -> Tournament {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// returns: The pairs of engines playing each other, by index.
+    fn pairings(&self) -> Vec<(usize, usize)> {
+        let engines = self.engines.len();
+        match self.format {
+            TournamentFormat::RoundRobin => (0..engines)
+                .flat_map(|first| (first + 1..engines).map(move |second| (first, second)))
+                .collect(),
+            TournamentFormat::Gauntlet => (1..engines).map(|second| (0, second)).collect(),
+        }
+    }
+
+    /// Plays all games of the tournament, on as many threads as set with
+    /// [with_threads](Tournament::with_threads). For each pairing of engines, the openings are
+    /// played in order, each once with either engine as white. An engine which plays an illegal
+    /// move, or finds no move in a game which is not over, loses the game by adjudication.
+    ///
+    /// returns: The results of the tournament, with the games in the order described above.
+    pub fn run(&mut self) -> TournamentResults {
+        let mut schedule = Vec::new();
+        for (first, second) in self.pairings() {
+            for opening in 0..self.openings.len() {
+                for (white, black) in [(first, second), (second, first)] {
+                    schedule.push((opening, white, black));
+                }
+            }
+        }
+        // the games are dealt out to the threads in turn, each with its own copies of the engines
+        let threads = self.threads.min(schedule.len()).max(1);
+        let mut batches: Vec<Vec<_>> = (0..threads).map(|_| Vec::new()).collect();
+        for (index, &(opening, white, black)) in schedule.iter().enumerate() {
+            let engines = [(self.engines[white].1)(), (self.engines[black].1)()];
+            batches[index % threads].push((index, opening, engines));
+        }
+        let (settings, openings) = (&self.settings, &self.openings);
+        let mut played: Vec<(usize, ChessGame)> = thread::scope(|scope| {
+            let handles: Vec<_> = batches.into_iter()
+                .map(|batch| scope.spawn(move || {
+                    batch.into_iter()
+                        .map(|(index, opening, mut engines)| {
+                            (index, settings.play_game(&openings[opening], &mut engines))
+                        })
+                        .collect::<Vec<_>>()
+                }))
+                .collect();
+            handles.into_iter()
+                .flat_map(|handle| {
+                    handle.join().unwrap_or_else(|error| panic::resume_unwind(error))
+                })
+                .collect()
+        });
+        played.sort_by_key(|(index, _)| *index);
+        TournamentResults {
+            names: self.engines.iter().map(|(name, _)| name.clone()).collect(),
+            games: played.into_iter()
+                .map(|(index, game)| {
+                    let (_, white, black) = schedule[index];
+                    TournamentGame { white, black, game }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl GameSettings {
+    /// Plays a game from an opening between two engines, the first of which plays white.
+    fn play_game(&self, opening: &ChessGame, engines: &mut [Box<dyn MovePolicy>; 2]) -> ChessGame {
+        let mut game = opening.clone();
+        for engine in engines.iter_mut() {
+            engine.new_game();
+        }
+        let mut clock = ChessClock::new(self.time_control.clone());
+        if let Some(clock) = &mut clock {
+            clock.start(game.active_player(), Instant::now());
+        }
+        let (resign, draw, limits) = (self.resign, self.draw, &self.limits);
+        // the moves in a row of white and black with a lost position, and the plies in a row
+        // with an equal position
+        let mut lost_moves = [0, 0];
+        let mut equal_plies = 0;
+        let mut stop = None;
+        let result = game.play_out(|game| {
+            let player = game.active_player();
+            let (engine, lost) = match player {
+                PlayerColor::White => (&mut engines[0], &mut lost_moves[0]),
+                PlayerColor::Black => (&mut engines[1], &mut lost_moves[1]),
+            };
+            let mut limits = limits.clone();
+            if let Some(clock) = &clock {
+                let now = Instant::now();
+                let budget = clock.remaining_time(player, now) / 30
+                    + clock.delay_mode(player).duration();
+                limits.max_time = Some(limits.max_time.map_or(budget, |time| time.min(budget)));
+            }
+            let Some(policy_move) = engine.choose_move(game, &limits) else {
+                stop = Some(Stop::NoMove(player));
+                return None;
+            };
+            if let Some(clock) = &mut clock
+                && let Err(ClockError::Flagged(player)) = clock.on_move(Instant::now())
+            {
+                stop = Some(Stop::Flagged(player));
+                return None;
+            }
+            let score = policy_move.score;
+            *lost = match (resign, score) {
+                (Some(resign), Some(score)) if score <= -resign.threshold => *lost + 1,
+                _ => 0,
+            };
+            equal_plies = match (draw, score) {
+                (Some(draw), Some(score)) if score.abs() <= draw.margin => equal_plies + 1,
+                _ => 0,
+            };
+            if resign.is_some_and(|resign| *lost >= resign.moves) {
+                stop = Some(Stop::Resigned(player));
+                return None;
+            }
+            if draw.is_some_and(|draw| equal_plies >= 2 * draw.moves) {
+                stop = Some(Stop::Drawn);
+                return None;
+            }
+            Some(policy_move.chess_move)
+        }, self.max_plies);
+        // the game is not over unless it was played to the end, so ending it always succeeds
+        let other_wins = |player: PlayerColor| AdjudicatedResult::WinFor(player.other_player());
+        match (result, stop) {
+            (Err(_), _) => {
+                let _ = game.adjudicate(other_wins(game.active_player()), "illegal move");
+            }
+            (Ok(_), Some(Stop::NoMove(player))) => {
+                let _ = game.adjudicate(other_wins(player), "no move");
+            }
+            (Ok(_), Some(Stop::Flagged(player))) => {
+                let _ = game.flag(player);
+            }
+            (Ok(_), Some(Stop::Resigned(player))) => {
+                let _ = game.adjudicate(other_wins(player), "resign adjudication");
+            }
+            (Ok(_), Some(Stop::Drawn)) => {
+                let _ = game.adjudicate(AdjudicatedResult::Draw, "draw adjudication");
+            }
+            (Ok(_), None) => {
+                let _ = game.adjudicate(AdjudicatedResult::Draw, "move limit");
+            }
+        }
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chess::{DrawReason, GameStatus, WinReason};
+    use crate::moves::{sort_moves_canonical, ChessMove};
+    use crate::search::{AlphaBetaBackend, SearchResult};
+    use super::*;
+
+    /// An engine which always plays the first legal move in canonical order, scoring every
+    /// position with a fixed score.
+    #[derive(Clone)]
+    struct FirstMove(i32);
+
+    impl SearchBackend for FirstMove {
+        fn search(&mut self, game: &ChessGame, _: &SearchLimits) -> Option<SearchResult> {
+            let mut moves = game.legal_moves();
+            sort_moves_canonical(&mut moves);
+            let best_move = *moves.first()?;
+            Some(SearchResult {
+                best_move,
+                score: self.0,
+                principal_variation: vec![best_move],
+                iterations: Vec::new(),
+            })
+        }
+    }
+
+    fn opening(moves: &[(&str, &str)]) -> ChessGame {
+        let mut game = ChessGame::new(Board::default_board());
+        for (from, to) in moves {
+            game.do_move(ChessMove::from_coords(from, to).unwrap()).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn two_engine_match() {
+        let openings = vec![
+            opening(&[("e2", "e4"), ("e7", "e5")]),
+            opening(&[("d2", "d4"), ("d7", "d5")]),
+        ];
+        let limits = SearchLimits { max_depth: Some(1), ..SearchLimits::default() };
+        let mut tournament = Tournament::new(TournamentFormat::RoundRobin, TimeControl::Untimed)
+            .with_engine("alpha-beta", AlphaBetaBackend)
+            .with_engine("first move", FirstMove(0))
+            .with_openings(openings)
+            .with_search_limits(limits)
+            .with_resign_adjudication(ResignAdjudication { threshold: 500, moves: 3 })
+            .with_max_plies(40);
+        let results = tournament.run();
+
+        assert_eq!(results.games().len(), 4);
+        let pairings: Vec<(usize, usize)> = results.games().iter()
+            .map(|game| (game.white, game.black))
+            .collect();
+        assert_eq!(pairings, [(0, 1), (1, 0), (0, 1), (1, 0)]);
+        // the last game reaches the move limit
+        assert_eq!(results.scores(), [3.5, 0.5]);
+        assert_eq!(results.games()[3].game.termination(), Some("move limit"));
+        let standings = results.standings();
+        assert_eq!(standings[0].name, "alpha-beta");
+        assert_eq!((standings[0].wins, standings[0].draws, standings[0].losses), (3, 1, 0));
+        assert_eq!(standings[1].games(), 4);
+        assert_eq!(results.to_string(), "\
+            Rank  Name        Points  Games  Wins  Draws  Losses\n   \
+               1  alpha-beta     3.5      4     3      1       0\n   \
+               2  first move     0.5      4     0      1       3");
+
+        let pgn = results.to_pgn("Test match");
+        assert_eq!(pgn.matches("[Event \"Test match\"]").count(), 4);
+        assert!(pgn.contains("[Round \"4\"]"));
+        assert!(pgn.contains("1. d4 d5"));
+        for game in results.games() {
+            assert!(game.game.game_status().is_over());
+        }
+    }
+
+    #[test]
+    fn adjudication_and_gauntlet() {
+        // both engines consider every position equal, and the third one lost
+        let mut tournament = Tournament::new(TournamentFormat::Gauntlet, TimeControl::Untimed)
+            .with_engine("first", FirstMove(0))
+            .with_engine("second", FirstMove(10))
+            .with_engine("third", FirstMove(-1000))
+            .with_draw_adjudication(DrawAdjudication { margin: 20, moves: 5 })
+            .with_resign_adjudication(ResignAdjudication { threshold: 500, moves: 2 });
+        let results = tournament.run();
+        let pairings: Vec<(usize, usize)> = results.games().iter()
+            .map(|game| (game.white, game.black))
+            .collect();
+        assert_eq!(pairings, [(0, 1), (1, 0), (0, 2), (2, 0)]);
+
+        let drawn = &results.games()[0].game;
+        assert_eq!(*drawn.game_status(), GameStatus::Draw(DrawReason::Adjudication));
+        assert_eq!(drawn.termination(), Some("draw adjudication"));
+        // the game is drawn instead of the tenth move
+        assert_eq!(drawn.history().len(), 9);
+        let resigned = &results.games()[2].game;
+        assert_eq!(*resigned.game_status(),
+                   GameStatus::Win(PlayerColor::White, WinReason::Adjudication));
+        assert_eq!(resigned.termination(), Some("resign adjudication"));
+        // black resigns instead of making its second move
+        assert_eq!(resigned.history().len(), 3);
+        assert_eq!(results.scores(), [3.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn function_policies_on_threads() {
+        // plays the last legal move in canonical order, which is never scored
+        let last_move = FnPolicy(|game: &ChessGame| {
+            let mut moves = game.legal_moves();
+            sort_moves_canonical(&mut moves);
+            moves.last().copied()
+        });
+        let tournament = |threads| {
+            Tournament::new(TournamentFormat::RoundRobin, TimeControl::Untimed)
+                .with_engine("first", FirstMove(-1000))
+                .with_engine("last", last_move)
+                .with_engine("second", FirstMove(0))
+                .with_openings(vec![
+                    opening(&[("e2", "e4"), ("e7", "e5")]),
+                    opening(&[("d2", "d4"), ("d7", "d5")]),
+                ])
+                .with_resign_adjudication(ResignAdjudication { threshold: 500, moves: 2 })
+                .with_max_plies(8)
+                .with_threads(threads)
+                .run()
+        };
+        let results = tournament(1);
+        assert_eq!(results.games().len(), 12);
+        for game in results.games() {
+            let engines = [game.white, game.black];
+            // only the first engine resigns, and the other games reach the move limit
+            match game.game.termination() {
+                Some("resign adjudication") => assert!(engines.contains(&0)),
+                termination => assert_eq!(termination, Some("move limit")),
+            }
+        }
+        assert_eq!(results.scores().iter().sum::<f64>(), 12.0);
+        assert_eq!(results.scores()[0], 0.0);
+
+        let concurrent = tournament(5);
+        assert_eq!(concurrent.scores(), results.scores());
+        assert_eq!(concurrent.to_pgn("Test"), results.to_pgn("Test"));
+    }
+
+    #[test]
+    fn move_limit() {
+        let mut tournament = Tournament::new(TournamentFormat::RoundRobin, TimeControl::Untimed)
+            .with_engine("first", FirstMove(0))
+            .with_engine("second", FirstMove(0))
+            .with_max_plies(6);
+        let results = tournament.run();
+        for game in results.games() {
+            assert_eq!(game.game.history().len(), 6);
+            assert_eq!(game.game.termination(), Some("move limit"));
+        }
+        assert_eq!(results.scores(), [1.0, 1.0]);
+    }
+}
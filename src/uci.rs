@@ -0,0 +1,493 @@
+//! The engine side of the Universal Chess Interface (UCI) protocol, which lets chess GUIs such as
+//! Cute Chess run a [SearchBackend] as an engine. See [run_uci].
+//!
+//! The supported commands are `uci`, `isready`, `ucinewgame`, `setoption` (with the
+//! `UCI_Variant` option), `position`, `go` (with the `depth`, `nodes`, `movetime` and `infinite`
+//! limits), `stop` and `quit`. Other commands and other `go` parameters, such as time controls,
+//! are ignored. Errors in commands are reported to the GUI in `info string` lines.
+//!
+//! Besides standard chess, the engine plays [Crazyhouse](Variant::Crazyhouse) after
+//! `setoption name UCI_Variant value crazyhouse`, where drops are written like `N@f3`, both in
+//! the moves of `position` commands and in the moves the engine sends.
+//!
+//! The [client] module implements the other side of the protocol, for driving external engines.
+//!
+//! see: [Universal Chess Interface - Chessprogramming wiki](https://www.chessprogramming.org/UCI)
+
+pub mod client;
+
+use std::io::{BufRead, Write};
+use core::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{Scope, ScopedJoinHandle};
+use std::time::Duration;
+use std::{io, thread};
+use thiserror::Error;
+use crate::board::Board;
+use crate::chess::ChessGame;
+use crate::chess::crazyhouse::DropMove;
+use crate::chess::variant::Variant;
+use crate::moves::{ChessMove, PromotionType};
+use crate::search::{is_mate_score, SearchBackend, SearchLimits, SearchResult, MATE_SCORE};
+
+/// The name the engine identifies itself with in response to the `uci` command.
+pub const ENGINE_NAME: &str = concat!("leben-chess ", env!("CARGO_PKG_VERSION"));
+
+/// An error in a command received from the GUI.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum UciError {
+    /// A `position` command did not continue with `startpos` or `fen`.
+    #[error("expected `startpos` or `fen`")]
+    MissingPosition,
+    /// A `position` command contained an unexpected token where `moves` was expected.
+    #[error("unexpected token `{0}`")]
+    UnexpectedToken(String),
+    /// The FEN string of a `position` command is not a valid position.
+    #[error("invalid FEN string `{0}`")]
+    InvalidFen(String),
+    /// A move of a `position` command is not legal in the position it is played in.
+    #[error("illegal move `{0}`")]
+    IllegalMove(String),
+    /// A parameter of a `go` command or an option of a `setoption` command is missing its value
+    /// or has an invalid value.
+    #[error("invalid value for `{0}`")]
+    InvalidValue(String),
+}
+
+/// returns: The move in the coordinate notation used by UCI, such as `"e2e4"` or `"e7e8q"`, or
+///          a drop such as `"N@f3"` (see [format_drop]).
+pub fn format_move(chess_move: ChessMove) -> String {
+    chess_move.to_string()
+}
+
+/// Parses a move in the coordinate notation used by UCI, such as `"e2e4"` or `"e7e8q"`, or a drop
+/// such as `"N@f3"` (see [parse_drop]), without checking whether it is legal in any position. See
+/// [parse_coordinate_move](ChessGame::parse_coordinate_move) for parsing legal moves.
+///
+/// returns: The move, or `None` if the string is not a move in the notation.
+pub fn parse_move(string: &str) -> Option<ChessMove> {
+    if string.contains('@') {
+        return parse_drop(string).map(ChessMove::from);
+    }
+    if !string.is_ascii() || string.len() < 4 {
+        return None;
+    }
+    let (movement, promotion) = string.split_at(4);
+    let piece_movement = movement.parse().ok()?;
+    let promotion = match promotion.chars().collect::<Vec<char>>().as_slice() {
+        [] => None,
+        [ch] => Some(PromotionType::try_from(*ch).ok()?),
+        _ => return None,
+    };
+    Some(ChessMove { piece_movement, promotion, drop: None })
+}
+
+/// returns: The crazyhouse drop in the notation used by UCI engines supporting the variant,
+///          such as `"N@f3"` or `"P@e4"`.
+pub fn format_drop(drop: DropMove) -> String {
+    drop.to_string()
+}
+
+/// Parses a crazyhouse drop in the notation used by UCI engines supporting the variant, such as
+/// `"N@f3"`, without checking whether it is legal in any position. See
+/// [parse_san_drop](ChessGame::parse_san_drop) for parsing legal drops.
+///
+/// returns: The drop, or `None` if the string is not a drop in the notation.
+pub fn parse_drop(string: &str) -> Option<DropMove> {
+    string.parse().ok()
+}
+
+impl ChessGame {
+    /// Checks many candidate moves in the coordinate notation used by UCI for legality at once,
+    /// like [filter_legal](ChessGame::filter_legal). See [parse_move] for the notation.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidates`: The moves to check, such as `"e2e4"` or `"e7e8q"`.
+    ///
+    /// returns: Whether each candidate is a legal move, in the order of `candidates`. A
+    ///          candidate which is not a move in the notation is illegal.
+    pub fn filter_legal_uci(&self, candidates: &[&str]) -> Vec<bool> {
+        let moves: Vec<Option<ChessMove>> = candidates.iter()
+            .map(|candidate| parse_move(candidate))
+            .collect();
+        let parsed: Vec<ChessMove> = moves.iter().flatten().copied().collect();
+        let mut legal = self.filter_legal(&parsed).into_iter();
+        moves.iter()
+            .map(|chess_move| chess_move.is_some() && legal.next().unwrap_or(false))
+            .collect()
+    }
+}
+
+/// returns: The score of a search as written in `info` lines: `cp` followed by the score in
+///          centipawns, or `mate` followed by the number of moves until checkmate, which is
+///          negative if the engine is getting checkmated.
+fn format_score(score: i32) -> String {
+    if is_mate_score(score) {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", score)
+    }
+}
+
+/// returns: The game described by the arguments of a `position` command, played with the rules
+///          of the given variant.
+fn parse_position<'a>(mut tokens: impl Iterator<Item = &'a str>, variant: Variant)
+                      -> Result<ChessGame, UciError>
+{
+    let mut game = match tokens.next() {
+        Some("startpos") => {
+            match tokens.next() {
+                None | Some("moves") => {}
+                Some(token) => return Err(UciError::UnexpectedToken(token.to_string())),
+            }
+            ChessGame::with_variant(Board::default_board(), variant)
+        }
+        Some("fen") => {
+            // the moves follow the `moves` token consumed here
+            let fields: Vec<&str> = tokens.by_ref().take_while(|&token| token != "moves")
+                .collect();
+            ChessGame::from_fen_with_variant(&fields.join(" "), variant)
+                .map_err(|_| UciError::InvalidFen(fields.join(" ")))?
+        }
+        _ => return Err(UciError::MissingPosition),
+    };
+    for token in tokens {
+        let illegal = || UciError::IllegalMove(token.to_string());
+        let chess_move = game.parse_coordinate_move(token).map_err(|_| illegal())?;
+        game.do_move(chess_move).map_err(|_| illegal())?;
+    }
+    Ok(game)
+}
+
+/// returns: The variant set by the arguments of a `setoption` command, or `None` if the command
+///          sets another option, which is ignored.
+fn parse_setoption<'a>(tokens: impl Iterator<Item = &'a str>)
+                       -> Result<Option<Variant>, UciError>
+{
+    let tokens: Vec<&str> = tokens.collect();
+    let value_index = tokens.iter().position(|&token| token == "value").unwrap_or(tokens.len());
+    let (name, value) = tokens.split_at(value_index);
+    if name != ["name", "UCI_Variant"] {
+        return Ok(None);
+    }
+    let value = value.get(1..).unwrap_or_default().join(" ");
+    match Variant::from_name(&value) {
+        Some(variant @ (Variant::Standard | Variant::Crazyhouse)) => Ok(Some(variant)),
+        _ => Err(UciError::InvalidValue("UCI_Variant".to_string())),
+    }
+}
+
+/// returns: The value following a parameter of a `go` command.
+fn parse_value<T: FromStr>(name: &str, value: Option<&str>) -> Result<T, UciError> {
+    value.and_then(|value| value.parse().ok())
+        .ok_or_else(|| UciError::InvalidValue(name.to_string()))
+}
+
+/// returns: The search limits given by the arguments of a `go` command.
+fn parse_go<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<SearchLimits, UciError> {
+    let mut limits = SearchLimits::default();
+    while let Some(token) = tokens.next() {
+        match token {
+            "depth" => limits.max_depth = Some(parse_value(token, tokens.next())?),
+            "nodes" => limits.max_nodes = Some(parse_value(token, tokens.next())?),
+            "movetime" => {
+                limits.max_time = Some(Duration::from_millis(parse_value(token, tokens.next())?));
+            }
+            _ => {}
+        }
+    }
+    Ok(limits)
+}
+
+/// Writes the `info` lines of a search result, one for each completed iteration with the
+/// principal variation on the last line, followed by the `bestmove` line. `bestmove 0000` is
+/// written if the game is over.
+fn write_result(output: &mut impl Write, result: Option<SearchResult>) -> io::Result<()> {
+    let Some(result) = result else {
+        writeln!(output, "bestmove 0000")?;
+        return output.flush();
+    };
+    for (i, iteration) in result.iterations.iter().enumerate() {
+        write!(output, "info depth {} score {} nodes {} time {}", iteration.depth,
+               format_score(iteration.score), iteration.nodes, iteration.elapsed.as_millis())?;
+        if i + 1 == result.iterations.len() {
+            let pv: Vec<String> = result.principal_variation.iter()
+                .map(|&chess_move| format_move(chess_move))
+                .collect();
+            write!(output, " pv {}", pv.join(" "))?;
+        }
+        writeln!(output)?;
+    }
+    writeln!(output, "bestmove {}", format_move(result.best_move))?;
+    output.flush()
+}
+
+/// A search running on another thread, which gives the engine back when it is finished.
+struct RunningSearch<'scope, E> {
+    stop_flag: Arc<AtomicBool>,
+    handle: ScopedJoinHandle<'scope, (E, io::Result<()>)>,
+}
+
+/// The state of a UCI session between commands.
+struct Session<'scope, E, W> {
+    output: &'scope Mutex<W>,
+    /// The variant set with the `UCI_Variant` option.
+    variant: Variant,
+    game: ChessGame,
+    /// The engine, or `None` while it is searching.
+    engine: Option<E>,
+    search: Option<RunningSearch<'scope, E>>,
+}
+
+impl<'scope, E: SearchBackend + 'scope, W: Write + Send> Session<'scope, E, W> {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        writeln!(output, "{}", line)?;
+        output.flush()
+    }
+
+    fn report_error(&self, error: UciError) -> io::Result<()> {
+        self.write_line(&format!("info string error: {}", error))
+    }
+
+    /// Waits for the running search to finish, if any, after setting its stop flag if `stop` is
+    /// set. Returns the error of writing the search's result, if any.
+    fn finish_search(&mut self, stop: bool) -> io::Result<()> {
+        let Some(search) = self.search.take() else {
+            return Ok(());
+        };
+        if stop {
+            search.stop_flag.store(true, Ordering::Relaxed);
+        }
+        let (engine, result) = search.handle.join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        self.engine = Some(engine);
+        result
+    }
+
+    fn start_search(&mut self, scope: &'scope Scope<'scope, '_>, limits: SearchLimits) {
+        let Some(mut engine) = self.engine.take() else {
+            return;
+        };
+        let stop_flag = limits.stop_flag.clone();
+        let game = self.game.clone();
+        let output = self.output;
+        let handle = scope.spawn(move || {
+            let result = engine.search(&game, &limits);
+            let mut output = output.lock().unwrap_or_else(PoisonError::into_inner);
+            let written = write_result(&mut *output, result);
+            (engine, written)
+        });
+        self.search = Some(RunningSearch { stop_flag, handle });
+    }
+
+    /// Handles a command. Returns `Ok(false)` if the session should end.
+    fn handle_command(&mut self, scope: &'scope Scope<'scope, '_>, line: &str)
+                      -> io::Result<bool>
+    {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("uci") => {
+                self.write_line(&format!("id name {}", ENGINE_NAME))?;
+                self.write_line("id author the leben-chess authors")?;
+                self.write_line("option name UCI_Variant type combo default chess \
+                                 var chess var crazyhouse")?;
+                self.write_line("uciok")?;
+            }
+            Some("isready") => self.write_line("readyok")?,
+            Some("ucinewgame") => {
+                self.finish_search(true)?;
+                self.game = ChessGame::with_variant(Board::default_board(), self.variant);
+                if let Some(engine) = &mut self.engine {
+                    engine.new_game();
+                }
+            }
+            Some("setoption") => {
+                self.finish_search(true)?;
+                match parse_setoption(tokens) {
+                    Ok(Some(variant)) => {
+                        self.variant = variant;
+                        self.game = ChessGame::with_variant(Board::default_board(), variant);
+                    }
+                    Ok(None) => {}
+                    Err(error) => self.report_error(error)?,
+                }
+            }
+            Some("position") => {
+                self.finish_search(true)?;
+                match parse_position(tokens, self.variant) {
+                    Ok(game) => self.game = game,
+                    Err(error) => self.report_error(error)?,
+                }
+            }
+            Some("go") => {
+                self.finish_search(true)?;
+                match parse_go(tokens) {
+                    Ok(limits) => self.start_search(scope, limits),
+                    Err(error) => self.report_error(error)?,
+                }
+            }
+            Some("stop") => self.finish_search(true)?,
+            Some("quit") => {
+                self.finish_search(true)?;
+                return Ok(false);
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+}
+
+/// Runs the engine side of a UCI session, reading commands from `input` and writing responses to
+/// `output` until the `quit` command is received or the input ends. See
+/// [the module documentation](self) for the supported commands.
+///
+/// Searches run on another thread, so that commands such as `isready` and `stop` are handled
+/// while the engine is searching. When the input ends during a search, the search is completed
+/// before returning. A `position` command which is malformed or contains an illegal move is
+/// reported in an `info string` line, and the previous position is kept.
+///
+/// # Arguments
+///
+/// * `input`: The commands from the GUI, usually standard input.
+/// * `output`: The responses to the GUI, usually standard output.
+/// * `engine`: The search used to respond to `go` commands.
+///
+/// returns: `Ok(())` when the session ends, or the first error reading from `input` or writing to
+///          `output`.
+pub fn run_uci(input: impl BufRead, output: impl Write + Send, engine: impl SearchBackend)
+               -> io::Result<()>
+{
+    let output = Mutex::new(output);
+    thread::scope(|scope| {
+        let mut session = Session {
+            output: &output,
+            variant: Variant::Standard,
+            game: ChessGame::new(Board::default_board()),
+            engine: Some(engine),
+            search: None,
+        };
+        for line in input.lines() {
+            if !session.handle_command(scope, &line?)? {
+                return Ok(());
+            }
+        }
+        session.finish_search(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::AlphaBetaBackend;
+
+    #[test]
+    fn filter_legal_uci() {
+        let game = ChessGame::from_fen("rn1rk3/1P6/8/8/8/8/4P3/4K1N1 w - - 0 1").unwrap();
+        let candidates = ["b7a8q", "b7a8n", "b7a8", "b7b8q", "e1d2", "e1f2", "g1f3", "e2e4 ",
+                          "", "xyz", "e9e4", "b7a8k", "b7a8qq"];
+        assert_eq!(game.filter_legal_uci(&candidates),
+                   [true, true, false, false, false, true, true, false, false, false, false,
+                    false, false]);
+    }
+
+    fn run_session(input: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run_uci(input.as_bytes(), &mut output, AlphaBetaBackend).unwrap();
+        String::from_utf8(output).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn scripted_session() {
+        let lines = run_session("uci\nisready\nucinewgame\n\
+            position startpos moves e2e4 e7e5 d1h5 b8c6 f1c4 g8f6\ngo depth 2\nquit\n");
+        assert_eq!(lines[..5], [format!("id name {}", ENGINE_NAME),
+            "id author the leben-chess authors".to_string(),
+            "option name UCI_Variant type combo default chess var chess var crazyhouse"
+                .to_string(),
+            "uciok".to_string(), "readyok".to_string()]);
+        assert!(lines[5].starts_with("info depth 1 score mate 1 nodes "));
+        assert!(lines[5].ends_with(" pv h5f7"));
+        assert_eq!(lines[6], "bestmove h5f7");
+        assert_eq!(lines.len(), 7);
+
+        let lines = run_session("position fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1\n\
+            go depth 3\n");
+        assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+        let lines = run_session("position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1\ngo depth 1\n");
+        assert_eq!(lines.last().unwrap(), "bestmove a7a8q");
+        let lines = run_session("go nodes 100 movetime 1000\n");
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+        let lines = run_session("position startpos\ngo infinite\nstop\nisready\n");
+        assert!(lines.last().unwrap() == "readyok");
+        assert!(lines.iter().any(|line| line.starts_with("bestmove ")));
+    }
+
+    #[test]
+    fn malformed_position() {
+        let lines = run_session("position fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1\n\
+            position fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 x - - 0 1\n\
+            position fen 6k1/5ppp\n\
+            position startpos moves e2e5\n\
+            position startpos e2e4\n\
+            position\n\
+            go depth x\n\
+            go depth 2\n");
+        assert_eq!(lines[..6], [
+            "info string error: invalid FEN string `6k1/5ppp/8/8/8/8/5PPP/R5K1 x - - 0 1`",
+            "info string error: invalid FEN string `6k1/5ppp`",
+            "info string error: illegal move `e2e5`",
+            "info string error: unexpected token `e2e4`",
+            "info string error: expected `startpos` or `fen`",
+            "info string error: invalid value for `depth`",
+        ].map(str::to_string));
+        // the valid position is kept
+        assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+    }
+
+    #[test]
+    fn move_notation() {
+        for string in ["e2e4", "a7a8q", "h2h1n", "N@f3", "P@e4"] {
+            assert_eq!(format_move(parse_move(string).unwrap()), string);
+        }
+        assert_eq!(parse_move("N@f3"), parse_drop("N@f3").map(ChessMove::from));
+        for string in ["e2e", "e2e9", "e7e8k", "e7e8qq", "e2-e4"] {
+            assert_eq!(parse_move(string), None);
+        }
+    }
+
+    #[test]
+    fn crazyhouse_session() {
+        // white takes the queen and drops it to checkmate
+        let lines = run_session("setoption name UCI_Variant value crazyhouse
+            position fen 6k1/5p1p/7P/8/8/8/8/qR2K3 w - - 0 1 moves b1a1 f7f6
+go depth 1
+");
+        assert!(lines[0].starts_with("info depth 1 score mate 1 "));
+        assert!(lines[1].starts_with("bestmove Q@"), "{}", lines[1]);
+
+        let lines = run_session("setoption name UCI_Variant value crazyhouse
+            position startpos moves e2e4 d7d5 e4d5 d8d5 P@e6 d5e6 N@f3
+            position startpos moves e2e4 d7d5 e4d5 d8d5 P@e1
+            setoption name UCI_Variant value atomic
+            setoption name Hash value 16
+            setoption name UCI_Variant value chess
+            position startpos moves e2e4 d7d5 e4d5 d8d5 P@e6
+");
+        assert_eq!(lines, [
+            "info string error: illegal move `N@f3`",
+            "info string error: illegal move `P@e1`",
+            "info string error: invalid value for `UCI_Variant`",
+            "info string error: illegal move `P@e6`",
+        ]);
+    }
+
+    #[test]
+    fn game_over_position() {
+        let lines = run_session("position startpos moves f2f3 e7e5 g2g4 d8h4\ngo depth 1\n");
+        assert_eq!(lines, ["bestmove 0000"]);
+    }
+}
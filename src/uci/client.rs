@@ -0,0 +1,427 @@
+//! A client for driving external engines, such as Stockfish, over the UCI protocol. See
+//! [UciEngine].
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::{io, thread};
+use thiserror::Error;
+use crate::board::Board;
+use crate::chess::ChessGame;
+use crate::moves::ChessMove;
+use crate::search::SearchLimits;
+use crate::uci::{format_move, parse_move};
+
+/// The default time to wait for a response from the engine, see [UciEngine::set_timeout].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An error while communicating with an engine.
+#[derive(Error, Debug)]
+pub enum UciClientError {
+    /// Starting the engine or writing a command to it failed.
+    #[error("engine I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// The engine did not respond in time.
+    #[error("timed out waiting for `{0}`")]
+    Timeout(&'static str),
+    /// The engine exited or closed its output.
+    #[error("engine exited")]
+    EngineExited,
+    /// The engine sent a `bestmove` which is not a legal move in the position searched.
+    #[error("invalid best move `{0}`")]
+    InvalidBestMove(String),
+}
+
+/// The score of a position reported by an engine, from the perspective of the player to move.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EngineScore {
+    /// A score in centipawns.
+    Centipawns(i32),
+    /// Checkmate in the given number of moves, which is negative if the player to move is getting
+    /// checkmated.
+    Mate(i32),
+}
+
+/// The search progress reported by an engine in an `info` line. Tokens the client does not know
+/// are skipped, so every field is `None` or empty if the line does not report it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AnalysisInfo {
+    /// The depth in plies of the search.
+    pub depth: Option<u32>,
+    /// The score of the best move found.
+    pub score: Option<EngineScore>,
+    /// The number of nodes searched.
+    pub nodes: Option<u64>,
+    /// The line of best play found, starting with the best move.
+    pub pv: Vec<ChessMove>,
+}
+
+impl AnalysisInfo {
+    /// Parses an `info` line sent by an engine, such as
+    /// `"info depth 12 score cp 31 nodes 52844 pv e2e4 e7e5"`. Tokens which are not known are
+    /// skipped, and `string` comments are ignored.
+    ///
+    /// returns: The parsed information, or `None` if the line is not an `info` line.
+    pub fn parse(line: &str) -> Option<AnalysisInfo> {
+        let mut tokens = line.split_whitespace().peekable();
+        if tokens.next() != Some("info") {
+            return None;
+        }
+        let mut info = AnalysisInfo::default();
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => info.depth = tokens.next().and_then(|value| value.parse().ok()),
+                "nodes" => info.nodes = tokens.next().and_then(|value| value.parse().ok()),
+                "score" => {
+                    let kind = tokens.next();
+                    let value = tokens.next().and_then(|value| value.parse().ok());
+                    info.score = match (kind, value) {
+                        (Some("cp"), Some(value)) => Some(EngineScore::Centipawns(value)),
+                        (Some("mate"), Some(value)) => Some(EngineScore::Mate(value)),
+                        _ => None,
+                    };
+                }
+                "pv" => {
+                    // the variation continues until the first token which is not a move
+                    while let Some(chess_move) = tokens.peek().and_then(|&token| parse_move(token))
+                    {
+                        info.pv.push(chess_move);
+                        tokens.next();
+                    }
+                }
+                "string" => break,
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+}
+
+/// The result of a search by an engine, see [UciEngine::go].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EngineAnalysis {
+    /// The best move found.
+    pub best_move: ChessMove,
+    /// The `info` lines sent during the search which reported a depth, in the order they were
+    /// sent. The last entry usually holds the final score and line of best play.
+    pub info: Vec<AnalysisInfo>,
+}
+
+/// returns: The `position` command for the current position of a game: `startpos` if the game's
+///          history starts from the standard starting position, otherwise the FEN string of the
+///          [starting position](ChessGame::starting_position), followed by the moves of the
+///          history.
+pub fn position_command(game: &ChessGame) -> String {
+    let start_fen = game.starting_position().fen();
+    let mut command = if start_fen == ChessGame::new(Board::default_board()).fen() {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {}", start_fen)
+    };
+    if !game.history().is_empty() {
+        command += " moves";
+        for details in game.history() {
+            command.push(' ');
+            command += &format_move(details.chess_move);
+        }
+    }
+    command
+}
+
+/// A running external engine, driven over the UCI protocol through its standard input and
+/// output. The engine is shut down with [quit](UciEngine::quit), or killed when the [UciEngine]
+/// is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::process::Command;
+/// use leben_chess::board::Board;
+/// use leben_chess::chess::ChessGame;
+/// use leben_chess::search::SearchLimits;
+/// use leben_chess::uci::client::UciEngine;
+///
+/// let mut engine = UciEngine::spawn(Command::new("stockfish")).unwrap();
+/// let game = ChessGame::new(Board::default_board());
+/// let limits = SearchLimits { max_depth: Some(20), ..SearchLimits::default() };
+/// let analysis = engine.analyze(&game, &limits).unwrap();
+/// println!("best move: {:?}", analysis.best_move);
+/// engine.quit().unwrap();
+/// ```
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    name: Option<String>,
+    timeout: Duration,
+}
+
+impl UciEngine {
+    /// Starts an engine and performs the UCI handshake, waiting for `uciok` and `readyok`. The
+    /// standard input and output of the command are replaced by pipes to the engine.
+    ///
+    /// returns: The running engine, or an error if it could not be started or did not complete
+    ///          the handshake within the [DEFAULT_TIMEOUT].
+    pub fn spawn(mut command: Command) -> Result<UciEngine, UciClientError> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(UciClientError::EngineExited);
+        };
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut engine = UciEngine { child, stdin, lines, name: None, timeout: DEFAULT_TIMEOUT };
+        engine.send("uci")?;
+        let deadline = Instant::now() + engine.timeout;
+        loop {
+            let line = engine.receive(deadline, "uciok")?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(name.trim().to_string());
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.wait_ready()?;
+        Ok(engine)
+    }
+
+    /// returns: The name the engine identified itself with, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Sets how long to wait for responses from the engine. For searches with a time limit, the
+    /// limit is added to the timeout.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), UciClientError> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    fn receive(&self, deadline: Instant, expected: &'static str)
+               -> Result<String, UciClientError>
+    {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|error| match error {
+            RecvTimeoutError::Timeout => UciClientError::Timeout(expected),
+            RecvTimeoutError::Disconnected => UciClientError::EngineExited,
+        })
+    }
+
+    /// Sends `isready` and waits for `readyok`.
+    fn wait_ready(&mut self) -> Result<(), UciClientError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.receive(deadline, "readyok")?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    /// Tells the engine that the next searches belong to a new game, and waits until it is
+    /// ready.
+    pub fn new_game(&mut self) -> Result<(), UciClientError> {
+        self.send("ucinewgame")?;
+        self.wait_ready()
+    }
+
+    /// Sends the current position of a game to the engine, see [position_command].
+    pub fn set_position(&mut self, game: &ChessGame) -> Result<(), UciClientError> {
+        self.send(&position_command(game))
+    }
+
+    /// Starts a search in the position last [set](UciEngine::set_position) and waits for the
+    /// best move. The depth, node and time limits are sent as `depth`, `nodes` and `movetime`;
+    /// a search without limits is sent as `go infinite`, and is stopped when the stop flag of the
+    /// limits is set.
+    ///
+    /// Lines the engine sent before the search, such as the result of an earlier search which
+    /// timed out, are discarded.
+    ///
+    /// returns: The [EngineAnalysis] of the search, or
+    ///          [Timeout](UciClientError::Timeout) if the engine did not send a best move in
+    ///          time, in which case the engine is told to stop.
+    pub fn go(&mut self, limits: &SearchLimits) -> Result<EngineAnalysis, UciClientError> {
+        while self.lines.try_recv().is_ok() {}
+        let mut command = "go".to_string();
+        if let Some(depth) = limits.max_depth {
+            command += &format!(" depth {}", depth);
+        }
+        if let Some(nodes) = limits.max_nodes {
+            command += &format!(" nodes {}", nodes);
+        }
+        if let Some(time) = limits.max_time {
+            command += &format!(" movetime {}", time.as_millis());
+        }
+        let infinite = command == "go";
+        if infinite {
+            command += " infinite";
+        }
+        self.send(&command)?;
+
+        let start = Instant::now();
+        let timeout = self.timeout + limits.max_time.unwrap_or_default();
+        let mut stop_sent = false;
+        let mut info = Vec::new();
+        loop {
+            if infinite && !stop_sent && limits.stop_flag.load(Ordering::Relaxed) {
+                self.send("stop")?;
+                stop_sent = true;
+            }
+            let deadline = if infinite && !stop_sent {
+                // check the stop flag regularly
+                Instant::now() + Duration::from_millis(10)
+            } else {
+                start + timeout
+            };
+            let line = match self.receive(deadline, "bestmove") {
+                Ok(line) => line,
+                Err(UciClientError::Timeout(_)) if infinite && !stop_sent => continue,
+                Err(UciClientError::Timeout(expected)) => {
+                    let _ = self.send("stop");
+                    return Err(UciClientError::Timeout(expected));
+                }
+                Err(error) => return Err(error),
+            };
+            if let Some(best_move) = line.strip_prefix("bestmove") {
+                let best_move = best_move.split_whitespace().next().unwrap_or_default();
+                let best_move = parse_move(best_move)
+                    .ok_or_else(|| UciClientError::InvalidBestMove(best_move.to_string()))?;
+                return Ok(EngineAnalysis { best_move, info });
+            }
+            if let Some(line_info) = AnalysisInfo::parse(&line)
+                && line_info.depth.is_some()
+            {
+                info.push(line_info);
+            }
+        }
+    }
+
+    /// Sends the current position of a game and searches it, see [go](UciEngine::go).
+    ///
+    /// returns: The [EngineAnalysis] of the search, or
+    ///          [InvalidBestMove](UciClientError::InvalidBestMove) if the best move is not legal
+    ///          in the game.
+    pub fn analyze(&mut self, game: &ChessGame, limits: &SearchLimits)
+                   -> Result<EngineAnalysis, UciClientError>
+    {
+        self.set_position(game)?;
+        let analysis = self.go(limits)?;
+        let best_move = format_move(analysis.best_move);
+        match game.parse_coordinate_move(&best_move) {
+            Ok(_) => Ok(analysis),
+            Err(_) => Err(UciClientError::InvalidBestMove(best_move)),
+        }
+    }
+
+    /// Shuts the engine down by sending `quit`, killing it if it does not exit within the
+    /// timeout.
+    pub fn quit(mut self) -> Result<(), UciClientError> {
+        self.send("quit")?;
+        let deadline = Instant::now() + self.timeout;
+        while Instant::now() < deadline {
+            if self.child.try_wait()?.is_some() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.child.kill()?;
+        self.child.wait()?;
+        Ok(())
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::piece::PlayerColor;
+    use crate::moves::{CastlingRights, PromotionType};
+
+    fn fake_engine() -> UciEngine {
+        let mut command = Command::new("sh");
+        command.arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fake_engine.sh"));
+        UciEngine::spawn(command).unwrap()
+    }
+
+    #[test]
+    fn parse_info() {
+        let info = AnalysisInfo::parse("info depth 2 seldepth 4 multipv 1 score mate -3 \
+            lowerbound wdl 0 0 1000 nodes 40 foo pv e2e4 e7e5 hashfull 3").unwrap();
+        assert_eq!(info, AnalysisInfo {
+            depth: Some(2),
+            score: Some(EngineScore::Mate(-3)),
+            nodes: Some(40),
+            pv: vec![ChessMove::from_coords("e2", "e4").unwrap(),
+                     ChessMove::from_coords("e7", "e5").unwrap()],
+        });
+        assert_eq!(AnalysisInfo::parse("info string depth 3"), Some(AnalysisInfo::default()));
+        assert_eq!(AnalysisInfo::parse("info depth x score cp"), Some(AnalysisInfo::default()));
+        assert_eq!(AnalysisInfo::parse("bestmove e2e4"), None);
+    }
+
+    #[test]
+    fn position_commands() {
+        let mut game = ChessGame::new(Board::default_board());
+        assert_eq!(position_command(&game), "position startpos");
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        game.do_move(ChessMove::from_coords("e7", "e5").unwrap()).unwrap();
+        assert_eq!(position_command(&game), "position startpos moves e2e4 e7e5");
+        let board = Board::from_fen_string("4k3/8/8/8/8/8/4P3/4K3").unwrap();
+        let no_rights = (CastlingRights::NONE, CastlingRights::NONE);
+        game.set_position(board, PlayerColor::White, no_rights, None).unwrap();
+        game.do_move(ChessMove::from_coords("e2", "e4").unwrap()).unwrap();
+        assert_eq!(position_command(&game),
+                   "position fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 moves e2e4");
+    }
+
+    #[test]
+    fn fake_engine_session() {
+        let mut engine = fake_engine();
+        assert_eq!(engine.name(), Some("Fake Engine 1.0"));
+        engine.new_game().unwrap();
+
+        let game = ChessGame::new(Board::default_board());
+        let limits = SearchLimits { max_depth: Some(1), ..SearchLimits::default() };
+        let analysis = engine.analyze(&game, &limits).unwrap();
+        assert_eq!(analysis.best_move, ChessMove::from_coords("e2", "e4").unwrap());
+        assert_eq!(analysis.info.len(), 1);
+        assert_eq!(analysis.info[0].score, Some(EngineScore::Centipawns(25)));
+
+        // the canned best move is not legal in the starting position
+        let limits = SearchLimits { max_depth: Some(2), ..SearchLimits::default() };
+        assert!(matches!(engine.analyze(&game, &limits),
+                         Err(UciClientError::InvalidBestMove(best_move)) if best_move == "a7a8q"));
+        let analysis = engine.go(&limits).unwrap();
+        let a7 = "a7".parse().unwrap();
+        let a8 = "a8".parse().unwrap();
+        assert_eq!(analysis.best_move, ChessMove::promoting(a7, a8, PromotionType::Queen));
+        assert_eq!(analysis.info.len(), 2);
+        assert_eq!(analysis.info[1].score, Some(EngineScore::Mate(-3)));
+        assert_eq!(analysis.info[1].pv.len(), 2);
+
+        engine.set_timeout(Duration::from_millis(100));
+        let limits = SearchLimits { max_nodes: Some(1000), ..SearchLimits::default() };
+        assert!(matches!(engine.go(&limits), Err(UciClientError::Timeout("bestmove"))));
+        engine.quit().unwrap();
+    }
+}
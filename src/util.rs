@@ -1,10 +1,11 @@
 //! Utility integer types used in various other parts of the library.
 
+use core::fmt::{Display, Formatter};
 use crate::board::board_pos::BoardPosition;
 
 /// Contains a `u8` value with the invariant of always being in the `0b0000_0000` to `0b0000_0111`
 /// range (inclusive).
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Default)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
 pub struct U3 { value: u8 }
 
 impl U3 {
@@ -18,21 +19,59 @@ impl U3 {
         }
     }
 
+    /// returns: The [U3] of the three lowest bits of `value`, which is `value` itself if it is in
+    /// the range `0b0000_0000` to `0b0000_0111` (inclusive).
+    pub const fn from_low_bits(value: u8) -> U3 {
+        U3 { value: value & 0b00000111 }
+    }
+
     /// returns: The underlying `u8` value.
     pub const fn get(self) -> u8 {
         self.value
     }
+
+    /// returns: An iterator over all values in ascending order, from 0 to 7 (inclusive).
+    pub fn all() -> impl DoubleEndedIterator<Item=U3> + ExactSizeIterator {
+        (0..8).map(|value| U3 { value })
+    }
+
+    /// returns: `Some(U3)` containing the sum of the value and `offset` if the sum is in the range
+    /// `0b0000_0000` to `0b0000_0111` (inclusive), otherwise `None`.
+    pub const fn checked_add_signed(self, offset: i8) -> Option<U3> {
+        match self.value.checked_add_signed(offset) {
+            Some(value) => U3::new(value),
+            None => None,
+        }
+    }
+}
+
+impl Display for U3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.value)
+    }
 }
 
-impl Into<u8> for U3 {
-    fn into(self) -> u8 {
-        self.get()
+impl From<U3> for i8 {
+    fn from(value: U3) -> Self {
+        value.get() as i8
     }
 }
 
-impl Into<usize> for U3 {
-    fn into(self) -> usize {
-        self.get() as usize
+impl From<U3> for u32 {
+    fn from(value: U3) -> Self {
+        value.get() as u32
+    }
+}
+
+impl From<U3> for u8 {
+    fn from(value: U3) -> Self {
+        value.get()
+    }
+}
+
+impl From<U3> for usize {
+    fn from(value: U3) -> Self {
+        value.get() as usize
     }
 }
 
@@ -45,7 +84,7 @@ impl TryFrom<u8> for U3 {
 
 /// Contains a `u8` value with the invariant of always being in the `0b0000_0000` to `0b0011_1111`
 /// range (inclusive).
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Default)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
 pub struct U6 { value: u8 }
 
 impl U6 {
@@ -63,17 +102,44 @@ impl U6 {
     pub const fn get(self) -> u8 {
         self.value
     }
+
+    /// returns: A `U6` with `high` as its three most significant bits and `low` as its three least
+    /// significant bits. For square indices, these are the file and the rank, respectively.
+    pub const fn from_parts(high: U3, low: U3) -> U6 {
+        U6 { value: (high.get() << 3) | low.get() }
+    }
+
+    /// returns: The three most significant bits, which is the file for a square index.
+    pub const fn file(self) -> U3 {
+        U3 { value: (self.value >> 3) & 0b0000_0111 }
+    }
+
+    /// returns: The three least significant bits, which is the rank for a square index.
+    pub const fn rank(self) -> U3 {
+        U3 { value: self.value & 0b0000_0111 }
+    }
+
+    /// returns: An iterator over all values in ascending order, from 0 to 63 (inclusive).
+    pub fn all() -> impl DoubleEndedIterator<Item=U6> + ExactSizeIterator {
+        (0..64).map(|value| U6 { value })
+    }
+}
+
+impl Display for U6 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.value)
+    }
 }
 
-impl Into<u8> for U6 {
-    fn into(self) -> u8 {
-        self.get()
+impl From<U6> for u8 {
+    fn from(value: U6) -> Self {
+        value.get()
     }
 }
 
-impl Into<usize> for U6 {
-    fn into(self) -> usize {
-        self.get() as usize
+impl From<U6> for usize {
+    fn from(value: U6) -> Self {
+        value.get() as usize
     }
 }
 
@@ -84,18 +150,15 @@ impl TryFrom<u8> for U6 {
     }
 }
 
-impl Into<BoardPosition> for U6 {
-    fn into(self) -> BoardPosition {
-        let x: U3 = ((self.value >> 3) & 0b0000_0111).try_into().unwrap();
-        let y: U3 = (self.value & 0b0000_0111).try_into().unwrap();
-        BoardPosition { file: x, rank: y }
+impl From<U6> for BoardPosition {
+    fn from(value: U6) -> Self {
+        BoardPosition { file: value.file(), rank: value.rank() }
     }
 }
 
 impl From<BoardPosition> for U6 {
     fn from(board_pos: BoardPosition) -> Self {
-        let (x, y): (u8, u8) = board_pos.into();
-        U6 { value: (x << 3) | y }
+        U6::from_parts(board_pos.file, board_pos.rank)
     }
 }
 
@@ -104,6 +167,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn u3_test() {
         for i in 0x00..0xff {
             assert_eq!(matches!(U3::new(i), None), i > 7);
@@ -111,9 +175,53 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn u6_test() {
         for i in 0x00..0xff {
             assert_eq!(matches!(U6::new(i), None), i > 63);
         }
     }
+
+    #[test]
+    fn u3_arithmetic() {
+        let zero = U3::new(0).unwrap();
+        let seven = U3::new(7).unwrap();
+        assert_eq!(zero.checked_add_signed(0), Some(zero));
+        assert_eq!(zero.checked_add_signed(7), Some(seven));
+        assert_eq!(zero.checked_add_signed(8), None);
+        assert_eq!(zero.checked_add_signed(-1), None);
+        assert_eq!(zero.checked_add_signed(i8::MIN), None);
+        assert_eq!(zero.checked_add_signed(i8::MAX), None);
+        assert_eq!(seven.checked_add_signed(0), Some(seven));
+        assert_eq!(seven.checked_add_signed(-7), Some(zero));
+        assert_eq!(seven.checked_add_signed(1), None);
+        assert_eq!(seven.checked_add_signed(-8), None);
+        assert_eq!(seven.checked_add_signed(i8::MAX), None);
+        assert_eq!(seven.checked_add_signed(i8::MIN), None);
+        for a in U3::all() {
+            for offset in -10..10 {
+                let expected = a.get() as i8 + offset;
+                let expected = (0..8).contains(&expected).then(|| U3::new(expected as u8).unwrap());
+                assert_eq!(a.checked_add_signed(offset), expected);
+            }
+        }
+
+        let values: Vec<u8> = U3::all().map(U3::get).collect();
+        assert_eq!(values, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(U3::all().next_back(), Some(seven));
+        assert_eq!(seven.to_string(), "7");
+        assert_eq!(i8::from(seven), 7);
+        assert_eq!(u32::from(seven), 7);
+    }
+
+    #[test]
+    fn u6_parts() {
+        for value in U6::all() {
+            assert_eq!(U6::from_parts(value.file(), value.rank()), value);
+            assert_eq!(value.file().get(), value.get() / 8);
+            assert_eq!(value.rank().get(), value.get() % 8);
+        }
+        assert_eq!(U6::all().count(), 64);
+        assert_eq!(U6::new(42).unwrap().to_string(), "42");
+    }
 }
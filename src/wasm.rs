@@ -0,0 +1,274 @@
+//! WebAssembly bindings (enable the `wasm` feature), which expose a [ChessGame] to JavaScript
+//! through [JsChessGame], so that the same rules engine can run in the browser as on a server.
+//!
+//! Only strings, numbers and plain objects cross the boundary. Invalid input, such as an invalid
+//! FEN string or an illegal move, is reported by throwing a JavaScript `Error` rather than by
+//! panicking.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use crate::board::Board;
+use crate::board::board_pos::BoardPosition;
+use crate::chess::{ChessError, ChessGame, MoveDetails};
+use crate::moves::ChessMove;
+use crate::uci::format_move;
+
+/// A legal destination square of a piece, as returned by [JsChessGame::legal_moves].
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct JsMoveTarget {
+    /// The name of the square, such as `"e4"`.
+    to: String,
+    /// Whether the move is a promotion, which must be played with a promotion letter.
+    is_promotion: bool,
+}
+
+/// A performed move, as returned by [JsChessGame::play_move].
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct JsMoveDetails {
+    /// The move in the coordinate notation used by UCI, such as `"e7e8q"`.
+    uci: String,
+    /// The move in standard algebraic notation, such as `"e8=Q+"`.
+    san: String,
+    /// The FEN letter of the piece which was moved, such as `"P"` or `"n"`.
+    piece: String,
+    /// The FEN letter of the piece which was captured, if any.
+    captured_piece: Option<String>,
+    /// The origin and destination squares of the rook in a castling move, such as `"h1f1"`.
+    secondary_movement: Option<String>,
+    /// The square the captured piece was removed from, which differs from the destination
+    /// square for captures en passant.
+    removed_from: Option<String>,
+}
+
+impl JsMoveDetails {
+    fn new(details: &MoveDetails, san: String) -> JsMoveDetails {
+        JsMoveDetails {
+            uci: format_move(details.chess_move),
+            san,
+            piece: details.piece.to_char().to_string(),
+            captured_piece: details.captured_piece.map(|piece| piece.to_char().to_string()),
+            secondary_movement: details.secondary_movement.map(|movement| movement.to_string()),
+            removed_from: details.removed_piece.map(|(pos, _)| pos.to_string()),
+        }
+    }
+}
+
+fn to_js_value(value: &impl Serialize) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// A chess game for use from JavaScript, wrapping a [ChessGame].
+#[wasm_bindgen]
+pub struct JsChessGame {
+    game: ChessGame,
+}
+
+impl Default for JsChessGame {
+    fn default() -> Self {
+        JsChessGame::new()
+    }
+}
+
+#[wasm_bindgen]
+impl JsChessGame {
+    /// returns: A new game from the standard starting position.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsChessGame {
+        JsChessGame { game: ChessGame::new(Board::default_board()) }
+    }
+
+    /// Restarts the game from the standard starting position.
+    #[wasm_bindgen(js_name = newGame)]
+    pub fn new_game(&mut self) {
+        self.game = ChessGame::new(Board::default_board());
+    }
+
+    /// Restarts the game from the position given by a FEN string, see [ChessGame::from_fen].
+    /// Throws an error if the string is not a valid position, in which case the game is left
+    /// unchanged.
+    #[wasm_bindgen(js_name = loadFen)]
+    pub fn load_fen(&mut self, fen: &str) -> Result<(), JsError> {
+        self.game = ChessGame::from_fen(fen)?;
+        Ok(())
+    }
+
+    /// Returns the legal destination squares of the piece on a square, as an array of objects
+    /// with the square name `to` and the flag `isPromotion`. The array is empty if there is no
+    /// piece of the player to move on the square. Throws an error if the square name is invalid.
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self, square: &str) -> Result<JsValue, JsError> {
+        to_js_value(&self.move_targets(square.parse()?))
+    }
+
+    /// Plays a move given in the coordinate notation used by UCI, such as `"e2e4"` or
+    /// `"e7e8q"`, and returns an object describing the move: its notations `uci` and `san`, the
+    /// FEN letters of the moved `piece` and of the `capturedPiece`, the `secondaryMovement` of
+    /// the rook when castling, and the square the captured piece was `removedFrom`. Throws an
+    /// error if the move is not legal or the game has ended.
+    #[wasm_bindgen(js_name = playMove)]
+    pub fn play_move(&mut self, uci: &str) -> Result<JsValue, JsError> {
+        let chess_move = self.game.parse_coordinate_move(uci)?;
+        to_js_value(&self.perform_move(chess_move)?)
+    }
+
+    /// Returns the FEN string of the current position, see [ChessGame::fen].
+    pub fn fen(&self) -> String {
+        self.game.fen()
+    }
+
+    /// Returns the status of the game as text, such as `"Normal play"` or
+    /// `"White won by checkmate"`.
+    pub fn status(&self) -> String {
+        self.game.game_status().to_string()
+    }
+
+    /// Takes back the last move, if any move has been made since the starting position.
+    ///
+    /// returns: Whether a move was taken back.
+    pub fn undo(&mut self) -> bool {
+        self.game.undo_move().is_some()
+    }
+}
+
+impl JsChessGame {
+    /// returns: The legal destination squares of the piece on a square, see
+    ///          [legal_moves](JsChessGame::legal_moves).
+    fn move_targets(&self, from: BoardPosition) -> Vec<JsMoveTarget> {
+        self.game.move_targets(from).into_iter()
+            .map(|target| JsMoveTarget {
+                to: target.to.to_string(),
+                is_promotion: target.is_promotion,
+            })
+            .collect()
+    }
+
+    /// Performs a move, see [play_move](JsChessGame::play_move).
+    ///
+    /// returns: The description of the move, or the [ChessError] if it could not be performed.
+    fn perform_move(&mut self, chess_move: ChessMove) -> Result<JsMoveDetails, ChessError> {
+        let san = self.game.move_to_san(chess_move).unwrap_or_default();
+        let details = self.game.do_move(chess_move)?;
+        Ok(JsMoveDetails::new(&details, san))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod host_tests {
+    use super::*;
+
+    fn targets(game: &JsChessGame, square: &str) -> Vec<JsMoveTarget> {
+        game.move_targets(square.parse().unwrap())
+    }
+
+    fn play(game: &mut JsChessGame, uci: &str) -> Result<JsMoveDetails, ChessError> {
+        let chess_move = game.game.parse_coordinate_move(uci).unwrap();
+        game.perform_move(chess_move)
+    }
+
+    #[test]
+    fn move_targets() {
+        let mut game = JsChessGame::new();
+        assert_eq!(targets(&game, "g1"), [
+            JsMoveTarget { to: "f3".to_string(), is_promotion: false },
+            JsMoveTarget { to: "h3".to_string(), is_promotion: false },
+        ]);
+        assert!(targets(&game, "e7").is_empty());
+        assert!(targets(&game, "e4").is_empty());
+
+        game.load_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(targets(&game, "a7"),
+                   [JsMoveTarget { to: "a8".to_string(), is_promotion: true }]);
+    }
+
+    #[test]
+    fn move_details() {
+        let mut game = JsChessGame::new();
+        game.load_fen("r3k3/8/8/3pP3/8/8/8/4K2R w K d6 0 1").unwrap();
+        assert_eq!(play(&mut game, "e5d6"), Ok(JsMoveDetails {
+            uci: "e5d6".to_string(),
+            san: "exd6".to_string(),
+            piece: "P".to_string(),
+            captured_piece: Some("p".to_string()),
+            secondary_movement: None,
+            removed_from: Some("d5".to_string()),
+        }));
+        play(&mut game, "a8a7").unwrap();
+        let castling = play(&mut game, "e1g1").unwrap();
+        assert_eq!(castling.san, "O-O");
+        assert_eq!(castling.secondary_movement, Some("h1f1".to_string()));
+        // a legal move for the wrong player
+        let chess_move = ChessMove::new("f1".parse().unwrap(), "f2".parse().unwrap());
+        assert!(game.perform_move(chess_move).is_err());
+    }
+
+    #[test]
+    fn undo() {
+        let mut game = JsChessGame::new();
+        assert!(!game.undo());
+        for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            play(&mut game, uci).unwrap();
+        }
+        assert_eq!(game.status(), "Black won by checkmate");
+        assert!(game.undo());
+        assert_eq!(game.status(), "Normal play");
+        assert_eq!(game.fen(), "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2");
+        play(&mut game, "d8h4").unwrap();
+        assert_eq!(game.status(), "Black won by checkmate");
+
+        game.load_fen("4k3/8/8/8/8/8/8/4K1N1 w - - 12 40").unwrap();
+        play(&mut game, "g1f3").unwrap();
+        assert!(game.undo());
+        assert_eq!(game.fen(), "4k3/8/8/8/8/8/8/4K1N1 w - - 12 40");
+        assert!(!game.undo());
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use super::*;
+
+    fn targets(game: &JsChessGame, square: &str) -> Vec<(String, bool)> {
+        let targets = js_sys::Array::from(&game.legal_moves(square).unwrap());
+        targets.iter()
+            .map(|target| {
+                let get = |key: &str| js_sys::Reflect::get(&target, &key.into()).unwrap();
+                (get("to").as_string().unwrap(), get("isPromotion").as_bool().unwrap())
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn move_legality() {
+        let mut game = JsChessGame::new();
+        assert_eq!(targets(&game, "e2"), [("e3".to_string(), false), ("e4".to_string(), false)]);
+        assert!(targets(&game, "e7").is_empty());
+        assert!(game.legal_moves("e9").is_err());
+        assert!(game.play_move("e2e5").is_err());
+        assert!(game.play_move("nonsense").is_err());
+
+        let details = game.play_move("e2e4").unwrap();
+        let san = js_sys::Reflect::get(&details, &"san".into()).unwrap();
+        assert_eq!(san.as_string().unwrap(), "e4");
+        assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert!(game.undo());
+        assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(!game.undo());
+        assert!(game.load_fen("not a fen").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn promotion() {
+        let mut game = JsChessGame::new();
+        game.load_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(targets(&game, "a7"), [("a8".to_string(), true)]);
+        assert!(game.play_move("a7a8").is_err());
+        let details = game.play_move("a7a8q").unwrap();
+        let san = js_sys::Reflect::get(&details, &"san".into()).unwrap();
+        assert_eq!(san.as_string().unwrap(), "a8=Q+");
+        assert_eq!(game.fen(), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(game.status(), "Normal play");
+    }
+}
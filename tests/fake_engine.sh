@@ -0,0 +1,32 @@
+#!/bin/sh
+# A fake UCI engine which answers commands with canned responses, used by the tests of the UCI
+# client (src/uci/client.rs). `go nodes ...` is never answered, to test timeouts.
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name Fake Engine 1.0"
+            echo "id author nobody"
+            echo "option name Hash type spin default 16 min 1 max 1024"
+            echo "uciok"
+            ;;
+        isready)
+            echo "readyok"
+            ;;
+        "go depth 1")
+            echo "info depth 1 seldepth 1 score cp 25 nodes 20 nps 1000 tbhits 0 time 1 pv e2e4"
+            echo "bestmove e2e4"
+            ;;
+        "go nodes"*)
+            ;;
+        go*)
+            echo "info string thinking hard"
+            echo "info depth 1 score cp -13 nodes 20 pv d2d4"
+            echo "info depth 2 seldepth 4 multipv 1 score mate -3 lowerbound wdl 0 0 1000 hashfull 3 nodes 40 pv a7a8q e8d7"
+            echo "info currmove a7a8q currmovenumber 1"
+            echo "bestmove a7a8q ponder e8d7"
+            ;;
+        quit)
+            exit 0
+            ;;
+    esac
+done
@@ -0,0 +1,699 @@
+//! Generates the three-piece Syzygy tables of this directory.
+//!
+//! The positions are solved by retrograde analysis and the tables are written in the format read
+//! by Stockfish and the Syzygy probing code: the index of each position, the Huffman coded
+//! sub-tables with pairs of symbols, the sparse index and the 64-byte aligned blocks. The files
+//! are much smaller than the official ones, since the blocks are small and the values of
+//! impossible positions are chosen to compress well, but they store the same values.
+//!
+//! Run from this directory with `rustc --edition 2024 -O generate.rs && ./generate`.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+const SPLIT: u8 = 1;
+const HAS_PAWNS: u8 = 2;
+const MAPPED: u8 = 2;
+const SINGLE_VALUE: u8 = 128;
+const BLOCK_SIZE_LOG: u8 = 7;
+const SPAN_LOG: u8 = 9;
+const MAX_BLOCK_VALUES: usize = 30000;
+const KING: u8 = 6;
+const BLACK: u8 = 8;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Piece {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+}
+
+impl Piece {
+    fn code(self) -> u8 {
+        self as u8 + 1
+    }
+
+    fn letter(self) -> char {
+        b"PNBRQ"[self as usize] as char
+    }
+
+    fn directions(self) -> &'static [(i32, i32)] {
+        match self {
+            Piece::Rook => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            Piece::Bishop => &[(1, 1), (1, -1), (-1, 1), (-1, -1)],
+            Piece::Queen => &[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)],
+            Piece::Knight => {
+                &[(1, 2), (2, 1), (-1, 2), (-2, 1), (1, -2), (2, -1), (-1, -2), (-2, -1)]
+            }
+            Piece::Pawn => &[],
+        }
+    }
+}
+
+fn file(square: usize) -> usize {
+    square & 7
+}
+
+fn rank(square: usize) -> usize {
+    square >> 3
+}
+
+fn step(square: usize, (df, dr): (i32, i32)) -> Option<usize> {
+    let (f, r) = (file(square) as i32 + df, rank(square) as i32 + dr);
+    ((0..8).contains(&f) && (0..8).contains(&r)).then_some((8 * r + f) as usize)
+}
+
+/// Whether two squares are equal or next to each other.
+fn near(a: usize, b: usize) -> bool {
+    file(a).abs_diff(file(b)) <= 1 && rank(a).abs_diff(rank(b)) <= 1
+}
+
+fn king_moves(square: usize) -> Vec<usize> {
+    Piece::Queen.directions().iter().filter_map(|&d| step(square, d)).collect()
+}
+
+/// Whether the white piece attacks the target square, with the white king as the only blocker.
+fn attacks(piece: Piece, from: usize, target: usize, white_king: usize) -> bool {
+    match piece {
+        Piece::Pawn => rank(target) == rank(from) + 1 && file(target).abs_diff(file(from)) == 1,
+        Piece::Knight => piece.directions().iter().any(|&d| step(from, d) == Some(target)),
+        _ => piece.directions().iter().any(|&d| {
+            let mut square = from;
+            while let Some(next) = step(square, d) {
+                if next == target {
+                    return true;
+                }
+                if next == white_king {
+                    break;
+                }
+                square = next;
+            }
+            false
+        }),
+    }
+}
+
+/// The moves of the white piece other than pawn moves, to empty squares.
+fn piece_moves(piece: Piece, from: usize, white_king: usize, black_king: usize) -> Vec<usize> {
+    let mut targets = Vec::new();
+    for &d in piece.directions() {
+        let mut square = from;
+        while let Some(next) = step(square, d) {
+            if next == white_king || next == black_king {
+                break;
+            }
+            targets.push(next);
+            if piece == Piece::Knight {
+                break;
+            }
+            square = next;
+        }
+    }
+    targets
+}
+
+const POSITIONS: usize = 64 * 64 * 64 * 2;
+
+/// The index of a position with a white king, a white piece and a black king.
+fn position(white_king: usize, piece: usize, black_king: usize, black_to_move: bool) -> usize {
+    ((white_king * 64 + piece) * 64 + black_king) * 2 + black_to_move as usize
+}
+
+fn unpack(pid: usize) -> (usize, usize, usize, bool) {
+    (pid / 8192, pid / 128 % 64, pid / 2 % 64, pid % 2 == 1)
+}
+
+enum Move {
+    /// A move to a position of the same slice.
+    Quiet(usize),
+    /// A zeroing move, with the WDL value for the moving player.
+    Zeroing(i8),
+}
+
+/// The solved positions of a material, by [position].
+struct Solution {
+    piece: Piece,
+    legal: Vec<bool>,
+    wdl: Vec<i8>,
+    dtz: Vec<i16>,
+}
+
+impl Solution {
+    fn new(piece: Piece) -> Solution {
+        let mut legal = vec![false; POSITIONS];
+        for (pid, legal) in legal.iter_mut().enumerate() {
+            let (wk, x, bk, black_to_move) = unpack(pid);
+            *legal = wk != x && x != bk && !near(wk, bk)
+                && (piece != Piece::Pawn || (1..7).contains(&rank(x)))
+                && (black_to_move || !attacks(piece, x, bk, wk));
+        }
+        Solution { piece, legal, wdl: vec![i8::MIN; POSITIONS], dtz: vec![0; POSITIONS] }
+    }
+
+    fn in_check(&self, pid: usize) -> bool {
+        let (wk, x, bk, black_to_move) = unpack(pid);
+        black_to_move && attacks(self.piece, x, bk, wk)
+    }
+
+    fn moves(&self, pid: usize, promotions: &[(Piece, &Solution)]) -> Vec<Move> {
+        let (wk, x, bk, black_to_move) = unpack(pid);
+        let mut moves = Vec::new();
+        if black_to_move {
+            for to in king_moves(bk) {
+                if near(to, wk) || (to != x && attacks(self.piece, x, to, wk)) {
+                    continue;
+                }
+                moves.push(if to == x {
+                    Move::Zeroing(0)
+                } else {
+                    Move::Quiet(position(wk, x, to, false))
+                });
+            }
+            return moves;
+        }
+        for to in king_moves(wk) {
+            if to != x && !near(to, bk) {
+                moves.push(Move::Quiet(position(to, x, bk, true)));
+            }
+        }
+        if self.piece != Piece::Pawn {
+            for to in piece_moves(self.piece, x, wk, bk) {
+                moves.push(Move::Quiet(position(wk, to, bk, true)));
+            }
+            return moves;
+        }
+        let empty = |square: usize| square != wk && square != bk;
+        let one = x + 8;
+        if !empty(one) {
+            return moves;
+        }
+        if rank(one) == 7 {
+            for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                let wdl = promotions.iter().find(|(promoted, _)| *promoted == piece)
+                    .map_or(0, |(_, solution)| solution.wdl[position(wk, one, bk, true)]);
+                moves.push(Move::Zeroing(-wdl));
+            }
+            return moves;
+        }
+        moves.push(Move::Zeroing(-self.wdl[position(wk, one, bk, true)]));
+        if rank(x) == 1 && empty(x + 16) {
+            moves.push(Move::Zeroing(-self.wdl[position(wk, x + 16, bk, true)]));
+        }
+        moves
+    }
+
+    /// Solves the positions with the white piece on the given squares, whose zeroing moves lead
+    /// to solved positions.
+    fn solve(&mut self, squares: &[usize], promotions: &[(Piece, &Solution)]) {
+        let slice: Vec<usize> = (0..POSITIONS)
+            .filter(|&pid| self.legal[pid] && squares.contains(&unpack(pid).1))
+            .collect();
+        let mut predecessors: HashMap<usize, Vec<usize>> = HashMap::new();
+        // the number of quiet moves not yet known to lose, of the positions without a zeroing
+        // move which saves them
+        let mut remaining: HashMap<usize, usize> = HashMap::new();
+        let mut mated = HashSet::new();
+        let mut buckets: Vec<Vec<(usize, i8)>> = vec![vec![]; 256];
+        for &pid in &slice {
+            let moves = self.moves(pid, promotions);
+            let mut quiet = 0;
+            let mut best_zeroing = -3;
+            for chess_move in &moves {
+                match *chess_move {
+                    Move::Quiet(child) => {
+                        predecessors.entry(child).or_default().push(pid);
+                        quiet += 1;
+                    }
+                    Move::Zeroing(wdl) => best_zeroing = best_zeroing.max(wdl),
+                }
+            }
+            if moves.is_empty() {
+                if self.in_check(pid) {
+                    mated.insert(pid);
+                    buckets[1].push((pid, -2));
+                }
+            } else if best_zeroing == 2 {
+                buckets[1].push((pid, 2));
+            } else if quiet == 0 && best_zeroing == -2 {
+                buckets[1].push((pid, -2));
+            } else if best_zeroing < 0 {
+                remaining.insert(pid, quiet);
+            }
+        }
+        for distance in 1..buckets.len() {
+            let mut i = 0;
+            while i < buckets[distance].len() {
+                let (pid, wdl) = buckets[distance][i];
+                i += 1;
+                if self.wdl[pid] != i8::MIN {
+                    continue;
+                }
+                self.wdl[pid] = wdl;
+                self.dtz[pid] = if wdl > 0 { distance as i16 } else { -(distance as i16) };
+                for &parent in predecessors.get(&pid).map_or(&[][..], |parents| parents) {
+                    if self.wdl[parent] != i8::MIN {
+                        continue;
+                    }
+                    if wdl < 0 {
+                        // a mate is counted as a zeroing move
+                        let parent_distance = if mated.contains(&pid) { 1 } else { distance + 1 };
+                        buckets[parent_distance].push((parent, 2));
+                    } else if let Some(quiet) = remaining.get_mut(&parent) {
+                        // the last losing move found is the one delaying the loss the longest
+                        *quiet -= 1;
+                        if *quiet == 0 {
+                            buckets[distance + 1].push((parent, -2));
+                        }
+                    }
+                }
+            }
+        }
+        for pid in slice {
+            if self.wdl[pid] == i8::MIN {
+                self.wdl[pid] = 0;
+            }
+        }
+    }
+}
+
+/// The lookup tables of the Syzygy index.
+struct IndexTables {
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [u64; 64],
+    lead_pawn_idx: [u64; 64],
+}
+
+fn off_diagonal(square: usize) -> i32 {
+    rank(square) as i32 - file(square) as i32
+}
+
+fn flip_diagonal(square: usize) -> usize {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+impl IndexTables {
+    fn new() -> IndexTables {
+        let mut map_b1h1h7 = [0; 64];
+        let mut code = 0;
+        for square in (0..64).filter(|&square| off_diagonal(square) < 0) {
+            map_b1h1h7[square] = code;
+            code += 1;
+        }
+        let mut map_a1d1d4 = [0; 64];
+        let mut diagonal = vec![];
+        code = 0;
+        for square in (0..28).filter(|&square| file(square) <= 3) {
+            if off_diagonal(square) < 0 {
+                map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            map_a1d1d4[square] = code;
+            code += 1;
+        }
+        // a single leading pawn is indexed by its rank
+        let mut lead_pawn_idx = [0; 64];
+        for square in 8..56 {
+            lead_pawn_idx[square] = rank(square) as u64 - 1;
+        }
+        IndexTables { map_b1h1h7, map_a1d1d4, lead_pawn_idx }
+    }
+
+    /// The index of three unique pieces in the order of the table, and the file of the pawn
+    /// sub-table for tables with a pawn first.
+    fn index(&self, squares: [usize; 3], has_pawn: bool) -> (usize, u64) {
+        let mut squares = squares;
+        if file(squares[0]) > 3 {
+            squares = squares.map(|square| square ^ 7);
+        }
+        if has_pawn {
+            // the king groups follow the pawn, each adjusted for the squares before it
+            let mut idx = self.lead_pawn_idx[squares[0]];
+            let mut multiplier = 6;
+            for i in 1..3 {
+                let adjust = squares[..i].iter().filter(|&&other| squares[i] > other).count();
+                idx += (squares[i] - adjust) as u64 * multiplier;
+                multiplier *= 64 - i as u64;
+            }
+            return (file(squares[0]), idx);
+        }
+        if rank(squares[0]) > 3 {
+            squares = squares.map(|square| square ^ 56);
+        }
+        if let Some(i) = (0..3).find(|&i| off_diagonal(squares[i]) != 0)
+            && off_diagonal(squares[i]) > 0
+        {
+            for square in &mut squares[i..] {
+                *square = flip_diagonal(*square);
+            }
+        }
+        let [s0, s1, s2] = squares.map(|square| square as u64);
+        let adjust1 = (s1 > s0) as u64;
+        let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+        let [r0, r1, r2] = squares.map(|square| rank(square) as u64);
+        let idx = if off_diagonal(squares[0]) != 0 {
+            (self.map_a1d1d4[squares[0]] * 63 + s1 - adjust1) * 62 + s2 - adjust2
+        } else if off_diagonal(squares[1]) != 0 {
+            (6 * 63 + r0 * 28 + self.map_b1h1h7[squares[1]]) * 62 + s2 - adjust2
+        } else if off_diagonal(squares[2]) != 0 {
+            6 * 63 * 62 + 4 * 28 * 62 + r0 * 7 * 28 + (r1 - adjust1) * 28
+                + self.map_b1h1h7[squares[2]]
+        } else {
+            6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + r0 * 7 * 6 + (r1 - adjust1) * 6 + r2 - adjust2
+        };
+        (0, idx)
+    }
+}
+
+/// A compressed sub-table: the coding parameters, the sparse index, the block lengths and the
+/// blocks.
+struct SubTable {
+    sizes: Vec<u8>,
+    sparse_index: Vec<u8>,
+    block_lengths: Vec<u8>,
+    blocks: Vec<u8>,
+}
+
+/// Compresses the values of a sub-table, choosing the values of impossible positions.
+fn compress(values: &[Option<u16>], flags: u8) -> SubTable {
+    let mut last = values.iter().flatten().next().copied().unwrap_or(0);
+    let values: Vec<u16> = values.iter().map(|value| {
+        last = value.unwrap_or(last);
+        last
+    }).collect();
+    let mut distinct = values.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if let [value] = distinct[..] {
+        return SubTable {
+            sizes: vec![flags | SINGLE_VALUE, value as u8],
+            sparse_index: vec![],
+            block_lengths: vec![],
+            blocks: vec![],
+        };
+    }
+
+    // symbols are leaves with a value or pairs of symbols, replacing the most frequent pairs
+    let mut tree: Vec<(usize, usize, usize)> = distinct.iter()
+        .map(|&value| (value as usize, 0xFFF, 1))
+        .collect();
+    let mut sequence: Vec<usize> = values.iter()
+        .map(|value| distinct.binary_search(value).unwrap())
+        .collect();
+    while tree.len() < 2048 {
+        let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+        for pair in sequence.windows(2) {
+            if tree[pair[0]].2 + tree[pair[1]].2 <= 256 {
+                *counts.entry((pair[0], pair[1])).or_default() += 1;
+            }
+        }
+        let most_frequent = counts.iter().max_by_key(|&(&pair, &count)| (count, Reverse(pair)));
+        let Some((&(left, right), &count)) = most_frequent else {
+            break;
+        };
+        if count < 8 {
+            break;
+        }
+        let sym = tree.len();
+        tree.push((left, right, tree[left].2 + tree[right].2));
+        let mut replaced = Vec::with_capacity(sequence.len());
+        let mut i = 0;
+        while i < sequence.len() {
+            if i + 1 < sequence.len() && sequence[i] == left && sequence[i + 1] == right {
+                replaced.push(sym);
+                i += 2;
+            } else {
+                replaced.push(sequence[i]);
+                i += 1;
+            }
+        }
+        sequence = replaced;
+    }
+
+    // canonical Huffman code, in which longer codes have lower values and lower symbols
+    let mut frequencies = vec![0usize; tree.len()];
+    for &sym in &sequence {
+        frequencies[sym] += 1;
+    }
+    let mut lengths = vec![0u8; tree.len()];
+    let used: Vec<usize> = (0..tree.len()).filter(|&sym| frequencies[sym] > 0).collect();
+    if used.len() == 1 {
+        lengths[used[0]] = 1;
+    } else {
+        let mut heap: BinaryHeap<Reverse<(usize, usize, Vec<usize>)>> = used.iter()
+            .map(|&sym| Reverse((frequencies[sym], sym, vec![sym])))
+            .collect();
+        while heap.len() > 1 {
+            let Reverse((f1, t1, s1)) = heap.pop().unwrap();
+            let Reverse((f2, t2, s2)) = heap.pop().unwrap();
+            for &sym in s1.iter().chain(&s2) {
+                lengths[sym] += 1;
+            }
+            heap.push(Reverse((f1 + f2, t1.min(t2), [s1, s2].concat())));
+        }
+    }
+    let mut order: Vec<usize> = used.clone();
+    order.sort_by_key(|&sym| (Reverse(lengths[sym]), sym));
+    order.extend((0..tree.len()).filter(|&sym| frequencies[sym] == 0));
+    let mut new_id = vec![0; tree.len()];
+    for (id, &sym) in order.iter().enumerate() {
+        new_id[sym] = id;
+    }
+    let min_len = used.iter().map(|&sym| lengths[sym]).min().unwrap();
+    let max_len = used.iter().map(|&sym| lengths[sym]).max().unwrap();
+    let count = |len: u8| used.iter().filter(|&&sym| lengths[sym] == len).count() as u64;
+    let mut base = vec![0u64; (max_len + 1) as usize];
+    for len in (min_len..max_len).rev() {
+        base[len as usize] = (base[len as usize + 1] + count(len + 1)) / 2;
+    }
+    let lowest_sym = |len: u8| used.iter().filter(|&&sym| lengths[sym] > len).count();
+    let code = |sym: usize| {
+        base[lengths[sym] as usize] + (new_id[sym] - lowest_sym(lengths[sym])) as u64
+    };
+    let codes: Vec<u64> = (0..tree.len()).map(|sym| if lengths[sym] > 0 { code(sym) } else { 0 })
+        .collect();
+
+    // blocks of whole symbols, each fitting in the block size
+    let block_bits = 8 << BLOCK_SIZE_LOG;
+    let mut blocks: Vec<(usize, usize, Vec<usize>)> = vec![(0, 0, vec![])];
+    for &sym in &sequence {
+        let (bits, count, _) = blocks.last().unwrap();
+        if bits + lengths[sym] as usize > block_bits || count + tree[sym].2 > MAX_BLOCK_VALUES {
+            blocks.push((0, 0, vec![]));
+        }
+        let (bits, count, symbols) = blocks.last_mut().unwrap();
+        *bits += lengths[sym] as usize;
+        *count += tree[sym].2;
+        symbols.push(sym);
+    }
+    let mut data = vec![];
+    for (_, _, symbols) in &blocks {
+        let start = data.len();
+        data.resize(start + (1 << BLOCK_SIZE_LOG), 0);
+        let mut position = 0;
+        for &sym in symbols {
+            for bit in (0..lengths[sym]).rev() {
+                if codes[sym] >> bit & 1 != 0 {
+                    data[start + position / 8] |= 0x80 >> (position % 8);
+                }
+                position += 1;
+            }
+        }
+    }
+    let mut starts = vec![];
+    let mut start = 0;
+    for (_, count, _) in &blocks {
+        starts.push(start);
+        start += count;
+    }
+    let span = 1usize << SPAN_LOG;
+    let mut sparse_index = vec![];
+    for k in 0..values.len().div_ceil(span) {
+        let middle = k * span + span / 2;
+        let block = starts.iter().rposition(|&start| start <= middle).unwrap();
+        sparse_index.extend((block as u32).to_le_bytes());
+        sparse_index.extend(u16::try_from(middle - starts[block]).unwrap().to_le_bytes());
+    }
+    let mut block_lengths = vec![];
+    for (_, count, _) in &blocks {
+        block_lengths.extend((*count as u16 - 1).to_le_bytes());
+    }
+
+    let mut sizes = vec![flags, BLOCK_SIZE_LOG, SPAN_LOG, 0];
+    sizes.extend((blocks.len() as u32).to_le_bytes());
+    sizes.extend([max_len, min_len]);
+    for len in min_len..=max_len {
+        sizes.extend((lowest_sym(len) as u16).to_le_bytes());
+    }
+    sizes.extend((tree.len() as u16).to_le_bytes());
+    for &sym in &order {
+        let (left, right) = match tree[sym] {
+            (value, 0xFFF, _) => (value, 0xFFF),
+            (left, right, _) => (new_id[left], new_id[right]),
+        };
+        sizes.extend([left as u8, ((left >> 8) | (right << 4)) as u8, (right >> 4) as u8]);
+    }
+    if tree.len() % 2 == 1 {
+        sizes.push(0);
+    }
+    SubTable { sizes, sparse_index, block_lengths, blocks: data }
+}
+
+/// Writes a table file of a material with a white king, a white piece and a black king.
+fn write_file(path: &str, magic: [u8; 4], piece: Piece, sub_tables: &[SubTable], maps: &[Vec<u8>]) {
+    let has_pawns = piece == Piece::Pawn;
+    let mut bytes = magic.to_vec();
+    bytes.push(SPLIT | if has_pawns { HAS_PAWNS } else { 0 });
+    let pieces = if has_pawns {
+        [piece.code(), KING, KING | BLACK]
+    } else {
+        [KING, piece.code(), KING | BLACK]
+    };
+    for _ in 0..if has_pawns { 4 } else { 1 } {
+        bytes.push(0);
+        bytes.extend(pieces.map(|piece| piece | piece << 4));
+    }
+    bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+    for sub_table in sub_tables {
+        bytes.extend(&sub_table.sizes);
+    }
+    if magic == DTZ_MAGIC {
+        for map in maps {
+            bytes.extend(map);
+        }
+        bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+    }
+    for sub_table in sub_tables {
+        bytes.extend(&sub_table.sparse_index);
+    }
+    for sub_table in sub_tables {
+        bytes.extend(&sub_table.block_lengths);
+    }
+    for sub_table in sub_tables.iter().filter(|sub_table| !sub_table.blocks.is_empty()) {
+        bytes.resize((bytes.len() + 0x3F) & !0x3F, 0);
+        bytes.extend(&sub_table.blocks);
+    }
+    std::fs::write(path, bytes).unwrap();
+}
+
+fn fen(pid: usize, piece: Piece) -> String {
+    let (wk, x, bk, black_to_move) = unpack(pid);
+    let mut rows = vec![];
+    for rank in (0..8).rev() {
+        let mut row = String::new();
+        let mut empty = 0;
+        for file in 0..8 {
+            let square = 8 * rank + file;
+            let letter = if square == wk {
+                'K'
+            } else if square == x {
+                piece.letter()
+            } else if square == bk {
+                'k'
+            } else {
+                empty += 1;
+                continue;
+            };
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+                empty = 0;
+            }
+            row.push(letter);
+        }
+        if empty > 0 {
+            row.push_str(&empty.to_string());
+        }
+        rows.push(row);
+    }
+    format!("{} {} - - 0 1", rows.join("/"), if black_to_move { 'b' } else { 'w' })
+}
+
+/// Writes the WDL and DTZ files of a solved material, and a list of the solved positions to
+/// check the probing code against if `CHECK_DIRECTORY` is set.
+fn write_tables(solution: &Solution, tables: &IndexTables) {
+    let piece = solution.piece;
+    let name = format!("K{}vK", piece.letter());
+    let has_pawns = piece == Piece::Pawn;
+    let files = if has_pawns { 4 } else { 1 };
+    let size = if has_pawns { 6 * 63 * 62 } else { 31332 };
+    let mut wdl = vec![vec![None; size]; 2 * files];
+    let mut dtz = vec![vec![None; size]; files];
+    let mut checked = String::new();
+    for pid in (0..POSITIONS).filter(|&pid| solution.legal[pid]) {
+        let (wk, x, bk, black_to_move) = unpack(pid);
+        let squares = if has_pawns { [x, wk, bk] } else { [wk, x, bk] };
+        let (file, idx) = tables.index(squares, has_pawns);
+        let value = solution.wdl[pid];
+        let stored = wdl[2 * file + black_to_move as usize][idx as usize].replace(value as u16 + 2);
+        assert!(stored.is_none_or(|stored| stored == value as u16 + 2), "{}", fen(pid, piece));
+        if !black_to_move && value == 2 {
+            assert_eq!(solution.dtz[pid] % 2, 1, "{}", fen(pid, piece));
+            dtz[file][idx as usize] = Some(solution.dtz[pid] as u16 / 2);
+        }
+        checked.push_str(&format!("{} {} {}\n", fen(pid, piece), value, solution.dtz[pid]));
+    }
+    if let Ok(directory) = std::env::var("CHECK_DIRECTORY") {
+        std::fs::write(format!("{directory}/{name}.txt"), checked).unwrap();
+    }
+
+    let sub_tables: Vec<SubTable> = wdl.iter().map(|values| compress(values, 0)).collect();
+    write_file(&format!("{name}.rtbw"), WDL_MAGIC, piece, &sub_tables, &[]);
+    let mut maps = vec![];
+    let mut sub_tables = vec![];
+    for mut values in dtz {
+        if has_pawns {
+            // the winning distances of each file are mapped from the list of their values
+            let mut map: Vec<u16> = values.iter().flatten().copied().collect();
+            map.sort_unstable();
+            map.dedup();
+            for value in values.iter_mut().flatten() {
+                *value = map.binary_search(value).unwrap() as u16;
+            }
+            let mut bytes = vec![map.len() as u8];
+            bytes.extend(map.iter().map(|&value| u8::try_from(value).unwrap()));
+            bytes.extend([0, 0, 0]);
+            maps.push(bytes);
+        }
+        sub_tables.push(compress(&values, if has_pawns { MAPPED } else { 0 }));
+    }
+    write_file(&format!("{name}.rtbz"), DTZ_MAGIC, piece, &sub_tables, &maps);
+    let longest = (0..POSITIONS).filter(|&pid| solution.legal[pid])
+        .map(|pid| solution.dtz[pid].abs()).max().unwrap();
+    println!("{name}: longest distance to zeroing {longest} plies");
+}
+
+fn main() {
+    let tables = IndexTables::new();
+    let all_squares: Vec<usize> = (0..64).collect();
+    let mut solutions = vec![];
+    for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+        let mut solution = Solution::new(piece);
+        if matches!(piece, Piece::Queen | Piece::Rook) {
+            solution.solve(&all_squares, &[]);
+        } else {
+            // a lone minor piece can not checkmate
+            for pid in 0..POSITIONS {
+                solution.wdl[pid] = 0;
+            }
+        }
+        write_tables(&solution, &tables);
+        solutions.push((piece, solution));
+    }
+    let promotions: Vec<(Piece, &Solution)> = solutions.iter()
+        .map(|(piece, solution)| (*piece, solution))
+        .collect();
+    let mut pawn = Solution::new(Piece::Pawn);
+    for rank in (1..7).rev() {
+        for file in 0..8 {
+            pawn.solve(&[8 * rank + file], &promotions);
+        }
+    }
+    write_tables(&pawn, &tables);
+}
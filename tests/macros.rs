@@ -0,0 +1,8 @@
+//! Checks that the [board!](leben_chess::board!) and [game!](leben_chess::game!) macros reject
+//! invalid layouts at compile time, with readable diagnostics.
+
+#[test]
+fn invalid_layouts_do_not_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
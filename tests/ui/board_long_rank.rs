@@ -0,0 +1,14 @@
+use leben_chess::board;
+
+fn main() {
+    let _ = board! {
+        "r . . . k . . r" /
+        "p p p . . p p p" /
+        ". . . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        "P P P . . P P P" /
+        "R . . . K . . R"
+    };
+}
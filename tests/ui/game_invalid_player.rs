@@ -0,0 +1,15 @@
+use leben_chess::game;
+
+fn main() {
+    let _ = game! {
+        ". . . . k . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        ". . . . . . . ." /
+        "R . . . K . . R";
+        Red, "KQ"
+    };
+}